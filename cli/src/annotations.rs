@@ -64,8 +64,7 @@ impl<'a> std::ops::Add<&'a NodeTags> for &'a NodeTags {
             .into_iter()
             .map(|(cost, dims)| (*cost, dims.into_iter().fold(0.to_dim(), |acc, d| acc + &d.1)))
             .collect::<Vec<(Cost, TDim)>>();
-        let profile = self.profile.unwrap_or(Duration::default())
-            + other.profile.unwrap_or(Duration::default());
+        let profile = self.profile.unwrap_or_default() + other.profile.unwrap_or_default();
         let profile = if profile != Duration::default() { Some(profile) } else { None };
         let style = self.style.or(other.style);
         let labels = self.labels.iter().chain(other.labels.iter()).cloned().collect();
@@ -189,15 +188,12 @@ impl Annotations {
         for (name, proto_node) in &proto_model.config_lines.nodes {
             if let Ok(node_id) = model.node_id_by_name(&*name) {
                 let mut vs = vec![];
-                match proto_node {
-                    NodeLine::Component(compo) => {
-                        let comp = &proto_model.components[&compo.component];
-                        for (k, v) in &comp.attributes {
-                            let value = format!("{:?}", v);
-                            vs.push(format!("Attr {}: {:.240}", bold.paint(k), value));
-                        }
+                if let NodeLine::Component(compo) = proto_node {
+                    let comp = &proto_model.components[&compo.component];
+                    for (k, v) in &comp.attributes {
+                        let value = format!("{:?}", v);
+                        vs.push(format!("Attr {}: {:.240}", bold.paint(k), value));
                     }
-                    _ => (),
                 }
                 self.node_mut(node_id.into()).sections.push(vs)
             }
@@ -241,7 +237,7 @@ impl Annotations {
         let bold = Style::new().bold();
         for gnode in model_proto.graph.as_ref().unwrap().node.iter() {
             let mut node_name = &gnode.name;
-            if node_name == "" && gnode.output.len() > 0 {
+            if node_name.is_empty() && gnode.output.len() > 0 {
                 node_name = &gnode.output[0];
             }
             if let Ok(id) = model.node_id_by_name(&*node_name) {
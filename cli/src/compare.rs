@@ -23,6 +23,8 @@ pub fn handle(
         return handle_reference_stage(cumulative, params, &output_params);
     } else if let Some(npz) = options.value_of("npz") {
         return handle_npz(cumulative, npz, params, &output_params);
+    } else if let Some(ark) = options.value_of("ark") {
+        return handle_ark(cumulative, ark, params, &output_params);
     } else if options.is_present("twice") {
         return handle_twice(cumulative, params, &output_params);
     }
@@ -132,10 +134,12 @@ pub fn handle_npz(
                 let name = name
                     .split("/")
                     .nth(1)
-                    .with_context(|| format!(
-                        "npy filenames should be turn_XX/... in multiturn mode, got `{}'",
-                        name
-                    ))?
+                    .with_context(|| {
+                        format!(
+                            "npy filenames should be turn_XX/... in multiturn mode, got `{}'",
+                            name
+                        )
+                    })?
                     .trim_end_matches(".npy");
                 values.entry(name.to_string()).or_default().push(Ok(value.into_arc_tensor()));
             } else {
@@ -153,6 +157,52 @@ pub fn handle_npz(
     ))
 }
 
+#[cfg(not(feature = "kaldi"))]
+pub fn handle_ark(
+    _cumulative: bool,
+    _ark: &str,
+    _params: &Parameters,
+    _output_params: &DisplayParams,
+) -> CliResult<()> {
+    bail!("`kaldi` feature is required for this to work");
+}
+
+#[cfg(feature = "kaldi")]
+pub fn handle_ark(
+    cumulative: bool,
+    ark: &str,
+    params: &Parameters,
+    output_params: &DisplayParams,
+) -> CliResult<()> {
+    let model = &params.tract_model;
+    let output = model.node_name(model.output_outlets()[0].node).to_string();
+    let mut entries = tract_kaldi::ark::read(ark)?;
+    if let Some(utterances) = &params.utterances {
+        // match the input utterances, in order
+        let mut by_key: HashMap<String, Tensor> = entries.into_iter().collect();
+        entries = utterances
+            .iter()
+            .map(|key| {
+                let value = by_key.remove(key).with_context(|| format!("No {} in {}", key, ark))?;
+                CliResult::Ok((key.clone(), value))
+            })
+            .collect::<CliResult<_>>()?;
+    }
+    let mut values: HashMap<String, Vec<CliResult<Arc<Tensor>>>> = params
+        .input_values
+        .iter()
+        .map(|(name, turns)| (name.clone(), turns.iter().map(|t| Ok(t.clone())).collect()))
+        .collect();
+    values.insert(output, entries.into_iter().map(|(_, t)| Ok(t.into_arc_tensor())).collect());
+    dispatch_model_no_pulse!(params.tract_model, |m| compare(
+        cumulative,
+        m,
+        &values,
+        &params,
+        output_params
+    ))
+}
+
 #[cfg(not(feature = "onnx"))]
 pub fn handle_pbdir(
     cumulative: bool,
@@ -267,10 +317,14 @@ where
     let mut ok = 0;
 
     for (turn, inputs) in tensor::retrieve_or_make_inputs(tract, params)?.into_iter().enumerate() {
+        if params.utterances.is_some() {
+            // utterances are independent
+            state.reset_op_states()?;
+        }
         state.run_plan_with_eval(
             inputs,
             |session_state, state, node, input| -> TractResult<TVec<Arc<Tensor>>> {
-                let mut tags = annotations.node_mut(node.id.into());
+                let tags = annotations.node_mut(node.id.into());
                 let reference: Option<TVec<Arc<Tensor>>> = (0..node.outputs.len())
                     .map(|ix| {
                         let label = tract.outlet_label((node.id, ix).into()).unwrap_or(&node.name);
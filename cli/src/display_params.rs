@@ -3,19 +3,14 @@ use tract_core::prelude::*;
 
 use crate::model::Model;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub enum Io {
     None,
+    #[default]
     Short,
     Long,
 }
 
-impl Default for Io {
-    fn default() -> Io {
-        Io::Short
-    }
-}
-
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct DisplayParams {
     pub konst: bool,
@@ -64,12 +64,7 @@ impl DrawingState {
     }
 
     fn inputs_to_draw(&self, model: &dyn Model, node: usize) -> Vec<OutletId> {
-        model
-            .node_inputs(node)
-            .iter()
-            .cloned()
-//            .filter(|o| self.wires.iter().find(|w| w.outlet == *o).unwrap().color.is_some())
-            .collect()
+        model.node_inputs(node).to_vec()
     }
 
     fn passthrough_count(&self, node: usize) -> usize {
@@ -113,8 +108,7 @@ impl DrawingState {
                     .count()
                     + must_clone as usize;
                 // println!("{}->{} (offset: {})", little, big, offset);
-                if moving.color.is_some() && offset != 0 {
-                    let color = moving.color.unwrap();
+                if let Some(color) = moving.color.filter(|_| offset != 0) {
                     for w in &self.wires[0..little] {
                         if let Some(c) = w.color {
                             p!("{}", c.paint(VERTICAL));
@@ -169,7 +163,7 @@ impl DrawingState {
         opts: &DisplayParams,
     ) -> CliResult<Vec<String>> {
         let mut lines = vec![String::new()];
-        macro_rules! p { ($($args: expr),*) => { write!(lines.last_mut().unwrap(), $($args),*)?;} }
+        macro_rules! p { ($($args: expr),*) => { write!(lines.last_mut().unwrap(), $($args),*)?} }
         macro_rules! ln {
             () => {
                 lines.push(String::new())
@@ -189,10 +183,9 @@ impl DrawingState {
         if display {
             self.latest_node_color = if inputs.len() > 0 {
                 let wire0 = &self.wires[passthrough_count];
-                if wire0.color.is_some() && !wire0.should_change_color {
-                    wire0.color.unwrap()
-                } else {
-                    self.next_color()
+                match wire0.color {
+                    Some(color) if !wire0.should_change_color => color,
+                    _ => self.next_color(),
                 }
             } else {
                 self.next_color()
@@ -275,7 +268,7 @@ impl DrawingState {
                 if let Some(color) = self.wires[wanted_at].color {
                     write!(&mut s, "{}", color.paint(DOWN_RIGHT))?;
                     for w in is_at + 1..wanted_at {
-                        if let Some(_) = self.wires[w].color {
+                        if self.wires[w].color.is_some() {
                             write!(&mut s, "{}", color.paint(HORIZONTAL))?;
                         }
                     }
@@ -97,7 +97,7 @@ pub fn handle(
     if options.cost {
         let total = annotations.tags.values().sum::<NodeTags>();
         let assert =
-            sub_matches.value_of("assert-cost").map(|a| crate::cost::parse_costs(a)).transpose()?;
+            sub_matches.value_of("assert-cost").map(crate::cost::parse_costs).transpose()?;
         if let Some(assert) = assert {
             let assert: HashMap<Cost, TDim> =
                 assert.iter().map(|(c, n)| (*c, n.to_dim())).collect();
@@ -116,6 +116,21 @@ pub fn handle(
         terminal::render_summaries(model, &annotations, options)?;
     }
 
+    if sub_matches.is_present("latency") {
+        #[cfg(feature = "pulse")]
+        {
+            use tract_pulse::internal::{LatencyReport, PulsedModel};
+            let pulsed = params
+                .pulsed_model
+                .as_deref()
+                .or_else(|| model.downcast_ref::<PulsedModel>())
+                .context("Pulsed model not generated. (using --pulse ?)")?;
+            terminal::render_latency(&LatencyReport::new(pulsed)?);
+        }
+        #[cfg(not(feature = "pulse"))]
+        bail!("tract is build without pulse support")
+    }
+
     Ok(())
 }
 
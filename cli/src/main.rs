@@ -28,6 +28,7 @@ mod export;
 mod model;
 mod params;
 mod profile;
+mod quantize;
 mod run;
 #[cfg(feature = "pulse")]
 mod stream_check;
@@ -54,7 +55,7 @@ fn info_usage(stage: &str, probe: Option<&Probe>) {
     }
 }
 
-pub const STAGES: &'static [&'static str] = &[
+pub const STAGES: &[&str] = &[
     "load",
     "analyse",
     "incorporate",
@@ -101,6 +102,9 @@ fn main() -> tract_core::anyhow::Result<()> {
     (@arg input_bundle: --("input-bundle") +takes_value +multiple number_of_values(1)
      "Path to an input container (.npz)")
 
+    (@arg input_ark: --("input-ark") +takes_value
+     "Kaldi archive (.ark or .scp) of values for the first input, one run per utterance")
+
     (@arg kaldi_adjust_final_offset: --("kaldi-adjust-final-offset") +takes_value
      "Adjust value of final offset in network (for reproducibility)")
 
@@ -122,12 +126,18 @@ fn main() -> tract_core::anyhow::Result<()> {
     (@arg tf_initializer_output_node: --("tf-initializer-output-node") +takes_value +multiple number_of_values(1)
      "Set an initializer node")
 
+    (@arg tf_signature: --("tf-signature") +takes_value
+     "Signature to load from a TensorFlow SavedModel directory (default: serving_default)")
+
     (@arg output_node: --("output-node") +takes_value +multiple number_of_values(1)
      "Override output nodes name (auto-detects otherwise).")
 
     (@arg override_fact: --("override-fact") +takes_value +multiple number_of_values(1)
      "Override a fact.")
 
+    (@arg assert: --assert +takes_value +multiple number_of_values(1)
+     "Assert a property of a symbol (\"seq_len >= 1\", \"seq_len <= 512\", \"seq_len % 8 == 0\").")
+
     (@arg analyse_fail_fast: --("analyse-fail-fast") "Stop analyse at first error.")
     (@arg recursive: --recursive "Apply to sub graphes")
 
@@ -178,9 +188,15 @@ fn main() -> tract_core::anyhow::Result<()> {
                 .takes_value(true)
                 .help("protobuf directory file to compare against (like ONNX tests)"),
         )
+        .arg(
+            Arg::with_name("ark")
+                .long("ark")
+                .takes_value(true)
+                .help("Kaldi archive (.ark or .scp) of the first output to compare against"),
+        )
         .group(
             ArgGroup::with_name("reference")
-                .args(&["npz", "pbdir", "stage", "tf", "twice"])
+                .args(&["npz", "pbdir", "ark", "stage", "tf", "twice"])
                 .required(true),
         )
         .arg(
@@ -211,6 +227,11 @@ fn main() -> tract_core::anyhow::Result<()> {
         .long_about("Dumps the Tensorflow graph in human readable form.")
         .arg(Arg::with_name("cost").long("cost").help("Include const information"))
         .arg(Arg::with_name("profile").long("profile").help("Include results for profile run"))
+        .arg(
+            Arg::with_name("latency")
+            .long("latency")
+            .help("Report delays and state buffers of the pulsed model (with --pulse)"),
+            )
         .arg(
             Arg::with_name("assert-cost")
             .takes_value(true)
@@ -282,6 +303,12 @@ fn main() -> tract_core::anyhow::Result<()> {
                 .takes_value(true)
                 .help("Save intermediary values"),
         )
+        .arg(
+            Arg::with_name("output-ark")
+                .long("output-ark")
+                .takes_value(true)
+                .help("Save the outputs to a Kaldi archive (.ark)"),
+        )
         .arg(
             Arg::with_name("assert-sane-floats")
                 .long("assert-sane-floats")
@@ -313,6 +340,54 @@ fn main() -> tract_core::anyhow::Result<()> {
         );
     app = app.subcommand(output_options(run));
 
+    let quantize =
+        clap::SubCommand::with_name("quantize")
+            .long_about("Post-training static quantization of convolutions and matrix products")
+            .arg(
+                Arg::with_name("calibration")
+                    .takes_value(true)
+                    .multiple(true)
+                    .number_of_values(1)
+                    .long("calibration")
+                    .help("Calibration sample (.npz, one tensor per input, named after it)"),
+            )
+            .arg(
+                Arg::with_name("method")
+                    .takes_value(true)
+                    .long("method")
+                    .possible_values(&["minmax", "histogram"])
+                    .help("Calibration statistics (default: minmax)"),
+            )
+            .arg(Arg::with_name("percentile").takes_value(true).long("percentile").help(
+                "Percentage of values to keep in range with histogram method (default: 99.99)",
+            ))
+            .arg(
+                Arg::with_name("type")
+                    .takes_value(true)
+                    .long("type")
+                    .possible_values(&["i8", "u8"])
+                    .help("Quantized type (default: i8)"),
+            )
+            .arg(
+                Arg::with_name("nnef-dir")
+                    .takes_value(true)
+                    .long("nnef-dir")
+                    .help("Dump the quantized network in NNEF format (as a directory)"),
+            )
+            .arg(
+                Arg::with_name("nnef-tar")
+                    .takes_value(true)
+                    .long("nnef-tar")
+                    .help("Dump the quantized network in NNEF format (as a tar file)"),
+            )
+            .arg(
+                Arg::with_name("nnef")
+                    .takes_value(true)
+                    .long("nnef")
+                    .help("Dump the quantized network in NNEF format (as a tar.gz file)"),
+            );
+    app = app.subcommand(output_options(quantize));
+
     let optimize = clap::SubCommand::with_name("optimize").help("Optimize the graph");
     app = app.subcommand(output_options(optimize));
 
@@ -489,6 +564,10 @@ fn handle(matches: clap::ArgMatches, probe: Option<&Probe>) -> CliResult<()> {
 
         ("run", Some(m)) => run::handle(&params, m),
 
+        ("quantize", Some(m)) => {
+            quantize::handle(&params, &matches, m, &display_params_from_clap(&matches, m)?)
+        }
+
         #[cfg(feature = "pulse")]
         ("stream-check", Some(m)) => {
             stream_check::handle(&params, &display_params_from_clap(&matches, m)?)
@@ -77,9 +77,7 @@ pub trait Model:
             vec![lir.iteration_count(input)]
         } else if let Some(mir) = self.node_op(id).downcast_ref::<tract_core::ops::scan::Scan>() {
             vec![mir.iteration_count(input)]
-        } else if let Some(_) =
-            self.node_op(id).downcast_ref::<tract_hir::ops::scan::InferenceScan>()
-        {
+        } else if self.node_op(id).downcast_ref::<tract_hir::ops::scan::InferenceScan>().is_some() {
             // if we have typefact, we hopefully have type ops
             unreachable!();
         } else {
@@ -91,6 +89,8 @@ pub trait Model:
 
     fn properties(&self) -> &HashMap<String, Arc<Tensor>>;
 
+    fn symbol_table(&self) -> &SymbolScope;
+
     fn rename_node(&mut self, id: usize, name: &str) -> TractResult<()>;
 }
 
@@ -156,7 +156,7 @@ where
     }
 
     fn set_input_names(&mut self, names: &[&str]) -> TractResult<()> {
-        self.set_input_names(names.into_iter())
+        self.set_input_names(names.iter())
     }
 
     fn set_output_names(&mut self, names: &[&str]) -> TractResult<()> {
@@ -195,6 +195,10 @@ where
         &self.properties
     }
 
+    fn symbol_table(&self) -> &SymbolScope {
+        &self.symbol_table
+    }
+
     fn rename_node(&mut self, id: usize, name: &str) -> TractResult<()> {
         self.rename_node(id, name)
     }
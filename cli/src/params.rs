@@ -22,6 +22,7 @@ use super::model::Model;
 
 use std::convert::*;
 
+#[allow(dead_code)]
 #[derive(Debug)]
 pub enum SomeGraphDef {
     NoGraphDef,
@@ -69,6 +70,8 @@ pub struct Parameters {
     pub tf_model: (),
 
     pub input_values: HashMap<String, Vec<Arc<Tensor>>>,
+    /// utterance keys, when input values come from a Kaldi archive
+    pub utterances: Option<Vec<String>>,
 
     pub assertions: Assertions,
 
@@ -110,6 +113,8 @@ impl Parameters {
                 "onnx"
             } else if filename.extension().map(|s| s == "raw" || s == "txt").unwrap_or(false) {
                 "kaldi"
+            } else if filename.join("saved_model.pb").exists() {
+                "tf"
             } else if filename.is_dir()
                 || filename.to_string_lossy().ends_with(".tar")
                 || filename.to_string_lossy().ends_with(".tar.gz")
@@ -172,7 +177,7 @@ impl Parameters {
                 info_usage("loaded framework (onnx)", probe);
                 let graph = onnx.proto_model_for_path(&filename)?;
                 info_usage("proto model loaded", probe);
-                let parsed = onnx.parse(&graph)?;
+                let parsed = onnx.parse(&graph, filename.parent())?;
                 if need_graph {
                     (
                         SomeGraphDef::Onnx(graph, parsed.clone()),
@@ -187,6 +192,16 @@ impl Parameters {
             "tf" => {
                 let tf = tract_tensorflow::tensorflow();
                 info_usage("loaded framework (tf)", probe);
+                if filename.is_dir() {
+                    let model_and_ext =
+                        tf.model_for_saved_model_dir(&filename, matches.value_of("tf_signature"))?;
+                    info_usage("saved model loaded", probe);
+                    return Ok((
+                        SomeGraphDef::NoGraphDef,
+                        Box::new(model_and_ext.0),
+                        Some(model_and_ext.1),
+                    ));
+                }
                 let mut graph = tf.proto_model_for_path(&filename)?;
                 info_usage("proto model loaded", probe);
                 if matches.is_present("determinize") {
@@ -275,6 +290,7 @@ impl Parameters {
         Graph<F, O>: SpecialOps<F, O>,
         E: std::fmt::Debug,
     {
+        let symbol_table = raw_model.symbol_table.clone();
         let files = inputs_dir
             .read_dir()?
             .map(|file| {
@@ -292,14 +308,15 @@ impl Parameters {
                         .nth(0)
                         .unwrap()
                         .parse::<usize>()?;
-                    let (name, tensor) = tensor::for_data(file.path().to_str().unwrap())?;
+                    let (name, tensor) =
+                        tensor::for_data(&symbol_table, file.path().to_str().unwrap())?;
                     Ok(Some((ix, filename.starts_with("input_"), filename, name.unwrap(), tensor)))
                 } else {
                     Ok(None)
                 }
             })
             .collect::<CliResult<Vec<Option<_>>>>()?;
-        let files = files.into_iter().filter_map(|x| x).collect::<Vec<_>>();
+        let files = files.into_iter().flatten().collect::<Vec<_>>();
         let (inputs, outputs) = files.iter().partition::<Vec<_>, _>(|f| f.1);
         let inputs = inputs.into_iter().sorted_by_key(|f| f.0).collect::<Vec<_>>();
         let outputs = outputs.into_iter().sorted_by_key(|f| f.0).collect::<Vec<_>>();
@@ -353,10 +370,11 @@ impl Parameters {
         E: std::fmt::Debug,
     {
         let mut input_values = HashMap::new();
+        let symbol_table = raw_model.symbol_table.clone();
 
         if let Some(inputs) = matches.values_of("input") {
             for (ix, v) in inputs.enumerate() {
-                let (name, t) = tensor::for_string(v)?;
+                let (name, t) = tensor::for_string(&symbol_table, v)?;
                 let fact = t.clone().without_value();
                 let fact: F = (&fact).try_into().unwrap();
                 let outlet = if let Some(name) = name.filter(|s| s.len() > 0) {
@@ -588,7 +606,10 @@ impl Parameters {
                 stage!("concretize-stream-dim-declutter", typed_model -> typed_model, |m:TypedModel| Ok(m.declutter()?));
             } else if let Some(pulse) = pulse {
                 stage!("pulse", typed_model -> pulsed_model, |m:TypedModel| Ok(PulsedModel::new(&m, pulse)?));
+                // keep the pulsed model for stream-check and latency reports
+                let pulsed = pulsed_model.clone();
                 stage!("pulse-to-type", pulsed_model -> typed_model, |m:PulsedModel| Ok(m.into_typed()?));
+                pulsed_model = pulsed;
                 stage!("pulse-declutter", typed_model -> typed_model, |m:TypedModel| Ok(m.declutter()?));
             }
         }
@@ -624,6 +645,10 @@ impl Parameters {
         let (filename, onnx_tc) = Self::disco_model(matches)?;
         let (mut graph, mut raw_model, tf_model_extensions) =
             Self::load_model(matches, probe, &filename)?;
+        let symbol_table = raw_model.symbol_table().clone();
+        for assertion in matches.values_of("assert").into_iter().flatten() {
+            symbol_table.add_assertion(assertion)?;
+        }
 
         info!("Model {:?} loaded", filename);
         info_usage("model loaded", probe);
@@ -668,7 +693,7 @@ impl Parameters {
 
         if let Some(inputs) = matches.values_of("input") {
             let names = inputs
-                .map(|t| Ok(tensor::for_string(t)?.0))
+                .map(|t| Ok(tensor::for_string(&symbol_table, t)?.0))
                 .collect::<CliResult<Vec<Option<String>>>>()?;
             if names.iter().all(|s| s.is_some() && s.as_ref().unwrap().len() > 0) {
                 let names: Vec<&str> = names.iter().map(|s| &**s.as_ref().unwrap()).collect();
@@ -677,18 +702,18 @@ impl Parameters {
         }
 
         if let Some(inputs) = matches.values_of("input_node") {
-            let inputs: Vec<&str> = inputs.map(|s| s).collect();
+            let inputs: Vec<&str> = inputs.collect();
             raw_model.set_input_names(&inputs)?;
         };
 
         if let Some(outputs) = matches.values_of("output_node") {
-            let outputs: Vec<&str> = outputs.map(|s| s).collect();
+            let outputs: Vec<&str> = outputs.collect();
             raw_model.set_output_names(&outputs)?;
         };
 
         if let Some(override_facts) = matches.values_of("override_fact") {
             for fact in override_facts {
-                let (name, fact) = tensor::for_string(fact)?;
+                let (name, fact) = tensor::for_string(&symbol_table, fact)?;
                 let node = raw_model.node_id_by_name(&name.unwrap())?;
                 if let Some(inf) = raw_model.downcast_mut::<InferenceModel>() {
                     inf.set_outlet_fact(OutletId::new(node, 0), fact)?;
@@ -713,7 +738,8 @@ impl Parameters {
             })
             .collect();
 
-        let mut assertions = Assertions::from_clap(matches, &*output_names_and_labels)?;
+        let mut assertions =
+            Assertions::from_clap(matches, &symbol_table, &*output_names_and_labels)?;
 
         if let Some(sub) = matches.value_of("kaldi_downsample") {
             dispatch_model_mut_no_pulse!(raw_model, |m| Self::kaldi_downsample(m, sub.parse()?))?;
@@ -727,7 +753,8 @@ impl Parameters {
             dispatch_model_mut_no_pulse!(raw_model, |m| Self::kaldi_context(m, left, right))?;
         }
 
-        let input_values = dispatch_model_mut_no_pulse!(raw_model, |m| Self::inputs(
+        #[allow(unused_mut)]
+        let mut input_values = dispatch_model_mut_no_pulse!(raw_model, |m| Self::inputs(
             m,
             &mut assertions,
             matches,
@@ -735,6 +762,29 @@ impl Parameters {
             onnx_tc
         ))?;
 
+        #[cfg(feature = "kaldi")]
+        let utterances = if let Some(ark) = matches.value_of("input_ark") {
+            if matches.is_present("multiturn") {
+                bail!("--input-ark runs each utterance separately, it can not be --multiturn")
+            }
+            let entries = tract_kaldi::ark::read(ark)?;
+            let input = raw_model.input_outlets()[0];
+            let name = raw_model.node_name(input.node).to_string();
+            info!("Using {} utterance(s) from {} for input {}", entries.len(), ark, name);
+            let (keys, values): (Vec<String>, Vec<Arc<Tensor>>) =
+                entries.into_iter().map(|(key, t)| (key, t.into_arc_tensor())).unzip();
+            input_values.insert(name, values);
+            Some(keys)
+        } else {
+            None
+        };
+        #[cfg(not(feature = "kaldi"))]
+        let utterances = if matches.is_present("input_ark") {
+            bail!("--input-ark requires the kaldi feature")
+        } else {
+            None
+        };
+
         if matches.is_present("partial") {
             if let Some(m) = raw_model.downcast_ref::<InferenceModel>() {
                 raw_model = Box::new(m.compact()?);
@@ -743,28 +793,24 @@ impl Parameters {
             }
         }
 
-        Self::pipeline(
-            matches,
-            probe,
-            raw_model,
-            tf_model_extensions,
-            need_reference_model.as_deref(),
+        Self::pipeline(matches, probe, raw_model, tf_model_extensions, need_reference_model).map(
+            |(tract_model, pulsed_model, reference_model)| {
+                info!("Model ready");
+                info_usage("model ready", probe);
+                Parameters {
+                    graph,
+                    pulsed_model,
+                    tract_model,
+                    reference_model,
+                    tf_model,
+                    input_values,
+                    utterances,
+                    assertions,
+                    machine_friendly: matches.is_present("machine_friendly"),
+                    multiturn: matches.is_present("multiturn"),
+                }
+            },
         )
-        .map(|(tract_model, pulsed_model, reference_model)| {
-            info!("Model ready");
-            info_usage("model ready", probe);
-            Parameters {
-                graph,
-                pulsed_model,
-                tract_model,
-                reference_model,
-                tf_model,
-                input_values,
-                assertions,
-                machine_friendly: matches.is_present("machine_friendly"),
-                multiturn: matches.is_present("multiturn"),
-            }
-        })
     }
 }
 
@@ -830,13 +876,15 @@ pub struct Assertions {
 impl Assertions {
     fn from_clap(
         matches: &clap::ArgMatches,
+        symbol_table: &SymbolScope,
         output_names: &[Vec<String>],
     ) -> CliResult<Assertions> {
         if let Some(sub) = matches.subcommand.as_ref().map(|sub| &sub.matches) {
             let mut assert_outputs: Vec<Option<Arc<Tensor>>> = vec![None; output_names.len()];
             if let Some(values) = sub.values_of("assert-output") {
                 for (ix, o) in values.enumerate() {
-                    assert_outputs[ix] = tensor::for_string(o).unwrap().1.value.concretize();
+                    assert_outputs[ix] =
+                        tensor::for_string(symbol_table, o).unwrap().1.value.concretize();
                 }
             }
 
@@ -857,17 +905,16 @@ impl Assertions {
                 }
             }
 
-            if sub.values_of("assert_output").is_some()
-                || sub.values_of("assert-output-bundle").is_some()
+            if (sub.values_of("assert_output").is_some()
+                || sub.values_of("assert-output-bundle").is_some())
+                && assert_outputs.contains(&None)
             {
-                if assert_outputs.contains(&None) {
-                    bail!("Could not find assertions for all outputs: names and aliases are {:?}, found {:?}", output_names, assert_outputs);
-                }
+                bail!("Could not find assertions for all outputs: names and aliases are {:?}, found {:?}", output_names, assert_outputs);
             }
 
             let assert_output_facts: Option<Vec<InferenceFact>> = matches
                 .values_of("assert-output-fact")
-                .map(|vs| vs.map(|v| tensor::for_string(v).unwrap().1).collect());
+                .map(|vs| vs.map(|v| tensor::for_string(symbol_table, v).unwrap().1).collect());
 
             Ok(Assertions { assert_outputs, assert_output_facts })
         } else {
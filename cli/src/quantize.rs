@@ -0,0 +1,497 @@
+use crate::annotations::Annotations;
+use crate::display_params::DisplayParams;
+use crate::model::Model;
+use crate::CliResult;
+use crate::Parameters;
+use tract_core::ops::cnn::{ConvUnary, KernelFormat};
+use tract_core::ops::matmul::{MatMul, MatMulUnary, QMatMul, QParams};
+use tract_core::ops::quant::{quantize_linear_i8, quantize_linear_u8, DequantizeLinearF32};
+use tract_hir::internal::*;
+use tract_ndarray::{ArrayD, Axis};
+
+const HISTOGRAM_BINS: usize = 2048;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Method {
+    MinMax,
+    /// Keep the given percentage of the observed values, clipping the tails.
+    Histogram(f32),
+}
+
+pub fn handle(
+    params: &Parameters,
+    matches: &clap::ArgMatches,
+    sub_matches: &clap::ArgMatches,
+    display_params: &DisplayParams,
+) -> CliResult<()> {
+    let model = params
+        .tract_model
+        .downcast_ref::<TypedModel>()
+        .context("Can only quantize a decluttered typed model (do not use -O)")?;
+    let dt: DatumType = sub_matches.value_of("type").unwrap_or("i8").parse()?;
+    if dt != i8::datum_type() && dt != u8::datum_type() {
+        bail!("Can only quantize to i8 or u8, not {:?}", dt);
+    }
+    let method = match sub_matches.value_of("method").unwrap_or("minmax") {
+        "minmax" => Method::MinMax,
+        "histogram" => {
+            Method::Histogram(sub_matches.value_of("percentile").unwrap_or("99.99").parse()?)
+        }
+        s => bail!("Unknown calibration method {}", s),
+    };
+    let samples = sub_matches
+        .values_of("calibration")
+        .context("At least one calibration set is required (--calibration inputs.npz)")?
+        .map(|path| load_calibration_sample(model, path))
+        .collect::<CliResult<Vec<_>>>()?;
+
+    let ranges = calibrate(model, &samples, method)?;
+    let quantized = quantize(model, &ranges, dt)?;
+
+    report_errors(model, &quantized, &samples, display_params)?;
+
+    let mut nnef = super::nnef(matches);
+    if !matches.is_present("nnef_tract_core") {
+        nnef = nnef.with_tract_core();
+    }
+    let mut written = false;
+    if let Some(path) = sub_matches.value_of("nnef") {
+        let file = std::fs::File::create(path)?;
+        let encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+        nnef.write_to_tar(&quantized, encoder)?;
+        written = true;
+    }
+    if let Some(path) = sub_matches.value_of("nnef-tar") {
+        let file = std::fs::File::create(path)?;
+        nnef.write_to_tar(&quantized, file)?;
+        written = true;
+    }
+    if let Some(path) = sub_matches.value_of("nnef-dir") {
+        nnef.write_to_dir(&quantized, path)?;
+        written = true;
+    }
+    if !written {
+        warn!("No output specified (--nnef, --nnef-tar or --nnef-dir), quantized model discarded");
+    }
+    Ok(())
+}
+
+/// A calibration sample is a npz file holding one tensor per model input, named after it.
+fn load_calibration_sample(model: &TypedModel, path: &str) -> CliResult<TVec<Tensor>> {
+    let mut npz = ndarray_npy::NpzReader::new(
+        std::fs::File::open(path).with_context(|| format!("opening {:?}", path))?,
+    )?;
+    model
+        .input_outlets()?
+        .iter()
+        .map(|input| {
+            let name = &model.node(input.node).name;
+            crate::tensor::for_npz(&mut npz, name)
+                .or_else(|_| crate::tensor::for_npz(&mut npz, &format!("{}.npy", name)))
+                .with_context(|| format!("Looking for input {} in {}", name, path))
+        })
+        .collect()
+}
+
+/// Annotate the outputs of the quantized model with their maximum absolute error over all the
+/// calibration samples, and render them.
+fn report_errors(
+    model: &TypedModel,
+    quantized: &TypedModel,
+    samples: &[TVec<Tensor>],
+    display_params: &DisplayParams,
+) -> CliResult<()> {
+    let reference_plan = SimplePlan::new(model)?;
+    let quantized_plan = SimplePlan::new(quantized)?;
+    let mut errors = vec![0f32; quantized.output_outlets()?.len()];
+    for inputs in samples {
+        let reference = reference_plan.run(inputs.clone())?;
+        let found = quantized_plan.run(inputs.clone())?;
+        for (error, (r, f)) in errors.iter_mut().zip(reference.iter().zip(found.iter())) {
+            if let (Ok(r), Ok(f)) = (r.as_slice::<f32>(), f.as_slice::<f32>()) {
+                *error = r.iter().zip(f.iter()).fold(*error, |acc, (r, f)| acc.max((r - f).abs()));
+            }
+        }
+    }
+    let mut annotations = Annotations::from_model(quantized as &dyn Model)?;
+    for (outlet, error) in quantized.output_outlets()?.iter().zip(errors.iter()) {
+        annotations.node_mut(outlet.node.into()).labels.push(format!(
+            "Max absolute error over {} calibration sample(s): {}",
+            samples.len(),
+            error
+        ));
+    }
+    for outlet in quantized.output_outlets()? {
+        crate::terminal::render_node(quantized, outlet.node, &annotations, display_params)?;
+    }
+    Ok(())
+}
+
+/// Run the calibration samples, calling `f` on every f32 value computed by the model.
+fn for_each_f32_outlet(
+    model: &TypedModel,
+    samples: &[TVec<Tensor>],
+    mut f: impl FnMut(OutletId, &[f32]),
+) -> CliResult<()> {
+    let plan = SimplePlan::new(model)?;
+    let mut state = SimpleState::new(&plan)?;
+    for inputs in samples {
+        state.run_plan_with_eval(inputs.clone(), |session_state, op_state, node, input| {
+            let outputs = tract_core::plan::eval(session_state, op_state, node, input)?;
+            for (ix, output) in outputs.iter().enumerate() {
+                if let Ok(values) = output.as_slice::<f32>() {
+                    f(OutletId::new(node.id, ix), values)
+                }
+            }
+            Ok(outputs) as TractResult<_>
+        })?;
+    }
+    Ok(())
+}
+
+/// Values seen on an outlet during calibration.
+#[derive(Clone, Debug, Default)]
+struct OutletStats {
+    min: f32,
+    max: f32,
+    histogram: Option<Histogram>,
+}
+
+/// Histogram over a range that doubles whenever a value falls outside of it, merging bins
+/// pairwise, so it can be filled while the range is still being discovered.
+#[derive(Clone, Debug, PartialEq)]
+struct Histogram {
+    low: f32,
+    high: f32,
+    bins: Vec<u64>,
+}
+
+impl Histogram {
+    /// An empty histogram over a range including `min`, `max` and zero.
+    fn new(min: f32, max: f32) -> Histogram {
+        let (low, high) = (min.min(0.0), max.max(0.0));
+        let high = if high > low { high } else { low + f32::EPSILON };
+        Histogram { low, high, bins: vec![0; HISTOGRAM_BINS] }
+    }
+
+    fn add(&mut self, v: f32) {
+        while v < self.low {
+            self.grow(false);
+        }
+        while v >= self.high {
+            self.grow(true);
+        }
+        let bin = ((v - self.low) / (self.high - self.low) * self.bins.len() as f32) as usize;
+        let last = self.bins.len() - 1;
+        self.bins[bin.min(last)] += 1;
+    }
+
+    /// Double the range, upwards or downwards, keeping the bin boundaries aligned.
+    fn grow(&mut self, upwards: bool) {
+        let half = self.bins.len() / 2;
+        let width = self.high - self.low;
+        if upwards {
+            for i in 0..half {
+                self.bins[i] = self.bins[2 * i] + self.bins[2 * i + 1];
+            }
+            self.bins[half..].iter_mut().for_each(|b| *b = 0);
+            self.high = self.low + 2.0 * width;
+        } else {
+            for i in (0..half).rev() {
+                self.bins[half + i] = self.bins[2 * i] + self.bins[2 * i + 1];
+            }
+            self.bins[..half].iter_mut().for_each(|b| *b = 0);
+            self.low = self.high - 2.0 * width;
+        }
+    }
+}
+
+/// Compute the range to quantize on for each f32 outlet, in a single pass over the samples.
+fn calibrate(
+    model: &TypedModel,
+    samples: &[TVec<Tensor>],
+    method: Method,
+) -> CliResult<HashMap<OutletId, (f32, f32)>> {
+    let mut stats: HashMap<OutletId, OutletStats> = HashMap::new();
+    for_each_f32_outlet(model, samples, |outlet, values| {
+        let stats = stats.entry(outlet).or_default();
+        let finite = move || values.iter().copied().filter(|v| v.is_finite());
+        for v in finite() {
+            stats.min = stats.min.min(v);
+            stats.max = stats.max.max(v);
+        }
+        if let Method::Histogram(_) = method {
+            let (min, max) = (stats.min, stats.max);
+            let histogram = stats.histogram.get_or_insert_with(|| Histogram::new(min, max));
+            finite().for_each(|v| histogram.add(v));
+        }
+    })?;
+    Ok(stats
+        .into_iter()
+        .map(|(outlet, stats)| {
+            let range = match (method, &stats.histogram) {
+                (Method::Histogram(percentile), Some(h)) => {
+                    let (low, high) = clip_range((h.low, h.high), &h.bins, percentile);
+                    (low.max(stats.min), high.min(stats.max))
+                }
+                _ => (stats.min, stats.max),
+            };
+            (outlet, range)
+        })
+        .collect())
+}
+
+/// Shrink a range so that it keeps `percentile` percent of the histogram mass, trimming both
+/// tails evenly.
+fn clip_range((min, max): (f32, f32), histogram: &[u64], percentile: f32) -> (f32, f32) {
+    let total: u64 = histogram.iter().sum();
+    let tail = (total as f64 * (100.0 - percentile as f64) / 200.0) as u64;
+    let mut low = 0;
+    let mut acc = 0;
+    while low < histogram.len() - 1 && acc + histogram[low] <= tail {
+        acc += histogram[low];
+        low += 1;
+    }
+    let mut high = histogram.len() - 1;
+    acc = 0;
+    while high > low && acc + histogram[high] <= tail {
+        acc += histogram[high];
+        high -= 1;
+    }
+    let width = (max - min) / histogram.len() as f32;
+    ((min + low as f32 * width).min(0.0), (min + (high + 1) as f32 * width).max(0.0))
+}
+
+/// Scale and zero point mapping a float range (stretched to include zero) to dt.
+fn range_qparams((min, max): (f32, f32), dt: DatumType) -> (f32, i32) {
+    let (min, max) = (min.min(0.0), max.max(0.0));
+    let (qmin, qmax) = if dt == i8::datum_type() { (-128, 127) } else { (0, 255) };
+    let scale = if max > min { (max - min) / (qmax - qmin) as f32 } else { 1.0 };
+    let zero_point = (qmin as f32 - min / scale).round().max(qmin as f32).min(qmax as f32);
+    (scale, zero_point as i32)
+}
+
+fn quantize_value(x: f32, scale: f32, zero_point: i32, dt: DatumType) -> i32 {
+    let (qmin, qmax) = if dt == i8::datum_type() { (-128, 127) } else { (0, 255) };
+    ((x / scale).round() as i32 + zero_point).max(qmin).min(qmax)
+}
+
+/// Quantize a weight tensor with one scale and zero point per slice along `axis`. i8 weights are
+/// quantized symmetrically.
+fn quantize_weights(
+    weights: &Tensor,
+    axis: usize,
+    dt: DatumType,
+) -> CliResult<(Tensor, Tensor, Tensor)> {
+    let weights = weights.to_array_view::<f32>()?;
+    let (scales, zero_points): (Vec<f32>, Vec<i32>) = weights
+        .axis_iter(Axis(axis))
+        .map(|slice| {
+            if dt == i8::datum_type() {
+                let max = slice.iter().fold(0f32, |acc, x| acc.max(x.abs()));
+                (if max > 0.0 { max / 127.0 } else { 1.0 }, 0)
+            } else {
+                let min = slice.iter().fold(0f32, |acc, &x| acc.min(x));
+                let max = slice.iter().fold(0f32, |acc, &x| acc.max(x));
+                range_qparams((min, max), dt)
+            }
+        })
+        .unzip();
+    let quantized = ArrayD::from_shape_fn(weights.shape(), |ix| {
+        let c = ix[axis];
+        quantize_value(weights[&ix], scales[c], zero_points[c], dt)
+    });
+    let quantized = quantized.into_tensor().cast_to_dt(dt)?.into_owned();
+    let zero_points = tensor1(&zero_points).cast_to_dt(dt)?.into_owned();
+    Ok((quantized, tensor1(&scales), zero_points))
+}
+
+fn scalar_qparam(value: i32, dt: DatumType) -> CliResult<AttrOrInput> {
+    Ok(AttrOrInput::Attr(tensor0(value).cast_to_dt(dt)?.into_owned().into_arc_tensor()))
+}
+
+/// Replace f32 convolutions and matrix products by their quantized counterparts, with
+/// quantize/dequantize pairs around them.
+fn quantize(
+    model: &TypedModel,
+    ranges: &HashMap<OutletId, (f32, f32)>,
+    dt: DatumType,
+) -> CliResult<TypedModel> {
+    let mut quantized = model.clone();
+    let mut count = 0;
+    for id in model.eval_order()? {
+        let node = model.node(id);
+        let is_f32 = |o: &OutletId| {
+            model.outlet_fact(*o).map(|f| f.datum_type == f32::datum_type()).unwrap_or(false)
+        };
+        let candidate =
+            (node.op_is::<ConvUnary>() || node.op_is::<MatMulUnary>() || node.op_is::<MatMul>())
+                && node.inputs.iter().all(is_f32)
+                && node.inputs.iter().all(|i| ranges.contains_key(i))
+                && ranges.contains_key(&OutletId::new(id, 0));
+        if !candidate {
+            continue;
+        }
+        if let Some(patch) = quantize_node(&quantized, node, ranges, dt)
+            .with_context(|| format!("Quantizing {}", node))?
+        {
+            patch.apply(&mut quantized)?;
+            count += 1;
+        }
+    }
+    info!("Quantized {} operators", count);
+    Ok(quantized.compact()?)
+}
+
+fn quantize_node(
+    current: &TypedModel,
+    node: &TypedNode,
+    ranges: &HashMap<OutletId, (f32, f32)>,
+    dt: DatumType,
+) -> CliResult<Option<TypedModelPatch>> {
+    let mut patch = TypedModelPatch::new(format!("Quantizing {}", node.name));
+    let mut inputs = tvec!();
+    let mut input_qparams = tvec!();
+    for (ix, input) in node.inputs.iter().enumerate() {
+        let (scale, zero_point) = range_qparams(ranges[input], dt);
+        let op = if dt == i8::datum_type() {
+            quantize_linear_i8(scale.recip(), zero_point as i8)
+        } else {
+            quantize_linear_u8(scale.recip(), zero_point as u8)
+        };
+        let wire = patch.tap_model(current, current.node(node.id).inputs[ix])?;
+        inputs
+            .push(patch.wire_node(format!("{}.quantize_input_{}", node.name, ix), op, &[wire])?[0]);
+        input_qparams.push((scale, zero_point));
+    }
+    let (c_scale, c0) = range_qparams(ranges[&OutletId::new(node.id, 0)], dt);
+    let wire = if let Some(conv) = node.op_as::<ConvUnary>() {
+        if conv.kernel.datum_type() != f32::datum_type() || conv.q_params.is_some() {
+            return Ok(None);
+        }
+        let c_axis = match conv.kernel_fmt {
+            KernelFormat::OIHW => 0,
+            KernelFormat::HWIO => conv.kernel.rank() - 1,
+        };
+        let (kernel, a_scale, a0) = quantize_weights(&conv.kernel, c_axis, dt)?;
+        let (b_scale, b0) = input_qparams[0];
+        let bias = if let Some(bias) = &conv.bias {
+            let bias = bias.as_slice::<f32>()?;
+            let a_scale = a_scale.as_slice::<f32>()?;
+            let bias: Vec<i32> = bias
+                .iter()
+                .enumerate()
+                .map(|(c, b)| (b / (a_scale[c] * b_scale)).round() as i32)
+                .collect();
+            Some(rctensor1(&bias))
+        } else {
+            None
+        };
+        let params = QParams {
+            a0: AttrOrInput::Attr(a0.into_arc_tensor()),
+            a_scale: AttrOrInput::Attr(a_scale.into_arc_tensor()),
+            b0: scalar_qparam(b0, dt)?,
+            b_scale: AttrOrInput::Attr(rctensor0(b_scale)),
+            c0: scalar_qparam(c0, dt)?,
+            c_scale: AttrOrInput::Attr(rctensor0(c_scale)),
+        };
+        let op = ConvUnary {
+            kernel: kernel.into_arc_tensor(),
+            bias,
+            q_params: Some((dt, params)),
+            ..conv.clone()
+        };
+        patch.wire_node(format!("{}.quantized", node.name), op, &inputs)?[0]
+    } else if let Some(mm) = node.op_as::<MatMulUnary>() {
+        if mm.a.datum_type() != f32::datum_type() {
+            return Ok(None);
+        }
+        let mut a = mm.a.clone().into_tensor();
+        let rank = current.outlet_fact(current.node(node.id).inputs[0])?.rank();
+        while a.rank() < rank {
+            a.insert_axis(0)?;
+        }
+        let m_axis = rank - 2 + mm.a_trans as usize;
+        let (a, a_scale, a0) = quantize_weights(&a, m_axis, dt)?;
+        let (b_scale, b0) = input_qparams[0];
+        let params = QParams {
+            a0: AttrOrInput::Attr(a0.into_arc_tensor()),
+            a_scale: AttrOrInput::Attr(a_scale.into_arc_tensor()),
+            b0: scalar_qparam(b0, dt)?,
+            b_scale: AttrOrInput::Attr(rctensor0(b_scale)),
+            c0: scalar_qparam(c0, dt)?,
+            c_scale: AttrOrInput::Attr(rctensor0(c_scale)),
+        };
+        let a = patch.add_const(format!("{}.a", node.name), a)?;
+        let bias = patch.add_const(format!("{}.bias", node.name), tensor0(0i32))?;
+        let op = QMatMul::new(mm.a_trans, mm.b_trans, mm.c_trans, dt, params);
+        patch.wire_node(format!("{}.quantized", node.name), op, &[a, inputs[0], bias])?[0]
+    } else if let Some(mm) = node.op_as::<MatMul>() {
+        let (a_scale, a0) = input_qparams[0];
+        let (b_scale, b0) = input_qparams[1];
+        let params = QParams {
+            a0: scalar_qparam(a0, dt)?,
+            a_scale: AttrOrInput::Attr(rctensor0(a_scale)),
+            b0: scalar_qparam(b0, dt)?,
+            b_scale: AttrOrInput::Attr(rctensor0(b_scale)),
+            c0: scalar_qparam(c0, dt)?,
+            c_scale: AttrOrInput::Attr(rctensor0(c_scale)),
+        };
+        let bias = patch.add_const(format!("{}.bias", node.name), tensor0(0i32))?;
+        let op = QMatMul::new(mm.a_trans, mm.b_trans, mm.c_trans, dt, params);
+        patch.wire_node(format!("{}.quantized", node.name), op, &[inputs[0], inputs[1], bias])?[0]
+    } else {
+        return Ok(None);
+    };
+    let wire = patch.wire_node(
+        format!("{}.dequantize", node.name),
+        DequantizeLinearF32::new(c_scale, c0),
+        &[wire],
+    )?[0];
+    patch.shunt_outside(current, OutletId::new(node.id, 0), wire)?;
+    Ok(Some(patch))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn clip_range_trims_tails() {
+        let mut histogram = vec![0u64; 10];
+        histogram[0] = 1;
+        histogram[1..9].iter_mut().for_each(|b| *b = 100);
+        histogram[9] = 1;
+        assert_eq!(clip_range((-1.0, 9.0), &histogram, 99.0), (0.0, 8.0));
+        assert_eq!(clip_range((-1.0, 9.0), &histogram, 100.0), (-1.0, 9.0));
+    }
+
+    #[test]
+    fn clip_range_includes_zero() {
+        let mut histogram = vec![0u64; 10];
+        histogram[8] = 1000;
+        assert_eq!(clip_range((10.0, 20.0), &histogram, 99.0), (0.0, 19.0));
+    }
+
+    #[test]
+    fn range_qparams_i8_and_u8() {
+        assert_eq!(range_qparams((-128.0, 127.0), i8::datum_type()), (1.0, 0));
+        assert_eq!(range_qparams((-64.0, 63.5), i8::datum_type()), (0.5, 0));
+        assert_eq!(range_qparams((-64.0, 63.5), u8::datum_type()), (0.5, 128));
+        assert_eq!(range_qparams((1.0, 255.0), u8::datum_type()), (1.0, 0));
+        assert_eq!(range_qparams((-255.0, -1.0), u8::datum_type()), (1.0, 255));
+        assert_eq!(range_qparams((0.0, 0.0), i8::datum_type()), (1.0, -128));
+    }
+
+    #[test]
+    fn histogram_growth_matches_direct_fill() {
+        let values: Vec<f32> = (0..1000).map(|i| i as f32 / 100.0 - 3.0).collect();
+        let mut grown = Histogram::new(0.0, 1.0);
+        values.iter().for_each(|&v| grown.add(v));
+        assert_eq!(grown.bins.iter().sum::<u64>(), 1000);
+        assert!(grown.low <= -3.0 && grown.high >= 6.99);
+        let mut direct = Histogram { bins: vec![0; HISTOGRAM_BINS], ..grown.clone() };
+        values.iter().for_each(|&v| direct.add(v));
+        assert_eq!(grown, direct);
+    }
+}
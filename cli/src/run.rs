@@ -8,14 +8,20 @@ use tract_pulse::internal::*;
 pub fn handle(params: &Parameters, options: &clap::ArgMatches) -> CliResult<()> {
     let dump = options.is_present("dump");
     #[cfg(feature = "pulse")]
-    let outputs = if let Some(pulse) = params.tract_model.downcast_ref::<PulsedModel>() {
+    let all_outputs = if let Some(pulse) = params.tract_model.downcast_ref::<PulsedModel>() {
         run_pulse_t(pulse, &params)?
     } else {
         dispatch_model!(&*params.tract_model, |m| run_regular(m, &params, options))?
     };
 
     #[cfg(not(feature = "pulse"))]
-    let outputs = dispatch_model!(&*params.tract_model, |m| run_regular(m, &params, options))?;
+    let all_outputs = dispatch_model!(&*params.tract_model, |m| run_regular(m, &params, options))?;
+
+    if let Some(ark) = options.value_of("output-ark") {
+        save_ark(ark, params, &all_outputs)?;
+    }
+
+    let outputs = all_outputs.last().cloned().unwrap_or_default();
 
     if dump {
         for (ix, output) in outputs.iter().enumerate() {
@@ -49,7 +55,7 @@ fn run_regular(
     tract: &dyn Model,
     params: &Parameters,
     options: &clap::ArgMatches,
-) -> CliResult<TVec<Arc<Tensor>>> {
+) -> CliResult<Vec<TVec<Arc<Tensor>>>> {
     let steps = options.is_present("steps");
     let assert_sane_floats = options.is_present("assert-sane-floats");
     let mut npz = if let Some(npz) = options.value_of("save-steps") {
@@ -61,112 +67,162 @@ fn run_regular(
     dispatch_model!(tract, |m| {
         let plan = SimplePlan::new(m)?;
         let mut state = SimpleState::new(plan)?;
-        let mut results = tvec!();
+        let mut all_results = vec![];
         for (turn, inputs) in
             crate::tensor::retrieve_or_make_inputs(tract, params)?.into_iter().enumerate()
         {
-            results = state.run_plan_with_eval(inputs, |session_state, state, node, input| {
-                if steps {
-                    for i in &input {
-                        eprintln!(
-                            "{}{}{:?}",
-                            White.bold().paint(node.to_string()),
-                            Blue.bold().paint(" << "),
-                            i
-                        );
-                    }
-                }
-                let r = tract_core::plan::eval(session_state, state, node, input)?;
-                if steps {
-                    for o in &r {
-                        eprintln!(
-                            "{}{}{:?}",
-                            White.bold().paint(node.to_string()),
-                            Yellow.bold().paint(" >> "),
-                            o
-                        );
+            if params.utterances.is_some() {
+                // utterances are independent
+                state.reset_op_states()?;
+            }
+            let results =
+                state.run_plan_with_eval(inputs, |session_state, state, node, input| {
+                    if steps {
+                        for i in &input {
+                            eprintln!(
+                                "{}{}{:?}",
+                                White.bold().paint(node.to_string()),
+                                Blue.bold().paint(" << "),
+                                i
+                            );
+                        }
                     }
-                }
-                if let Some(npz) = npz.as_mut() {
-                    for (ix, t) in r.iter().enumerate() {
-                        let mut name = if ix == 0 {
-                            node.name.to_string()
-                        } else {
-                            format!("{}:{}", node.name, ix)
-                        };
-                        if params.multiturn {
-                            name = format!("turn_{}/{}", turn, name);
+                    let r = tract_core::plan::eval(session_state, state, node, input)?;
+                    if steps {
+                        for o in &r {
+                            eprintln!(
+                                "{}{}{:?}",
+                                White.bold().paint(node.to_string()),
+                                Yellow.bold().paint(" >> "),
+                                o
+                            );
                         }
-                        match t.datum_type() {
-                            DatumType::F32 => npz.add_array(name, &t.to_array_view::<f32>()?)?,
-                            DatumType::F64 => npz.add_array(name, &t.to_array_view::<f64>()?)?,
-                            DatumType::I32 => npz.add_array(name, &t.to_array_view::<i32>()?)?,
-                            DatumType::I8 => npz.add_array(name, &t.to_array_view::<i8>()?)?,
-                            DatumType::U8 => npz.add_array(name, &t.to_array_view::<u8>()?)?,
-                            _ => warn!("Not writing {}, {:?}, unsupported type", name, t),
+                    }
+                    if let Some(npz) = npz.as_mut() {
+                        for (ix, t) in r.iter().enumerate() {
+                            let mut name = if ix == 0 {
+                                node.name.to_string()
+                            } else {
+                                format!("{}:{}", node.name, ix)
+                            };
+                            if let Some(utterances) = &params.utterances {
+                                name = format!("{}/{}", utterances[turn], name);
+                            } else if params.multiturn {
+                                name = format!("turn_{}/{}", turn, name);
+                            }
+                            match t.datum_type() {
+                                DatumType::F32 => {
+                                    npz.add_array(name, &t.to_array_view::<f32>()?)?
+                                }
+                                DatumType::F64 => {
+                                    npz.add_array(name, &t.to_array_view::<f64>()?)?
+                                }
+                                DatumType::I32 => {
+                                    npz.add_array(name, &t.to_array_view::<i32>()?)?
+                                }
+                                DatumType::I8 => npz.add_array(name, &t.to_array_view::<i8>()?)?,
+                                DatumType::U8 => npz.add_array(name, &t.to_array_view::<u8>()?)?,
+                                _ => warn!("Not writing {}, {:?}, unsupported type", name, t),
+                            }
                         }
                     }
-                }
-                if assert_sane_floats {
-                    for (ix, o) in r.iter().enumerate() {
-                        if let Ok(floats) = o.as_slice::<f32>() {
-                            if let Some(pos) = floats.iter().position(|f| !f.is_finite()) {
-                                eprintln!("{:?}", floats);
-                                tract_core::anyhow::bail!(
-                                    "Found {} in output {} of {}",
-                                    floats[pos],
-                                    ix,
-                                    node
-                                );
+                    if assert_sane_floats {
+                        for (ix, o) in r.iter().enumerate() {
+                            if let Ok(floats) = o.as_slice::<f32>() {
+                                if let Some(pos) = floats.iter().position(|f| !f.is_finite()) {
+                                    eprintln!("{:?}", floats);
+                                    tract_core::anyhow::bail!(
+                                        "Found {} in output {} of {}",
+                                        floats[pos],
+                                        ix,
+                                        node
+                                    );
+                                }
                             }
                         }
                     }
-                }
-                Ok(r)
-            })?;
+                    Ok(r)
+                })?;
+            all_results.push(results);
         }
-        Ok(results)
+        Ok(all_results)
     })
 }
 
+#[cfg(feature = "kaldi")]
+fn save_ark(path: &str, params: &Parameters, outputs: &[TVec<Arc<Tensor>>]) -> CliResult<()> {
+    let model = &params.tract_model;
+    let mut entries = vec![];
+    for (turn, outputs) in outputs.iter().enumerate() {
+        let key = if let Some(utterances) = &params.utterances {
+            utterances[turn].clone()
+        } else {
+            format!("turn_{}", turn)
+        };
+        for (ix, output) in outputs.iter().enumerate() {
+            // one matrix per key: name outputs when there are several
+            let key = if outputs.len() > 1 {
+                format!("{}/{}", key, model.node_name(model.output_outlets()[ix].node))
+            } else {
+                key.clone()
+            };
+            entries.push((key, output.clone().into_tensor()));
+        }
+    }
+    tract_kaldi::ark::write(path, &entries)?;
+    Ok(())
+}
+
+#[cfg(not(feature = "kaldi"))]
+fn save_ark(_path: &str, _params: &Parameters, _outputs: &[TVec<Arc<Tensor>>]) -> CliResult<()> {
+    bail!("--output-ark requires the kaldi feature")
+}
+
 #[cfg(feature = "pulse")]
-fn run_pulse_t(model: &PulsedModel, params: &Parameters) -> CliResult<TVec<Arc<Tensor>>> {
+fn run_pulse_t(model: &PulsedModel, params: &Parameters) -> CliResult<Vec<TVec<Arc<Tensor>>>> {
+    let name = model.node_name(model.input_outlets()?[0].node);
+    let inputs = params.input_values.get(name).with_context(|| format!("No value for {}", name))?;
+    inputs.iter().map(|input| run_pulse_input(model, input)).collect()
+}
+
+#[cfg(feature = "pulse")]
+fn run_pulse_input(model: &PulsedModel, input: &Tensor) -> CliResult<TVec<Arc<Tensor>>> {
     let input_fact = model.input_fact(0)?;
     let output_fact = model.output_fact(0)?;
+    let input_stream = input_fact.streaming()?;
+    let output_stream = output_fact.streaming()?;
 
-    let output_pulse = output_fact.pulse();
+    let output_pulse = output_fact.pulse().unwrap();
     //    println!("output_fact: {:?}", output_fact);
-    let axis = input_fact.axis;
-    let name = model.node_name(model.input_outlets()?[0].node);
-    let input: &Tensor = &params.input_values.get(name).unwrap()[0];
+    let axis = input_stream.axis;
     //    println!("input_shape: {:?}", input.shape());
     let input_dim = input.shape()[axis];
     //    println!("output_fact: {:?}", output_fact);
-    let output_dim = output_fact
+    let output_dim = output_stream
         .dim
         .eval(&SymbolValues::default().with(stream_symbol(), input_dim as i64))
         .to_usize()?;
     let mut output_shape = output_fact.shape.to_vec();
-    output_shape[output_fact.axis] =
-        (output_dim as usize + output_fact.delay + 4 * output_fact.pulse()).to_dim();
+    output_shape[output_stream.axis] =
+        (output_dim as usize + output_stream.delay + 4 * output_fact.pulse().unwrap()).to_dim();
     let output_shape: TVec<usize> = output_shape.iter().map(|d| d.to_usize().unwrap()).collect();
     let plan = SimplePlan::new(model)?;
     let mut state = ::tract_core::plan::SimpleState::new(&plan)?;
     //    println!("output_shape: {:?}", output_shape);
-    let pulse = input_fact.pulse();
+    let pulse = input_fact.pulse().unwrap();
     let mut result = tract_ndarray::ArrayD::<f32>::default(&*output_shape);
     let input = input.to_array_view::<f32>()?;
     for ix in 0..input_dim.div_ceil(pulse) {
         let chunk =
             input.slice_axis(tract_ndarray::Axis(axis), (ix * pulse..(ix + 1) * pulse).into());
-        let input = if chunk.shape()[input_fact.axis] < pulse {
+        let input = if chunk.shape()[input_stream.axis] < pulse {
             let mut chunk_shape = chunk.shape().to_vec();
-            chunk_shape[input_fact.axis] = pulse;
+            chunk_shape[input_stream.axis] = pulse;
             let mut padded_chunk = tract_ndarray::ArrayD::<f32>::default(chunk_shape);
             padded_chunk
                 .slice_axis_mut(
-                    tract_ndarray::Axis(input_fact.axis),
-                    (..chunk.shape()[input_fact.axis]).into(),
+                    tract_ndarray::Axis(input_stream.axis),
+                    (..chunk.shape()[input_stream.axis]).into(),
                 )
                 .assign(&chunk);
             padded_chunk
@@ -177,13 +233,18 @@ fn run_pulse_t(model: &PulsedModel, params: &Parameters) -> CliResult<TVec<Arc<T
         let result_chunk = outputs[0].to_array_view::<f32>()?;
         result
             .slice_axis_mut(
-                tract_ndarray::Axis(output_fact.axis),
+                tract_ndarray::Axis(output_stream.axis),
                 ((output_pulse * ix)..(output_pulse * (ix + 1))).into(),
             )
             .assign(&result_chunk);
     }
-    result.slice_axis_inplace(tract_ndarray::Axis(output_fact.axis), (output_fact.delay..).into());
-    result
-        .slice_axis_inplace(tract_ndarray::Axis(output_fact.axis), (..output_dim as usize).into());
+    result.slice_axis_inplace(
+        tract_ndarray::Axis(output_stream.axis),
+        (output_stream.delay..).into(),
+    );
+    result.slice_axis_inplace(
+        tract_ndarray::Axis(output_stream.axis),
+        (..output_dim as usize).into(),
+    );
     Ok(tvec!(result.into_arc_tensor()))
 }
@@ -1,5 +1,5 @@
-use tract_itertools::Itertools;
 use tract_core::ndarray::{ArrayD, Axis};
+use tract_itertools::Itertools;
 
 use tract_core::model::OutletId;
 use tract_core::plan::SimpleState;
@@ -26,7 +26,8 @@ pub fn handle(params: &Parameters, options: &DisplayParams) -> CliResult<()> {
 
     let decl_input_fact = decl.input_fact(0)?;
     let pulsed_input_fact = pulsed.input_fact(0)?;
-    let input_pulse = pulsed_input_fact.pulse();
+    let input_axis = pulsed_input_fact.streaming()?.axis;
+    let input_pulse = pulsed_input_fact.pulse().unwrap();
 
     let annotations = crate::annotations::Annotations::from_model(&*params.tract_model)?
         .with_graph_def(&*params.tract_model, &params.graph)?;
@@ -49,9 +50,13 @@ pub fn handle(params: &Parameters, options: &DisplayParams) -> CliResult<()> {
             let outlet = OutletId::new(node, output_slot);
 
             let pulsed_output_fact = pulsed.outlet_fact(pulsed_outlet)?;
-            let output_pulse = pulsed_output_fact.pulse();
-            let output_axis = pulsed_output_fact.axis;
-            let delay = pulsed_output_fact.delay;
+            let (output_axis, delay) = if let Some(stream) = &pulsed_output_fact.stream {
+                (stream.axis, stream.delay)
+            } else {
+                debug!("skipping non-streaming output");
+                continue;
+            };
+            let output_pulse = pulsed_output_fact.pulse().unwrap();
 
             let stream_dim = delay + 3 * input_pulse + input_pulse / 2;
 
@@ -79,12 +84,11 @@ pub fn handle(params: &Parameters, options: &DisplayParams) -> CliResult<()> {
                 let offset = i * input_pulse;
                 if offset < stream_dim {
                     let count = input_pulse.min(stream_dim - offset);
-                    pulsed_input
-                        .slice_axis_mut(Axis(pulsed_input_fact.axis), (0..count).into())
-                        .assign(&fixed_input.to_array_view::<f32>()?.slice_axis(
-                            Axis(pulsed_input_fact.axis),
-                            (offset..offset + count).into(),
-                        ));
+                    pulsed_input.slice_axis_mut(Axis(input_axis), (0..count).into()).assign(
+                        &fixed_input
+                            .to_array_view::<f32>()?
+                            .slice_axis(Axis(input_axis), (offset..offset + count).into()),
+                    );
                 };
                 if offset + input_pulse > stream_dim {
                     debug!("Set known_stream_len: {}", stream_dim);
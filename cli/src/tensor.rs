@@ -25,22 +25,22 @@ fn parse_dt(dt: &str) -> CliResult<DatumType> {
     })
 }
 
-pub fn parse_spec(size: &str) -> CliResult<InferenceFact> {
+pub fn parse_spec(symbol_table: &SymbolScope, size: &str) -> CliResult<InferenceFact> {
     if size.len() == 0 {
         return Ok(InferenceFact::default());
     }
     if size.contains("x") && !size.contains(",") {
         parse_x_spec(size)
     } else {
-        parse_coma_spec(size)
+        parse_coma_spec(symbol_table, size)
     }
 }
 
-pub fn parse_coma_spec(size: &str) -> CliResult<InferenceFact> {
+pub fn parse_coma_spec(symbol_table: &SymbolScope, size: &str) -> CliResult<InferenceFact> {
     let splits = size.split(",").collect::<Vec<_>>();
 
     if splits.len() < 1 {
-        bail!("The <size> argument should be formatted as {size},{...},{type}.");
+        bail!("The <size> argument should be formatted as {{size}},{{...}},{{type}}.");
     }
 
     let last = splits.last().unwrap();
@@ -54,7 +54,11 @@ pub fn parse_coma_spec(size: &str) -> CliResult<InferenceFact> {
         shape
             .iter()
             .map(|&s| {
-                Ok(if s == "_" { GenericFactoid::Any } else { GenericFactoid::Only(parse_dim(s)?) })
+                Ok(if s == "_" {
+                    GenericFactoid::Any
+                } else {
+                    GenericFactoid::Only(parse_dim(symbol_table, s)?)
+                })
             })
             .collect::<CliResult<TVec<DimFact>>>()?,
     );
@@ -66,26 +70,26 @@ pub fn parse_coma_spec(size: &str) -> CliResult<InferenceFact> {
     }
 }
 
-pub fn parse_dim(i: &str) -> CliResult<TDim> {
-    // ensure the magic S is pre-registered
-    #[cfg(feature = "pulse")]
-    let _ = tract_pulse::internal::stream_symbol();
-
+pub fn parse_dim(symbol_table: &SymbolScope, i: &str) -> CliResult<TDim> {
     if i.len() == 0 {
         bail!("Can not parse empty string as Dim")
     }
-    let number_len = i.chars().take_while(|c| c.is_digit(10)).count();
-    let symbol_len = i.len() - number_len;
-    if symbol_len > 1 {
+    let number_len = i.chars().take_while(|c| c.is_ascii_digit()).count();
+    let symbol = &i[number_len..];
+    if !symbol.chars().all(|c| c.is_alphanumeric() || c == '_') {
         bail!("Can not parse {} as Dim", i)
     }
     let number: i64 = if number_len > 0 { i[..number_len].parse()? } else { 1 };
-    if symbol_len == 0 {
+    if symbol.len() == 0 {
         return Ok(number.to_dim());
     }
-    let symbol = i.chars().last().unwrap();
-    let symbol = Symbol::from(symbol);
-    Ok(symbol.to_dim() * number)
+    #[cfg(feature = "pulse")]
+    {
+        if symbol == "S" {
+            return Ok(tract_pulse::internal::stream_dim() * number);
+        }
+    }
+    Ok(symbol_table.sym(symbol).to_dim() * number)
 }
 
 pub fn parse_x_spec(size: &str) -> CliResult<InferenceFact> {
@@ -95,7 +99,7 @@ pub fn parse_x_spec(size: &str) -> CliResult<InferenceFact> {
     let splits = size.split("x").collect::<Vec<_>>();
 
     if splits.len() < 1 {
-        bail!("The <size> argument should be formatted as {size},{...},{type}.");
+        bail!("The <size> argument should be formatted as {{size}},{{...}},{{type}}.");
     }
 
     let last = splits.last().unwrap();
@@ -134,14 +138,14 @@ fn parse_values<'a, T: Datum + FromStr>(shape: &[usize], it: Vec<&'a str>) -> Cl
     Ok(tract_ndarray::Array::from_shape_vec(shape, values)?.into())
 }
 
-fn tensor_for_text_data(filename: &str) -> CliResult<Tensor> {
+fn tensor_for_text_data(symbol_table: &SymbolScope, filename: &str) -> CliResult<Tensor> {
     let mut file = fs::File::open(filename)
         .map_err(|e| format_err!("Reading tensor from {}, {:?}", filename, e))?;
     let mut data = String::new();
     file.read_to_string(&mut data)?;
 
     let mut lines = data.lines();
-    let proto = parse_spec(lines.next().context("Empty data file")?)?;
+    let proto = parse_spec(symbol_table, lines.next().context("Empty data file")?)?;
     let shape = proto.shape.concretize().unwrap();
 
     let values = lines.flat_map(|l| l.split_whitespace()).collect::<Vec<&str>>();
@@ -156,7 +160,10 @@ fn tensor_for_text_data(filename: &str) -> CliResult<Tensor> {
 }
 
 /// Parses the `data` command-line argument.
-pub fn for_data(filename: &str) -> CliResult<(Option<String>, InferenceFact)> {
+pub fn for_data(
+    symbol_table: &SymbolScope,
+    filename: &str,
+) -> CliResult<(Option<String>, InferenceFact)> {
     #[allow(unused_imports)]
     use std::convert::TryFrom;
     if filename.ends_with(".pb") {
@@ -177,7 +184,7 @@ pub fn for_data(filename: &str) -> CliResult<(Option<String>, InferenceFact)> {
         let mut npz = ndarray_npy::NpzReader::new(std::fs::File::open(filename)?)?;
         Ok((None, for_npz(&mut npz, inner)?.into()))
     } else {
-        Ok((None, tensor_for_text_data(filename)?.into()))
+        Ok((None, tensor_for_text_data(symbol_table, filename)?.into()))
     }
 }
 
@@ -222,9 +229,12 @@ pub fn for_npz(npz: &mut ndarray_npy::NpzReader<fs::File>, name: &str) -> CliRes
     bail!("Can not extract tensor from {}", name);
 }
 
-pub fn for_string(value: &str) -> CliResult<(Option<String>, InferenceFact)> {
+pub fn for_string(
+    symbol_table: &SymbolScope,
+    value: &str,
+) -> CliResult<(Option<String>, InferenceFact)> {
     if value.starts_with("@") {
-        for_data(&value[1..])
+        for_data(symbol_table, &value[1..])
     } else {
         let (name, value) = if value.contains(":") {
             let mut splits = value.split(":");
@@ -234,7 +244,7 @@ pub fn for_string(value: &str) -> CliResult<(Option<String>, InferenceFact)> {
         };
         if value.contains("=") {
             let mut split = value.split("=");
-            let spec = parse_spec(split.next().unwrap())?;
+            let spec = parse_spec(symbol_table, split.next().unwrap())?;
             let value = split.next().unwrap().split(",");
             let dt = spec
                 .datum_type
@@ -247,7 +257,7 @@ pub fn for_string(value: &str) -> CliResult<(Option<String>, InferenceFact)> {
             let tensor = dispatch_datum!(parse_values(dt)(&*shape, value.collect()))?;
             Ok((name, tensor.into()))
         } else {
-            Ok((name, parse_spec(value)?))
+            Ok((name, parse_spec(symbol_table, value)?))
         }
     }
 }
@@ -258,7 +268,7 @@ fn parse_dim_stream(s: &str) -> CliResult<TDim> {
     if s == "S" {
         Ok(stream_dim())
     } else if s.ends_with("S") {
-        let number: String = s.chars().take_while(|c| c.is_digit(10)).collect();
+        let number: String = s.chars().take_while(|c| c.is_ascii_digit()).collect();
         let number: i64 = number.parse::<i64>().map(|i| i.into())?;
         Ok(stream_dim() * number)
     } else {
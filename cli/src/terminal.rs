@@ -84,7 +84,7 @@ fn render_node_prefixed(
 ) -> CliResult<()> {
     let qid = NodeQId(scope.into(), node_id);
     let tags = annotations.tags.get(&qid).cloned().unwrap_or_default();
-    let name_color = tags.style.clone().unwrap_or(White.into());
+    let name_color = tags.style.unwrap_or(White.into());
     let node_name = model.node_name(node_id);
     let node_op_name = model.node_op(node_id).name();
     let profile_column_pad = format!("{:>1$}", "", options.profile as usize * 20);
@@ -127,7 +127,7 @@ fn render_node_prefixed(
                     let value = render_tdim(&c.1);
                     let value_visible_len = c.1.to_string().len();
                     let padding = 24usize.saturating_sub(value_visible_len + key.len());
-                    key + &*std::iter::repeat(' ').take(padding).join("") + &value + " "
+                    key + &*std::iter::repeat_n(' ', padding).join("") + &value + " "
                 })
                 .peekable(),
         )
@@ -229,16 +229,16 @@ fn render_node_prefixed(
             {
                 format!(
                     "{} {}",
-                    Cyan.bold().paint(format!("MODEL INPUT #{}", id)).to_string(),
-                    tags.model_input.as_ref().map(|s| &**s).unwrap_or("")
+                    Cyan.bold().paint(format!("MODEL INPUT #{}", id)),
+                    tags.model_input.as_deref().unwrap_or("")
                 )
             } else if let Some(id) =
                 model.output_outlets().iter().position(|n| n.node == node_id && n.slot == ix)
             {
                 format!(
                     "{} {}",
-                    Yellow.bold().paint(format!("MODEL OUTPUT #{}", id)).to_string(),
-                    tags.model_output.as_ref().map(|s| &**s).unwrap_or("")
+                    Yellow.bold().paint(format!("MODEL OUTPUT #{}", id)),
+                    tags.model_output.as_deref().unwrap_or("")
                 )
             } else {
                 "".to_string()
@@ -327,7 +327,7 @@ fn render_node_prefixed(
 
     while cost_column.as_mut().map(|cost| cost.peek().is_some()).unwrap_or(false) {
         prefix!();
-        println!("");
+        println!();
     }
     Ok(())
 }
@@ -445,6 +445,23 @@ pub fn dur_avg_ratio(measure: Duration, global: Duration) -> String {
     )
 }
 
+#[cfg(feature = "pulse")]
+pub fn render_latency(report: &tract_pulse::latency::LatencyReport) {
+    println!("{}", White.bold().paint("Latency"));
+    for node in &report.nodes {
+        println!(
+            " * {} {} delay: +{} (total delay: {}) buffer: {} bytes",
+            Blue.bold().paint(format!("{:5}", node.node)),
+            node.name,
+            node.added_delay,
+            node.delay,
+            render_tdim(&node.buffer_bytes)
+        );
+    }
+    println!(" * output delays: {} frames", report.output_delays.iter().join(", "));
+    println!(" * state buffers: {} bytes", render_tdim(&report.buffer_bytes));
+}
+
 fn render_tdim(d: &TDim) -> ANSIString<'static> {
     if let Ok(i) = d.to_i64() {
         render_big_integer(i)
@@ -0,0 +1,251 @@
+use crate::internal::*;
+use crate::model::translator::Translate;
+use crate::ops::binary::{MergeOpUnicast, TypedBinOp, UnaryOp};
+use crate::ops::cast::{cast, Cast};
+use crate::ops::cnn::{ConvUnary, DeconvUnary};
+use crate::ops::element_wise::ElementWiseOp;
+use crate::ops::konst::Const;
+use crate::ops::matmul::MatMulUnary;
+use crate::ops::nn::{LayerNorm, Reduce, Reducer, ScaledDotProductAttention};
+use crate::ops::source::TypedSource;
+use std::collections::HashSet;
+
+/// Translates a single precision model to half precision.
+///
+/// Sources, constants and the tensors embedded in operators are cast to f16. Operators
+/// accumulating over many values (sums, products, layer normalization and attention) are kept
+/// in f32, their inputs and outputs being cast around them. So are the element-wise operators
+/// computing the terms of a sum or product (the squares of a sum of squares, the exponentials
+/// of a softmax) and the ones consuming it (the division of a mean or a softmax), and the
+/// operators that can not run on f16 inputs.
+#[derive(Debug)]
+pub struct HalfTranslator {
+    /// Nodes kept in f32, by id.
+    keep_f32: HashSet<usize>,
+}
+
+impl HalfTranslator {
+    pub fn new(model: &TypedModel) -> TractResult<HalfTranslator> {
+        // successors come first in reverse evaluation order
+        let mut feeds_accumulator = vec![false; model.nodes().len()];
+        let mut keep_f32 = HashSet::new();
+        for &id in model.eval_order()?.iter().rev() {
+            let node = model.node(id);
+            let mut successors = node.outputs.iter().flat_map(|o| o.successors.iter()).peekable();
+            feeds_accumulator[id] = successors.peek().is_some()
+                && successors.all(|succ| {
+                    let succ = model.node(succ.node);
+                    Self::accumulates(succ)
+                        || (Self::is_element_wise(succ)
+                            && (feeds_accumulator[succ.id]
+                                || Self::fed_by_accumulator(model, succ)))
+                });
+            if Self::accumulates(node)
+                || node.op_is::<LayerNorm>()
+                || node.op_is::<ScaledDotProductAttention>()
+                || (Self::is_element_wise(node)
+                    && (feeds_accumulator[id] || Self::fed_by_accumulator(model, node)))
+            {
+                keep_f32.insert(id);
+            }
+        }
+        Ok(HalfTranslator { keep_f32 })
+    }
+
+    fn accumulates(node: &TypedNode) -> bool {
+        node.op_as::<Reduce>()
+            .map(|reduce| matches!(reduce.reducer, Reducer::Sum | Reducer::Prod))
+            .unwrap_or(false)
+    }
+
+    fn is_element_wise(node: &TypedNode) -> bool {
+        node.op_is::<ElementWiseOp>()
+            || node.op_is::<UnaryOp>()
+            || node.op_is::<TypedBinOp>()
+            || node.op_is::<MergeOpUnicast>()
+    }
+
+    fn fed_by_accumulator(model: &TypedModel, node: &TypedNode) -> bool {
+        node.inputs.iter().any(|i| Self::accumulates(model.node(i.node)))
+    }
+
+    fn translate_op(node: &TypedNode) -> TractResult<Box<dyn TypedOp>> {
+        let op: Box<dyn TypedOp> = if let Some(konst) = node.op_as::<Const>() {
+            Box::new(Const(half(&konst.0)?))
+        } else if let Some(op) = node.op_as::<UnaryOp>() {
+            Box::new(UnaryOp { mini_op: op.mini_op.clone(), a: half(&op.a)? })
+        } else if let Some(op) = node.op_as::<MatMulUnary>() {
+            Box::new(MatMulUnary { a: half(&op.a)?, ..op.clone() })
+        } else if let Some(op) = node.op_as::<ConvUnary>() {
+            Box::new(ConvUnary {
+                kernel: half(&op.kernel)?,
+                bias: op.bias.as_ref().map(half).transpose()?,
+                ..op.clone()
+            })
+        } else if let Some(op) = node.op_as::<DeconvUnary>() {
+            Box::new(DeconvUnary {
+                kernel: half(&op.kernel)?,
+                bias: op.bias.as_ref().map(half).transpose()?,
+                ..op.clone()
+            })
+        } else {
+            node.op.clone()
+        };
+        Ok(op)
+    }
+
+    fn wire_in_f32(
+        node: &TypedNode,
+        target: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let mut wires = tvec!();
+        for (ix, input) in inputs.iter().enumerate() {
+            if let Some(f32_wire) = Self::f32_before_cast(target, *input)? {
+                // chained with another operator kept in f32
+                wires.push(f32_wire);
+            } else if target.outlet_fact(*input)?.datum_type == f16::datum_type() {
+                let name = format!("{}.cast-{}", node.name, ix);
+                wires.push(target.wire_node(name, cast(f32::datum_type()), &[*input])?[0]);
+            } else {
+                wires.push(*input);
+            }
+        }
+        let outputs = target.wire_node(&node.name, node.op.clone(), &wires)?;
+        let mut wires = tvec!();
+        for (ix, output) in outputs.iter().enumerate() {
+            if target.outlet_fact(*output)?.datum_type == f32::datum_type() {
+                let name = format!("{}.cast-output-{}", node.name, ix);
+                wires.push(target.wire_node(name, cast(f16::datum_type()), &[*output])?[0]);
+            } else {
+                wires.push(*output);
+            }
+        }
+        Ok(wires)
+    }
+
+    fn f32_before_cast(target: &TypedModel, outlet: OutletId) -> TractResult<Option<OutletId>> {
+        let node = target.node(outlet.node);
+        let to_f16 = node
+            .op_as::<ElementWiseOp>()
+            .and_then(|op| op.0.downcast_ref::<Cast>())
+            .map(|cast| cast.to == f16::datum_type())
+            .unwrap_or(false);
+        if to_f16 && target.outlet_fact(node.inputs[0])?.datum_type == f32::datum_type() {
+            Ok(Some(node.inputs[0]))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+fn half(t: &Arc<Tensor>) -> TractResult<Arc<Tensor>> {
+    if t.datum_type() == f32::datum_type() {
+        Ok(t.cast_to::<f16>()?.into_owned().into_arc_tensor())
+    } else {
+        Ok(t.clone())
+    }
+}
+
+impl Translate<TypedFact, Box<dyn TypedOp>, TypedFact, Box<dyn TypedOp>> for HalfTranslator {
+    fn translate_node(
+        &self,
+        _source: &TypedModel,
+        node: &TypedNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let inputs = node.inputs.iter().map(|i| mapping[i]).collect::<TVec<_>>();
+        if let Some(source) = node.op_as::<TypedSource>() {
+            let fact = if source.fact.datum_type == f32::datum_type() {
+                TypedFact::dt_shape(f16::datum_type(), source.fact.shape.clone())
+            } else {
+                source.fact.clone()
+            };
+            return target.wire_node(&node.name, TypedSource::new(fact), &[]);
+        }
+        if self.keep_f32.contains(&node.id) {
+            return Self::wire_in_f32(node, target, &inputs);
+        }
+        let op = Self::translate_op(node)?;
+        let runs_in_f16 = {
+            let input_facts =
+                inputs.iter().map(|i| target.outlet_fact(*i)).collect::<TractResult<TVec<_>>>()?;
+            op.output_facts(&input_facts)
+                .map(|facts| facts.iter().all(|f| f.datum_type != f32::datum_type()))
+                .unwrap_or(false)
+        };
+        if runs_in_f16 {
+            target.wire_node(&node.name, op, &inputs)
+        } else {
+            Self::wire_in_f32(node, target, &inputs)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::math;
+    use crate::ops::nn;
+
+    #[test]
+    fn half_model_keeps_sums_in_f32() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", TypedFact::dt_shape(f32::datum_type(), &[2, 3]))?;
+        let y = model.wire_node("y", math::mul::unary(rctensor2(&[[2f32]])), &[x])?;
+        let sum = nn::Reduce::new(tvec!(1), nn::Reducer::Sum);
+        let z = model.wire_node("z", sum, &y)?;
+        model.set_output_outlets(&z)?;
+        let half = model.half()?;
+        assert_eq!(half.input_fact(0)?.datum_type, f16::datum_type());
+        assert_eq!(half.output_fact(0)?.datum_type, f16::datum_type());
+        let reduce = half.node(half.node_id_by_name("z")?);
+        assert_eq!(half.outlet_fact(reduce.inputs[0])?.datum_type, f32::datum_type());
+        let input = tensor2(&[[1f32, 2., 3.], [4., 5., 6.]]).cast_to::<f16>()?.into_owned();
+        let output = half.into_runnable()?.run(tvec!(input))?;
+        let expected = tensor2(&[[12f32], [30.]]).cast_to::<f16>()?.into_owned();
+        assert_eq!(*output[0], expected);
+        Ok(())
+    }
+
+    #[test]
+    fn half_model_keeps_mean_of_squares_in_f32() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", TypedFact::dt_shape(f32::datum_type(), &[1, 4]))?;
+        let sq = model.wire_node("sq", math::square(), &[x])?;
+        let sum = model.wire_node("sum", nn::Reduce::new(tvec!(1), nn::Reducer::Sum), &sq)?;
+        let mean = model.wire_node("mean", math::mul::unary(rctensor2(&[[0.25f32]])), &sum)?;
+        model.set_output_outlets(&mean)?;
+        let half = model.half()?;
+        for name in &["sq", "mean"] {
+            let node = half.node(half.node_id_by_name(name)?);
+            assert_eq!(half.outlet_fact(node.inputs[0])?.datum_type, f32::datum_type());
+        }
+        // the sum of the squares (160000) does not fit in f16, the mean does
+        let input = tensor2(&[[200f32; 4]]).cast_to::<f16>()?.into_owned();
+        let output = half.into_runnable()?.run(tvec!(input))?;
+        let expected = tensor2(&[[40000f32]]).cast_to::<f16>()?.into_owned();
+        assert_eq!(*output[0], expected);
+        Ok(())
+    }
+
+    #[test]
+    fn half_model_with_many_paths_to_a_sum() -> TractResult<()> {
+        // every diamond doubles the paths from x to the sum
+        let mut model = TypedModel::default();
+        let mut wire = model.add_source("x", TypedFact::dt_shape(f32::datum_type(), &[1, 4]))?;
+        for i in 0..64 {
+            let left = model.wire_node(format!("left-{}", i), math::square(), &[wire])?[0];
+            let right = model.wire_node(format!("right-{}", i), math::abs(), &[wire])?[0];
+            wire =
+                model.wire_node(format!("add-{}", i), math::add::bin_typed(), &[left, right])?[0];
+        }
+        let sum = model.wire_node("sum", nn::Reduce::new(tvec!(1), nn::Reducer::Sum), &[wire])?;
+        model.set_output_outlets(&sum)?;
+        let half = model.half()?;
+        let first = half.node(half.node_id_by_name("left-0")?);
+        assert_eq!(half.outlet_fact(first.inputs[0])?.datum_type, f32::datum_type());
+        Ok(())
+    }
+}
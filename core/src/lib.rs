@@ -69,11 +69,16 @@ pub extern crate tract_linalg;
 pub mod ops;
 
 pub mod broadcast;
+pub mod floats;
 pub mod framework;
 mod hash;
+pub mod memory_plan;
 pub mod model;
 pub mod optim;
+#[cfg(feature = "parallel-plan")]
+pub mod parallel_plan;
 pub mod plan;
+pub mod specializing_plan;
 
 pub use dyn_clone;
 
@@ -84,7 +89,10 @@ pub type TractResult<T> = Result<T, anyhow::Error>;
 pub mod prelude {
     pub use crate::framework::Framework;
     pub use crate::model::*;
-    pub use crate::plan::{SimplePlan, SimpleState};
+    #[cfg(feature = "parallel-plan")]
+    pub use crate::parallel_plan::{ParallelPlan, ParallelState};
+    pub use crate::plan::{SimplePlan, SimpleState, StateSnapshot};
+    pub use crate::specializing_plan::SpecializingPlan;
     pub use crate::{TractError, TractResult};
     pub use std::sync::Arc;
     pub use tract_data::prelude::*;
@@ -103,7 +111,9 @@ pub mod internal {
     pub use crate::ops::change_axes::*;
     pub use crate::ops::element_wise::ElementWiseMiniOp;
     pub use crate::ops::invariants::*;
-    pub use crate::ops::{AttrOrInput, AxisInfo, Cost, EvalOp, Invariants, Op, OpState, Validation};
+    pub use crate::ops::{
+        AttrOrInput, AxisInfo, Cost, EvalOp, Invariants, Op, OpState, Validation,
+    };
     pub use crate::plan::SessionState;
     pub use crate::prelude::*;
     pub use anyhow::{bail, format_err, Context as TractErrorContext};
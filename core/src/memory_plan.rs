@@ -0,0 +1,281 @@
+//! Static placement of node outputs in a single reusable arena.
+use std::fmt::{Debug, Display};
+use std::ops::Range;
+
+use crate::internal::*;
+use crate::model::{Fact, Graph, OutletId};
+use crate::ops::binary::MergeOpUnicast;
+use crate::ops::element_wise::ElementWiseOp;
+
+/// A buffer to place: a node output, possibly reused in place by its consumers.
+#[derive(Debug, Clone)]
+struct Buffer {
+    node: usize,
+    bytes: usize,
+    lifetime: Range<usize>,
+}
+
+/// Offsets of node outputs in one arena, computed from their lifetimes in the evaluation order
+/// and their concrete sizes.
+///
+/// Outlets that can not be planned (symbolic shapes, non-copy types, model outputs, outputs of
+/// stateful ops) stay on the heap.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryPlan {
+    /// Byte ranges in the arena.
+    pub regions: Vec<Range<usize>>,
+    /// For each node, the regions its outputs will be allocated in.
+    pub node_regions: Vec<TVec<usize>>,
+    /// For nodes computed in place, the input whose storage they take over.
+    pub in_place: Vec<Option<usize>>,
+    /// Arena size, in bytes.
+    pub size: usize,
+}
+
+impl MemoryPlan {
+    pub fn new<F, O>(
+        model: &Graph<F, O>,
+        order: &[usize],
+        outputs: &[OutletId],
+    ) -> TractResult<MemoryPlan>
+    where
+        F: Fact + Hash + Clone + 'static,
+        O: Debug + Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + 'static + Hash,
+    {
+        // node values are kept until their last consumer runs (see SimplePlan flush lists)
+        let mut last_use = vec![0; model.nodes().len()];
+        for (step, &node) in order.iter().enumerate() {
+            for input in &model.node(node).inputs {
+                last_use[input.node] = last_use[input.node].max(step);
+            }
+        }
+
+        let plannable = |node: usize| -> bool {
+            let node = model.node(node);
+            !node.inputs.is_empty()
+                && node.op().is_stateless()
+                && !outputs.iter().any(|o| o.node == node.id)
+        };
+
+        let mut buffers: Vec<Buffer> = vec![];
+        let mut buffer_of_node: Vec<Option<usize>> = vec![None; model.nodes().len()];
+        let mut in_place = vec![None; model.nodes().len()];
+        for (step, &id) in order.iter().enumerate() {
+            if !plannable(id) {
+                continue;
+            }
+            let node = model.node(id);
+            if let Some((input, buffer)) = Self::in_place_input(model, node, &buffer_of_node) {
+                in_place[id] = Some(input);
+                buffers[buffer].lifetime.end = last_use[id].max(step) + 1;
+                buffer_of_node[id] = Some(buffer);
+                continue;
+            }
+            for output in &node.outputs {
+                let fact = output.fact.to_typed_fact()?;
+                if let (Some(shape), true) = (fact.shape.as_concrete(), fact.datum_type.is_copy()) {
+                    let bytes = shape.iter().product::<usize>() * fact.datum_type.size_of();
+                    if bytes == 0 {
+                        continue;
+                    }
+                    if node.outputs.len() == 1 {
+                        buffer_of_node[id] = Some(buffers.len());
+                    }
+                    buffers.push(Buffer {
+                        node: id,
+                        bytes,
+                        lifetime: step..last_use[id].max(step) + 1,
+                    });
+                }
+            }
+        }
+
+        // greedy placement, largest buffers first, at the lowest offset clear of every placed
+        // buffer living at the same time
+        let mut by_size: Vec<usize> = (0..buffers.len()).collect();
+        by_size.sort_by_key(|&b| std::cmp::Reverse(buffers[b].bytes));
+        let mut offsets: Vec<Option<usize>> = vec![None; buffers.len()];
+        for &b in &by_size {
+            let buffer = &buffers[b];
+            let mut taken: Vec<Range<usize>> = by_size
+                .iter()
+                .filter_map(|&other| {
+                    let offset = offsets[other]?;
+                    let other = &buffers[other];
+                    if other.lifetime.start < buffer.lifetime.end
+                        && buffer.lifetime.start < other.lifetime.end
+                    {
+                        Some(offset..offset + other.bytes)
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+            taken.sort_by_key(|r| r.start);
+            let mut offset = 0;
+            for r in taken {
+                if offset + buffer.bytes <= r.start {
+                    break;
+                }
+                offset = offset.max(r.end.div_ceil(ARENA_ALIGNMENT) * ARENA_ALIGNMENT);
+            }
+            offsets[b] = Some(offset);
+        }
+
+        let mut node_regions = vec![tvec!(); model.nodes().len()];
+        let regions: Vec<Range<usize>> = buffers
+            .iter()
+            .zip(offsets.iter())
+            .map(|(b, o)| o.unwrap()..o.unwrap() + b.bytes)
+            .collect();
+        for (ix, b) in buffers.iter().enumerate() {
+            node_regions[b.node].push(ix);
+        }
+        let size = regions.iter().map(|r| r.end).max().unwrap_or(0);
+        Ok(MemoryPlan { regions, node_regions, in_place, size })
+    }
+
+    /// Element-wise ops, and unicast merges on their second operand, run in place when they
+    /// keep the type of that input and are its only consumer: they can take over its buffer.
+    ///
+    /// Element-wise ops declaring an output type (even the input one, like LookupTable) always
+    /// evaluate out of place.
+    fn in_place_input<F, O>(
+        model: &Graph<F, O>,
+        node: &Node<F, O>,
+        buffer_of_node: &[Option<usize>],
+    ) -> Option<(usize, usize)>
+    where
+        F: Fact + Hash + Clone + 'static,
+        O: Debug + Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + 'static + Hash,
+    {
+        let ix = if node.op_is::<ElementWiseOp>() {
+            0
+        } else if node.op_is::<MergeOpUnicast>() {
+            1
+        } else {
+            return None;
+        };
+        let input = node.inputs[ix];
+        let prec = model.node(input.node);
+        let input_fact = prec.outputs[input.slot].fact.to_typed_fact().ok()?;
+        let output_fact = node.outputs[0].fact.to_typed_fact().ok()?;
+        let evals_in_place = if let Some(op) = node.op_as::<ElementWiseOp>() {
+            op.0.output_type(input_fact.datum_type).is_none()
+        } else if let Some(op) = node.op_as::<MergeOpUnicast>() {
+            let a = model.outlet_fact(node.inputs[0]).ok()?.to_typed_fact().ok()?;
+            op.0.result_datum_type(a.datum_type, input_fact.datum_type).ok()?
+                == input_fact.datum_type
+        } else {
+            false
+        };
+        if !evals_in_place
+            || input_fact.datum_type != output_fact.datum_type
+            || input_fact.shape != output_fact.shape
+            || prec.outputs.len() != 1
+            || prec.outputs[0].successors.len() != 1
+        {
+            return None;
+        }
+        Some((ix, buffer_of_node[input.node]?))
+    }
+
+    /// Allocate an arena matching the plan.
+    pub fn arena(&self) -> TractResult<Arc<Arena>> {
+        Arena::new(self.regions.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::math;
+
+    fn model() -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let source = model.add_source("source", TypedFact::dt_shape(f32::datum_type(), &[64]))?;
+        let a = model.wire_node("a", math::add::unary(rctensor1(&[1f32; 64])), &[source])?;
+        let b = model.wire_node("b", math::abs(), &a)?;
+        let c = model.wire_node("c", math::mul::unary(rctensor1(&[2f32; 64])), &b)?;
+        let d = model.wire_node("d", math::mul::unary(rctensor1(&[3f32; 64])), &c)?;
+        let e = model.wire_node("e", math::add::unary(rctensor1(&[-1f32; 64])), &d)?;
+        model.set_output_outlets(&e)?;
+        Ok(model)
+    }
+
+    #[test]
+    fn reuse_and_in_place() -> TractResult<()> {
+        let model = model()?;
+        let plan = SimplePlan::new(&model)?;
+        let memory = MemoryPlan::new(&model, &plan.order, &plan.outputs)?;
+        let b = model.node_by_name("b")?.id;
+        assert_eq!(memory.in_place[b], Some(0));
+        assert!(memory.node_regions[b].is_empty());
+        // three 256 bytes buffers for a (and b), c and d, but d can reuse a
+        assert_eq!(memory.regions.len(), 3);
+        assert_eq!(memory.size, 512);
+        Ok(())
+    }
+
+    #[test]
+    fn run_with_arena() -> TractResult<()> {
+        let model = model()?;
+        let input = tensor1(&[-3f32; 64]);
+        let reference = SimplePlan::new(&model)?.run(tvec!(input.clone()))?;
+        let plan = SimplePlan::new(&model)?.with_memory_plan()?;
+        let mut state = SimpleState::new(&plan)?;
+        for _ in 0..3 {
+            assert_eq!(state.run(tvec!(input.clone()))?, reference);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn out_of_place_element_wise_ops() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let source = model.add_source("source", TypedFact::dt_shape(u8::datum_type(), &[64]))?;
+        let a = model.wire_node("a", math::add::unary(rctensor1(&[1u8; 64])), &[source])?;
+        let table: Vec<u8> = (0..=255u8).map(|x| x.wrapping_mul(3)).collect();
+        let lut = crate::ops::quant::lookup_table((tract_linalg::ops().lut_u8)(&table));
+        let b = model.wire_node("lut", lut, &a)?;
+        let c = model.wire_node("cast", crate::ops::cast::cast(u8::datum_type()), &b)?;
+        let d = model.wire_node("d", math::add::unary(rctensor1(&[2u8; 64])), &c)?;
+        model.set_output_outlets(&d)?;
+        let plan = SimplePlan::new(&model)?.with_memory_plan()?;
+        let memory = plan.memory_plan.as_ref().unwrap();
+        // both declare an output type, the input one, and evaluate out of place
+        assert_eq!(memory.in_place[model.node_by_name("lut")?.id], None);
+        assert_eq!(memory.in_place[model.node_by_name("cast")?.id], None);
+        let input = tensor1(&[5u8; 64]);
+        let reference = SimplePlan::new(&model)?.run(tvec!(input.clone()))?;
+        assert_eq!(reference[0], rctensor1(&[20u8; 64]));
+        assert_eq!(SimpleState::new(&plan)?.run(tvec!(input))?, reference);
+        Ok(())
+    }
+
+    #[test]
+    fn in_place_output_aliases_input() -> TractResult<()> {
+        let model = model()?;
+        let b = model.node_by_name("b")?.id;
+        let plan = SimplePlan::new(&model)?.with_memory_plan()?;
+        let mut state = SimpleState::new(&plan)?;
+        let mut buffers = None;
+        state.run_plan_with_eval(
+            tvec!(tensor1(&[-3f32; 64])),
+            |session, state, node, inputs| {
+                if node.id != b {
+                    return crate::plan::eval(session, state, node, inputs);
+                }
+                let input = unsafe { inputs[0].as_ptr_unchecked::<u8>() };
+                let in_arena = inputs[0].is_in_arena();
+                let outputs = crate::plan::eval(session, state, node, inputs)?;
+                buffers = Some((input, in_arena, unsafe { outputs[0].as_ptr_unchecked::<u8>() }));
+                Ok(outputs)
+            },
+        )?;
+        let (input, in_arena, output) = buffers.unwrap();
+        assert!(in_arena);
+        assert_eq!(input, output);
+        Ok(())
+    }
+}
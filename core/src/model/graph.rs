@@ -41,6 +41,9 @@ where
     /// model properties
     #[educe(Hash(method = "hash_properties"))]
     pub properties: HashMap<String, Arc<Tensor>>,
+    /// symbols of the model, by name
+    #[educe(Hash(ignore))]
+    pub symbol_table: SymbolScope,
 }
 
 fn hash_outlet_labels<H: std::hash::Hasher>(it: &HashMap<OutletId, String>, state: &mut H) {
@@ -73,6 +76,7 @@ where
             outputs: vec![],
             outlet_labels: HashMap::new(),
             properties: HashMap::new(),
+            symbol_table: SymbolScope::default(),
         }
     }
 }
@@ -389,11 +393,10 @@ where
     /// Get multiple mutable tensor information for outlets.
     pub fn outlets_fact_mut(&mut self, outlets: &[OutletId]) -> TractResult<TVec<&mut F>> {
         assert!(outlets.iter().tuple_combinations().all(|(a, b)| a != b));
+        assert!(outlets.iter().all(|o| o.node < self.nodes.len()));
+        let nodes = self.nodes.as_mut_ptr();
         Ok(unsafe {
-            outlets
-                .iter()
-                .map(|o| &mut *(&self.nodes[o.node].outputs[o.slot].fact as *const F as *mut F))
-                .collect()
+            outlets.iter().map(|o| &mut (&mut *nodes.add(o.node)).outputs[o.slot].fact).collect()
         })
     }
 
@@ -434,7 +437,7 @@ where
 
     /// Find outlet by label.
     pub fn find_outlet_label(&self, label: &str) -> Option<OutletId> {
-        self.outlet_labels.iter().find(|(_k, v)| &**v == label).map(|(k, _v)| *k)
+        self.outlet_labels.iter().find(|(_k, v)| **v == label).map(|(k, _v)| *k)
     }
 
     // misc
@@ -566,12 +569,12 @@ where
     fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
         for i in 0..self.nodes.len() {
             let input_1 =
-                self.nodes[i].inputs.get(0).map(|o| format!("{:?}", o)).unwrap_or("".to_string());
+                self.nodes[i].inputs.first().map(|o| format!("{:?}", o)).unwrap_or("".to_string());
             let input_2 =
                 self.nodes[i].inputs.get(1).map(|o| format!("{:?}", o)).unwrap_or("".to_string());
             let output_1 = self
                 .outlet_successors(OutletId::new(i, 0))
-                .get(0)
+                .first()
                 .map(|o| format!("{:?}", o))
                 .unwrap_or("".to_string());
             let output_2 = self
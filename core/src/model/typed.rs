@@ -50,7 +50,7 @@ impl SpecialOps<TypedFact, Box<dyn TypedOp>> for TypedModel {
                     input_facts.iter().map(|f| f.konst.clone().unwrap()).collect::<TVec<_>>();
                 let outputs =
                     op.eval(tensors).with_context(|| format!("Eager eval of {} {:?}", name, op))?;
-                outputs.into_iter().map(|t| TypedFact::from(t)).collect()
+                outputs.into_iter().map(TypedFact::from).collect()
             } else {
                 op.output_facts(&*input_facts)
                     .with_context(|| format!("wiring {} ({:?})", name, op))?
@@ -65,6 +65,20 @@ impl SpecialOps<TypedFact, Box<dyn TypedOp>> for TypedModel {
     }
 }
 
+impl crate::model::translator::Translate<TypedFact, Box<dyn TypedOp>, TypedFact, Box<dyn TypedOp>>
+    for SymbolValues
+{
+    fn translate_node(
+        &self,
+        source: &TypedModel,
+        node: &TypedNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        node.op.concretize_dims(source, node, target, mapping, self)
+    }
+}
+
 impl TypedModel {
     pub fn signature(&self) -> u64 {
         use std::hash::Hasher;
@@ -123,20 +137,15 @@ impl TypedModel {
 
     pub fn concretize_dims(&self, values: &SymbolValues) -> TractResult<TypedModel> {
         use crate::model::translator::Translate;
-        impl Translate<TypedFact, Box<dyn TypedOp>, TypedFact, Box<dyn TypedOp>> for SymbolValues {
-            fn translate_node(
-                &self,
-                source: &TypedModel,
-                node: &TypedNode,
-                target: &mut TypedModel,
-                mapping: &HashMap<OutletId, OutletId>,
-            ) -> TractResult<TVec<OutletId>> {
-                node.op.concretize_dims(source, node, target, mapping, self)
-            }
-        }
         values.translate_model(&self)
     }
 
+    /// Translate a f32 model to f16, keeping sensitive reductions in f32.
+    pub fn half(&self) -> TractResult<TypedModel> {
+        use crate::model::translator::Translate;
+        crate::floats::HalfTranslator::new(self)?.translate_model(self)
+    }
+
     /// Translate the graph to locally optimized operators (LIR or MIR ops).
     pub fn optimize(self) -> TractResult<TypedModel> {
         crate::optim::Optimizer::codegen().optimize(&self)
@@ -25,7 +25,7 @@ impl Op for MultiBroadcastTo {
 
 impl EvalOp for MultiBroadcastTo {
     fn is_stateless(&self) -> bool {
-        true
+        self.shape.iter().all(|d| d.to_usize().is_ok())
     }
 
     fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
@@ -34,6 +34,43 @@ impl EvalOp for MultiBroadcastTo {
             self.shape.iter().map(|d| Ok(d.to_usize()?)).collect::<TractResult<_>>()?;
         dispatch_datum!(Self::eval_t(input.datum_type())(&*input, &*dims))
     }
+
+    fn state(
+        &self,
+        _session: &mut SessionState,
+        _node_id: usize,
+    ) -> TractResult<Option<Box<dyn OpState>>> {
+        Ok(Some(Box::new(MultiBroadcastToState)))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct MultiBroadcastToState;
+
+impl OpState for MultiBroadcastToState {
+    fn eval(
+        &mut self,
+        session: &mut SessionState,
+        op: &dyn Op,
+        mut inputs: TVec<Arc<Tensor>>,
+    ) -> TractResult<TVec<Arc<Tensor>>> {
+        let op = op.downcast_ref::<MultiBroadcastTo>().unwrap();
+        let input = args_1!(inputs);
+        let dims: Vec<usize> = op
+            .shape
+            .iter()
+            .map(|d| Ok(d.eval(&session.resolved_symbols).to_usize()?))
+            .collect::<TractResult<_>>()?;
+        dispatch_datum!(MultiBroadcastTo::eval_t(input.datum_type())(&*input, &*dims))
+    }
+
+    fn snapshot(&self) -> TractResult<TVec<Tensor>> {
+        Ok(tvec!())
+    }
+
+    fn restore(&mut self, _tensors: TVec<Tensor>) -> TractResult<()> {
+        Ok(())
+    }
 }
 
 impl TypedOp for MultiBroadcastTo {
@@ -56,3 +93,24 @@ impl TypedOp for MultiBroadcastTo {
 
     as_op!();
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::math;
+
+    #[test]
+    fn broadcast_to_symbolic_shape() -> TractResult<()> {
+        let s = Symbol::new('S');
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", TypedFact::dt_shape(f32::datum_type(), &[s.to_dim()]))?;
+        let y = model.add_source("y", TypedFact::dt_shape(f32::datum_type(), &[1]))?;
+        let y = model.wire_node("broadcast", MultiBroadcastTo::new(tvec!(s.to_dim())), &[y])?;
+        let sum = model.wire_node("sum", math::add::bin_typed(), &[x, y[0]])?;
+        model.set_output_outlets(&sum)?;
+        let plan = SimplePlan::new(model)?;
+        let output = plan.run(tvec!(tensor1(&[1f32, 2., 3.]), tensor1(&[1f32])))?;
+        assert_eq!(*output[0], tensor1(&[2f32, 3., 4.]));
+        Ok(())
+    }
+}
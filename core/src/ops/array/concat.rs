@@ -32,7 +32,7 @@ impl_dyn_hash!(TypedConcat);
 
 impl TypedConcat {
     pub fn concat_vars(axis: usize, n: usize) -> TypedConcat {
-        TypedConcat { axis, slices: std::iter::repeat(ConcatSlice::Var).take(n).collect() }
+        TypedConcat { axis, slices: std::iter::repeat_n(ConcatSlice::Var, n).collect() }
     }
 
     pub fn offsets(&self, inputs: &[&TypedFact]) -> TractResult<Vec<TDim>> {
@@ -67,7 +67,7 @@ impl TypedOp for TypedConcat {
 
     fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
         let mut fact = inputs
-            .get(0)
+            .first()
             .cloned()
             .cloned()
             .or_else(|| {
@@ -0,0 +1,116 @@
+use crate::internal::*;
+use ndarray::*;
+
+/// Gather individual elements along an axis: output has the shape of
+/// indices, and each index replaces its own coordinate on `axis`.
+#[derive(Debug, Clone, new, Hash)]
+pub struct GatherElements {
+    pub axis: usize,
+}
+impl_dyn_hash!(GatherElements);
+
+impl Op for GatherElements {
+    fn name(&self) -> Cow<str> {
+        "GatherElements".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("axis: {}", self.axis)])
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+impl GatherElements {
+    unsafe fn eval_t<T: Datum>(
+        &self,
+        data: Arc<Tensor>,
+        indices: &ArrayViewD<i64>,
+    ) -> TractResult<Arc<Tensor>> {
+        let data_view = data.to_array_view_unchecked::<T>();
+        if indices.ndim() != data_view.ndim()
+            || (0..indices.ndim())
+                .any(|ax| ax != self.axis && indices.shape()[ax] > data_view.shape()[ax])
+        {
+            bail!(
+                "GatherElements indices of shape {:?} do not fit data of shape {:?}",
+                indices.shape(),
+                data_view.shape()
+            )
+        }
+        let axis_len = data_view.shape()[self.axis];
+        let resolved = indices
+            .iter()
+            .map(|&ix| super::resolve_index(ix, axis_len))
+            .collect::<TractResult<Vec<_>>>()?;
+        let resolved = ArrayD::from_shape_vec(indices.raw_dim(), resolved)?;
+        let output = ArrayD::<T>::from_shape_fn(indices.shape(), |mut coords| {
+            coords[self.axis] = resolved[&coords];
+            data_view[coords].clone()
+        });
+        let mut tensor = output.into_tensor();
+        tensor.set_datum_type(data.datum_type());
+        Ok(tensor.into_arc_tensor())
+    }
+}
+
+impl TypedOp for GatherElements {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        if inputs[0].rank() != inputs[1].rank() {
+            bail!("GatherElements data and indices must have the same rank, got {:?}", inputs)
+        }
+        Ok(tvec!(TypedFact::dt_shape(inputs[0].datum_type, &*inputs[1].shape.to_tvec())))
+    }
+}
+
+impl EvalOp for GatherElements {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let (data, indices) = args_2!(inputs);
+        let indices = indices.cast_to::<i64>()?;
+        let indices = indices.to_array_view::<i64>()?;
+        unsafe {
+            Ok(tvec!(dispatch_datum!(Self::eval_t(data.datum_type())(self, data, &indices))?))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gather_elements_axis_1() {
+        let op = GatherElements::new(1);
+        let output =
+            op.eval(tvec!(rctensor2(&[[1, 2], [3, 4]]), rctensor2(&[[0i64, 0], [1, 0]]))).unwrap();
+        assert_eq!(output[0], rctensor2(&[[1, 1], [4, 3]]));
+    }
+
+    #[test]
+    fn gather_elements_negative_indices() {
+        let op = GatherElements::new(0);
+        let output = op
+            .eval(tvec!(
+                rctensor2(&[[1, 2, 3], [4, 5, 6], [7, 8, 9]]),
+                rctensor2(&[[-1i64, -2, 0], [2, 0, 0]])
+            ))
+            .unwrap();
+        assert_eq!(output[0], rctensor2(&[[7, 5, 3], [7, 2, 3]]));
+    }
+
+    #[test]
+    fn gather_elements_invalid_indices() {
+        let op = GatherElements::new(0);
+        let data = rctensor2(&[[1, 2], [3, 4]]);
+        assert!(op.eval(tvec!(data.clone(), rctensor2(&[[2i64, 0]]))).is_err());
+        assert!(op.eval(tvec!(data.clone(), rctensor2(&[[-3i64, 0]]))).is_err());
+        assert!(op.eval(tvec!(data, rctensor2(&[[0i64, 0, 0]]))).is_err());
+    }
+}
@@ -1,27 +1,47 @@
-use tract_hir::internal::*;
-use tract_ndarray::prelude::*;
-
-use crate::model::ParsingContext;
-use crate::tfpb::tensorflow::NodeDef;
+use crate::internal::*;
+use ndarray::*;
 
+/// Gather slices of data, addressed by the last axis of indices.
+///
+/// The first `batch_dims` axes of data and indices are batch axes and must
+/// match.
 #[derive(Debug, Clone, new, Hash)]
-pub struct GatherNd {}
-
-impl_dyn_hash!(GatherNd);
-
-pub fn gather_nd(_ctx: &ParsingContext, _pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
-    Ok(Box::new(GatherNd::new()))
+pub struct GatherNd {
+    pub batch_dims: usize,
 }
+impl_dyn_hash!(GatherNd);
 
 impl GatherNd {
-    fn compute_shape<D: DimLike>(
+    pub fn compute_shape<D: DimLike>(
         &self,
         data_shape: &[D],
         indices_shape: &[D],
     ) -> TractResult<TVec<D>> {
         let mut shape: TVec<D> = indices_shape.into();
-        let n = shape.pop().unwrap().to_usize()?;
-        shape.extend(data_shape[n..].iter().cloned());
+        let n = shape.pop().context("GatherNd indices can not be a scalar")?.to_usize()?;
+        if self.batch_dims + n > data_shape.len() {
+            bail!(
+                "GatherNd indices address {} axes after {} batch axes, data has rank {}",
+                n,
+                self.batch_dims,
+                data_shape.len()
+            );
+        }
+        if self.batch_dims > shape.len() {
+            bail!(
+                "GatherNd has {} batch axes, indices have rank {}",
+                self.batch_dims,
+                indices_shape.len()
+            );
+        }
+        for (axis, (d, i)) in data_shape.iter().zip(indices_shape).take(self.batch_dims).enumerate()
+        {
+            // symbolic dims may only be known to match at runtime
+            if d != i && d.to_usize().is_ok() && i.to_usize().is_ok() {
+                bail!("GatherNd batch axis {} is {} in data and {} in indices", axis, d, i);
+            }
+        }
+        shape.extend(data_shape[self.batch_dims + n..].iter().cloned());
         Ok(shape)
     }
 
@@ -29,22 +49,28 @@ impl GatherNd {
         &self,
         output: &mut Tensor,
         data: &Tensor,
-        indices: &ArrayViewD<i32>,
-    ) {
+        indices: &ArrayViewD<i64>,
+    ) -> TractResult<()> {
         let data = data.to_array_view_unchecked::<T>();
-        for prefix in tract_ndarray::indices(&indices.shape()[0..indices.ndim() - 1]) {
-            let mut dst = output.to_array_view_mut_unchecked();
+        let mut output = output.to_array_view_mut_unchecked::<T>();
+        for prefix in ndarray::indices(&indices.shape()[0..indices.ndim() - 1]) {
+            let mut dst = output.view_mut();
             let mut coords = indices.view();
             for &x in prefix.slice().iter() {
                 dst.index_axis_inplace(Axis(0), x);
                 coords.index_axis_inplace(Axis(0), x);
             }
             let mut src = data.view();
+            for &x in prefix.slice()[..self.batch_dims].iter() {
+                src.index_axis_inplace(Axis(0), x);
+            }
             for &x in coords.iter() {
-                src.index_axis_inplace(Axis(0), x as _);
+                let x = super::resolve_index(x, src.shape()[0])?;
+                src.index_axis_inplace(Axis(0), x);
             }
             dst.assign(&src);
         }
+        Ok(())
     }
 }
 
@@ -53,7 +79,11 @@ impl Op for GatherNd {
         "GatherNd".into()
     }
 
-    op_tf!();
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("batch_dims: {}", self.batch_dims)])
+    }
+
+    op_core_mir!();
     op_as_typed_op!();
 }
 
@@ -65,8 +95,8 @@ impl EvalOp for GatherNd {
     fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
         let (data, indices) = args_2!(inputs);
         let shape = self.compute_shape(&data.shape(), &indices.shape())?;
-        let indices = indices.cast_to::<i32>()?;
-        let indices = indices.to_array_view::<i32>()?;
+        let indices = indices.cast_to::<i64>()?;
+        let indices = indices.to_array_view::<i64>()?;
         unsafe {
             let mut output = Tensor::uninitialized_dt(data.datum_type(), &*shape)?;
             dispatch_datum_by_size!(Self::eval_t(data.datum_type())(
@@ -74,46 +104,12 @@ impl EvalOp for GatherNd {
                 &mut output,
                 &data,
                 &indices
-            ));
+            ))?;
             Ok(tvec!(output.into_arc_tensor()))
         }
     }
 }
 
-impl InferenceRulesOp for GatherNd {
-    fn rules<'r, 'p: 'r, 's: 'r>(
-        &'s self,
-        s: &mut Solver<'r>,
-        inputs: &'p [TensorProxy],
-        outputs: &'p [TensorProxy],
-    ) -> InferenceResult {
-        check_input_arity(&inputs, 2)?;
-        check_output_arity(&outputs, 1)?;
-        s.equals(&outputs[0].datum_type, &inputs[0].datum_type)?;
-        s.given(&inputs[1].rank, move |s, indices_rank| {
-            let indices_rank = indices_rank as usize;
-            for i in 0..(indices_rank - 1) {
-                s.equals(&outputs[0].shape[i], &inputs[1].shape[i])?;
-            }
-            s.given_2(
-                &inputs[1].shape[indices_rank - 1],
-                &inputs[1].rank,
-                move |s, n, input_rank| {
-                    if let Ok(n) = n.to_i64() {
-                        for i in 0..(input_rank - n) as usize {
-                            s.equals(&outputs[0].shape[indices_rank - 1 + i], &inputs[1].shape[i])?;
-                        }
-                    }
-                    Ok(())
-                },
-            )
-        })
-    }
-
-    as_op!();
-    to_typed!();
-}
-
 impl TypedOp for GatherNd {
     as_op!();
 
@@ -127,29 +123,33 @@ impl TypedOp for GatherNd {
         model: &TypedModel,
         node: &TypedNode,
     ) -> TractResult<Option<TypedModelPatch>> {
+        if self.batch_dims != 0 {
+            return Ok(None);
+        }
         if let Some(indices) = &model.outlet_fact(node.inputs[1])?.konst {
-            if indices.rank() == 2 && indices.shape()[0] == 1 {
+            let indices = indices.cast_to::<i64>()?;
+            if indices.rank() == 2
+                && indices.shape()[0] == 1
+                && indices.as_slice::<i64>()?.iter().all(|&i| i >= 0)
+            {
                 let mut patch = TypedModelPatch::default();
                 let mut wire = patch.tap_model(model, node.inputs[0])?;
-                for (axis, &i) in indices.cast_to::<i32>()?.as_slice::<i32>()?.iter().enumerate() {
+                for (axis, &i) in indices.as_slice::<i64>()?.iter().enumerate() {
                     wire = patch.wire_node(
                         format!("{}-slice-axis-{}", node.name, axis),
-                        tract_hir::ops::array::Slice::new(axis, i as usize, (i + 1) as usize),
+                        crate::ops::array::Slice::new(axis, i as usize, (i + 1) as usize),
                         &[wire],
                     )?[0];
                 }
                 for i in (0..indices.shape()[1]).rev() {
                     wire = patch.wire_node(
                         format!("{}-remove_axis_{}", node.name, i),
-                        tract_hir::tract_core::ops::change_axes::AxisOp::Rm(i),
+                        AxisOp::Rm(i),
                         &[wire],
                     )?[0];
                 }
-                wire = patch.wire_node(
-                    format!("{}-add_axis", node.name),
-                    tract_hir::tract_core::ops::change_axes::AxisOp::Add(0),
-                    &[wire],
-                )?[0];
+                wire =
+                    patch.wire_node(format!("{}-add_axis", node.name), AxisOp::Add(0), &[wire])?[0];
                 patch.shunt_outside(model, node.id.into(), wire)?;
                 return Ok(Some(patch));
             }
@@ -165,7 +165,7 @@ mod tests {
     // https://www.tensorflow.org/api_docs/python/tf/gather_nd
     #[test]
     fn simple_indexing() {
-        let g = GatherNd::new();
+        let g = GatherNd::new(0);
         assert_eq!(
             g.eval(tvec!(rctensor2(&[[1, 2], [3, 4]]), rctensor2(&[[0, 0], [1, 1]]))).unwrap(),
             tvec!(rctensor1(&[1, 4]))
@@ -174,7 +174,7 @@ mod tests {
 
     #[test]
     fn slice_indexing() {
-        let g = GatherNd::new();
+        let g = GatherNd::new(0);
         assert_eq!(
             g.eval(tvec!(rctensor2(&[[1, 2], [3, 4]]), rctensor2(&[[1], [0]]))).unwrap(),
             tvec!(rctensor2(&[[3, 4], [1, 2]]))
@@ -183,7 +183,7 @@ mod tests {
 
     #[test]
     fn tensor_3d_1() {
-        let g = GatherNd::new();
+        let g = GatherNd::new(0);
         let t = rctensor3(&[[[10, 20], [30, 40]], [[11, 21], [31, 41]]]);
         assert_eq!(
             g.eval(tvec!(t.clone(), rctensor2(&[[1]]))).unwrap(),
@@ -193,7 +193,7 @@ mod tests {
 
     #[test]
     fn tensor_3d_2() {
-        let g = GatherNd::new();
+        let g = GatherNd::new(0);
         let t = rctensor3(&[[[10, 20], [30, 40]], [[11, 21], [31, 41]]]);
         assert_eq!(
             g.eval(tvec!(t.clone(), rctensor2(&[[0, 1], [1, 0]]))).unwrap(),
@@ -203,11 +203,37 @@ mod tests {
 
     #[test]
     fn tensor_3d_3() {
-        let g = GatherNd::new();
+        let g = GatherNd::new(0);
         let t = rctensor3(&[[[10, 20], [30, 40]], [[11, 21], [31, 41]]]);
         assert_eq!(
             g.eval(tvec!(t.clone(), rctensor2(&[[0, 0, 1], [1, 0, 1]]))).unwrap(),
             tvec!(rctensor1(&[20, 21]))
         );
     }
+
+    // onnx GatherND example 5
+    #[test]
+    fn batch_dims_1() {
+        let g = GatherNd::new(1);
+        let t = rctensor3(&[[[0, 1], [2, 3]], [[4, 5], [6, 7]]]);
+        assert_eq!(
+            g.eval(tvec!(t, rctensor2(&[[1], [0]]))).unwrap(),
+            tvec!(rctensor2(&[[2, 3], [4, 5]]))
+        );
+    }
+
+    #[test]
+    fn invalid_indices() {
+        let g = GatherNd::new(0);
+        let t = rctensor2(&[[1, 2], [3, 4]]);
+        assert!(g.eval(tvec!(t.clone(), rctensor2(&[[0, 2]]))).is_err());
+        assert!(g.eval(tvec!(t, rctensor2(&[[-3, 0]]))).is_err());
+    }
+
+    #[test]
+    fn mismatched_batch_dims() {
+        let g = GatherNd::new(1);
+        let t = rctensor3(&[[[0, 1], [2, 3]], [[4, 5], [6, 7]]]);
+        assert!(g.eval(tvec!(t, rctensor2(&[[1], [0], [1]]))).is_err());
+    }
 }
@@ -1,20 +1,41 @@
 /// # Operators on array and shapes
+use crate::internal::*;
+
 mod broadcast;
 pub(crate) mod concat;
 mod constant_of_shape;
 mod gather;
+mod gather_elements;
+mod gather_nd;
 mod one_hot;
 mod pad;
 mod reshape;
+mod scatter_elements;
+mod scatter_nd;
 mod slice;
+mod sort;
 mod tile;
 
 pub use self::broadcast::MultiBroadcastTo;
 pub use self::concat::{ConcatSlice, TypedConcat};
 pub use self::constant_of_shape::ConstantOfShape;
 pub use self::gather::Gather;
+pub use self::gather_elements::GatherElements;
+pub use self::gather_nd::GatherNd;
 pub use self::one_hot::OneHot;
 pub use self::pad::{Pad, PadMode};
 pub use self::reshape::FiniteReshape;
+pub use self::scatter_elements::ScatterElements;
+pub use self::scatter_nd::ScatterNd;
 pub use self::slice::Slice;
+pub use self::sort::{ArgSort, Sort, TopK};
 pub use self::tile::Tile;
+
+/// Checks an index on an axis of length `len`, negative indices counting from the end.
+fn resolve_index(index: i64, len: usize) -> TractResult<usize> {
+    let resolved = if index < 0 { index + len as i64 } else { index };
+    if resolved < 0 || resolved >= len as i64 {
+        bail!("Index {} out of range for an axis of length {}", index, len)
+    }
+    Ok(resolved as usize)
+}
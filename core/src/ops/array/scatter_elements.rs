@@ -0,0 +1,118 @@
+use crate::internal::*;
+use ndarray::*;
+
+/// Copy data, replacing individual elements with updates: each index
+/// replaces its own coordinate on `axis`.
+#[derive(Debug, Clone, new, Hash)]
+pub struct ScatterElements {
+    pub axis: usize,
+}
+impl_dyn_hash!(ScatterElements);
+
+impl Op for ScatterElements {
+    fn name(&self) -> Cow<str> {
+        "ScatterElements".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("axis: {}", self.axis)])
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+impl ScatterElements {
+    unsafe fn eval_t<T: Datum>(
+        &self,
+        data: &mut Tensor,
+        indices: &ArrayViewD<i64>,
+        updates: &Tensor,
+    ) -> TractResult<()> {
+        let mut data = data.to_array_view_mut_unchecked::<T>();
+        let updates = updates.to_array_view_unchecked::<T>();
+        if indices.shape() != updates.shape()
+            || indices.ndim() != data.ndim()
+            || (0..indices.ndim())
+                .any(|ax| ax != self.axis && indices.shape()[ax] > data.shape()[ax])
+        {
+            bail!(
+                "ScatterElements indices and updates of shapes {:?} and {:?} do not fit data of shape {:?}",
+                indices.shape(),
+                updates.shape(),
+                data.shape()
+            )
+        }
+        let axis_len = data.shape()[self.axis];
+        for (mut coords, index) in indices.indexed_iter() {
+            let value = updates[&coords].clone();
+            coords[self.axis] = super::resolve_index(*index, axis_len)?;
+            data[coords] = value;
+        }
+        Ok(())
+    }
+}
+
+impl TypedOp for ScatterElements {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        if inputs[0].datum_type != inputs[2].datum_type {
+            bail!("ScatterElements data and updates must have the same type, got {:?}", inputs)
+        }
+        if inputs[0].rank() != inputs[1].rank() || inputs[1].shape != inputs[2].shape {
+            bail!(
+                "ScatterElements indices and updates must have the same shape, and the rank of data. Got {:?}",
+                inputs
+            )
+        }
+        Ok(tvec!(TypedFact::dt_shape(inputs[0].datum_type, &*inputs[0].shape.to_tvec())))
+    }
+}
+
+impl EvalOp for ScatterElements {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let (data, indices, updates) = args_3!(inputs);
+        let indices = indices.cast_to::<i64>()?;
+        let indices = indices.to_array_view::<i64>()?;
+        let mut data = data.into_tensor();
+        unsafe {
+            dispatch_datum!(Self::eval_t(data.datum_type())(self, &mut data, &indices, &updates))?;
+        }
+        Ok(tvec!(data.into_arc_tensor()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // onnx ScatterElements example 2
+    #[test]
+    fn scatter_elements_axis_1() {
+        let op = ScatterElements::new(1);
+        let output = op
+            .eval(tvec!(
+                rctensor2(&[[1f32, 2., 3., 4., 5.]]),
+                rctensor2(&[[1i64, 3]]),
+                rctensor2(&[[1.1f32, 2.1]])
+            ))
+            .unwrap();
+        assert_eq!(output[0], rctensor2(&[[1f32, 1.1, 3., 2.1, 5.]]));
+    }
+
+    #[test]
+    fn scatter_elements_invalid_indices() {
+        let op = ScatterElements::new(1);
+        let data = rctensor2(&[[1f32, 2., 3.]]);
+        assert!(op.eval(tvec!(data.clone(), rctensor2(&[[3i64]]), rctensor2(&[[0f32]]))).is_err());
+        assert!(op.eval(tvec!(data.clone(), rctensor2(&[[-4i64]]), rctensor2(&[[0f32]]))).is_err());
+        assert!(op
+            .eval(tvec!(data, rctensor2(&[[0i64, 1]]), rctensor2(&[[0f32, 1.], [2., 3.]])))
+            .is_err());
+    }
+}
@@ -0,0 +1,139 @@
+use crate::internal::*;
+use ndarray::*;
+
+/// Copy data, replacing the slices addressed by the last axis of indices
+/// with updates.
+#[derive(Debug, Clone, Hash)]
+pub struct ScatterNd;
+impl_dyn_hash!(ScatterNd);
+
+impl Op for ScatterNd {
+    fn name(&self) -> Cow<str> {
+        "ScatterNd".into()
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+impl ScatterNd {
+    unsafe fn eval_t<T: Datum>(
+        &self,
+        data: &mut Tensor,
+        indices: &ArrayViewD<i64>,
+        updates: &Tensor,
+    ) -> TractResult<()> {
+        let mut data = data.to_array_view_mut_unchecked::<T>();
+        let updates = updates.to_array_view_unchecked::<T>();
+        let depth =
+            indices.shape().last().copied().context("ScatterNd indices can not be a scalar")?;
+        let mut expected: TVec<usize> = indices.shape()[..indices.ndim() - 1].into();
+        if depth > data.ndim() {
+            bail!("ScatterNd indices address {} axes, data has rank {}", depth, data.ndim())
+        }
+        expected.extend(data.shape()[depth..].iter().copied());
+        if updates.shape() != &*expected {
+            bail!(
+                "ScatterNd updates must have shape {:?} for indices of shape {:?} and data of shape {:?}, got {:?}",
+                expected,
+                indices.shape(),
+                data.shape(),
+                updates.shape()
+            )
+        }
+        for prefix in ndarray::indices(&indices.shape()[0..indices.ndim() - 1]) {
+            let mut src = updates.view();
+            let mut coords = indices.view();
+            for &x in prefix.slice().iter() {
+                src.index_axis_inplace(Axis(0), x);
+                coords.index_axis_inplace(Axis(0), x);
+            }
+            let mut dst = data.view_mut();
+            for &x in coords.iter() {
+                let x = super::resolve_index(x, dst.shape()[0])?;
+                dst.index_axis_inplace(Axis(0), x);
+            }
+            dst.assign(&src);
+        }
+        Ok(())
+    }
+}
+
+impl TypedOp for ScatterNd {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        if inputs[0].datum_type != inputs[2].datum_type {
+            bail!("ScatterNd data and updates must have the same type, got {:?}", inputs)
+        }
+        Ok(tvec!(TypedFact::dt_shape(inputs[0].datum_type, &*inputs[0].shape.to_tvec())))
+    }
+}
+
+impl EvalOp for ScatterNd {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let (data, indices, updates) = args_3!(inputs);
+        let indices = indices.cast_to::<i64>()?;
+        let indices = indices.to_array_view::<i64>()?;
+        let mut data = data.into_tensor();
+        unsafe {
+            dispatch_datum_by_size!(Self::eval_t(data.datum_type())(
+                self, &mut data, &indices, &updates
+            ))?;
+        }
+        Ok(tvec!(data.into_arc_tensor()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // onnx ScatterND example 1
+    #[test]
+    fn scatter_elements_in_vector() {
+        let output = ScatterNd
+            .eval(tvec!(
+                rctensor1(&[1, 2, 3, 4, 5, 6, 7, 8]),
+                rctensor2(&[[4i64], [3], [1], [7]]),
+                rctensor1(&[9, 10, 11, 12])
+            ))
+            .unwrap();
+        assert_eq!(output[0], rctensor1(&[1, 11, 3, 10, 9, 6, 7, 12]));
+    }
+
+    #[test]
+    fn scatter_slices() {
+        let output = ScatterNd
+            .eval(tvec!(
+                rctensor2(&[[1, 2], [3, 4], [5, 6]]),
+                rctensor2(&[[2i64], [0]]),
+                rctensor2(&[[10, 20], [30, 40]])
+            ))
+            .unwrap();
+        assert_eq!(output[0], rctensor2(&[[30, 40], [3, 4], [10, 20]]));
+    }
+
+    #[test]
+    fn scatter_invalid_indices() {
+        let data = rctensor1(&[1, 2, 3, 4]);
+        assert!(ScatterNd
+            .eval(tvec!(data.clone(), rctensor2(&[[4i64]]), rctensor1(&[9])))
+            .is_err());
+        assert!(ScatterNd.eval(tvec!(data, rctensor2(&[[-5i64]]), rctensor1(&[9]))).is_err());
+    }
+
+    #[test]
+    fn scatter_invalid_updates_shape() {
+        let output = ScatterNd.eval(tvec!(
+            rctensor2(&[[1, 2], [3, 4], [5, 6]]),
+            rctensor2(&[[2i64], [0]]),
+            rctensor2(&[[10, 20, 30], [40, 50, 60]])
+        ));
+        assert!(output.is_err());
+    }
+}
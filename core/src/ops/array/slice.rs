@@ -69,10 +69,8 @@ impl TypedOp for Slice {
 
     fn invariants(&self, model: &TypedModel, node: &TypedNode) -> TractResult<Invariants> {
         let fact = model.outlet_fact(node.inputs[0])?;
-        let axes = (0..fact.shape.rank())
-            .filter(|&ax| self.axis != ax)
-            .map(|axis| AxisInfo::simple(axis))
-            .collect();
+        let axes =
+            (0..fact.shape.rank()).filter(|&ax| self.axis != ax).map(AxisInfo::simple).collect();
         Ok(axes)
     }
 
@@ -99,6 +97,19 @@ impl TypedOp for Slice {
         }
     }
 
+    fn concretize_dims(
+        &self,
+        _source: &TypedModel,
+        node: &TypedNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+        values: &SymbolValues,
+    ) -> TractResult<TVec<OutletId>> {
+        let op =
+            Slice { axis: self.axis, start: self.start.eval(values), end: self.end.eval(values) };
+        target.wire_node(&node.name, op, &[mapping[&node.inputs[0]]])
+    }
+
     fn declutter(
         &self,
         model: &TypedModel,
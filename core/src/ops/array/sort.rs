@@ -0,0 +1,293 @@
+use crate::internal::*;
+use ndarray::*;
+use std::cmp::Ordering;
+
+/// Total order on values: NaNs (the values not equal to themselves) are
+/// greater than anything else, as in ONNX runtimes.
+fn nan_last_cmp<T: PartialOrd>(a: &T, b: &T) -> Ordering {
+    a.partial_cmp(b).unwrap_or_else(|| {
+        let (a_nan, b_nan) = (a.partial_cmp(a).is_none(), b.partial_cmp(b).is_none());
+        a_nan.cmp(&b_nan)
+    })
+}
+
+/// Positions of a lane's items, stably sorted by value.
+fn sort_lane<T: PartialOrd>(lane: ArrayView1<T>, descending: bool) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..lane.len()).collect();
+    order.sort_by(|&a, &b| {
+        let ord = nan_last_cmp(&lane[a], &lane[b]);
+        if descending {
+            ord.reverse()
+        } else {
+            ord
+        }
+    });
+    order
+}
+
+/// Sort values along an axis.
+#[derive(Debug, Clone, new, Hash)]
+pub struct Sort {
+    pub axis: usize,
+    pub descending: bool,
+}
+impl_dyn_hash!(Sort);
+
+impl Sort {
+    fn eval_t<T: Datum + PartialOrd>(&self, input: &Tensor) -> TractResult<Tensor> {
+        let input = input.to_array_view::<T>()?;
+        let mut output = input.to_owned();
+        Zip::from(output.lanes_mut(Axis(self.axis))).and(input.lanes(Axis(self.axis))).for_each(
+            |mut o, i| {
+                for (o, ix) in o.iter_mut().zip(sort_lane(i.view(), self.descending)) {
+                    *o = i[ix].clone();
+                }
+            },
+        );
+        Ok(output.into_tensor())
+    }
+}
+
+impl Op for Sort {
+    fn name(&self) -> Cow<str> {
+        "Sort".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("axis: {} descending: {}", self.axis, self.descending)])
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for Sort {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let output = dispatch_numbers!(Self::eval_t(inputs[0].datum_type())(self, &inputs[0]))?;
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl TypedOp for Sort {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(tvec!(TypedFact::dt_shape(inputs[0].datum_type, &*inputs[0].shape.to_tvec())))
+    }
+}
+
+/// Positions (as i64) that would sort values along an axis.
+#[derive(Debug, Clone, new, Hash)]
+pub struct ArgSort {
+    pub axis: usize,
+    pub descending: bool,
+}
+impl_dyn_hash!(ArgSort);
+
+impl ArgSort {
+    fn eval_t<T: Datum + PartialOrd>(&self, input: &Tensor) -> TractResult<Tensor> {
+        let input = input.to_array_view::<T>()?;
+        let mut output = ArrayD::<i64>::zeros(input.shape());
+        Zip::from(output.lanes_mut(Axis(self.axis))).and(input.lanes(Axis(self.axis))).for_each(
+            |mut o, i| {
+                for (o, ix) in o.iter_mut().zip(sort_lane(i, self.descending)) {
+                    *o = ix as i64;
+                }
+            },
+        );
+        Ok(output.into_tensor())
+    }
+}
+
+impl Op for ArgSort {
+    fn name(&self) -> Cow<str> {
+        "ArgSort".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("axis: {} descending: {}", self.axis, self.descending)])
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for ArgSort {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let output = dispatch_numbers!(Self::eval_t(inputs[0].datum_type())(self, &inputs[0]))?;
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl TypedOp for ArgSort {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(tvec!(TypedFact::dt_shape(i64::datum_type(), &*inputs[0].shape.to_tvec())))
+    }
+}
+
+/// K largest (or smallest) values along an axis, and their positions.
+///
+/// K is the second input. When `sorted` is false, the selected values come
+/// in their input order. `fallback_k` is the size of the output axis when k
+/// is only known at run time, usually a symbol of the model scope.
+#[derive(Debug, Clone, new, Hash)]
+pub struct TopK {
+    pub axis: usize,
+    pub largest: bool,
+    pub sorted: bool,
+    pub fallback_k: TDim,
+}
+impl_dyn_hash!(TopK);
+
+impl TopK {
+    fn eval_t<T: Datum + PartialOrd>(&self, input: &Tensor, k: usize) -> TractResult<TVec<Tensor>> {
+        let input = input.to_array_view::<T>()?;
+        if k > input.shape()[self.axis] {
+            bail!("TopK: k is {}, but axis {} of input is {:?}", k, self.axis, input.shape());
+        }
+        let mut shape = input.shape().to_vec();
+        shape[self.axis] = k;
+        let mut values = ArrayD::<T>::default(&*shape);
+        let mut indices = ArrayD::<i64>::zeros(&*shape);
+        Zip::from(values.lanes_mut(Axis(self.axis)))
+            .and(indices.lanes_mut(Axis(self.axis)))
+            .and(input.lanes(Axis(self.axis)))
+            .for_each(|mut v, mut ix, i| {
+                let mut order = sort_lane(i, self.largest);
+                order.truncate(k);
+                if !self.sorted {
+                    order.sort();
+                }
+                for ((v, ix), pos) in v.iter_mut().zip(ix.iter_mut()).zip(order) {
+                    *v = i[pos].clone();
+                    *ix = pos as i64;
+                }
+            });
+        Ok(tvec!(values.into_tensor(), indices.into_tensor()))
+    }
+}
+
+impl Op for TopK {
+    fn name(&self) -> Cow<str> {
+        "TopK".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("axis: {} largest: {} sorted: {}", self.axis, self.largest, self.sorted)])
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for TopK {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let k = inputs[1].cast_to_scalar::<i64>()? as usize;
+        let outputs = dispatch_numbers!(Self::eval_t(inputs[0].datum_type())(self, &inputs[0], k))?;
+        Ok(outputs.into_iter().map(|t| t.into_arc_tensor()).collect())
+    }
+}
+
+impl TypedOp for TopK {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let k = if let Some(k) = &inputs[1].konst {
+            if k.len() != 1 {
+                bail!("TopK expects a single k value, got {:?}", k);
+            }
+            k.cast_to::<TDim>()?.as_slice::<TDim>()?[0].clone()
+        } else {
+            self.fallback_k.clone()
+        };
+        let mut shape = inputs[0].shape.to_tvec();
+        shape[self.axis] = k;
+        Ok(tvec!(
+            TypedFact::dt_shape(inputs[0].datum_type, &*shape),
+            TypedFact::dt_shape(i64::datum_type(), &*shape)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sort_descending() -> TractResult<()> {
+        let output = Sort::new(1, true).eval(tvec!(rctensor2(&[[1f32, 3., 2.], [0., -1., 5.]])))?;
+        assert_eq!(output[0], rctensor2(&[[3f32, 2., 1.], [5., 0., -1.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn argsort_is_stable() -> TractResult<()> {
+        let output = ArgSort::new(0, false).eval(tvec!(rctensor1(&[2i32, 1, 2, 1])))?;
+        assert_eq!(output[0], rctensor1(&[1i64, 3, 0, 2]));
+        Ok(())
+    }
+
+    #[test]
+    fn topk_largest() -> TractResult<()> {
+        let op = TopK::new(1, true, true, 2.to_dim());
+        let output =
+            op.eval(tvec!(rctensor2(&[[0f32, 1., 2., 3.], [7., 6., 5., 4.]]), rctensor1(&[2i64])))?;
+        assert_eq!(output[0], rctensor2(&[[3f32, 2.], [7., 6.]]));
+        assert_eq!(output[1], rctensor2(&[[3i64, 2], [0, 1]]));
+        Ok(())
+    }
+
+    #[test]
+    fn topk_smallest_unsorted() -> TractResult<()> {
+        let op = TopK::new(0, false, false, 2.to_dim());
+        let output = op.eval(tvec!(rctensor1(&[4f32, 1., 3., 0.]), rctensor0(2i64)))?;
+        assert_eq!(output[0], rctensor1(&[1f32, 0.]));
+        assert_eq!(output[1], rctensor1(&[1i64, 3]));
+        Ok(())
+    }
+
+    #[test]
+    fn topk_with_k_as_input() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let k = model.symbol_table.new_with_prefix("k");
+        let x = model.add_source("x", TypedFact::dt_shape(f32::datum_type(), &[4]))?;
+        let k_input = model.add_source("k", TypedFact::dt_scalar(i64::datum_type()))?;
+        let topk =
+            model.wire_node("topk", TopK::new(0, true, true, k.clone().into()), &[x, k_input])?;
+        model.set_output_outlets(&topk)?;
+        assert_eq!(model.outlet_fact(topk[0])?.shape[0], k.into());
+        let plan = SimplePlan::new(model.into_optimized()?)?;
+        for k in 1..=3 {
+            let output = plan.run(tvec!(tensor1(&[4f32, 1., 3., 0.]), tensor0(k as i64)))?;
+            assert_eq!(output[0], rctensor1(&[4f32, 3., 1.][..k]));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn nan_sorts_last() -> TractResult<()> {
+        let input = rctensor1(&[f32::NAN, 1., f32::NAN, 0.]);
+        let output = ArgSort::new(0, false).eval(tvec!(input.clone()))?;
+        assert_eq!(output[0], rctensor1(&[3i64, 1, 0, 2]));
+        let output = ArgSort::new(0, true).eval(tvec!(input))?;
+        assert_eq!(output[0], rctensor1(&[0i64, 2, 1, 3]));
+        let output = TopK::new(0, true, true, 2.to_dim())
+            .eval(tvec!(rctensor1(&[1f32, f32::NAN, 3., 2.]), rctensor0(2i64)))?;
+        assert_eq!(output[1], rctensor1(&[1i64, 2]));
+        Ok(())
+    }
+}
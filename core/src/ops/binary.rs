@@ -288,7 +288,7 @@ impl TypedOp for TypedBinOp {
                 )?));
             }
         }
-        return Ok(None);
+        Ok(None)
     }
 
     fn codegen(
@@ -336,7 +336,7 @@ fn declutter_bin_to_unary(
             ));
         }
     }
-    return Ok(None);
+    Ok(None)
 }
 
 #[derive(Debug, Clone, new, Hash)]
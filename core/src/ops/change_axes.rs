@@ -21,6 +21,7 @@ impl InOut {
     }
 }
 
+#[allow(clippy::derived_hash_with_manual_eq)]
 #[derive(Clone, Debug, Hash)]
 pub enum AxisOp {
     Add(usize),
@@ -465,6 +466,7 @@ impl TypedOp for AxisOp {
         Ok(tvec!((InOut::Out(0), self.recip()), (InOut::In(0), self.clone())))
     }
 
+    #[allow(clippy::only_used_in_recursion)]
     fn change_axes(
         &self,
         model: &TypedModel,
@@ -605,8 +607,10 @@ pub fn change_axes(
                 }
                 for (wire, op) in wire_changes.into_iter() {
                     let outlet = wire.as_outlet(node);
-                    if !changed_wires.contains_key(&outlet) {
-                        changed_wires.insert(outlet, op.clone());
+                    if let std::collections::hash_map::Entry::Vacant(e) =
+                        changed_wires.entry(outlet)
+                    {
+                        e.insert(op.clone());
                         todo_changes.push((AxisChange { outlet, op }, Some(node_id)));
                     }
                 }
@@ -915,8 +919,7 @@ mod proptests {
         type Parameters = TVec<usize>;
         type Strategy = BoxedStrategy<AxisOp>;
         fn arbitrary_with(shape: TVec<usize>) -> Self::Strategy {
-            let mut ops: BoxedStrategy<AxisOp> =
-                (0usize..shape.len() + 1).prop_map(|ax| Add(ax)).boxed();
+            let mut ops: BoxedStrategy<AxisOp> = (0usize..shape.len() + 1).prop_map(Add).boxed();
             if shape.len() > 1 {
                 ops = ops
                     .prop_union(
@@ -924,8 +927,7 @@ mod proptests {
                     )
                     .boxed()
             }
-            let rms =
-                (0..shape.len()).filter(|&ax| shape[ax] == 1).map(|ax| Rm(ax)).collect::<Vec<_>>();
+            let rms = (0..shape.len()).filter(|&ax| shape[ax] == 1).map(Rm).collect::<Vec<_>>();
             if rms.len() > 0 {
                 ops = ops
                     .prop_union((0..rms.len()).prop_map(move |rm| rms[rm].clone()).boxed())
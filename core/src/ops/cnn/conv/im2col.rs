@@ -3,7 +3,7 @@ use tract_linalg::frame::Packer;
 use crate::internal::*;
 use ndarray::prelude::*;
 
-use crate::ops::cnn::Patch;
+use crate::ops::cnn::{Patch, PoolSpec};
 use crate::ops::nn::{DataFormat, DataShape};
 
 #[derive(Debug, Clone, Educe)]
@@ -125,7 +125,7 @@ impl EvalOp for Im2Col {
             let input_shape = self.data_format_with_n.shape(input.shape().into())?;
             // in the loop, we have normalized the input so that N is
             // always here, and output so that N and G are there.
-            if !output_shape.iter().any(|d| *d == 0) {
+            if !output_shape.contains(&0) {
                 for i in 0..*input_shape.n().unwrap_or(&1) {
                     let input = input.view_at_prefix(&[i])?;
                     for g in 0..self.group {
@@ -184,6 +184,116 @@ impl TypedOp for Im2Col {
     }
 }
 
+/// Im2Col for inputs with symbolic spatial dimensions.
+///
+/// The patch geometry is computed on evaluation from the actual input shape, and kept until the
+/// input shape changes.
+#[derive(Debug, Clone, Hash)]
+pub struct SymbolicIm2Col {
+    pub pool_spec: PoolSpec,
+    pub k: usize,
+    pub group: usize,
+    pub ci_per_group: usize,
+    pub b_pack: Packer,
+}
+
+impl_dyn_hash!(SymbolicIm2Col);
+
+impl SymbolicIm2Col {
+    fn concretize(&self, input_shape: &[usize]) -> TractResult<Im2Col> {
+        let (_, patch, _) = self.pool_spec.compute_geo(input_shape)?;
+        let n = patch.output_shape.iter().product();
+        Im2Col::new(
+            patch,
+            self.pool_spec.data_format,
+            self.k,
+            n,
+            self.group,
+            self.ci_per_group,
+            self.b_pack.clone(),
+        )
+    }
+}
+
+impl Op for SymbolicIm2Col {
+    fn name(&self) -> Cow<str> {
+        "SymbolicIm2col".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("k:{} groups:{} {:?}", self.k, self.group, self.b_pack)])
+    }
+
+    op_core_lir!();
+    op_as_typed_op!();
+}
+
+#[derive(Clone, Debug, Default)]
+struct SymbolicIm2ColState(Option<(TVec<usize>, Im2Col)>);
+
+impl OpState for SymbolicIm2ColState {
+    fn eval(
+        &mut self,
+        _session: &mut SessionState,
+        op: &dyn Op,
+        inputs: TVec<Arc<Tensor>>,
+    ) -> TractResult<TVec<Arc<Tensor>>> {
+        let op = op.downcast_ref::<SymbolicIm2Col>().unwrap();
+        let shape = inputs[0].shape();
+        if self.0.as_ref().map(|(cached, _)| &**cached != shape).unwrap_or(true) {
+            self.0 = Some((shape.into(), op.concretize(shape)?));
+        }
+        self.0.as_ref().unwrap().1.eval(inputs)
+    }
+
+    fn snapshot(&self) -> TractResult<TVec<Tensor>> {
+        Ok(tvec!())
+    }
+
+    fn restore(&mut self, _tensors: TVec<Tensor>) -> TractResult<()> {
+        Ok(())
+    }
+}
+
+impl EvalOp for SymbolicIm2Col {
+    fn is_stateless(&self) -> bool {
+        false
+    }
+
+    fn state(
+        &self,
+        _session: &mut SessionState,
+        _node_id: usize,
+    ) -> TractResult<Option<Box<dyn OpState>>> {
+        Ok(Some(Box::new(SymbolicIm2ColState::default())))
+    }
+
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        self.concretize(inputs[0].shape())?.eval(inputs)
+    }
+}
+
+impl TypedOp for SymbolicIm2Col {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let data_format = self.pool_spec.data_format;
+        let input_shape = data_format.shape(inputs[0].shape.to_tvec())?;
+        let output_shape = self.pool_spec.output_facts(&inputs[0..1])?.remove(0).shape;
+        let n: TDim =
+            data_format.shape(output_shape.to_tvec())?.hw_dims().iter().maybe_product()?;
+        let mut shape: TVec<TDim> = tvec!();
+        if let Some(n) = input_shape.n() {
+            shape.push(n.clone());
+        }
+        if self.group != 1 {
+            shape.push(self.group.into());
+        }
+        shape.push(self.b_pack.len_dim(&n));
+        Ok(tvec!(TypedFact::dt_shape(inputs[0].datum_type, shape)))
+    }
+}
+
 #[derive(Copy, Clone, Debug, Hash)]
 enum Patcher {
     Generic,
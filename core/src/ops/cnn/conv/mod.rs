@@ -7,22 +7,17 @@ mod proptest_q;
 mod q_sum_b;
 mod unary;
 
-pub use self::im2col::Im2Col;
+pub use self::im2col::{Im2Col, SymbolicIm2Col};
 pub(crate) use self::q_sum_b::QSumB;
 pub use self::unary::ConvUnary;
 
-#[derive(Debug, Copy, Clone, PartialEq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Hash, Default)]
 pub enum KernelFormat {
+    #[default]
     OIHW,
     HWIO,
 }
 
-impl Default for KernelFormat {
-    fn default() -> KernelFormat {
-        KernelFormat::OIHW
-    }
-}
-
 impl KernelFormat {
     pub fn h_axis(&self) -> usize {
         match self {
@@ -118,7 +118,7 @@ impl Arbitrary for ConvProblem {
             1usize..=4,
             1usize..=4,
             1usize..=3,
-            (1usize..=3).prop_flat_map(|r| shapes(r)),
+            (1usize..=3).prop_flat_map(shapes),
         )
             .prop_flat_map(|(df, kf, n, mut ci0, co0, group, (mut ker_shape, data_shape))| {
                 // FIXME in HWIO order, only regular and depthwise are supported
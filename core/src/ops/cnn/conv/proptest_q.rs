@@ -33,7 +33,7 @@ pub fn q_params() -> BoxedStrategy<QParams> {
         .boxed()
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct QConvProblem {
     shape_in: DataShape,
     shape_out: DataShape,
@@ -50,18 +50,14 @@ impl QConvProblem {
         &self.kernel.shape()[self.kernel_format.h_axis()..][..self.shape_in.hw_rank()]
     }
 
-    fn reference(&self) -> ArrayD<i8> {
-        setup_test_logger();
-        assert_eq!(self.data.shape(), &*self.shape_in.shape);
+    /// Convolve `data` by `kernel`, both laid out like the problem's data and kernel.
+    fn conv<T: LinalgScalar>(&self, data: &ArrayD<T>, kernel: &ArrayD<T>) -> ArrayD<T> {
+        assert_eq!(data.shape(), &*self.shape_in.shape);
         assert_eq!(self.shape_out.fmt, self.shape_in.fmt);
         let n = *self.shape_in.n().clone().unwrap_or(&1);
         let ci_per_g = self.shape_in.c() / self.group;
         let co_per_g = self.shape_out.c() / self.group;
-        let a0 = self.qp.a0.as_static().unwrap().cast_to_scalar::<i32>().unwrap();
-        let b0 = self.qp.b0.as_static().unwrap().cast_to_scalar::<i32>().unwrap();
-        let c0 = self.qp.c0.as_static().unwrap().cast_to_scalar::<i32>().unwrap();
-        let scale = self.qp.c_scale.as_static().unwrap().cast_to_scalar::<f32>().unwrap();
-        let mut temp = ArrayD::<i32>::zeros(&*self.shape_out.shape);
+        let mut output = ArrayD::<T>::zeros(&*self.shape_out.shape);
         for n in 0..n {
             for g in 0..self.group {
                 for geo_out in tract_ndarray::indices(self.shape_out.hw_dims()) {
@@ -79,7 +75,7 @@ impl QConvProblem {
                         input_coords.insert(self.shape_in.c_axis(), 0);
                         for ci in 0..ci_per_g {
                             input_coords[self.shape_in.c_axis()] = ci + g * ci_per_g;
-                            let i = self.data[&*input_coords] as i32;
+                            let i = data[&*input_coords];
                             for co in 0..co_per_g {
                                 output_coords[self.shape_out.c_axis()] = co + g * co_per_g;
                                 let mut kernel_coords: TVec<usize> = geo_ker.slice().into();
@@ -93,26 +89,90 @@ impl QConvProblem {
                                         kernel_coords.push(co);
                                     }
                                 }
-                                let k = self.kernel[&*kernel_coords] as i32;
-                                temp[&*output_coords] += (k - a0) * (i - b0);
+                                let k = kernel[&*kernel_coords];
+                                output[&*output_coords] = output[&*output_coords] + k * i;
                             }
                         }
                     }
                 }
             }
         }
+        output
+    }
+
+    /// Output channel of a kernel coefficient.
+    fn output_channel(&self, kernel_coords: &[usize]) -> usize {
+        match self.kernel_format {
+            KernelFormat::OIHW => kernel_coords[0],
+            KernelFormat::HWIO => {
+                let ci_per_g = self.shape_in.c() / self.group;
+                let co_per_g = self.shape_out.c() / self.group;
+                let rank = kernel_coords.len();
+                kernel_coords[rank - 2] / ci_per_g * co_per_g + kernel_coords[rank - 1]
+            }
+        }
+    }
+
+    fn reference(&self) -> ArrayD<i8> {
+        setup_test_logger();
+        let a0 = self.qp.a0.as_static().unwrap().cast_to_scalar::<i32>().unwrap();
+        let b0 = self.qp.b0.as_static().unwrap().cast_to_scalar::<i32>().unwrap();
+        let c0 = self.qp.c0.as_static().unwrap().cast_to_scalar::<i32>().unwrap();
+        let scale = self.qp.c_scale.as_static().unwrap().cast_to_scalar::<f32>().unwrap();
+        let mut temp =
+            self.conv(&self.data.mapv(|i| i as i32 - b0), &self.kernel.mapv(|k| k as i32 - a0));
         if let Some(bias) = &self.bias {
             let mut shape = vec![1; temp.ndim()];
             shape[self.shape_out.c_axis()] = bias.len();
             temp += &bias.clone().into_shape(shape).unwrap();
         }
+        // tract requantizes by multiplying with the inverse scale
+        let factor = scale.recip();
         temp.mapv(|i| {
-            (round_away(i as f32 / scale as f32) as i32 + c0)
+            (round_away(i as f32 * factor) as i32 + c0)
                 .max(std::i8::MIN as i32)
                 .min(std::i8::MAX as i32) as i8
         })
     }
 
+    /// Dequantize, convolve in f32 and quantize back. Kernel parameters may be per output
+    /// channel.
+    fn reference_f32(&self) -> ArrayD<i8> {
+        setup_test_logger();
+        let channels = *self.shape_out.c();
+        let per_channel = |param: &AttrOrInput| -> Vec<f32> {
+            let param = param.as_static().unwrap().cast_to::<f32>().unwrap();
+            let param = param.as_slice::<f32>().unwrap();
+            if param.len() == 1 {
+                vec![param[0]; channels]
+            } else {
+                param.to_vec()
+            }
+        };
+        let scalar = |param: &AttrOrInput| param.as_static().unwrap().cast_to_scalar::<f32>();
+        let a0 = per_channel(&self.qp.a0);
+        let a_scale = per_channel(&self.qp.a_scale);
+        let b0 = scalar(&self.qp.b0).unwrap();
+        let b_scale = scalar(&self.qp.b_scale).unwrap();
+        let c0 = scalar(&self.qp.c0).unwrap();
+        let c_scale = scalar(&self.qp.c_scale).unwrap();
+        let data = self.data.mapv(|i| (i as f32 - b0) * b_scale);
+        let kernel = ArrayD::from_shape_fn(self.kernel.shape(), |coords| {
+            let c = self.output_channel(coords.slice());
+            (self.kernel[&coords] as f32 - a0[c]) * a_scale[c]
+        });
+        let mut output = self.conv(&data, &kernel);
+        if let Some(bias) = &self.bias {
+            let bias = bias.as_slice().unwrap();
+            for (c, mut lane) in output.axis_iter_mut(Axis(self.shape_out.c_axis())).enumerate() {
+                lane += bias[c] as f32 * a_scale[c] * b_scale;
+            }
+        }
+        output.mapv(|x| {
+            (round_away(x / c_scale) + c0).max(std::i8::MIN as f32).min(std::i8::MAX as f32) as i8
+        })
+    }
+
     fn tract(&self, optim: bool) -> anyhow::Result<ArrayD<i8>> {
         setup_test_logger();
         assert_eq!(self.data.shape(), &*self.shape_in.shape);
@@ -150,6 +210,23 @@ impl QConvProblem {
         prop_assert_eq!(self.tract(true).unwrap(), self.reference());
         Ok(())
     }
+
+    // float rounding may put the requantized values one step away
+    fn check_f32(&self) -> TestCaseResult {
+        let reference = self.reference_f32();
+        for optim in &[false, true] {
+            let found = self.tract(*optim).unwrap();
+            prop_assert!(
+                izip!(found.iter(), reference.iter())
+                    .all(|(f, r)| (*f as i32 - *r as i32).abs() <= 1),
+                "optim: {} found: {:?} reference: {:?}",
+                optim,
+                found,
+                reference
+            );
+        }
+        Ok(())
+    }
 }
 
 impl Arbitrary for QConvProblem {
@@ -162,8 +239,8 @@ impl Arbitrary for QConvProblem {
             1usize..=10,
             1usize..=8,
             1usize..=8,
-            1usize..=1, // group, FIXME
-            (1usize..=3).prop_flat_map(|r| shapes(r)),
+            1usize..=3, // group
+            (1usize..=3).prop_flat_map(shapes),
             q_params(),
         )
             .prop_flat_map(|(df, kf, n, mut ci0, co0, group, (mut ker_shape, data_shape), qp)| {
@@ -207,6 +284,27 @@ proptest::proptest! {
     }
 }
 
+fn per_channel_problem() -> BoxedStrategy<QConvProblem> {
+    any::<QConvProblem>()
+        .prop_flat_map(|pb| {
+            let channels = *pb.shape_out.c();
+            (Just(pb), vec(-10i32..10, channels..=channels), vec(0.1f32..2., channels..=channels))
+        })
+        .prop_map(|(mut pb, a0, a_scale)| {
+            pb.qp.a0 = AttrOrInput::Attr(rctensor1(&a0));
+            pb.qp.a_scale = AttrOrInput::Attr(rctensor1(&a_scale));
+            pb
+        })
+        .boxed()
+}
+
+proptest::proptest! {
+    #[test]
+    fn prop_per_channel(pb in per_channel_problem()) {
+        pb.check_f32().unwrap()
+    }
+}
+
 #[test]
 fn trivial_0() {
     QConvProblem {
@@ -340,7 +438,6 @@ fn scale_1() {
 }
 
 #[test]
-#[ignore]
 fn group_0() {
     QConvProblem {
         shape_in: HWC.from_n_c_hw(1, 2, &[1]).unwrap(),
@@ -356,6 +453,90 @@ fn group_0() {
     .unwrap();
 }
 
+#[test]
+fn group_1() {
+    let mut qp = QParams::noop_static(i8::datum_type());
+    qp.b0 = AttrOrInput::Attr(rctensor0(-1i32));
+    QConvProblem {
+        shape_in: CHW.from_n_c_hw(1, 2, &[1]).unwrap(),
+        shape_out: CHW.from_n_c_hw(1, 2, &[1]).unwrap(),
+        kernel_format: OIHW,
+        group: 2,
+        data: arr2(&[[0], [0]]).into_dyn(),
+        kernel: arr3(&[[[0]], [[5]]]).into_dyn(),
+        bias: None,
+        qp,
+    }
+    .check()
+    .unwrap();
+}
+
+#[test]
+fn group_2() {
+    let mut qp = QParams::noop_static(i8::datum_type());
+    qp.b0 = AttrOrInput::Attr(rctensor0(1i32));
+    qp.c_scale = AttrOrInput::Attr(rctensor0(0.001f32));
+    QConvProblem {
+        shape_in: NHWC.from_n_c_hw(1, 2, &[1]).unwrap(),
+        shape_out: NHWC.from_n_c_hw(1, 2, &[1]).unwrap(),
+        kernel_format: OIHW,
+        group: 2,
+        data: arr3(&[[[0, 1]]]).into_dyn(),
+        kernel: arr3(&[[[2]], [[0]]]).into_dyn(),
+        bias: None,
+        qp,
+    }
+    .check()
+    .unwrap();
+}
+
+#[test]
+fn per_channel_0() {
+    let mut qp = QParams::noop_static(i8::datum_type());
+    qp.a0 = AttrOrInput::Attr(rctensor1(&[0i32, 2]));
+    qp.a_scale = AttrOrInput::Attr(rctensor1(&[1f32, 0.5]));
+    qp.c_scale = AttrOrInput::Attr(rctensor0(0.5f32));
+    QConvProblem {
+        shape_in: HWC.from_n_c_hw(1, 1, &[2]).unwrap(),
+        shape_out: HWC.from_n_c_hw(1, 2, &[1]).unwrap(),
+        kernel_format: OIHW,
+        group: 1,
+        data: arr2(&[[3i8], [-1]]).into_dyn(),
+        kernel: arr3(&[[[1i8, 2]], [[4, -2]]]).into_dyn(),
+        bias: Some(arr1(&[1i32, -2]).into_dyn()),
+        qp,
+    }
+    .check_f32()
+    .unwrap();
+}
+
+#[test]
+fn per_channel_group_0() {
+    let mut qp = QParams::noop_static(i8::datum_type());
+    qp.a0 = AttrOrInput::Attr(rctensor1(&[1i32, -1, 0, 3]));
+    qp.a_scale = AttrOrInput::Attr(rctensor1(&[0.5f32, 1., 1.5, 2.]));
+    qp.b0 = AttrOrInput::Attr(rctensor0(1i32));
+    qp.c_scale = AttrOrInput::Attr(rctensor0(0.25f32));
+    QConvProblem {
+        shape_in: NHWC.from_n_c_hw(1, 4, &[2]).unwrap(),
+        shape_out: NHWC.from_n_c_hw(1, 4, &[1]).unwrap(),
+        kernel_format: OIHW,
+        group: 2,
+        data: arr3(&[[[1i8, 2, 3, 4], [-4, -3, -2, -1]]]).into_dyn(),
+        kernel: arr3(&[
+            [[1i8, 2], [3, 4]],
+            [[0, 1], [-1, 0]],
+            [[2, 2], [-2, 2]],
+            [[5, -5], [1, 1]],
+        ])
+        .into_dyn(),
+        bias: None,
+        qp,
+    }
+    .check_f32()
+    .unwrap();
+}
+
 #[test]
 fn rounding_on_arm() {
     let mut qp = QParams::noop_static(i8::datum_type());
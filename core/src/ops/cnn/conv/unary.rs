@@ -4,13 +4,13 @@ use crate::internal::*;
 use crate::model::*;
 
 use super::depth_wise::DepthWise;
-use super::im2col::Im2Col;
+use super::im2col::{Im2Col, SymbolicIm2Col};
 use crate::ops::cnn::conv::KernelFormat;
 use crate::ops::cnn::Patch;
 use crate::ops::cnn::PoolSpec;
 use crate::ops::matmul::lir_unary::{LirMatMulUnary, ProtoFusedSpec};
 use crate::ops::matmul::QParams;
-use crate::ops::nn::{DataFormat, DataShape};
+use crate::ops::nn::{BaseDataShape, DataFormat, DataShape};
 
 use tract_linalg::mmm::MatMatMul;
 use tract_linalg::{frame::Packer, mmm::MatrixStoreSpec};
@@ -81,13 +81,13 @@ impl ConvUnary {
             let mut packed_as = Array1::from(
                 (0..self.group)
                     .map(|g| {
-                        let mut packed = Tensor::uninitialized_aligned_dt(
+                        let packed = Tensor::uninitialized_aligned_dt(
                             kernel.datum_type(),
                             &[packer.len(m)],
                             packer.alignment(),
                         )?;
                         packer.pack(
-                            &mut TensorView::at_prefix(&mut packed, &[])?,
+                            &mut TensorView::at_prefix(&packed, &[])?,
                             &kernel.view_at_prefix(&[g])?,
                             1,
                             0,
@@ -111,14 +111,16 @@ impl ConvUnary {
     where
         T: Datum + Copy,
     {
-        let mut ops = Array1::from_elem(self.group, vec!());
+        let mut ops = Array1::from_elem(self.group, vec![]);
 
         if let Some(bias) = &self.bias {
             let bias = bias.cast_to::<T>()?;
             let bias = bias.as_slice::<T>()?;
-            ops.iter_mut().zip(bias.chunks(self.output_channels() / self.group)).for_each(|(ops, bias)| {
-                ops.push(ProtoFusedSpec::PerRowAdd(rctensor1(bias).into()));
-            })
+            ops.iter_mut().zip(bias.chunks(self.output_channels() / self.group)).for_each(
+                |(ops, bias)| {
+                    ops.push(ProtoFusedSpec::PerRowAdd(rctensor1(bias).into()));
+                },
+            )
         }
         let mut ops = ops.into_dyn();
 
@@ -157,8 +159,14 @@ impl ConvUnary {
             })
             .collect::<TractResult<Vec<OutletId>>>()?;
 
-        let a0 = params[0];
-        let a_scale = params[1];
+        let (mmm_output_shape, c_axis, h_axis) = self.mmm_output_shape(&output_shape)?;
+        let rank = mmm_output_shape.len();
+
+        // kernel parameters can be per output channel
+        let a0 =
+            self.wire_kernel_q_param(model, &format!("{}.a0", name), params[0], rank, c_axis)?;
+        let a_scale =
+            self.wire_kernel_q_param(model, &format!("{}.a_scale", name), params[1], rank, c_axis)?;
         let b0 = params[2];
         let b_scale = params[3];
         let c0 = params[4];
@@ -192,15 +200,23 @@ impl ConvUnary {
         }
         let sum_a = model.add_const(format!("{}.sum_a", name), sum_a)?;
 
-        let sum_b = model.wire_node(
+        let mut sum_b = model.wire_node(
             format!("{}.sum_b", name),
             super::QSumB { n, r: mmm.b_pack().panel_width(), k },
             &[im2col],
         )?[0];
+        // sum_b comes group first, the product has its geometry before the groups when c is last
+        if self.group > 1 && self.pool_spec.data_format.c_is_last() {
+            let rank = model.outlet_fact(sum_b)?.rank();
+            sum_b = model.wire_node(
+                format!("{}.sum_b_geo_first", name),
+                AxisOp::Move(rank - 1, h_axis),
+                &[sum_b],
+            )?[0];
+        }
 
         let b_dt = model.outlet_fact(wires[0])?.datum_type;
         let b_storage = mmm.b_packed(b_dt);
-        let (mmm_output_shape, c_axis, h_axis) = self.mmm_output_shape(&output_shape)?;
         let res = self.wire_lir_matmatmul(
             model,
             name,
@@ -219,7 +235,8 @@ impl ConvUnary {
             model,
             name,
             res,
-            self.pool_spec.data_format.c_is_last(),
+            c_axis,
+            h_axis,
             k.to_dim(),
             a0,
             b0,
@@ -228,41 +245,77 @@ impl ConvUnary {
         )?;
 
         let wire = qmm::requant(model, name, res, c_dt, abc_scale, c0)?;
+        let wire = self.wire_group_reshape(model, name, wire, &mmm_output_shape, c_axis, m)?;
         let wire = Self::wire_geo_reshape(model, name, wire, &output_shape)?;
         Ok(wire)
     }
 
+    /// Lay a kernel quantization parameter along the channel axis of the matmul output. Per
+    /// channel parameters are split by group first.
+    fn wire_kernel_q_param(
+        &self,
+        model: &mut TypedModel,
+        name: &str,
+        mut param: OutletId,
+        rank: usize,
+        c_axis: usize,
+    ) -> TractResult<OutletId> {
+        let fact = model.outlet_fact(param)?;
+        if self.group > 1 && fact.rank() == 1 && fact.shape[0] != 1.to_dim() {
+            let channels = fact.shape[0].clone();
+            param = model.wire_node(
+                format!("{}.split_groups", name),
+                AxisOp::Reshape(
+                    0,
+                    tvec!(channels.clone()),
+                    tvec!(self.group.to_dim(), channels / self.group),
+                ),
+                &[param],
+            )?[0];
+        }
+        crate::ops::matmul::mir_quant::wire_per_axis_param(model, name, param, rank, c_axis)
+    }
+
     pub unsafe fn wire_as_im2col_pair(
         &self,
         model: &mut TypedModel,
         name: &str,
         mut wire: OutletId,
     ) -> TractResult<OutletId> {
-        let b_fact = model.outlet_fact(wire)?;
+        let b_fact = model.outlet_fact(wire)?.clone();
         let b_dt = b_fact.datum_type;
         let c_dt = crate::ops::matmul::output_type(b_fact.datum_type);
 
-        let (input_shape, geo, output_shape, m, k, n, mmm) =
-            self.compute_geo(model.outlet_fact(wire)?)?;
+        let (input_shape, output_shape, m, k, mmm) = self.compute_symbolic_geo(&b_fact)?;
+        let ci_per_group = input_shape.c_dim().to_usize()? / self.group;
         let padding = model.add_const(format!("{}.b0", name), Tensor::zero_dt(b_dt, &[])?)?;
 
-        wire = model.wire_node(
-            format!("{}.im2col", name),
-            Im2Col::new(
+        let im2col: Box<dyn TypedOp> = if let Some(shape) = b_fact.shape.as_concrete() {
+            let (_, geo, _) = self.pool_spec.compute_geo(shape)?;
+            let n = geo.output_shape.iter().product();
+            Box::new(Im2Col::new(
                 geo,
-                self.pool_spec.data_format.clone(),
+                self.pool_spec.data_format,
                 k,
                 n,
                 self.group,
-                *input_shape.c_dim() / self.group,
+                ci_per_group,
                 mmm.b_pack(),
-            )?,
-            &[wire, padding],
-        )?[0];
+            )?)
+        } else {
+            Box::new(SymbolicIm2Col {
+                pool_spec: self.pool_spec.clone(),
+                k,
+                group: self.group,
+                ci_per_group,
+                b_pack: mmm.b_pack(),
+            })
+        };
+        wire = model.wire_node(format!("{}.im2col", name), im2col, &[wire, padding])?[0];
 
         let b_storage = mmm.b_packed(b_dt);
         let (mmm_output_shape, c_axis, h_axis) = self.mmm_output_shape(&output_shape)?;
-        let mut wire = self.wire_lir_matmatmul(
+        let wire = self.wire_lir_matmatmul(
             model,
             name,
             wire,
@@ -276,37 +329,52 @@ impl ConvUnary {
             h_axis,
         )?;
 
-        if self.group > 1 {
-            wire = model.wire_node(
-                format!("{}.reshape_group", name),
-                AxisOp::Reshape(
-                    c_axis - 1,
-                    mmm_output_shape[c_axis - 1..][..2].iter().map(|d| d.to_dim()).collect(),
-                    tvec!((m * self.group).to_dim()),
-                ),
-                &[wire],
-            )?[0];
-        }
+        let wire = self.wire_group_reshape(model, name, wire, &mmm_output_shape, c_axis, m)?;
         let wire = Self::wire_geo_reshape(model, name, wire, &output_shape)?;
         Ok(wire)
     }
 
-    fn mmm_output_shape(
+    /// Merge the group axis of a matmul output back into its channels.
+    fn wire_group_reshape<D: DimLike>(
         &self,
-        output_shape: &DataShape,
-    ) -> TractResult<(TVec<usize>, usize, usize)> {
-        let geo_collapsed_out: usize = output_shape.hw_dims().iter().maybe_product()?;
+        model: &mut TypedModel,
+        name: &str,
+        wire: OutletId,
+        mmm_output_shape: &[D],
+        c_axis: usize,
+        m: usize,
+    ) -> TractResult<OutletId> {
+        if self.group == 1 {
+            return Ok(wire);
+        }
+        Ok(model.wire_node(
+            format!("{}.reshape_group", name),
+            AxisOp::Reshape(
+                c_axis - 1,
+                mmm_output_shape[c_axis - 1..][..2].iter().map(|d| d.to_dim()).collect(),
+                tvec!((m * self.group).to_dim()),
+            ),
+            &[wire],
+        )?[0])
+    }
+
+    fn mmm_output_shape<D: DimLike>(
+        &self,
+        output_shape: &BaseDataShape<D, TVec<D>>,
+    ) -> TractResult<(TVec<D>, usize, usize)> {
+        let geo_collapsed_out: D = output_shape.hw_dims().iter().maybe_product()?;
         let shape = output_shape.fmt.from_n_c_hw(
-            *output_shape.n().clone().unwrap_or(&1),
-            *output_shape.c(),
+            output_shape.n().cloned().unwrap_or_else(|| D::from(1usize)),
+            output_shape.c().clone(),
             tvec!(geo_collapsed_out),
         )?;
-        let mut mmm_output_shape: TVec<usize> = shape.shape.clone().into();
+        let mut mmm_output_shape: TVec<D> = shape.shape.clone();
         let mut c_axis = shape.c_axis();
         let mut h_axis = shape.h_axis();
         if self.group > 1 {
-            mmm_output_shape[shape.c_axis()] /= self.group;
-            mmm_output_shape.insert(shape.c_axis(), self.group);
+            mmm_output_shape[shape.c_axis()] =
+                mmm_output_shape[shape.c_axis()].clone() / self.group;
+            mmm_output_shape.insert(shape.c_axis(), D::from(self.group));
             if self.group > 1 {
                 if h_axis > c_axis {
                     h_axis += 1;
@@ -317,13 +385,13 @@ impl ConvUnary {
         Ok((mmm_output_shape, c_axis, h_axis))
     }
 
-    fn wire_geo_reshape(
+    fn wire_geo_reshape<D: DimLike>(
         model: &mut TypedModel,
         name: &str,
         wire: OutletId,
-        output_shape: &DataShape,
+        output_shape: &BaseDataShape<D, TVec<D>>,
     ) -> TractResult<OutletId> {
-        let geo_collapsed_out: usize = output_shape.hw_dims().iter().maybe_product()?;
+        let geo_collapsed_out: D = output_shape.hw_dims().iter().maybe_product()?;
         let wire = model.wire_node(
             name,
             AxisOp::Reshape(
@@ -401,14 +469,45 @@ impl ConvUnary {
         Ok((input_shape, geo, output_shape, m, k, n, mmm))
     }
 
-    fn wire_lir_matmatmul(
+    /// Same as `compute_geo`, for inputs with symbolic spatial dimensions. The multiplier is
+    /// picked for an arbitrary n if n is symbolic.
+    fn compute_symbolic_geo(
+        &self,
+        input_fact: &TypedFact,
+    ) -> TractResult<(
+        BaseDataShape<TDim, TVec<TDim>>,
+        BaseDataShape<TDim, TVec<TDim>>,
+        usize,
+        usize,
+        Box<dyn MatMatMul>,
+    )> {
+        let a_dt = self.kernel.datum_type();
+        let b_dt = input_fact.datum_type;
+        let c_dt = crate::ops::matmul::output_type(b_dt);
+
+        let input_shape = self.pool_spec.data_format.shape(input_fact.shape.to_tvec())?;
+        let output_fact = self.pool_spec.output_facts(&[input_fact])?.remove(0);
+        let output_shape = self.pool_spec.data_format.shape(output_fact.shape.to_tvec())?;
+
+        let m = self.output_channels() / self.group;
+        let k = self.kernel.len() / self.output_channels();
+        let n: TDim = output_shape.hw_dims().iter().maybe_product()?;
+
+        let mmm = tract_linalg::ops()
+            .mmm(a_dt, b_dt, c_dt, m, k, n.to_usize().unwrap_or(0))
+            .with_context(|| format!("No multiplier for {:?}x{:?} to {:?}", a_dt, b_dt, c_dt,))?;
+
+        Ok((input_shape, output_shape, m, k, mmm))
+    }
+
+    fn wire_lir_matmatmul<D: DimLike>(
         &self,
         model: &mut TypedModel,
         name: &str,
         wire: OutletId,
         mmm: Box<dyn MatMatMul>,
         c_datum_type: DatumType,
-        mmm_output_shape: &[usize],
+        mmm_output_shape: &[D],
         m: usize,
         k: usize,
         input_storage: MatrixStoreSpec,
@@ -418,8 +517,9 @@ impl ConvUnary {
         let kernels = self.kernel_as_packed_as(&mmm.a_pack(), m)?;
         let shape = kernels.shape();
         let fused_ops = dispatch_copy!(Self::bias_as_non_linear(mmm.internal_type())(self))?;
-        let mut iter = kernels.into_iter().cloned().zip(fused_ops.into_iter().cloned());
+        let mut iter = kernels.iter().cloned().zip(fused_ops.iter().cloned());
         let micro_ops = ArrayD::from_shape_fn(shape, |_| iter.next().unwrap());
+        let mmm_output_shape: TVec<TDim> = mmm_output_shape.iter().map(|d| d.to_dim()).collect();
 
         let wire = model.wire_node(
             format!("{}.matmatmul", name),
@@ -464,7 +564,7 @@ impl ConvUnary {
         if let Some(axis) = (0..spatial_rank).find(|&ax| {
             self.pool_spec.padding.valid_dim(ax)
                 && self.pool_spec.stride(ax) > 1
-                && self.pool_spec.dilation(ax) % self.pool_spec.stride(ax) == 0
+                && self.pool_spec.dilation(ax).is_multiple_of(self.pool_spec.stride(ax))
         }) {
             let downsample_factor = self.pool_spec.stride(axis);
             let mut new_op = self.clone();
@@ -670,7 +770,7 @@ impl TypedOp for ConvUnary {
         let mut axes = vec![];
         if let Some(n_axis) = shape.n_axis() {
             let mut info = AxisInfo::simple(n_axis).disposable(true);
-            info.inputs.extend(std::iter::repeat(None).take(node.inputs.len() - 1));
+            info.inputs.extend(std::iter::repeat_n(None, node.inputs.len() - 1));
             axes.push(info);
         }
         let kernel_spatial_shape =
@@ -679,7 +779,7 @@ impl TypedOp for ConvUnary {
         for (ix, &dim) in kernel_spatial_shape.iter().enumerate() {
             if dim == 1 && self.pool_spec.stride(ix) == 1 {
                 let mut info = AxisInfo::simple(ix + h_axis).disposable(true);
-                info.inputs.extend(std::iter::repeat(None).take(node.inputs.len() - 1));
+                info.inputs.extend(std::iter::repeat_n(None, node.inputs.len() - 1));
                 axes.push(info)
             }
         }
@@ -720,7 +820,7 @@ impl TypedOp for ConvUnary {
         );
         let n_output_points: TDim = output_dims.iter().map(|d| d.output.clone()).maybe_product()?;
         let n_output_channels = self.output_channels().to_dim();
-        let kernel_surface = kernel_spatial_shape.into_iter().product::<usize>().to_dim();
+        let kernel_surface = kernel_spatial_shape.iter().product::<usize>().to_dim();
         let one = 1.to_dim();
         Ok(tvec!(
             (
@@ -831,12 +931,13 @@ impl TypedOp for ConvUnary {
             bias: self.bias.clone(),
             q_params: self.q_params.clone(),
         };
-        return Ok(Some(AxisChangeConsequence {
+        Ok(Some(AxisChangeConsequence {
             substitute_op: Some(Box::new(new_op)),
             wire_changes: tvec!((InOut::In(0), change.clone()), (InOut::Out(0), change.clone())),
-        }));
+        }))
     }
 
+    #[allow(clippy::unnecessary_unwrap)]
     fn codegen(
         &self,
         model: &TypedModel,
@@ -847,7 +948,11 @@ impl TypedOp for ConvUnary {
         let input_shape = self.pool_spec.data_format.shape(&full_input_shape)?;
         let spatial_rank = input_shape.hw_rank();
         let kernel_spatial_shape = &self.kernel.shape()[self.kernel_fmt.h_axis()..][..spatial_rank];
-        if let Some(shape) = input_fact.shape.as_concrete() {
+        let concrete_shape = input_fact.shape.as_concrete();
+        // with symbolic spatial dimensions, patches are computed on evaluation
+        if concrete_shape.is_some()
+            || (self.q_params.is_none() && self.compute_symbolic_geo(input_fact).is_ok())
+        {
             unsafe {
                 let dt = input_fact.datum_type;
                 if self.q_params.is_some() {
@@ -864,6 +969,8 @@ impl TypedOp for ConvUnary {
                     && (0..spatial_rank)
                         .all(|i| self.pool_spec.stride(i) == 1 && self.pool_spec.dilation(i) == 1)
                     && self.group == 1
+                    // symbolic reshapes can not be evaluated
+                    && (concrete_shape.is_some() || spatial_rank == 1)
                 {
                     use crate::ops::matmul::MatMulUnary;
                     let mut patch = TypedModelPatch::default();
@@ -926,8 +1033,16 @@ impl TypedOp for ConvUnary {
                     )?[0];
                     patch.shunt_outside(model, OutletId::new(node.id, 0), wire)?;
                     return Ok(Some(patch));
+                } else if concrete_shape.is_none() {
+                    let mut patch = TypedModelPatch::default();
+                    let wire = patch.tap_model(model, node.inputs[0])?;
+                    let wire = self
+                        .wire_as_im2col_pair(&mut patch, &*node.name, wire)
+                        .context("in wire_as_im2col_pair")?;
+                    patch.shunt_outside(model, OutletId::new(node.id, 0), wire)?;
+                    return Ok(Some(patch));
                 } else if should_use_direct(
-                    &self.pool_spec.data_format.shape(shape.into())?,
+                    &self.pool_spec.data_format.shape(concrete_shape.unwrap().into())?,
                     &self.pool_spec,
                     self.group,
                 ) {
@@ -942,8 +1057,9 @@ impl TypedOp for ConvUnary {
                     && self.group == self.output_channels()
                     && self.group == self.input_channels()
                 {
-                    let op = dispatch_floatlike!(Self::to_depth_wise(dt)(self, &shape))
-                        .context("in to_depth_wise")?;
+                    let op =
+                        dispatch_floatlike!(Self::to_depth_wise(dt)(self, concrete_shape.unwrap()))
+                            .context("in to_depth_wise")?;
                     return Ok(Some(TypedModelPatch::single_unary_op(model, node, op)?));
                 } else {
                     let mut patch = TypedModelPatch::default();
@@ -1129,4 +1245,33 @@ mod test {
         assert!(!use_direct(24, 3)); // tdnn3
         assert!(!use_direct(10, 1)); // tdnn4,5
     }
+
+    #[test]
+    fn symbolic_im2col() -> TractResult<()> {
+        let op = ConvUnary {
+            pool_spec: PoolSpec::new(CHW, tvec!(2), PaddingSpec::Valid, None, None, Some(2)),
+            kernel_fmt: KernelFormat::OIHW,
+            kernel: rctensor3(&[[[1f32, 1f32]], [[1f32, -1f32]]]),
+            group: 1,
+            bias: None,
+            q_params: None,
+        };
+        let s = Symbol::new('S');
+        let mut model = TypedModel::default();
+        let source = model.add_source(
+            "source",
+            TypedFact::dt_shape(f32::datum_type(), &[1.to_dim(), s.to_dim()]),
+        )?;
+        let conv = model.wire_node("conv", op, &[source])?;
+        model.set_output_outlets(&conv)?;
+        let model = model.into_optimized()?;
+        assert!(model.nodes().iter().all(|n| !n.op_is::<ConvUnary>()));
+        assert!(model.nodes().iter().any(|n| n.op_is::<SymbolicIm2Col>()));
+        let plan = SimplePlan::new(model)?;
+        let output = plan.run(tvec!(tensor2(&[[1f32, 2., 4.]])))?;
+        assert_eq!(*output[0], tensor2(&[[3f32, 6.], [-1., -2.]]));
+        let output = plan.run(tvec!(tensor2(&[[1f32, 2.]])))?;
+        assert_eq!(*output[0], tensor2(&[[3f32], [-1.]]));
+        Ok(())
+    }
 }
@@ -0,0 +1,142 @@
+use crate::internal::*;
+use crate::ops::cnn::PoolSpec;
+use num_traits::Float;
+
+/// Scatter-add step of a deconvolution ("col2im").
+///
+/// Input is the product of the kernel by the deconvolution input, shaped as
+/// [N, C, kernel positions, input positions]. Each value is added to the
+/// output position it contributes to, then the padding is cropped out.
+#[derive(Clone, Debug, new, Hash)]
+pub struct DeconvSum {
+    pub pool_spec: PoolSpec,
+    /// Shape of the deconvolution input, in the pool spec data format.
+    pub input_shape: TVec<TDim>,
+    pub adjustments: TVec<usize>,
+    pub bias: Option<Arc<Tensor>>,
+}
+
+impl_dyn_hash!(DeconvSum);
+
+impl DeconvSum {
+    /// Concrete spatial input shape, working out the symbolic dim (if any)
+    /// from the number of input positions.
+    fn concrete_input_spatial_shape(&self, positions: usize) -> TractResult<TVec<usize>> {
+        let shape = self.pool_spec.data_format.shape(&*self.input_shape)?;
+        let known: usize = shape.hw_dims().iter().filter_map(|d| d.to_usize().ok()).product();
+        shape
+            .hw_dims()
+            .iter()
+            .map(|d| Ok(if let Ok(d) = d.to_usize() { d } else { positions / known }))
+            .collect()
+    }
+
+    fn eval_t<T: Datum + Float>(&self, input: &Tensor) -> TractResult<Tensor> {
+        let (n, c, kernel_len, positions) = match input.shape() {
+            &[n, c, k, p] => (n, c, k, p),
+            _ => bail!("DeconvSum expects a rank 4 input, got {:?}", input.shape()),
+        };
+        let input_spatial_shape = self.concrete_input_spatial_shape(positions)?;
+        let strides = self.pool_spec.strides();
+        let dilations = self.pool_spec.dilations();
+        let computed = self.pool_spec.padding.compute_for_deconv(
+            &input_spatial_shape,
+            &self.pool_spec.kernel_shape,
+            &dilations,
+            &strides,
+            &self.adjustments,
+        );
+        let output_spatial_shape: TVec<usize> = computed.iter().map(|d| d.output).collect();
+        let output_positions: usize = output_spatial_shape.iter().product();
+
+        // for each (kernel position, input position), the output position it lands on
+        let mut targets: Vec<Option<usize>> = Vec::with_capacity(kernel_len * positions);
+        for kernel_coords in ndarray::indices(&*self.pool_spec.kernel_shape) {
+            for input_coords in ndarray::indices(&*input_spatial_shape) {
+                let mut offset = 0;
+                let mut valid = true;
+                for axis in 0..input_spatial_shape.len() {
+                    let coord = (input_coords[axis] * strides[axis]
+                        + kernel_coords[axis] * dilations[axis])
+                        as isize
+                        - computed[axis].pad_before as isize;
+                    if coord < 0 || coord >= output_spatial_shape[axis] as isize {
+                        valid = false;
+                        break;
+                    }
+                    offset = offset * output_spatial_shape[axis] + coord as usize;
+                }
+                targets.push(if valid { Some(offset) } else { None });
+            }
+        }
+
+        let mut output = Tensor::zero::<T>(&[n, c, output_positions])?;
+        {
+            let output = output.as_slice_mut::<T>()?;
+            if let Some(bias) = &self.bias {
+                let bias = bias.cast_to::<T>()?;
+                let bias = bias.as_slice::<T>()?;
+                for (ix, chunk) in output.chunks_mut(output_positions).enumerate() {
+                    chunk.iter_mut().for_each(|o| *o = bias[ix % c]);
+                }
+            }
+            let input = input.as_slice::<T>()?;
+            for (ix, slab) in input.chunks(kernel_len * positions).enumerate() {
+                let output = &mut output[ix * output_positions..][..output_positions];
+                for (value, target) in slab.iter().zip(targets.iter()) {
+                    if let Some(target) = target {
+                        output[*target] = output[*target] + *value;
+                    }
+                }
+            }
+        }
+
+        let mut shape = tvec!(n, c);
+        shape.extend(output_spatial_shape.iter().cloned());
+        output.set_shape(&shape)?;
+        if self.pool_spec.data_format.c_is_last() {
+            let mut permutation: TVec<usize> = (0..shape.len()).collect();
+            permutation[1..].rotate_left(1);
+            output = output.permute_axes(&permutation)?;
+        }
+        if !self.pool_spec.data_format.has_n() {
+            output.remove_axis(0)?;
+        }
+        Ok(output)
+    }
+}
+
+impl Op for DeconvSum {
+    fn name(&self) -> Cow<str> {
+        "DeconvSum".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        let mut info = self.pool_spec.info();
+        info.push(format!("Adjustments: {:?}", self.adjustments));
+        Ok(info)
+    }
+
+    op_core_lir!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for DeconvSum {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let output = dispatch_floatlike!(Self::eval_t(inputs[0].datum_type())(self, &inputs[0]))?;
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl TypedOp for DeconvSum {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let shape = super::output_shape(&self.pool_spec, &self.input_shape, &self.adjustments)?;
+        Ok(tvec!(TypedFact::dt_shape(inputs[0].datum_type, &*shape)))
+    }
+}
@@ -0,0 +1,35 @@
+use crate::internal::*;
+use crate::ops::cnn::PoolSpec;
+
+mod deconv_sum;
+mod unary;
+
+pub use deconv_sum::DeconvSum;
+pub use unary::DeconvUnary;
+
+/// Full output shape of a deconvolution, in the pool spec data format.
+pub fn output_shape<D: DimLike>(
+    pool_spec: &PoolSpec,
+    x_shape: &[D],
+    adjustments: &[usize],
+) -> TractResult<TVec<D>> {
+    let x_shape = pool_spec.data_format.shape(x_shape)?;
+    let spatial_output_details = pool_spec.padding.compute_for_deconv(
+        x_shape.hw_dims(),
+        &pool_spec.kernel_shape,
+        &pool_spec.dilations(),
+        &pool_spec.strides(),
+        adjustments,
+    );
+    let deconv_shape: TVec<D> =
+        spatial_output_details.into_iter().map(|comp| comp.output).collect();
+    let co = pool_spec
+        .output_channel_override
+        .ok_or_else(|| format_err!("Deconvolution output channels must be specified"))?;
+    let output_shape = pool_spec.data_format.from_n_c_hw(
+        x_shape.n().cloned().unwrap_or(1.into()),
+        co.into(),
+        deconv_shape,
+    )?;
+    Ok(output_shape.shape)
+}
@@ -0,0 +1,298 @@
+use crate::internal::*;
+use crate::ops::cnn::{KernelFormat, PoolSpec};
+use crate::ops::matmul::MatMulUnary;
+
+use super::DeconvSum;
+
+/// Transposed convolution, with a constant kernel.
+///
+/// The kernel is described in the format of the convolution it transposes:
+/// with `OIHW`, O is the deconvolution input channels and I its output
+/// channels (divided by group).
+#[derive(Clone, Debug, new, Hash)]
+pub struct DeconvUnary {
+    pub pool_spec: PoolSpec,
+    pub kernel_format: KernelFormat,
+    pub kernel: Arc<Tensor>,
+    pub bias: Option<Arc<Tensor>>,
+    pub adjustments: TVec<usize>,
+    pub group: usize,
+}
+
+impl_dyn_hash!(DeconvUnary);
+
+impl DeconvUnary {
+    pub fn input_channels(&self) -> usize {
+        match self.kernel_format {
+            KernelFormat::OIHW => self.kernel.shape()[0],
+            KernelFormat::HWIO => self.kernel.shape()[self.kernel.rank() - 1],
+        }
+    }
+
+    pub fn output_channels(&self) -> usize {
+        match self.kernel_format {
+            KernelFormat::OIHW => self.kernel.shape()[1] * self.group,
+            KernelFormat::HWIO => self.kernel.shape()[self.kernel.rank() - 2] * self.group,
+        }
+    }
+
+    /// Kernel as a [1, group, output channels per group * kernel len, input channels per group]
+    /// matrix stack.
+    pub fn kernel_as_g_ok_i(&self) -> TractResult<Tensor> {
+        let kernel_len: usize = self.pool_spec.kernel_shape.iter().product();
+        let ci = self.input_channels() / self.group;
+        let co = self.output_channels() / self.group;
+        let kernel = self.kernel.clone().into_tensor();
+        let kernel = match self.kernel_format {
+            KernelFormat::OIHW => {
+                kernel.into_shape(&[self.group, ci, co, kernel_len])?.permute_axes(&[0, 2, 3, 1])?
+            }
+            KernelFormat::HWIO => {
+                kernel.into_shape(&[kernel_len, co, self.group, ci])?.permute_axes(&[2, 1, 0, 3])?
+            }
+        };
+        Ok(kernel.into_shape(&[1, self.group, co * kernel_len, ci])?)
+    }
+
+    /// Wire the deconvolution as a matrix product followed by a DeconvSum.
+    pub fn wire_with_deconv_sum(
+        &self,
+        name: &str,
+        target: &mut TypedModel,
+        input: OutletId,
+    ) -> TractResult<TVec<OutletId>> {
+        let input_shape = target.outlet_fact(input)?.shape.to_tvec();
+        let shape = self.pool_spec.data_format.shape(&*input_shape)?;
+        let geo_dim: TDim = shape.hw_dims().iter().maybe_product()?;
+        let kernel_len: usize = self.pool_spec.kernel_shape.iter().product();
+        let mut wire = tvec!(input);
+        if !self.pool_spec.data_format.has_n() {
+            wire = target.wire_node(format!("{}.add_n", name), AxisOp::Add(0), &wire)?;
+        }
+        if self.pool_spec.data_format.c_is_last() {
+            let rank = shape.hw_rank() + 2;
+            wire = target.wire_node(
+                format!("{}.channels_first", name),
+                AxisOp::Move(rank - 1, 1),
+                &wire,
+            )?;
+        }
+        let mut from: TVec<TDim> = tvec!(shape.c().clone());
+        from.extend(shape.hw_dims().iter().cloned());
+        wire = target.wire_node(
+            format!("{}.reshape_input", name),
+            AxisOp::Reshape(
+                1,
+                from,
+                tvec!(self.group.to_dim(), (self.input_channels() / self.group).to_dim(), geo_dim),
+            ),
+            &wire,
+        )?;
+        wire = target.wire_node(
+            format!("{}.matmul", name),
+            MatMulUnary::new(self.kernel_as_g_ok_i()?.into_arc_tensor(), false, false, false),
+            &wire,
+        )?;
+        wire = target.wire_node(
+            format!("{}.reshape_output", name),
+            AxisOp::Reshape(
+                1,
+                tvec!(
+                    self.group.to_dim(),
+                    (self.output_channels() / self.group * kernel_len).to_dim()
+                ),
+                tvec!(self.output_channels().to_dim(), kernel_len.to_dim()),
+            ),
+            &wire,
+        )?;
+        target.wire_node(
+            name,
+            DeconvSum::new(
+                self.pool_spec.clone(),
+                input_shape,
+                self.adjustments.clone(),
+                self.bias.clone(),
+            ),
+            &wire,
+        )
+    }
+}
+
+impl Op for DeconvUnary {
+    fn name(&self) -> Cow<str> {
+        "DeconvUnary".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        let mut info = self.pool_spec.info();
+        info.push(format!(
+            "Kernel {:?} (group: {}, adjustments: {:?})",
+            self.kernel_format, self.group, self.adjustments
+        ));
+        Ok(info)
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for DeconvUnary {
+    fn is_stateless(&self) -> bool {
+        false
+    }
+
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let mut state = self.state_for(inputs[0].datum_type(), inputs[0].shape())?;
+        state.run(inputs.into_iter().map(|t| t.into_tensor()).collect())
+    }
+
+    fn state(
+        &self,
+        _session: &mut SessionState,
+        _node_id: usize,
+    ) -> TractResult<Option<Box<dyn OpState>>> {
+        Ok(Some(Box::new(DeconvUnaryState::default())))
+    }
+}
+
+type DeconvPlanState = TypedSimpleState<TypedModel, Arc<TypedSimplePlan<TypedModel>>>;
+
+impl DeconvUnary {
+    /// State evaluating the deconvolution of inputs of a given type and shape.
+    fn state_for(&self, dt: DatumType, shape: &[usize]) -> TractResult<DeconvPlanState> {
+        let mut model = TypedModel::default();
+        let source = model.add_source("source", TypedFact::dt_shape(dt, shape))?;
+        let output = self.wire_with_deconv_sum("adhoc", &mut model, source)?;
+        model.set_output_outlets(&output)?;
+        DeconvPlanState::new(Arc::new(SimplePlan::new(model)?))
+    }
+}
+
+/// Keeps the matrix product and DeconvSum plan from one evaluation to the
+/// next, until the input shape changes.
+#[derive(Clone, Debug, Default)]
+struct DeconvUnaryState {
+    cached: Option<(DatumType, TVec<usize>, DeconvPlanState)>,
+}
+
+impl OpState for DeconvUnaryState {
+    fn eval(
+        &mut self,
+        _session: &mut SessionState,
+        op: &dyn Op,
+        inputs: TVec<Arc<Tensor>>,
+    ) -> TractResult<TVec<Arc<Tensor>>> {
+        let op = op.downcast_ref::<DeconvUnary>().context("Wrong op")?;
+        let (dt, shape) = (inputs[0].datum_type(), inputs[0].shape());
+        let hit = matches!(&self.cached, Some((d, s, _)) if *d == dt && &**s == shape);
+        if !hit {
+            self.cached = Some((dt, shape.into(), op.state_for(dt, shape)?));
+        }
+        let state = &mut self.cached.as_mut().unwrap().2;
+        state.run(inputs.into_iter().map(|t| t.into_tensor()).collect())
+    }
+
+    fn snapshot(&self) -> TractResult<TVec<Tensor>> {
+        Ok(tvec!())
+    }
+
+    fn restore(&mut self, _tensors: TVec<Tensor>) -> TractResult<()> {
+        Ok(())
+    }
+}
+
+impl TypedOp for DeconvUnary {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let x_shape = self.pool_spec.data_format.shape(&**inputs[0].shape)?;
+        if x_shape.c() != &self.input_channels().to_dim() {
+            bail!(
+                "Inconsistent deconvolution: input is {:?}, kernel expects {} input channels, {:?}",
+                inputs[0],
+                self.input_channels(),
+                self
+            );
+        }
+        if self.pool_spec.output_channel_override != Some(self.output_channels()) {
+            bail!(
+                "Inconsistent deconvolution: output channels from pool spec is {:?}, kernel expects {}",
+                self.pool_spec.output_channel_override,
+                self.output_channels(),
+            );
+        }
+        if let Some(bias) = &self.bias {
+            if bias.len() != self.output_channels() {
+                bail!("Bias should have one value per output channel, got:{:?}", bias);
+            }
+        }
+        let shape = super::output_shape(&self.pool_spec, &**inputs[0].shape, &self.adjustments)?;
+        Ok(tvec!(TypedFact::dt_shape(inputs[0].datum_type, &*shape)))
+    }
+
+    fn codegen(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        let mut patch = TypedModelPatch::default();
+        let input = patch.tap_model(model, node.inputs[0])?;
+        let output = self.wire_with_deconv_sum(&node.name, &mut patch, input)?;
+        patch.shunt_outside(model, node.id.into(), output[0])?;
+        Ok(Some(patch))
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::cnn::PaddingSpec;
+    use crate::ops::nn::DataFormat;
+
+    fn deconv_1d(padding: PaddingSpec, stride: usize) -> DeconvUnary {
+        // one input channel, two output channels, kernel of length 2
+        let kernel = tensor3(&[[[1f32, 2.], [10., 20.]]]);
+        let pool_spec =
+            PoolSpec::new(DataFormat::NCHW, tvec!(2), padding, None, Some(tvec!(stride)), Some(2));
+        DeconvUnary::new(pool_spec, KernelFormat::OIHW, kernel.into_arc_tensor(), None, tvec!(0), 1)
+    }
+
+    #[test]
+    fn valid_stride_1() -> TractResult<()> {
+        let op = deconv_1d(PaddingSpec::Valid, 1);
+        let output = op.eval(tvec!(tensor3(&[[[1f32, 1., 1.]]]).into_arc_tensor()))?;
+        assert_eq!(*output[0], tensor3(&[[[1f32, 3., 3., 2.], [10., 30., 30., 20.]]]));
+        Ok(())
+    }
+
+    #[test]
+    fn valid_stride_2() -> TractResult<()> {
+        let op = deconv_1d(PaddingSpec::Valid, 2);
+        let output = op.eval(tvec!(tensor3(&[[[1f32, 2.]]]).into_arc_tensor()))?;
+        assert_eq!(*output[0], tensor3(&[[[1f32, 2., 2., 4.], [10., 20., 20., 40.]]]));
+        Ok(())
+    }
+
+    #[test]
+    fn explicit_padding_and_bias() -> TractResult<()> {
+        let mut op = deconv_1d(PaddingSpec::Explicit(tvec!(1), tvec!(0), false), 1);
+        op.bias = Some(rctensor1(&[0.5f32, -0.5]));
+        let output = op.eval(tvec!(tensor3(&[[[1f32, 1., 1.]]]).into_arc_tensor()))?;
+        assert_eq!(*output[0], tensor3(&[[[3.5f32, 3.5, 2.5], [29.5, 29.5, 19.5]]]));
+        Ok(())
+    }
+
+    #[test]
+    fn nhwc_matches_nchw() -> TractResult<()> {
+        let op = deconv_1d(PaddingSpec::SameUpper, 2);
+        let input = tensor3(&[[[1f32, 2., 3.]]]);
+        let nchw = op.eval(tvec!(input.clone().into_arc_tensor()))?;
+        let op = DeconvUnary {
+            pool_spec: PoolSpec { data_format: DataFormat::NHWC, ..op.pool_spec.clone() },
+            ..op
+        };
+        let nhwc = op.eval(tvec!(input.permute_axes(&[0, 2, 1])?.into_arc_tensor()))?;
+        assert_eq!(nhwc[0].clone().into_tensor().permute_axes(&[0, 2, 1])?, *nchw[0]);
+        Ok(())
+    }
+}
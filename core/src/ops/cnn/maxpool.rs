@@ -15,6 +15,8 @@ pub struct MaxPool {
 impl_dyn_hash!(MaxPool);
 
 impl MaxPool {
+    // T only selects the datum types dispatch_floatlike! accepts
+    #[allow(clippy::extra_unused_type_parameters)]
     fn to_fixed<T: Datum + Float>(&self, input_shape: &[usize]) -> TractResult<Box<dyn TypedOp>> {
         let (input_shape, patch, output_shape) = self.pool_spec.compute_geo(input_shape)?;
         let op = MaxPoolFixed::new(patch, input_shape, output_shape, self.with_index_outputs);
@@ -1,4 +1,5 @@
 pub mod conv;
+pub mod deconv;
 mod maxpool;
 mod padding;
 mod patch_axis;
@@ -7,6 +8,7 @@ pub mod pools;
 mod sumpool;
 
 pub use self::conv::{ConvUnary, KernelFormat};
+pub use self::deconv::DeconvUnary;
 pub use self::maxpool::MaxPool;
 pub use self::padding::PaddingSpec;
 pub use self::patch_axis::PatchAxis;
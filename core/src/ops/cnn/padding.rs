@@ -1,19 +1,14 @@
 use crate::internal::*;
 
-#[derive(Debug, Clone, PartialEq, Hash)]
+#[derive(Debug, Clone, PartialEq, Hash, Default)]
 pub enum PaddingSpec {
     Explicit(TVec<usize>, TVec<usize>, bool),
+    #[default]
     Valid,
     SameUpper,
     SameLower,
 }
 
-impl Default for PaddingSpec {
-    fn default() -> PaddingSpec {
-        PaddingSpec::Valid
-    }
-}
-
 #[derive(Debug, Clone, new, PartialEq)]
 pub struct ComputedPaddedDim<D: DimLike> {
     pub output: D,
@@ -136,6 +131,63 @@ impl PaddingSpec {
         let (before, after) = if upper { (lower_pad, higher_pad) } else { (higher_pad, lower_pad) };
         ComputedPaddedDim::new(output, before, after)
     }
+
+    /// Output and padding of a transposed convolution, for each spatial axis.
+    ///
+    /// Pads are expressed in output space: they are cropped from the full
+    /// deconvolution result.
+    pub fn compute_for_deconv<D: DimLike>(
+        &self,
+        input_spatial_shape: &[D],
+        kernel_spatial_shape: &[usize],
+        dilations: &[usize],
+        strides: &[usize],
+        adjustments: &[usize],
+    ) -> TVec<ComputedPaddedDim<D>> {
+        (0..input_spatial_shape.len())
+            .map(|d| {
+                self.compute_one_for_deconv(
+                    d,
+                    &input_spatial_shape[d],
+                    kernel_spatial_shape[d],
+                    dilations[d],
+                    strides[d],
+                    adjustments[d],
+                )
+            })
+            .collect()
+    }
+
+    pub fn compute_one_for_deconv<D: DimLike>(
+        &self,
+        axis: usize,
+        input: &D,
+        kernel: usize,
+        dilation: usize,
+        stride: usize,
+        adjustment: usize,
+    ) -> ComputedPaddedDim<D> {
+        let kernel_field = (kernel - 1) * dilation + 1;
+        let full = (input.clone() - 1) * stride + kernel_field + adjustment;
+        match self {
+            PaddingSpec::Valid => ComputedPaddedDim::new(full, 0.into(), 0.into()),
+            PaddingSpec::Explicit(ref bef, ref aft, _) => ComputedPaddedDim::new(
+                full - bef[axis] - aft[axis],
+                bef[axis].into(),
+                aft[axis].into(),
+            ),
+            PaddingSpec::SameUpper | PaddingSpec::SameLower => {
+                let output = input.clone() * stride + adjustment;
+                let pad = kernel_field.saturating_sub(stride);
+                let (before, after) = if let PaddingSpec::SameUpper = self {
+                    (pad / 2, pad - pad / 2)
+                } else {
+                    (pad - pad / 2, pad / 2)
+                };
+                ComputedPaddedDim::new(output, before.into(), after.into())
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -196,4 +248,24 @@ mod tests {
     fn same_upper() {
         assert_eq!(PaddingSpec::same(&7usize, 1usize, 1, 2, true), ComputedPaddedDim::new(4, 0, 0));
     }
+
+    #[test]
+    fn deconv_same_stride_2() {
+        assert_eq!(
+            PaddingSpec::SameUpper.compute_one_for_deconv(0, &3usize, 3, 1, 2, 0),
+            ComputedPaddedDim::new(6, 0, 1)
+        );
+        assert_eq!(
+            PaddingSpec::SameLower.compute_one_for_deconv(0, &3usize, 3, 1, 2, 0),
+            ComputedPaddedDim::new(6, 1, 0)
+        );
+    }
+
+    #[test]
+    fn deconv_same_adjustment_grows_output() {
+        assert_eq!(
+            PaddingSpec::SameUpper.compute_one_for_deconv(0, &3usize, 3, 1, 2, 1),
+            ComputedPaddedDim::new(7, 0, 1)
+        );
+    }
 }
@@ -7,7 +7,8 @@ use super::PatchAxis;
 
 use std::ops::Range;
 
-use tract_itertools::{izip, zip, Itertools};
+use std::iter::zip;
+use tract_itertools::{izip, Itertools};
 
 #[derive(Debug, Clone, PartialEq, Hash)]
 pub struct PatchSpec {
@@ -97,7 +98,7 @@ impl PatchSpec {
 
         fn strides(shape: &[usize], inner: usize) -> TVec<isize> {
             let mut strides: TVec<isize> = tvec![inner as isize];
-            for dim in shape.into_iter().skip(1).rev() {
+            for dim in shape.iter().skip(1).rev() {
                 let previous = *strides.last().unwrap();
                 strides.push(*dim as isize * previous);
             }
@@ -187,7 +188,7 @@ impl PatchSpec {
         }
 
         let op_strides_times_input_storage_strides =
-            zip(&self.strides, &input_layout_strides).map(|(a, b)| (*a as isize * b)).collect();
+            zip(&self.strides, &input_layout_strides).map(|(a, b)| *a as isize * b).collect();
 
         Patch {
             spec: self,
@@ -456,9 +457,9 @@ impl<'p> Iterator for PatchIterator<'p> {
     type Item = Option<isize>;
     #[inline(always)]
     fn next(&mut self) -> Option<Option<isize>> {
-        match self {
-            &mut PatchIterator::Fast(ref mut it) => it.next(),
-            &mut PatchIterator::Safe(ref mut it) => it.next(),
+        match *self {
+            PatchIterator::Fast(ref mut it) => it.next(),
+            PatchIterator::Safe(ref mut it) => it.next(),
         }
     }
 }
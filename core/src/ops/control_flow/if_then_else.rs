@@ -0,0 +1,288 @@
+use crate::internal::*;
+
+/// Evaluate one of two sub-models depending on a scalar boolean condition.
+///
+/// Input 0 is the condition. Each body input is fed from the outer input
+/// designated by the matching entry of its input mapping.
+#[derive(Debug, Clone, Default, Hash)]
+pub struct IfThenElse {
+    pub then_body: TypedModel,
+    pub then_input_mapping: Vec<usize>,
+    pub else_body: TypedModel,
+    pub else_input_mapping: Vec<usize>,
+    /// Output shapes, unified over both branches.
+    pub output_shapes: Vec<TVec<TDim>>,
+}
+
+impl_dyn_hash!(IfThenElse);
+
+impl IfThenElse {
+    /// Dimensions differing between the branches outputs are replaced by fresh symbols.
+    pub fn new(
+        then_body: TypedModel,
+        then_input_mapping: Vec<usize>,
+        else_body: TypedModel,
+        else_input_mapping: Vec<usize>,
+        symbols: &SymbolScope,
+    ) -> TractResult<IfThenElse> {
+        let then_outputs = then_body.output_outlets()?.len();
+        let else_outputs = else_body.output_outlets()?.len();
+        if then_outputs != else_outputs {
+            bail!("Then branch has {} outputs, else branch has {}", then_outputs, else_outputs);
+        }
+        let output_shapes = (0..then_outputs)
+            .map(|ix| {
+                let then_fact = then_body.output_fact(ix)?;
+                let else_fact = else_body.output_fact(ix)?;
+                if then_fact.datum_type != else_fact.datum_type
+                    || then_fact.rank() != else_fact.rank()
+                {
+                    bail!(
+                        "Branches output #{} mismatch: then {:?}, else {:?}",
+                        ix,
+                        then_fact,
+                        else_fact
+                    );
+                }
+                Ok(then_fact
+                    .shape
+                    .iter()
+                    .zip(else_fact.shape.iter())
+                    .map(
+                        |(t, e)| {
+                            if t == e {
+                                t.clone()
+                            } else {
+                                symbols.new_with_prefix("if").into()
+                            }
+                        },
+                    )
+                    .collect())
+            })
+            .collect::<TractResult<_>>()?;
+        Ok(IfThenElse {
+            then_body,
+            then_input_mapping,
+            else_body,
+            else_input_mapping,
+            output_shapes,
+        })
+    }
+
+    fn declutter_const_condition(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        let cond = if let Some(cond) = &model.outlet_fact(node.inputs[0])?.konst {
+            *cond.to_scalar::<bool>()?
+        } else {
+            return Ok(None);
+        };
+        let (body, input_mapping) = if cond {
+            (&self.then_body, &self.then_input_mapping)
+        } else {
+            (&self.else_body, &self.else_input_mapping)
+        };
+        let mut patch = TypedModelPatch::default();
+        let inputs = input_mapping
+            .iter()
+            .map(|slot| patch.tap_model(model, node.inputs[*slot]))
+            .collect::<TractResult<TVec<_>>>()?;
+        let outputs = super::wire_body(&mut patch, &node.name, body, &inputs)?;
+        for (ix, output) in outputs.into_iter().enumerate() {
+            patch.shunt_outside(model, OutletId::new(node.id, ix), output)?;
+        }
+        Ok(Some(patch))
+    }
+}
+
+impl Op for IfThenElse {
+    fn name(&self) -> Cow<str> {
+        "IfThenElse".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![
+            format!("then inputs: {:?}", self.then_input_mapping),
+            format!("else inputs: {:?}", self.else_input_mapping),
+        ])
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for IfThenElse {
+    fn is_stateless(&self) -> bool {
+        false
+    }
+
+    fn state(
+        &self,
+        _session: &mut SessionState,
+        _node_id: usize,
+    ) -> TractResult<Option<Box<dyn OpState>>> {
+        Ok(Some(Box::new(IfThenElseState {
+            then_state: TypedSimpleState::new(Arc::new(TypedSimplePlan::new(
+                self.then_body.clone(),
+            )?))?,
+            else_state: TypedSimpleState::new(Arc::new(TypedSimplePlan::new(
+                self.else_body.clone(),
+            )?))?,
+        })))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct IfThenElseState {
+    then_state: TypedSimpleState<TypedModel, Arc<TypedSimplePlan<TypedModel>>>,
+    else_state: TypedSimpleState<TypedModel, Arc<TypedSimplePlan<TypedModel>>>,
+}
+
+impl OpState for IfThenElseState {
+    fn eval(
+        &mut self,
+        _session: &mut SessionState,
+        op: &dyn Op,
+        inputs: TVec<Arc<Tensor>>,
+    ) -> TractResult<TVec<Arc<Tensor>>> {
+        let op = op.downcast_ref::<IfThenElse>().context("Wrong op")?;
+        let cond = *inputs[0].to_scalar::<bool>()?;
+        let (state, input_mapping) = if cond {
+            (&mut self.then_state, &op.then_input_mapping)
+        } else {
+            (&mut self.else_state, &op.else_input_mapping)
+        };
+        let body_inputs =
+            input_mapping.iter().map(|slot| inputs[*slot].clone().into_tensor()).collect();
+        state.run(body_inputs)
+    }
+
+    fn snapshot(&self) -> TractResult<TVec<Tensor>> {
+        if !self.then_state.snapshot()?.states.is_empty()
+            || !self.else_state.snapshot()?.states.is_empty()
+        {
+            bail!("Can not snapshot an IfThenElse with stateful branches");
+        }
+        Ok(tvec!())
+    }
+
+    fn restore(&mut self, _tensors: TVec<Tensor>) -> TractResult<()> {
+        Ok(())
+    }
+}
+
+impl TypedOp for IfThenElse {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        if inputs[0].datum_type != bool::datum_type() || inputs[0].rank() != 0 {
+            bail!("IfThenElse condition must be a boolean scalar, got {:?}", inputs[0]);
+        }
+        if self.then_body.output_outlets()?.len() != self.output_shapes.len() {
+            bail!(
+                "Then branch has {} outputs, expected {}",
+                self.then_body.output_outlets()?.len(),
+                self.output_shapes.len()
+            );
+        }
+        self.output_shapes
+            .iter()
+            .enumerate()
+            .map(|(ix, shape)| {
+                let datum_type = self.then_body.output_fact(ix)?.datum_type;
+                Ok(TypedFact::dt_shape(datum_type, &**shape))
+            })
+            .collect()
+    }
+
+    fn declutter(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        self.declutter_const_condition(model, node)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::math;
+
+    fn branch(k: f32) -> TractResult<TypedModel> {
+        let mut body = TypedModel::default();
+        let x = body.add_source("x", TypedFact::dt_shape(f32::datum_type(), &[2]))?;
+        let k = body.add_const("k", tensor1(&[k, k]))?;
+        let y = body.wire_node("mul", math::mul::bin_typed(), &[x, k])?;
+        body.set_output_outlets(&y)?;
+        Ok(body)
+    }
+
+    fn constant_branch(t: Tensor) -> TractResult<TypedModel> {
+        let mut body = TypedModel::default();
+        let _x = body.add_source("x", TypedFact::dt_shape(f32::datum_type(), &[2]))?;
+        let c = body.add_const("c", t)?;
+        body.set_output_outlets(&[c])?;
+        Ok(body)
+    }
+
+    fn model(cond: Option<bool>) -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let cond = if let Some(c) = cond {
+            model.add_const("cond", tensor0(c))?
+        } else {
+            model.add_source("cond", TypedFact::dt_scalar(bool::datum_type()))?
+        };
+        let x = model.add_source("x", TypedFact::dt_shape(f32::datum_type(), &[2]))?;
+        let op =
+            IfThenElse::new(branch(2.0)?, vec![1], branch(3.0)?, vec![1], &model.symbol_table)?;
+        let y = model.wire_node("if", op, &[cond, x])?;
+        model.set_output_outlets(&y)?;
+        Ok(model)
+    }
+
+    #[test]
+    fn eval() -> TractResult<()> {
+        let plan = SimplePlan::new(model(None)?)?;
+        let x = tensor1(&[1f32, 2.]);
+        assert_eq!(*plan.run(tvec!(tensor0(true), x.clone()))?[0], tensor1(&[2f32, 4.]));
+        assert_eq!(*plan.run(tvec!(tensor0(false), x))?[0], tensor1(&[3f32, 6.]));
+        Ok(())
+    }
+
+    #[test]
+    fn fold_const_condition() -> TractResult<()> {
+        let model = model(Some(false))?.declutter()?;
+        assert!(model.nodes().iter().all(|n| !n.op_is::<IfThenElse>()));
+        let output = model.into_runnable()?.run(tvec!(tensor1(&[1f32, 2.])))?;
+        assert_eq!(*output[0], tensor1(&[3f32, 6.]));
+        Ok(())
+    }
+
+    #[test]
+    fn branches_with_different_shapes() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let cond = model.add_source("cond", TypedFact::dt_scalar(bool::datum_type()))?;
+        let x = model.add_source("x", TypedFact::dt_shape(f32::datum_type(), &[2]))?;
+        let op = IfThenElse::new(
+            constant_branch(tensor2(&[[1f32, 2.]]))?,
+            vec![1],
+            constant_branch(tensor2(&[[3f32], [4.]]))?,
+            vec![1],
+            &model.symbol_table,
+        )?;
+        let y = model.wire_node("if", op, &[cond, x])?;
+        model.set_output_outlets(&y)?;
+        let shape = &model.outlet_fact(y[0])?.shape;
+        assert_eq!(shape.rank(), 2);
+        assert!(shape[0].to_usize().is_err() && shape[1].to_usize().is_err());
+        assert_ne!(shape[0], shape[1]);
+        let plan = SimplePlan::new(model)?;
+        let x = tensor1(&[1f32, 2.]);
+        assert_eq!(*plan.run(tvec!(tensor0(true), x.clone()))?[0], tensor2(&[[1f32, 2.]]));
+        assert_eq!(*plan.run(tvec!(tensor0(false), x))?[0], tensor2(&[[3f32], [4.]]));
+        Ok(())
+    }
+}
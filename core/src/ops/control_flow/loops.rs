@@ -0,0 +1,336 @@
+use crate::internal::*;
+use crate::ops::scan::{InputMapping, OutputMapping, Scan, StateInitializer};
+use crate::ops::source::TypedSource;
+
+/// A generic loop, with a trip count and a continuation condition.
+///
+/// Outer inputs are: trip count (i64 scalar), initial condition (bool scalar),
+/// `state_count` loop-carried values, then closure values.
+///
+/// Body inputs are: iteration number (i64 scalar), condition, loop-carried
+/// values, then closure values. Body outputs are: condition, loop-carried
+/// values, then values to be stacked along a new leading axis.
+///
+/// Outer outputs are the final loop-carried values followed by the stacked
+/// scan outputs.
+#[derive(Debug, Clone, Hash)]
+pub struct Loop {
+    pub body: TypedModel,
+    pub state_count: usize,
+    /// Iteration count used for stacked outputs when it can not be computed
+    /// from the trip count.
+    pub iters: Symbol,
+}
+
+impl_dyn_hash!(Loop);
+
+impl Loop {
+    pub fn new(body: TypedModel, state_count: usize, iters: Symbol) -> Loop {
+        Loop { body, state_count, iters }
+    }
+
+    fn scan_output_count(&self) -> TractResult<usize> {
+        Ok(self.body.output_outlets()?.len() - 1 - self.state_count)
+    }
+
+    /// True if the body never requests an early exit.
+    fn body_always_continues(&self) -> TractResult<bool> {
+        let cond_out = self.body.output_outlets()?[0];
+        if cond_out == self.body.input_outlets()?[1] {
+            return Ok(true);
+        }
+        Ok(self.body.outlet_fact(cond_out)?.konst.as_ref().map(|k| k.to_scalar::<bool>().ok())
+            == Some(Some(&true)))
+    }
+
+    /// Trip count, if known statically and not cut short by the condition.
+    fn static_trip_count(&self, inputs: &[&TypedFact]) -> TractResult<Option<usize>> {
+        let trip_count = if let Some(k) = &inputs[0].konst {
+            k.cast_to_scalar::<i64>()?
+        } else {
+            return Ok(None);
+        };
+        let cond = if let Some(k) = &inputs[1].konst { *k.to_scalar::<bool>()? } else { false };
+        if cond && self.body_always_continues()? && trip_count < i64::MAX {
+            Ok(Some(trip_count.max(0) as usize))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn declutter_as_scan(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        let inputs = model.node_input_facts(node.id)?;
+        let trip_count = if let Some(t) = self.static_trip_count(&inputs)? {
+            t
+        } else {
+            return Ok(None);
+        };
+        if trip_count == 0 {
+            return Ok(None);
+        }
+        // a scan state keeps its type and shape from one iteration to the next
+        for ix in 0..self.state_count {
+            let input = self.body.input_fact(2 + ix)?;
+            let output = self.body.output_fact(1 + ix)?;
+            if input.datum_type != output.datum_type || input.shape != output.shape {
+                return Ok(None);
+            }
+        }
+        let mut body = self.body.clone();
+        let iter_input = body.input_outlets()?[0];
+        let iter_scalar = body.outlet_fact(iter_input)?.clone();
+        let patch = TypedModelPatch::intercept(
+            &body,
+            iter_input,
+            format!("{}.iter-rm-axis", node.name),
+            AxisOp::Rm(0),
+            iter_scalar,
+        )?;
+        patch.apply(&mut body)?;
+        let iter_fact = TypedFact::dt_shape(i64::datum_type(), &[1]);
+        body.node_mut(iter_input.node).op_as_mut::<TypedSource>().unwrap().fact = iter_fact.clone();
+        body.set_outlet_fact(iter_input, iter_fact)?;
+        let scan_outputs = self.scan_output_count()?;
+        for ix in 0..scan_outputs {
+            let outlet = body.output_outlets()?[1 + self.state_count + ix];
+            let wire = body.wire_node(
+                format!("{}.scan-add-axis-{}", node.name, ix),
+                AxisOp::Add(0),
+                &[outlet],
+            )?;
+            let mut outputs = body.output_outlets()?.to_vec();
+            outputs[1 + self.state_count + ix] = wire[0];
+            body.set_output_outlets(&outputs)?;
+        }
+        // the condition is known to stay true, the scan does not need it as an output
+        let mut outputs = body.output_outlets()?.to_vec();
+        outputs.remove(0);
+        body.set_output_outlets(&outputs)?;
+        let closures = body.input_outlets()?.len() - 2 - self.state_count;
+        let mut input_mapping =
+            vec![InputMapping::Scan { slot: 0, axis: 0, chunk: 1 }, InputMapping::Full { slot: 1 }];
+        for ix in 0..self.state_count {
+            input_mapping
+                .push(InputMapping::State { initializer: StateInitializer::FromInput(2 + ix) });
+        }
+        for ix in 0..closures {
+            input_mapping.push(InputMapping::Full { slot: 2 + self.state_count + ix });
+        }
+        let mut output_mapping = vec![];
+        for ix in 0..self.state_count {
+            output_mapping.push(OutputMapping {
+                full_slot: None,
+                axis: 0,
+                chunk: 1,
+                full_dim_hint: None,
+                last_value_slot: Some(ix),
+                state: true,
+            });
+        }
+        for ix in 0..scan_outputs {
+            output_mapping.push(OutputMapping {
+                full_slot: Some(self.state_count + ix),
+                axis: 0,
+                chunk: 1,
+                full_dim_hint: None,
+                last_value_slot: None,
+                state: false,
+            });
+        }
+        let scan = Scan::new(body, input_mapping, output_mapping, None, 0)?;
+
+        let mut patch = TypedModelPatch::default();
+        let iters = patch.add_const(
+            format!("{}.iters", node.name),
+            tensor1(&(0..trip_count as i64).collect::<Vec<_>>()),
+        )?;
+        let mut scan_inputs = tvec!(iters);
+        for input in &node.inputs[1..] {
+            scan_inputs.push(patch.tap_model(model, *input)?);
+        }
+        let outputs = patch.wire_node(&*node.name, scan, &scan_inputs)?;
+        for (ix, output) in outputs.into_iter().enumerate() {
+            patch.shunt_outside(model, OutletId::new(node.id, ix), output)?;
+        }
+        Ok(Some(patch))
+    }
+}
+
+impl Op for Loop {
+    fn name(&self) -> Cow<str> {
+        "Loop".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!(
+            "{} loop-carried values, {} scan outputs",
+            self.state_count,
+            self.scan_output_count()?
+        )])
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for Loop {
+    fn is_stateless(&self) -> bool {
+        false
+    }
+
+    fn state(
+        &self,
+        _session: &mut SessionState,
+        _node_id: usize,
+    ) -> TractResult<Option<Box<dyn OpState>>> {
+        Ok(Some(Box::new(LoopState {
+            body_state: TypedSimpleState::new(Arc::new(TypedSimplePlan::new(self.body.clone())?))?,
+        })))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct LoopState {
+    body_state: TypedSimpleState<TypedModel, Arc<TypedSimplePlan<TypedModel>>>,
+}
+
+impl OpState for LoopState {
+    fn eval(
+        &mut self,
+        _session: &mut SessionState,
+        op: &dyn Op,
+        inputs: TVec<Arc<Tensor>>,
+    ) -> TractResult<TVec<Arc<Tensor>>> {
+        let op = op.downcast_ref::<Loop>().context("Wrong op")?;
+        let trip_count = inputs[0].cast_to_scalar::<i64>()?;
+        let mut cond = *inputs[1].to_scalar::<bool>()?;
+        let mut state: TVec<Arc<Tensor>> = inputs[2..][..op.state_count].into();
+        let closures = &inputs[2 + op.state_count..];
+        let mut scans: Vec<Vec<Arc<Tensor>>> = vec![vec![]; op.scan_output_count()?];
+        let mut i = 0i64;
+        while i < trip_count && cond {
+            let mut body_inputs: TVec<Tensor> = tvec!(tensor0(i), tensor0(cond));
+            body_inputs.extend(state.drain(..).map(|t| t.into_tensor()));
+            body_inputs.extend(closures.iter().map(|t| t.clone().into_tensor()));
+            let mut outputs = self.body_state.run(body_inputs).context("Evaluating loop body")?;
+            let scan_values = outputs.drain(1 + op.state_count..).collect::<TVec<_>>();
+            cond = *outputs[0].to_scalar::<bool>()?;
+            state.extend(outputs.drain(1..));
+            for (scan, value) in scans.iter_mut().zip(scan_values.into_iter()) {
+                scan.push(value);
+            }
+            i += 1;
+        }
+        let mut outputs = state;
+        for (ix, scan) in scans.into_iter().enumerate() {
+            let stacked = if scan.len() > 0 {
+                let views = scan
+                    .iter()
+                    .map(|t| t.as_ref().clone().into_shape(&[&[1], t.shape()].concat()))
+                    .collect::<TractResult<Vec<_>>>()?;
+                Tensor::stack_tensors(0, &views)?
+            } else {
+                let fact = op.body.output_fact(1 + op.state_count + ix)?;
+                let shape =
+                    fact.shape.iter().map(|d| d.to_usize().unwrap_or(0)).collect::<Vec<_>>();
+                Tensor::zero_dt(fact.datum_type, &[&[0], &*shape].concat())?
+            };
+            outputs.push(stacked.into_arc_tensor());
+        }
+        Ok(outputs)
+    }
+
+    fn snapshot(&self) -> TractResult<TVec<Tensor>> {
+        if !self.body_state.snapshot()?.states.is_empty() {
+            bail!("Can not snapshot a Loop with a stateful body");
+        }
+        Ok(tvec!())
+    }
+
+    fn restore(&mut self, _tensors: TVec<Tensor>) -> TractResult<()> {
+        Ok(())
+    }
+}
+
+impl TypedOp for Loop {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        if inputs.len() < 2 + self.state_count {
+            bail!("Loop expects at least {} inputs, got {}", 2 + self.state_count, inputs.len());
+        }
+        let iters: TDim = if let Some(t) = self.static_trip_count(inputs)? {
+            t.into()
+        } else {
+            (&self.iters).into()
+        };
+        let mut facts = tvec!();
+        for ix in 0..self.state_count {
+            let fact = self.body.output_fact(1 + ix)?;
+            facts.push(TypedFact::dt_shape(fact.datum_type, fact.shape.clone()));
+        }
+        for ix in 0..self.scan_output_count()? {
+            let fact = self.body.output_fact(1 + self.state_count + ix)?;
+            let mut shape = fact.shape.clone();
+            shape.insert_axis(0)?;
+            shape.set(0, iters.clone());
+            facts.push(TypedFact::dt_shape(fact.datum_type, shape));
+        }
+        Ok(facts)
+    }
+
+    fn declutter(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        self.declutter_as_scan(model, node)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::math;
+
+    // acc = acc + x at each iteration, also stack acc
+    fn model() -> TractResult<TypedModel> {
+        let mut body = TypedModel::default();
+        let _iter = body.add_source("iter", TypedFact::dt_scalar(i64::datum_type()))?;
+        let cond = body.add_source("cond", TypedFact::dt_scalar(bool::datum_type()))?;
+        let acc = body.add_source("acc", TypedFact::dt_shape(f32::datum_type(), &[2]))?;
+        let x = body.add_source("x", TypedFact::dt_shape(f32::datum_type(), &[2]))?;
+        let sum = body.wire_node("add", math::add::bin_typed(), &[acc, x])?[0];
+        body.set_output_outlets(&[cond, sum, sum])?;
+
+        let mut model = TypedModel::default();
+        let trip = model.add_const("trip", tensor0(3i64))?;
+        let cond = model.add_const("cond", tensor0(true))?;
+        let acc = model.add_source("acc", TypedFact::dt_shape(f32::datum_type(), &[2]))?;
+        let x = model.add_source("x", TypedFact::dt_shape(f32::datum_type(), &[2]))?;
+        let iters = model.symbol_table.new_with_prefix("L");
+        let outputs = model.wire_node("loop", Loop::new(body, 1, iters), &[trip, cond, acc, x])?;
+        model.set_output_outlets(&outputs)?;
+        Ok(model)
+    }
+
+    #[test]
+    fn eval_and_declutter_to_scan() -> TractResult<()> {
+        let model = model()?;
+        assert_eq!(model.output_fact(1)?.shape, ShapeFact::from(&[3, 2]));
+        let inputs = tvec!(tensor1(&[0f32, 1.]), tensor1(&[1f32, 2.]));
+        let expected = model.clone().into_runnable()?.run(inputs.clone())?;
+        assert_eq!(*expected[0], tensor1(&[3f32, 7.]));
+        assert_eq!(*expected[1], tensor2(&[[1f32, 3.], [2., 5.], [3., 7.]]));
+        let decluttered = model.declutter()?;
+        assert!(decluttered.nodes().iter().any(|n| n.op_is::<Scan>()));
+        assert!(decluttered.nodes().iter().all(|n| !n.op_is::<Loop>()));
+        let found = decluttered.into_runnable()?.run(inputs)?;
+        assert_eq!(found, expected);
+        Ok(())
+    }
+}
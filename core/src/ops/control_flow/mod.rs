@@ -0,0 +1,35 @@
+use crate::internal::*;
+
+mod if_then_else;
+mod loops;
+
+pub use if_then_else::IfThenElse;
+pub use loops::Loop;
+
+/// Inline the nodes of `body` in `patch`, feeding its inputs from `inputs`.
+///
+/// Returns the patch outlets matching the body outputs.
+pub(crate) fn wire_body(
+    patch: &mut TypedModelPatch,
+    prefix: &str,
+    body: &TypedModel,
+    inputs: &[OutletId],
+) -> TractResult<TVec<OutletId>> {
+    let mut mapping: HashMap<OutletId, OutletId> = HashMap::new();
+    for (ix, input) in body.input_outlets()?.iter().enumerate() {
+        mapping.insert(*input, inputs[ix]);
+    }
+    for n in body.eval_order()? {
+        if body.input_outlets()?.iter().any(|i| i.node == n) {
+            continue;
+        }
+        let node = body.node(n);
+        let node_inputs = node.inputs.iter().map(|i| mapping[i]).collect::<TVec<_>>();
+        let wires =
+            patch.wire_node(format!("{}.{}", prefix, node.name), node.op.clone(), &node_inputs)?;
+        for (ix, w) in wires.into_iter().enumerate() {
+            mapping.insert(OutletId::new(n, ix), w);
+        }
+    }
+    Ok(body.output_outlets()?.iter().map(|o| mapping[o]).collect())
+}
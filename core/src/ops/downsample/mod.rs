@@ -15,7 +15,7 @@ pub struct Downsample {
 
 impl Downsample {
     pub(crate) fn transform_dim(&self, input_dim: &TDim) -> TDim {
-        (input_dim.clone() - self.modulo).div_ceil(self.stride.abs() as _)
+        (input_dim.clone() - self.modulo).div_ceil(self.stride.unsigned_abs() as _)
     }
 
     pub(crate) fn transform_fact(&self, input_fact: &TypedFact) -> TractResult<TypedFact> {
@@ -51,8 +51,8 @@ pub fn pull_downsample_over_scan(
 
     let inputs = inner_model.input_outlets()?.to_vec();
     for input in inputs {
-        let ref mut node = inner_model.node_mut(input.node);
-        let ref mut fact = node.outputs[0].fact;
+        let node = &mut inner_model.node_mut(input.node);
+        let fact = &mut node.outputs[0].fact;
         *fact = down_op.transform_fact(fact)?;
         node.op_as_mut::<crate::ops::source::TypedSource>().unwrap().fact = fact.clone();
         let downsamples = inner_model.node(input.node).outputs[0].successors.clone();
@@ -79,21 +79,23 @@ pub fn pull_downsample_over_scan(
                 }
             }
             InputMapping::Scan { ref mut chunk, .. } => {
-                if *chunk > 0 && *chunk as usize % down_op.stride as usize != 0 {
+                if *chunk > 0 && !(*chunk as usize).is_multiple_of(down_op.stride as usize) {
                     return Ok(None);
                 }
-                *chunk = (chunk.abs() as usize).div_ceil(down_op.stride as usize) as isize
-                    * chunk.signum()
+                *chunk =
+                    chunk.unsigned_abs().div_ceil(down_op.stride as usize) as isize * chunk.signum()
             }
             _ => (),
         }
     }
     for output in &mut new_scan.output_mapping {
-        if output.chunk as usize % down_op.stride as usize != 0 {
+        if !(output.chunk as usize).is_multiple_of(down_op.stride as usize) {
             return Ok(None);
         }
-        output.full_dim_hint.as_mut().map(|d| *d = down_op.transform_dim(d));
-        output.chunk = (output.chunk.abs() as usize).div_ceil(down_op.stride as usize) as isize
+        if let Some(d) = output.full_dim_hint.as_mut() {
+            *d = down_op.transform_dim(d);
+        }
+        output.chunk = output.chunk.unsigned_abs().div_ceil(down_op.stride as usize) as isize
             * output.chunk.signum()
     }
 
@@ -1,6 +1,6 @@
 use crate::internal::*;
-use tract_itertools::Itertools;
 use std::fmt;
+use tract_itertools::Itertools;
 
 #[derive(Clone, Default)]
 pub struct Invariants {
@@ -140,10 +140,10 @@ impl Invariants {
             self.axes
                 .iter()
                 .find(|connection| {
-                    connection.outputs.get(0) == Some(&Some(axis)) && connection.period == 1
+                    connection.outputs.first() == Some(&Some(axis)) && connection.period == 1
                 })
                 .filter(|conn| conn.disposable || !only_disposable)
-                .and_then(|connection| connection.inputs.get(0))
+                .and_then(|connection| connection.inputs.first())
                 .and_then(|d| *d)
         }
     }
@@ -156,10 +156,10 @@ impl Invariants {
             self.axes
                 .iter()
                 .find(|connection| {
-                    connection.inputs.get(0) == Some(&Some(axis)) && connection.period == 1
+                    connection.inputs.first() == Some(&Some(axis)) && connection.period == 1
                 })
                 .filter(|conn| conn.disposable || !only_disposable)
-                .and_then(|connection| connection.outputs.get(0))
+                .and_then(|connection| connection.outputs.first())
                 .and_then(|d| *d)
         }
     }
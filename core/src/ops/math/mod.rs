@@ -119,17 +119,17 @@ bin_to_super_type!(rem, Rem,
                    [f32, i8, i16, i32, i64, u8, u16, u32, u64, f16, f64] => |c, a, b| *c = a.clone() % b);
 
 bin_to_super_type!(min, Min, flip:commute,
-                   [f32, f64] => |c,a,b| *c = a.min(*b),
+                   [f16, f32, f64] => |c,a,b| *c = a.min(*b),
                    [i8, i16, i32, i64, u8, u16, u32, u64] => |c, a, b| *c = *a.min(b));
 bin_to_super_type!(max, Max, flip:commute,
-                   [f32, f64] => |c,a,b| *c = a.max(*b),
+                   [f16, f32, f64] => |c,a,b| *c = a.max(*b),
                    [i8, i16, i32, i64, u8, u16, u32, u64] => |c, a, b| *c = *a.max(b));
 
 bin_to_super_type!(pow, Pow,
-                   [f32, f64] => |c,a,b| *c = a.powf(*b),
+                   [f16, f32, f64] => |c,a,b| *c = a.powf(*b),
                    [i32, i64] => |c,a,b| *c = a.pow(*b as u32));
 bin_to_super_type!(flipped_pow, FlippedPow,
-                   [f32, f64] => |c,a,b| *c = b.powf(*a),
+                   [f16, f32, f64] => |c,a,b| *c = b.powf(*a),
                    [i32, i64] => |c,a,b| *c = b.pow(*a as u32));
 
 bin_to_super_type!(shift_left, ShiftLeft,
@@ -193,7 +193,8 @@ fn declutter_unary_mul_magic_values(
     {
         return Ok(Some(TypedModelPatch::shunt_one_op(model, node)?));
     } else if a.is_uniform() && a.cast_to_scalar::<f64>()?.is_zero() {
-        let fact = model.outlet_fact(node.inputs[0])?;
+        // the constant may broadcast the input: the output fact has the actual shape
+        let fact = &node.outputs[0].fact;
         let zero = Tensor::zero_dt(fact.datum_type, &[])?;
         Ok(Some(TypedModelPatch::replace_single_op(
             model,
@@ -238,7 +239,7 @@ fn declutter_div_as_shift(
     if let Some(a) = &a.konst {
         declutter_as_shift(model, node, a, Box::new(FlippedShiftRight))
     } else {
-        return Ok(None);
+        Ok(None)
     }
 }
 
@@ -426,7 +427,8 @@ element_wise!(sinh, Sinh, [f16, f32, f64] => |_, xs| {
 
 element_wise!(tanh, Tanh,
  [f32] => |_, xs| { (tract_linalg::ops().tanh_f32)().run(xs) },
- [f16, f64] => |_, xs| { xs.iter_mut().for_each(|x| *x = x.tanh()); Ok(()) };
+ [f16] => |_, xs| { (tract_linalg::ops().tanh_f16)().run(xs) },
+ [f64] => |_, xs| { xs.iter_mut().for_each(|x| *x = x.tanh()); Ok(()) };
  cost: |dt| {tvec!((Cost::FMA(dt), 11), (Cost::Div(dt), 1))}
 );
 
@@ -479,6 +481,19 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn mul_by_broadcasting_zeros() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("a", TypedFact::dt_shape(i32::datum_type(), &[1usize, 1]))?;
+        let y = model.wire_node("c", mul::unary(rctensor2(&[[0, 0]])), [x].as_ref())?[0];
+        model.set_output_outlets(&[y])?;
+        let decluttered = model.declutter()?;
+        assert_eq!(decluttered.output_fact(0)?.shape.as_concrete(), Some(&[1usize, 2][..]));
+        let result = SimplePlan::new(&decluttered)?.run(tvec!(tensor2(&[[3]])))?;
+        assert_eq!(result[0], rctensor2(&[[0, 0]]));
+        Ok(())
+    }
+
     #[test]
     fn div_as_shift() -> TractResult<()> {
         let mut model = TypedModel::default();
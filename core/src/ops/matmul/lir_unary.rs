@@ -67,6 +67,7 @@ impl LirMatMulUnary {
     }
 }
 
+#[allow(clippy::borrowed_box)]
 fn hash_mmm<H: std::hash::Hasher>(mmm: &Box<dyn MatMatMul>, state: &mut H) {
     // FIXME: this is buggy, but it should not matter too much
     mmm.type_id().hash(state)
@@ -100,8 +101,9 @@ impl Op for LirMatMulUnary {
     op_as_typed_op!();
 }
 
-#[derive(Clone, Debug)]
-struct State;
+/// Multiplier specialized for the last symbolic n met, if any.
+#[derive(Clone, Debug, Default)]
+struct State(Option<(usize, Box<dyn MatMatMul>)>);
 impl OpState for State {
     fn eval(
         &mut self,
@@ -112,21 +114,48 @@ impl OpState for State {
         let op = op.downcast_ref::<LirMatMulUnary>().unwrap();
         let shape = op.c_fact.shape.eval_to_usize(&session.resolved_symbols)?;
         let final_shape = op.c_final_shape.eval_to_usize(&session.resolved_symbols)?;
+        // a symbolic n: the multiplier is specialized for the actual one
+        let mmm = if op.n().to_usize().is_err() {
+            let n = shape[op.c_n_axis];
+            if self.0.as_ref().map(|(cached, _)| *cached != n).unwrap_or(true) {
+                self.0 = Some((n, op.mmm.with_n(n)));
+            }
+            &self.0.as_ref().unwrap().1
+        } else {
+            &op.mmm
+        };
         unsafe {
             if session
                 .cached_mmm_scratch_space
                 .as_deref()
-                .map(|scratch| op.mmm.can_use_scratch_space(scratch))
+                .map(|scratch| mmm.can_use_scratch_space(scratch))
                 == Some(false)
             {
                 session.cached_mmm_scratch_space = None
             }
             let scratch = session
                 .cached_mmm_scratch_space
-                .get_or_insert_with(|| op.mmm.allocate_scratch_space());
-            eval(op, scratch.as_mut(), &inputs, &shape, op.c_m_axis, op.c_n_axis, &final_shape)
+                .get_or_insert_with(|| mmm.allocate_scratch_space());
+            eval(
+                op,
+                &**mmm,
+                scratch.as_mut(),
+                &inputs,
+                &shape,
+                op.c_m_axis,
+                op.c_n_axis,
+                &final_shape,
+            )
         }
     }
+
+    fn snapshot(&self) -> TractResult<TVec<Tensor>> {
+        Ok(tvec!())
+    }
+
+    fn restore(&mut self, _tensors: TVec<Tensor>) -> TractResult<()> {
+        Ok(())
+    }
 }
 
 impl EvalOp for LirMatMulUnary {
@@ -139,13 +168,14 @@ impl EvalOp for LirMatMulUnary {
         _session: &mut SessionState,
         _node_id: usize,
     ) -> TractResult<Option<Box<dyn OpState>>> {
-        Ok(Some(Box::new(State)))
+        Ok(Some(Box::new(State::default())))
     }
 
     fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
         let mut scratch = unsafe { self.mmm.allocate_scratch_space() };
         eval(
             self,
+            &*self.mmm,
             scratch.as_mut(),
             &*inputs,
             self.c_fact.shape.as_concrete().unwrap(),
@@ -158,6 +188,7 @@ impl EvalOp for LirMatMulUnary {
 
 fn eval(
     op: &LirMatMulUnary,
+    mmm: &dyn MatMatMul,
     scratch: &mut dyn ScratchSpace,
     inputs: &[Arc<Tensor>],
     c_shape: &[usize],
@@ -168,10 +199,10 @@ fn eval(
     unsafe {
         let a_dt = op.micro_ops.iter().next().unwrap().0.datum_type();
         let mut c = Tensor::uninitialized_dt(op.c_fact.datum_type, &c_shape)?;
-        let c_storage = if c_shape[c_n_axis] == 1 {
-            op.mmm.c_vec_from_data()
+        let c_storage = if *op.n() == 1.to_dim() {
+            mmm.c_vec_from_data()
         } else {
-            op.mmm.c_view_with_axis(c_m_axis, c_n_axis)
+            mmm.c_view_with_axis(c_m_axis, c_n_axis)
         };
         if op
             .c_fact
@@ -196,9 +227,9 @@ fn eval(
                 }
                 let (pa, fused) = ops.iter().next().unwrap();
                 let f: Vec<FusedSpec> = fused.iter().map(|f| f.resolve(inputs)).collect::<Vec<_>>();
-                op.mmm.run_with_scratch_space(
+                mmm.run_with_scratch_space(
                     scratch,
-                    &op.mmm.a_packed(a_dt).wrap(&pa.view()),
+                    &mmm.a_packed(a_dt).wrap(&pa.view()),
                     &op.b_storage.wrap(&TensorView::at_prefix_unchecked(&inputs[0], &*b_prefix)),
                     &mut c_storage.wrap(&c_view),
                     &f,
@@ -207,9 +238,9 @@ fn eval(
         } else {
             let (pa, fused) = op.micro_ops.iter().next().unwrap();
             let f: Vec<FusedSpec> = fused.iter().map(|f| f.resolve(inputs)).collect::<Vec<_>>();
-            op.mmm.run_with_scratch_space(
+            mmm.run_with_scratch_space(
                 scratch,
-                &op.mmm.a_packed(a_dt).wrap(&pa.view()),
+                &mmm.a_packed(a_dt).wrap(&pa.view()),
                 &op.b_storage.wrap(&inputs[0].view()),
                 &mut c_storage.wrap(&c.view_mut()),
                 &f,
@@ -246,6 +277,7 @@ impl TypedOp for LirMatMulUnary {
         ))
     }
 
+    #[allow(clippy::if_same_then_else)]
     fn fuse(&self, model: &TypedModel, node: &TypedNode) -> TractResult<Option<TypedModelPatch>> {
         use crate::ops;
         if node.outputs.len() != 1
@@ -298,7 +330,7 @@ impl TypedOp for LirMatMulUnary {
                             i8::datum_type(),
                             self.m(),
                             self.k(),
-                            self.n().to_usize()?,
+                            self.n().to_usize().unwrap_or(0),
                         )
                         .context("MMM instantiation")?;
                     let c_fact = TypedFact::dt_shape(i8::datum_type(), self.c_fact.shape.clone());
@@ -312,6 +344,8 @@ impl TypedOp for LirMatMulUnary {
                 }
             }
         } else if let Some(op) = succ.op_as::<ops::binary::UnaryOp>() {
+            // the kernel applies fused operands to its accumulators
+            let a = op.a.cast_to_dt(self.mmm.internal_type())?.into_owned().into_arc_tensor();
             if op.a.len() == 1 {
                 if op.mini_op.is::<ops::quant::Scale>()
                     && self.c_fact.datum_type == i32::datum_type()
@@ -328,35 +362,29 @@ impl TypedOp for LirMatMulUnary {
                     let shift = 126usize - current_exponent as usize;
                     return merge(&[ProtoFusedSpec::QAway(tensor0(int_multi).into(), shift)], &[]);
                 } else if op.mini_op.is::<ops::math::Max>() {
-                    return merge(&[ProtoFusedSpec::Max((&op.a).into())], &[]);
+                    return merge(&[ProtoFusedSpec::Max((&a).into())], &[]);
                 } else if op.mini_op.is::<ops::math::Min>() {
-                    return merge(&[ProtoFusedSpec::Min((&op.a).into())], &[]);
+                    return merge(&[ProtoFusedSpec::Min((&a).into())], &[]);
                 } else if op.mini_op.is::<ops::math::Mul>() {
-                    return merge(&[ProtoFusedSpec::ScalarMul((&op.a).into())], &[]);
-                }
-            } else if op.a.shape()[op.a.rank() - 2] == 1
-                && op.a.shape()[op.a.rank() - 1].to_dim() == self.c_fact.shape[self.c_m_axis]
-            {
-                if op.mini_op.is::<ops::math::Mul>() {
-                    return merge(&[ProtoFusedSpec::PerRowMul((&op.a).into())], &[]);
-                } else if op.mini_op.is::<ops::math::Add>() {
-                    return merge(&[ProtoFusedSpec::PerRowAdd((&op.a).into())], &[]);
+                    return merge(&[ProtoFusedSpec::ScalarMul((&a).into())], &[]);
                 }
-            } else if op.a.shape()[op.a.rank() - 1] == 1
-                && op.a.shape()[op.a.rank() - 2].to_dim()
-                    == self.c_fact.shape[self.c_fact.rank() - 2]
+            } else if op.a.rank() == self.c_fact.rank()
+                && op.a.shape()[self.c_m_axis].to_dim() == self.c_fact.shape[self.c_m_axis]
+                && op.a.shape().iter().enumerate().all(|(ax, d)| ax == self.c_m_axis || *d == 1)
             {
-                let arg = &op.a;
+                // operands varying along any other axis (like groups) can not be split between
+                // the micro ops
                 if op.mini_op.is::<ops::math::Mul>() {
-                    return merge(&[ProtoFusedSpec::PerRowMul(arg.into())], &[]);
+                    return merge(&[ProtoFusedSpec::PerRowMul((&a).into())], &[]);
                 } else if op.mini_op.is::<ops::math::Add>() {
-                    return merge(&[ProtoFusedSpec::PerRowAdd(arg.into())], &[]);
+                    return merge(&[ProtoFusedSpec::PerRowAdd((&a).into())], &[]);
                 }
             }
         } else if let Some(op) = succ.op_as::<ops::binary::MergeOpUnicast>() {
             let other_slot = 1 - node.outputs[0].successors[0].slot;
             let other_input = succ.inputs[other_slot];
-            if op.0.is::<ops::math::Add>() {
+            // the other operand is added as is to the accumulators
+            if op.0.is::<ops::math::Add>() && self.mmm.internal_type() == self.c_fact.datum_type {
                 return merge(
                     &[ProtoFusedSpec::AddUnicast(node.inputs.len().into())],
                     &[other_input],
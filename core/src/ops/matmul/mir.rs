@@ -43,7 +43,7 @@ impl EvalOp for MatMul {
     }
 
     fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
-        if &inputs[0].rank() != &inputs[1].rank() {
+        if inputs[0].rank() != inputs[1].rank() {
             bail!("Rank mismatch {:?} vs {:?}", inputs[0], inputs[1]);
         }
         Ok(tvec!(eval(&inputs[0], &inputs[1], self.a_trans, self.b_trans, self.c_trans)?
@@ -82,7 +82,7 @@ impl TypedOp for MatMul {
         } else if b_fact.konst.is_some() {
             1
         } else {
-            return Ok(None);
+            return crate::ops::nn::declutter_attention(model, node);
         };
 
         let var_ix = 1 - konst_ix;
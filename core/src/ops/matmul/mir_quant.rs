@@ -145,7 +145,7 @@ impl EvalOp for QMatMul {
     }
 
     fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
-        if &inputs[0].rank() != &inputs[1].rank() {
+        if inputs[0].rank() != inputs[1].rank() {
             bail!("Rank mismatch {:?} vs {:?}", inputs[0], inputs[1]);
         }
 
@@ -157,7 +157,9 @@ impl EvalOp for QMatMul {
         let params = self
             .params
             .iter()
-            .map(|(name, qp)| model.add_const(format!("source_{}", name), qp.tensor(&inputs).clone()))
+            .map(|(name, qp)| {
+                model.add_const(format!("source_{}", name), qp.tensor(&inputs).clone())
+            })
             .collect::<TractResult<Vec<_>>>()?;
 
         let result = self.wire(&mut model, "adhoc", a, b, c, &params)?;
@@ -218,7 +220,9 @@ impl TypedOp for QMatMul {
             .iter()
             .map(|(name, qp)| match qp {
                 AttrOrInput::Input(o) => patch.tap_model(model, node.inputs[*o]),
-                AttrOrInput::Attr(t) => patch.add_const(format!("{}_{}", node.name, name), t.clone()),
+                AttrOrInput::Attr(t) => {
+                    patch.add_const(format!("{}_{}", node.name, name), t.clone())
+                }
             })
             .collect::<TractResult<Vec<OutletId>>>()?;
 
@@ -254,7 +258,17 @@ impl QMatMul {
         let rank = a_fact.rank();
         let k = model.outlet_fact(a)?.shape[rank - 2 + !self.a_trans as usize].clone();
 
-        let abc_scale = combine_scales(model, name, params[1], params[3], params[5])?;
+        // per-axis a parameters go along the rows of c, b parameters along its columns
+        let m_axis = rank - 2 + self.c_trans as usize;
+        let n_axis = rank - 1 - self.c_trans as usize;
+        let a0 = wire_per_axis_param(model, &format!("{}.a0", name), params[0], rank, m_axis)?;
+        let a_scale =
+            wire_per_axis_param(model, &format!("{}.a_scale", name), params[1], rank, m_axis)?;
+        let b0 = wire_per_axis_param(model, &format!("{}.b0", name), params[2], rank, n_axis)?;
+        let b_scale =
+            wire_per_axis_param(model, &format!("{}.b_scale", name), params[3], rank, n_axis)?;
+
+        let abc_scale = combine_scales(model, name, a_scale, b_scale, params[5])?;
 
         let a_i32 = model.wire_node(
             format!("{}.a_as_i32", name),
@@ -291,21 +305,42 @@ impl QMatMul {
             ops::math::add::bin_typed(),
             &[result, c],
         )?[0];
-        let result = compensate_zero_points(
-            model,
-            name,
-            result,
-            self.c_trans,
-            k,
-            params[0],
-            params[2],
-            sum_a,
-            sum_b,
-        )?;
+        let result =
+            compensate_zero_points(model, name, result, m_axis, n_axis, k, a0, b0, sum_a, sum_b)?;
         requant(model, name, result, self.output_type, abc_scale, params[4])
     }
 }
 
+/// Lay a quantization parameter out so that it broadcasts along `axis` of a rank `rank` tensor.
+///
+/// Scalars are left untouched. Per-axis parameters (1D, or 2D for grouped convolution) get
+/// their last axis on `axis`.
+pub(crate) fn wire_per_axis_param(
+    model: &mut TypedModel,
+    name: &str,
+    param: OutletId,
+    rank: usize,
+    axis: usize,
+) -> TractResult<OutletId> {
+    let param_rank = model.outlet_fact(param)?.rank();
+    if param_rank == 0 {
+        return Ok(param);
+    }
+    if param_rank > axis + 1 {
+        bail!("Can not lay a rank {} quantization parameter along axis {}", param_rank, axis);
+    }
+    let mut wire = param;
+    for ix in 0..axis + 1 - param_rank {
+        wire =
+            model.wire_node(format!("{}.prepend_axis_{}", name, ix), AxisOp::Add(0), &[wire])?[0];
+    }
+    for ix in axis + 1..rank {
+        wire =
+            model.wire_node(format!("{}.append_axis_{}", name, ix), AxisOp::Add(ix), &[wire])?[0];
+    }
+    Ok(wire)
+}
+
 pub(crate) fn combine_scales(
     model: &mut TypedModel,
     name: &str,
@@ -328,11 +363,14 @@ pub(crate) fn combine_scales(
     Ok(abc_scale)
 }
 
+/// Subtracts the zero point terms from a i32 product laid out with its m and n on `m_axis` and
+/// `n_axis`. `sum_a` is laid out as the product without its n axis, `sum_b` without its m axis.
 pub(crate) fn compensate_zero_points(
     model: &mut TypedModel,
     name: &str,
     result: OutletId,
-    c_trans: bool,
+    m_axis: usize,
+    n_axis: usize,
     k: TDim,
     a0: OutletId,
     b0: OutletId,
@@ -341,22 +379,25 @@ pub(crate) fn compensate_zero_points(
 ) -> TractResult<OutletId> {
     let input_shape = model.outlet_fact(result)?.shape.clone();
     let rank = model.outlet_fact(result)?.rank();
-    let m_axis = rank - 2 + c_trans as usize;
-    let n_axis = rank - 1 - c_trans as usize;
 
     debug_assert_eq!(model.outlet_fact(sum_a)?.rank(), rank - 1);
     debug_assert_eq!(model.outlet_fact(sum_b)?.rank(), rank - 1);
 
     // make sum_a into from a 1D vector to a vertical matrix, sum_b horizontal
-    // switch shapes if c_trans
     let sum_a =
         model.wire_node(format!("{}.reshape_sum_a", name), AxisOp::Add(n_axis), &[sum_a])?[0];
 
     let sum_b =
         model.wire_node(format!("{}.reshape_sum_b", name), AxisOp::Add(m_axis), &[sum_b])?[0];
 
-    debug_assert_eq!(model.outlet_fact(sum_a)?.shape[m_axis], model.outlet_fact(result)?.shape[m_axis]);
-    debug_assert_eq!(model.outlet_fact(sum_b)?.shape[n_axis], model.outlet_fact(result)?.shape[n_axis]);
+    debug_assert_eq!(
+        model.outlet_fact(sum_a)?.shape[m_axis],
+        model.outlet_fact(result)?.shape[m_axis]
+    );
+    debug_assert_eq!(
+        model.outlet_fact(sum_b)?.shape[n_axis],
+        model.outlet_fact(result)?.shape[n_axis]
+    );
 
     let a0 =
         model.wire_node(format!("{}.cast_a0", name), ops::cast::cast(i32::datum_type()), &[a0])?[0];
@@ -595,7 +636,8 @@ mod test {
             a_scale: 1.0,
             b_scale: 0.15,
             c_scale: 0.6,
-        };
+        }
+        .check();
     }
 
     #[test]
@@ -614,6 +656,23 @@ mod test {
         .check()
     }
 
+    #[test]
+    fn per_axis_params() -> TractResult<()> {
+        // a parameters are per row of a, b parameters per column of b
+        let mut params = QParams::noop_static(i8::datum_type());
+        params.a0 = AttrOrInput::Attr(rctensor1(&[1i8, 2]));
+        params.a_scale = AttrOrInput::Attr(rctensor1(&[0.5f32, 1.0]));
+        params.b0 = AttrOrInput::Attr(rctensor1(&[0i8, 1]));
+        params.b_scale = AttrOrInput::Attr(rctensor1(&[1.0f32, 2.0]));
+        params.c_scale = AttrOrInput::Attr(rctensor0(0.5f32));
+        let op = QMatMul::new(false, false, false, i8::datum_type(), params);
+        let a = rctensor2(&[[1i8, 2], [3, 4]]);
+        let b = rctensor2(&[[5i8, 6], [7, 8]]);
+        let result = op.eval(tvec!(a, b, rctensor0(0i32)))?;
+        assert_eq!(result[0], rctensor2(&[[7i8, 14], [38, 76]]));
+        Ok(())
+    }
+
     #[derive(Debug)]
     struct QMatMulProblem {
         a: Array2<i8>,
@@ -74,7 +74,7 @@ impl TypedOp for MatMulUnary {
             broadcasted_a_shape.insert(0, 1);
         }
         let mut invars = broadcasted_a_shape[..broadcasted_a_shape.len() - 2]
-            .into_iter()
+            .iter()
             .enumerate()
             .map(|(axis, &period)| AxisInfo::simple(axis).with_period(period))
             .collect::<Vec<_>>();
@@ -134,7 +134,7 @@ impl TypedOp for MatMulUnary {
                     Some(Box::new(MatMulUnary { a: a.into_arc_tensor(), ..self.clone() }) as _);
                 Ok(Some(AxisChangeConsequence::new(model, node, op, change)))
             }
-            _ => return Ok(None),
+            _ => Ok(None),
         }
     }
 
@@ -171,7 +171,7 @@ impl TypedOp for MatMulUnary {
                         }
                     };
                     let mut a = self.a.slice(k_axis, offsets[ix], offsets[ix + 1])?;
-                    while a.rank() > 0 && a.shape()[0] == 1 {
+                    while a.rank() > 2 && a.shape()[0] == 1 {
                         a.remove_axis(0)?;
                     }
                     let wire = patch.wire_node(
@@ -220,7 +220,7 @@ impl TypedOp for MatMulUnary {
                 )?[0],
             ));
         }
-        return Ok(None);
+        Ok(None)
     }
 
     fn cost(&self, inputs: &[&TypedFact]) -> TractResult<TVec<(Cost, TDim)>> {
@@ -241,40 +241,46 @@ impl TypedOp for MatMulUnary {
         node: &TypedNode,
     ) -> TractResult<Option<TypedModelPatch>> {
         let b = args_1!(model.node_input_facts(node.id)?);
-        if let Some(b_shape) = b.shape.as_concrete() {
-            return Ok(Some(self.new_mat_mul_unary_finite(model, node, &b_shape, b.datum_type)?));
+        // n can be symbolic, but k must be known to pack a
+        if b.shape[b.rank() - 2 + self.b_trans as usize].to_usize().is_err() {
+            return Ok(None);
         }
-        Ok(None)
+        Ok(Some(self.new_mat_mul_unary(model, node, &b.shape, b.datum_type)?))
     }
 
     as_op!();
 }
 
 impl MatMulUnary {
-    fn new_mat_mul_unary_finite(
+    fn new_mat_mul_unary(
         &self,
         model: &TypedModel,
         node: &TypedNode,
-        b_shape: &[usize],
+        b_shape: &[TDim],
         b_dt: DatumType,
     ) -> TractResult<TypedModelPatch> {
         let mut patch = TypedModelPatch::default();
         let mut wire = patch.tap_model(model, node.inputs[0])?;
 
         let c_dt = output_type(self.a.datum_type());
+        let a_shape = self.a.shape().iter().map(|d| d.to_dim()).collect::<TVec<_>>();
         let (m, k, n, c_shape) =
-            compute_shape(&self.a.shape(), b_shape, self.a_trans, self.b_trans, self.c_trans)?;
+            compute_shape(&a_shape, b_shape, self.a_trans, self.b_trans, self.c_trans)?;
+        let (m, k) = (m.to_usize()?, k.to_usize()?);
+        // a symbolic n is only known at runtime: the multiplier is then picked for an arbitrary
+        // n, and adjusted by LirMatMulUnary on each evaluation
+        let n_hint = n.to_usize().unwrap_or(0);
 
-        let mm = tract_linalg::ops().mmm(self.a.datum_type(), b_dt, c_dt, m, k, n).with_context(
-            || {
+        let mm = tract_linalg::ops()
+            .mmm(self.a.datum_type(), b_dt, c_dt, m, k, n_hint)
+            .with_context(|| {
                 format!(
                     "No matrix multiplier for {:?}x{:?} to {:?}",
                     self.a.datum_type(),
                     b_dt,
                     c_dt
                 )
-            },
-        )?;
+            })?;
 
         let packed_as =
             Array::from_shape_fn(&self.a.shape()[0..self.a.rank() - 2], |a_prefix| unsafe {
@@ -290,40 +296,28 @@ impl MatMulUnary {
                     !self.a_trans as usize,
                     self.a_trans as usize,
                 );
-                (pa.into_arc_tensor(), vec!())
+                (pa.into_arc_tensor(), vec![])
             });
         unsafe {
-            let b_storage = if n == 1 {
+            let b_storage = if n_hint == 1 {
                 mm.b_vec_from_data_and_stride(
                     b_dt,
-                    if self.b_trans { 1 } else { *b_shape.last().unwrap() as isize },
+                    if self.b_trans { 1 } else { b_shape.last().unwrap().to_isize()? },
                 )
             } else {
-                let mut packed_b_shape: TVec<usize> = b_shape[..b_shape.len() - 2].into();
-                packed_b_shape.push(mm.b_pack().len(n));
                 wire = patch.wire_node(
                     format!("{}.pack", &*node.name),
-                    super::MatMatMulPack {
-                        packer: mm.b_pack(),
-                        trans: self.b_trans,
-                        output_shape: packed_b_shape,
-                    },
+                    super::MatMatMulPack { packer: mm.b_pack(), trans: self.b_trans },
                     &[wire],
                 )?[0];
                 mm.b_packed(b_dt)
             };
             let rank = c_shape.len();
-            let mut strides = natural_strides(&c_shape);
-            let mut overrided_shape = c_shape.clone();
-            if self.c_trans {
-                overrided_shape.swap(rank - 2, rank - 1);
-                strides.swap(rank - 2, rank - 1);
-            }
             wire = patch.wire_node(
                 format!("{}.matmatmul", &*node.name),
                 LirMatMulUnary {
                     b_storage,
-                    c_fact: TypedFact::dt_shape(c_dt, &c_shape),
+                    c_fact: TypedFact::dt_shape(c_dt, c_shape.clone()),
                     micro_ops: packed_as,
                     mmm: mm,
                     k,
@@ -339,3 +333,53 @@ impl MatMulUnary {
         Ok(patch)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::array::TypedConcat;
+
+    #[test]
+    fn split_over_k_concat_keeps_a_matrix() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let x1 = model.add_source("x1", TypedFact::dt_shape(f32::datum_type(), &[3, 2]))?;
+        let x2 = model.add_source("x2", TypedFact::dt_shape(f32::datum_type(), &[3, 2]))?;
+        let concat = model.wire_node("concat", TypedConcat::concat_vars(1, 2), &[x1, x2])?;
+        let a = rctensor2(&[[1f32, 2., 3., 4.]]);
+        let mm = model.wire_node("mm", MatMulUnary::new(a, false, true, false), &concat)?;
+        model.set_output_outlets(&mm)?;
+        let model = model.declutter()?;
+        assert!(model.nodes().iter().all(|n| !n.op_is::<TypedConcat>()));
+        let output = model.into_runnable()?.run(tvec!(
+            tensor2(&[[1f32, 0.], [0., 1.], [1., 1.]]),
+            tensor2(&[[0f32, 0.], [1., 0.], [0., 1.]])
+        ))?;
+        assert_eq!(*output[0], tensor2(&[[1f32, 5., 7.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn symbolic_n() -> TractResult<()> {
+        let s = Symbol::new('S');
+        let mut model = TypedModel::default();
+        let b = model.add_source(
+            "b",
+            TypedFact::dt_shape(f32::datum_type(), &[2.to_dim(), s.to_dim() * 2]),
+        )?;
+        let a = rctensor2(&[[1f32, 2.], [3., 4.]]);
+        let mm = model.wire_node("mm", MatMulUnary::new(a, false, false, false), &[b])?;
+        model.set_output_outlets(&mm)?;
+        let model = model.into_optimized()?;
+        assert!(model.nodes().iter().any(|n| n.op_is::<LirMatMulUnary>()));
+        let mut state = SimpleState::new(SimplePlan::new(model)?)?;
+        for n in [2, 4, 2] {
+            let b = tensor1(&*vec![1f32; 2 * n]).into_shape(&[2, n])?;
+            let output = state.run(tvec!(b))?;
+            let expected =
+                tensor1(&*[vec![3f32; n], vec![7f32; n]].concat()).into_shape(&[2, n])?;
+            assert_eq!(*output[0], expected);
+        }
+        assert!(state.run(tvec!(tensor2(&[[1f32; 3]; 2]))).is_err());
+        Ok(())
+    }
+}
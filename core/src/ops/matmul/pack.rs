@@ -8,7 +8,6 @@ use tract_linalg::frame::Packer;
 pub struct MatMatMulPack {
     pub(crate) packer: Packer,
     pub(crate) trans: bool,
-    pub(crate) output_shape: TVec<usize>,
 }
 
 impl DynHash for MatMatMulPack {
@@ -17,6 +16,14 @@ impl DynHash for MatMatMulPack {
     }
 }
 
+impl MatMatMulPack {
+    fn output_shape<D: DimLike>(&self, input: &[D]) -> TVec<D> {
+        let mut packed_shape: TVec<D> = input[0..input.len() - 2].into();
+        packed_shape.push(self.packer.len_dim(&input[input.len() - 2 + !self.trans as usize]));
+        packed_shape
+    }
+}
+
 impl Op for MatMatMulPack {
     fn name(&self) -> Cow<str> {
         "MatMatMulPack".into()
@@ -39,9 +46,12 @@ impl EvalOp for MatMatMulPack {
         let b = args_1!(inputs);
         let dt = b.datum_type();
         unsafe {
-            let mut packed =
-                Tensor::uninitialized_aligned_dt(dt, &*self.output_shape, self.packer.alignment())
-                    .unwrap();
+            let mut packed = Tensor::uninitialized_aligned_dt(
+                dt,
+                &*self.output_shape(b.shape()),
+                self.packer.alignment(),
+            )
+            .unwrap();
             for prefix in indices(&b.shape()[..b.rank() - 2]) {
                 self.packer.pack(
                     &mut packed.view_at_prefix_mut(prefix.slice())?,
@@ -57,7 +67,10 @@ impl EvalOp for MatMatMulPack {
 
 impl TypedOp for MatMatMulPack {
     fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
-        Ok(tvec!(TypedFact::dt_shape(inputs[0].datum_type, &self.output_shape)))
+        Ok(tvec!(TypedFact::dt_shape(
+            inputs[0].datum_type,
+            self.output_shape::<TDim>(&inputs[0].shape),
+        )))
     }
 
     as_op!();
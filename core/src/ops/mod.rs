@@ -18,6 +18,7 @@ pub mod array;
 pub mod cast;
 pub mod change_axes;
 pub mod cnn;
+pub mod control_flow;
 pub mod downsample;
 pub mod dummy;
 pub mod identity;
@@ -72,6 +73,18 @@ pub trait OpState: fmt::Debug + Send + dyn_clone::DynClone {
         op: &dyn Op,
         inputs: TVec<Arc<Tensor>>,
     ) -> TractResult<TVec<Arc<Tensor>>>;
+
+    /// Tensors making up the state, to checkpoint a stream. States holding no data return an
+    /// empty list.
+    fn snapshot(&self) -> TractResult<TVec<Tensor>> {
+        bail!("Can not snapshot {}", std::any::type_name::<Self>())
+    }
+
+    /// Restore tensors obtained from `snapshot` on the state of the same node.
+    #[allow(unused_variables)]
+    fn restore(&mut self, tensors: TVec<Tensor>) -> TractResult<()> {
+        bail!("Can not restore {}", std::any::type_name::<Self>())
+    }
 }
 dyn_clone::clone_trait_object!(OpState);
 
@@ -0,0 +1,556 @@
+use crate::internal::*;
+use crate::ops::binary::{TypedBinOp, UnaryOp};
+use crate::ops::element_wise::ElementWiseOp;
+use crate::ops::math::{Div, Exp, Mul, Recip, Sub};
+use crate::ops::matmul::MatMul;
+use crate::ops::nn::{Reduce, Reducer};
+use ndarray::*;
+use num_traits::Float;
+
+/// Which keys a query can attend to, for self-attention (query `i` and key `i` are the same
+/// position in the sequence).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AttentionMask {
+    /// Every query attends every key.
+    #[default]
+    Full,
+    /// Query `i` attends keys `i - left_context ..= i` (all the past if `left_context` is None).
+    Causal { left_context: Option<usize> },
+    /// The sequence is split in chunks of `chunk` positions. Queries attend the keys of their
+    /// chunk and of the `left_chunks` previous ones.
+    Chunked { chunk: usize, left_chunks: usize },
+}
+
+impl AttentionMask {
+    /// Can the query at position `q` attend the key at position `k` ?
+    pub fn visible(&self, q: i64, k: i64) -> bool {
+        match *self {
+            AttentionMask::Full => true,
+            AttentionMask::Causal { left_context } => {
+                k <= q && left_context.map(|l| q - k <= l as i64).unwrap_or(true)
+            }
+            AttentionMask::Chunked { chunk, left_chunks } => {
+                let (qc, kc) = (q.div_euclid(chunk as i64), k.div_euclid(chunk as i64));
+                kc <= qc && qc - kc <= left_chunks as i64
+            }
+        }
+    }
+}
+
+/// Fused softmax(scale * Q·Kᵀ)·V on the two innermost axes.
+///
+/// Inputs are Q [.., M, D], K [.., N, D] and V [.., N, Dv]. Outer axes broadcast like
+/// MatMul's. Keys are processed in blocks of KEY_BLOCK with an online softmax, and both
+/// products run on the matrix multiplier, so the M×N attention matrix is never materialized.
+#[derive(Debug, Clone, new, Educe)]
+#[educe(Hash)]
+pub struct ScaledDotProductAttention {
+    #[educe(Hash(method = "hash_f32"))]
+    pub scale: f32,
+    #[new(default)]
+    pub mask: AttentionMask,
+}
+
+impl_dyn_hash!(ScaledDotProductAttention);
+
+/// Number of keys evaluated together.
+const KEY_BLOCK: usize = 128;
+
+/// Inner 2D view for an output prefix, broadcasting the outer axes of length 1.
+fn at_prefix<'a, T>(view: &ArrayViewD<'a, T>, prefix: &[usize]) -> TractResult<ArrayView2<'a, T>> {
+    let mut view = view.clone();
+    for &ix in prefix {
+        let ix = ix.min(view.shape()[0] - 1);
+        view.index_axis_inplace(Axis(0), ix);
+    }
+    Ok(view.into_dimensionality()?)
+}
+
+impl ScaledDotProductAttention {
+    fn output_shape<D: DimLike>(&self, q: &[D], k: &[D], v: &[D]) -> TractResult<TVec<D>> {
+        let rank = q.len();
+        if k.len() != rank || v.len() != rank || rank < 2 {
+            bail!("Attention expects Q, K and V of the same rank (at least 2)");
+        }
+        if q[rank - 1] != k[rank - 1] || k[rank - 2] != v[rank - 2] {
+            bail!(
+                "Inconsistent attention shapes: Q {:?}, K {:?}, V {:?}",
+                q.iter().map(|d| d.to_string()).collect::<Vec<_>>(),
+                k.iter().map(|d| d.to_string()).collect::<Vec<_>>(),
+                v.iter().map(|d| d.to_string()).collect::<Vec<_>>()
+            );
+        }
+        let mut shape =
+            crate::broadcast::multi_broadcast(&[&q[..rank - 2], &k[..rank - 2], &v[..rank - 2]])
+                .ok_or_else(|| format_err!("Could not broadcast"))?;
+        shape.push(q[rank - 2].clone());
+        shape.push(v[rank - 1].clone());
+        Ok(shape)
+    }
+
+    pub fn with_mask(self, mask: AttentionMask) -> ScaledDotProductAttention {
+        ScaledDotProductAttention { mask, ..self }
+    }
+
+    /// Evaluate with the first query at position `q_start` and the first key at `k_start`.
+    ///
+    /// Keys at negative positions are masked out. Queries with no visible key output zeros.
+    pub fn eval_at(
+        &self,
+        q: &Tensor,
+        k: &Tensor,
+        v: &Tensor,
+        q_start: i64,
+        k_start: i64,
+    ) -> TractResult<Tensor> {
+        dispatch_floatlike!(Self::eval_t(q.datum_type())(self, q, k, v, q_start, k_start))
+    }
+
+    fn eval_t<T: Datum + Float>(
+        &self,
+        q: &Tensor,
+        k: &Tensor,
+        v: &Tensor,
+        q_start: i64,
+        k_start: i64,
+    ) -> TractResult<Tensor> {
+        let rank = q.rank();
+        let shape = self.output_shape(q.shape(), k.shape(), v.shape())?;
+        let dt = T::datum_type();
+        let blockwise = tract_linalg::ops().mmm(dt, dt, dt, 1, 1, 1).is_some();
+        let (q, k, v) = (q.to_array_view::<T>()?, k.to_array_view::<T>()?, v.to_array_view::<T>()?);
+        let mut output = ArrayD::<T>::zeros(&*shape);
+        for prefix in indices(&shape[..rank - 2]) {
+            let prefix = prefix.slice();
+            let (q, k, v) =
+                (at_prefix(&q, prefix)?, at_prefix(&k, prefix)?, at_prefix(&v, prefix)?);
+            let mut output = output.view_mut();
+            for &ix in prefix {
+                output.index_axis_inplace(Axis(0), ix);
+            }
+            let output = output.into_dimensionality::<Ix2>()?;
+            if blockwise {
+                self.eval_blocks(q, k, v, output, q_start, k_start)?;
+            } else {
+                self.eval_rows(q, k, v, output, q_start, k_start);
+            }
+        }
+        Ok(output.into_tensor())
+    }
+
+    fn visible(&self, q_pos: i64, k_pos: i64) -> bool {
+        k_pos >= 0 && self.mask.visible(q_pos, k_pos)
+    }
+
+    /// Online softmax over blocks of KEY_BLOCK keys, with both products running on the
+    /// matrix multiplier: only a M×KEY_BLOCK block of scores is alive at a time, and the
+    /// partial outputs are rescaled when a later block raises a row maximum.
+    fn eval_blocks<T: Datum + Float>(
+        &self,
+        q: ArrayView2<T>,
+        k: ArrayView2<T>,
+        v: ArrayView2<T>,
+        mut output: ArrayViewMut2<T>,
+        q_start: i64,
+        k_start: i64,
+    ) -> TractResult<()> {
+        let scale = T::from(self.scale).unwrap();
+        let q = q.to_owned().into_tensor();
+        let mut max = vec![T::neg_infinity(); output.nrows()];
+        let mut sum = vec![T::zero(); output.nrows()];
+        for start in (0..k.nrows()).step_by(KEY_BLOCK) {
+            let end = (start + KEY_BLOCK).min(k.nrows());
+            let k_block = k.slice(s![start..end, ..]).to_owned().into_tensor();
+            let v_block = v.slice(s![start..end, ..]).to_owned().into_tensor();
+            let mut scores = crate::ops::matmul::eval(&q, &k_block, false, true, false)?
+                .into_array::<T>()?
+                .into_dimensionality::<Ix2>()?;
+            for (i, mut row) in scores.outer_iter_mut().enumerate() {
+                let q_pos = q_start + i as i64;
+                let mut block_max = T::neg_infinity();
+                for (j, score) in row.iter_mut().enumerate() {
+                    if self.visible(q_pos, k_start + (start + j) as i64) {
+                        *score = *score * scale;
+                        block_max = block_max.max(*score);
+                    } else {
+                        *score = T::neg_infinity();
+                    }
+                }
+                let new_max = max[i].max(block_max);
+                if new_max == T::neg_infinity() {
+                    row.fill(T::zero());
+                    continue;
+                }
+                let correction = (max[i] - new_max).exp();
+                row.mapv_inplace(|score| (score - new_max).exp());
+                sum[i] = sum[i] * correction + row.iter().fold(T::zero(), |acc, &p| acc + p);
+                output.row_mut(i).mapv_inplace(|o| o * correction);
+                max[i] = new_max;
+            }
+            let weighted =
+                crate::ops::matmul::eval(&scores.into_tensor(), &v_block, false, false, false)?;
+            Zip::from(&mut output)
+                .and(&weighted.to_array_view::<T>()?.into_dimensionality::<Ix2>()?)
+                .for_each(|o, &w| *o = *o + w);
+        }
+        for (mut row, sum) in output.outer_iter_mut().zip(sum) {
+            if sum > T::zero() {
+                row.mapv_inplace(|x| x / sum);
+            }
+        }
+        Ok(())
+    }
+
+    /// One query row at a time, for types the matrix multiplier does not cover.
+    fn eval_rows<T: Datum + Float>(
+        &self,
+        q: ArrayView2<T>,
+        k: ArrayView2<T>,
+        v: ArrayView2<T>,
+        mut output: ArrayViewMut2<T>,
+        q_start: i64,
+        k_start: i64,
+    ) {
+        let scale = T::from(self.scale).unwrap();
+        let mut scores = vec![T::zero(); k.nrows()];
+        for (i, (q_row, mut o_row)) in q.outer_iter().zip(output.outer_iter_mut()).enumerate() {
+            let q_pos = q_start + i as i64;
+            let visible = |j: usize| self.visible(q_pos, k_start + j as i64);
+            let mut max = T::neg_infinity();
+            for (j, (score, k_row)) in scores.iter_mut().zip(k.outer_iter()).enumerate() {
+                if visible(j) {
+                    *score = q_row.dot(&k_row) * scale;
+                    max = max.max(*score);
+                }
+            }
+            let mut sum = T::zero();
+            for (j, (score, v_row)) in scores.iter_mut().zip(v.outer_iter()).enumerate() {
+                if visible(j) {
+                    *score = (*score - max).exp();
+                    sum = sum + *score;
+                    o_row.scaled_add(*score, &v_row);
+                }
+            }
+            if sum > T::zero() {
+                o_row.mapv_inplace(|x| x / sum);
+            }
+        }
+    }
+}
+
+impl Op for ScaledDotProductAttention {
+    fn name(&self) -> Cow<str> {
+        "ScaledDotProductAttention".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("scale: {} mask: {:?}", self.scale, self.mask)])
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for ScaledDotProductAttention {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let (q, k, v) = args_3!(inputs);
+        let output = self.eval_at(&*q, &*k, &*v, 0, 0)?;
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl TypedOp for ScaledDotProductAttention {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let shape = self.output_shape(
+            &*inputs[0].shape.to_tvec(),
+            &*inputs[1].shape.to_tvec(),
+            &*inputs[2].shape.to_tvec(),
+        )?;
+        Ok(tvec!(TypedFact::dt_shape(inputs[0].datum_type, shape)))
+    }
+
+    fn cost(&self, inputs: &[&TypedFact]) -> TractResult<TVec<(Cost, TDim)>> {
+        let dt = inputs[0].datum_type;
+        let rank = inputs[0].rank();
+        let shape = self.output_shape(
+            &*inputs[0].shape.to_tvec(),
+            &*inputs[1].shape.to_tvec(),
+            &*inputs[2].shape.to_tvec(),
+        )?;
+        let rows: TDim = shape[..rank - 1].iter().maybe_product()?;
+        let n = inputs[1].shape[rank - 2].clone();
+        let d = inputs[0].shape[rank - 1].clone() + inputs[2].shape[rank - 1].clone();
+        Ok(tvec!(
+            (Cost::FMA(dt), rows.maybe_mul(&n)?.maybe_mul(&d)?),
+            (Cost::Div(dt), rows.maybe_mul(&shape[rank - 1])?)
+        ))
+    }
+}
+
+/// Recognize softmax(scale * Q·Kᵀ)·V or softmax(Q·Kᵀ / d)·V ending at the MatMul `node` and
+/// replace the chain with a single ScaledDotProductAttention.
+///
+/// The softmax must be the one hir expands to (max, sub, exp, sum, div) over the last axis.
+/// The div may already have been decluttered to a multiplication by a reciprocal.
+pub(crate) fn declutter_attention(
+    model: &TypedModel,
+    node: &TypedNode,
+) -> TractResult<Option<TypedModelPatch>> {
+    let av = node.op_as::<MatMul>().unwrap();
+    if av.a_trans || av.c_trans {
+        return Ok(None);
+    }
+    let fact = model.outlet_fact(node.inputs[0])?;
+    if fact.datum_type != f32::datum_type() && fact.datum_type != f64::datum_type() {
+        return Ok(None);
+    }
+    let rank = fact.rank();
+    let mut fused = tvec!();
+    macro_rules! prec {
+        ($outlet: expr) => {{
+            let prec = model.node($outlet.node);
+            fused.push(prec.id);
+            prec
+        }};
+    }
+    let is_last_axis_reduce = |node: &TypedNode, reducer: Reducer| {
+        node.op_as::<Reduce>()
+            .map(|r| r.reducer == reducer && r.axes[..] == [rank - 1])
+            .unwrap_or(false)
+    };
+
+    // softmax normalization: exp / sum, or exp * recip(sum)
+    let norm = prec!(node.inputs[0]);
+    let (exp, sum) = if norm.op_as::<TypedBinOp>().map(|op| op.0.is::<Div>()).unwrap_or(false) {
+        (norm.inputs[0], norm.inputs[1])
+    } else if norm.op_as::<TypedBinOp>().map(|op| op.0.is::<Mul>()).unwrap_or(false) {
+        let recip_slot = norm.inputs.iter().position(|i| {
+            model
+                .node(i.node)
+                .op_as::<ElementWiseOp>()
+                .map(|op| op.0.is::<Recip>())
+                .unwrap_or(false)
+        });
+        if let Some(slot) = recip_slot {
+            let recip = prec!(norm.inputs[slot]);
+            (norm.inputs[1 - slot], recip.inputs[0])
+        } else {
+            return Ok(None);
+        }
+    } else {
+        return Ok(None);
+    };
+    let sum = prec!(sum);
+    if !is_last_axis_reduce(sum, Reducer::Sum) || sum.inputs[0] != exp {
+        return Ok(None);
+    }
+    let exp = prec!(exp);
+    if !exp.op_as::<ElementWiseOp>().map(|op| op.0.is::<Exp>()).unwrap_or(false) {
+        return Ok(None);
+    }
+    let sub = prec!(exp.inputs[0]);
+    if !sub.op_as::<TypedBinOp>().map(|op| op.0.is::<Sub>()).unwrap_or(false) {
+        return Ok(None);
+    }
+    let max = prec!(sub.inputs[1]);
+    if !is_last_axis_reduce(max, Reducer::Max) || max.inputs[0] != sub.inputs[0] {
+        return Ok(None);
+    }
+
+    // optional scaling of the scores: a multiplication, or a division by a constant like sqrt(d)
+    let mut scores = model.node(sub.inputs[0].node);
+    let mut scale = 1.0f32;
+    if let Some(op) = scores.op_as::<UnaryOp>() {
+        if op.mini_op.is::<Mul>() && op.a.len() == 1 {
+            scale = op.a.cast_to_scalar::<f32>()?;
+            fused.push(scores.id);
+            scores = model.node(scores.inputs[0].node);
+        } else {
+            return Ok(None);
+        }
+    } else if scores.op_as::<TypedBinOp>().map(|op| op.0.is::<Div>()).unwrap_or(false) {
+        match &model.outlet_fact(scores.inputs[1])?.konst {
+            Some(divisor) if divisor.len() == 1 => {
+                scale = divisor.cast_to_scalar::<f32>()?.recip();
+                fused.push(scores.id);
+                scores = model.node(scores.inputs[0].node);
+            }
+            _ => return Ok(None),
+        }
+    }
+    let qk = if let Some(qk) = scores.op_as::<MatMul>() {
+        qk
+    } else {
+        return Ok(None);
+    };
+    if qk.c_trans {
+        return Ok(None);
+    }
+    fused.push(scores.id);
+
+    // every intermediate result must be consumed by the chain only
+    for &id in &fused {
+        let n = model.node(id);
+        if model.output_outlets()?.iter().any(|o| o.node == id)
+            || n.outputs
+                .iter()
+                .any(|o| o.successors.iter().any(|s| s.node != node.id && !fused.contains(&s.node)))
+        {
+            return Ok(None);
+        }
+    }
+
+    let mut patch = TypedModelPatch::default();
+    let mut q = patch.tap_model(model, scores.inputs[0])?;
+    let mut k = patch.tap_model(model, scores.inputs[1])?;
+    let mut v = patch.tap_model(model, node.inputs[1])?;
+    let transpose = AxisOp::Move(rank - 2, rank - 1);
+    if qk.a_trans {
+        q = patch.wire_node(format!("{}.q_trans", node.name), transpose.clone(), &[q])?[0];
+    }
+    if !qk.b_trans {
+        k = patch.wire_node(format!("{}.k_trans", node.name), transpose.clone(), &[k])?[0];
+    }
+    if av.b_trans {
+        v = patch.wire_node(format!("{}.v_trans", node.name), transpose, &[v])?[0];
+    }
+    let output = patch.wire_node(&node.name, ScaledDotProductAttention::new(scale), &[q, k, v])?[0];
+    patch.shunt_outside(model, node.id.into(), output)?;
+    Ok(Some(patch))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::{math, nn};
+
+    enum Scaling {
+        None,
+        Mul(f32),
+        Div(f32),
+    }
+
+    fn attention_model(scaling: Scaling) -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let q = model.add_source("q", TypedFact::dt_shape(f32::datum_type(), &[2usize, 3, 4]))?;
+        let k = model.add_source("k", TypedFact::dt_shape(f32::datum_type(), &[2usize, 5, 4]))?;
+        let v = model.add_source("v", TypedFact::dt_shape(f32::datum_type(), &[2usize, 5, 2]))?;
+        let mut wire = model.wire_node("qk", MatMul::default().with_b_trans(true), &[q, k])?[0];
+        match scaling {
+            Scaling::None => (),
+            Scaling::Mul(scale) => {
+                let op = math::mul::unary(rctensor3(&[[[scale]]]));
+                wire = model.wire_node("scale", op, &[wire])?[0];
+            }
+            Scaling::Div(divisor) => {
+                let divisor = model.add_const("divisor", rctensor3(&[[[divisor]]]))?;
+                wire = model.wire_node("scale", math::div::bin_typed(), &[wire, divisor])?[0];
+            }
+        }
+        let max = model.wire_node("max", nn::Reduce::new(tvec!(2), Reducer::Max), &[wire])?[0];
+        let normed = model.wire_node("normed", math::sub::bin_typed(), &[wire, max])?[0];
+        let exp = model.wire_node("exp", math::exp(), &[normed])?[0];
+        let sum = model.wire_node("sum", nn::Reduce::new(tvec!(2), Reducer::Sum), &[exp])?[0];
+        let softmax = model.wire_node("softmax", math::div::bin_typed(), &[exp, sum])?[0];
+        let output = model.wire_node("av", MatMul::default(), &[softmax, v])?[0];
+        model.set_output_outlets(&[output])?;
+        Ok(model)
+    }
+
+    fn inputs() -> TVec<Tensor> {
+        let q = Tensor::from(Array::linspace(-1f32, 1., 24).into_shape((2, 3, 4)).unwrap());
+        let k = Tensor::from(Array::linspace(1f32, -0.5, 40).into_shape((2, 5, 4)).unwrap());
+        let v = Tensor::from(Array::linspace(0f32, 2., 20).into_shape((2, 5, 2)).unwrap());
+        tvec!(q, k, v)
+    }
+
+    fn check(model: TypedModel) -> TractResult<()> {
+        let expected = SimplePlan::new(&model)?.run(inputs())?;
+        let decluttered = model.declutter()?;
+        assert!(decluttered.nodes().iter().any(|n| n.op_is::<ScaledDotProductAttention>()));
+        assert!(!decluttered.nodes().iter().any(|n| n.op_is::<Reduce>()));
+        let found = SimplePlan::new(&decluttered)?.run(inputs())?;
+        found[0].close_enough(&expected[0], true)
+    }
+
+    #[test]
+    fn fuse_scaled_attention() -> TractResult<()> {
+        check(attention_model(Scaling::Mul(0.5))?)
+    }
+
+    #[test]
+    fn fuse_attention_divided_by_sqrt_d() -> TractResult<()> {
+        let model = attention_model(Scaling::Div(2.0))?;
+        // matched as is, before the division is turned into a multiplication
+        let patch = declutter_attention(&model, model.node_by_name("av")?)?.unwrap();
+        let fused = patch.model.nodes().iter().find_map(|n| n.op_as::<ScaledDotProductAttention>());
+        assert_eq!(fused.unwrap().scale, 0.5);
+        check(model)
+    }
+
+    #[test]
+    fn fuse_unscaled_attention() -> TractResult<()> {
+        check(attention_model(Scaling::None)?)
+    }
+
+    #[test]
+    fn causal_mask() -> TractResult<()> {
+        let inputs = inputs();
+        let (q, k) = (inputs[0].slice(1, 0, 3)?, inputs[1].slice(1, 0, 3)?);
+        let v = inputs[2].slice(1, 0, 3)?;
+        let op = ScaledDotProductAttention::new(0.5)
+            .with_mask(AttentionMask::Causal { left_context: Some(1) });
+        let output = op.eval_at(&q, &k, &v, 0, 0)?;
+        // each query row matches unmasked attention on its visible keys only
+        for row in 0..3usize {
+            let from = row.saturating_sub(1);
+            let expected = ScaledDotProductAttention::new(0.5).eval_at(
+                &q.slice(1, row, row + 1)?,
+                &k.slice(1, from, row + 1)?,
+                &v.slice(1, from, row + 1)?,
+                0,
+                0,
+            )?;
+            output.slice(1, row, row + 1)?.close_enough(&expected, true)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn blocks_match_rows() -> TractResult<()> {
+        let n = 2 * KEY_BLOCK + 7;
+        let q = Array::linspace(-1f32, 1., 5 * 4).into_shape((5, 4)).unwrap();
+        let k = Array::linspace(2f32, -2., n * 4).into_shape((n, 4)).unwrap();
+        let v = Array::linspace(0f32, 3., n * 2).into_shape((n, 2)).unwrap();
+        for mask in &[
+            AttentionMask::Full,
+            AttentionMask::Causal { left_context: Some(KEY_BLOCK + 3) },
+            AttentionMask::Chunked { chunk: 100, left_chunks: 1 },
+        ] {
+            let op = ScaledDotProductAttention::new(0.5).with_mask(*mask);
+            let mut blocks = Array2::<f32>::zeros((5, 2));
+            let mut rows = Array2::<f32>::zeros((5, 2));
+            op.eval_blocks(q.view(), k.view(), v.view(), blocks.view_mut(), n as i64 - 3, -2)?;
+            op.eval_rows(q.view(), k.view(), v.view(), rows.view_mut(), n as i64 - 3, -2);
+            blocks.into_tensor().close_enough(&rows.into_tensor(), true)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn do_not_fuse_when_scores_are_used() -> TractResult<()> {
+        let mut model = attention_model(Scaling::None)?;
+        let scores = model.node_by_name("qk")?.id;
+        let output = model.output_outlets()?[0];
+        model.set_output_outlets(&[output, scores.into()])?;
+        let decluttered = model.declutter()?;
+        assert!(!decluttered.nodes().iter().any(|n| n.op_is::<ScaledDotProductAttention>()));
+        Ok(())
+    }
+}
@@ -1,25 +1,20 @@
 use crate::internal::*;
 use std::fmt;
 
-#[derive(Copy, Clone, Debug, PartialEq, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Hash, Default)]
 pub enum DataFormat {
+    #[default]
     NCHW,
     NHWC,
     CHW,
     HWC,
 }
 
-impl Default for DataFormat {
-    fn default() -> DataFormat {
-        DataFormat::NCHW
-    }
-}
-
 impl DataFormat {
     pub fn dispose_n_axis(&self) -> DataFormat {
-        match self {
-            &DataFormat::NCHW => DataFormat::CHW,
-            &DataFormat::NHWC => DataFormat::HWC,
+        match *self {
+            DataFormat::NCHW => DataFormat::CHW,
+            DataFormat::NHWC => DataFormat::HWC,
             _ => panic!("Attempt at removing N axis on {:?}", self),
         }
     }
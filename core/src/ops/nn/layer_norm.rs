@@ -0,0 +1,119 @@
+use crate::internal::*;
+use num_traits::Float;
+
+/// Normalize over the trailing axes, starting at `axis`, then apply scale and bias.
+///
+/// Inputs are the data, the scale and the bias. Scale and bias must be broadcastable to the
+/// normalized (trailing) part of the data shape.
+#[derive(Debug, Clone, new, Educe)]
+#[educe(Hash)]
+pub struct LayerNorm {
+    pub axis: usize,
+    #[educe(Hash(method = "hash_f32"))]
+    pub epsilon: f32,
+}
+
+impl_dyn_hash!(LayerNorm);
+
+impl LayerNorm {
+    fn eval_t<T: Datum + Float>(&self, inputs: &[Arc<Tensor>]) -> TractResult<Tensor> {
+        let mut output = inputs[0].clone().into_tensor();
+        let inner_shape = &inputs[0].shape()[self.axis..];
+        let inner: usize = inner_shape.iter().product();
+        let flat = |t: &Tensor| -> TractResult<Vec<T>> {
+            Ok(t.to_array_view::<T>()?
+                .broadcast(inner_shape)
+                .with_context(|| format!("Can not broadcast {:?} to {:?}", t, inner_shape))?
+                .iter()
+                .copied()
+                .collect())
+        };
+        let scale = flat(&inputs[1])?;
+        let bias = flat(&inputs[2])?;
+        let epsilon = T::from(self.epsilon).unwrap();
+        let len = T::from(inner).unwrap();
+        if inner > 0 {
+            for chunk in output.as_slice_mut::<T>()?.chunks_mut(inner) {
+                let mean = chunk.iter().fold(T::zero(), |acc, &x| acc + x) / len;
+                let var =
+                    chunk.iter().fold(T::zero(), |acc, &x| acc + (x - mean) * (x - mean)) / len;
+                let inv_std = (var + epsilon).sqrt().recip();
+                for (i, x) in chunk.iter_mut().enumerate() {
+                    *x = (*x - mean) * inv_std * scale[i] + bias[i];
+                }
+            }
+        }
+        Ok(output)
+    }
+}
+
+impl Op for LayerNorm {
+    fn name(&self) -> Cow<str> {
+        "LayerNorm".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("axis: {} epsilon: {}", self.axis, self.epsilon)])
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for LayerNorm {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let output = dispatch_floatlike!(Self::eval_t(inputs[0].datum_type())(self, &inputs))?;
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl TypedOp for LayerNorm {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        if inputs.len() != 3 {
+            bail!("LayerNorm expects data, scale and bias inputs, got {}", inputs.len());
+        }
+        if self.axis >= inputs[0].rank() {
+            bail!("LayerNorm axis {} is out of range for {:?}", self.axis, inputs[0]);
+        }
+        Ok(tvec!(TypedFact::dt_shape(inputs[0].datum_type, inputs[0].shape.clone())))
+    }
+
+    fn invariants(&self, _model: &TypedModel, _node: &TypedNode) -> TractResult<Invariants> {
+        Ok((0..self.axis).map(AxisInfo::simple).collect())
+    }
+
+    fn cost(&self, inputs: &[&TypedFact]) -> TractResult<TVec<(Cost, TDim)>> {
+        let dt = inputs[0].datum_type;
+        let len: TDim = inputs[0].shape.iter().maybe_product()?;
+        Ok(tvec!((Cost::FMA(dt), len.clone() * 3), (Cost::Div(dt), len)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn last_axis() -> TractResult<()> {
+        let op = LayerNorm::new(1, 0.0);
+        let x = tensor2(&[[1f32, 3.], [4., 2.]]);
+        let output = op.eval(tvec!(x.into(), rctensor1(&[2f32, 1.]), rctensor0(1f32)))?;
+        assert_eq!(output[0], rctensor2(&[[-1f32, 2.], [3., 0.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn trailing_axes() -> TractResult<()> {
+        let op = LayerNorm::new(1, 0.0);
+        let x = tensor3(&[[[1f32, 1.], [3., 3.]]]);
+        let output = op.eval(tvec!(x.into(), rctensor0(1f32), rctensor0(0f32)))?;
+        assert_eq!(output[0], rctensor3(&[[[-1f32, -1.], [1., 1.]]]));
+        Ok(())
+    }
+}
@@ -1,13 +1,18 @@
+mod attention;
 mod data_formats;
+mod layer_norm;
 mod reduce;
 
+pub(crate) use self::attention::declutter_attention;
+pub use self::attention::{AttentionMask, ScaledDotProductAttention};
 pub use self::data_formats::{BaseDataShape, DataFormat, DataShape};
+pub use self::layer_norm::LayerNorm;
 pub use self::reduce::{Reduce, Reducer};
 
 pub use crate::internal::*;
 
-element_wise!(sigmoid, Sigmoid, [f32] => |_, xs| {
-    (tract_linalg::ops().sigmoid_f32)().run(xs)
-};
-    cost: |dt| {tvec!((Cost::FMA(dt), 11), (Cost::Div(dt), 1))}
+element_wise!(sigmoid, Sigmoid,
+ [f32] => |_, xs| { (tract_linalg::ops().sigmoid_f32)().run(xs) },
+ [f16] => |_, xs| { (tract_linalg::ops().sigmoid_f16)().run(xs) };
+ cost: |dt| {tvec!((Cost::FMA(dt), 11), (Cost::Div(dt), 1))}
 );
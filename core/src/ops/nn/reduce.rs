@@ -128,7 +128,7 @@ impl Reducer {
             };
             output = Some(current_output);
         }
-        return output.unwrap().into_tensor();
+        output.unwrap().into_tensor()
     }
 }
 
@@ -230,7 +230,7 @@ impl TypedOp for Reduce {
         let input = model.outlet_fact(node.inputs[0])?;
         let axes = (0..input.rank())
             .filter(|axis| !self.axes.contains(axis))
-            .map(|axis| AxisInfo::simple(axis))
+            .map(AxisInfo::simple)
             .collect::<TVec<_>>();
         Ok(axes.into())
     }
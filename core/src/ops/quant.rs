@@ -67,8 +67,8 @@ fn info_quantize_linear_i8(q: &QuantizeLinearI8) -> TractResult<Vec<String>> {
 #[educe(Hash)]
 pub struct DequantizeLinearF32 {
     #[educe(Hash(method = "hash_f32"))]
-    scale: f32,
-    zero_point: i32,
+    pub scale: f32,
+    pub zero_point: i32,
 }
 
 impl DequantizeLinearF32 {
@@ -149,10 +149,9 @@ impl TypedOp for DequantizeLinearF32 {
             let q_params = if let Some(op) = quant.op_as::<ElementWiseOp>() {
                 if let Some(mop) = op.0.downcast_ref::<QuantizeLinearU8>() {
                     Some((mop.scale, mop.zero_point as i32, u8::datum_type()))
-                } else if let Some(mop) = op.0.downcast_ref::<QuantizeLinearI8>() {
-                    Some((mop.scale, mop.zero_point as i32, i8::datum_type()))
                 } else {
-                    None
+                    op.0.downcast_ref::<QuantizeLinearI8>()
+                        .map(|mop| (mop.scale, mop.zero_point as i32, i8::datum_type()))
                 }
             } else {
                 None
@@ -213,7 +212,9 @@ impl TypedOp for DequantizeLinearF32 {
                     };
                     let output = SimplePlan::new(adhoc_model)?.run(tvec!(input))?.remove(0);
                     let table: &[u8] = match dt {
-                        DatumType::I8 => unsafe { std::mem::transmute(output.as_slice::<i8>()?) },
+                        DatumType::I8 => unsafe {
+                            std::mem::transmute::<&[i8], &[u8]>(output.as_slice::<i8>()?)
+                        },
                         DatumType::U8 => output.as_slice::<u8>()?,
                         _ => unreachable!(),
                     };
@@ -261,6 +262,7 @@ element_wise_oop!(lookup_table,
  }
 );
 
+#[allow(clippy::borrowed_box)]
 fn hash_lookup_table<H: std::hash::Hasher>(lut: &Box<dyn Lut>, h: &mut H) {
     Hash::hash_slice(lut.table(), h)
 }
@@ -290,10 +292,8 @@ impl crate::ops::binary::BinMiniOp for Scale {
 
     fn eval_uniform_in_place(&self, a: &Tensor, b: &mut Tensor) -> TractResult<()> {
         let a = a.to_scalar::<f32>()?;
-        unsafe fn eval_in_place_t<T: Datum + AsPrimitive<f32>>(
-            a: f32,
-            b: &mut Tensor,
-        ) where
+        unsafe fn eval_in_place_t<T: Datum + AsPrimitive<f32>>(a: f32, b: &mut Tensor)
+        where
             f32: AsPrimitive<T>,
         {
             b.as_slice_mut_unchecked::<T>().iter_mut().for_each(|x| *x = scale_by(*x, a));
@@ -102,7 +102,7 @@ impl MutableState {
         unsafe {
             let full_len = input.shape()[axis];
             let mut shape: TVec<usize> = input.shape().into();
-            shape[axis] = chunk_dim.abs() as usize;
+            shape[axis] = chunk_dim.unsigned_abs();
             let mut t = Tensor::uninitialized_dt(input.datum_type(), &shape)?;
             if chunk_dim < 0 {
                 let chunk_dim = (-chunk_dim) as usize;
@@ -152,7 +152,7 @@ impl MutableState {
 impl OpState for State {
     fn eval(
         &mut self,
-        _session: &mut SessionState,
+        session: &mut SessionState,
         _op: &dyn Op,
         inputs: TVec<Arc<Tensor>>,
     ) -> TractResult<TVec<Arc<Tensor>>> {
@@ -179,18 +179,19 @@ impl OpState for State {
                 })
                 .next()
                 .unwrap();
-            inputs[outside_slot].shape()[axis].div_ceil(chunk.abs() as usize)
+            inputs[outside_slot].shape()[axis].div_ceil(chunk.unsigned_abs())
         };
 
         let mut outputs = tvec!();
         for (ix, output) in op.output_mapping.iter().enumerate() {
             if let Some(slot) = output.full_slot {
                 let fact = op.plan.model().output_fact(ix)?;
-                let mut shape: TVec<usize> = fact.shape.as_concrete().unwrap().into();
+                let mut shape: TVec<usize> =
+                    fact.shape.eval_to_usize(&session.resolved_symbols)?.into_owned();
                 let scanning_dim = output
                     .full_dim_hint
                     .as_ref()
-                    .and_then(|d| d.to_usize().ok())
+                    .and_then(|d| d.eval(&session.resolved_symbols).to_usize().ok())
                     .unwrap_or(shape[output.axis] * iters);
                 shape[output.axis] = scanning_dim;
                 let t = unsafe { Tensor::uninitialized_dt(fact.datum_type, &*shape)? };
@@ -230,7 +231,7 @@ impl OpState for State {
                 })
                 .collect::<TractResult<Vec<_>>>()?
                 .into_iter()
-                .filter_map(|x| x)
+                .flatten()
                 .collect();
 
             trace!("iter_inputs: {:?}", iter_inputs);
@@ -261,11 +262,59 @@ impl OpState for State {
 
         Ok(outputs.into_iter().map(Arc::new).collect())
     }
+
+    fn snapshot(&self) -> TractResult<TVec<Tensor>> {
+        if !self.mutable.model_state.snapshot()?.states.is_empty() {
+            bail!("Can not snapshot a Scan with a stateful body");
+        }
+        let mut tensors = tvec!(tensor0(self.mutable.position as i64));
+        tensors.extend(self.mutable.hidden_state.iter().cloned());
+        Ok(tensors)
+    }
+
+    fn restore(&mut self, mut tensors: TVec<Tensor>) -> TractResult<()> {
+        if tensors.is_empty() || tensors[0].rank() != 0 {
+            bail!("Scan state expects a scalar position first, got {:?}", tensors);
+        }
+        let position = tensors.remove(0).cast_to_scalar::<i64>().context("Scan position")?;
+        // hidden states are absent until the first iteration
+        if !tensors.is_empty() {
+            let states = self
+                .op
+                .input_mapping
+                .iter()
+                .enumerate()
+                .filter(|(_, m)| m.as_state().is_some())
+                .map(|(ix, _)| self.op.plan.model().input_fact(ix))
+                .collect::<TractResult<TVec<_>>>()?;
+            if states.len() != tensors.len() {
+                bail!("Scan has {} hidden states, got {} tensors", states.len(), tensors.len());
+            }
+            for (ix, (fact, tensor)) in states.iter().zip(tensors.iter()).enumerate() {
+                let shape_matches = fact.rank() == tensor.rank()
+                    && fact
+                        .shape
+                        .iter()
+                        .zip(tensor.shape())
+                        .all(|(d, s)| d.to_usize().map(|d| d == *s).unwrap_or(true));
+                if fact.datum_type != tensor.datum_type() || !shape_matches {
+                    bail!("Scan hidden state #{} is {:?}, expected {:?}", ix, tensor, fact);
+                }
+            }
+        }
+        self.mutable.position = position as usize;
+        self.mutable.hidden_state = tensors;
+        Ok(())
+    }
 }
 
 impl TypedOp for LirScan {
     as_op!();
 
+    fn cost(&self, _inputs: &[&TypedFact]) -> TractResult<TVec<(Cost, TDim)>> {
+        state_cost(self.plan.model(), &self.input_mapping)
+    }
+
     fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
         let mut outputs = tvec!();
         let iters = {
@@ -278,7 +327,7 @@ impl TypedOp for LirScan {
                 })
                 .next()
                 .unwrap();
-            inputs[outside_slot].shape[axis].clone().div_ceil(chunk.abs() as _)
+            inputs[outside_slot].shape[axis].clone().div_ceil(chunk.unsigned_abs() as _)
         };
         for (ix, output) in self.output_mapping.iter().enumerate() {
             let fact = self.plan.model().output_fact(ix)?;
@@ -128,7 +128,7 @@ impl Scan {
                 )?));
             }
         }
-        return Ok(None);
+        Ok(None)
     }
 
     fn remove_outer_input_from_mappings(
@@ -183,26 +183,17 @@ impl Scan {
     ) -> TractResult<Option<TypedModelPatch>> {
         let inputs = model.node_input_facts(node.id)?;
         for (ix, mapping) in self.input_mapping.iter().enumerate() {
-            match mapping {
-                InputMapping::State { initializer } => match initializer {
-                    StateInitializer::FromInput(n) => {
-                        if let Some(i) = inputs[*n].konst.as_ref() {
-                            let mut op = self.clone();
-                            op.input_mapping[ix] = InputMapping::State {
-                                initializer: StateInitializer::Value(i.clone()),
-                            };
-                            op.input_mapping =
-                                Self::remove_outer_input_from_mappings(&op.input_mapping, *n);
-                            let mut inputs = node.inputs.clone();
-                            inputs.remove(*n);
-                            return Ok(Some(TypedModelPatch::replace_single_op(
-                                model, node, &inputs, op,
-                            )?));
-                        }
-                    }
-                    _ => (),
-                },
-                _ => (),
+            if let InputMapping::State { initializer: StateInitializer::FromInput(n) } = mapping {
+                if let Some(i) = inputs[*n].konst.as_ref() {
+                    let mut op = self.clone();
+                    op.input_mapping[ix] =
+                        InputMapping::State { initializer: StateInitializer::Value(i.clone()) };
+                    op.input_mapping =
+                        Self::remove_outer_input_from_mappings(&op.input_mapping, *n);
+                    let mut inputs = node.inputs.clone();
+                    inputs.remove(*n);
+                    return Ok(Some(TypedModelPatch::replace_single_op(model, node, &inputs, op)?));
+                }
             }
         }
         Ok(None)
@@ -215,7 +206,10 @@ impl Scan {
     ) -> TractResult<Option<TypedModelPatch>> {
         for (inner_input_id, input) in self.body.input_outlets()?.iter().enumerate() {
             let source_node = self.body.node(input.node);
-            if source_node.outputs[0].successors.len() == 0 {
+            // the last scanning input is kept, as it sets the iteration count
+            let last_scan = self.input_mapping[inner_input_id].as_scan().is_some()
+                && self.input_mapping.iter().filter(|m| m.as_scan().is_some()).count() == 1;
+            if source_node.outputs[0].successors.len() == 0 && !last_scan {
                 let mut new_inputs = node.inputs.clone();
                 let slot = match &self.input_mapping[inner_input_id] {
                     InputMapping::Full { slot } => Some(slot),
@@ -605,12 +599,16 @@ impl EvalOp for Scan {
 impl TypedOp for Scan {
     as_op!();
 
+    fn cost(&self, _inputs: &[&TypedFact]) -> TractResult<TVec<(Cost, TDim)>> {
+        state_cost(&self.body, &self.input_mapping)
+    }
+
     fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
         let mut outputs = tvec!();
         let iters = {
             let (outside_slot, axis, chunk) =
                 self.input_mapping.iter().flat_map(|it| it.as_scan()).next().unwrap();
-            inputs[outside_slot].shape[axis].clone().div_ceil(chunk.abs() as _)
+            inputs[outside_slot].shape[axis].clone().div_ceil(chunk.unsigned_abs() as _)
         };
         for (ix, output) in self.output_mapping.iter().enumerate() {
             let fact = self.body.output_fact(ix)?;
@@ -759,3 +757,99 @@ impl TypedOp for Scan {
         )?))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::math;
+
+    #[test]
+    fn keep_unused_last_scanning_input() -> TractResult<()> {
+        // the body counts iterations without reading the scanned input
+        let mut body = TypedModel::default();
+        body.add_source("x", TypedFact::dt_shape(f32::datum_type(), &[1]))?;
+        let count = body.add_source("count", TypedFact::dt_shape(f32::datum_type(), &[1]))?;
+        let one = body.add_const("one", tensor1(&[1f32]))?;
+        let next = body.wire_node("next", math::add::bin_typed(), &[count, one])?;
+        body.set_output_outlets(&next)?;
+        let scan = Scan::new(
+            body,
+            vec![
+                InputMapping::Scan { slot: 0, axis: 0, chunk: 1 },
+                InputMapping::State { initializer: StateInitializer::FromInput(1) },
+            ],
+            vec![OutputMapping {
+                full_slot: None,
+                axis: 0,
+                chunk: 1,
+                full_dim_hint: None,
+                last_value_slot: Some(0),
+                state: true,
+            }],
+            None,
+            0,
+        )?;
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", TypedFact::dt_shape(f32::datum_type(), &[3]))?;
+        let count = model.add_source("count", TypedFact::dt_shape(f32::datum_type(), &[1]))?;
+        let scan = model.wire_node("scan", scan, &[x, count])?;
+        model.set_output_outlets(&scan)?;
+        let model = model.declutter()?;
+        let scan = model.nodes().iter().find(|n| n.op_is::<Scan>()).unwrap();
+        assert_eq!(scan.inputs.len(), 2);
+        let output = model.into_runnable()?.run(tvec!(tensor1(&[0f32; 3]), tensor1(&[0f32])))?;
+        assert_eq!(*output[0], tensor1(&[3f32]));
+        Ok(())
+    }
+
+    #[test]
+    fn symbolic_scan_length() -> TractResult<()> {
+        // running sum of the scanned input
+        let mut body = TypedModel::default();
+        let x = body.add_source("x", TypedFact::dt_shape(f32::datum_type(), &[1]))?;
+        let acc = body.add_source("acc", TypedFact::dt_shape(f32::datum_type(), &[1]))?;
+        let next = body.wire_node("next", math::add::bin_typed(), &[x, acc])?;
+        body.set_output_outlets(&[next[0], next[0]])?;
+        let scan = Scan::new(
+            body,
+            vec![
+                InputMapping::Scan { slot: 0, axis: 0, chunk: 1 },
+                InputMapping::State { initializer: StateInitializer::FromInput(1) },
+            ],
+            vec![
+                OutputMapping {
+                    full_slot: None,
+                    axis: 0,
+                    chunk: 1,
+                    full_dim_hint: None,
+                    last_value_slot: None,
+                    state: true,
+                },
+                OutputMapping {
+                    full_slot: Some(0),
+                    axis: 0,
+                    chunk: 1,
+                    full_dim_hint: None,
+                    last_value_slot: None,
+                    state: false,
+                },
+            ],
+            None,
+            0,
+        )?;
+        let s = Symbol::new('S');
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", TypedFact::dt_shape(f32::datum_type(), &[s.to_dim()]))?;
+        let acc = model.add_source("acc", TypedFact::dt_shape(f32::datum_type(), &[1]))?;
+        let scan = model.wire_node("scan", scan, &[x, acc])?;
+        model.set_output_outlets(&scan)?;
+        let model = model.into_optimized()?;
+        assert!(model.nodes().iter().any(|n| n.op_is::<super::super::LirScan>()));
+        let plan = SimplePlan::new(model)?;
+        let output = plan.run(tvec!(tensor1(&[1f32, 2., 3.]), tensor1(&[0f32])))?;
+        assert_eq!(*output[0], tensor1(&[1f32, 3., 6.]));
+        let output = plan.run(tvec!(tensor1(&[1f32; 5]), tensor1(&[1f32])))?;
+        assert_eq!(*output[0], tensor1(&[2f32, 3., 4., 5., 6.]));
+        Ok(())
+    }
+}
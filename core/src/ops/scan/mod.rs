@@ -49,6 +49,22 @@ impl InputMapping {
     }
 }
 
+/// Hidden state carried by a scan from one iteration (and one call) to the next.
+fn state_cost(
+    body: &TypedModel,
+    input_mapping: &[InputMapping],
+) -> TractResult<TVec<(Cost, TDim)>> {
+    input_mapping
+        .iter()
+        .enumerate()
+        .filter(|(_, m)| m.as_state().is_some())
+        .map(|(ix, _)| {
+            let fact = body.input_fact(ix)?;
+            Ok((Cost::Buffer(fact.datum_type), fact.shape.iter().maybe_product()?))
+        })
+        .collect()
+}
+
 impl fmt::Debug for InputMapping {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -7,7 +7,7 @@ pub struct PropConst;
 
 impl super::TypedPass for PropConst {
     fn reset(&mut self) -> TractResult<()> {
-        return Ok(());
+        Ok(())
     }
     fn next(&mut self, model: &TypedModel) -> TractResult<Option<TypedModelPatch>> {
         let mut patch = TypedModelPatch::default();
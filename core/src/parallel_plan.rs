@@ -0,0 +1,315 @@
+//! Inter-op parallel execution plan.
+//!
+//! `ParallelPlan` evaluates the same nodes as `SimplePlan`, but starts every
+//! node as soon as all its inputs are available. Stateless nodes are handed
+//! over to a rayon thread pool, while stateful nodes run on the calling
+//! thread, which is the only one to own the `SessionState`.
+use std::borrow::Borrow;
+use std::fmt::{Debug, Display};
+use std::sync::mpsc;
+
+use crate::internal::*;
+use crate::model::{Fact, Graph, OutletId};
+use crate::plan::SimplePlan;
+
+#[derive(Debug, Clone)]
+pub struct ParallelPlan<F, O, M>
+where
+    F: Fact + Hash + Clone + 'static,
+    O: Debug + Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + 'static + Hash,
+    M: Borrow<Graph<F, O>> + Hash,
+{
+    pub plan: SimplePlan<F, O, M>,
+    /// For each node, the number of inputs wires it consumes within the plan.
+    pub pending_inputs: Vec<usize>,
+    /// For each node, the number of times its outputs are consumed within the plan.
+    pub consumers: Vec<usize>,
+    /// For each node, the nodes of the plan consuming one of its outputs.
+    pub successors: Vec<TVec<usize>>,
+    pub pool: Option<Arc<rayon::ThreadPool>>,
+}
+
+impl<F, O, M> ParallelPlan<F, O, M>
+where
+    F: Fact + Hash + Clone + 'static,
+    O: Debug + Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + 'static + Hash + Send + Sync,
+    M: Borrow<Graph<F, O>> + Hash,
+{
+    /// This contructor returns a plan that will compute all the model default outputs.
+    pub fn new(model: M) -> TractResult<ParallelPlan<F, O, M>> {
+        Self::from_simple_plan(SimplePlan::new(model)?)
+    }
+
+    /// This contructor returns a plan that will compute all specified outputs.
+    pub fn new_for_outputs(model: M, outputs: &[OutletId]) -> TractResult<ParallelPlan<F, O, M>> {
+        Self::from_simple_plan(SimplePlan::new_for_outputs(model, outputs)?)
+    }
+
+    /// Build a parallel plan covering the same nodes as `plan`.
+    pub fn from_simple_plan(plan: SimplePlan<F, O, M>) -> TractResult<ParallelPlan<F, O, M>> {
+        let model = plan.model();
+        let mut in_plan = vec![false; model.nodes().len()];
+        for &n in &plan.order {
+            in_plan[n] = true;
+        }
+        let mut pending_inputs = vec![0; model.nodes().len()];
+        let mut consumers = vec![0; model.nodes().len()];
+        let mut successors = vec![tvec!(); model.nodes().len()];
+        for &n in &plan.order {
+            for input in &model.node(n).inputs {
+                if !in_plan[input.node] {
+                    bail!("Node {} depends on {} which is not in the plan", n, input.node);
+                }
+                pending_inputs[n] += 1;
+                consumers[input.node] += 1;
+                successors[input.node].push(n);
+            }
+        }
+        Ok(ParallelPlan { plan, pending_inputs, consumers, successors, pool: None })
+    }
+
+    /// Use a dedicated thread pool instead of rayon global one.
+    pub fn with_thread_pool(mut self, pool: Arc<rayon::ThreadPool>) -> ParallelPlan<F, O, M> {
+        self.pool = Some(pool);
+        self
+    }
+
+    pub fn run(&self, inputs: TVec<Tensor>) -> TractResult<TVec<Arc<Tensor>>> {
+        let mut state = ParallelState::new(self)?;
+        state.run(inputs)
+    }
+
+    pub fn model(&self) -> &Graph<F, O> {
+        self.plan.model()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ParallelState<F, O, M, P>
+where
+    F: Fact + Hash + Clone + 'static,
+    O: Debug + Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + 'static + Hash,
+    M: Borrow<Graph<F, O>> + Hash,
+    P: Borrow<ParallelPlan<F, O, M>>,
+{
+    plan: P,
+    pub states: Vec<Option<Box<dyn OpState>>>,
+    pub session_state: SessionState,
+    pub values: Vec<Option<TVec<Arc<Tensor>>>>,
+    _phantom: PhantomData<(M, F, O)>,
+}
+
+impl<F, O, M, P> ParallelState<F, O, M, P>
+where
+    F: Fact + Hash + Clone + 'static,
+    O: Debug + Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + 'static + Hash + Send + Sync,
+    M: Borrow<Graph<F, O>> + Hash,
+    P: Borrow<ParallelPlan<F, O, M>> + Clone,
+{
+    pub fn new(plan: P) -> TractResult<ParallelState<F, O, M, P>> {
+        let values = vec![None; plan.borrow().model().nodes().len()];
+        let mut session = SessionState::default();
+        let states = plan
+            .borrow()
+            .model()
+            .nodes()
+            .iter()
+            .map(|n| n.op().state(&mut session, n.id))
+            .collect::<TractResult<_>>()?;
+        Ok(ParallelState { plan, states, session_state: session, values, _phantom: PhantomData })
+    }
+
+    /// Reset wires state.
+    pub fn reset_wires(&mut self) -> TractResult<()> {
+        self.values.iter_mut().for_each(|s| *s = None);
+        Ok(())
+    }
+
+    /// Reset op states.
+    pub fn reset_op_states(&mut self) -> TractResult<()> {
+        let &mut ParallelState { ref plan, ref mut session_state, ref mut states, .. } = self;
+        *states = plan
+            .borrow()
+            .model()
+            .nodes()
+            .iter()
+            .map(|n| n.op().state(session_state, n.id))
+            .collect::<TractResult<_>>()?;
+        Ok(())
+    }
+
+    pub fn run(&mut self, inputs: TVec<Tensor>) -> TractResult<TVec<Arc<Tensor>>> {
+        self.set_inputs(inputs)?;
+        self.exec()?;
+        let plan = self.plan.borrow();
+        let mut result = tvec!();
+        for output in &plan.plan.outputs {
+            let value = self.values[output.node]
+                .as_ref()
+                .ok_or_else(|| format_err!("Output {:?} was not computed", output))?;
+            result.push(value[output.slot].clone());
+        }
+        self.reset_wires()?;
+        Ok(result)
+    }
+
+    /// Evaluate every node of the plan, leaving the plan outputs in `values`.
+    pub fn exec(&mut self) -> TractResult<()> {
+        let ParallelState {
+            ref plan, ref mut session_state, ref mut states, ref mut values, ..
+        } = self;
+        let plan = plan.borrow();
+        if let Some(pool) = &plan.pool {
+            pool.in_place_scope(|scope| schedule(scope, plan, session_state, states, values))
+        } else {
+            rayon::in_place_scope(|scope| schedule(scope, plan, session_state, states, values))
+        }
+    }
+
+    pub fn set_inputs(&mut self, inputs: TVec<Tensor>) -> TractResult<()> {
+        for (ix, t) in inputs.into_iter().enumerate() {
+            self.set_input(ix, t)?
+        }
+        Ok(())
+    }
+
+    pub fn set_input(&mut self, input: usize, t: Tensor) -> TractResult<()> {
+        let outlet: OutletId = *self
+            .model()
+            .input_outlets()?
+            .get(input)
+            .ok_or_else(|| format_err!("Invalid input id for model ({}).", input))?;
+        self.model()
+            .outlet_fact(outlet)?
+            .matches(&t)
+            .with_context(|| format!("Setting input {}", input))?;
+        self.session_state.inputs.insert(outlet.node, t.into());
+        Ok(())
+    }
+
+    pub fn take_outputs(&mut self) -> TractResult<Vec<Arc<Tensor>>> {
+        let ParallelState { ref plan, ref mut values, .. } = self;
+        let mut v = vec![];
+        for o in plan.borrow().model().output_outlets()?.iter() {
+            let vs = values[o.node].as_mut().ok_or_else(|| {
+                format_err!(
+                    "Outputs of {:?} are not computed",
+                    &plan.borrow().model().nodes()[o.node]
+                )
+            })?;
+            v.push(vs[o.slot].clone())
+        }
+        Ok(v)
+    }
+
+    pub fn plan(&self) -> &ParallelPlan<F, O, M> {
+        &self.plan.borrow()
+    }
+
+    pub fn model(&self) -> &Graph<F, O> {
+        self.plan().model()
+    }
+}
+
+fn schedule<'s, F, O, M>(
+    scope: &rayon::Scope<'s>,
+    plan: &'s ParallelPlan<F, O, M>,
+    session_state: &mut SessionState,
+    states: &mut [Option<Box<dyn OpState>>],
+    values: &mut [Option<TVec<Arc<Tensor>>>],
+) -> TractResult<()>
+where
+    F: Fact + Hash + Clone + 'static,
+    O: Debug + Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + 'static + Hash + Send + Sync,
+    M: Borrow<Graph<F, O>> + Hash,
+{
+    let model = plan.model();
+    let mut pending_inputs = plan.pending_inputs.clone();
+    let mut consumers = plan.consumers.clone();
+    let mut is_output = vec![false; model.nodes().len()];
+    for o in &plan.plan.outputs {
+        is_output[o.node] = true;
+    }
+    let mut ready: Vec<usize> =
+        plan.plan.order.iter().cloned().filter(|&n| pending_inputs[n] == 0).collect();
+    let mut remaining = plan.plan.order.len();
+    let (tx, rx) = mpsc::channel::<(usize, TractResult<TVec<Arc<Tensor>>>)>();
+    let mut running = 0;
+    while remaining > 0 {
+        while let Some(n) = ready.pop() {
+            let node = model.node(n);
+            let mut inputs: TVec<Arc<Tensor>> = tvec!();
+            for i in &node.inputs {
+                let prec = values[i.node].as_ref().ok_or_else(|| {
+                    format_err!("Computing {}, precursor {} not done", node, model.node(i.node))
+                })?;
+                inputs.push(prec[i.slot].clone());
+            }
+            for i in &node.inputs {
+                consumers[i.node] -= 1;
+                if consumers[i.node] == 0 && !is_output[i.node] {
+                    values[i.node] = None;
+                }
+            }
+            if let Some(state) = states[n].as_mut() {
+                let result = state
+                    .eval(session_state, node.op(), inputs)
+                    .with_context(|| format!("Evaluating {}", node));
+                tx.send((n, result))?;
+            } else {
+                let tx = tx.clone();
+                scope.spawn(move |_| {
+                    let result =
+                        node.op().eval(inputs).with_context(|| format!("Evaluating {}", node));
+                    let _ = tx.send((n, result));
+                });
+            }
+            running += 1;
+        }
+        if running == 0 {
+            bail!("Parallel plan is stuck with {} nodes left to compute", remaining);
+        }
+        let (n, result) = rx.recv()?;
+        running -= 1;
+        remaining -= 1;
+        // on error, tasks still running are joined by the scope, and their results dropped
+        values[n] = Some(result?);
+        for &succ in &plan.successors[n] {
+            pending_inputs[succ] -= 1;
+            if pending_inputs[succ] == 0 {
+                ready.push(succ);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::math;
+
+    #[test]
+    fn towers() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let input = model.add_source("input", TypedFact::dt_shape(f32::datum_type(), &[3]))?;
+        let mut towers = tvec!();
+        for i in 0..4 {
+            let k = model.add_const(format!("k.{}", i), tensor1(&[i as f32; 3]))?;
+            let wire =
+                model.wire_node(format!("add.{}", i), math::add::bin_typed(), &[input, k])?;
+            towers.push(
+                model.wire_node(format!("mul.{}", i), math::mul::bin_typed(), &[wire[0], k])?[0],
+            );
+        }
+        let sum = model.wire_node("sum.0", math::add::bin_typed(), &[towers[0], towers[1]])?;
+        let sum = model.wire_node("sum.1", math::add::bin_typed(), &[sum[0], towers[2]])?;
+        let sum = model.wire_node("sum.2", math::add::bin_typed(), &[sum[0], towers[3]])?;
+        model.set_output_outlets(&[sum[0], towers[1]])?;
+        let input = tensor1(&[1f32, 2., 3.]);
+        let simple = SimplePlan::new(&model)?.run(tvec!(input.clone()))?;
+        let parallel = ParallelPlan::new(&model)?.run(tvec!(input))?;
+        assert_eq!(simple, parallel);
+        Ok(())
+    }
+}
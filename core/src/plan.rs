@@ -3,8 +3,10 @@ use std::fmt::{Debug, Display};
 use std::marker::PhantomData;
 
 use crate::internal::*;
+use crate::memory_plan::MemoryPlan;
 use crate::model::order::eval_order_for_nodes;
 use crate::model::{Fact, Graph, OutletId};
+use tract_linalg::multithread::{multithread_tract_scope, Executor};
 
 #[derive(Default)]
 pub struct SessionState {
@@ -31,6 +33,16 @@ impl Debug for SessionState {
     }
 }
 
+/// Op states and session tensors of a `SimpleState`, to checkpoint a stream and resume it in a
+/// state built from the same plan.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StateSnapshot {
+    /// Tensors of the stateful nodes, by node name. Nodes with nothing to save are omitted.
+    pub states: Vec<(String, TVec<Tensor>)>,
+    /// Session tensors (TensorFlow variables).
+    pub tensors: HashMap<String, Tensor>,
+}
+
 #[derive(Debug, Clone, Educe)]
 #[educe(Hash)]
 pub struct SimplePlan<F, O, M>
@@ -43,6 +55,10 @@ where
     pub outputs: Vec<OutletId>,
     pub order: Vec<usize>,
     pub flush_lists: Vec<TVec<usize>>,
+    #[educe(Hash(ignore))]
+    pub executor: Option<Executor>,
+    #[educe(Hash(ignore))]
+    pub memory_plan: Option<Arc<MemoryPlan>>,
     _casper: PhantomData<(F, O)>,
 }
 
@@ -54,7 +70,7 @@ where
 {
     /// This contructor returns a plan that will compute all the model default outputs in one pass.
     pub fn new(model: M) -> TractResult<SimplePlan<F, O, M>> {
-        let outputs = model.borrow().output_outlets()?.iter().cloned().collect::<Vec<OutletId>>();
+        let outputs = model.borrow().output_outlets()?.to_vec();
         Self::new_for_outputs(model, &outputs)
     }
 
@@ -96,10 +112,27 @@ where
             order,
             flush_lists,
             outputs: outputs.to_vec(),
+            executor: None,
+            memory_plan: None,
             _casper: PhantomData,
         })
     }
 
+    /// Run the matrix products of this plan with the given executor instead
+    /// of the process-wide default.
+    pub fn with_executor(mut self, executor: Executor) -> SimplePlan<F, O, M> {
+        self.executor = Some(executor);
+        self
+    }
+
+    /// Place the intermediate values of this plan in a single arena, reused from one run to
+    /// the next.
+    pub fn with_memory_plan(mut self) -> TractResult<SimplePlan<F, O, M>> {
+        let plan = MemoryPlan::new(self.model(), &self.order, &self.outputs)?;
+        self.memory_plan = Some(Arc::new(plan));
+        Ok(self)
+    }
+
     pub fn run(&self, inputs: TVec<Tensor>) -> TractResult<TVec<Arc<Tensor>>> {
         let mut state = SimpleState::new(self)?;
         state.run(inputs)
@@ -122,6 +155,7 @@ where
     pub states: Vec<Option<Box<dyn OpState>>>,
     pub session_state: SessionState,
     pub values: Vec<Option<TVec<Arc<Tensor>>>>,
+    arena: Option<Arc<Arena>>,
     _phantom: PhantomData<(M, F, O)>,
 }
 
@@ -141,7 +175,15 @@ where
             .iter()
             .map(|n: &Node<F, O>| n.op().state(&mut session, n.id))
             .collect::<TractResult<_>>()?;
-        Ok(SimpleState { plan, states, session_state: session, values, _phantom: PhantomData })
+        let arena = plan.borrow().memory_plan.as_ref().map(|mp| mp.arena()).transpose()?;
+        Ok(SimpleState {
+            plan,
+            states,
+            session_state: session,
+            values,
+            arena,
+            _phantom: PhantomData,
+        })
     }
 
     /// Reset wires state.
@@ -163,11 +205,66 @@ where
         Ok(())
     }
 
+    /// Save the op states and the session tensors.
+    pub fn snapshot(&self) -> TractResult<StateSnapshot> {
+        let model = self.plan.borrow().model();
+        let mut states = vec![];
+        for (id, state) in self.states.iter().enumerate() {
+            if let Some(state) = state {
+                let node = model.node(id);
+                let tensors =
+                    state.snapshot().with_context(|| format!("Saving state of {}", node))?;
+                if !tensors.is_empty() {
+                    states.push((node.name.clone(), tensors));
+                }
+            }
+        }
+        Ok(StateSnapshot { states, tensors: self.session_state.tensors.clone() })
+    }
+
+    /// Reset the op states, then restore a snapshot taken on a state of the same plan.
+    pub fn restore(&mut self, snapshot: &StateSnapshot) -> TractResult<()> {
+        self.reset_op_states()?;
+        self.session_state.tensors = snapshot.tensors.clone();
+        let model = self.plan.borrow().model();
+        for (name, tensors) in &snapshot.states {
+            let node = model.node_by_name(name)?;
+            let state = self.states[node.id]
+                .as_mut()
+                .ok_or_else(|| format_err!("{} has no state to restore", node))?;
+            state
+                .restore(tensors.clone())
+                .with_context(|| format!("Restoring state of {}", node))?;
+        }
+        Ok(())
+    }
+
     pub fn run(&mut self, inputs: TVec<Tensor>) -> TractResult<TVec<Arc<Tensor>>> {
         self.run_plan_with_eval(inputs, self::eval)
     }
 
     pub fn run_plan_with_eval<Eval, E>(
+        &mut self,
+        inputs: TVec<Tensor>,
+        eval: Eval,
+    ) -> TractResult<TVec<Arc<Tensor>>>
+    where
+        Eval: for<'a, 'b, 'c> FnMut(
+            &'a mut SessionState,
+            Option<&'b mut (dyn OpState + 'static)>,
+            &'c Node<F, O>,
+            TVec<Arc<Tensor>>,
+        ) -> Result<TVec<Arc<Tensor>>, E>,
+        E: Into<anyhow::Error> + Send + Sync + 'static,
+    {
+        if let Some(executor) = self.plan().executor.clone() {
+            multithread_tract_scope(executor, || self.do_run_plan_with_eval(inputs, eval))
+        } else {
+            self.do_run_plan_with_eval(inputs, eval)
+        }
+    }
+
+    fn do_run_plan_with_eval<Eval, E>(
         &mut self,
         inputs: TVec<Tensor>,
         mut eval: Eval,
@@ -189,10 +286,13 @@ where
                 ref mut session_state,
                 ref mut states,
                 ref mut values,
+                ref arena,
                 ..
             } = self;
             let plan = plan.borrow();
-            let model = plan.model().borrow();
+            let model = plan.model();
+            // symbols bound by the debug checks stay out of the session
+            let mut checked_symbols = session_state.resolved_symbols.clone();
             for (step, n) in plan.order.iter().enumerate() {
                 let node = model.node(*n);
                 trace!("Running step {}, node {}", step, node);
@@ -222,7 +322,7 @@ where
                         );
                     }
                     for (ix, (v, f)) in inputs.iter().zip(facts.iter()).enumerate() {
-                        if !f.matches(v)? {
+                        if !fact_matches(*f, v, &mut checked_symbols)? {
                             bail!(
                                 "Evaluating {}: input {:?}, expected {:?}, got {:?}",
                                 node,
@@ -234,9 +334,29 @@ where
                     }
                 }
 
-                let vs =
-                    eval(session_state, states[node.id].as_mut().map(|s| &mut **s), node, inputs)
-                        .map_err(|e| e.into())?;
+                let state = states[node.id].as_deref_mut();
+                // a node planned in place gets the only reference to its input (the precursor
+                // value has just been flushed) and computes its output over it
+                let in_place = plan
+                    .memory_plan
+                    .as_ref()
+                    .and_then(|mp| mp.in_place[node.id])
+                    .map(|ix| unsafe { inputs[ix].as_ptr_unchecked::<u8>() });
+                let vs = if let (Some(arena), Some(mp)) = (arena, &plan.memory_plan) {
+                    arena.scope(&mp.node_regions[node.id], || {
+                        eval(session_state, state, node, inputs)
+                    })
+                } else {
+                    eval(session_state, state, node, inputs)
+                }
+                .map_err(|e| e.into())?;
+                if let Some(input) = in_place {
+                    debug_assert!(
+                        unsafe { vs[0].as_ptr_unchecked::<u8>() } == input,
+                        "Evaluating {}: planned in place, but output does not reuse input",
+                        node
+                    );
+                }
 
                 if cfg!(debug_assertions) {
                     let facts = model.node_output_facts(node.id)?;
@@ -252,7 +372,7 @@ where
                         if node.outputs[ix].successors.len() == 0 {
                             continue;
                         }
-                        if !f.matches(v)? {
+                        if !fact_matches(*f, v, &mut checked_symbols)? {
                             bail!(
                                 "Evaluating {}: output {:?}, expected {:?}, got {:?}",
                                 node,
@@ -275,25 +395,43 @@ where
         Ok(result)
     }
 
+    /// Set all the inputs, binding the symbols of the input facts afresh.
     pub fn set_inputs(&mut self, inputs: TVec<Tensor>) -> TractResult<()> {
+        let mut symbols = vec![];
+        for outlet in self.model().input_outlets()? {
+            if let Ok(fact) = self.model().outlet_fact(*outlet)?.to_typed_fact() {
+                symbols.extend(fact.shape.iter().flat_map(|d| d.symbols()));
+            }
+        }
+        for sym in symbols {
+            self.session_state.resolved_symbols[sym] = None;
+        }
         for (ix, t) in inputs.into_iter().enumerate() {
             self.set_input(ix, t)?
         }
         Ok(())
     }
 
+    /// Set one input. Symbols already bound, by the other inputs, must agree with its shape.
     pub fn set_input(&mut self, input: usize, t: Tensor) -> TractResult<()> {
         let outlet: OutletId = *self
             .model()
             .input_outlets()?
             .get(input)
             .ok_or_else(|| format_err!("Invalid input id for model ({}).", input))?;
-        self.plan
-            .borrow()
-            .model()
-            .outlet_fact(outlet)?
-            .matches(&t)
-            .with_context(|| format!("Setting input {}", input))?;
+        // symbolic input dimensions are bound to the actual ones for the ops resolving their
+        // shapes at runtime
+        if let Ok(fact) = self.model().outlet_fact(outlet)?.to_typed_fact() {
+            bind_symbols(&fact, &t, &mut self.session_state.resolved_symbols)
+                .with_context(|| format!("Setting input {}", input))?;
+        } else {
+            self.plan
+                .borrow()
+                .model()
+                .outlet_fact(outlet)?
+                .matches(&t)
+                .with_context(|| format!("Setting input {}", input))?;
+        }
         self.session_state.inputs.insert(outlet.node, t.into());
         Ok(())
     }
@@ -381,9 +519,9 @@ where
             let plan = plan.borrow();
             match states[node] {
                 Some(ref mut state) => {
-                    state.eval(session_state, plan.borrow().model().nodes()[node].op(), inputs)
+                    state.eval(session_state, plan.model().nodes()[node].op(), inputs)
                 }
-                None => plan.borrow().model().nodes()[node].op().eval(inputs),
+                None => plan.model().nodes()[node].op().eval(inputs),
             }
             .with_context(|| format!("Evaluating {:?}", node))?
         };
@@ -431,3 +569,85 @@ where
     .with_context(|| format!("Evaluating {}", node));
     r
 }
+
+/// Binds the symbols of a typed fact to the actual shape of `t`, then checks `t` against it.
+///
+/// Affine dimensions (`S`, `2*S+1`...) are solved in turn, each one once the other symbols it
+/// depends on are known. Symbols already bound in `values` are checked, not rebound.
+fn bind_symbols(fact: &TypedFact, t: &Tensor, values: &mut SymbolValues) -> TractResult<()> {
+    if fact.datum_type != t.datum_type() || fact.rank() != t.rank() {
+        bail!("Expected {:?}, got {:?}", fact, t);
+    }
+    let mut trial = values.clone();
+    let dims = fact.shape.iter().zip(t.shape().iter().copied()).collect::<TVec<_>>();
+    solve_symbols(&dims, &mut trial);
+    for (dim, &actual) in fact.shape.iter().zip(t.shape().iter()) {
+        match dim.eval(&trial).to_usize() {
+            Ok(d) if d == actual => (),
+            Ok(_) => bail!("Expected {:?}, got {:?}", fact, t),
+            Err(_) => bail!("Can not bind the symbols of {} from {:?}", dim, t.shape()),
+        }
+    }
+    *values = trial;
+    Ok(())
+}
+
+/// Bind the symbols of dimensions affine in a single unknown symbol to match the actual sizes,
+/// until no more symbol can be found. Dimensions are not checked: some may be left unsolved or
+/// not match.
+pub(crate) fn solve_symbols(dims: &[(TDim, usize)], values: &mut SymbolValues) {
+    loop {
+        let mut progress = false;
+        for (dim, actual) in dims {
+            let dim = dim.eval(values);
+            let unbound = dim.symbols();
+            if unbound.len() == 1 {
+                let sym = unbound.into_iter().next().unwrap();
+                if let Some(value) = dim.solve(&sym, *actual as i64, values) {
+                    values[sym] = Some(value);
+                    progress = true;
+                }
+            }
+        }
+        if !progress {
+            break;
+        }
+    }
+}
+
+/// Check a value against its fact, symbolic dimensions being evaluated with the symbols bound
+/// so far. Symbols met for the first time (the k of a TopK with a dynamic k...) are bound to
+/// the value actually computed. Inference facts are unified with the value.
+fn fact_matches(fact: &dyn Fact, t: &Tensor, values: &mut SymbolValues) -> TractResult<bool> {
+    if let Ok(fact) = fact.to_typed_fact() {
+        Ok(bind_symbols(&fact, t, values).is_ok())
+    } else {
+        fact.matches(t)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::math;
+
+    #[test]
+    fn inputs_agree_on_symbols() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let s = model.symbol_table.sym("S");
+        let fact = TypedFact::dt_shape(f32::datum_type(), [s.to_dim()].as_ref());
+        let a = model.add_source("a", fact.clone())?;
+        let b = model.add_source("b", fact)?;
+        let a = model.wire_node("neg-a", math::neg(), &[a])?[0];
+        let b = model.wire_node("neg-b", math::neg(), &[b])?[0];
+        model.set_output_outlets(&[a, b])?;
+        let mut state = SimpleState::new(SimplePlan::new(model)?)?;
+        let run = |state: &mut TypedSimpleState<_, _>, a: usize, b: usize| {
+            state.run(tvec!(Tensor::zero::<f32>(&[a])?, Tensor::zero::<f32>(&[b])?))
+        };
+        assert!(run(&mut state, 3, 5).is_err());
+        assert_eq!(run(&mut state, 3, 3)?[1].shape(), &[3]);
+        assert_eq!(run(&mut state, 5, 5)?[1].shape(), &[5]);
+        Ok(())
+    }
+}
@@ -0,0 +1,196 @@
+//! Plans specialized for the values taken by the symbols of a model.
+use std::sync::Mutex;
+
+use crate::internal::*;
+
+type SpecializedPlan = Arc<TypedSimplePlan<TypedModel>>;
+
+/// Runs a model with symbolic input dimensions at the speed of a concretely shaped one.
+///
+/// On each run, the values of the symbols are inferred from the input shapes, then the model is
+/// concretized, optimized and planned for these values. Plans are cached by binding, the least
+/// recently used one being dropped when the cache is full.
+#[derive(Debug)]
+pub struct SpecializingPlan {
+    model: TypedModel,
+    symbols: Vec<Symbol>,
+    capacity: usize,
+    memory_plan: bool,
+    plans: Mutex<Vec<(Vec<i64>, SpecializedPlan)>>,
+}
+
+impl SpecializingPlan {
+    /// `model` is expected to be decluttered, but not optimized.
+    pub fn new(model: TypedModel) -> TractResult<SpecializingPlan> {
+        let mut symbols = vec![];
+        for input in model.input_outlets()? {
+            for dim in model.outlet_fact(*input)?.shape.iter() {
+                for symbol in dim.symbols() {
+                    if !symbols.contains(&symbol) {
+                        symbols.push(symbol);
+                    }
+                }
+            }
+        }
+        symbols.sort();
+        Ok(SpecializingPlan {
+            model,
+            symbols,
+            capacity: 16,
+            memory_plan: false,
+            plans: Mutex::new(vec![]),
+        })
+    }
+
+    /// Number of specialized plans to keep around (16 by default).
+    pub fn with_capacity(mut self, capacity: usize) -> SpecializingPlan {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Give the specialized plans a memory plan (see `SimplePlan::with_memory_plan`).
+    pub fn with_memory_plan(mut self) -> SpecializingPlan {
+        self.memory_plan = true;
+        self
+    }
+
+    pub fn model(&self) -> &TypedModel {
+        &self.model
+    }
+
+    /// Build the plans for these sets of input shapes ahead of the runs.
+    pub fn prewarm(&self, shapes: &[TVec<TVec<usize>>]) -> TractResult<()> {
+        for shapes in shapes {
+            let shapes = shapes.iter().map(|s| &**s).collect::<TVec<_>>();
+            self.plan_for(&shapes)?;
+        }
+        Ok(())
+    }
+
+    /// Values of the model symbols for these input shapes.
+    ///
+    /// Symbols are found from input dimensions affine in one unknown symbol, then all input
+    /// dimensions are checked against the actual shapes.
+    pub fn symbol_values(&self, shapes: &[&[usize]]) -> TractResult<SymbolValues> {
+        let inputs = self.model.input_outlets()?;
+        if shapes.len() != inputs.len() {
+            bail!("Model has {} inputs, got {} shapes", inputs.len(), shapes.len());
+        }
+        let facts =
+            inputs.iter().map(|i| self.model.outlet_fact(*i)).collect::<TractResult<TVec<_>>>()?;
+        let mut dims = tvec!();
+        for (ix, (fact, shape)) in facts.iter().zip(shapes.iter()).enumerate() {
+            if fact.rank() != shape.len() {
+                bail!("Input {} is expected to have rank {}, got {:?}", ix, fact.rank(), shape);
+            }
+            dims.extend(fact.shape.iter().zip(shape.iter().copied()));
+        }
+        let mut values = SymbolValues::default();
+        crate::plan::solve_symbols(&dims, &mut values);
+        for (ix, (fact, shape)) in facts.iter().zip(shapes.iter()).enumerate() {
+            let expected = fact
+                .shape
+                .iter()
+                .map(|d| d.eval(&values).to_usize())
+                .collect::<TractResult<TVec<_>>>()
+                .with_context(|| format!("Inferring symbols from input {} ({:?})", ix, shape))?;
+            if &*expected != *shape {
+                bail!("Input {} has shape {:?}, model expects {:?}", ix, shape, expected);
+            }
+        }
+        Ok(values)
+    }
+
+    /// The plan specialized for these input shapes, built if it is not in the cache.
+    pub fn plan_for(&self, shapes: &[&[usize]]) -> TractResult<SpecializedPlan> {
+        let values = self.symbol_values(shapes)?;
+        let key = self.symbols.iter().map(|s| values[s].unwrap()).collect::<Vec<i64>>();
+        {
+            let mut plans = self.plans.lock().unwrap();
+            if let Some(pos) = plans.iter().position(|(k, _)| k == &key) {
+                let entry = plans.remove(pos);
+                let plan = entry.1.clone();
+                plans.push(entry);
+                return Ok(plan);
+            }
+        }
+        let model = self
+            .model
+            .concretize_dims(&values)
+            .and_then(|m| m.into_optimized())
+            .with_context(|| format!("Specializing model for input shapes {:?}", shapes))?;
+        let mut plan = SimplePlan::new(model)?;
+        if self.memory_plan {
+            plan = plan.with_memory_plan()?;
+        }
+        let plan = Arc::new(plan);
+        let mut plans = self.plans.lock().unwrap();
+        if !plans.iter().any(|(k, _)| k == &key) {
+            plans.push((key, plan.clone()));
+        }
+        while plans.len() > self.capacity {
+            plans.remove(0);
+        }
+        Ok(plan)
+    }
+
+    pub fn run(&self, inputs: TVec<Tensor>) -> TractResult<TVec<Arc<Tensor>>> {
+        let plan = {
+            let shapes = inputs.iter().map(|t| t.shape()).collect::<TVec<_>>();
+            self.plan_for(&shapes)?
+        };
+        plan.run(inputs)
+    }
+
+    /// Number of plans in the cache.
+    pub fn len(&self) -> usize {
+        self.plans.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::math;
+
+    fn model(s: Symbol) -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let shape = [s.to_dim() * 2, 4.to_dim()];
+        let source = model.add_source("source", TypedFact::dt_shape(f32::datum_type(), &shape))?;
+        let abs = model.wire_node("abs", math::abs(), &[source])?;
+        model.set_output_outlets(&abs)?;
+        Ok(model)
+    }
+
+    #[test]
+    fn infer_symbols() -> TractResult<()> {
+        let s = Symbol::new('S');
+        let plans = SpecializingPlan::new(model(s.clone())?)?;
+        assert_eq!(plans.symbol_values(&[&[6, 4]])?[&s], Some(3));
+        assert!(plans.symbol_values(&[&[5, 4]]).is_err());
+        assert!(plans.symbol_values(&[&[6, 3]]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn cache_and_evict() -> TractResult<()> {
+        let plans = SpecializingPlan::new(model(Symbol::new('S'))?)?.with_capacity(2);
+        plans.prewarm(&[tvec!(tvec!(2, 4)), tvec!(tvec!(4, 4))])?;
+        assert_eq!(plans.len(), 2);
+        let warm = plans.plan_for(&[&[2, 4]])?;
+        assert!(Arc::ptr_eq(&warm, &plans.plan_for(&[&[2, 4]])?));
+        assert!(warm.model().output_fact(0)?.shape.as_concrete().is_some());
+
+        let output = plans.run(tvec!(tensor2(&[[-1f32; 4]; 6])))?;
+        assert_eq!(*output[0], tensor2(&[[1f32; 4]; 6]));
+        assert_eq!(plans.len(), 2);
+        // [4, 4] was the least recently used plan, [2, 4] is still there
+        assert!(Arc::ptr_eq(&warm, &plans.plan_for(&[&[2, 4]])?));
+        assert_eq!(plans.len(), 2);
+        Ok(())
+    }
+}
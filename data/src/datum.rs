@@ -79,7 +79,7 @@ impl DatumType {
             None => return None,
             Some(it) => *it.borrow(),
         };
-        while let Some(n) = iter.next() {
+        for n in iter {
             match current.common_super_type(*n.borrow()) {
                 None => return None,
                 Some(it) => current = it,
@@ -96,7 +96,7 @@ impl DatumType {
                 }
             }
         }
-        return None;
+        None
     }
 
     pub fn is_unsigned(&self) -> bool {
@@ -10,6 +10,7 @@ pub trait ArrayDatum: Sized {
         tensors: &[impl std::borrow::Borrow<Tensor>],
     ) -> anyhow::Result<Tensor>;
     unsafe fn stack_views(axis: usize, views: &[ArrayViewD<Self>]) -> anyhow::Result<ArrayD<Self>>;
+    #[allow(dead_code)]
     unsafe fn uninitialized_array<S, D, Sh>(shape: Sh) -> ArrayBase<S, D>
     where
         Sh: ShapeBuilder<Dim = D>,
@@ -5,7 +5,7 @@ use std::ops;
 
 mod tree;
 
-pub use self::tree::{Symbol, SymbolValues, TDim};
+pub use self::tree::{Symbol, SymbolScope, SymbolValues, TDim};
 type TractError = anyhow::Error;
 type TractResult<T> = anyhow::Result<T>;
 
@@ -85,8 +85,8 @@ impl DimLike for TDim {
             (_, _) => {
                 if self.symbols().len() == 1 && other.symbols().len() == 1 {
                     let sym = self.symbols().into_iter().nth(0).unwrap();
-                    let slope_p = self.slope(sym);
-                    let slope_q = other.slope(sym);
+                    let slope_p = self.slope(&sym);
+                    let slope_q = other.slope(&sym);
                     let (p, q) = tree::reduce_ratio(
                         slope_p.0 * slope_q.1 as i64,
                         slope_q.0 * slope_p.1 as i64,
@@ -190,7 +190,7 @@ impl<I: Into<TDim> + Clone> ToDim for I {
 
 impl<'a> ToDim for &'a TDim {
     fn to_dim(&self) -> TDim {
-        self.clone().clone()
+        (*self).clone()
     }
 }
 
@@ -203,7 +203,7 @@ mod tests {
     }
 
     pub fn s() -> TDim {
-        (*S).into()
+        TDim::from(&*S)
     }
 
     #[test]
@@ -230,4 +230,11 @@ mod tests {
     fn div_sym_sym_rem() {
         assert!((s() + 1).maybe_div(&(s() * 4)).is_err());
     }
+
+    #[test]
+    fn div_sym_asserted_multiple() {
+        let len: TDim = SymbolScope::default().add_assertion("len % 8 == 0").unwrap().into();
+        assert!((s() * 64).maybe_div(&512.into()).is_err());
+        assert_eq!((len.clone() * 64).maybe_div(&512.into()).unwrap(), (len / 8, 1));
+    }
 }
@@ -1,39 +1,197 @@
 use itertools::Itertools;
 use num_traits::{AsPrimitive, Zero};
 use std::collections::HashMap;
+use std::sync::atomic::{self, AtomicI64, AtomicU64, AtomicUsize};
+use std::sync::Arc;
 use std::{fmt, ops};
 
 macro_rules! b( ($e:expr) => { Box::new($e) } );
 
 lazy_static::lazy_static! {
-    static ref SYMBOL_TABLE: std::sync::Mutex<Vec<char>> = std::sync::Mutex::new(Vec::new());
+    static ref CHAR_SYMBOLS: SymbolScope = SymbolScope::default();
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Ord, PartialOrd, Hash, Debug)]
-pub struct Symbol(char, usize);
+static SYMBOL_IDS: AtomicUsize = AtomicUsize::new(0);
+
+/// Name of a symbol, and what is asserted about its values.
+///
+/// Assertions only ever narrow the values, so they are kept in atomics: reading them during
+/// simplifications takes no lock.
+#[derive(Debug)]
+struct SymbolData {
+    id: usize,
+    name: String,
+    min: AtomicI64,
+    /// `i64::MAX` when unbounded.
+    max: AtomicI64,
+    multiple_of: AtomicU64,
+}
+
+/// A symbol, freed with the last dimension or scope referring to it.
+#[derive(Clone)]
+pub struct Symbol(Arc<SymbolData>);
 
 impl Symbol {
-    pub fn new(c: char) -> Symbol {
-        let mut table = SYMBOL_TABLE.lock().unwrap();
-        table.push(c);
-        Symbol(c, table.len() - 1)
+    /// Creates a new symbol, distinct from any existing one, even if it has the same name.
+    pub fn new(name: impl Into<String>) -> Symbol {
+        Symbol(Arc::new(SymbolData {
+            id: SYMBOL_IDS.fetch_add(1, atomic::Ordering::Relaxed),
+            name: name.into(),
+            min: AtomicI64::new(0),
+            max: AtomicI64::new(i64::MAX),
+            multiple_of: AtomicU64::new(1),
+        }))
+    }
+
+    pub fn name(&self) -> String {
+        self.0.name.clone()
+    }
+
+    /// Asserted bounds of the symbol. Symbols stand for dimensions, so they are never negative.
+    pub fn bounds(&self) -> (i64, Option<i64>) {
+        let max = self.0.max.load(atomic::Ordering::Relaxed);
+        (self.0.min.load(atomic::Ordering::Relaxed), Some(max).filter(|&m| m != i64::MAX))
+    }
+
+    /// Asserted divisor of the symbol (1 if nothing is known).
+    pub fn multiple_of(&self) -> u64 {
+        self.0.multiple_of.load(atomic::Ordering::Relaxed)
+    }
+
+    /// Asserts `self >= min`.
+    pub fn assert_min(&self, min: i64) {
+        self.0.min.fetch_max(min, atomic::Ordering::Relaxed);
+    }
+
+    /// Asserts `self <= max`.
+    pub fn assert_max(&self, max: i64) {
+        self.0.max.fetch_min(max, atomic::Ordering::Relaxed);
+    }
+
+    /// Asserts `self % q == 0`.
+    pub fn assert_multiple_of(&self, q: u64) {
+        use num_integer::Integer;
+        let _ = self.0.multiple_of.fetch_update(
+            atomic::Ordering::Relaxed,
+            atomic::Ordering::Relaxed,
+            |m| Some(m.lcm(&q)),
+        );
     }
 }
 
-impl From<char> for Symbol {
-    fn from(c: char) -> Symbol {
-        let mut table = SYMBOL_TABLE.lock().unwrap();
-        if let Some(pos) = table.iter().position(|s| *s == c) {
-            Symbol(c, pos)
+impl PartialEq for Symbol {
+    fn eq(&self, other: &Symbol) -> bool {
+        self.0.id == other.0.id
+    }
+}
+
+impl Eq for Symbol {}
+
+impl PartialOrd for Symbol {
+    fn partial_cmp(&self, other: &Symbol) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Symbol {
+    fn cmp(&self, other: &Symbol) -> std::cmp::Ordering {
+        self.0.id.cmp(&other.0.id)
+    }
+}
+
+impl std::hash::Hash for Symbol {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.id.hash(state)
+    }
+}
+
+/// Symbols of a model, by name.
+///
+/// The same name in two scopes gives two distinct symbols, so what is asserted about the
+/// symbols of a model does not leak to the other models of the process. Clones share the scope.
+#[derive(Clone, Debug, Default)]
+pub struct SymbolScope(std::sync::Arc<std::sync::Mutex<HashMap<String, Symbol>>>);
+
+impl SymbolScope {
+    /// The symbol called `name` in this scope, created on first use.
+    pub fn sym(&self, name: &str) -> Symbol {
+        self.0.lock().unwrap().entry(name.to_string()).or_insert_with(|| Symbol::new(name)).clone()
+    }
+
+    /// A new symbol in this scope, named `prefix`, or `prefix_<n>` if the name is already taken.
+    pub fn new_with_prefix(&self, prefix: &str) -> Symbol {
+        let mut table = self.0.lock().unwrap();
+        let name = if table.contains_key(prefix) {
+            (1..).map(|n| format!("{}_{}", prefix, n)).find(|n| !table.contains_key(n)).unwrap()
+        } else {
+            prefix.to_string()
+        };
+        let sym = Symbol::new(name.clone());
+        table.insert(name, sym.clone());
+        sym
+    }
+
+    /// The symbol called `name` in this scope, if it has been used.
+    pub fn get(&self, name: &str) -> Option<Symbol> {
+        self.0.lock().unwrap().get(name).cloned()
+    }
+
+    /// Parses and records an assertion on a symbol of the scope, referred to by name:
+    /// `seq_len >= 1`, `seq_len <= 512` or `seq_len % 8 == 0`.
+    pub fn add_assertion(&self, assertion: &str) -> anyhow::Result<Symbol> {
+        let symbol = |name: &str| -> anyhow::Result<Symbol> {
+            let name = name.trim();
+            if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                anyhow::bail!("Invalid symbol name {:?} in assertion {:?}", name, assertion)
+            }
+            Ok(self.sym(name))
+        };
+        if let Some(ix) = assertion.find(">=") {
+            let sym = symbol(&assertion[..ix])?;
+            sym.assert_min(assertion[ix + 2..].trim().parse()?);
+            Ok(sym)
+        } else if let Some(ix) = assertion.find("<=") {
+            let sym = symbol(&assertion[..ix])?;
+            sym.assert_max(assertion[ix + 2..].trim().parse()?);
+            Ok(sym)
+        } else if let (Some(rem), Some(eq)) = (assertion.find('%'), assertion.find("==")) {
+            if rem > eq || assertion[eq + 2..].trim() != "0" {
+                anyhow::bail!("Can not parse assertion {:?}", assertion)
+            }
+            let modulus: i64 = assertion[rem + 1..eq].trim().parse()?;
+            if modulus <= 0 {
+                anyhow::bail!("Modulus must be positive in assertion {:?}", assertion)
+            }
+            let sym = symbol(&assertion[..rem])?;
+            sym.assert_multiple_of(modulus as u64);
+            Ok(sym)
         } else {
-            table.push(c);
-            Symbol(c, table.len() - 1)
+            anyhow::bail!("Can not parse assertion {:?}", assertion)
         }
     }
 }
 
+impl From<char> for Symbol {
+    fn from(c: char) -> Symbol {
+        CHAR_SYMBOLS.sym(&*c.to_string())
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}", self.0.name)
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}", self.0.name)
+    }
+}
+
+/// Values bound to symbols.
 #[derive(Clone, Debug, Default)]
-pub struct SymbolValues(Vec<Option<i64>>);
+pub struct SymbolValues(HashMap<Symbol, Option<i64>>);
 
 impl SymbolValues {
     pub fn with(mut self, s: Symbol, v: i64) -> Self {
@@ -45,20 +203,26 @@ impl SymbolValues {
 impl std::ops::Index<Symbol> for SymbolValues {
     type Output = Option<i64>;
     fn index(&self, index: Symbol) -> &Self::Output {
-        if index.1 < self.0.len() {
-            &self.0[index.1]
-        } else {
-            &None
-        }
+        self.0.get(&index).unwrap_or(&None)
     }
 }
 
 impl std::ops::IndexMut<Symbol> for SymbolValues {
     fn index_mut(&mut self, index: Symbol) -> &mut Self::Output {
-        if index.1 >= self.0.len() {
-            self.0.resize_with(index.1 + 1, Default::default)
-        }
-        &mut self.0[index.1]
+        self.0.entry(index).or_insert(None)
+    }
+}
+
+impl std::ops::Index<&Symbol> for SymbolValues {
+    type Output = Option<i64>;
+    fn index(&self, index: &Symbol) -> &Self::Output {
+        self.0.get(index).unwrap_or(&None)
+    }
+}
+
+impl std::ops::IndexMut<&Symbol> for SymbolValues {
+    fn index_mut(&mut self, index: &Symbol) -> &mut Self::Output {
+        self.0.entry(index.clone()).or_insert(None)
     }
 }
 
@@ -69,6 +233,10 @@ pub enum TDim {
     Add(Vec<TDim>),
     Mul(i64, Box<TDim>),
     Div(Box<TDim>, u64),
+    /// Euclidean remainder, always in `0..q`.
+    Mod(Box<TDim>, u64),
+    Min(Vec<TDim>),
+    Max(Vec<TDim>),
 }
 
 use TDim::*;
@@ -76,11 +244,14 @@ use TDim::*;
 impl fmt::Display for TDim {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         match &self {
-            Sym(sym) => write!(fmt, "{}", sym.0),
+            Sym(sym) => write!(fmt, "{}", sym),
             Val(it) => write!(fmt, "{}", it),
             Add(it) => write!(fmt, "{}", it.iter().map(|x| format!("{}", x)).join("+")),
             Mul(a, b) => write!(fmt, "{}.{}", a, b),
             Div(a, b) => write!(fmt, "({})/{}", a, b),
+            Mod(a, b) => write!(fmt, "({})%{}", a, b),
+            Min(it) => write!(fmt, "min({})", it.iter().map(|x| format!("{}", x)).join(",")),
+            Max(it) => write!(fmt, "max({})", it.iter().map(|x| format!("{}", x)).join(",")),
         }
     }
 }
@@ -100,11 +271,64 @@ impl TDim {
 
     pub fn eval(&self, values: &SymbolValues) -> TDim {
         match self {
-            Sym(sym) => values[*sym].map(|s| Val(s)).unwrap_or(Sym(*sym)),
+            Sym(sym) => values[sym].map(Val).unwrap_or_else(|| Sym(sym.clone())),
             Val(v) => Val(*v),
             Add(terms) => terms.iter().fold(Val(0), |acc, it| -> TDim { acc + it.eval(values) }),
             Div(a, q) => a.eval(values) / *q as i64,
             Mul(p, a) => a.eval(values) * *p,
+            Mod(a, q) => a.eval(values) % *q,
+            Min(terms) => Min(terms.iter().map(|t| t.eval(values)).collect()).reduce(),
+            Max(terms) => Max(terms.iter().map(|t| t.eval(values)).collect()).reduce(),
+        }
+    }
+
+    /// Smallest of two dimensions.
+    pub fn min(self, other: TDim) -> TDim {
+        Min(vec![self, other]).reduce()
+    }
+
+    /// Largest of two dimensions.
+    pub fn max(self, other: TDim) -> TDim {
+        Max(vec![self, other]).reduce()
+    }
+
+    /// Lower and upper bounds of the expression, given what is asserted about its symbols.
+    pub fn bounds(&self) -> (Option<i64>, Option<i64>) {
+        fn sum(mut it: impl Iterator<Item = Option<i64>>) -> Option<i64> {
+            it.try_fold(0, |acc, b| Some(acc + b?))
+        }
+        match self {
+            Val(v) => (Some(*v), Some(*v)),
+            Sym(s) => {
+                let (min, max) = s.bounds();
+                (Some(min), max)
+            }
+            Add(terms) => {
+                (sum(terms.iter().map(|t| t.bounds().0)), sum(terms.iter().map(|t| t.bounds().1)))
+            }
+            Mul(p, a) => {
+                let (min, max) = a.bounds();
+                if *p >= 0 {
+                    (min.map(|m| m * p), max.map(|m| m * p))
+                } else {
+                    (max.map(|m| m * p), min.map(|m| m * p))
+                }
+            }
+            Div(a, q) => {
+                let (min, max) = a.bounds();
+                (min.map(|m| m / *q as i64), max.map(|m| m / *q as i64))
+            }
+            Mod(_, q) => (Some(0), Some(*q as i64 - 1)),
+            Min(terms) => {
+                let bounds = terms.iter().map(|t| t.bounds()).collect::<Vec<_>>();
+                let min = bounds.iter().map(|b| b.0).try_fold(i64::MAX, |acc, b| Some(acc.min(b?)));
+                (min, bounds.iter().filter_map(|b| b.1).min())
+            }
+            Max(terms) => {
+                let bounds = terms.iter().map(|t| t.bounds()).collect::<Vec<_>>();
+                let max = bounds.iter().map(|b| b.1).try_fold(i64::MIN, |acc, b| Some(acc.max(b?)));
+                (bounds.iter().filter_map(|b| b.0).max(), max)
+            }
         }
     }
 
@@ -126,25 +350,26 @@ impl TDim {
             Add(terms) => 2 * terms.iter().map(TDim::cost).sum::<usize>(),
             Div(a, _) => 3 * a.cost(),
             Mul(_, a) => 2 * a.cost(),
+            Mod(a, _) => 3 * a.cost(),
+            Min(terms) | Max(terms) => 2 * terms.iter().map(TDim::cost).sum::<usize>(),
         }
     }
 
     fn wiggle(&self) -> Vec<TDim> {
         use self::TDim::*;
         match self {
-            Sym(_) | Val(_) => vec![self.clone()],
+            Sym(_) | Val(_) | Min(_) | Max(_) => vec![self.clone()],
             Add(terms) => {
                 let mut forms = vec![];
                 let sub_wiggle = terms.iter().map(|e| e.wiggle()).multi_cartesian_product();
                 for sub in sub_wiggle {
-                    for (ix, num, q) in sub
-                        .iter()
-                        .enumerate()
-                        .filter_map(
-                            |(ix, t)| if let Div(a, q) = t { Some((ix, a, q)) } else { None },
-                        )
-                        .next()
-                    {
+                    if let Some((ix, num, q)) = sub.iter().enumerate().find_map(|(ix, t)| {
+                        if let Div(a, q) = t {
+                            Some((ix, a, q))
+                        } else {
+                            None
+                        }
+                    }) {
                         let new_num = sub
                             .iter()
                             .enumerate()
@@ -168,7 +393,7 @@ impl TDim {
                 for num in a.wiggle() {
                     if let Add(terms) = &num {
                         let (integer, non_integer): (Vec<_>, Vec<_>) =
-                            terms.into_iter().cloned().partition(|a| a.gcd() % q == 0);
+                            terms.iter().cloned().partition(|a| a.gcd() % q == 0);
                         let mut new_terms = integer.iter().map(|i| i.div(*q)).collect::<Vec<_>>();
                         if non_integer.len() > 0 {
                             new_terms.push(Div(b!(Add(non_integer)), *q));
@@ -179,6 +404,7 @@ impl TDim {
                 }
                 forms
             }
+            Mod(a, q) => a.wiggle().into_iter().map(|a| Mod(b!(a), *q)).collect(),
         }
     }
 
@@ -244,6 +470,13 @@ impl TDim {
                     Val(p * p2)
                 } else if let Mul(p2, a) = a {
                     Mul(p * p2, a)
+                } else if let Div(b, q) = &a {
+                    // exact division, thanks to the assertions on the symbols
+                    if p % *q as i64 == 0 && b.gcd() % *q == 0 {
+                        Mul(p / *q as i64, b.clone()).simplify()
+                    } else {
+                        Mul(p, b!(a))
+                    }
                 } else {
                     Mul(p, b!(a))
                 }
@@ -287,7 +520,7 @@ impl TDim {
                         let offset = if v >= q as i64 {
                             Some(v / q as i64)
                         } else if v < 0 {
-                            Some(-(-v).div_ceil(&(q as i64)))
+                            Some(-Integer::div_ceil(&-v, &(q as i64)))
                         } else {
                             None
                         };
@@ -315,25 +548,99 @@ impl TDim {
                             Div(b!(Mul(p, a)), q)
                         }
                     }
+                } else if let Mod(_, m) = &a {
+                    if *m <= q {
+                        Val(0)
+                    } else {
+                        Div(b!(a), q)
+                    }
                 } else {
                     Div(b!(a), q)
                 }
             }
+            Mod(a, q) => {
+                if q == 1 {
+                    return Val(0);
+                }
+                match a.simplify() {
+                    Val(v) => Val(v.rem_euclid(q as i64)),
+                    Mod(a, q2) if q2 % q == 0 => Mod(a, q).simplify(),
+                    Add(terms) => {
+                        let len = terms.len();
+                        let terms: Vec<TDim> = terms
+                            .into_iter()
+                            .filter_map(|t| match t {
+                                Val(v) => {
+                                    Some(Val(v.rem_euclid(q as i64))).filter(|v| !v.is_zero())
+                                }
+                                t if t.gcd() % q == 0 => None,
+                                t => Some(t),
+                            })
+                            .collect();
+                        if terms.len() < len {
+                            Mod(b!(Add(terms)), q).simplify()
+                        } else {
+                            Mod(b!(Add(terms)), q)
+                        }
+                    }
+                    a if a.gcd() % q == 0 => Val(0),
+                    a => Mod(b!(a), q),
+                }
+            }
+            Min(terms) => Self::simplify_extremum(terms, false),
+            Max(terms) => Self::simplify_extremum(terms, true),
             _ => self,
         }
     }
 
+    fn simplify_extremum(terms: Vec<TDim>, max: bool) -> TDim {
+        let mut flat = vec![];
+        let mut todo = terms;
+        while let Some(term) = todo.pop() {
+            match term.simplify() {
+                Min(terms) if !max => todo.extend(terms.into_iter()),
+                Max(terms) if max => todo.extend(terms.into_iter()),
+                term => flat.push(term),
+            }
+        }
+        flat.sort();
+        flat.dedup();
+        // a >= b for any values of the symbols
+        let ge = |a: &TDim, b: &TDim| (a.clone() - b).bounds().0.map(|m| m >= 0).unwrap_or(false);
+        let mut kept = vec![];
+        for (ix, term) in flat.iter().enumerate() {
+            let dominated = flat.iter().enumerate().any(|(ix2, other)| {
+                let (wins, loses) = if max {
+                    (ge(other, term), ge(term, other))
+                } else {
+                    (ge(term, other), ge(other, term))
+                };
+                ix2 != ix && wins && (!loses || ix2 < ix)
+            });
+            if !dominated {
+                kept.push(term.clone());
+            }
+        }
+        if kept.len() == 1 {
+            kept.remove(0)
+        } else if max {
+            Max(kept)
+        } else {
+            Min(kept)
+        }
+    }
+
     fn gcd(&self) -> u64 {
         use self::TDim::*;
         use num_integer::Integer;
         match self {
-            Val(v) => v.abs() as u64,
-            Sym(_) => 1,
-            Add(terms) => {
+            Val(v) => v.unsigned_abs(),
+            Sym(s) => s.multiple_of(),
+            Add(terms) | Min(terms) | Max(terms) => {
                 let (head, tail) = terms.split_first().unwrap();
                 tail.iter().fold(head.gcd(), |a, b| a.gcd(&b.gcd()))
             }
-            Mul(p, a) => a.gcd() * p.abs() as u64,
+            Mul(p, a) => a.gcd() * p.unsigned_abs(),
             Div(a, q) => {
                 if a.gcd() % *q == 0 {
                     a.gcd() / *q
@@ -341,6 +648,7 @@ impl TDim {
                     1
                 }
             }
+            Mod(a, q) => a.gcd().gcd(q),
         }
     }
 
@@ -352,13 +660,15 @@ impl TDim {
         }
         match self {
             Val(v) => Val(v / d as i64),
-            Sym(_) => panic!(),
+            Sym(_) | Mod(_, _) => Div(b!(self.clone()), d),
             Add(terms) => Add(terms.iter().map(|t| t.div(d)).collect()),
+            Min(terms) => Min(terms.iter().map(|t| t.div(d)).collect()),
+            Max(terms) => Max(terms.iter().map(|t| t.div(d)).collect()),
             Mul(p, a) => {
                 if *p == d as i64 {
                     (**a).clone()
                 } else {
-                    let gcd = (p.abs() as u64).gcd(&d);
+                    let gcd = p.unsigned_abs().gcd(&d);
                     Mul(p / gcd as i64, b!(a.div(d / gcd)))
                 }
             }
@@ -370,11 +680,11 @@ impl TDim {
         TDim::Div(Box::new(Add(vec![self, Val(rhs as i64 - 1)])), rhs).reduce()
     }
 
-    pub fn slope(&self, sym: Symbol) -> (i64, u64) {
-        fn slope_rec(d: &TDim, sym: Symbol) -> (i64, i64) {
+    pub fn slope(&self, sym: &Symbol) -> (i64, u64) {
+        fn slope_rec(d: &TDim, sym: &Symbol) -> (i64, i64) {
             match d {
                 Val(_) => (0, 1),
-                Sym(s) => ((sym == *s) as i64, 1),
+                Sym(s) => ((sym == s) as i64, 1),
                 Add(terms) => terms
                     .iter()
                     .map(|d| slope_rec(d, sym))
@@ -387,22 +697,43 @@ impl TDim {
                     let (n, d) = slope_rec(a, sym);
                     (n, d * *q as i64)
                 }
+                Mod(_, _) | Min(_) | Max(_) => (0, 1),
             }
         }
         let (p, q) = slope_rec(self, sym);
         reduce_ratio(p, q)
     }
 
+    /// Finds the value of `sym` making `self` equal to `actual`, the other symbols being bound in
+    /// `values`. Only expressions affine in `sym` are solved.
+    pub fn solve(&self, sym: &Symbol, actual: i64, values: &SymbolValues) -> Option<i64> {
+        let at = |v: i64| self.eval(&values.clone().with(sym.clone(), v)).to_i64().ok();
+        let (b, a_plus_b) = (at(0)?, at(1)?);
+        let a = a_plus_b - b;
+        if a == 0 || (actual - b) % a != 0 || (actual - b) / a < 0 {
+            return None;
+        }
+        let x = (actual - b) / a;
+        // not affine (division, min, max...) if the guess does not check out
+        if at(x)? == actual && at(x + 1)? == actual + a {
+            Some(x)
+        } else {
+            None
+        }
+    }
+
     pub fn symbols(&self) -> std::collections::HashSet<Symbol> {
         match self {
             Val(_) => maplit::hashset!(),
-            Sym(s) => maplit::hashset!(*s),
-            Add(terms) => terms.iter().fold(maplit::hashset!(), |mut set, v| {
-                set.extend(v.symbols().into_iter());
-                set
-            }),
+            Sym(s) => maplit::hashset!(s.clone()),
+            Add(terms) | Min(terms) | Max(terms) => {
+                terms.iter().fold(maplit::hashset!(), |mut set, v| {
+                    set.extend(v.symbols().into_iter());
+                    set
+                })
+            }
             Mul(_, a) => a.symbols(),
-            Div(a, _) => a.symbols(),
+            Div(a, _) | Mod(a, _) => a.symbols(),
         }
     }
 }
@@ -476,7 +807,7 @@ impl From<Symbol> for TDim {
 
 impl<'a> From<&'a Symbol> for TDim {
     fn from(it: &'a Symbol) -> Self {
-        TDim::Sym(*it)
+        TDim::Sym(it.clone())
     }
 }
 
@@ -522,6 +853,7 @@ impl<'a> ops::Add<&'a TDim> for TDim {
     }
 }
 
+#[allow(clippy::suspicious_op_assign_impl)]
 impl<'a> ops::SubAssign<&'a TDim> for TDim {
     fn sub_assign(&mut self, rhs: &'a TDim) {
         use std::ops::Neg;
@@ -529,6 +861,7 @@ impl<'a> ops::SubAssign<&'a TDim> for TDim {
     }
 }
 
+#[allow(clippy::suspicious_op_assign_impl)]
 impl<I> ops::SubAssign<I> for TDim
 where
     I: Into<TDim>,
@@ -588,7 +921,7 @@ impl<I: AsPrimitive<u64>> ops::Div<I> for TDim {
 
 impl<I: AsPrimitive<u64>> ops::RemAssign<I> for TDim {
     fn rem_assign(&mut self, rhs: I) {
-        *self += -(self.clone() / rhs.as_() * rhs.as_());
+        *self = TDim::Mod(Box::new(std::mem::take(self)), rhs.as_()).reduce()
     }
 }
 
@@ -618,7 +951,7 @@ mod tests {
     }
 
     fn s() -> TDim {
-        (*S).into()
+        TDim::from(&*S)
     }
 
     fn neg(a: &TDim) -> TDim {
@@ -700,10 +1033,10 @@ mod tests {
     #[test]
     fn substitution() {
         let x = Symbol::new('x');
-        let e: TDim = x.into();
-        assert_eq!(e.eval(&SymbolValues::default().with(x, 2)).to_i64().unwrap(), 2);
+        let e: TDim = (&x).into();
+        assert_eq!(e.eval(&SymbolValues::default().with(x.clone(), 2)).to_i64().unwrap(), 2);
         let e = e + 3;
-        assert_eq!(e.eval(&SymbolValues::default().with(x, 2)).to_i64().unwrap(), 5);
+        assert_eq!(e.eval(&SymbolValues::default().with(x.clone(), 2)).to_i64().unwrap(), 5);
     }
 
     #[test]
@@ -811,4 +1144,108 @@ mod tests {
         let e = (s() - 3 + 1).div_ceil(1);
         assert_eq!(e, s() + -2);
     }
+
+    #[test]
+    fn named_symbols() {
+        let scope = SymbolScope::default();
+        let batch = scope.sym("batch");
+        assert_eq!(batch, scope.sym("batch"));
+        assert_ne!(batch, Symbol::new("batch"));
+        assert_ne!(batch, SymbolScope::default().sym("batch"));
+        assert_eq!(format!("{}", TDim::from(batch) * 2), "2.batch");
+    }
+
+    #[test]
+    fn fresh_symbols() {
+        let scope = SymbolScope::default();
+        let l = scope.new_with_prefix("L");
+        let l1 = scope.new_with_prefix("L");
+        assert_eq!(l.name(), "L");
+        assert_eq!(l1.name(), "L_1");
+        assert_eq!(scope.sym("L_1"), l1);
+    }
+
+    #[test]
+    fn reduce_min_max() {
+        assert_eq!(s().min(s() + 1), s());
+        assert_eq!(s().max(s() + 1), s() + 1);
+        assert_eq!(s().max(0.into()), s());
+        assert_eq!(TDim::from(3).min(4.into()), 3.into());
+        assert_eq!(s().min(4.into()), Min(vec!(s(), 4.into())));
+    }
+
+    #[test]
+    fn eval_min_mod() {
+        let x = Symbol::new("x");
+        let e = TDim::from(&x).min(5.into()) + TDim::from(&x) % 4;
+        assert_eq!(e.eval(&SymbolValues::default().with(x.clone(), 3)), 6.into());
+        assert_eq!(e.eval(&SymbolValues::default().with(x.clone(), 7)), 8.into());
+    }
+
+    #[test]
+    fn mod_negative_values() {
+        let x = Symbol::new("x");
+        assert_eq!(TDim::from(-3) % 4, 1.into());
+        let e = (TDim::from(&x) - 3) % 4;
+        for v in -9..9 {
+            let expected = (v - 3i64).rem_euclid(4);
+            assert_eq!(e.eval(&SymbolValues::default().with(x.clone(), v)), expected.into());
+            assert_eq!(
+                e.clone().reduce().eval(&SymbolValues::default().with(x.clone(), v)),
+                expected.into()
+            );
+        }
+    }
+
+    #[test]
+    fn assertions_min_max() -> anyhow::Result<()> {
+        let scope = SymbolScope::default();
+        let len = scope.add_assertion("len >= 4")?;
+        scope.add_assertion("len <= 16")?;
+        assert_eq!(len.bounds(), (4, Some(16)));
+        assert_eq!(TDim::from(&len).min(3.into()), 3.into());
+        assert_eq!(TDim::from(&len).max(20.into()), 20.into());
+        assert_eq!(TDim::from(&len).min(16.into()), len.into());
+        Ok(())
+    }
+
+    #[test]
+    fn assertions_multiple_of() -> anyhow::Result<()> {
+        let len = SymbolScope::default().add_assertion("len % 8 == 0")?;
+        let len: TDim = len.into();
+        assert_eq!(len.clone() / 8 * 8, len);
+        assert_eq!(len.clone() % 8, 0.into());
+        assert_eq!((len + 3) % 8, 3.into());
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_assertions() {
+        let scope = SymbolScope::default();
+        assert!(scope.add_assertion("x > 2").is_err());
+        assert!(scope.add_assertion("x % 2 == 1").is_err());
+        assert!(scope.add_assertion("2 * x >= 2").is_err());
+        assert!(scope.add_assertion("x % 0 == 0").is_err());
+        assert!(scope.add_assertion("x % -4 == 0").is_err());
+        assert!(scope.get("x").is_none());
+    }
+
+    #[test]
+    fn assertions_are_scoped() -> anyhow::Result<()> {
+        let scope = SymbolScope::default();
+        let len = scope.add_assertion("len >= 4")?;
+        assert_eq!(scope.get("len"), Some(len));
+        assert_eq!(SymbolScope::default().sym("len").bounds(), (0, None));
+        Ok(())
+    }
+
+    #[test]
+    fn solve_affine() {
+        let values = SymbolValues::default();
+        assert_eq!((s() * 2 + 1).solve(&S, 7, &values), Some(3));
+        assert_eq!((s() + 1).solve(&S, 5, &values), Some(4));
+        assert_eq!((s() * 2).solve(&S, 7, &values), None);
+        assert_eq!((s() / 2).solve(&S, 3, &values), None);
+        assert_eq!((s() + 3).solve(&S, 1, &values), None);
+    }
 }
@@ -2,6 +2,7 @@ use std::{fmt, ops};
 
 #[allow(non_camel_case_types)]
 #[derive(Copy, Clone, Default, PartialEq, PartialOrd, Debug)]
+#[repr(transparent)]
 pub struct f16(pub half::f16);
 
 macro_rules! binary_f16 {
@@ -136,6 +137,23 @@ impl num_traits::ToPrimitive for f16 {
     }
 }
 
+impl ndarray::ScalarOperand for f16 {}
+
+impl num_traits::FromPrimitive for f16 {
+    fn from_i64(n: i64) -> Option<f16> {
+        Some(f16(half::f16::from_f64(n as f64)))
+    }
+    fn from_u64(n: u64) -> Option<f16> {
+        Some(f16(half::f16::from_f64(n as f64)))
+    }
+    fn from_f32(n: f32) -> Option<f16> {
+        Some(f16(half::f16::from_f32(n)))
+    }
+    fn from_f64(n: f64) -> Option<f16> {
+        Some(f16(half::f16::from_f64(n)))
+    }
+}
+
 impl num_traits::AsPrimitive<f32> for f16 {
     fn as_(self) -> f32 {
         self.0.to_f32()
@@ -287,6 +305,12 @@ impl ops::Sub<&f16> for f16 {
     }
 }
 
+impl ops::SubAssign<f16> for f16 {
+    fn sub_assign(&mut self, other: f16) {
+        *self = *self - other
+    }
+}
+
 impl ops::Mul<f16> for f16 {
     type Output = f16;
     fn mul(self, other: f16) -> f16 {
@@ -301,6 +325,12 @@ impl ops::Mul<&f16> for f16 {
     }
 }
 
+impl ops::MulAssign<f16> for f16 {
+    fn mul_assign(&mut self, other: f16) {
+        *self = *self * other
+    }
+}
+
 impl ops::Div<f16> for f16 {
     type Output = f16;
     fn div(self, other: f16) -> f16 {
@@ -1,6 +1,5 @@
 #[macro_use]
 extern crate educe;
-#[macro_use]
 pub extern crate itertools;
 
 #[macro_use]
@@ -14,7 +13,7 @@ pub type TVec<T> = smallvec::SmallVec<[T; 4]>;
 
 pub mod prelude {
     pub use crate::datum::{Blob, Datum, DatumType};
-    pub use crate::dim::{Symbol, SymbolValues, TDim};
+    pub use crate::dim::{Symbol, SymbolScope, SymbolValues, TDim};
     pub use crate::f16::*;
     pub use crate::tensor::litteral::*;
     pub use crate::tensor::{natural_strides, IntoArcTensor, IntoTensor, Tensor};
@@ -30,6 +29,7 @@ pub mod prelude {
 pub mod internal {
     pub use crate::dim::{DimLike, MaybeProduct, TDim, ToDim};
     pub use crate::prelude::*;
+    pub use crate::tensor::arena::{Arena, ARENA_ALIGNMENT};
     pub use crate::tensor::view::TensorView;
     pub use ndarray as tract_ndarray;
     pub use smallvec as tract_smallvec;
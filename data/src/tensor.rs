@@ -14,6 +14,7 @@ use std::mem::{align_of, size_of};
 use std::ops::Range;
 use std::sync::Arc;
 
+pub mod arena;
 pub mod litteral;
 pub mod view;
 
@@ -24,6 +25,15 @@ pub struct Tensor {
     strides: TVec<isize>,
     layout: alloc::Layout,
     data: *mut u8,
+    storage: Option<Storage>,
+}
+
+/// Owner of the tensor data, when it is not a heap allocation of the tensor itself.
+enum Storage {
+    /// A region of an arena, to be released on drop.
+    Arena(Arc<arena::Arena>, usize),
+    /// Borrowed from a shared buffer kept alive by the tensor (e.g. a memory-mapped file).
+    Shared(#[allow(dead_code)] Arc<dyn std::any::Any + Send + Sync>),
 }
 
 unsafe impl Send for Tensor {}
@@ -95,8 +105,14 @@ impl Drop for Tensor {
                     .for_each(|s| std::ptr::drop_in_place(s as *mut TDim));
             }
         }
-        if !self.data.is_null() && self.layout.size() > 0 {
-            unsafe { alloc::dealloc(self.data, self.layout) }
+        match self.storage.take() {
+            Some(Storage::Arena(arena, region)) => arena.release(region),
+            Some(Storage::Shared(_)) => (),
+            None => {
+                if !self.data.is_null() && self.layout.size() > 0 {
+                    unsafe { alloc::dealloc(self.data, self.layout) }
+                }
+            }
         }
     }
 }
@@ -136,14 +152,17 @@ impl Tensor {
         assert!(dt.is_copy());
         let bytes = shape.iter().cloned().product::<usize>() * dt.size_of();
         let layout = alloc::Layout::from_size_align(bytes, alignment)?;
-        let data = if bytes == 0 {
-            std::ptr::null()
+        let (data, storage) = if bytes == 0 {
+            (std::ptr::null_mut(), None)
+        } else if let Some((arena, region, ptr)) = arena::try_alloc(bytes, alignment) {
+            (ptr, Some(Storage::Arena(arena, region)))
         } else {
             let ptr = alloc::alloc(layout);
             assert!(!ptr.is_null());
-            ptr
-        } as *mut u8;
-        let mut tensor = Tensor { strides: tvec!(), layout, dt, shape: shape.into(), data };
+            (ptr, None)
+        };
+        let mut tensor =
+            Tensor { strides: tvec!(), layout, dt, shape: shape.into(), data, storage };
         #[cfg(debug_assertions)]
         {
             if dt == DatumType::F32 {
@@ -266,6 +285,46 @@ impl Tensor {
         Ok(tensor)
     }
 
+    /// Create a tensor borrowing its data from a buffer owned by `owner`, without copying it.
+    ///
+    /// `content` must live as long as `owner`. The mutable accessors of the tensor write straight
+    /// to it, so shared files must be mapped copy-on-write. The data is copied if `content` is not
+    /// aligned for `dt`.
+    pub unsafe fn from_raw_dt_shared(
+        dt: DatumType,
+        shape: &[usize],
+        content: &[u8],
+        owner: Arc<dyn std::any::Any + Send + Sync>,
+    ) -> anyhow::Result<Tensor> {
+        if !dt.is_copy() {
+            anyhow::bail!("Can not borrow the data of a {:?} tensor", dt);
+        }
+        let bytes = shape.iter().product::<usize>() * dt.size_of();
+        if content.len() != bytes {
+            anyhow::bail!(
+                "Expected {} bytes for a {:?} tensor of shape {:?}, got {}",
+                bytes,
+                dt,
+                shape,
+                content.len()
+            );
+        }
+        if bytes == 0 || !(content.as_ptr() as usize).is_multiple_of(dt.alignment()) {
+            return Self::from_raw_dt(dt, shape, content);
+        }
+        let layout = alloc::Layout::from_size_align(bytes, dt.alignment())?;
+        let mut tensor = Tensor {
+            strides: tvec!(),
+            layout,
+            dt,
+            shape: shape.into(),
+            data: content.as_ptr() as *mut u8,
+            storage: Some(Storage::Shared(owner)),
+        };
+        tensor.update_strides();
+        Ok(tensor)
+    }
+
     pub unsafe fn from_slice_align<T: Datum>(
         content: &[T],
         align: usize,
@@ -300,6 +359,16 @@ impl Tensor {
         &self.strides
     }
 
+    /// Is the tensor data stored in an `Arena` (as opposed to its own heap allocation) ?
+    pub fn is_in_arena(&self) -> bool {
+        matches!(self.storage, Some(Storage::Arena(..)))
+    }
+
+    /// Is the tensor data borrowed from a shared buffer (see `from_raw_dt_shared`) ?
+    pub fn is_shared(&self) -> bool {
+        matches!(self.storage, Some(Storage::Shared(_)))
+    }
+
     fn update_strides(&mut self) {
         self.strides.clear();
         compute_natural_stride_to(&mut self.strides, &self.shape);
@@ -651,21 +720,29 @@ impl Tensor {
 
     /// Access the data as a slice.
     pub fn as_slice<D: Datum>(&self) -> anyhow::Result<&[D]> {
-        unsafe { Ok(std::slice::from_raw_parts::<D>(self.as_ptr()?, self.len())) }
+        self.check_for_access::<D>()?;
+        unsafe { Ok(self.as_slice_unchecked()) }
     }
 
     /// Access the data as a mutable slice.
     pub fn as_slice_mut<D: Datum>(&mut self) -> anyhow::Result<&mut [D]> {
-        unsafe { Ok(std::slice::from_raw_parts_mut::<D>(self.as_ptr_mut()?, self.len())) }
+        self.check_for_access::<D>()?;
+        unsafe { Ok(self.as_slice_mut_unchecked()) }
     }
 
     /// Access the data as a slice.
     pub unsafe fn as_slice_unchecked<D: Datum>(&self) -> &[D] {
+        if self.data.is_null() {
+            return &[];
+        }
         std::slice::from_raw_parts::<D>(self.data as *const D, self.len())
     }
 
     /// Access the data as a mutable slice.
     pub unsafe fn as_slice_mut_unchecked<D: Datum>(&mut self) -> &mut [D] {
+        if self.data.is_null() {
+            return &mut [];
+        }
         std::slice::from_raw_parts_mut::<D>(self.data as *mut D, self.len())
     }
 
@@ -684,10 +761,16 @@ impl Tensor {
     }
 
     pub unsafe fn as_bytes(&self) -> &[u8] {
+        if self.data.is_null() {
+            return &[];
+        }
         std::slice::from_raw_parts(self.data, self.layout.size())
     }
 
     pub unsafe fn as_bytes_mut(&mut self) -> &mut [u8] {
+        if self.data.is_null() {
+            return &mut [];
+        }
         std::slice::from_raw_parts_mut(self.data, self.layout.size())
     }
 
@@ -848,7 +931,7 @@ impl Tensor {
     /// Access the data as a scalar, after a cast.
     pub fn cast_to_scalar<D: Datum + Copy>(&self) -> anyhow::Result<D> {
         let casted = self.cast_to::<D>()?;
-        casted.to_scalar::<D>().map(|&x| x)
+        casted.to_scalar::<D>().copied()
     }
 
     /// Access the nth element of the tensor, returned as a 0-rank Tensor
@@ -890,12 +973,13 @@ impl Tensor {
         let vec = if it.as_slice().is_some() {
             it.into_raw_vec().into_boxed_slice()
         } else {
-            it.into_owned().into_iter().cloned().collect::<Box<[T]>>()
+            it.iter().cloned().collect::<Box<[T]>>()
         };
         let layout =
             alloc::Layout::from_size_align(vec.len() * size_of::<T>(), align_of::<T>()).unwrap();
         let data = Box::into_raw(vec) as *mut u8;
-        let mut t = Tensor { dt: T::datum_type(), shape, layout, data, strides: tvec!() };
+        let mut t =
+            Tensor { dt: T::datum_type(), shape, layout, data, strides: tvec!(), storage: None };
         t.update_strides();
         t
     }
@@ -907,7 +991,9 @@ impl Tensor {
                 data: data.as_ptr() as *mut u8,
                 shape: self.shape.clone(),
                 strides: self.strides.clone(),
-                ..*self
+                dt: self.dt,
+                layout: self.layout,
+                storage: None,
             };
             std::mem::forget(data);
             t
@@ -917,7 +1003,9 @@ impl Tensor {
                 data: data.as_ptr() as *mut u8,
                 shape: self.shape.clone(),
                 strides: self.strides.clone(),
-                ..*self
+                dt: self.dt,
+                layout: self.layout,
+                storage: None,
             };
             std::mem::forget(data);
             t
@@ -1003,7 +1091,7 @@ fn compute_natural_stride_to(strides: &mut TVec<isize>, shape: &[usize]) {
         _ => {
             strides.push(1);
             for dim in shape.as_ref().iter().skip(1).rev() {
-                let previous = strides.last().unwrap().clone();
+                let previous = *strides.last().unwrap();
                 strides.push(previous * *dim as isize)
             }
             strides.reverse();
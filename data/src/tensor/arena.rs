@@ -0,0 +1,154 @@
+//! Pre-planned memory for tensors.
+use std::alloc;
+use std::cell::RefCell;
+use std::fmt;
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+
+/// Alignment of the arena block, and of every region offset.
+pub const ARENA_ALIGNMENT: usize = 128;
+
+/// A single memory block, split in regions.
+///
+/// Regions may overlap: they are meant to be assigned to tensors that will not be alive at the
+/// same time. A region is only handed out if no overlapping region is in use, so a plan that
+/// turns out to be wrong degrades to heap allocation instead of aliasing.
+pub struct Arena {
+    data: *mut u8,
+    layout: alloc::Layout,
+    regions: Vec<Range<usize>>,
+    overlaps: Vec<Vec<usize>>,
+    busy: Mutex<Vec<bool>>,
+}
+
+unsafe impl Send for Arena {}
+unsafe impl Sync for Arena {}
+
+impl fmt::Debug for Arena {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Arena({} bytes, {} regions)", self.layout.size(), self.regions.len())
+    }
+}
+
+thread_local! {
+    static SCOPE: RefCell<Option<(Arc<Arena>, Vec<usize>)>> = const { RefCell::new(None) };
+}
+
+impl Arena {
+    /// Regions offsets must be multiples of `ARENA_ALIGNMENT`.
+    pub fn new(regions: Vec<Range<usize>>) -> anyhow::Result<Arc<Arena>> {
+        if let Some(r) = regions.iter().find(|r| r.start % ARENA_ALIGNMENT != 0) {
+            anyhow::bail!("Misaligned arena region {:?}", r);
+        }
+        let size = regions.iter().map(|r| r.end).max().unwrap_or(0);
+        let layout = alloc::Layout::from_size_align(size.max(1), ARENA_ALIGNMENT)?;
+        let data = unsafe { alloc::alloc(layout) };
+        if data.is_null() {
+            anyhow::bail!("Failed to allocate arena of {} bytes", size);
+        }
+        let overlaps = regions
+            .iter()
+            .enumerate()
+            .map(|(ix, r)| {
+                regions
+                    .iter()
+                    .enumerate()
+                    .filter(|(other, o)| *other != ix && o.start < r.end && r.start < o.end)
+                    .map(|(other, _)| other)
+                    .collect()
+            })
+            .collect();
+        let busy = Mutex::new(vec![false; regions.len()]);
+        Ok(Arc::new(Arena { data, layout, regions, overlaps, busy }))
+    }
+
+    /// Size of the memory block, in bytes.
+    pub fn size(&self) -> usize {
+        self.layout.size()
+    }
+
+    /// Run `f` with tensor allocations from the calling thread going to the first free region
+    /// among `regions` of exactly the allocated size, falling back to the heap.
+    ///
+    /// Regions are sized for the outputs they are planned for, so scratch buffers allocated
+    /// while computing them (packing, temporaries) stay on the heap.
+    pub fn scope<R>(self: &Arc<Self>, regions: &[usize], f: impl FnOnce() -> R) -> R {
+        struct Restore(Option<(Arc<Arena>, Vec<usize>)>);
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                let previous = self.0.take();
+                SCOPE.with(|s| *s.borrow_mut() = previous);
+            }
+        }
+        let previous = SCOPE.with(|s| s.replace(Some((self.clone(), regions.to_vec()))));
+        let _restore = Restore(previous);
+        f()
+    }
+
+    pub(super) fn release(&self, region: usize) {
+        self.busy.lock().unwrap()[region] = false;
+    }
+}
+
+impl Drop for Arena {
+    fn drop(&mut self) {
+        unsafe { alloc::dealloc(self.data, self.layout) }
+    }
+}
+
+/// Try to find room for `bytes` in the arena of the current scope.
+pub(super) fn try_alloc(bytes: usize, alignment: usize) -> Option<(Arc<Arena>, usize, *mut u8)> {
+    SCOPE.with(|s| {
+        let scope = s.borrow();
+        let (arena, regions) = scope.as_ref()?;
+        if alignment > ARENA_ALIGNMENT {
+            return None;
+        }
+        let mut busy = arena.busy.lock().unwrap();
+        let region = *regions.iter().find(|&&r| {
+            !busy[r]
+                && arena.regions[r].len() == bytes
+                && arena.overlaps[r].iter().all(|&o| !busy[o])
+        })?;
+        busy[region] = true;
+        let ptr = unsafe { arena.data.add(arena.regions[region].start) };
+        Some((arena.clone(), region, ptr))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn tensor_in_arena() -> anyhow::Result<()> {
+        let arena = Arena::new(vec![0..64, 0..256])?;
+        let (a, b) = arena.scope(&[0, 1], || -> anyhow::Result<_> {
+            let a = unsafe { Tensor::uninitialized::<f32>(&[16])? };
+            let b = unsafe { Tensor::uninitialized::<f32>(&[16])? };
+            Ok((a, b))
+        })?;
+        assert!(a.is_in_arena());
+        // region 0 is busy, region 1 is not sized for b
+        assert!(!b.is_in_arena());
+        std::mem::drop(a);
+        let c = arena.scope(&[1], || unsafe { Tensor::uninitialized::<f32>(&[64]) })?;
+        assert!(c.is_in_arena());
+        Ok(())
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn scratch_stays_on_heap() -> anyhow::Result<()> {
+        let arena = Arena::new(vec![0..256])?;
+        let (scratch, output) = arena.scope(&[0], || -> anyhow::Result<_> {
+            let scratch = unsafe { Tensor::uninitialized::<f32>(&[8])? };
+            let output = unsafe { Tensor::uninitialized::<f32>(&[64])? };
+            Ok((scratch, output))
+        })?;
+        assert!(!scratch.is_in_arena());
+        assert!(output.is_in_arena());
+        Ok(())
+    }
+}
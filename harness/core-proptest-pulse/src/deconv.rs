@@ -0,0 +1,92 @@
+use proptest::proptest;
+use proptest::test_runner::TestCaseResult;
+use tract_hir::internal::*;
+use tract_hir::ops::cnn;
+
+use super::*;
+
+#[derive(Debug, Clone)]
+struct DeconvProblem {
+    input: Array3<f32>,
+    pulse: usize,
+    stride: usize,
+    dilation: usize,
+    ker: Array3<f32>,
+    padding: cnn::PaddingSpec,
+}
+
+impl Arbitrary for DeconvProblem {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> BoxedStrategy<Self> {
+        (
+            1usize..4,
+            1usize..3,
+            vec(1usize..4),
+            prop_oneof![Just(cnn::PaddingSpec::Valid), Just(cnn::PaddingSpec::SameUpper)],
+            1usize..4,
+            vec(1usize..10),
+        )
+            .prop_map(|(stride, dilation, ker, padding, pulse, input)| DeconvProblem {
+                input: Array3::from_shape_vec((1, 1, input.len()), input).unwrap(),
+                pulse,
+                stride,
+                dilation,
+                ker: Array3::from_shape_vec((1, 1, ker.len()), ker).unwrap(),
+                padding,
+            })
+            .boxed()
+    }
+}
+
+impl DeconvProblem {
+    pub fn run(&self) -> TestCaseResult {
+        let mut model = InferenceModel::default();
+        let input = model
+            .add_source("a", InferenceFact::dt_shape(f32::datum_type(), shapefactoid!(1, 1, S)))
+            .unwrap();
+        let kernel = model.add_const("kernel", self.ker.clone()).unwrap();
+        let deconv = cnn::Deconv {
+            padding: self.padding.clone(),
+            strides: Some(tvec!(self.stride)),
+            dilations: Some(tvec!(self.dilation)),
+            group: 1,
+            ..cnn::Deconv::default()
+        };
+        model.wire_node("deconv", expand(deconv), &[input, kernel]).unwrap();
+        model.auto_outputs().unwrap();
+        proptest_regular_against_pulse(model, self.pulse as _, self.input.clone().into_dyn(), 2)
+    }
+}
+
+proptest! {
+    #[test]
+    fn proptest(pb in DeconvProblem::arbitrary()) { pb.run().unwrap() }
+}
+
+#[test]
+fn overlap_over_several_pulses() {
+    let pb = DeconvProblem {
+        input: Array3::from_shape_fn((1, 1, 5), |(_, _, x)| x as f32),
+        pulse: 1,
+        stride: 1,
+        dilation: 1,
+        ker: arr3(&[[[1f32, 2.0, 3.0]]]),
+        padding: cnn::PaddingSpec::Valid,
+    };
+    pb.run().unwrap();
+}
+
+#[test]
+fn stride_and_same_padding() {
+    let pb = DeconvProblem {
+        input: Array3::from_shape_fn((1, 1, 4), |(_, _, x)| x as f32),
+        pulse: 2,
+        stride: 2,
+        dilation: 1,
+        ker: arr3(&[[[1f32, 1.0, 1.0]]]),
+        padding: cnn::PaddingSpec::SameUpper,
+    };
+    pb.run().unwrap();
+}
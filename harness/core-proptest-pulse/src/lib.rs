@@ -12,6 +12,7 @@ use tract_ndarray::*;
 use tract_pulse::internal::*;
 
 mod conv_plus_conv;
+mod deconv;
 mod delay_plus_pool;
 mod pad_plus_conv;
 
@@ -44,8 +45,9 @@ fn proptest_regular_against_pulse(
     dbg!(&pulsed);
     let output_fact = pulsed.output_fact(0).unwrap().clone();
 
-    let output_stream_axis = output_fact.axis;
-    let delay = output_fact.delay;
+    let output_stream = output_fact.streaming().unwrap().clone();
+    let output_stream_axis = output_stream.axis;
+    let delay = output_stream.delay;
     let mut initial_output_shape = output_fact.shape.clone();
     initial_output_shape[output_stream_axis] = 0.to_dim();
     let initial_output_shape: TVec<usize> =
@@ -73,8 +75,8 @@ fn proptest_regular_against_pulse(
                 &[chunk.view(), ArrayD::from_elem(filler_shape, std::f32::NAN).view()],
             )
             .unwrap();
-            state.session_state.resolved_symbols[s] = Some(written as i64);
-            output_len = output_fact
+            state.session_state.resolved_symbols[&s] = Some(written as i64);
+            output_len = output_stream
                 .dim
                 .eval(&state.session_state.resolved_symbols)
                 .to_isize()
@@ -88,17 +90,14 @@ fn proptest_regular_against_pulse(
         )
         .unwrap();
         if let Some(output_len) = output_len {
-            if got.shape()[output_stream_axis] >= output_len.max(0) as usize + delay {
+            if got.shape()[output_stream_axis] >= output_len as usize + delay {
                 break;
             }
         }
     }
 
     let pulsed_output = got
-        .slice_axis(
-            Axis(output_stream_axis),
-            (output_fact.delay..output_fact.delay + output_len.unwrap().max(0) as usize).into(),
-        )
+        .slice_axis(Axis(output_stream_axis), (delay..delay + output_len.unwrap() as usize).into())
         .to_owned()
         .into_tensor();
 
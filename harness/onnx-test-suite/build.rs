@@ -67,7 +67,7 @@ pub fn ensure_onnx_git_checkout() {
 enum Mode {
     Plain,
     Optim,
-    NNEF,
+    Nnef,
 }
 
 pub fn make_test_file(root: &mut fs::File, tests_set: &str, onnx_tag: &str) {
@@ -108,18 +108,18 @@ pub fn make_test_file(root: &mut fs::File, tests_set: &str, onnx_tag: &str) {
         .collect();
     tests.sort();
     writeln!(rs, "mod {} {{", tests_set_ver).unwrap();
-    for &mode in &[Plain, Optim, NNEF] {
+    for &mode in &[Plain, Optim, Nnef] {
         writeln!(rs, "mod {} {{", format!("{:?}", mode).to_lowercase()).unwrap();
         writeln!(rs, "use tract_core::internal::*;").unwrap();
         writeln!(rs, "use crate::onnx::{{run_one, Mode}};").unwrap();
         for t in &tests {
             writeln!(rs, "#[test]").unwrap();
-            let pair = working_list.iter().find(|pair| &*pair.0 == &*t);
+            let pair = working_list.iter().find(|pair| pair.0 == *t);
             let ignore = pair.is_none()
                 || match mode {
                     Mode::Plain => false,
                     Mode::Optim => pair.as_ref().unwrap().1.contains(&"not-typable".to_string()),
-                    Mode::NNEF => {
+                    Mode::Nnef => {
                         pair.as_ref().unwrap().1.contains(&"not-typable".to_string())
                             || pair.as_ref().unwrap().1.contains(&"not-nnef".to_string())
                     }
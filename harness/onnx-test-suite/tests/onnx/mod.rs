@@ -36,7 +36,7 @@ pub fn load_half_dataset(prefix: &str, path: &path::Path) -> TVec<Tensor> {
 pub enum Mode {
     Plain,
     Optim,
-    NNEF,
+    Nnef,
 }
 
 pub fn run_one<P: AsRef<path::Path>>(
@@ -152,7 +152,7 @@ pub fn run_one<P: AsRef<path::Path>>(
                     trace!("Run analysed model:\n{:#?}", model);
                     run_model(model, inputs, &data_path)
                 }
-                NNEF => {
+                Nnef => {
                     let model = model.into_typed().unwrap();
                     info!("Declutter");
                     let optimized = model.declutter().unwrap();
@@ -49,12 +49,11 @@ pub fn load_image<P: AsRef<path::Path>>(p: P) -> Tensor {
     let image = image::open(&p).unwrap().to_rgb8();
     let resized =
         image::imageops::resize(&image, 299, 299, ::image::imageops::FilterType::Triangle);
-    let image = tract_ndarray::Array4::from_shape_fn((1, 299, 299, 3), |(_, y, x, c)| {
+    tract_ndarray::Array4::from_shape_fn((1, 299, 299, 3), |(_, y, x, c)| {
         resized[(x as _, y as _)][c] as f32 / 255.0
     })
     .into_dyn()
-    .into();
-    image
+    .into()
 }
 
 #[cfg(test)]
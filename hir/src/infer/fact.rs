@@ -57,13 +57,11 @@ impl InferenceFact {
 
     pub fn format_dt_shape(&self) -> String {
         if !self.shape.open && self.shape.dims.len() == 0 {
-            format!(
-                "{}",
-                self.datum_type
-                    .concretize()
-                    .map(|dt| format!("{:?}", dt))
-                    .unwrap_or("?".to_string())
-            )
+            self.datum_type
+                .concretize()
+                .map(|dt| format!("{:?}", dt))
+                .unwrap_or("?".to_string())
+                .to_string()
         } else {
             format!(
                 "{:?},{}",
@@ -78,9 +78,10 @@ pub trait Factoid: fmt::Debug + Clone + PartialEq + Default + Hash {
 
 /// Partial information about a value of type T.
 #[cfg_attr(feature = "serialize", derive(Serialize))]
-#[derive(Clone, PartialEq, Hash)]
+#[derive(Clone, PartialEq, Hash, Default)]
 pub enum GenericFactoid<T: fmt::Debug + Clone + PartialEq + Hash> {
     Only(T),
+    #[default]
     Any,
 }
 
@@ -110,12 +111,6 @@ impl<T: fmt::Debug + Clone + PartialEq + Hash> Factoid for GenericFactoid<T> {
     }
 }
 
-impl<T: fmt::Debug + Clone + PartialEq + Hash> Default for GenericFactoid<T> {
-    fn default() -> Self {
-        GenericFactoid::Any
-    }
-}
-
 impl<T: fmt::Debug + Clone + PartialEq + Hash> From<T> for GenericFactoid<T> {
     fn from(t: T) -> Self {
         GenericFactoid::Only(t)
@@ -198,7 +193,7 @@ impl ShapeFactoid {
             return false;
         }
         self.dims[i] = GenericFactoid::Only(d);
-        return true;
+        true
     }
 
     pub fn dims(&self) -> impl Iterator<Item = &DimFact> {
@@ -275,7 +270,7 @@ impl Default for ShapeFactoid {
 impl FromIterator<TDim> for ShapeFactoid {
     /// Converts an iterator over usize into a closed shape.
     fn from_iter<I: IntoIterator<Item = TDim>>(iter: I) -> ShapeFactoid {
-        ShapeFactoid::closed(iter.into_iter().map(|d| GenericFactoid::Only(d)).collect())
+        ShapeFactoid::closed(iter.into_iter().map(GenericFactoid::Only).collect())
     }
 }
 
@@ -65,10 +65,10 @@ pub fn infer_shape_broadcasting(shapes: &[&ShapeFactoid]) -> TractResult<Option<
         if unknown > 1 {
             debug!("Can't infer shape (broadcasting): there are multiple unknown values at same index.");
             return Ok(None);
-        } else if unknown == 1 && previous != None {
+        } else if unknown == 1 && previous.is_some() {
             debug!("Can't infer shape (broadcasting): there are both unknown and known values at same index.");
             return Ok(None);
-        } else if unknown == 1 && previous == None {
+        } else if unknown == 1 && previous.is_none() {
             output_shape.push(GenericFactoid::Any);
         } else if let Some(previous) = previous {
             output_shape.push(GenericFactoid::Only(previous.clone()));
@@ -22,7 +22,6 @@ pub use self::rules::InferenceResult;
 pub use self::rules::InferenceRulesOp;
 pub use self::rules::Solver;
 pub use self::rules::TensorProxy;
-pub use wrap;
 
 pub fn check_input_arity(inputs: &[TensorProxy], expected: usize) -> TractResult<()> {
     if inputs.len() != expected {
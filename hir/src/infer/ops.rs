@@ -38,27 +38,21 @@ pub trait InferenceOp:
         let (infered_inputs, infered_outputs, observed) =
             self.infer_facts(inputs, outputs, observed).context("Infering facts")?;
 
-        if self.is_stateless() {
-            if infered_inputs.iter().all(|i| i.value.is_concrete()) {
-                let input_values = infered_inputs
-                    .iter()
-                    .map(|i| i.value.concretize().unwrap().clone().into())
-                    .collect(); // checked
-                match self.eval(input_values) {
-                    Ok(values) => {
-                        let output_values =
-                            values.into_iter().map(|t| t.into()).collect::<TVec<_>>();
-                        return Ok((infered_inputs, output_values, observed));
-                    }
-                    Err(e) => match e {
-                        //            TractError(TractErrorKind::StreamTensor, _) => (),
-                        e => return Err(e).context("Eager eval"),
-                    },
+        if self.is_stateless() && infered_inputs.iter().all(|i| i.value.is_concrete()) {
+            let input_values = infered_inputs
+                .iter()
+                .map(|i| i.value.concretize().unwrap().clone().into())
+                .collect(); // checked
+            match self.eval(input_values) {
+                Ok(values) => {
+                    let output_values = values.into_iter().map(|t| t.into()).collect::<TVec<_>>();
+                    return Ok((infered_inputs, output_values, observed));
                 }
+                Err(e) => return Err(e).context("Eager eval"),
             }
         }
 
-        return Ok((infered_inputs, infered_outputs, observed));
+        Ok((infered_inputs, infered_outputs, observed))
     }
 
     /// Allow an op to specify a supplementary list of outlets facts that
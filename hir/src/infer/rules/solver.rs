@@ -82,7 +82,7 @@ impl<'rules, T: Output + Factoid> Rule<'rules> for EqualsRule<T> {
         for item in &self.items {
             changed |= item.set(context, value.clone())?;
         }
-        return Ok((changed, vec![]));
+        Ok((changed, vec![]))
     }
 
     /// Returns the paths that the rule depends on.
@@ -91,7 +91,7 @@ impl<'rules, T: Output + Factoid> Rule<'rules> for EqualsRule<T> {
     }
 }
 
-impl<'rules, T: Output + Factoid> fmt::Debug for EqualsRule<T> {
+impl<T: Output + Factoid> fmt::Debug for EqualsRule<T> {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         write!(formatter, "{:?}", self.items[0])?;
         for item in &self.items[1..] {
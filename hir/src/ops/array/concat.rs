@@ -13,7 +13,7 @@ impl_dyn_hash!(Concat);
 
 impl Concat {
     fn resolve_axis(&self, rank: i64) -> TractResult<usize> {
-        if 0 <= self.axis && self.axis <= rank - 1 {
+        if 0 <= self.axis && self.axis < rank {
             Ok(self.axis as usize)
         } else if -rank <= self.axis && self.axis < 0 {
             Ok((self.axis + rank) as usize)
@@ -72,7 +72,7 @@ impl Expansion for Concat {
     ) -> TractResult<TVec<OutletId>> {
         let facts = inputs
             .iter()
-            .map(|i| target.outlet_fact(*i).map(|x| x.clone()))
+            .map(|i| target.outlet_fact(*i).cloned())
             .collect::<TractResult<TVec<_>>>()?;
 
         let super_type = if let Some(super_type) =
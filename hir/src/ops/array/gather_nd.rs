@@ -0,0 +1,46 @@
+use crate::infer::*;
+use crate::internal::*;
+
+pub use tract_core::ops::array::GatherNd;
+
+impl InferenceRulesOp for GatherNd {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 2)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&outputs[0].datum_type, &inputs[0].datum_type)?;
+        s.given(&inputs[1].rank, move |s, indices_rank| {
+            let indices_rank = indices_rank as usize;
+            for i in 0..(indices_rank - 1) {
+                s.equals(&outputs[0].shape[i], &inputs[1].shape[i])?;
+            }
+            for i in 0..self.batch_dims {
+                s.equals(&inputs[0].shape[i], &inputs[1].shape[i])?;
+            }
+            s.given_2(
+                &inputs[1].shape[indices_rank - 1],
+                &inputs[0].rank,
+                move |s, n, data_rank| {
+                    if let Ok(n) = n.to_usize() {
+                        let tail = data_rank as usize - self.batch_dims - n;
+                        s.equals(&outputs[0].rank, (indices_rank - 1 + tail) as i64)?;
+                        for i in 0..tail {
+                            s.equals(
+                                &outputs[0].shape[indices_rank - 1 + i],
+                                &inputs[0].shape[self.batch_dims + n + i],
+                            )?;
+                        }
+                    }
+                    Ok(())
+                },
+            )
+        })
+    }
+
+    as_op!();
+    to_typed!();
+}
@@ -66,7 +66,7 @@ fn compute_shape(input: &[TDim], shape_spec: &[TDim]) -> TractResult<TVec<TDim>>
                 if remaining_dim_input != TDim::one() {
                     bail!("Invalid remaining dim");
                 }
-                *slot = input_dims.peek().context("Invalid")?.clone().clone();
+                *slot = (*input_dims.peek().context("Invalid")?).clone();
             }
             loop {
                 let quotient = remaining_dim_input.maybe_div(slot);
@@ -112,7 +112,7 @@ pub fn to_axis_ops(input_orig: &[TDim], output_spec: &[TDim]) -> TractResult<TVe
                 op.change_shape_array(&mut shape)?;
                 Ok(shape)
             })?;
-        if &current_input == &final_output {
+        if current_input == final_output {
             return Ok(stack);
         }
         if let Some(common) =
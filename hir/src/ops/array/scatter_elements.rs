@@ -0,0 +1,45 @@
+use crate::infer::*;
+use crate::internal::*;
+
+/// ScatterElements, with a possibly negative axis.
+#[derive(Debug, Clone, new, Hash)]
+pub struct ScatterElements {
+    axis: i64,
+}
+
+impl_dyn_hash!(ScatterElements);
+
+impl Expansion for ScatterElements {
+    fn name(&self) -> Cow<str> {
+        "InferenceScatterElements".into()
+    }
+
+    op_hir!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 3)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&outputs[0].datum_type, &inputs[0].datum_type)?;
+        s.equals(&inputs[2].datum_type, &inputs[0].datum_type)?;
+        s.equals(&inputs[0].rank, &inputs[1].rank)?;
+        s.equals(&inputs[1].shape, &inputs[2].shape)?;
+        s.equals(&outputs[0].shape, &inputs[0].shape)?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        target: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let rank = target.outlet_fact(inputs[0])?.rank() as i64;
+        let axis = (if self.axis < 0 { self.axis + rank } else { self.axis }) as usize;
+        target.wire_node(prefix, tract_core::ops::array::ScatterElements::new(axis), inputs)
+    }
+}
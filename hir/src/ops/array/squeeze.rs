@@ -29,7 +29,7 @@ impl Squeeze {
             }
             Ok(shape)
         } else {
-            Ok(input.into_iter().filter(|&d| d != &D::one()).cloned().collect())
+            Ok(input.iter().filter(|&d| d != &D::one()).cloned().collect())
         }
     }
 }
@@ -58,13 +58,13 @@ impl StridedSlice {
             None
         } else {
             let begin = begin.cast_to::<TDim>()?;
-            begin.as_slice::<TDim>()?.iter().nth(ix).cloned()
+            begin.as_slice::<TDim>()?.get(ix).cloned()
         };
 
         let mut end: Option<TDim> = if self.ignore_end(ix) || ix >= end.len() {
             None
         } else if end.datum_type() == i64::datum_type() {
-            let end = *end.as_slice::<i64>()?.iter().nth(ix).unwrap();
+            let end = *end.as_slice::<i64>()?.get(ix).unwrap();
             if end == std::i64::MAX || end == std::i64::MIN || end == std::i64::MIN + 1 {
                 None
             } else {
@@ -72,7 +72,7 @@ impl StridedSlice {
             }
         } else {
             let end = end.cast_to::<TDim>()?;
-            end.as_slice::<TDim>()?.iter().nth(ix).cloned()
+            end.as_slice::<TDim>()?.get(ix).cloned()
         };
 
         let stride = strides.get(ix).cloned().unwrap_or(1);
@@ -84,7 +84,7 @@ impl StridedSlice {
             } else {
                 let symbols = bound.symbols();
                 if symbols.len() == 1 {
-                    let sym = symbols.into_iter().nth(0).unwrap();
+                    let sym = symbols.into_iter().next().unwrap();
                     let values = SymbolValues::default().with(sym, 100_000_000);
                     bound.eval(&values).to_isize().unwrap() < 0
                 } else {
@@ -191,7 +191,7 @@ impl Expansion for StridedSlice {
                 let end = &params[1];
                 let strides = if let Some(i) = self.optional_steps_input {
                     let t = params[i - 1].cast_to::<i32>()?;
-                    t.as_slice::<i32>()?.iter().cloned().collect()
+                    t.as_slice::<i32>()?.to_vec()
                 } else {
                     vec![1; input_shape.len()]
                 };
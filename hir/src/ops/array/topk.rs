@@ -0,0 +1,78 @@
+use crate::infer::*;
+use crate::internal::*;
+
+/// TopK, with a possibly negative axis.
+#[derive(Debug, Clone, new, Hash)]
+pub struct TopK {
+    axis: i64,
+    largest: bool,
+    sorted: bool,
+}
+
+impl_dyn_hash!(TopK);
+
+impl TopK {
+    fn resolve_axis(&self, rank: usize) -> usize {
+        if self.axis < 0 {
+            (self.axis + rank as i64) as usize
+        } else {
+            self.axis as usize
+        }
+    }
+}
+
+impl Expansion for TopK {
+    fn name(&self) -> Cow<str> {
+        "InferenceTopK".into()
+    }
+
+    op_hir!();
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(2)
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 2)?;
+        check_output_arity(&outputs, 2)?;
+        s.equals(&outputs[0].datum_type, &inputs[0].datum_type)?;
+        s.equals(&outputs[1].datum_type, i64::datum_type())?;
+        s.equals(&outputs[0].rank, &inputs[0].rank)?;
+        s.equals(&outputs[1].rank, &inputs[0].rank)?;
+        s.given(&inputs[0].rank, move |s, rank| {
+            let rank = rank as usize;
+            let axis = self.resolve_axis(rank);
+            for ix in 0..rank {
+                if ix != axis {
+                    s.equals(&outputs[0].shape[ix], &inputs[0].shape[ix])?;
+                    s.equals(&outputs[1].shape[ix], &inputs[0].shape[ix])?;
+                }
+            }
+            s.given(&inputs[1].value, move |s, k| {
+                let k = k.cast_to::<TDim>()?.as_slice::<TDim>()?[0].clone();
+                s.equals(&outputs[0].shape[axis], k.clone())?;
+                s.equals(&outputs[1].shape[axis], k)
+            })
+        })
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        target: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let axis = self.resolve_axis(target.outlet_fact(inputs[0])?.rank());
+        let fallback_k = target.symbol_table.new_with_prefix("k").into();
+        target.wire_node(
+            prefix,
+            tract_core::ops::array::TopK::new(axis, self.largest, self.sorted, fallback_k),
+            inputs,
+        )
+    }
+}
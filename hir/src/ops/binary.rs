@@ -140,7 +140,7 @@ impl EvalOp for Nary {
 
     fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
         let mut t = inputs[0].clone().into_tensor();
-        for i in inputs[1..].into_iter() {
+        for i in inputs[1..].iter() {
             let mut i = i.clone();
             let operating_datum_type =
                 self.0.operating_datum_type(t.datum_type(), i.datum_type())?;
@@ -254,7 +254,7 @@ impl Expansion for Conv {
         };
 
         let inputs = inputs
-            .into_iter()
+            .iter()
             .enumerate()
             .filter(|&(ix, _)| {
                 ix != self.k_input.unwrap_or(1) && self.bias_input.map(|b| ix != b).unwrap_or(true)
@@ -0,0 +1,246 @@
+use crate::infer::*;
+use crate::internal::*;
+
+use tract_core::ops::cnn::deconv::{output_shape, DeconvUnary};
+use tract_core::ops::cnn::{KernelFormat, PaddingSpec, PoolSpec};
+use tract_core::ops::nn::DataFormat;
+
+/// Transposed convolution. Inputs are the data, the kernel, and optionally
+/// the bias.
+///
+/// When `output_shape` is set, padding and adjustments are worked out so that
+/// the output spatial dimensions match it.
+#[derive(Debug, Clone, Default, Hash)]
+pub struct Deconv {
+    pub data_format: DataFormat,
+    pub kernel_format: KernelFormat,
+    pub padding: PaddingSpec,
+    pub strides: Option<TVec<usize>>,
+    pub dilations: Option<TVec<usize>>,
+    pub adjustments: Option<TVec<usize>>,
+    pub output_shape: Option<TVec<usize>>,
+    pub group: usize,
+    pub bias_input: Option<usize>,
+}
+
+impl_dyn_hash!(Deconv);
+
+impl Deconv {
+    fn output_channels(&self, kshape: &[usize]) -> usize {
+        match self.kernel_format {
+            KernelFormat::OIHW => kshape[1] * self.group,
+            KernelFormat::HWIO => kshape[kshape.len() - 2] * self.group,
+        }
+    }
+
+    fn pool_spec(&self, kshape: &[usize]) -> PoolSpec {
+        let spatial_rank = kshape.len() - 2;
+        PoolSpec {
+            data_format: self.data_format,
+            kernel_shape: kshape[self.kernel_format.h_axis()..][..spatial_rank].into(),
+            padding: self.padding.clone(),
+            dilations: self.dilations.clone(),
+            strides: self.strides.clone(),
+            output_channel_override: Some(self.output_channels(kshape)),
+        }
+    }
+
+    /// Pool spec and adjustments to use for the core operator, given the
+    /// input and kernel shape.
+    pub fn resolve<D: DimLike>(
+        &self,
+        ishape: &[D],
+        kshape: &[usize],
+    ) -> TractResult<(PoolSpec, TVec<usize>)> {
+        let mut pool_spec = self.pool_spec(kshape);
+        let rank = pool_spec.rank();
+        let mut adjustments = self.adjustments.clone().unwrap_or_else(|| tvec!(0; rank));
+        if let Some(wanted) = &self.output_shape {
+            // some exporters put the full shape there, only keep spatial dims
+            let wanted = &wanted[wanted.len() - rank..];
+            let ishape = self.data_format.shape(ishape)?;
+            let mut before = tvec!();
+            let mut after = tvec!();
+            for ix in 0..rank {
+                let full = PaddingSpec::Valid
+                    .compute_one_for_deconv(
+                        ix,
+                        &ishape.hw_dims()[ix].to_usize()?,
+                        pool_spec.kernel_shape[ix],
+                        pool_spec.dilation(ix),
+                        pool_spec.stride(ix),
+                        adjustments[ix],
+                    )
+                    .output;
+                if full < wanted[ix] {
+                    adjustments[ix] += wanted[ix] - full;
+                    before.push(0);
+                    after.push(0);
+                } else {
+                    let (b, a) = self.split_padding(full - wanted[ix]);
+                    before.push(b);
+                    after.push(a);
+                }
+            }
+            pool_spec.padding = PaddingSpec::Explicit(before, after, false);
+        } else if matches!(self.padding, PaddingSpec::SameUpper | PaddingSpec::SameLower)
+            && adjustments.iter().any(|a| *a != 0)
+        {
+            // with auto padding, the output size is input * stride whatever the adjustments:
+            // they only move the padding
+            let mut before = tvec!();
+            let mut after = tvec!();
+            for ix in 0..rank {
+                let kernel_field = (pool_spec.kernel_shape[ix] - 1) * pool_spec.dilation(ix) + 1;
+                let total = (kernel_field + adjustments[ix]).saturating_sub(pool_spec.stride(ix));
+                let (b, a) = self.split_padding(total);
+                before.push(b);
+                after.push(a);
+            }
+            pool_spec.padding = PaddingSpec::Explicit(before, after, false);
+        }
+        Ok((pool_spec, adjustments))
+    }
+
+    /// Split a total padding in before and after parts: the larger part goes
+    /// first, unless padding is SAME_UPPER.
+    fn split_padding(&self, total: usize) -> (usize, usize) {
+        if matches!(self.padding, PaddingSpec::SameUpper) {
+            (total / 2, total - total / 2)
+        } else {
+            (total - total / 2, total / 2)
+        }
+    }
+}
+
+impl Expansion for Deconv {
+    fn name(&self) -> Cow<str> {
+        "DeconvHir".into()
+    }
+
+    op_hir!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 2 + self.bias_input.is_some() as usize)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &inputs[1].datum_type)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].rank, &outputs[0].rank)?;
+        let has_n = self.data_format.has_n();
+        s.equals(&inputs[0].rank, inputs[1].rank.bex() + (has_n as usize as i64 - 1))?;
+        if let Some(bias) = self.bias_input {
+            s.equals(&inputs[bias].rank, 1)?;
+        }
+        s.given_2(&inputs[0].shape, &inputs[1].shape, move |s, ishape, kshape| {
+            if let Some(kshape) =
+                kshape.iter().map(|d| d.to_usize().ok()).collect::<Option<TVec<_>>>()
+            {
+                if self.output_shape.is_some() && ishape.iter().any(|d| d.to_usize().is_err()) {
+                    return Ok(());
+                }
+                let (pool_spec, adjustments) = self.resolve(&*ishape, &*kshape)?;
+                let oshape = output_shape(&pool_spec, &*ishape, &*adjustments)?;
+                s.equals(&outputs[0].shape, oshape)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let kernel = model.outlet_fact(inputs[1])?.konst.clone().context("Kernel must be const")?;
+        let bias = if let Some(slot) = self.bias_input {
+            Some(model.outlet_fact(inputs[slot])?.konst.clone().context("Bias must be const")?)
+        } else {
+            None
+        };
+        let ishape = model.outlet_fact(inputs[0])?.shape.to_tvec();
+        let (pool_spec, adjustments) = self.resolve(&*ishape, kernel.shape())?;
+        let op =
+            DeconvUnary::new(pool_spec, self.kernel_format, kernel, bias, adjustments, self.group);
+        model.wire_node(prefix, op, &[inputs[0]])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn infer_output_shape_with_strides() -> TractResult<()> {
+        let mut op = expand(Deconv { strides: Some(tvec!(2, 2)), group: 1, ..Deconv::default() });
+        let ifact = InferenceFact::dt_shape(DatumType::F32, shapefactoid!(1, 1, 3, 3));
+        let kfact = InferenceFact::dt_shape(DatumType::F32, shapefactoid!(1, 2, 3, 3));
+        let ofact = InferenceFact::default();
+        let facts = op.infer_facts(tvec!(&ifact, &kfact), tvec!(&ofact), tvec!())?;
+        assert_eq!(
+            facts.1,
+            tvec!(InferenceFact::dt_shape(DatumType::F32, shapefactoid!(1, 2, 7, 7)))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn infer_output_shape_from_attribute() -> TractResult<()> {
+        let mut op = expand(Deconv {
+            strides: Some(tvec!(2, 2)),
+            output_shape: Some(tvec!(8, 6)),
+            group: 1,
+            ..Deconv::default()
+        });
+        let ifact = InferenceFact::dt_shape(DatumType::F32, shapefactoid!(1, 1, 3, 3));
+        let kfact = InferenceFact::dt_shape(DatumType::F32, shapefactoid!(1, 2, 3, 3));
+        let ofact = InferenceFact::default();
+        let facts = op.infer_facts(tvec!(&ifact, &kfact), tvec!(&ofact), tvec!())?;
+        assert_eq!(
+            facts.1,
+            tvec!(InferenceFact::dt_shape(DatumType::F32, shapefactoid!(1, 2, 8, 6)))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn output_shape_puts_larger_padding_first() -> TractResult<()> {
+        // full output is 7, 2 is cropped: 1 on each side, then 3: 2 before and 1 after
+        let op =
+            Deconv { strides: Some(tvec!(2)), output_shape: Some(tvec!(5)), ..Deconv::default() };
+        let (pool_spec, _) = op.resolve(&[1usize, 1, 3], &[1, 1, 3])?;
+        assert_eq!(pool_spec.padding, PaddingSpec::Explicit(tvec!(1), tvec!(1), false));
+        let op = Deconv { output_shape: Some(tvec!(4)), ..op };
+        let (pool_spec, _) = op.resolve(&[1usize, 1, 3], &[1, 1, 3])?;
+        assert_eq!(pool_spec.padding, PaddingSpec::Explicit(tvec!(2), tvec!(1), false));
+        let op = Deconv { padding: PaddingSpec::SameUpper, ..op };
+        let (pool_spec, _) = op.resolve(&[1usize, 1, 3], &[1, 1, 3])?;
+        assert_eq!(pool_spec.padding, PaddingSpec::Explicit(tvec!(1), tvec!(2), false));
+        Ok(())
+    }
+
+    #[test]
+    fn same_padding_adjustments_keep_output_shape() -> TractResult<()> {
+        let mut op = expand(Deconv {
+            padding: PaddingSpec::SameUpper,
+            strides: Some(tvec!(2, 2)),
+            adjustments: Some(tvec!(1, 1)),
+            group: 1,
+            ..Deconv::default()
+        });
+        let ifact = InferenceFact::dt_shape(DatumType::F32, shapefactoid!(1, 1, 3, 3));
+        let kfact = InferenceFact::dt_shape(DatumType::F32, shapefactoid!(1, 2, 3, 3));
+        let ofact = InferenceFact::default();
+        let facts = op.infer_facts(tvec!(&ifact, &kfact), tvec!(&ofact), tvec!())?;
+        assert_eq!(
+            facts.1,
+            tvec!(InferenceFact::dt_shape(DatumType::F32, shapefactoid!(1, 2, 6, 6)))
+        );
+        Ok(())
+    }
+}
@@ -154,7 +154,7 @@ impl Reduce {
     }
 
     fn resolve_axis(axis: i64, rank: usize) -> TractResult<usize> {
-        if 0 <= axis && axis as usize <= rank - 1 {
+        if 0 <= axis && (axis as usize) < rank {
             Ok(axis as usize)
         } else if -(rank as i64) <= axis && axis < 0 {
             Ok((axis + rank as i64) as usize)
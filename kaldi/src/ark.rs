@@ -0,0 +1,290 @@
+//! Kaldi archives (`.ark`) and script files (`.scp`) of matrices and vectors, as written by
+//! feature extraction or `nnet3-compute`.
+//!
+//! Binary (`FM`, `DM`, `FV`, `DV`), compressed (`CM`, `CM2`, `CM3`) and text objects are read,
+//! all as f32. Archives are written as binary f32 objects.
+use std::io::Write;
+use std::path::Path;
+
+use tract_hir::internal::*;
+
+use nom::IResult;
+use nom::{
+    branch::alt,
+    bytes::complete::*,
+    character::complete::*,
+    combinator::*,
+    error::ErrorKind,
+    multi::{count, many0},
+    number::complete::{le_f32, le_f64, le_i32, le_u16, le_u8},
+    sequence::*,
+};
+
+use tract_itertools::Itertools;
+
+use crate::parser::{integer, text};
+
+/// Reads `ark:` or `scp:` specifiers, or a path, as an archive unless it ends with `.scp`.
+pub fn read(spec: &str) -> TractResult<Vec<(String, Tensor)>> {
+    let (scp, path) = if spec.starts_with("scp:") {
+        (true, &spec[4..])
+    } else if spec.starts_with("ark:") {
+        (false, &spec[4..])
+    } else {
+        (spec.ends_with(".scp"), spec)
+    };
+    if scp {
+        let scp = std::fs::read_to_string(path).with_context(|| format!("Reading {}", path))?;
+        read_scp(&scp)
+    } else {
+        let ark = std::fs::read(path).with_context(|| format!("Reading {}", path))?;
+        read_ark(&ark).with_context(|| format!("Reading {}", path))
+    }
+}
+
+/// Parses all the entries of an archive.
+pub fn read_ark(ark: &[u8]) -> TractResult<Vec<(String, Tensor)>> {
+    let (_, entries) =
+        all_consuming(terminated(many0(entry), multispace0))(ark).map_err(|e| match e {
+            nom::Err::Error(err) | nom::Err::Failure(err) => format_err!(
+                "Parsing kaldi archive at: {:?}",
+                err.input.iter().take(120).map(|b| format!("{}", *b as char)).join("")
+            ),
+            e => format_err!("{:?}", e),
+        })?;
+    Ok(entries)
+}
+
+/// Reads the entries of a script file: one `key path[:offset]` per line.
+pub fn read_scp(scp: &str) -> TractResult<Vec<(String, Tensor)>> {
+    let mut files: HashMap<&str, Vec<u8>> = HashMap::new();
+    let mut entries = vec![];
+    for line in scp.lines().map(|l| l.trim()).filter(|l| l.len() > 0) {
+        let mut split = line.splitn(2, char::is_whitespace);
+        let key = split.next().unwrap();
+        let location = split.next().with_context(|| format!("No location in {}", line))?.trim();
+        if location.ends_with(']') || location.ends_with('|') {
+            bail!("Only plain path and offset locations are supported, got {}", location)
+        }
+        let (path, offset) = match location.rfind(':') {
+            Some(colon) if location[colon + 1..].chars().all(|c| c.is_ascii_digit()) => {
+                (&location[..colon], location[colon + 1..].parse::<usize>()?)
+            }
+            _ => (location, 0),
+        };
+        if !files.contains_key(path) {
+            let bytes = std::fs::read(path).with_context(|| format!("Reading {}", path))?;
+            files.insert(path, bytes);
+        }
+        let bytes = &files[path];
+        if offset > bytes.len() {
+            bail!("Offset {} is past the end of {}", offset, path)
+        }
+        let (_, tensor) =
+            object(&bytes[offset..]).map_err(|_| format_err!("Parsing {} at {}", path, offset))?;
+        entries.push((key.to_string(), tensor));
+    }
+    Ok(entries)
+}
+
+/// Appends an entry to a binary archive. Tensors must be of rank 1 or 2, and castable to f32.
+pub fn write_ark<W: Write>(writer: &mut W, key: &str, tensor: &Tensor) -> TractResult<()> {
+    fn int<W: Write>(writer: &mut W, i: usize) -> TractResult<()> {
+        writer.write_all(&[4])?;
+        writer.write_all(&(i as i32).to_le_bytes())?;
+        Ok(())
+    }
+    let tensor = tensor.cast_to::<f32>()?;
+    write!(writer, "{} \0B", key)?;
+    match tensor.shape() {
+        [len] => {
+            writer.write_all(b"FV ")?;
+            int(writer, *len)?;
+        }
+        [rows, cols] => {
+            writer.write_all(b"FM ")?;
+            int(writer, *rows)?;
+            int(writer, *cols)?;
+        }
+        shape => bail!("Can only write vectors and matrices to an archive, got {:?}", shape),
+    }
+    for f in tensor.as_slice::<f32>()? {
+        writer.write_all(&f.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+/// Writes a binary archive.
+pub fn write(path: impl AsRef<Path>, entries: &[(String, Tensor)]) -> TractResult<()> {
+    let path = path.as_ref();
+    let file = std::fs::File::create(path).with_context(|| format!("Creating {:?}", path))?;
+    let mut writer = std::io::BufWriter::new(file);
+    for (key, tensor) in entries {
+        write_ark(&mut writer, key, tensor).with_context(|| format!("Writing {}", key))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn entry(i: &[u8]) -> IResult<&[u8], (String, Tensor)> {
+    let (i, key) = preceded(
+        multispace0,
+        map_res(take_till1(|c: u8| c.is_ascii_whitespace()), std::str::from_utf8),
+    )(i)?;
+    let (i, tensor) = preceded(tag(" "), object)(i)?;
+    Ok((i, (key.to_string(), tensor)))
+}
+
+fn object(i: &[u8]) -> IResult<&[u8], Tensor> {
+    alt((preceded(tag("\0B"), binary_object), text::matrix))(i)
+}
+
+fn binary_object(i: &[u8]) -> IResult<&[u8], Tensor> {
+    let (i, token) = terminated(take_till1(|c| c == b' '), tag(" "))(i)?;
+    match token {
+        b"FM" => matrix(i, le_f32),
+        b"DM" => matrix(i, map(le_f64, |f| f as f32)),
+        b"FV" => vector(i, le_f32),
+        b"DV" => vector(i, map(le_f64, |f| f as f32)),
+        b"CM" | b"CM2" | b"CM3" => compressed(i, token),
+        _ => Err(nom::Err::Error(nom::error::make_error(i, ErrorKind::Tag))),
+    }
+}
+
+fn vector<'a>(
+    i: &'a [u8],
+    value: impl FnMut(&'a [u8]) -> IResult<&'a [u8], f32>,
+) -> IResult<&'a [u8], Tensor> {
+    let (i, len) = integer(true)(i)?;
+    map(count(value, len as usize), |data| tensor1(&*data))(i)
+}
+
+fn matrix<'a>(
+    i: &'a [u8],
+    value: impl FnMut(&'a [u8]) -> IResult<&'a [u8], f32>,
+) -> IResult<&'a [u8], Tensor> {
+    let (i, rows) = integer(true)(i)?;
+    let (i, cols) = integer(true)(i)?;
+    let (rows, cols) = (rows as usize, cols as usize);
+    map_res(count(value, rows * cols), move |data| {
+        tract_ndarray::Array2::from_shape_vec((rows, cols), data).map(Tensor::from)
+    })(i)
+}
+
+// See kaldi's matrix/compressed-matrix.cc: values are quantized in a [min, min + range] global
+// interval, and for CM, in piecewise linear per column intervals (0, 25, 75 and 100 percentiles).
+fn compressed<'a>(i: &'a [u8], format: &[u8]) -> IResult<&'a [u8], Tensor> {
+    let (i, (min, range, rows, cols)) = tuple((le_f32, le_f32, le_i32, le_i32))(i)?;
+    let (rows, cols) = (rows as usize, cols as usize);
+    let global = move |v: u16| min + range * (v as f32 / 65535.0);
+    let (i, data) = match format {
+        b"CM" => {
+            let (i, headers) = count(count(le_u16, 4), cols)(i)?;
+            // stored column by column
+            let (i, bytes) = take(rows * cols)(i)?;
+            let mut data = Vec::with_capacity(rows * cols);
+            for row in 0..rows {
+                for (col, header) in headers.iter().enumerate() {
+                    let p = header.iter().map(|&v| global(v)).collect::<Vec<_>>();
+                    let c = bytes[col * rows + row] as f32;
+                    data.push(if c <= 64.0 {
+                        p[0] + (p[1] - p[0]) * c / 64.0
+                    } else if c <= 192.0 {
+                        p[1] + (p[2] - p[1]) * (c - 64.0) / 128.0
+                    } else {
+                        p[2] + (p[3] - p[2]) * (c - 192.0) / 63.0
+                    });
+                }
+            }
+            (i, data)
+        }
+        b"CM2" => map(count(le_u16, rows * cols), |v| v.into_iter().map(global).collect())(i)?,
+        _ => map(count(le_u8, rows * cols), |v| {
+            v.into_iter().map(|v| min + range * (v as f32 / 255.0)).collect()
+        })(i)?,
+    };
+    Ok((i, tract_ndarray::Array2::from_shape_vec((rows, cols), data).unwrap().into_tensor()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn binary_roundtrip() -> TractResult<()> {
+        let matrix = tensor2(&[[1f32, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+        let vector = tensor1(&[-1f32, 0.5]);
+        let mut ark = vec![];
+        write_ark(&mut ark, "utt1", &matrix)?;
+        write_ark(&mut ark, "utt2", &vector)?;
+        let entries = read_ark(&ark)?;
+        assert_eq!(entries, vec![("utt1".to_string(), matrix), ("utt2".to_string(), vector)]);
+        Ok(())
+    }
+
+    #[test]
+    fn text_and_double() -> TractResult<()> {
+        let mut ark = b"utt1  [\n  1 2\n  3 4 ]\nutt2 \0BDM ".to_vec();
+        for i in &[1i32, 2] {
+            ark.push(4);
+            ark.extend(&i.to_le_bytes());
+        }
+        for f in &[0.5f64, 1.5] {
+            ark.extend(&f.to_le_bytes());
+        }
+        let entries = read_ark(&ark)?;
+        assert_eq!(entries[0], ("utt1".to_string(), tensor2(&[[1f32, 2.], [3., 4.]])));
+        assert_eq!(entries[1], ("utt2".to_string(), tensor2(&[[0.5f32, 1.5]])));
+        Ok(())
+    }
+
+    fn compressed_header(format: &str, rows: i32, cols: i32) -> Vec<u8> {
+        let mut ark = format!("utt \0B{} ", format).into_bytes();
+        ark.extend(&1f32.to_le_bytes());
+        ark.extend(&2f32.to_le_bytes());
+        ark.extend(&rows.to_le_bytes());
+        ark.extend(&cols.to_le_bytes());
+        ark
+    }
+
+    #[test]
+    fn compressed() -> TractResult<()> {
+        // values in [1, 3]
+        let mut cm3 = compressed_header("CM3", 1, 2);
+        cm3.extend(&[0u8, 255]);
+        assert_eq!(read_ark(&cm3)?[0].1, tensor2(&[[1f32, 3.]]));
+        let mut cm2 = compressed_header("CM2", 2, 1);
+        for v in &[0u16, 65535] {
+            cm2.extend(&v.to_le_bytes());
+        }
+        assert_eq!(read_ark(&cm2)?[0].1, tensor2(&[[1f32], [3.]]));
+        // one column, percentiles at 1, 1.5, 2.5 and 3
+        let mut cm = compressed_header("CM", 3, 1);
+        for v in &[0u16, 16384, 49151, 65535] {
+            cm.extend(&v.to_le_bytes());
+        }
+        cm.extend(&[0u8, 64, 255]);
+        let found = read_ark(&cm)?.remove(0).1;
+        found.close_enough(&tensor2(&[[1f32], [1.5], [3.]]), true)?;
+        Ok(())
+    }
+
+    #[test]
+    fn scp() -> TractResult<()> {
+        let dir = std::env::temp_dir().join(format!("tract-kaldi-scp-{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let ark = dir.join("feats.ark");
+        let entries = vec![
+            ("utt1".to_string(), tensor2(&[[1f32, 2.0]])),
+            ("utt2".to_string(), tensor2(&[[3f32, 4.0], [5.0, 6.0]])),
+        ];
+        write(&ark, &entries)?;
+        let mut first = vec![];
+        write_ark(&mut first, "utt1", &entries[0].1)?;
+        let scp = format!("utt2 {}:{}\nutt1 {}:5\n", ark.display(), first.len() + 5, ark.display());
+        let found = read_scp(&scp)?;
+        std::fs::remove_dir_all(&dir)?;
+        assert_eq!(found, vec![entries[1].clone(), entries[0].clone()]);
+        Ok(())
+    }
+}
@@ -0,0 +1,519 @@
+//! Kaldi feature extraction (`compute-mfcc-feats`, `compute-fbank-feats`, `apply-cmvn`) as a
+//! model front-end, from raw PCM samples to features.
+//!
+//! Pitch features (`compute-kaldi-pitch-feats`, `--add-pitch`) are out of scope: options asking
+//! for them are rejected.
+//!
+//! The reference features of the tests come from a transcription of Kaldi's feature code
+//! (`test_cases/features/reference.py`), not from Kaldi binaries: see the README there.
+use tract_hir::internal::*;
+use tract_hir::ops::binary::IntoHir;
+
+use crate::ops::features::{DftPowerSpectrum, FlooredLog, FrameTransform, Framing, LogEnergy};
+use crate::ops::scale_offset::ScaleAndOffset;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FeatureKind {
+    Mfcc,
+    Fbank,
+}
+
+/// Feature extraction options, with Kaldi names and defaults.
+#[derive(Clone, Debug)]
+pub struct FeatureOptions {
+    pub kind: FeatureKind,
+    pub sample_frequency: f32,
+    pub frame_length_ms: f32,
+    pub frame_shift_ms: f32,
+    pub preemphasis_coefficient: f32,
+    pub remove_dc_offset: bool,
+    pub window_type: String,
+    pub blackman_coeff: f32,
+    pub round_to_power_of_two: bool,
+    pub num_mel_bins: usize,
+    pub low_freq: f32,
+    pub high_freq: f32,
+    pub use_energy: bool,
+    pub raw_energy: bool,
+    pub energy_floor: f32,
+    pub use_power: bool,
+    pub use_log_fbank: bool,
+    pub num_ceps: usize,
+    pub cepstral_lifter: f32,
+    pub cmvn: Option<Cmvn>,
+}
+
+impl FeatureOptions {
+    pub fn mfcc() -> FeatureOptions {
+        FeatureOptions {
+            kind: FeatureKind::Mfcc,
+            sample_frequency: 16000.0,
+            frame_length_ms: 25.0,
+            frame_shift_ms: 10.0,
+            preemphasis_coefficient: 0.97,
+            remove_dc_offset: true,
+            window_type: "povey".to_string(),
+            blackman_coeff: 0.42,
+            round_to_power_of_two: true,
+            num_mel_bins: 23,
+            low_freq: 20.0,
+            high_freq: 0.0,
+            use_energy: true,
+            raw_energy: true,
+            energy_floor: 0.0,
+            use_power: true,
+            use_log_fbank: true,
+            num_ceps: 13,
+            cepstral_lifter: 22.0,
+            cmvn: None,
+        }
+    }
+
+    pub fn fbank() -> FeatureOptions {
+        FeatureOptions { kind: FeatureKind::Fbank, use_energy: false, ..FeatureOptions::mfcc() }
+    }
+
+    /// Options from the defaults of `kind` and a Kaldi configuration file (`mfcc.conf`,
+    /// `fbank.conf`).
+    pub fn parse(kind: FeatureKind, conf: &str) -> TractResult<FeatureOptions> {
+        let mut options = if kind == FeatureKind::Mfcc {
+            FeatureOptions::mfcc()
+        } else {
+            FeatureOptions::fbank()
+        };
+        for line in conf.lines() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.len() == 0 {
+                continue;
+            }
+            if !line.starts_with("--") {
+                bail!("Expected --option=value, got {}", line)
+            }
+            let mut split = line[2..].splitn(2, '=');
+            let key = split.next().unwrap();
+            let value = split.next().unwrap_or("true");
+            options.set(key, value).with_context(|| format!("Setting option {}", line))?;
+        }
+        Ok(options)
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> TractResult<()> {
+        fn boolean(value: &str) -> TractResult<bool> {
+            match value {
+                "true" => Ok(true),
+                "false" => Ok(false),
+                _ => bail!("Expected true or false, got {}", value),
+            }
+        }
+        match key {
+            "sample-frequency" => self.sample_frequency = value.parse()?,
+            "frame-length" => self.frame_length_ms = value.parse()?,
+            "frame-shift" => self.frame_shift_ms = value.parse()?,
+            "preemphasis-coefficient" => self.preemphasis_coefficient = value.parse()?,
+            "remove-dc-offset" => self.remove_dc_offset = boolean(value)?,
+            "window-type" => self.window_type = value.to_string(),
+            "blackman-coeff" => self.blackman_coeff = value.parse()?,
+            "round-to-power-of-two" => self.round_to_power_of_two = boolean(value)?,
+            "num-mel-bins" => self.num_mel_bins = value.parse()?,
+            "low-freq" => self.low_freq = value.parse()?,
+            "high-freq" => self.high_freq = value.parse()?,
+            "use-energy" => self.use_energy = boolean(value)?,
+            "raw-energy" => self.raw_energy = boolean(value)?,
+            "energy-floor" => self.energy_floor = value.parse()?,
+            "use-power" => self.use_power = boolean(value)?,
+            "use-log-fbank" => self.use_log_fbank = boolean(value)?,
+            "num-ceps" => self.num_ceps = value.parse()?,
+            "cepstral-lifter" => self.cepstral_lifter = value.parse()?,
+            // dithering adds noise, inference runs without it
+            "dither" => {
+                if value.parse::<f32>()? != 0.0 {
+                    warn!("Ignoring dither={}", value)
+                }
+            }
+            "add-pitch" if boolean(value)? => bail!("Pitch features are not supported"),
+            "pitch-config" | "min-f0" | "max-f0" => bail!("Pitch features are not supported"),
+            "snip-edges" if !boolean(value)? => bail!("Only snip-edges=true is supported"),
+            "htk-compat" | "subtract-mean" if boolean(value)? => bail!("Unsupported option"),
+            "vtln-warp" if value.parse::<f32>()? != 1.0 => bail!("Unsupported option"),
+            "add-pitch"
+            | "snip-edges"
+            | "htk-compat"
+            | "subtract-mean"
+            | "vtln-warp"
+            | "allow-downsample"
+            | "allow-upsample"
+            | "min-duration"
+            | "max-feature-vectors"
+            | "output-format"
+            | "debug-mel"
+            | "verbose" => (),
+            _ => bail!("Unknown option"),
+        }
+        Ok(())
+    }
+
+    pub fn frame_length(&self) -> usize {
+        (self.sample_frequency * 0.001 * self.frame_length_ms) as usize
+    }
+
+    pub fn frame_shift(&self) -> usize {
+        (self.sample_frequency * 0.001 * self.frame_shift_ms) as usize
+    }
+
+    fn padded_length(&self) -> usize {
+        if self.round_to_power_of_two {
+            self.frame_length().next_power_of_two()
+        } else {
+            self.frame_length()
+        }
+    }
+
+    pub fn dim(&self) -> usize {
+        match self.kind {
+            FeatureKind::Mfcc => self.num_ceps,
+            FeatureKind::Fbank => self.num_mel_bins + self.use_energy as usize,
+        }
+    }
+
+    fn window(&self) -> TractResult<Vec<f64>> {
+        use std::f64::consts::PI;
+        let length = self.frame_length();
+        let a = 2.0 * PI / (length - 1) as f64;
+        let blackman = self.blackman_coeff as f64;
+        (0..length)
+            .map(|i| {
+                let i = i as f64;
+                Ok(match &*self.window_type {
+                    "hanning" => 0.5 - 0.5 * (a * i).cos(),
+                    "sine" => (0.5 * a * i).sin(),
+                    "hamming" => 0.54 - 0.46 * (a * i).cos(),
+                    "povey" => (0.5 - 0.5 * (a * i).cos()).powf(0.85),
+                    "rectangular" => 1.0,
+                    "blackman" => {
+                        blackman - 0.5 * (a * i).cos() + (0.5 - blackman) * (2.0 * a * i).cos()
+                    }
+                    _ => bail!("Unknown window type {}", self.window_type),
+                })
+            })
+            .collect()
+    }
+
+    /// Triangular mel filters, as a `[padded / 2 + 1, num_mel_bins]` matrix.
+    fn mel_banks(&self) -> Tensor {
+        fn mel_scale(freq: f64) -> f64 {
+            1127.0 * (1.0 + freq / 700.0).ln()
+        }
+        let nyquist_bin = self.padded_length() / 2;
+        let nyquist = 0.5 * self.sample_frequency as f64;
+        let high_freq = if self.high_freq > 0.0 {
+            self.high_freq as f64
+        } else {
+            nyquist + self.high_freq as f64
+        };
+        let bin_width = self.sample_frequency as f64 / self.padded_length() as f64;
+        let mel_low = mel_scale(self.low_freq as f64);
+        let mel_delta = (mel_scale(high_freq) - mel_low) / (self.num_mel_bins + 1) as f64;
+        tract_ndarray::Array2::from_shape_fn((nyquist_bin + 1, self.num_mel_bins), |(i, bin)| {
+            let left = mel_low + bin as f64 * mel_delta;
+            let center = left + mel_delta;
+            let right = center + mel_delta;
+            let mel = mel_scale(bin_width * i as f64);
+            if i == nyquist_bin || mel <= left || mel >= right {
+                0.0
+            } else if mel <= center {
+                ((mel - left) / (center - left)) as f32
+            } else {
+                ((right - mel) / (right - center)) as f32
+            }
+        })
+        .into_tensor()
+    }
+
+    /// DCT-II and cepstral liftering, as a `[num_mel_bins, num_ceps]` matrix.
+    fn dct(&self) -> Tensor {
+        use std::f64::consts::PI;
+        let bins = self.num_mel_bins as f64;
+        let lifter = self.cepstral_lifter as f64;
+        tract_ndarray::Array2::from_shape_fn((self.num_mel_bins, self.num_ceps), |(n, k)| {
+            // with energy, the first coefficient is replaced
+            if k == 0 && self.use_energy {
+                return 0.0;
+            }
+            let normalizer = if k == 0 { (1.0 / bins).sqrt() } else { (2.0 / bins).sqrt() };
+            let lifter = if lifter != 0.0 {
+                1.0 + 0.5 * lifter * (PI * k as f64 / lifter).sin()
+            } else {
+                1.0
+            };
+            (normalizer * lifter * (PI / bins * (n as f64 + 0.5) * k as f64).cos()) as f32
+        })
+        .into_tensor()
+    }
+
+    /// Wires the features of `samples`, a rank 1 signal (with 16-bit sample values).
+    pub fn wire(
+        &self,
+        prefix: &str,
+        model: &mut InferenceModel,
+        samples: OutletId,
+    ) -> TractResult<OutletId> {
+        let length = self.frame_length();
+        let node = |model: &mut InferenceModel, name: &str, op: Box<dyn InferenceOp>, wire| {
+            TractResult::Ok(model.wire_node(format!("{}.{}", prefix, name), op, &[wire])?[0])
+        };
+        let matrix = |model: &mut InferenceModel, name: &str, m: Tensor, wire| {
+            node(model, name, expand(FrameTransform::new(m.into_arc_tensor())), wire)
+        };
+        let mut wire =
+            node(model, "framing", expand(Framing::new(length, self.frame_shift())), samples)?;
+        if self.remove_dc_offset {
+            let m = tract_ndarray::Array2::from_shape_fn((length, length), |(i, j)| {
+                (i == j) as usize as f32 - 1.0 / length as f32
+            });
+            wire = matrix(model, "remove_dc_offset", m.into_tensor(), wire)?;
+        }
+        let floor = f32::MIN_POSITIVE.max(self.energy_floor);
+        let mut energy = None;
+        if self.use_energy && self.raw_energy {
+            energy = Some(node(model, "energy", expand(LogEnergy::new(floor)), wire)?);
+        }
+        if self.preemphasis_coefficient != 0.0 {
+            let coef = self.preemphasis_coefficient;
+            let m = tract_ndarray::Array2::from_shape_fn((length, length), |(i, j)| {
+                if i == j {
+                    1.0 - coef * (i == 0) as usize as f32
+                } else if i + 1 == j {
+                    -coef
+                } else {
+                    0.0
+                }
+            });
+            wire = matrix(model, "preemphasis", m.into_tensor(), wire)?;
+        }
+        let window = self.window()?.iter().map(|w| *w as f32).collect::<Vec<_>>();
+        let window = tensor1(&window).into_shape(&[1, length])?;
+        let window = model.add_const(format!("{}.window.function", prefix), window)?;
+        wire = model.wire_node(
+            format!("{}.window", prefix),
+            tract_hir::ops::math::Mul.into_hir(),
+            &[wire, window],
+        )?[0];
+        if self.use_energy && !self.raw_energy {
+            energy = Some(node(model, "energy", expand(LogEnergy::new(floor)), wire)?);
+        }
+        let spectrum = DftPowerSpectrum::new(length, self.padded_length());
+        wire = node(model, "power_spectrum", expand(spectrum), wire)?;
+        if !self.use_power {
+            wire = node(model, "magnitude", Box::new(tract_hir::ops::math::sqrt()), wire)?;
+        }
+        wire = matrix(model, "mel_banks", self.mel_banks(), wire)?;
+        if self.kind == FeatureKind::Mfcc || self.use_log_fbank {
+            wire = node(model, "log", expand(FlooredLog::new(f32::EPSILON)), wire)?;
+        }
+        let dim = self.dim();
+        if self.kind == FeatureKind::Mfcc {
+            wire = matrix(model, "dct", self.dct(), wire)?;
+        } else if self.use_energy {
+            // make room for the energy in the first column
+            let m = tract_ndarray::Array2::from_shape_fn((dim - 1, dim), |(i, j)| {
+                (i + 1 == j) as usize as f32
+            });
+            wire = matrix(model, "shift", m.into_tensor(), wire)?;
+        }
+        if let Some(energy) = energy {
+            let mut first = tract_ndarray::Array2::<f32>::zeros((1, dim));
+            first[(0, 0)] = 1.0;
+            let energy = matrix(model, "energy.column", first.into_tensor(), energy)?;
+            wire = model.wire_node(
+                format!("{}.with_energy", prefix),
+                tract_hir::ops::math::Add.into_hir(),
+                &[wire, energy],
+            )?[0];
+        }
+        if let Some(cmvn) = &self.cmvn {
+            if cmvn.means.len() != dim {
+                bail!("CMVN stats are for dimension {}, features are {}", cmvn.means.len(), dim)
+            }
+            wire = node(model, "cmvn", expand(cmvn.scale_and_offset()), wire)?;
+        }
+        Ok(wire)
+    }
+
+    /// A model computing the features of its input, a stream of samples.
+    pub fn model(&self) -> TractResult<InferenceModel> {
+        let mut model = InferenceModel::default();
+        let samples = model.add_source(
+            "samples",
+            InferenceFact::dt_shape(f32::datum_type(), tvec!(tract_pulse::internal::stream_dim())),
+        )?;
+        let features = self.wire("features", &mut model, samples)?;
+        model.set_output_outlets(&[features])?;
+        Ok(model)
+    }
+}
+
+/// Rows of a matrix in Kaldi text format (`[ 1 2\n 3 4 ]`), in double precision.
+fn text_matrix(text: &str) -> TractResult<Vec<Vec<f64>>> {
+    let start = text.find('[').context("Expected [")?;
+    let end = text.rfind(']').context("Expected ]")?;
+    text[start + 1..end]
+        .lines()
+        .filter(|line| line.trim().len() > 0)
+        .map(|line| Ok(line.split_whitespace().map(|x| x.parse()).collect::<Result<_, _>>()?))
+        .collect()
+}
+
+/// Cepstral mean (and variance) normalization from global statistics.
+#[derive(Clone, Debug)]
+pub struct Cmvn {
+    pub means: Vec<f32>,
+    pub scales: Option<Vec<f32>>,
+}
+
+impl Cmvn {
+    /// Normalization from `compute-cmvn-stats` statistics (text format), normalizing the
+    /// variance too if `norm_vars` is set.
+    pub fn parse(stats: &str, norm_vars: bool) -> TractResult<Cmvn> {
+        let stats = text_matrix(stats).context("Parsing CMVN stats")?;
+        if stats.len() != 2 || stats[0].len() < 2 || stats[1].len() != stats[0].len() {
+            bail!("CMVN stats must be a 2 rows matrix")
+        }
+        let dim = stats[0].len() - 1;
+        let count = stats[0][dim];
+        if count < 1.0 {
+            bail!("CMVN stats have a count of {}", count)
+        }
+        let means: Vec<f64> = (0..dim).map(|d| stats[0][d] / count).collect();
+        let scales = if norm_vars {
+            let scales = (0..dim)
+                .map(|d| {
+                    let var = (stats[1][d] / count - means[d] * means[d]).max(1e-20);
+                    (1.0 / var.sqrt()) as f32
+                })
+                .collect();
+            Some(scales)
+        } else {
+            None
+        };
+        Ok(Cmvn { means: means.iter().map(|m| *m as f32).collect(), scales })
+    }
+
+    fn scale_and_offset(&self) -> ScaleAndOffset {
+        let scales = self.scales.clone().unwrap_or_else(|| vec![1.0; self.means.len()]);
+        let offsets: Vec<f32> = self.means.iter().zip(scales.iter()).map(|(m, s)| -m * s).collect();
+        let dim = self.means.len();
+        ScaleAndOffset::new(
+            tensor1(&scales).into_shape(&[1, dim]).unwrap().into_arc_tensor(),
+            tensor1(&offsets).into_shape(&[1, dim]).unwrap().into_arc_tensor(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // reference features from reference.py, not from Kaldi (see the README there)
+    const DIR: &str = "test_cases/features";
+
+    fn samples() -> Tensor {
+        let wav = std::fs::read(format!("{}/input.wav", DIR)).unwrap();
+        // 16 bits mono PCM after a 44 bytes header
+        let samples: Vec<f32> =
+            wav[44..].chunks(2).map(|s| i16::from_le_bytes([s[0], s[1]]) as f32).collect();
+        tensor1(&samples)
+    }
+
+    fn reference(name: &str) -> Tensor {
+        let ark = std::fs::read_to_string(format!("{}/{}", DIR, name)).unwrap();
+        let rows = text_matrix(&ark).unwrap();
+        let data: Vec<f32> = rows.iter().flat_map(|row| row.iter().map(|x| *x as f32)).collect();
+        tensor1(&data).into_shape(&[rows.len(), rows[0].len()]).unwrap()
+    }
+
+    fn options(conf: &str, kind: FeatureKind) -> FeatureOptions {
+        let conf = std::fs::read_to_string(format!("{}/{}", DIR, conf)).unwrap();
+        FeatureOptions::parse(kind, &conf).unwrap()
+    }
+
+    fn run(options: &FeatureOptions) -> Tensor {
+        let model = options.model().unwrap().into_typed().unwrap().declutter().unwrap();
+        let output = SpecializingPlan::new(model).unwrap().run(tvec!(samples())).unwrap();
+        output[0].clone().into_tensor()
+    }
+
+    fn assert_close(found: &Tensor, expected: &Tensor) {
+        assert_eq!(found.shape(), expected.shape());
+        let found = found.as_slice::<f32>().unwrap();
+        let expected = expected.as_slice::<f32>().unwrap();
+        for (ix, (f, e)) in found.iter().zip(expected.iter()).enumerate() {
+            assert!((f - e).abs() <= 1e-2 + 1e-3 * e.abs(), "at {}: {} != {}", ix, f, e);
+        }
+    }
+
+    #[test]
+    fn parse_conf() {
+        let options = FeatureOptions::parse(
+            FeatureKind::Fbank,
+            "# comment\n--num-mel-bins=40 # bins\n--use-energy\n--window-type=hamming\n",
+        )
+        .unwrap();
+        assert_eq!(options.num_mel_bins, 40);
+        assert!(options.use_energy);
+        assert_eq!(options.window_type, "hamming");
+        assert_eq!(options.dim(), 41);
+        assert!(FeatureOptions::parse(FeatureKind::Mfcc, "--snip-edges=false").is_err());
+        assert!(FeatureOptions::parse(FeatureKind::Mfcc, "--no-such-option=1").is_err());
+        assert!(FeatureOptions::parse(FeatureKind::Mfcc, "--add-pitch=true").is_err());
+        assert!(FeatureOptions::parse(FeatureKind::Mfcc, "--add-pitch=false").is_ok());
+    }
+
+    #[test]
+    fn mfcc() {
+        let options = options("mfcc.conf", FeatureKind::Mfcc);
+        assert_close(&run(&options), &reference("mfcc.ark"));
+    }
+
+    #[test]
+    fn fbank() {
+        let options = options("fbank.conf", FeatureKind::Fbank);
+        assert_close(&run(&options), &reference("fbank.ark"));
+    }
+
+    #[test]
+    fn mfcc_cmvn() {
+        let mut options = options("mfcc.conf", FeatureKind::Mfcc);
+        let stats = std::fs::read_to_string(format!("{}/cmvn.stats", DIR)).unwrap();
+        options.cmvn = Some(Cmvn::parse(&stats, true).unwrap());
+        assert_close(&run(&options), &reference("mfcc_cmvn.ark"));
+    }
+
+    #[test]
+    fn pulsed_mfcc() {
+        use tract_pulse::internal::{PulsedModel, PulsedModelExt};
+        let options = options("mfcc.conf", FeatureKind::Mfcc);
+        let model = options.model().unwrap().into_typed().unwrap().declutter().unwrap();
+        let pulse = 2 * options.frame_shift();
+        let pulsed = PulsedModel::new(&model, pulse).unwrap();
+        let delay = pulsed.output_fact(0).unwrap().delay();
+        let plan = SimplePlan::new(pulsed.into_typed().unwrap().into_optimized().unwrap()).unwrap();
+        let mut state = SimpleState::new(&plan).unwrap();
+        let samples = samples();
+        let mut frames = vec![];
+        for chunk in samples.as_slice::<f32>().unwrap().chunks(pulse) {
+            if chunk.len() == pulse {
+                let output = state.run(tvec!(tensor1(chunk))).unwrap();
+                frames.extend(output[0].as_slice::<f32>().unwrap().iter().cloned());
+            }
+        }
+        let expected = reference("mfcc.ark");
+        let found = &frames[delay * options.dim()..];
+        let expected = &expected.as_slice::<f32>().unwrap()[..found.len()];
+        assert!(found.len() > 10 * options.dim());
+        let shape = [found.len() / options.dim(), options.dim()];
+        assert_close(
+            &tensor1(found).into_shape(&shape).unwrap(),
+            &tensor1(expected).into_shape(&shape).unwrap(),
+        );
+    }
+}
@@ -5,6 +5,8 @@ extern crate educe;
 #[macro_use]
 extern crate log;
 
+pub mod ark;
+pub mod features;
 pub mod model;
 mod ops;
 pub mod parser;
@@ -1,6 +1,7 @@
 use std::collections::BTreeMap;
 
 use tract_hir::internal::*;
+use tract_hir::ops::binary::IntoHir;
 
 #[derive(Clone, Debug)]
 pub struct KaldiProtoModel {
@@ -11,8 +12,7 @@ pub struct KaldiProtoModel {
 
 #[derive(Clone, Debug)]
 pub struct ConfigLines {
-    pub input_name: String,
-    pub input_dim: usize,
+    pub inputs: Vec<(String, usize)>,
     pub nodes: Vec<(String, NodeLine)>,
     pub outputs: Vec<OutputLine>,
 }
@@ -23,34 +23,140 @@ pub enum NodeLine {
     DimRange(DimRangeNode),
 }
 
+impl NodeLine {
+    pub fn input(&self) -> &GeneralDescriptor {
+        match self {
+            NodeLine::Component(line) => &line.input,
+            NodeLine::DimRange(line) => &line.input,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct OutputLine {
     pub output_alias: String,
     pub descriptor: GeneralDescriptor,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Index {
+    T,
+    X,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum GeneralDescriptor {
     Append(Vec<GeneralDescriptor>),
+    Const(f32, usize),
+    Failover(Box<GeneralDescriptor>, Box<GeneralDescriptor>),
     IfDefined(Box<GeneralDescriptor>),
     Name(String),
     Offset(Box<GeneralDescriptor>, isize),
+    ReplaceIndex(Box<GeneralDescriptor>, Index, isize),
+    Round(Box<GeneralDescriptor>, usize),
+    Scale(f32, Box<GeneralDescriptor>),
+    Sum(Box<GeneralDescriptor>, Box<GeneralDescriptor>),
+    Switch(Vec<GeneralDescriptor>),
+}
+
+/// Frames covered by the value of a node or a descriptor.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Span {
+    /// The first frame is at time `start`, and the value stops `end` frames before the streaming
+    /// input does.
+    Frames(isize, isize),
+    /// The value does not depend on time (constants, i-vectors): it is a single row.
+    Constant,
+    /// Recurrent memories fit the frames of their consumer.
+    Any,
+}
+
+impl Span {
+    /// Frames where all the spans are defined.
+    fn intersection(spans: impl Iterator<Item = Span>) -> Span {
+        let mut result = Span::Constant;
+        for span in spans {
+            result = match (result, span) {
+                (Span::Frames(s1, e1), Span::Frames(s2, e2)) => {
+                    Span::Frames(s1.max(s2), e1.max(e2))
+                }
+                (Span::Frames(s, e), _) | (_, Span::Frames(s, e)) => Span::Frames(s, e),
+                (Span::Any, _) | (_, Span::Any) => Span::Any,
+                _ => Span::Constant,
+            }
+        }
+        result
+    }
+
+    fn round_start(self, modulus: usize) -> Span {
+        if let Span::Frames(s, e) = self {
+            Span::Frames(
+                s + (modulus as isize - s.rem_euclid(modulus as isize)) % modulus as isize,
+                e,
+            )
+        } else {
+            self
+        }
+    }
+
+    /// Span of the output of a node reading its input at the given time offsets.
+    fn context(self, offsets: &[isize]) -> Span {
+        match self {
+            Span::Frames(s, e) => {
+                let min = offsets.iter().cloned().min().unwrap_or(0);
+                let max = offsets.iter().cloned().max().unwrap_or(0);
+                Span::Frames(s - min, e + max)
+            }
+            span => span,
+        }
+    }
+
+    fn len(&self) -> TractResult<TDim> {
+        if let Span::Frames(s, e) = self {
+            Ok(tract_pulse::internal::stream_dim() - TDim::from((s + e) as i64))
+        } else {
+            bail!("{:?} has no length", self)
+        }
+    }
 }
 
 impl GeneralDescriptor {
     pub fn inputs(&self) -> TVec<&str> {
+        let mut inputs = tvec!();
+        self.collect_inputs(false, &mut inputs);
+        inputs
+    }
+
+    /// Inputs used at more than one time index.
+    pub fn streaming_inputs(&self) -> TVec<&str> {
+        let mut inputs = tvec!();
+        self.collect_inputs(true, &mut inputs);
+        inputs
+    }
+
+    fn collect_inputs<'a>(&'a self, streaming_only: bool, inputs: &mut TVec<&'a str>) {
         match self {
-            GeneralDescriptor::Append(ref gds) => gds.iter().fold(tvec!(), |mut acc, gd| {
-                gd.inputs().iter().for_each(|i| {
-                    if !acc.contains(i) {
-                        acc.push(i)
-                    }
-                });
-                acc
-            }),
-            GeneralDescriptor::IfDefined(ref gd) => gd.inputs(),
-            GeneralDescriptor::Name(ref s) => tvec!(&**s),
-            GeneralDescriptor::Offset(ref gd, _) => gd.inputs(),
+            GeneralDescriptor::Name(ref s) => {
+                if !inputs.contains(&&**s) {
+                    inputs.push(s)
+                }
+            }
+            GeneralDescriptor::ReplaceIndex(_, Index::T, _) if streaming_only => (),
+            _ => self.children().iter().for_each(|gd| gd.collect_inputs(streaming_only, inputs)),
+        }
+    }
+
+    fn children(&self) -> TVec<&GeneralDescriptor> {
+        use GeneralDescriptor::*;
+        match self {
+            Append(ref gds) | Switch(ref gds) => gds.iter().collect(),
+            Sum(ref a, ref b) | Failover(ref a, ref b) => tvec!(&**a, &**b),
+            IfDefined(ref gd)
+            | Offset(ref gd, _)
+            | ReplaceIndex(ref gd, _, _)
+            | Round(ref gd, _)
+            | Scale(_, ref gd) => tvec!(&**gd),
+            Const(..) | Name(_) => tvec!(),
         }
     }
 
@@ -72,15 +178,53 @@ impl GeneralDescriptor {
                 return Some((offsets.len(), dilation as usize));
             }
         }
-        return None;
+        None
+    }
+
+    fn memory(&self) -> Option<(&str, isize)> {
+        if let GeneralDescriptor::IfDefined(ref o) = self {
+            if let GeneralDescriptor::Offset(ref n, o) = &**o {
+                if let GeneralDescriptor::Name(n) = &**n {
+                    return Some((n, *o));
+                }
+            }
+        }
+        None
+    }
+
+    pub fn span(&self, spans: &HashMap<String, Span>) -> Span {
+        use GeneralDescriptor::*;
+        match self {
+            Name(n) => spans.get(n).cloned().unwrap_or(Span::Any),
+            Append(gds) => Span::intersection(gds.iter().map(|gd| gd.span(spans))),
+            Sum(..) => Span::intersection(self.children().iter().map(|gd| gd.span(spans))),
+            Switch(gds) => {
+                Span::intersection(gds.iter().map(|gd| gd.span(spans))).round_start(gds.len())
+            }
+            Const(..) | ReplaceIndex(_, Index::T, _) => Span::Constant,
+            IfDefined(_) if self.memory().is_some() => Span::Any,
+            Failover(gd, _) | IfDefined(gd) | ReplaceIndex(gd, Index::X, _) | Scale(_, gd) => {
+                gd.span(spans)
+            }
+            // positive offsets crop the beginning of the input, negative ones shift its times
+            Offset(gd, o) => match gd.span(spans) {
+                Span::Frames(s, e) if *o > 0 => Span::Frames(s, e + o),
+                Span::Frames(s, e) => Span::Frames(s - o, e + o),
+                span => span,
+            },
+            Round(gd, modulus) => gd.span(spans).round_start(*modulus),
+        }
     }
 
+    /// Edges are recorded in `deferred` and added once all nodes exist, as the inputs of a node
+    /// must be connected in order.
     fn wire<'a>(
         &'a self,
         inlet: InletId,
         name: &str,
         model: &mut InferenceModel,
         deferred: &mut BTreeMap<InletId, String>,
+        spans: &HashMap<String, Span>,
         adjust_final_offset: Option<isize>,
     ) -> TractResult<()> {
         use GeneralDescriptor::*;
@@ -96,32 +240,92 @@ impl GeneralDescriptor {
                     expand(tract_hir::ops::array::Concat::new(1)),
                     tvec!(InferenceFact::default()),
                 )?;
-                model.add_edge(OutletId::new(id, 0), inlet)?;
+                deferred.insert(inlet, name.to_string());
+                let span = self.span(spans);
                 for (ix, appendee) in appendees.iter().enumerate() {
                     let name = format!("{}-{}", name, ix);
-                    appendee.wire(
+                    appendee.wire_aligned(
+                        span,
+                        InletId::new(id, ix),
+                        &*name,
+                        model,
+                        deferred,
+                        spans,
+                        adjust_final_offset,
+                    )?;
+                }
+                return Ok(());
+            }
+            &Sum(a, b) => {
+                let name = format!("{}.Sum", name);
+                let id = model.add_node(
+                    &*name,
+                    tract_hir::ops::math::Add.into_hir(),
+                    tvec!(InferenceFact::default()),
+                )?;
+                deferred.insert(inlet, name.to_string());
+                let span = self.span(spans);
+                for (ix, gd) in [a, b].iter().enumerate() {
+                    let name = format!("{}-{}", name, ix);
+                    gd.wire_aligned(
+                        span,
                         InletId::new(id, ix),
                         &*name,
                         model,
                         deferred,
+                        spans,
                         adjust_final_offset,
                     )?;
                 }
                 return Ok(());
             }
-            &IfDefined(ref o) => {
-                if let &Offset(ref n, ref o) = &**o {
-                    if let Name(n) = &**n {
-                        let name = format!("{}.memory", name);
-                        model.add_node(
+            &Switch(gds) => {
+                let name = format!("{}.Switch", name);
+                let id = model.add_node(
+                    &*name,
+                    crate::ops::descriptor::Switch,
+                    tvec!(InferenceFact::default()),
+                )?;
+                deferred.insert(inlet, name.to_string());
+                let span = self.span(spans);
+                if let Span::Frames(..) = span {
+                    for (ix, gd) in gds.iter().enumerate() {
+                        let name = format!("{}-{}", name, ix);
+                        gd.wire_aligned(
+                            span,
+                            InletId::new(id, ix),
                             &*name,
-                            crate::ops::memory::Memory::new(n.to_string(), *o),
-                            tvec!(InferenceFact::default()),
+                            model,
+                            deferred,
+                            spans,
+                            adjust_final_offset,
                         )?;
-                        deferred.insert(inlet, name);
-                        return Ok(());
                     }
+                    return Ok(());
+                }
+            }
+            &Const(value, dim) => {
+                let value = tract_ndarray::Array2::from_elem((1, *dim), *value);
+                let name = format!("{}.Const", name);
+                model.add_const(&*name, value)?;
+                deferred.insert(inlet, name);
+                return Ok(());
+            }
+            &Failover(gd, _) => {
+                return gd.wire(inlet, name, model, deferred, spans, adjust_final_offset);
+            }
+            IfDefined(o) => {
+                if let Some((n, o)) = self.memory() {
+                    let name = format!("{}.memory", name);
+                    model.add_node(
+                        &*name,
+                        crate::ops::memory::Memory::new(n.to_string(), o),
+                        tvec!(InferenceFact::default()),
+                    )?;
+                    deferred.insert(inlet, name);
+                    return Ok(());
                 }
+                return o.wire(inlet, name, model, deferred, spans, adjust_final_offset);
             }
             &Offset(ref n, o) if *o > 0 => {
                 let name = format!("{}-Delay", name);
@@ -134,14 +338,118 @@ impl GeneralDescriptor {
                     expand(tract_hir::ops::array::Crop::new(0, crop as usize, 0)),
                     tvec!(InferenceFact::default()),
                 )?;
-                model.add_edge(OutletId::new(id, 0), inlet)?;
-                n.wire(InletId::new(id, 0), &*name, model, deferred, adjust_final_offset)?;
+                deferred.insert(inlet, name.to_string());
+                n.wire(InletId::new(id, 0), &*name, model, deferred, spans, adjust_final_offset)?;
+                return Ok(());
+            }
+            Offset(n, _) => {
+                return n.wire(inlet, name, model, deferred, spans, adjust_final_offset);
+            }
+            &ReplaceIndex(ref gd, Index::X, 0) => {
+                return gd.wire(inlet, name, model, deferred, spans, adjust_final_offset);
+            }
+            &ReplaceIndex(ref gd, Index::T, t) => match gd.span(spans) {
+                Span::Constant => {
+                    return gd.wire(inlet, name, model, deferred, spans, adjust_final_offset)
+                }
+                Span::Frames(s, _) if *t >= s => {
+                    let name = format!("{}.ReplaceIndex", name);
+                    let frame = (t - s) as usize;
+                    let id = model.add_node(
+                        &*name,
+                        tract_hir::ops::array::Slice::new(0, frame, frame + 1),
+                        tvec!(InferenceFact::default()),
+                    )?;
+                    deferred.insert(inlet, name.to_string());
+                    gd.wire(
+                        InletId::new(id, 0),
+                        &*name,
+                        model,
+                        deferred,
+                        spans,
+                        adjust_final_offset,
+                    )?;
+                    return Ok(());
+                }
+                _ => (),
+            },
+            &Round(ref gd, modulus) => match gd.span(spans) {
+                Span::Constant => {
+                    return gd.wire(inlet, name, model, deferred, spans, adjust_final_offset)
+                }
+                span @ Span::Frames(..) => {
+                    let name = format!("{}.Round", name);
+                    let id = model.add_node(
+                        &*name,
+                        crate::ops::descriptor::Round::new(*modulus),
+                        tvec!(InferenceFact::default()),
+                    )?;
+                    deferred.insert(inlet, name.to_string());
+                    gd.wire_aligned(
+                        span.round_start(*modulus),
+                        InletId::new(id, 0),
+                        &*name,
+                        model,
+                        deferred,
+                        spans,
+                        adjust_final_offset,
+                    )?;
+                    return Ok(());
+                }
+                _ => (),
+            },
+            &Scale(scale, ref gd) => {
+                let name = format!("{}.Scale", name);
+                let id = model.add_node(
+                    &*name,
+                    tract_hir::ops::math::Mul.into_hir(),
+                    tvec!(InferenceFact::default()),
+                )?;
+                deferred.insert(inlet, name.to_string());
+                let factor = format!("{}.factor", name);
+                model.add_const(&*factor, tensor0(*scale))?;
+                deferred.insert(InletId::new(id, 1), factor);
+                gd.wire(InletId::new(id, 0), &*name, model, deferred, spans, adjust_final_offset)?;
                 return Ok(());
             }
             _ => (),
         }
         bail!("Unhandled input descriptor: {:?}", self)
     }
+
+    /// Wire the descriptor cropped or broadcast to the frames of `span`.
+    fn wire_aligned(
+        &self,
+        span: Span,
+        inlet: InletId,
+        name: &str,
+        model: &mut InferenceModel,
+        deferred: &mut BTreeMap<InletId, String>,
+        spans: &HashMap<String, Span>,
+        adjust_final_offset: Option<isize>,
+    ) -> TractResult<()> {
+        match (self.span(spans), span) {
+            (Span::Frames(s, e), Span::Frames(ts, te)) if (s, e) != (ts, te) => {
+                let name = format!("{}.Crop", name);
+                let crop =
+                    tract_hir::ops::array::Crop::new(0, (ts - s) as usize, (te - e) as usize);
+                let id = model.add_node(&*name, expand(crop), tvec!(InferenceFact::default()))?;
+                deferred.insert(inlet, name.to_string());
+                self.wire(InletId::new(id, 0), &*name, model, deferred, spans, adjust_final_offset)
+            }
+            (Span::Constant, Span::Frames(..)) => {
+                let name = format!("{}.Broadcast", name);
+                let id = model.add_node(
+                    &*name,
+                    expand(crate::ops::descriptor::BroadcastTime::new(span.len()?)),
+                    tvec!(InferenceFact::default()),
+                )?;
+                deferred.insert(inlet, name.to_string());
+                self.wire(InletId::new(id, 0), &*name, model, deferred, spans, adjust_final_offset)
+            }
+            _ => self.wire(inlet, name, model, deferred, spans, adjust_final_offset),
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -167,6 +475,21 @@ pub struct ParsingContext<'a> {
     pub proto_model: &'a KaldiProtoModel,
 }
 
+impl<'a> ParsingContext<'a> {
+    /// Component used by a component node.
+    pub fn component(&self, node: &str) -> TractResult<&'a Component> {
+        let line = self.proto_model.config_lines.nodes.iter().find(|l| l.0 == node);
+        if let Some((_, NodeLine::Component(line))) = line {
+            self.proto_model
+                .components
+                .get(&line.component)
+                .with_context(|| format!("Could not find component {}", line.component))
+        } else {
+            bail!("Could not find component node {}", node)
+        }
+    }
+}
+
 #[derive(Clone, Default)]
 pub struct KaldiOpRegister(
     pub HashMap<String, fn(&ParsingContext, node: &str) -> TractResult<Box<dyn InferenceOp>>>,
@@ -199,15 +522,30 @@ impl Framework<KaldiProtoModel, InferenceModel> for Kaldi {
         let ctx = ParsingContext { proto_model };
         let mut model = InferenceModel::default();
         let s = tract_pulse::internal::stream_dim();
-        model.add_source(
-            proto_model.config_lines.input_name.clone(),
-            InferenceFact::dt_shape(
-                f32::datum_type(),
-                shapefactoid!(s, (proto_model.config_lines.input_dim)),
-            ),
-        )?;
+        let mut spans: HashMap<String, Span> = HashMap::default();
+        for (name, dim) in &proto_model.config_lines.inputs {
+            // inputs only read at a fixed time index (like i-vectors) are single rows
+            let streaming = proto_model
+                .config_lines
+                .nodes
+                .iter()
+                .map(|(_, node)| node.input())
+                .chain(proto_model.config_lines.outputs.iter().map(|o| &o.descriptor))
+                .any(|gd| gd.streaming_inputs().contains(&&**name));
+            let (rows, span) = if streaming {
+                (s.clone(), Span::Frames(0, 0))
+            } else {
+                (1.to_dim(), Span::Constant)
+            };
+            model.add_source(
+                name.clone(),
+                InferenceFact::dt_shape(f32::datum_type(), shapefactoid!(rows, (*dim))),
+            )?;
+            spans.insert(name.clone(), span);
+        }
         let mut inputs_to_wire: BTreeMap<InletId, String> = Default::default();
         for (name, node) in &proto_model.config_lines.nodes {
+            let input_span = node.input().span(&spans);
             match node {
                 NodeLine::Component(line) => {
                     let component = &proto_model.components[&line.component];
@@ -220,14 +558,26 @@ impl Framework<KaldiProtoModel, InferenceModel> for Kaldi {
                             op,
                             tvec!(InferenceFact::default()),
                         )?;
-                        inputs_to_wire
-                            .insert(InletId::new(id, 0), line.input.inputs()[0].to_owned());
+                        let input = line.input.inputs()[0];
+                        inputs_to_wire.insert(InletId::new(id, 0), input.to_owned());
+                        let offsets: Vec<isize> = match &line.input {
+                            GeneralDescriptor::Append(appendees) => appendees
+                                .iter()
+                                .map(|app| match app {
+                                    GeneralDescriptor::Offset(_, o) => *o,
+                                    _ => 0,
+                                })
+                                .collect(),
+                            _ => vec![0],
+                        };
+                        let span = spans.get(input).cloned().unwrap_or(Span::Any);
+                        spans.insert(name.to_string(), span.context(&offsets));
                     } else {
                         let op = match self.op_register.0.get(&*component.klass) {
                             Some(builder) => (builder)(&ctx, name)?,
                             None => Box::new(tract_hir::ops::unimpl::UnimplementedOp::new(
                                 1,
-                                component.klass.to_string(),
+                                &component.klass,
                                 format!("{:?}", line),
                             )),
                         };
@@ -241,8 +591,32 @@ impl Framework<KaldiProtoModel, InferenceModel> for Kaldi {
                             name,
                             &mut model,
                             &mut inputs_to_wire,
+                            &spans,
                             None,
-                        )?
+                        )?;
+                        let offsets: Vec<isize> = match &*component.klass {
+                            "TdnnComponent" => component
+                                .attributes
+                                .get("TimeOffsets")
+                                .context("missing attribute TimeOffsets")?
+                                .cast_to::<i32>()?
+                                .as_slice::<i32>()?
+                                .iter()
+                                .map(|o| *o as isize)
+                                .collect(),
+                            "TimeHeightConvolutionComponent" => component
+                                .attributes
+                                .get("Offsets")
+                                .context("missing attribute Offsets")?
+                                .cast_to::<i32>()?
+                                .as_slice::<i32>()?
+                                .iter()
+                                .step_by(2)
+                                .map(|o| *o as isize)
+                                .collect(),
+                            _ => vec![0],
+                        };
+                        spans.insert(name.to_string(), input_span.context(&offsets));
                     }
                 }
                 NodeLine::DimRange(line) => {
@@ -258,8 +632,10 @@ impl Framework<KaldiProtoModel, InferenceModel> for Kaldi {
                         name,
                         &mut model,
                         &mut inputs_to_wire,
+                        &spans,
                         None,
-                    )?
+                    )?;
+                    spans.insert(name.to_string(), input_span);
                 }
             }
         }
@@ -267,7 +643,7 @@ impl Framework<KaldiProtoModel, InferenceModel> for Kaldi {
         for o in &proto_model.config_lines.outputs {
             let output = model.add_node(
                 &*o.output_alias,
-                tract_hir::ops::identity::Identity::default(),
+                tract_hir::ops::identity::Identity,
                 tvec!(InferenceFact::default()),
             )?;
             o.descriptor.wire(
@@ -275,6 +651,7 @@ impl Framework<KaldiProtoModel, InferenceModel> for Kaldi {
                 "output",
                 &mut model,
                 &mut inputs_to_wire,
+                &spans,
                 Some(proto_model.adjust_final_offset),
             )?;
             outputs.push(OutletId::new(output, 0));
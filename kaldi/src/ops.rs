@@ -11,23 +11,37 @@ macro_rules! op_kaldi {
 }
 
 pub(crate) mod affine;
+pub(crate) mod descriptor;
+mod elementwise_product;
+pub(crate) mod features;
 pub(crate) mod lstm_nonlin;
 pub(crate) mod memory;
 mod renorm;
+pub(crate) mod scale_offset;
+mod time_height_conv;
 
-pub const AFFINE: &'static [&'static str] =
-    &["FixedAffineComponent", "NaturalGradientAffineComponent"];
+pub const AFFINE: &[&str] =
+    &["FixedAffineComponent", "NaturalGradientAffineComponent", "LinearComponent"];
 
 pub fn register_all_ops(reg: &mut KaldiOpRegister) {
     for affine in AFFINE {
         reg.insert(affine, affine::affine_component);
     }
     reg.insert("BackpropTruncationComponent", |_, _| {
-        Ok(Box::new(tract_hir::ops::identity::Identity::default()))
+        Ok(Box::new(tract_hir::ops::identity::Identity))
     });
     reg.insert("NormalizeComponent", renorm::renorm);
     reg.insert("LstmNonlinearityComponent", lstm_nonlin::lstm_nonlin);
     reg.insert("RectifiedLinearComponent", |_, _| {
         Ok(expand(tract_hir::ops::activations::Clip::new(Some(0.0), None)))
     });
+    reg.insert("SigmoidComponent", |_, _| Ok(Box::new(tract_hir::ops::nn::sigmoid())));
+    reg.insert("TanhComponent", |_, _| Ok(Box::new(tract_hir::ops::math::tanh())));
+    reg.insert("GeneralDropoutComponent", |_, _| Ok(Box::new(tract_hir::ops::identity::Identity)));
+    reg.insert("NoOpComponent", |_, _| Ok(Box::new(tract_hir::ops::identity::Identity)));
+    reg.insert("TdnnComponent", affine::tdnn_component);
+    reg.insert("BatchNormComponent", scale_offset::batch_norm);
+    reg.insert("ScaleAndOffsetComponent", scale_offset::scale_and_offset);
+    reg.insert("ElementwiseProductComponent", elementwise_product::elementwise_product);
+    reg.insert("TimeHeightConvolutionComponent", time_height_conv::time_height_convolution);
 }
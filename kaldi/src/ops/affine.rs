@@ -1,7 +1,6 @@
 use tract_hir::internal::*;
 
-use crate::model::NodeLine;
-use crate::model::ParsingContext;
+use crate::model::{Component, NodeLine, ParsingContext};
 
 pub fn affine_component(ctx: &ParsingContext, name: &str) -> TractResult<Box<dyn InferenceOp>> {
     let node = &ctx.proto_model.config_lines.nodes.iter().find(|l| l.0 == name);
@@ -12,19 +11,49 @@ pub fn affine_component(ctx: &ParsingContext, name: &str) -> TractResult<Box<dyn
     };
     let component = &ctx.proto_model.components[&line.component];
     let (kernel_len, dilation) = line.input.as_conv_shape_dilation().unwrap_or((1, 1));
-    let kernel: &Tensor =
-        component.attributes.get("LinearParams").context("missing attribute LinearParams")?;
-    let bias = component.attributes.get("BiasParams").context("missing attribute BiasParams")?;
+    affine(component, kernel_len, dilation)
+}
+
+/// TdnnComponent: an affine transform of the input at a regularly spaced set of time offsets.
+pub fn tdnn_component(ctx: &ParsingContext, name: &str) -> TractResult<Box<dyn InferenceOp>> {
+    let component = ctx.component(name)?;
+    let offsets =
+        component.attributes.get("TimeOffsets").context("missing attribute TimeOffsets")?;
+    let offsets = offsets.cast_to::<i32>()?;
+    let offsets = offsets.as_slice::<i32>()?;
+    let dilation = if offsets.len() > 1 { offsets[1] - offsets[0] } else { 1 };
+    if offsets.len() == 0 || dilation <= 0 || offsets.windows(2).any(|w| w[1] - w[0] != dilation) {
+        bail!("TdnnComponent {} has irregular time offsets {:?}", name, offsets)
+    }
+    affine(component, offsets.len(), dilation as usize)
+}
+
+fn affine(
+    component: &Component,
+    kernel_len: usize,
+    dilation: usize,
+) -> TractResult<Box<dyn InferenceOp>> {
+    // LinearComponent calls them Params and has no bias, TdnnComponent may have an empty one
+    let kernel: &Tensor = component
+        .attributes
+        .get("LinearParams")
+        .or_else(|| component.attributes.get("Params"))
+        .context("missing attribute LinearParams")?;
+    let output_dim = kernel.shape()[0];
+    let bias = match component.attributes.get("BiasParams") {
+        Some(bias) if bias.len() > 0 => Arc::clone(bias),
+        _ => tensor1(&*vec![0f32; output_dim]).into_arc_tensor(),
+    };
     // O•TI -> t -> TI•O -> T•I•O = HWIO
     let o_ti = kernel.to_array_view::<f32>()?;
-    let t_i_o_shape = (kernel_len, kernel.len() / kernel_len / bias.len(), bias.len());
+    let t_i_o_shape = (kernel_len, kernel.len() / kernel_len / output_dim, output_dim);
     let t_i_o =
         tract_ndarray::Array::from_shape_vec(t_i_o_shape, o_ti.t().iter().cloned().collect())?;
     Ok(expand(Affine {
         kernel_len,
         dilation,
         linear_params: t_i_o.into_arc_tensor(),
-        bias_params: Arc::clone(bias),
+        bias_params: bias,
     }))
 }
 
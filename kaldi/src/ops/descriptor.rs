@@ -0,0 +1,183 @@
+use tract_hir::internal::*;
+use tract_hir::tract_core::ops::array::MultiBroadcastTo;
+
+/// Repeats a single row (a time-independent value, like an i-vector) over `len` frames.
+#[derive(Clone, Debug, new, Hash)]
+pub struct BroadcastTime {
+    len: TDim,
+}
+
+impl_dyn_hash!(BroadcastTime);
+
+impl Expansion for BroadcastTime {
+    fn name(&self) -> Cow<str> {
+        "BroadcastTime".into()
+    }
+
+    op_kaldi!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 1)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].rank, 2)?;
+        s.equals(&outputs[0].rank, 2)?;
+        s.equals(&inputs[0].shape[0], 1.to_dim())?;
+        s.equals(&outputs[0].shape[0], self.len.clone())?;
+        s.equals(&inputs[0].shape[1], &outputs[0].shape[1])?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let dim = model.outlet_fact(inputs[0])?.shape[1].clone();
+        model.wire_node(prefix, MultiBroadcastTo::new(tvec!(self.len.clone(), dim)), inputs)
+    }
+}
+
+/// Round(descriptor, modulus): frame t reads the input at frame `t - t % modulus`.
+#[derive(Clone, Debug, new, Hash)]
+pub struct Round {
+    modulus: usize,
+}
+
+impl_dyn_hash!(Round);
+
+impl Op for Round {
+    fn name(&self) -> Cow<str> {
+        "Round".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("modulus: {}", self.modulus)])
+    }
+
+    op_kaldi!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for Round {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = inputs[0].to_array_view::<f32>()?;
+        let output = tract_ndarray::Array::from_shape_fn(input.raw_dim(), |ix| {
+            let mut ix = ix.clone();
+            ix[0] -= ix[0] % self.modulus;
+            input[ix]
+        });
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl TypedOp for Round {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(tvec!(inputs[0].clone()))
+    }
+}
+
+impl InferenceRulesOp for Round {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 1)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[0].datum_type, f32::datum_type())?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        Ok(())
+    }
+
+    as_op!();
+    to_typed!();
+}
+
+/// Switch(descriptors...): frame t reads the input number `t % n`.
+#[derive(Clone, Debug, Hash)]
+pub struct Switch;
+
+impl_dyn_hash!(Switch);
+
+impl Op for Switch {
+    fn name(&self) -> Cow<str> {
+        "Switch".into()
+    }
+
+    op_kaldi!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for Switch {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let views =
+            inputs.iter().map(|i| i.to_array_view::<f32>()).collect::<TractResult<Vec<_>>>()?;
+        let output = tract_ndarray::Array::from_shape_fn(views[0].raw_dim(), |ix| {
+            views[ix[0] % views.len()][&ix]
+        });
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl TypedOp for Switch {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(tvec!(inputs[0].clone()))
+    }
+}
+
+impl InferenceRulesOp for Switch {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_output_arity(&outputs, 1)?;
+        s.equals(&outputs[0].datum_type, f32::datum_type())?;
+        for input in inputs {
+            s.equals(&input.datum_type, &outputs[0].datum_type)?;
+            s.equals(&input.shape, &outputs[0].shape)?;
+        }
+        Ok(())
+    }
+
+    as_op!();
+    to_typed!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_and_switch() -> TractResult<()> {
+        let input = tensor2(&[[0f32], [1.], [2.], [3.], [4.]]).into_arc_tensor();
+        let rounded = Round::new(2).eval(tvec!(input.clone()))?;
+        assert_eq!(*rounded[0], tensor2(&[[0f32], [0.], [2.], [2.], [4.]]));
+        let negated = tensor2(&[[0f32], [-1.], [-2.], [-3.], [-4.]]).into_arc_tensor();
+        let switched = Switch.eval(tvec!(input, negated))?;
+        assert_eq!(*switched[0], tensor2(&[[0f32], [-1.], [2.], [-3.], [4.]]));
+        Ok(())
+    }
+}
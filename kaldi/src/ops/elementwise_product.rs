@@ -0,0 +1,87 @@
+use tract_hir::internal::*;
+
+use crate::model::ParsingContext;
+
+pub fn elementwise_product(ctx: &ParsingContext, name: &str) -> TractResult<Box<dyn InferenceOp>> {
+    let component = ctx.component(name)?;
+    let input_dim = component
+        .attributes
+        .get("InputDim")
+        .context("missing attribute InputDim")?
+        .cast_to_scalar::<i64>()? as usize;
+    let output_dim = component
+        .attributes
+        .get("OutputDim")
+        .context("missing attribute OutputDim")?
+        .cast_to_scalar::<i64>()? as usize;
+    if output_dim == 0 || !input_dim.is_multiple_of(output_dim) {
+        bail!("ElementwiseProduct input dim {} is not a multiple of {}", input_dim, output_dim)
+    }
+    Ok(expand(ElementwiseProduct::new(input_dim, output_dim)))
+}
+
+/// Multiplies together the `input_dim / output_dim` consecutive blocks of the input.
+#[derive(Clone, Debug, new, Hash)]
+struct ElementwiseProduct {
+    input_dim: usize,
+    output_dim: usize,
+}
+
+impl_dyn_hash!(ElementwiseProduct);
+
+impl Expansion for ElementwiseProduct {
+    fn name(&self) -> std::borrow::Cow<str> {
+        "ElementwiseProduct".into()
+    }
+
+    op_kaldi!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 1)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].rank, 2)?;
+        s.equals(&outputs[0].rank, 2)?;
+        s.equals(&inputs[0].shape[0], &outputs[0].shape[0])?;
+        s.equals(&inputs[0].shape[1], self.input_dim.to_dim())?;
+        s.equals(&outputs[0].shape[1], self.output_dim.to_dim())?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        use tract_hir::tract_core::ops::array::Slice;
+        let blocks = self.input_dim / self.output_dim;
+        let slice = |model: &mut TypedModel, ix: usize| {
+            model.wire_node(
+                format!("{}.slice-{}", prefix, ix),
+                Slice::new(1, ix * self.output_dim, (ix + 1) * self.output_dim),
+                inputs,
+            )
+        };
+        let mut wire = slice(model, 0)?;
+        for ix in 1..blocks {
+            let block = slice(model, ix)?;
+            let name = if ix + 1 == blocks {
+                prefix.to_string()
+            } else {
+                format!("{}.mul-{}", prefix, ix)
+            };
+            wire = model.wire_node(
+                name,
+                tract_hir::ops::math::mul::bin_typed(),
+                &[wire[0], block[0]],
+            )?;
+        }
+        Ok(wire)
+    }
+}
@@ -0,0 +1,309 @@
+use tract_hir::internal::*;
+use tract_hir::tract_core::ops::cnn::{ConvUnary, KernelFormat, PaddingSpec, PoolSpec};
+use tract_hir::tract_core::ops::matmul::MatMulUnary;
+use tract_hir::tract_core::ops::nn::{DataFormat, Reduce, Reducer};
+
+/// Cuts a signal in overlapping frames of `length` samples, every `shift` samples (Kaldi
+/// `snip-edges` framing).
+///
+/// Lowered to a strided convolution with an identity kernel, so it pulsifies as long as the pulse
+/// is a multiple of `shift`.
+#[derive(Clone, Debug, new, Hash)]
+pub struct Framing {
+    length: usize,
+    shift: usize,
+}
+
+impl_dyn_hash!(Framing);
+
+impl Expansion for Framing {
+    fn name(&self) -> Cow<str> {
+        "Framing".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("length: {} shift: {}", self.length, self.shift)])
+    }
+
+    op_kaldi!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 1)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[0].datum_type, f32::datum_type())?;
+        s.equals(&outputs[0].datum_type, f32::datum_type())?;
+        s.equals(&inputs[0].rank, 1)?;
+        s.equals(&outputs[0].rank, 2)?;
+        s.equals(&outputs[0].shape[1], self.length.to_dim())?;
+        s.given(&inputs[0].shape[0], move |s, samples| {
+            let frames = (samples - (self.length - 1).to_dim()).div_ceil(self.shift as u64);
+            s.equals(&outputs[0].shape[0], frames)
+        })?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let wire = model.wire_node(format!("{}.channel", prefix), AxisOp::Add(1), inputs)?;
+        let kernel =
+            tract_ndarray::Array3::from_shape_fn((self.length, 1, self.length), |(t, _, o)| {
+                if t == o {
+                    1f32
+                } else {
+                    0f32
+                }
+            });
+        model.wire_node(
+            prefix,
+            ConvUnary {
+                pool_spec: PoolSpec::new(
+                    DataFormat::HWC,
+                    tvec!(self.length),
+                    PaddingSpec::Valid,
+                    None,
+                    Some(tvec!(self.shift)),
+                    Some(self.length),
+                ),
+                kernel_fmt: KernelFormat::HWIO,
+                kernel: kernel.into_arc_tensor(),
+                group: 1,
+                bias: None,
+                q_params: None,
+            },
+            &wire,
+        )
+    }
+}
+
+/// Linear transform of each frame (a row of the input) by a constant matrix.
+#[derive(Clone, Debug, new, Hash)]
+pub struct FrameTransform {
+    matrix: Arc<Tensor>,
+}
+
+impl_dyn_hash!(FrameTransform);
+
+impl Expansion for FrameTransform {
+    fn name(&self) -> Cow<str> {
+        "FrameTransform".into()
+    }
+
+    op_kaldi!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 1)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[0].datum_type, f32::datum_type())?;
+        s.equals(&outputs[0].datum_type, f32::datum_type())?;
+        s.equals(&inputs[0].rank, 2)?;
+        s.equals(&outputs[0].rank, 2)?;
+        s.equals(&inputs[0].shape[0], &outputs[0].shape[0])?;
+        s.equals(&inputs[0].shape[1], self.matrix.shape()[0].to_dim())?;
+        s.equals(&outputs[0].shape[1], self.matrix.shape()[1].to_dim())?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        // x.M = (Mt.xt)t
+        model.wire_node(prefix, MatMulUnary::new(self.matrix.clone(), true, true, true), inputs)
+    }
+}
+
+/// Power spectrum of each frame, zero-padded to `padded` samples: `padded / 2 + 1` bins.
+///
+/// This is a dense real DFT, not an FFT: two products by `[length, padded / 2 + 1]` cosine and
+/// sine matrices, so O(length × padded) per frame. They run on the matmul kernels and pulsify
+/// like any other MatMulUnary.
+#[derive(Clone, Debug, new, Hash)]
+pub struct DftPowerSpectrum {
+    length: usize,
+    padded: usize,
+}
+
+impl_dyn_hash!(DftPowerSpectrum);
+
+impl Expansion for DftPowerSpectrum {
+    fn name(&self) -> Cow<str> {
+        "DftPowerSpectrum".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("padded to: {}", self.padded)])
+    }
+
+    op_kaldi!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 1)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[0].datum_type, f32::datum_type())?;
+        s.equals(&outputs[0].datum_type, f32::datum_type())?;
+        s.equals(&inputs[0].rank, 2)?;
+        s.equals(&outputs[0].rank, 2)?;
+        s.equals(&inputs[0].shape[0], &outputs[0].shape[0])?;
+        s.equals(&inputs[0].shape[1], self.length.to_dim())?;
+        s.equals(&outputs[0].shape[1], (self.padded / 2 + 1).to_dim())?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        // the padding zeros contribute nothing: only the first `length` rows are needed
+        let bins = self.padded / 2 + 1;
+        let dft = |f: fn(f64) -> f64| {
+            tract_ndarray::Array2::from_shape_fn((self.length, bins), |(n, k)| {
+                f(2.0 * std::f64::consts::PI * (k * n % self.padded) as f64 / self.padded as f64)
+                    as f32
+            })
+            .into_arc_tensor()
+        };
+        let mut squares = tvec!();
+        for (name, f) in &[("real", f64::cos as fn(f64) -> f64), ("imag", f64::sin)] {
+            let wire = model.wire_node(
+                format!("{}.{}", prefix, name),
+                MatMulUnary::new(dft(*f), true, true, true),
+                inputs,
+            )?;
+            let wire = model.wire_node(
+                format!("{}.{}.square", prefix, name),
+                tract_hir::ops::math::square(),
+                &wire,
+            )?;
+            squares.push(wire[0]);
+        }
+        model.wire_node(prefix, tract_hir::ops::math::add::bin_typed(), &squares)
+    }
+}
+
+/// Logarithm of the energy of each frame, floored: `[frames, length] -> [frames, 1]`.
+#[derive(Clone, Debug, new, Educe)]
+#[educe(Hash)]
+pub struct LogEnergy {
+    #[educe(Hash(method = "hash_f32"))]
+    floor: f32,
+}
+
+impl_dyn_hash!(LogEnergy);
+
+impl Expansion for LogEnergy {
+    fn name(&self) -> Cow<str> {
+        "LogEnergy".into()
+    }
+
+    op_kaldi!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 1)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[0].datum_type, f32::datum_type())?;
+        s.equals(&outputs[0].datum_type, f32::datum_type())?;
+        s.equals(&inputs[0].rank, 2)?;
+        s.equals(&outputs[0].rank, 2)?;
+        s.equals(&inputs[0].shape[0], &outputs[0].shape[0])?;
+        s.equals(&outputs[0].shape[1], 1.to_dim())?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let wire = model.wire_node(
+            format!("{}.square", prefix),
+            tract_hir::ops::math::square(),
+            inputs,
+        )?;
+        let wire = model.wire_node(
+            format!("{}.sum", prefix),
+            Reduce::new(tvec!(1), Reducer::Sum),
+            &wire,
+        )?;
+        FlooredLog::new(self.floor).wire(prefix, model, &wire)
+    }
+}
+
+/// Natural logarithm of `max(x, floor)`.
+#[derive(Clone, Debug, new, Educe)]
+#[educe(Hash)]
+pub struct FlooredLog {
+    #[educe(Hash(method = "hash_f32"))]
+    floor: f32,
+}
+
+impl_dyn_hash!(FlooredLog);
+
+impl Expansion for FlooredLog {
+    fn name(&self) -> Cow<str> {
+        "FlooredLog".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("floor: {}", self.floor)])
+    }
+
+    op_kaldi!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 1)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[0].datum_type, f32::datum_type())?;
+        s.equals(&outputs[0].datum_type, f32::datum_type())?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let floor = tensor2(&[[self.floor]]).into_arc_tensor();
+        let wire = model.wire_node(
+            format!("{}.floor", prefix),
+            tract_hir::ops::math::max::unary(floor),
+            inputs,
+        )?;
+        model.wire_node(prefix, tract_hir::ops::math::ln(), &wire)
+    }
+}
@@ -0,0 +1,82 @@
+use tract_hir::internal::*;
+
+use crate::model::{Component, ParsingContext};
+
+fn attribute<'a>(component: &'a Component, name: &str) -> TractResult<&'a Arc<Tensor>> {
+    component.attributes.get(name).with_context(|| format!("missing attribute {}", name))
+}
+
+/// Repeat a per-block vector over the whole dimension, as a [1, dim] tensor.
+fn tile(block: &[f32], dim: usize) -> TractResult<Arc<Tensor>> {
+    if block.len() == 0 || !dim.is_multiple_of(block.len()) {
+        bail!("Dimension {} is not a multiple of block dimension {}", dim, block.len())
+    }
+    let data: Vec<f32> = block.iter().cycle().take(dim).cloned().collect();
+    Ok(tensor1(&*data).into_shape(&[1, dim])?.into_arc_tensor())
+}
+
+pub fn batch_norm(ctx: &ParsingContext, name: &str) -> TractResult<Box<dyn InferenceOp>> {
+    let component = ctx.component(name)?;
+    let dim = attribute(component, "Dim")?.cast_to_scalar::<i64>()? as usize;
+    let epsilon = *attribute(component, "Epsilon")?.to_scalar::<f32>()?;
+    let target_rms = *attribute(component, "TargetRms")?.to_scalar::<f32>()?;
+    let mean = attribute(component, "StatsMean")?.as_slice::<f32>()?;
+    let var = attribute(component, "StatsVar")?.as_slice::<f32>()?;
+    // y = (x - mean) * target_rms / sqrt(var + epsilon)
+    let scales: Vec<f32> = var.iter().map(|v| target_rms / (v + epsilon).sqrt()).collect();
+    let offsets: Vec<f32> = mean.iter().zip(scales.iter()).map(|(m, s)| -m * s).collect();
+    Ok(expand(ScaleAndOffset::new(tile(&scales, dim)?, tile(&offsets, dim)?)))
+}
+
+pub fn scale_and_offset(ctx: &ParsingContext, name: &str) -> TractResult<Box<dyn InferenceOp>> {
+    let component = ctx.component(name)?;
+    let dim = attribute(component, "Dim")?.cast_to_scalar::<i64>()? as usize;
+    let scales = attribute(component, "Scales")?.as_slice::<f32>()?;
+    let offsets = attribute(component, "Offsets")?.as_slice::<f32>()?;
+    Ok(expand(ScaleAndOffset::new(tile(scales, dim)?, tile(offsets, dim)?)))
+}
+
+#[derive(Clone, Debug, new, Hash)]
+pub(crate) struct ScaleAndOffset {
+    scales: Arc<Tensor>,
+    offsets: Arc<Tensor>,
+}
+
+impl_dyn_hash!(ScaleAndOffset);
+
+impl Expansion for ScaleAndOffset {
+    fn name(&self) -> std::borrow::Cow<str> {
+        "ScaleAndOffset".into()
+    }
+
+    op_kaldi!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 1)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].rank, 2)?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        s.equals(&inputs[0].shape[1], self.scales.len().to_dim())?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let scaled = model.wire_node(
+            prefix.to_string() + ".scale",
+            tract_hir::ops::math::mul::unary(self.scales.clone()),
+            inputs,
+        )?;
+        model.wire_node(prefix, tract_hir::ops::math::add::unary(self.offsets.clone()), &scaled)
+    }
+}
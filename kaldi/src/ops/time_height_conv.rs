@@ -0,0 +1,181 @@
+use tract_hir::internal::*;
+use tract_hir::tract_core::ops::array::Slice;
+use tract_hir::tract_core::ops::cnn::{ConvUnary, KernelFormat, PaddingSpec, PoolSpec};
+use tract_hir::tract_core::ops::nn::DataFormat;
+
+use crate::model::{Component, ParsingContext};
+
+fn int_attribute(component: &Component, name: &str) -> TractResult<usize> {
+    let value =
+        component.attributes.get(name).with_context(|| format!("missing attribute {}", name))?;
+    Ok(value.cast_to_scalar::<i64>()? as usize)
+}
+
+pub fn time_height_convolution(
+    ctx: &ParsingContext,
+    name: &str,
+) -> TractResult<Box<dyn InferenceOp>> {
+    let component = ctx.component(name)?;
+    let filters_in = int_attribute(component, "NumFiltersIn")?;
+    let filters_out = int_attribute(component, "NumFiltersOut")?;
+    let height_in = int_attribute(component, "HeightIn")?;
+    let height_out = int_attribute(component, "HeightOut")?;
+    let subsample = int_attribute(component, "HeightSubsampleOut")?;
+    let offsets = component.attributes.get("Offsets").context("missing attribute Offsets")?;
+    let offsets = offsets.cast_to::<i32>()?;
+    let offsets = offsets.to_array_view::<i32>()?.into_dimensionality::<tract_ndarray::Ix2>()?;
+    let linear: &Tensor =
+        component.attributes.get("LinearParams").context("missing attribute LinearParams")?;
+    let bias = component.attributes.get("BiasParams").context("missing attribute BiasParams")?;
+
+    let mut times: Vec<i32> = offsets.column(0).to_vec();
+    times.sort();
+    times.dedup();
+    let time_dilation = if times.len() > 1 { times[1] - times[0] } else { 1 };
+    if times.windows(2).any(|w| w[1] - w[0] != time_dilation) {
+        bail!("TimeHeightConvolution {} has irregular time offsets {:?}", name, times)
+    }
+    let height_min = offsets.column(1).iter().cloned().min().context("no offsets")?;
+    let height_max = offsets.column(1).iter().cloned().max().context("no offsets")?;
+    let kernel_height = (height_max - height_min + 1) as usize;
+
+    // O•(offset, I) -> T•H•I•O = HWIO, with zeros for the offsets missing on the grid
+    let linear = linear.to_array_view::<f32>()?.into_dimensionality::<tract_ndarray::Ix2>()?;
+    let mut kernel =
+        tract_ndarray::Array4::<f32>::zeros((times.len(), kernel_height, filters_in, filters_out));
+    for (ix, offset) in offsets.outer_iter().enumerate() {
+        let t = ((offset[0] - times[0]) / time_dilation) as usize;
+        let h = (offset[1] - height_min) as usize;
+        for i in 0..filters_in {
+            for o in 0..filters_out {
+                kernel[(t, h, i, o)] = linear[(o, ix * filters_in + i)];
+            }
+        }
+    }
+
+    // input heights used by the output, from the first offset of the first output height to the
+    // last offset of the last one. heights outside of the input are zero-padded.
+    let first = height_min as isize;
+    let last = ((height_out - 1) * subsample) as isize + height_max as isize + 1;
+    Ok(expand(TimeHeightConvolution {
+        filters_in,
+        filters_out,
+        height_in,
+        height_out,
+        subsample,
+        time_dilation: time_dilation as usize,
+        heights: (first.max(0) as usize, last.min(height_in as isize) as usize),
+        padding: ((-first).max(0) as usize, (last - height_in as isize).max(0) as usize),
+        kernel: kernel.into_arc_tensor(),
+        bias: bias.clone(),
+    }))
+}
+
+/// Kaldi nnet3 TimeHeightConvolutionComponent: a 2D convolution over time and height, the
+/// features of a frame being height-major (`height * filters + filter`).
+#[derive(Clone, Debug, Hash)]
+struct TimeHeightConvolution {
+    filters_in: usize,
+    filters_out: usize,
+    height_in: usize,
+    height_out: usize,
+    subsample: usize,
+    time_dilation: usize,
+    heights: (usize, usize),
+    padding: (usize, usize),
+    kernel: Arc<Tensor>, // HWIO
+    bias: Arc<Tensor>,
+}
+
+impl_dyn_hash!(TimeHeightConvolution);
+
+impl TimeHeightConvolution {
+    fn time_context(&self) -> usize {
+        (self.kernel.shape()[0] - 1) * self.time_dilation
+    }
+}
+
+impl Expansion for TimeHeightConvolution {
+    fn name(&self) -> std::borrow::Cow<str> {
+        "TimeHeightConvolution".into()
+    }
+
+    op_kaldi!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 1)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[0].datum_type, f32::datum_type())?;
+        s.equals(&outputs[0].datum_type, f32::datum_type())?;
+        s.equals(&inputs[0].rank, 2)?;
+        s.equals(&outputs[0].rank, 2)?;
+        s.equals(&inputs[0].shape[1], (self.height_in * self.filters_in).to_dim())?;
+        s.equals(&outputs[0].shape[1], (self.height_out * self.filters_out).to_dim())?;
+        s.given(&inputs[0].shape[0], move |s, t| {
+            s.equals(&outputs[0].shape[0], t - self.time_context().to_dim())
+        })?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let mut wire = model.wire_node(
+            format!("{}.split", prefix),
+            AxisOp::Reshape(
+                1,
+                tvec!((self.height_in * self.filters_in).to_dim()),
+                tvec!(self.height_in.to_dim(), self.filters_in.to_dim()),
+            ),
+            inputs,
+        )?;
+        if self.heights != (0, self.height_in) {
+            wire = model.wire_node(
+                format!("{}.heights", prefix),
+                Slice::new(1, self.heights.0, self.heights.1),
+                &wire,
+            )?;
+        }
+        let kernel_shape: TVec<usize> = self.kernel.shape()[0..2].into();
+        wire = model.wire_node(
+            format!("{}.conv", prefix),
+            ConvUnary {
+                pool_spec: PoolSpec::new(
+                    DataFormat::HWC,
+                    kernel_shape,
+                    PaddingSpec::Explicit(
+                        tvec!(0, self.padding.0),
+                        tvec!(0, self.padding.1),
+                        false,
+                    ),
+                    Some(tvec!(self.time_dilation, 1)),
+                    Some(tvec!(1, self.subsample)),
+                    Some(self.filters_out),
+                ),
+                kernel_fmt: KernelFormat::HWIO,
+                kernel: self.kernel.clone(),
+                group: 1,
+                bias: Some(self.bias.clone()),
+                q_params: None,
+            },
+            &wire,
+        )?;
+        model.wire_node(
+            prefix,
+            AxisOp::Reshape(
+                1,
+                tvec!(self.height_out.to_dim(), self.filters_out.to_dim()),
+                tvec!((self.height_out * self.filters_out).to_dim()),
+            ),
+            &wire,
+        )
+    }
+}
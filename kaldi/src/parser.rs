@@ -20,7 +20,7 @@ mod bin;
 mod components;
 mod config_lines;
 mod descriptor;
-mod text;
+pub(crate) mod text;
 
 pub fn nnet3(slice: &[u8]) -> TractResult<KaldiProtoModel> {
     let (_, (config, components)) = parse_top_level(slice).map_err(|e| match e {
@@ -3,14 +3,200 @@ use tract_hir::internal::*;
 use nom::combinator::*;
 use nom::IResult;
 
-use super::components::COMPONENTS;
+use super::components::{KaldiAttributeKind, COMPONENTS};
 
 pub fn attributes<'a>(i: &'a [u8], klass: &str) -> IResult<&'a [u8], HashMap<String, Arc<Tensor>>> {
-    map(nom::multi::many0(|j| attribute(j, klass)), |v| v.into_iter().collect())(i)
+    map(nom::multi::many0(|j| attribute(j, klass)), |v| v.into_iter().flatten().collect())(i)
 }
 
-fn attribute<'a>(i: &'a [u8], klass: &str) -> IResult<&'a [u8], (String, Arc<Tensor>)> {
+fn attribute<'a>(i: &'a [u8], klass: &str) -> IResult<&'a [u8], Vec<(String, Arc<Tensor>)>> {
     let (i, name) = super::open_any(i)?;
+    if let KaldiAttributeKind::Nested = COMPONENTS[klass][name] {
+        // nested objects (like the ConvolutionModel) are flattened in their component
+        let (i, nested) = super::open_any(i)?;
+        let (i, attributes) = attributes(i, nested)?;
+        let (i, _) = super::close(i, nested)?;
+        return Ok((i, attributes.into_iter().collect()));
+    }
     let (i, value) = COMPONENTS[klass][name].parse_bin(i)?;
-    Ok((i, (name.to_string(), value.into_arc_tensor())))
+    Ok((i, vec![(name.to_string(), value.into_arc_tensor())]))
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::components::KaldiAttributeKind::*;
+    use super::super::nnet3;
+    use super::*;
+
+    // writers following kaldi-io: tokens are followed by a space, basic types are prefixed by
+    // their size, vector lengths are not
+    fn token(buf: &mut Vec<u8>, t: &str) {
+        buf.extend(t.as_bytes());
+        buf.push(b' ');
+    }
+
+    fn int(buf: &mut Vec<u8>, i: i32) {
+        buf.push(4);
+        buf.extend(&i.to_le_bytes());
+    }
+
+    fn float(buf: &mut Vec<u8>, f: f32) {
+        buf.push(4);
+        buf.extend(&f.to_le_bytes());
+    }
+
+    fn int_vector(buf: &mut Vec<u8>, v: &[i32]) {
+        buf.push(4);
+        buf.extend(&(v.len() as i32).to_le_bytes());
+        v.iter().for_each(|i| buf.extend(&i.to_le_bytes()));
+    }
+
+    fn int_pair_vector(buf: &mut Vec<u8>, v: &[(i32, i32)]) {
+        buf.push(4);
+        buf.extend(&(v.len() as i32).to_le_bytes());
+        v.iter().for_each(|(a, b)| {
+            buf.extend(&a.to_le_bytes());
+            buf.extend(&b.to_le_bytes());
+        });
+    }
+
+    fn float_vector(buf: &mut Vec<u8>, v: &[f32]) {
+        token(buf, "FV");
+        int(buf, v.len() as i32);
+        v.iter().for_each(|f| buf.extend(&f.to_le_bytes()));
+    }
+
+    fn float_matrix(buf: &mut Vec<u8>, rows: usize, cols: usize, v: &[f32]) {
+        token(buf, "FM");
+        int(buf, rows as i32);
+        int(buf, cols as i32);
+        v.iter().for_each(|f| buf.extend(&f.to_le_bytes()));
+    }
+
+    #[test]
+    fn test_int_vector() {
+        let mut buf = vec![];
+        int_vector(&mut buf, &[-1, 0, 3]);
+        int_vector(&mut buf, &[]);
+        let (rest, v) = IntVector.parse_bin(&buf).unwrap();
+        assert_eq!(v, tensor1(&[-1i32, 0, 3]));
+        let (rest, v) = IntVector.parse_bin(rest).unwrap();
+        assert_eq!(v, tensor1::<i32>(&[]));
+        assert!(rest.is_empty());
+    }
+
+    #[test]
+    fn test_int_pair_vector() {
+        let mut buf = vec![];
+        int_pair_vector(&mut buf, &[(-1, 0), (0, 1)]);
+        int_pair_vector(&mut buf, &[]);
+        let (rest, v) = IntPairVector.parse_bin(&buf).unwrap();
+        assert_eq!(v, tensor2(&[[-1i32, 0], [0, 1]]));
+        let (rest, v) = IntPairVector.parse_bin(rest).unwrap();
+        assert_eq!(v, tensor2(&[[0i32; 2]; 0]));
+        assert!(rest.is_empty());
+    }
+
+    #[test]
+    fn test_negative_lengths() {
+        let mut buf = vec![4];
+        buf.extend(&(-1i32).to_le_bytes());
+        assert!(IntVector.parse_bin(&buf).is_err());
+        assert!(IntPairVector.parse_bin(&buf).is_err());
+        let mut buf = vec![];
+        token(&mut buf, "FV");
+        int(&mut buf, -1);
+        assert!(FloatVector.parse_bin(&buf).is_err());
+        for (rows, cols) in &[(-1, 2), (2, -1), (-2, -2)] {
+            let mut buf = vec![];
+            token(&mut buf, "FM");
+            int(&mut buf, *rows);
+            int(&mut buf, *cols);
+            buf.extend(&[0u8; 16]);
+            assert!(FloatMatrix.parse_bin(&buf).is_err());
+        }
+    }
+
+    #[test]
+    fn test_int_vector_truncated() {
+        let mut buf = vec![];
+        int_vector(&mut buf, &[1, 2, 3]);
+        assert!(IntVector.parse_bin(&buf[..buf.len() - 4]).is_err());
+    }
+
+    // the thc component of the text TDNN-F test, written in binary
+    fn time_height_convolution(buf: &mut Vec<u8>) {
+        token(buf, "<TimeHeightConvolutionComponent>");
+        token(buf, "<LearningRateFactor>");
+        float(buf, 1.0);
+        token(buf, "<Model>");
+        token(buf, "<ConvolutionModel>");
+        for (name, value) in
+            &[("NumFiltersIn", 2), ("NumFiltersOut", 1), ("HeightIn", 2), ("HeightOut", 2)]
+        {
+            token(buf, &format!("<{}>", name));
+            int(buf, *value);
+        }
+        token(buf, "<HeightSubsampleOut>");
+        int(buf, 1);
+        token(buf, "<Offsets>");
+        int_pair_vector(buf, &[(-1, 0), (0, -1), (0, 0), (0, 1), (1, 0)]);
+        token(buf, "<RequiredTimeOffsets>");
+        int_vector(buf, &[-1, 0, 1]);
+        token(buf, "</ConvolutionModel>");
+        token(buf, "<LinearParams>");
+        float_matrix(buf, 1, 10, &[1.0; 10]);
+        token(buf, "<BiasParams>");
+        float_vector(buf, &[0.5]);
+        token(buf, "<MaxMemoryMb>");
+        float(buf, 200.0);
+        token(buf, "<UseNaturalGradient>");
+        token(buf, "T");
+        token(buf, "</TimeHeightConvolutionComponent>");
+    }
+
+    #[test]
+    fn test_nested_attributes() {
+        let mut buf = vec![];
+        time_height_convolution(&mut buf);
+        let (rest, klass) = super::super::open_any(&buf).unwrap();
+        let (rest, attributes) = attributes(rest, klass).unwrap();
+        let (rest, _) = super::super::close(rest, klass).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(*attributes["HeightOut"], tensor0(2i32));
+        assert_eq!(*attributes["RequiredTimeOffsets"], tensor1(&[-1i32, 0, 1]));
+        assert_eq!(*attributes["Offsets"], tensor2(&[[-1i32, 0], [0, -1], [0, 0], [0, 1], [1, 0]]));
+        assert_eq!(*attributes["BiasParams"], tensor1(&[0.5f32]));
+        assert_eq!(*attributes["UseNaturalGradient"], tensor0(true));
+        assert!(!attributes.contains_key("Model"));
+    }
+
+    #[test]
+    fn test_nested_run() {
+        let mut buf = vec![0, b'B'];
+        token(&mut buf, "<Nnet3>");
+        buf.extend(b"\ninput-node name=input dim=4\n");
+        buf.extend(b"component-node name=thc component=thc input=input\n");
+        buf.extend(b"output-node name=output input=thc objective=linear\n\n");
+        token(&mut buf, "<NumComponents>");
+        int(&mut buf, 1);
+        token(&mut buf, "<ComponentName>");
+        token(&mut buf, "thc");
+        time_height_convolution(&mut buf);
+        token(&mut buf, "</Nnet3>");
+        assert_eq!(nnet3(&buf).unwrap().components["thc"].attributes["NumFiltersIn"].len(), 1);
+        let model = crate::kaldi().model_for_read(&mut &*buf).unwrap();
+        let model = model
+            .with_input_fact(0, InferenceFact::dt_shape(f32::datum_type(), &[3, 4]))
+            .unwrap()
+            .into_optimized()
+            .unwrap();
+        let output = model
+            .into_runnable()
+            .unwrap()
+            .run(tvec!(Tensor::from(1f32).broadcast_scalar_to_shape(&[3, 4]).unwrap()))
+            .unwrap();
+        // 4 valid offsets of 2 filters at both heights, plus bias
+        assert_eq!(*output[0], tensor2(&[[8.5f32, 8.5]]));
+    }
 }
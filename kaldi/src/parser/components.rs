@@ -9,18 +9,25 @@ use nom::{
     branch::*,
     bytes::complete::*,
     combinator::*,
+    error::ErrorKind,
     multi::many_m_n,
-    number::complete::{le_f32, le_f64},
+    number::complete::{le_f32, le_f64, le_i32},
     sequence::*,
     IResult,
 };
 
 pub enum KaldiAttributeKind {
     Bool,
+    Flag,
     Int,
+    IntPair,
     Float,
+    FloatPair,
+    IntVector,
+    IntPairVector,
     FloatVector,
     FloatMatrix,
+    Nested,
 }
 
 impl KaldiAttributeKind {
@@ -30,10 +37,20 @@ impl KaldiAttributeKind {
                 map(tag("F"), |_| Tensor::from(false)),
                 map(tag("T"), |_| Tensor::from(true)),
             ))(i),
+            Flag => Ok((i, Tensor::from(true))),
             Int => map(super::integer(true), Tensor::from)(i),
+            IntPair => {
+                map(pair(super::integer(true), super::integer(true)), |(a, b)| tensor1(&[a, b]))(i)
+            }
             Float => map(Self::parse_float_value, Tensor::from)(i),
+            FloatPair => map(pair(Self::parse_float_value, Self::parse_float_value), |(a, b)| {
+                tensor1(&[a, b])
+            })(i),
+            IntVector => Self::parse_int_vector(i),
+            IntPairVector => Self::parse_int_pair_vector(i),
             FloatVector => preceded(multispaced(tag("FV")), Self::parse_float_vector)(i),
             FloatMatrix => preceded(multispaced(tag("FM")), Self::parse_float_matrix)(i),
+            Nested => Err(nom::Err::Failure(nom::error::make_error(i, ErrorKind::Verify))),
         }
     }
 
@@ -41,27 +58,67 @@ impl KaldiAttributeKind {
         alt((preceded(tag([4]), le_f32), map(preceded(tag([8]), le_f64), |f| f as f32)))(i)
     }
 
+    // lengths are signed in the file
+    fn length<'a>(i: &'a [u8], len: i32) -> IResult<&'a [u8], usize> {
+        if len < 0 {
+            Err(nom::Err::Failure(nom::error::make_error(i, ErrorKind::Verify)))
+        } else {
+            Ok((i, len as usize))
+        }
+    }
+
+    // integer vectors are written raw: element size, then an unprefixed i32 length
+    fn parse_int_vector<'a>(i: &'a [u8]) -> IResult<&'a [u8], Tensor> {
+        let (i, len) = preceded(tag([4]), le_i32)(i)?;
+        let (i, len) = Self::length(i, len)?;
+        if len == 0 {
+            Ok((i, tensor1(&[0i32; 0])))
+        } else {
+            map(many_m_n(len, len, le_i32), |data| tensor1(&*data))(i)
+        }
+    }
+
+    fn parse_int_pair_vector<'a>(i: &'a [u8]) -> IResult<&'a [u8], Tensor> {
+        let (i, len) = preceded(tag([4]), le_i32)(i)?;
+        let (i, len) = Self::length(i, len)?;
+        if len == 0 {
+            Ok((i, tensor2(&[[0i32; 2]; 0])))
+        } else {
+            map(
+                map_res(many_m_n(2 * len, 2 * len, le_i32), move |buf| {
+                    tract_ndarray::Array2::from_shape_vec((len, 2), buf)
+                }),
+                Tensor::from,
+            )(i)
+        }
+    }
+
     fn parse_float_vector<'a>(i: &'a [u8]) -> IResult<&'a [u8], Tensor> {
         let (i, len) = super::integer(true)(i)?;
+        let (i, len) = Self::length(i, len)?;
         // FIXME pending merge of https://github.com/Geal/nom/pull/995
         if len == 0 {
-            Ok((i, tensor1(&[0.0f32])))
+            Ok((i, tensor1(&[0.0f32; 0])))
         } else {
-            map(many_m_n(len as usize, len as usize, le_f32), |data| tensor1(&*data))(i)
+            map(many_m_n(len, len, le_f32), |data| tensor1(&*data))(i)
         }
     }
 
     fn parse_float_matrix<'a>(i: &'a [u8]) -> IResult<&'a [u8], Tensor> {
         let (i, rows) = super::integer(true)(i)?;
+        let (i, rows) = Self::length(i, rows)?;
         let (i, cols) = super::integer(true)(i)?;
-        let len = (rows * cols) as usize;
+        let (i, cols) = Self::length(i, cols)?;
+        let len = rows
+            .checked_mul(cols)
+            .ok_or_else(|| nom::Err::Failure(nom::error::make_error(i, ErrorKind::Verify)))?;
         // FIXME pending merge of https://github.com/Geal/nom/pull/995
         if len == 0 {
             Ok((i, tensor2(&[[0.0f32; 0]; 0])))
         } else {
             map(
                 map_res(many_m_n(len, len, le_f32), move |buf| {
-                    tract_ndarray::Array2::from_shape_vec((rows as usize, cols as usize), buf)
+                    tract_ndarray::Array2::from_shape_vec((rows, cols), buf)
                 }),
                 Tensor::from,
             )(i)
@@ -131,14 +188,111 @@ lazy_static::lazy_static! {
             "NumDimsSelfRepaired" => Int,
             "NumDimsProcessed" => Int,
         },
-        "RectifiedLinearComponent" => hashmap!{
+        "RectifiedLinearComponent" => nonlinear(),
+        "SigmoidComponent" => nonlinear(),
+        "TanhComponent" => nonlinear(),
+        "LinearComponent" => updatable(hashmap!{
+            "Params" => FloatMatrix,
+            "OrthonormalConstraint" => Float,
+            "UseNaturalGradient" => Bool,
+            "Alpha" => Float,
+            "RankInOut" => IntPair,
+            "UpdatePeriod" => Int,
+            "NumSamplesHistory" => Float,
+        }),
+        "TdnnComponent" => updatable(hashmap!{
+            "TimeOffsets" => IntVector,
+            "LinearParams" => FloatMatrix,
+            "BiasParams" => FloatVector,
+            "OrthonormalConstraint" => Float,
+            "UseNaturalGradient" => Bool,
+            "NumSamplesHistory" => Float,
+            "AlphaInOut" => FloatPair,
+            "RankInOut" => IntPair,
+        }),
+        "BatchNormComponent" => hashmap!{
             "Dim" => Int,
-            "ValueAvg" => FloatVector,
-            "DerivAvg" => FloatVector,
+            "BlockDim" => Int,
+            "Epsilon" => Float,
+            "TargetRms" => Float,
+            "TestMode" => Bool,
             "Count" => Float,
-            "NumDimsSelfRepaired" => Float,
-            "NumDimsProcessed" => Float,
-            "SelfRepairScale" => Float,
+            "StatsMean" => FloatVector,
+            "StatsVar" => FloatVector,
+        },
+        "ScaleAndOffsetComponent" => updatable(hashmap!{
+            "Dim" => Int,
+            "Scales" => FloatVector,
+            "Offsets" => FloatVector,
+            "UseNaturalGradient" => Bool,
+            "Rank" => Int,
+        }),
+        "GeneralDropoutComponent" => hashmap!{
+            "Dim" => Int,
+            "BlockDim" => Int,
+            "TimePeriod" => Int,
+            "DropoutProportion" => Float,
+            "SpecAugmentConfig" => Float,
+            "MaxRegions" => Int,
+            "Continuous" => Flag,
+            "TestMode" => Bool,
+        },
+        "NoOpComponent" => hashmap!{
+            "Dim" => Int,
+            "BackpropScale" => Float,
+        },
+        "ElementwiseProductComponent" => hashmap!{
+            "InputDim" => Int,
+            "OutputDim" => Int,
+        },
+        "TimeHeightConvolutionComponent" => updatable(hashmap!{
+            "Model" => Nested,
+            "LinearParams" => FloatMatrix,
+            "BiasParams" => FloatVector,
+            "MaxMemoryMb" => Float,
+            "UseNaturalGradient" => Bool,
+            "NumMinibatchesHistory" => Float,
+            "AlphaInOut" => FloatPair,
+            "RankInOut" => IntPair,
+        }),
+        "ConvolutionModel" => hashmap!{
+            "NumFiltersIn" => Int,
+            "NumFiltersOut" => Int,
+            "HeightIn" => Int,
+            "HeightOut" => Int,
+            "HeightSubsampleOut" => Int,
+            "Offsets" => IntPairVector,
+            "RequiredTimeOffsets" => IntVector,
         }
     };
 }
+
+/// Attributes written by `UpdatableComponent::WriteUpdatableCommon`.
+fn updatable(
+    mut attributes: HashMap<&'static str, KaldiAttributeKind>,
+) -> HashMap<&'static str, KaldiAttributeKind> {
+    attributes.insert("LearningRateFactor", Float);
+    attributes.insert("IsGradient", Bool);
+    attributes.insert("MaxChange", Float);
+    attributes.insert("L2Regularize", Float);
+    attributes.insert("LearningRate", Float);
+    attributes
+}
+
+/// Attributes of `NonlinearComponent` subclasses.
+fn nonlinear() -> HashMap<&'static str, KaldiAttributeKind> {
+    hashmap! {
+        "Dim" => Int,
+        "BlockDim" => Int,
+        "ValueAvg" => FloatVector,
+        "DerivAvg" => FloatVector,
+        "Count" => Float,
+        "OderivRms" => FloatVector,
+        "OderivCount" => Float,
+        "NumDimsSelfRepaired" => Float,
+        "NumDimsProcessed" => Float,
+        "SelfRepairLowerThreshold" => Float,
+        "SelfRepairUpperThreshold" => Float,
+        "SelfRepairScale" => Float,
+    }
+}
@@ -1,46 +1,70 @@
 use nom::IResult;
 use nom::{
-    bytes::complete::*, character::complete::*, combinator::*, multi::separated_list0, sequence::*,
+    branch::alt, bytes::complete::*, character::complete::*, combinator::*, multi::separated_list0,
+    number::complete::float, sequence::*,
 };
 
-use crate::model::GeneralDescriptor;
+use crate::model::{GeneralDescriptor, Index};
 use crate::parser::spaced;
 
 pub fn parse_general(i: &str) -> IResult<&str, GeneralDescriptor> {
-    spaced(nom::branch::alt((
+    spaced(alt((
+        map(call("Append", separated_list0(comma, parse_general)), GeneralDescriptor::Append),
         map(
-            preceded(
-                tag("Append"),
-                cut(delimited(
-                    spaced(tag("(")),
-                    separated_list0(spaced(tag(",")), parse_general),
-                    spaced(tag(")")),
-                )),
-            ),
-            GeneralDescriptor::Append,
+            // x offsets are always zero for the models tract loads
+            call("Offset", tuple((parse_general, comma, integer, opt(pair(comma, tag("0")))))),
+            |(inner, _, offset, _)| GeneralDescriptor::Offset(Box::new(inner), offset as isize),
         ),
+        map(call("IfDefined", parse_general), |inner| {
+            GeneralDescriptor::IfDefined(Box::new(inner))
+        }),
+        map(call("Sum", separated_pair(parse_general, comma, parse_general)), |(a, b)| {
+            GeneralDescriptor::Sum(Box::new(a), Box::new(b))
+        }),
+        map(call("Failover", separated_pair(parse_general, comma, parse_general)), |(a, b)| {
+            GeneralDescriptor::Failover(Box::new(a), Box::new(b))
+        }),
+        map(call("Scale", separated_pair(float, comma, parse_general)), |(scale, inner)| {
+            GeneralDescriptor::Scale(scale, Box::new(inner))
+        }),
+        map(call("Const", separated_pair(float, comma, integer)), |(value, dim)| {
+            GeneralDescriptor::Const(value, dim as usize)
+        }),
+        map(call("Round", separated_pair(parse_general, comma, integer)), |(inner, modulus)| {
+            GeneralDescriptor::Round(Box::new(inner), modulus as usize)
+        }),
         map(
-            preceded(
-                tag("Offset"),
-                cut(delimited(
-                    spaced(tag("(")),
-                    separated_pair(parse_general, spaced(tag(",")), integer),
-                    spaced(tag(")")),
+            call(
+                "ReplaceIndex",
+                tuple((
+                    parse_general,
+                    comma,
+                    alt((value(Index::T, tag("t")), value(Index::X, tag("x")))),
+                    comma,
+                    integer,
                 )),
             ),
-            |(inner, offset)| GeneralDescriptor::Offset(Box::new(inner), offset as isize),
-        ),
-        map(
-            preceded(
-                tag("IfDefined"),
-                cut(delimited(spaced(tag("(")), parse_general, spaced(tag(")")))),
-            ),
-            |inner| GeneralDescriptor::IfDefined(Box::new(inner)),
+            |(inner, _, index, _, value)| {
+                GeneralDescriptor::ReplaceIndex(Box::new(inner), index, value as isize)
+            },
         ),
+        map(call("Switch", separated_list0(comma, parse_general)), GeneralDescriptor::Switch),
         map(super::config_lines::identifier, |i| GeneralDescriptor::Name(i.to_string())),
     )))(i)
 }
 
+/// `keyword(arguments)`. Once the opening parenthesis is found, arguments must parse.
+fn call<'a, O>(
+    keyword: &'static str,
+    arguments: impl FnMut(&'a str) -> IResult<&'a str, O>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, O> {
+    preceded(pair(tag(keyword), spaced(tag("("))), cut(terminated(arguments, spaced(tag(")")))))
+}
+
+fn comma(i: &str) -> IResult<&str, &str> {
+    spaced(tag(","))(i)
+}
+
 pub fn integer(i: &str) -> IResult<&str, i32> {
     map_res(recognize(pair(opt(tag("-")), digit1)), |s: &str| s.parse::<i32>())(i)
 }
@@ -71,6 +95,44 @@ mod test {
         )
     }
 
+    #[test]
+    fn test_residual() {
+        assert_eq!(
+            parse_general("Sum(Scale(0.66, tdnnf2.dropout), tdnnf3.noop)").unwrap().1,
+            Sum(Scale(0.66, name("tdnnf2.dropout").into()).into(), name("tdnnf3.noop").into())
+        )
+    }
+
+    #[test]
+    fn test_ivector() {
+        assert_eq!(
+            parse_general("Append(Offset(input, -1), input, ReplaceIndex(ivector, t, 0))")
+                .unwrap()
+                .1,
+            Append(vec!(
+                Offset(name("input").into(), -1),
+                name("input"),
+                ReplaceIndex(name("ivector").into(), Index::T, 0)
+            ))
+        )
+    }
+
+    #[test]
+    fn test_const_round_switch() {
+        assert_eq!(
+            parse_general(
+                "Switch(Round(input, 3), Sum(input, Const(1.5, 40)), Offset(input, 2, 0))"
+            )
+            .unwrap()
+            .1,
+            Switch(vec!(
+                Round(name("input").into(), 3),
+                Sum(name("input").into(), Const(1.5, 40).into()),
+                Offset(name("input").into(), 2)
+            ))
+        )
+    }
+
     #[test]
     fn test_lstm() {
         assert_eq!(
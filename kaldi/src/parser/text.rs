@@ -2,26 +2,53 @@ use tract_hir::internal::*;
 
 use nom::IResult;
 use nom::{
-    bytes::complete::*, character::complete::*, combinator::*, multi::separated_list0,
-    number::complete::float, sequence::*,
+    bytes::complete::*,
+    character::complete::*,
+    combinator::*,
+    multi::{separated_list0, separated_list1},
+    number::complete::float,
+    sequence::*,
 };
 
-use super::{integer, multispaced, open_any, spaced};
+use super::{close, integer, multispaced, open_any, spaced};
 
 pub fn attributes(i: &[u8]) -> IResult<&[u8], HashMap<String, Arc<Tensor>>> {
-    let (i, attributes) = nom::multi::many0(map(pair(open_any, tensor), |(k, v)| {
-        (k.to_string(), v.into_arc_tensor())
-    }))(i)?;
-    Ok((i, attributes.into_iter().collect()))
+    let (i, attributes) = nom::multi::many0(attribute)(i)?;
+    Ok((i, attributes.into_iter().flatten().collect()))
+}
+
+fn attribute(i: &[u8]) -> IResult<&[u8], Vec<(String, Arc<Tensor>)>> {
+    let (i, name) = open_any(i)?;
+    nom::branch::alt((
+        // nested objects (like the ConvolutionModel) are flattened in their component
+        map(nested, |attributes| attributes.into_iter().collect()),
+        map(tensor, move |t| vec![(name.to_string(), t.into_arc_tensor())]),
+        // a token without value is a flag
+        move |i| Ok((i, vec![(name.to_string(), Tensor::from(true).into_arc_tensor())])),
+    ))(i)
+}
+
+fn nested(i: &[u8]) -> IResult<&[u8], HashMap<String, Arc<Tensor>>> {
+    let (i, klass) = open_any(i)?;
+    let (i, attributes) = attributes(i)?;
+    let (i, _) = close(i, klass)?;
+    Ok((i, attributes))
 }
 
 pub fn tensor(i: &[u8]) -> IResult<&[u8], Tensor> {
-    nom::branch::alt((scalar, vector, matrix))(i)
+    nom::branch::alt((scalar, vector, matrix, pair_vector))(i)
 }
 
 pub fn scalar(i: &[u8]) -> IResult<&[u8], Tensor> {
     nom::branch::alt((
-        map(float, Tensor::from),
+        // some attributes (RankInOut, AlphaInOut) are pairs of numbers
+        map(separated_list1(space1, float), |v| {
+            if v.len() == 1 {
+                Tensor::from(v[0])
+            } else {
+                tensor1(&*v)
+            }
+        }),
         map(integer(false), Tensor::from),
         map(tag("F"), |_| Tensor::from(false)),
         map(tag("T"), |_| Tensor::from(true)),
@@ -48,6 +75,20 @@ pub fn matrix(i: &[u8]) -> IResult<&[u8], Tensor> {
     Ok((i, t.into_tensor()))
 }
 
+pub fn pair_vector(i: &[u8]) -> IResult<&[u8], Tensor> {
+    map(
+        delimited(
+            spaced(tag("[")),
+            separated_list0(space1, separated_pair(integer(false), tag(","), integer(false))),
+            spaced(tag("]")),
+        ),
+        |pairs| {
+            let data: Vec<i32> = pairs.iter().flat_map(|(a, b)| vec![*a, *b]).collect();
+            tract_ndarray::Array2::from_shape_vec((pairs.len(), 2), data).unwrap().into_tensor()
+        },
+    )(i)
+}
+
 #[cfg(test)]
 #[allow(non_snake_case)]
 mod tests {
@@ -89,6 +130,113 @@ output-node name=output input=fixed1
         );
     }
 
+    const TDNNF: &str = r#"<Nnet3>
+
+input-node name=input dim=4
+component-node name=thc component=thc input=input
+component-node name=tdnn component=tdnn input=thc
+component-node name=linear component=linear input=tdnn
+component-node name=bn component=bn input=linear
+component-node name=noop component=noop input=bn
+component-node name=dropout component=dropout input=noop
+output-node name=output input=dropout objective=linear
+
+<NumComponents> 6
+<ComponentName> thc <TimeHeightConvolutionComponent> <LearningRateFactor> 1 <MaxChange> 0.75 <LearningRate> 0.001 <Model> <ConvolutionModel> <NumFiltersIn> 2 <NumFiltersOut> 1 <HeightIn> 2 <HeightOut> 2 <HeightSubsampleOut> 1 <Offsets> [ -1,0 0,-1 0,0 0,1 1,0 ] <RequiredTimeOffsets> [ -1 0 1 ] </ConvolutionModel> <LinearParams>  [
+  1 1 1 1 1 1 1 1 1 1 ]
+<BiasParams>  [ 0.5 ]
+<MaxMemoryMb> 200 <UseNaturalGradient> T </TimeHeightConvolutionComponent>
+<ComponentName> tdnn <TdnnComponent> <MaxChange> 0.75 <LearningRate> 0.001 <TimeOffsets> [ -1 0 ] <LinearParams>  [
+  0.1 0.1 0.1 0.1
+  0.1 0.1 0.1 0.1
+  0.1 0.1 0.1 0.1 ]
+<BiasParams>  [ ]
+<OrthonormalConstraint> 0 <UseNaturalGradient> T </TdnnComponent>
+<ComponentName> linear <LinearComponent> <LearningRate> 0.001 <Params>  [
+  1 0 0
+  0 1 -1 ]
+<RankInOut> 20 80 <UpdatePeriod> 4 <NumSamplesHistory> 2000 <Alpha> 4 </LinearComponent>
+<ComponentName> bn <BatchNormComponent> <Dim> 2 <BlockDim> 2 <Epsilon> 0 <TargetRms> 1 <TestMode> F <Count> 100 <StatsMean> [ 0.5 -0.5 ] <StatsVar> [ 4 1 ] </BatchNormComponent>
+<ComponentName> noop <NoOpComponent> <Dim> 2 <BackpropScale> 1 </NoOpComponent>
+<ComponentName> dropout <GeneralDropoutComponent> <Dim> 2 <BlockDim> 2 <TimePeriod> 0 <DropoutProportion> 0.1 <Continuous> </GeneralDropoutComponent>
+</Nnet3>"#;
+
+    #[test]
+    fn test_tdnnf_components() {
+        let model = nnet3(TDNNF.as_bytes()).unwrap();
+        let thc = &model.components["thc"];
+        assert_eq!(*thc.attributes["HeightOut"], tensor0(2f32));
+        assert_eq!(
+            *thc.attributes["Offsets"],
+            tensor2(&[[-1i32, 0], [0, -1], [0, 0], [0, 1], [1, 0]])
+        );
+        assert_eq!(*model.components["linear"].attributes["RankInOut"], tensor1(&[20f32, 80.]));
+        assert_eq!(*model.components["tdnn"].attributes["BiasParams"], tensor1::<f32>(&[]));
+        assert_eq!(*model.components["dropout"].attributes["Continuous"], tensor0(true));
+    }
+
+    #[test]
+    fn test_tdnnf_run() {
+        let model = crate::kaldi().model_for_read(&mut TDNNF.as_bytes()).unwrap();
+        let model = model
+            .with_input_fact(0, InferenceFact::dt_shape(f32::datum_type(), &[6, 4]))
+            .unwrap()
+            .into_optimized()
+            .unwrap();
+        let output = model
+            .into_runnable()
+            .unwrap()
+            .run(tvec!(Tensor::from(1f32).broadcast_scalar_to_shape(&[6, 4]).unwrap()))
+            .unwrap();
+        // thc: 4 valid offsets of 2 filters, plus bias: 8.5 ; tdnn: 3.4 ; linear: [3.4, 0]
+        let expected = tensor2(&[[1.45f32, 0.5], [1.45, 0.5], [1.45, 0.5]]);
+        output[0].close_enough(&expected, true).unwrap();
+    }
+
+    const IVECTOR: &str = r#"<Nnet3>
+
+input-node name=input dim=1
+input-node name=ivector dim=1
+component-node name=affine component=affine input=Append(Offset(input, -1), input, Offset(input, 1), ReplaceIndex(ivector, t, 0))
+output-node name=output input=Sum(Scale(2.0, affine), Offset(input, 1)) objective=linear
+
+<NumComponents> 1
+<ComponentName> affine <FixedAffineComponent> <LinearParams>  [
+  1 10 100 1000 ]
+<BiasParams>  [ 0 ]
+</FixedAffineComponent>
+</Nnet3>"#;
+
+    #[test]
+    fn test_ivector_run() {
+        let model = crate::kaldi().model_for_read(&mut IVECTOR.as_bytes()).unwrap();
+        let model = model.into_typed().unwrap().declutter().unwrap();
+        assert_eq!(model.input_fact(1).unwrap().shape.as_concrete(), Some(&[1usize, 1][..]));
+        let output = SpecializingPlan::new(model)
+            .unwrap()
+            .run(tvec!(tensor2(&[[1f32], [2.], [3.], [4.], [5.]]), tensor2(&[[7f32]])))
+            .unwrap();
+        // frames 1 to 3 of 2 * (x[t-1] + 10 x[t] + 100 x[t+1] + 1000 ivector) + x[t+1]
+        assert_eq!(*output[0], tensor2(&[[14645f32], [14868.], [15091.]]));
+    }
+
+    #[test]
+    fn test_ivector_pulse() {
+        use tract_pulse::internal::{PulsedModel, PulsedModelExt};
+        let model = crate::kaldi().model_for_read(&mut IVECTOR.as_bytes()).unwrap();
+        let model = model.into_typed().unwrap().declutter().unwrap();
+        let pulsed = PulsedModel::new(&model, 1).unwrap();
+        assert_eq!(pulsed.output_fact(0).unwrap().delay(), 2);
+        let plan = SimplePlan::new(pulsed.into_typed().unwrap()).unwrap();
+        let mut state = SimpleState::new(&plan).unwrap();
+        let mut outputs = vec![];
+        for x in 1..=5 {
+            let output = state.run(tvec!(tensor2(&[[x as f32]]), tensor2(&[[7f32]]))).unwrap();
+            outputs.push(*output[0].to_scalar::<f32>().unwrap());
+        }
+        assert_eq!(&outputs[2..], &[14645f32, 14868., 15091.]);
+    }
+
     #[test]
     fn fixed_affine_40x10_T40_S3() {
         let slice = std::fs::read("test_cases/fixed_affine_40x10_T40_S3/model.raw.txt").unwrap();
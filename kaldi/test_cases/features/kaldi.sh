@@ -0,0 +1,9 @@
+#!/bin/bash
+# Regenerate the reference features with Kaldi binaries, from input.wav. The stored features
+# were written by reference.py, not by Kaldi: running this checks tract against Kaldi itself.
+echo "utt input.wav" > wav.scp
+compute-mfcc-feats --config=mfcc.conf scp:wav.scp ark,t:mfcc.ark
+compute-fbank-feats --config=fbank.conf scp:wav.scp ark,t:fbank.ark
+compute-cmvn-stats --binary=false ark:mfcc.ark cmvn.stats
+apply-cmvn --norm-vars=true cmvn.stats ark:mfcc.ark ark,t:mfcc_cmvn.ark
+rm wav.scp
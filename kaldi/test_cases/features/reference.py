@@ -0,0 +1,169 @@
+#!/usr/bin/env python3
+# Writes input.wav, and reference features for it with mfcc.conf and fbank.conf, in Kaldi text
+# format (mfcc.ark, fbank.ark), plus global CMVN stats of the MFCC (cmvn.stats) and the MFCC
+# normalized with them (mfcc_cmvn.ark).
+#
+# The features are NOT computed by Kaldi: they come from this double precision transcription of
+# Kaldi feature-window.cc, mel-computations.cc, feature-mfcc.cc and feature-fbank.cc, and are
+# only as faithful as the transcription. The stored files were written by this script. With
+# Kaldi binaries around, kaldi.sh replaces them with Kaldi's own output (input.wav is kept).
+import math
+import struct
+import wave
+
+FLT_MIN = 1.17549435e-38
+FLT_EPSILON = 1.1920929e-07
+
+
+def signal(count, rate):
+    seed = 1234
+    samples = []
+    for i in range(count):
+        seed = (seed * 1103515245 + 12345) % 2 ** 31
+        noise = (seed / 2 ** 31 - 0.5) * 1000
+        t = i / rate
+        envelope = 0.6 + 0.4 * math.sin(2 * math.pi * 3 * t)
+        value = envelope * (3000 * math.sin(2 * math.pi * 440 * t) + 1500 * math.sin(2 * math.pi * 1250 * t + 0.3))
+        samples.append(int(round(value + noise)))
+    return samples
+
+
+def parse_conf(path):
+    opts = {}
+    for line in open(path):
+        line = line.split("#")[0].strip()
+        if line:
+            key, _, value = line[2:].partition("=")
+            opts[key] = value or "true"
+    return opts
+
+
+def window_function(kind, length, blackman_coeff):
+    a = 2 * math.pi / (length - 1)
+    def w(i):
+        if kind == "hanning":
+            return 0.5 - 0.5 * math.cos(a * i)
+        if kind == "sine":
+            return math.sin(0.5 * a * i)
+        if kind == "hamming":
+            return 0.54 - 0.46 * math.cos(a * i)
+        if kind == "povey":
+            return math.pow(0.5 - 0.5 * math.cos(a * i), 0.85)
+        if kind == "rectangular":
+            return 1.0
+        if kind == "blackman":
+            return blackman_coeff - 0.5 * math.cos(a * i) + (0.5 - blackman_coeff) * math.cos(2 * a * i)
+    return [w(i) for i in range(length)]
+
+
+def mel_scale(freq):
+    return 1127.0 * math.log(1.0 + freq / 700.0)
+
+
+def mel_banks(num_bins, padded, rate, low, high):
+    num_fft_bins = padded // 2
+    nyquist = 0.5 * rate
+    if high <= 0:
+        high += nyquist
+    bin_width = rate / padded
+    mel_low, mel_high = mel_scale(low), mel_scale(high)
+    delta = (mel_high - mel_low) / (num_bins + 1)
+    banks = []
+    for b in range(num_bins):
+        left, center, right = mel_low + b * delta, mel_low + (b + 1) * delta, mel_low + (b + 2) * delta
+        bank = [0.0] * (num_fft_bins + 1)
+        for i in range(num_fft_bins):
+            mel = mel_scale(bin_width * i)
+            if left < mel < right:
+                bank[i] = (mel - left) / (center - left) if mel <= center else (right - mel) / (right - center)
+        banks.append(bank)
+    return banks
+
+
+def features(samples, opts, mfcc):
+    rate = float(opts.get("sample-frequency", 16000))
+    length = int(rate * 0.001 * float(opts.get("frame-length", 25)))
+    shift = int(rate * 0.001 * float(opts.get("frame-shift", 10)))
+    padded = 1 << (length - 1).bit_length()
+    preemph = float(opts.get("preemphasis-coefficient", 0.97))
+    remove_dc = opts.get("remove-dc-offset", "true") == "true"
+    window = window_function(opts.get("window-type", "povey"), length, float(opts.get("blackman-coeff", 0.42)))
+    num_bins = int(opts.get("num-mel-bins", 23))
+    banks = mel_banks(num_bins, padded, rate, float(opts.get("low-freq", 20)), float(opts.get("high-freq", 0)))
+    use_energy = opts.get("use-energy", "true" if mfcc else "false") == "true"
+    raw_energy = opts.get("raw-energy", "true") == "true"
+    energy_floor = float(opts.get("energy-floor", 0))
+    use_power = opts.get("use-power", "true") == "true"
+    use_log = mfcc or opts.get("use-log-fbank", "true") == "true"
+    num_ceps = int(opts.get("num-ceps", 13))
+    lifter = float(opts.get("cepstral-lifter", 22))
+
+    rows = []
+    for f in range((len(samples) - length) // shift + 1):
+        w = [float(s) for s in samples[f * shift:f * shift + length]]
+        if remove_dc:
+            mean = sum(w) / length
+            w = [x - mean for x in w]
+        if use_energy and raw_energy:
+            energy = math.log(max(sum(x * x for x in w), FLT_MIN))
+        if preemph != 0:
+            for i in range(length - 1, 0, -1):
+                w[i] -= preemph * w[i - 1]
+            w[0] -= preemph * w[0]
+        w = [x * y for x, y in zip(w, window)]
+        if use_energy and not raw_energy:
+            energy = math.log(max(sum(x * x for x in w), FLT_MIN))
+        if use_energy and energy_floor > 0:
+            energy = max(energy, math.log(energy_floor))
+        power = []
+        for k in range(padded // 2 + 1):
+            re = sum(x * math.cos(2 * math.pi * k * n / padded) for n, x in enumerate(w))
+            im = sum(x * math.sin(2 * math.pi * k * n / padded) for n, x in enumerate(w))
+            power.append(re * re + im * im)
+        if not use_power:
+            power = [math.sqrt(p) for p in power]
+        mel = [sum(b * p for b, p in zip(bank, power)) for bank in banks]
+        if use_log:
+            mel = [math.log(max(m, FLT_EPSILON)) for m in mel]
+        if mfcc:
+            row = []
+            for k in range(num_ceps):
+                norm = math.sqrt((1.0 if k == 0 else 2.0) / num_bins)
+                c = sum(norm * math.cos(math.pi / num_bins * (n + 0.5) * k) * m for n, m in enumerate(mel))
+                if lifter != 0:
+                    c *= 1.0 + 0.5 * lifter * math.sin(math.pi * k / lifter)
+                row.append(c)
+            if use_energy:
+                row[0] = energy
+        else:
+            row = ([energy] if use_energy else []) + mel
+        rows.append(row)
+    return rows
+
+
+def write_ark(path, rows):
+    with open(path, "w") as f:
+        f.write("utt  [\n")
+        for ix, row in enumerate(rows):
+            f.write("  " + " ".join("%.6g" % x for x in row) + (" ]\n" if ix + 1 == len(rows) else "\n"))
+
+
+samples = signal(4000, 16000)
+with wave.open("input.wav", "wb") as w:
+    w.setnchannels(1)
+    w.setsampwidth(2)
+    w.setframerate(16000)
+    w.writeframes(struct.pack("<%dh" % len(samples), *samples))
+
+mfcc = features(samples, parse_conf("mfcc.conf"), True)
+write_ark("mfcc.ark", mfcc)
+write_ark("fbank.ark", features(samples, parse_conf("fbank.conf"), False))
+
+dim = len(mfcc[0])
+sums = [sum(row[d] for row in mfcc) for d in range(dim)]
+squares = [sum(row[d] ** 2 for row in mfcc) for d in range(dim)]
+with open("cmvn.stats", "w") as f:
+    f.write(" [\n  %s %d\n  %s 0 ]\n" % (" ".join("%.9g" % s for s in sums), len(mfcc), " ".join("%.9g" % s for s in squares)))
+means = [s / len(mfcc) for s in sums]
+scales = [1 / math.sqrt(max(sq / len(mfcc) - m * m, 1e-20)) for sq, m in zip(squares, means)]
+write_ark("mfcc_cmvn.ark", [[(x - m) * s for x, m, s in zip(row, means, scales)] for row in mfcc])
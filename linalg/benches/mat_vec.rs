@@ -6,7 +6,8 @@ use DatumType::F32;
 fn mat_vec_mul(c: &mut Criterion) {
     let mut group = c.benchmark_group("mat_vec_mul");
     unsafe {
-        for (m, k) in [(64usize, 64usize)].iter() {
+        {
+            let (m, k) = &(64usize, 64usize);
             group.throughput(Throughput::Elements((m * k) as u64));
             group.bench_with_input(
                 BenchmarkId::from_parameter(format!("{}x{}", m, k)),
@@ -18,7 +18,7 @@ fn mat_mul_smmm(be: &mut criterion::Bencher, &(m, k, n): &(usize, usize, usize))
             mm.run(
                 &mm.a_packed(F32).wrap(&pa.view()),
                 &mm.b_packed(F32).wrap(&pb.view()),
-                &mut mm.c_view().wrap(&mut c.view_mut()),
+                &mut mm.c_view().wrap(&c.view_mut()),
                 &[],
             )
         });
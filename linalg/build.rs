@@ -60,16 +60,18 @@ fn main() {
                         cc::Build::new()
                             .files(files)
                             .flag("-mfma")
-                            .static_flag(true)
                             .compile("x86_64_fma");
 
                         // clang at least (dunno about gcc) outputs .asm files in the
                         // root directory that we need to clean up so we don't pollute
                         // the build output/working directory
                         let _ = fs::remove_file("fma_mmm_f32_16x6.asm");
+                        let _ = fs::remove_file("fma_mmm_f16_16x6.asm");
                         let _ = fs::remove_file("fma_mmm_i8_8x8.asm");
                         let _ = fs::remove_file("fma_sigmoid_f32.asm");
                         let _ = fs::remove_file("fma_tanh_f32.asm");
+                        let _ = fs::remove_file("fma_f16_to_f32.asm");
+                        let _ = fs::remove_file("fma_f32_to_f16.asm");
                     }
                 }
                 "macos" => {
@@ -100,7 +102,6 @@ fn main() {
                     cc::Build::new()
                         .files(files)
                         .flag("-mfma")
-                        .static_flag(true)
                         .compile("x86_64_fma");
                 }
             }
@@ -111,20 +112,18 @@ fn main() {
                 .files(files)
                 .flag("-marm")
                 .flag("-mfpu=vfp")
-                .static_flag(true)
                 .compile("armvfpv2");
             let files = preprocess_files("arm32/armv7neon", &[], &suffix);
             cc::Build::new()
                 .files(files)
                 .flag("-marm")
                 .flag("-mfpu=neon")
-                .static_flag(true)
                 .compile("armv7neon");
         }
         "aarch64" => {
             let files =
                 preprocess_files("arm64/arm64simd", &[("core", vec!["a53", "gen"])], &suffix);
-            cc::Build::new().files(files).static_flag(true).compile("arm64");
+            cc::Build::new().files(files).compile("arm64");
         }
         _ => {}
     }
@@ -6,6 +6,7 @@ use crate::frame::ElementWiseImpl;
 use crate::frame::MatMatMulImpl;
 
 use tract_data::internal::DimLike;
+use tract_data::prelude::f16;
 
 fn is_cortex_a53() -> std::io::Result<bool> {
     let cpu_info = std::fs::read_to_string("/proc/cpuinfo")?;
@@ -49,4 +50,8 @@ pub fn plug(ops: &mut Ops) {
     ops.sigmoid_f32 =
         Box::new(|| Box::new(ElementWiseImpl::<arm64simd::SigmoidF32x4n, f32>::new()));
     ops.tanh_f32 = Box::new(|| Box::new(ElementWiseImpl::<arm64simd::TanhF32x4n, f32>::new()));
+    // f16 matrix products stay on the generic kernels: there is no fp16 (ARMv8.2-A) kernel yet
+    ops.sigmoid_f16 =
+        Box::new(|| Box::new(ElementWiseImpl::<arm64simd::SigmoidF16x4n, f16>::new()));
+    ops.tanh_f16 = Box::new(|| Box::new(ElementWiseImpl::<arm64simd::TanhF16x4n, f16>::new()));
 }
@@ -1,5 +1,6 @@
 use crate::frame::element_wise::ElementWiseKer;
 use crate::frame::mmm::*;
+use tract_data::prelude::f16;
 
 extern_kernel!(fn arm64simd_mmm_f32_8x8_a53(op: *const MatMatMulKerSpec<f32>) -> isize);
 extern_kernel!(fn arm64simd_mmm_f32_8x8_gen(op: *const MatMatMulKerSpec<f32>) -> isize);
@@ -10,6 +11,8 @@ extern_kernel!(fn arm64simd_mmm_f32_64x1_gen(op: *const MatMatMulKerSpec<f32>) -
 extern_kernel!(fn arm64simd_mmm_i8_8x8(op: *const MatMatMulKerSpec<i32>) -> isize);
 extern_kernel!(fn arm64simd_sigmoid_f32_4n(ptr: *mut f32, count: usize) -> ());
 extern_kernel!(fn arm64simd_tanh_f32_4n(ptr: *mut f32, count: usize) -> ());
+extern_kernel!(fn arm64simd_f16_to_f32_4n(src: *const f16, dst: *mut f32, count: usize) -> ());
+extern_kernel!(fn arm64simd_f32_to_f16_4n(src: *const f32, dst: *mut f16, count: usize) -> ());
 
 #[derive(Copy, Clone, Debug)]
 pub struct MatMatMulF32x8x8A53;
@@ -335,6 +338,75 @@ impl ElementWiseKer<f32> for TanhF32x4n {
     }
 }
 
+/// Runs a single precision kernel on a half precision buffer, converting by chunks.
+///
+/// `buf` length must be a multiple of 4.
+fn run_as_f32(buf: &mut [f16], kernel: impl Fn(&mut [f32])) {
+    #[repr(C, align(16))]
+    struct Scratch([f32; 64]);
+    let mut scratch = Scratch([0f32; 64]);
+    for chunk in buf.chunks_mut(64) {
+        let len = chunk.len();
+        unsafe {
+            arm64simd_f16_to_f32_4n(chunk.as_ptr(), scratch.0.as_mut_ptr(), len);
+            kernel(&mut scratch.0[..len]);
+            arm64simd_f32_to_f16_4n(scratch.0.as_ptr(), chunk.as_mut_ptr(), len);
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct SigmoidF16x4n;
+
+impl ElementWiseKer<f16> for SigmoidF16x4n {
+    #[inline(always)]
+    fn name() -> &'static str {
+        "arm64simd"
+    }
+    #[inline(always)]
+    fn nr() -> usize {
+        4
+    }
+    #[inline(always)]
+    fn alignment_items() -> usize {
+        4
+    }
+    #[inline(always)]
+    fn alignment_bytes() -> usize {
+        8
+    }
+    #[inline(never)]
+    fn run(buf: &mut [f16]) {
+        run_as_f32(buf, |buf| unsafe { arm64simd_sigmoid_f32_4n(buf.as_mut_ptr(), buf.len()) })
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct TanhF16x4n;
+
+impl ElementWiseKer<f16> for TanhF16x4n {
+    #[inline(always)]
+    fn name() -> &'static str {
+        "arm64simd"
+    }
+    #[inline(always)]
+    fn nr() -> usize {
+        4
+    }
+    #[inline(always)]
+    fn alignment_items() -> usize {
+        4
+    }
+    #[inline(always)]
+    fn alignment_bytes() -> usize {
+        8
+    }
+    #[inline(never)]
+    fn run(buf: &mut [f16]) {
+        run_as_f32(buf, |buf| unsafe { arm64simd_tanh_f32_4n(buf.as_mut_ptr(), buf.len()) })
+    }
+}
+
 test_mmm_kernel_f32!(crate::arm64::arm64simd::MatMatMulF32x8x8A53, test_MatMatMulF32x8x8a53, true);
 test_mmm_kernel_f32!(crate::arm64::arm64simd::MatMatMulF32x8x8, test_MatMatMulF32x8x8, true);
 test_mmm_kernel_f32!(
@@ -360,4 +432,16 @@ test_mmm_kernel_i8_i32!(
 mod test_simd {
     sigmoid_frame_tests!(true, crate::arm64::arm64simd::SigmoidF32x4n);
     tanh_frame_tests!(true, crate::arm64::arm64simd::TanhF32x4n);
+
+    proptest::proptest! {
+        #[test]
+        fn sigmoid_f16(xs in proptest::collection::vec(-25f32..25.0, 0..100)) {
+            crate::frame::sigmoid::test::test_sigmoid_f16::<super::SigmoidF16x4n>(&*xs).unwrap()
+        }
+
+        #[test]
+        fn tanh_f16(xs in proptest::collection::vec(-25f32..25.0, 0..100)) {
+            crate::frame::tanh::test::test_tanh_f16::<super::TanhF16x4n>(&*xs).unwrap()
+        }
+    }
 }
@@ -42,7 +42,7 @@ pub enum FusedKerSpec<TI: Copy> {
 
 #[cfg(test)]
 #[macro_use]
-pub mod test {
+pub(crate) mod test {
     use super::*;
     use crate::frame::mmm::storage::*;
     use crate::frame::mmm::*;
@@ -64,7 +64,7 @@ pub mod test {
         ($cond:expr, $ker:ty, $tc:ty, $ti: ty) => {
             mod fuse {
                 #[allow(unused_imports)]
-                use crate::frame::mmm::fuse::test;
+                use $crate::frame::mmm::fuse::test;
                 use proptest::prelude::*;
 
                 #[test]
@@ -170,9 +170,9 @@ pub mod test {
         ($cond:expr, $ker:ty, $ta:ty, $tb:ty, $tc:ty, $ti: ty) => {
             mod fuseq {
                 #[allow(unused_imports)]
-                use crate::frame::mmm::fuse::test;
-                use crate::frame::mmm::fuse::test::{QAwayProblem, QTowardsPlusInfProblem};
-                use crate::frame::mmm::kernel::MatMatMulKer;
+                use $crate::frame::mmm::fuse::test;
+                use $crate::frame::mmm::fuse::test::{QAwayProblem, QTowardsPlusInfProblem};
+                use $crate::frame::mmm::kernel::MatMatMulKer;
                 use num_traits::AsPrimitive;
                 use proptest::prelude::*;
 
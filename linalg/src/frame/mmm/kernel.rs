@@ -56,6 +56,24 @@ macro_rules! test_mmm_kernel_f32 {
     };
 }
 
+#[macro_export]
+macro_rules! test_mmm_kernel_f16 {
+    ($k: ty, $id: ident, $cond: expr) => {
+        #[cfg(test)]
+        #[allow(non_snake_case)]
+        mod $id {
+            mmm_kernel_tests!(
+                $cond,
+                $k,
+                tract_data::prelude::f16,
+                tract_data::prelude::f16,
+                tract_data::prelude::f16,
+                f32
+            );
+        }
+    };
+}
+
 #[macro_export]
 macro_rules! test_mmm_kernel_i8 {
     ($k: ty, $id: ident, $cond: expr) => {
@@ -121,7 +139,7 @@ macro_rules! test_mmm_kernel_u8 {
 
 #[cfg(test)]
 #[macro_use]
-pub mod test {
+pub(crate) mod test {
     use super::*;
     use num_traits::{AsPrimitive, One, Zero};
     use proptest::collection::vec;
@@ -146,9 +164,9 @@ pub mod test {
                 use num_traits::Zero;
                 use proptest::prelude::*;
                 #[allow(unused_imports)]
-                use crate::frame::mmm::kernel::test;
-                use crate::frame::mmm::kernel::test::{ PackedOffsetsProblem, PackedPackedProblem };
-                use crate::frame::mmm::MatMatMulKer;
+                use $crate::frame::mmm::kernel::test;
+                use $crate::frame::mmm::kernel::test::{ PackedOffsetsProblem, PackedPackedProblem };
+                use $crate::frame::mmm::MatMatMulKer;
 
                 proptest::proptest! {
                     #[test]
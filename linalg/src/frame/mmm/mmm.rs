@@ -1,6 +1,7 @@
 use super::ScratchSpaceFusedNonLinear;
 use super::*;
 use crate::frame::Packer;
+use crate::multithread::*;
 use num_traits::{AsPrimitive, Bounded, Zero};
 use std::fmt;
 use std::fmt::Debug;
@@ -17,6 +18,9 @@ pub trait MatMatMul:
 
     fn internal_type(&self) -> DatumType;
 
+    /// The same multiplier, for another n (the count of B columns).
+    fn with_n(&self, n: usize) -> Box<dyn MatMatMul>;
+
     unsafe fn a_packed(&self, dt: DatumType) -> MatrixStoreSpec;
 
     unsafe fn b_packed(&self, dt: DatumType) -> MatrixStoreSpec;
@@ -151,6 +155,10 @@ where
         TI::datum_type()
     }
 
+    fn with_n(&self, n: usize) -> Box<dyn MatMatMul> {
+        Box::new(MatMatMulImpl::<K, TC, TI> { n, ..self.clone() })
+    }
+
     unsafe fn a_packed(&self, dt: DatumType) -> MatrixStoreSpec {
         MatrixStoreSpec::Packed { panel_bytes: (self.k * K::mr() * dt.size_of()) }
     }
@@ -168,15 +176,14 @@ where
         debug_assert!(rows_offsets.len() > 0);
         debug_assert!(cols_offsets.len() > 0);
         // repeat the last offset to get to the panel boundary (pad to next multiple of nr)
-        let wanted = (cols_offsets.len() + K::nr() - 1) / K::nr() * K::nr();
+        let wanted = cols_offsets.len().div_ceil(K::nr()) * K::nr();
         let mut col_byte_offsets: Vec<_> =
             cols_offsets.iter().map(|o| o * dt.size_of() as isize).collect();
         while col_byte_offsets.len() < wanted {
             col_byte_offsets.push(*col_byte_offsets.last().unwrap());
         }
         // repeat the last offset four times to simplify kernel loop unrolling
-        let mut row_byte_offsets: Vec<_> = Vec::with_capacity(rows_offsets.len() + 4);
-        row_byte_offsets.set_len(rows_offsets.len() + 4);
+        let mut row_byte_offsets: Vec<isize> = vec![0; rows_offsets.len() + 4];
         for i in 0..rows_offsets.len() {
             *row_byte_offsets.get_unchecked_mut(i) =
                 *rows_offsets.get_unchecked(i) * dt.size_of() as isize;
@@ -254,114 +261,22 @@ where
         non_linear: &[FusedSpec],
     ) -> anyhow::Result<()> {
         use anyhow::Context;
-        let mr = K::mr();
-        let nr = K::nr();
-        let m = self.m;
-        let n = self.n;
         let scratch = scratch
             .downcast_mut::<ScratchSpaceFusedNonLinear<TI>>()
             .context("Wrong scratch space type")?;
-
-        let ref linear = LinearSpec::k(self.k);
-        for ia in 0..m / mr {
-            let ref a = a.panel_a(ia);
-            if let MatrixStore::VecStride { .. } = c {
-                let ref b = b.panel_b(nr, 0, n % nr);
-                self.prefetch(a, b);
-                scratch.clear();
-                let non_linear = scratch.for_tile::<TC, K>(&non_linear, ia, 0, c);
-                let ref direct_c = c.tile_c(ia, 0);
-                let err = K::kernel(&MatMatMulKerSpec {
-                    a: a as _,
-                    b: b as _,
-                    c: direct_c as _,
-                    linear,
-                    non_linear,
-                });
-                debug_assert_eq!(err, 0, "Kernel return error {}", err);
-            } else {
-                for ib in 0..n / nr {
-                    let ref b = b.panel_b(nr, ib, nr);
-                    self.prefetch(a, b);
-                    scratch.clear();
-                    let ref direct_c = c.tile_c(ia, ib);
-                    let non_linear = scratch.for_tile::<TC, K>(&non_linear, ia, ib, c);
-                    let err = K::kernel(&MatMatMulKerSpec {
-                        a: a as _,
-                        b: b as _,
-                        c: direct_c as _,
-                        linear,
-                        non_linear,
-                    });
-                    debug_assert_eq!(err, 0, "Kernel return error {}", err);
-                }
-                if n % nr != 0 {
-                    let ref b = b.panel_b(nr, n / nr, n % nr);
-                    self.prefetch(a, b);
-                    scratch.clear();
-                    let tmpc = scratch.tmp_tile_c(TC::datum_type(), mr, nr);
-                    let non_linear = scratch.for_tile::<TC, K>(&non_linear, ia, n / nr, c);
-                    let err = K::kernel(&MatMatMulKerSpec {
-                        a: a as _,
-                        b: b as _,
-                        c: &tmpc,
-                        linear,
-                        non_linear,
-                    });
-                    debug_assert_eq!(err, 0, "Kernel return error {}", err);
-                    c.set_from_tile::<TC>(ia, n / nr, mr, n % nr, &tmpc);
-                }
+        let tiles_down = self.m.div_ceil(K::mr());
+        let tiles_right =
+            if let MatrixStore::VecStride { .. } = c { 1 } else { self.n.div_ceil(K::nr()) };
+        match current_tract_executor() {
+            #[cfg(feature = "multithread-mm")]
+            Executor::MultiThread(pool) if tiles_down * tiles_right > 1 => {
+                self.run_tiles_in_pool(&pool, tiles_down, tiles_right, a, b, c, non_linear);
             }
-        }
-        if m % mr != 0 {
-            let ref panel_a = a.panel_a(m / mr);
-            if let MatrixStore::VecStride { .. } = c {
-                let ref b = b.panel_b(nr, 0, nr);
-                self.prefetch(panel_a, b);
-                scratch.clear();
-                let tmpc = scratch.tmp_tile_c(TC::datum_type(), mr, nr);
-                let non_linear = scratch.for_tile::<TC, K>(&non_linear, m / mr, 0, c);
-                let err = K::kernel(&MatMatMulKerSpec {
-                    a: panel_a as _,
-                    b: b as _,
-                    c: &tmpc,
-                    linear,
-                    non_linear,
-                });
-                debug_assert_eq!(err, 0, "Kernel return error {}", err);
-                c.set_from_tile::<TC>(m / mr, 0, m % mr, nr, &tmpc);
-            } else {
-                for ib in 0..n / nr {
-                    let ref b = b.panel_b(nr, ib, nr);
-                    self.prefetch(panel_a, b);
-                    scratch.clear();
-                    let tmpc = scratch.tmp_tile_c(TC::datum_type(), mr, nr);
-                    let non_linear = scratch.for_tile::<TC, K>(&non_linear, m / mr, ib, c);
-                    let err = K::kernel(&MatMatMulKerSpec {
-                        a: panel_a as _,
-                        b: b as _,
-                        c: &tmpc,
-                        linear,
-                        non_linear,
-                    });
-                    debug_assert_eq!(err, 0, "Kernel return error {}", err);
-                    c.set_from_tile::<TC>(m / mr, ib, m % mr, nr, &tmpc);
-                }
-                if n % nr != 0 {
-                    let ref b = b.panel_b(nr, n / nr, n % nr);
-                    self.prefetch(panel_a, b);
-                    scratch.clear();
-                    let tmpc = scratch.tmp_tile_c(TC::datum_type(), mr, nr);
-                    let non_linear = scratch.for_tile::<TC, K>(&non_linear, m / mr, n / nr, c);
-                    let err = K::kernel(&MatMatMulKerSpec {
-                        a: panel_a as _,
-                        b: b as _,
-                        c: &tmpc,
-                        linear,
-                        non_linear,
-                    });
-                    debug_assert_eq!(err, 0, "Kernel return error {}", err);
-                    c.set_from_tile::<TC>(m / mr, n / nr, m % mr, n % nr, &tmpc);
+            _ => {
+                for ia in 0..tiles_down {
+                    for ib in 0..tiles_right {
+                        self.run_tile(scratch, a, b, c, non_linear, ia, ib);
+                    }
                 }
             }
         }
@@ -369,6 +284,111 @@ where
     }
 }
 
+impl<K, TC, TI> MatMatMulImpl<K, TC, TI>
+where
+    TC: Datum + Copy + Debug + 'static + Bounded + AsPrimitive<TI>,
+    TI: Datum + Copy + Add + Mul<Output = TI> + Zero + Debug + 'static + Neg<Output = TI>,
+    K: MatMatMulKer<TI> + 'static,
+    i32: AsPrimitive<TI>,
+    usize: AsPrimitive<TI>,
+{
+    #[cfg(feature = "multithread-mm")]
+    unsafe fn run_tiles_in_pool(
+        &self,
+        pool: &rayon::ThreadPool,
+        tiles_down: usize,
+        tiles_right: usize,
+        a: &MatrixStore,
+        b: &MatrixStore,
+        c: &MatrixStore,
+        non_linear: &[FusedSpec],
+    ) {
+        use rayon::prelude::*;
+        // Stores are raw pointer bundles. Every tile writes to its own
+        // region of c, so sharing them across workers is sound.
+        struct Shared<'a, 's, 't>(
+            &'a MatrixStore<'s, 't>,
+            &'a MatrixStore<'s, 't>,
+            &'a MatrixStore<'s, 't>,
+            &'a [FusedSpec<'a>],
+        );
+        unsafe impl<'a, 's, 't> Sync for Shared<'a, 's, 't> {}
+        let shared = Shared(a, b, c, non_linear);
+        pool.install(|| {
+            (0..tiles_down * tiles_right).into_par_iter().for_each_init(
+                || ScratchSpaceFusedNonLinear::<TI>::default(),
+                |scratch, tile| {
+                    let Shared(a, b, c, non_linear) = &shared;
+                    self.run_tile(
+                        scratch,
+                        a,
+                        b,
+                        c,
+                        non_linear,
+                        tile / tiles_right,
+                        tile % tiles_right,
+                    )
+                },
+            )
+        })
+    }
+
+    #[inline]
+    unsafe fn run_tile(
+        &self,
+        scratch: &mut ScratchSpaceFusedNonLinear<TI>,
+        a: &MatrixStore,
+        b: &MatrixStore,
+        c: &MatrixStore,
+        non_linear: &[FusedSpec],
+        ia: usize,
+        ib: usize,
+    ) {
+        let mr = K::mr();
+        let nr = K::nr();
+        let m = self.m;
+        let n = self.n;
+        let linear = &LinearSpec::k(self.k);
+        let panel_a = &a.panel_a(ia);
+        let full_down = ia < m / mr;
+        let height = if full_down { mr } else { m % mr };
+        let (ref b, width) = if let MatrixStore::VecStride { .. } = c {
+            (b.panel_b(nr, 0, if full_down { n % nr } else { nr }), nr)
+        } else if ib < n / nr {
+            (b.panel_b(nr, ib, nr), nr)
+        } else {
+            (b.panel_b(nr, ib, n % nr), n % nr)
+        };
+        self.prefetch(panel_a, b);
+        scratch.clear();
+        let direct = full_down && (width == nr || matches!(c, MatrixStore::VecStride { .. }));
+        if direct {
+            let direct_c = &c.tile_c(ia, ib);
+            let non_linear = scratch.for_tile::<TC, K>(&non_linear, ia, ib, c);
+            let err = K::kernel(&MatMatMulKerSpec {
+                a: panel_a as _,
+                b: b as _,
+                c: direct_c as _,
+                linear,
+                non_linear,
+            });
+            debug_assert_eq!(err, 0, "Kernel return error {}", err);
+        } else {
+            let tmpc = scratch.tmp_tile_c(TC::datum_type(), mr, nr);
+            let non_linear = scratch.for_tile::<TC, K>(&non_linear, ia, ib, c);
+            let err = K::kernel(&MatMatMulKerSpec {
+                a: panel_a as _,
+                b: b as _,
+                c: &tmpc,
+                linear,
+                non_linear,
+            });
+            debug_assert_eq!(err, 0, "Kernel return error {}", err);
+            c.set_from_tile::<TC>(ia, ib, height, width, &tmpc);
+        }
+    }
+}
+
 impl<K, TC, TI> fmt::Display for MatMatMulImpl<K, TC, TI>
 where
     TC: Copy + Debug + 'static,
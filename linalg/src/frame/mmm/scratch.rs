@@ -74,6 +74,7 @@ impl<TI: Copy> ScratchSpaceFusedNonLinear<TI> {
         }
     }
 
+    #[allow(clippy::extra_unused_type_parameters)]
     unsafe fn fused_for_tile<TC, K: MatMatMulKer<TI>>(
         &mut self,
         specs: &[FusedSpec],
@@ -183,7 +183,7 @@ impl<'s, 't> MatrixStore<'s, 't> {
         match spec {
             MatrixStoreSpec::View { axes, .. } => {
                 let (m_axis, n_axis) = if let Some(axes) = axes {
-                    axes.clone()
+                    *axes
                 } else {
                     let rank = tensor.rank();
                     (rank - 2, rank - 1)
@@ -241,7 +241,7 @@ impl<'s, 't> MatrixStore<'s, 't> {
 
     #[inline]
     pub(super) unsafe fn set_from_tile<T: Datum + Copy>(
-        &mut self,
+        &self,
         down: usize,
         right: usize,
         height: usize,
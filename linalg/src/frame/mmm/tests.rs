@@ -317,7 +317,7 @@ where
                 for i in 0..k {
                     let a: TI = a.as_slice::<TA>().unwrap()[i + k * y].as_();
                     let b: TI = b.as_slice::<TB>().unwrap()[x + i * n].as_();
-                    v = v + a * b;
+                    v += a * b;
                 }
                 expected.as_slice_mut::<TC>().unwrap()[x + y * n] = v.as_();
             }
@@ -366,7 +366,7 @@ where
             for i in 0..k {
                 let a: TI = a.as_slice::<TA>().unwrap()[i + k * y].as_();
                 let b: TI = b.as_slice::<TB>().unwrap()[i].as_();
-                inter = inter + a * b;
+                inter += a * b;
             }
             expected.as_slice_mut().unwrap()[y] = inter.as_();
         }
@@ -413,7 +413,7 @@ where
         op.run(
             &op.a_packed(TA::datum_type()).wrap(&packed_a.view()),
             &op.b_vec_from_data(TB::datum_type()).wrap(&b.view()),
-            &mut op.c_vec_from_data().wrap(&mut found.view_mut()),
+            &mut op.c_vec_from_data().wrap(&found.view_mut()),
             spec,
         )
         .unwrap();
@@ -421,7 +421,7 @@ where
         op.run(
             &op.a_packed(TA::datum_type()).wrap(&packed_a.view()),
             &op.b_packed(TB::datum_type()).wrap(&packed_b.view()),
-            &mut op.c_from_data_and_strides(n as isize, 1).wrap(&mut found.view_mut()),
+            &mut op.c_from_data_and_strides(n as isize, 1).wrap(&found.view_mut()),
             spec,
         )
         .unwrap();
@@ -617,7 +617,7 @@ impl<TA: LADatum, TB: LADatum> ConvProblem<TA, TB> {
     }
     // this is not n, but the T in NTC of input to direct convolution
     pub fn input_width(&self) -> usize {
-        assert!(self.data.len() % self.ci == 0);
+        assert!(self.data.len().is_multiple_of(self.ci));
         self.data.len() / self.ci
     }
     pub fn output_width(&self) -> usize {
@@ -660,8 +660,8 @@ impl<TA: LADatum, TB: LADatum> ConvProblem<TA, TB> {
                             [ici * self.kt + ikt + self.ci * self.kt * ico];
                         let d = self.data.as_slice::<TB>().unwrap()
                             [x * self.stride + ikt * self.dilation + ici * self.input_width()];
-                        let ref mut pv =
-                            expect.as_slice_mut::<TI>().unwrap()[x + ico * self.output_width()];
+                        let pv = &mut expect.as_slice_mut::<TI>().unwrap()
+                            [x + ico * self.output_width()];
                         *pv += f.as_() * d.as_();
                     }
                 }
@@ -28,6 +28,11 @@ impl Packer {
         (n.div_ceil(self.r) * (self.k + self.end_padding_record)) * self.r
     }
 
+    /// Same as `len`, for a possibly symbolic n.
+    pub fn len_dim<D: DimLike>(&self, n: &D) -> D {
+        n.div_ceil(self.r) * ((self.k + self.end_padding_record) * self.r)
+    }
+
     unsafe fn pack_t<'p, 'i, T: Datum + Copy>(
         &self,
         pb: &mut TensorView<'p>,
@@ -126,7 +131,7 @@ where
     T: Copy + std::fmt::Debug,
 {
     pub fn new(data: &'p mut [T], panel_width: usize, mn: usize, k: usize) -> KOutWriter<'p, T> {
-        let panels = (mn + panel_width - 1) / panel_width;
+        let panels = mn.div_ceil(panel_width);
         let last_panel_width = mn - (panels - 1) * panel_width;
         KOutWriter {
             ptr: data.as_mut_ptr(),
@@ -189,7 +194,7 @@ where
     T: Copy + Debug,
 {
     pub fn new(data: &'p mut [T], panel_width: usize, mn: usize, k: usize) -> KInWriter<'p, T> {
-        let panels = (mn + panel_width - 1) / panel_width;
+        let panels = mn.div_ceil(panel_width);
         let last_panel_width = mn - (panels - 1) * panel_width;
         KInWriter {
             ptr: data.as_mut_ptr(),
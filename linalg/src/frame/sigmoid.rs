@@ -3,6 +3,7 @@
 pub mod test {
     use crate::frame::element_wise::*;
     use proptest::test_runner::TestCaseResult;
+    use tract_data::prelude::f16;
 
     #[macro_export]
     macro_rules! sigmoid_frame_tests {
@@ -11,7 +12,7 @@ pub mod test {
                 #[test]
                 fn sigmoid(xs in proptest::collection::vec(-25f32..25.0, 0..100)) {
                     if $cond {
-                        crate::frame::sigmoid::test::test_sigmoid::<$ker>(&*xs).unwrap()
+                        $crate::frame::sigmoid::test::test_sigmoid::<$ker>(&*xs).unwrap()
                     }
                 }
             }
@@ -19,7 +20,7 @@ pub mod test {
             #[test]
             fn sigmoid_4_magic() {
                 if $cond {
-                    crate::frame::sigmoid::test::test_sigmoid::<$ker>(&[0f32, -20.0, 20.0, 0.0])
+                    $crate::frame::sigmoid::test::test_sigmoid::<$ker>(&[0f32, -20.0, 20.0, 0.0])
                         .unwrap()
                 }
             }
@@ -27,21 +28,21 @@ pub mod test {
             #[test]
             fn sigmoid_4zeros() {
                 if $cond {
-                    crate::frame::sigmoid::test::test_sigmoid::<$ker>(&[0.0; 4]).unwrap();
+                    $crate::frame::sigmoid::test::test_sigmoid::<$ker>(&[0.0; 4]).unwrap();
                 }
             }
 
             #[test]
             fn sigmoid_20_ones() {
                 if $cond {
-                    crate::frame::sigmoid::test::test_sigmoid::<$ker>(&[1.0; 20]).unwrap();
+                    $crate::frame::sigmoid::test::test_sigmoid::<$ker>(&[1.0; 20]).unwrap();
                 }
             }
 
             #[test]
             fn sigmoid_18_zeros() {
                 if $cond {
-                    crate::frame::sigmoid::test::test_sigmoid::<$ker>(&[0.0; 18]).unwrap();
+                    $crate::frame::sigmoid::test::test_sigmoid::<$ker>(&[0.0; 18]).unwrap();
                 }
             }
         };
@@ -57,4 +58,13 @@ pub mod test {
         let expected = values.iter().map(|x| 1.0 / (1.0 + (-x).exp())).collect::<Vec<_>>();
         crate::test::check_close(&found[..values.len()], &*expected)
     }
+
+    pub fn test_sigmoid_f16<K: ElementWiseKer<f16>>(values: &[f32]) -> TestCaseResult {
+        let op = ElementWiseImpl::<K, f16>::new();
+        let mut found = values.iter().map(|x| f16::from(*x)).collect::<Vec<_>>();
+        op.run(&mut found).unwrap();
+        let expected =
+            values.iter().map(|x| f16::from(1.0 / (1.0 + (-x).exp()))).collect::<Vec<_>>();
+        crate::test::check_close(&*found, &*expected)
+    }
 }
@@ -3,6 +3,7 @@
 pub mod test {
     use crate::frame::element_wise::*;
     use proptest::test_runner::TestCaseResult;
+    use tract_data::prelude::f16;
 
     #[macro_export]
     macro_rules! tanh_frame_tests {
@@ -11,7 +12,7 @@ pub mod test {
                 #[test]
                 fn tanh(xs in proptest::collection::vec(-25f32..25.0, 0..100)) {
                     if $cond {
-                        crate::frame::tanh::test::test_tanh::<$ker>(&*xs).unwrap()
+                        $crate::frame::tanh::test::test_tanh::<$ker>(&*xs).unwrap()
                     }
                 }
             }
@@ -19,28 +20,28 @@ pub mod test {
             #[test]
             fn tanh_4_magic() {
                 if $cond {
-                    crate::frame::tanh::test::test_tanh::<$ker>(&[0f32, -20.0, 20.0, 0.0]).unwrap()
+                    $crate::frame::tanh::test::test_tanh::<$ker>(&[0f32, -20.0, 20.0, 0.0]).unwrap()
                 }
             }
 
             #[test]
             fn tanh_4zeros() {
                 if $cond {
-                    crate::frame::tanh::test::test_tanh::<$ker>(&[0.0; 4]).unwrap();
+                    $crate::frame::tanh::test::test_tanh::<$ker>(&[0.0; 4]).unwrap();
                 }
             }
 
             #[test]
             fn tanh_20_ones() {
                 if $cond {
-                    crate::frame::tanh::test::test_tanh::<$ker>(&[1.0; 20]).unwrap();
+                    $crate::frame::tanh::test::test_tanh::<$ker>(&[1.0; 20]).unwrap();
                 }
             }
 
             #[test]
             fn tanh_18_zeros() {
                 if $cond {
-                    crate::frame::tanh::test::test_tanh::<$ker>(&[0.0; 18]).unwrap();
+                    $crate::frame::tanh::test::test_tanh::<$ker>(&[0.0; 18]).unwrap();
                 }
             }
         };
@@ -53,4 +54,12 @@ pub mod test {
         let expected = values.iter().map(|x| x.tanh()).collect::<Vec<_>>();
         crate::test::check_close(&*found, &*expected)
     }
+
+    pub fn test_tanh_f16<K: ElementWiseKer<f16>>(values: &[f32]) -> TestCaseResult {
+        let op = ElementWiseImpl::<K, f16>::new();
+        let mut found = values.iter().map(|x| f16::from(*x)).collect::<Vec<_>>();
+        op.run(&mut found).unwrap();
+        let expected = values.iter().map(|x| f16::from(x.tanh())).collect::<Vec<_>>();
+        crate::test::check_close(&*found, &*expected)
+    }
 }
@@ -21,9 +21,9 @@ impl LutKer for GenericLut8 {
     }
 
     fn run(buf: *mut u8, len: usize, table: *const u8) {
-        debug_assert!(len % Self::n() == 0);
-        debug_assert!(buf as usize % Self::input_alignment_bytes() == 0);
-        debug_assert!(table as usize % Self::table_alignment_bytes() == 0);
+        debug_assert!(len.is_multiple_of(Self::n()));
+        debug_assert!((buf as usize).is_multiple_of(Self::input_alignment_bytes()));
+        debug_assert!((table as usize).is_multiple_of(Self::table_alignment_bytes()));
         for i in 0..((len / 8) as isize) {
             unsafe {
                 let ptr = buf.offset(8 * i);
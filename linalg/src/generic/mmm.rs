@@ -7,6 +7,7 @@ use crate::frame::mmm::PanelStore::*;
 use crate::frame::mmm::*;
 
 use num_traits::sign::Signed;
+use tract_data::prelude::f16;
 
 pub trait PseudoRightShift {
     fn q_away(self, mult: Self, shift: usize) -> Self;
@@ -48,6 +49,18 @@ impl PseudoRightShift for f32 {
     }
 }
 
+impl PseudoRightShift for f16 {
+    fn q_even(self, mult: Self, shift: usize) -> Self {
+        self * mult * f16::from(2f32.powi(-(shift as i32)))
+    }
+    fn q_to_plus_inf(self, mult: Self, shift: usize) -> Self {
+        self * mult * f16::from(2f32.powi(-(shift as i32)))
+    }
+    fn q_away(self, mult: Self, shift: usize) -> Self {
+        self * mult * f16::from(2f32.powi(-(shift as i32)))
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 pub struct GenericMmm4x4<TA, TB, TC, TI>(PhantomData<(TA, TB, TC, TI)>)
 where
@@ -392,7 +405,7 @@ where
                 _ => return 1,
             }
         }
-        return 0;
+        0
     }
 }
 
@@ -656,7 +669,7 @@ where
                 _ => return 1,
             }
         }
-        return 0;
+        0
     }
 }
 
@@ -953,7 +966,7 @@ where
                 _ => return 1,
             }
         }
-        return 0;
+        0
     }
 }
 
@@ -962,12 +975,14 @@ test_mmm_kernel_i8!(crate::generic::mmm::GenericMmm4x4<i8, i8, i8, i32>, test_Ge
 test_mmm_kernel_u8!(crate::generic::mmm::GenericMmm4x4<u8, u8, u8, i32>, test_GenericMmm4x4_u8, true);
 test_mmm_kernel_i8_i32!(crate::generic::mmm::GenericMmm4x4<i8, i8, i32, i32>, test_GenericMmm4x4_i8_i32, true);
 test_mmm_kernel_i8_u8_i32!(crate::generic::mmm::GenericMmm4x4<i8, u8, i32, i32>, test_GenericMmm4x4_i8_u8_i32, true);
+test_mmm_kernel_f16!(crate::generic::mmm::GenericMmm4x4<tract_data::prelude::f16, tract_data::prelude::f16, tract_data::prelude::f16, f32>, test_GenericMmm4x4_f16, true);
 
 test_mmm_kernel_f32!(crate::generic::mmm::GenericMmm4x1<f32, f32, f32, f32>, test_GenericMmm4x1_f32, true);
 test_mmm_kernel_i8!(crate::generic::mmm::GenericMmm4x1<i8, i8, i8, i32>, test_GenericMmm4x1_i8, true);
 test_mmm_kernel_u8!(crate::generic::mmm::GenericMmm4x1<u8, u8, u8, i32>, test_GenericMmm4x1_u8, true);
 test_mmm_kernel_i8_i32!(crate::generic::mmm::GenericMmm4x1<i8, i8, i32, i32>, test_GenericMmm4x1_i8_i32, true);
 test_mmm_kernel_i8_u8_i32!(crate::generic::mmm::GenericMmm4x1<i8, u8, i32, i32>, test_GenericMmm4x1_i8_u8_i32, true);
+test_mmm_kernel_f16!(crate::generic::mmm::GenericMmm4x1<tract_data::prelude::f16, tract_data::prelude::f16, tract_data::prelude::f16, f32>, test_GenericMmm4x1_f16, true);
 
 test_mmm_kernel_f32!(crate::generic::mmm::GenericMmmTest3x2<f32, f32, f32, f32>, test_GenericMmmTest3x2_f32, true);
 test_mmm_kernel_i8!(crate::generic::mmm::GenericMmmTest3x2<i8, i8, i8, i32>, test_GenericMmmTest3x2_i8, true);
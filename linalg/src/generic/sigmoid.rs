@@ -1,4 +1,5 @@
 use crate::frame::element_wise::ElementWiseKer;
+use tract_data::prelude::f16;
 
 const LOW: f32 = -18.0;
 const HIGH: f32 = 18.0;
@@ -57,14 +58,49 @@ impl ElementWiseKer<f32> for SSigmoid4 {
     }
 
     fn run(x: &mut [f32]) {
-        debug_assert!(x.len() % Self::nr() == 0);
-        debug_assert!(x.as_ptr() as usize % Self::alignment_bytes() == 0);
+        debug_assert!(x.len().is_multiple_of(Self::nr()));
+        debug_assert!((x.as_ptr() as usize).is_multiple_of(Self::alignment_bytes()));
         x.iter_mut().for_each(|px| *px = ssigmoid(*px))
     }
 }
 
+/// Half precision sigmoid, computed in single precision.
+#[derive(Clone, Debug)]
+pub struct HSigmoid8;
+
+impl ElementWiseKer<f16> for HSigmoid8 {
+    fn name() -> &'static str {
+        "generic"
+    }
+
+    fn alignment_bytes() -> usize {
+        16
+    }
+
+    fn alignment_items() -> usize {
+        8
+    }
+
+    fn nr() -> usize {
+        8
+    }
+
+    fn run(x: &mut [f16]) {
+        debug_assert!(x.len().is_multiple_of(Self::nr()));
+        debug_assert!((x.as_ptr() as usize).is_multiple_of(Self::alignment_bytes()));
+        x.iter_mut().for_each(|px| *px = ssigmoid(px.0.to_f32()).into())
+    }
+}
+
 #[cfg(test)]
 #[macro_use]
 pub mod test {
     sigmoid_frame_tests!(true, crate::generic::sigmoid::SSigmoid4);
+
+    proptest::proptest! {
+        #[test]
+        fn sigmoid_f16(xs in proptest::collection::vec(-25f32..25.0, 0..100)) {
+            crate::frame::sigmoid::test::test_sigmoid_f16::<super::HSigmoid8>(&*xs).unwrap()
+        }
+    }
 }
@@ -1,4 +1,5 @@
 use crate::frame::element_wise::ElementWiseKer;
+use tract_data::prelude::f16;
 
 const LOW: f32 = -9.0;
 const HIGH: f32 = 9.0;
@@ -55,14 +56,49 @@ impl ElementWiseKer<f32> for STanh4 {
     }
 
     fn run(x: &mut [f32]) {
-        debug_assert!(x.len() % Self::nr() == 0);
-        debug_assert!(x.as_ptr() as usize % Self::alignment_bytes() == 0);
+        debug_assert!(x.len().is_multiple_of(Self::nr()));
+        debug_assert!((x.as_ptr() as usize).is_multiple_of(Self::alignment_bytes()));
         x.iter_mut().for_each(|px| *px = stanh(*px))
     }
 }
 
+/// Half precision tanh, computed in single precision.
+#[derive(Clone, Debug)]
+pub struct HTanh8;
+
+impl ElementWiseKer<f16> for HTanh8 {
+    fn name() -> &'static str {
+        "generic"
+    }
+
+    fn alignment_items() -> usize {
+        8
+    }
+
+    fn alignment_bytes() -> usize {
+        16
+    }
+
+    fn nr() -> usize {
+        8
+    }
+
+    fn run(x: &mut [f16]) {
+        debug_assert!(x.len().is_multiple_of(Self::nr()));
+        debug_assert!((x.as_ptr() as usize).is_multiple_of(Self::alignment_bytes()));
+        x.iter_mut().for_each(|px| *px = stanh(px.0.to_f32()).into())
+    }
+}
+
 #[cfg(test)]
 #[macro_use]
 pub mod test {
     tanh_frame_tests!(true, crate::generic::tanh::STanh4);
+
+    proptest::proptest! {
+        #[test]
+        fn tanh_f16(xs in proptest::collection::vec(-25f32..25.0, 0..100)) {
+            crate::frame::tanh::test::test_tanh_f16::<super::HTanh8>(&*xs).unwrap()
+        }
+    }
 }
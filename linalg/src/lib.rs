@@ -16,6 +16,7 @@ include!(concat!(env!("OUT_DIR"), "/extern_kernel_macro.rs"));
 #[macro_use]
 pub mod frame;
 mod generic;
+pub mod multithread;
 
 #[cfg(target_arch = "x86_64")]
 pub mod x86_64_fma;
@@ -33,6 +34,8 @@ use tract_data::prelude::*;
 pub struct Ops {
     mmm_f32: Box<dyn Fn(usize, usize, usize) -> Box<dyn mmm::MatMatMul> + Send + Sync>,
     mmv_f32: Box<dyn Fn(usize, usize) -> Box<dyn mmm::MatMatMul> + Send + Sync>,
+    mmm_f16: Box<dyn Fn(usize, usize, usize) -> Box<dyn mmm::MatMatMul> + Send + Sync>,
+    mmv_f16: Box<dyn Fn(usize, usize) -> Box<dyn mmm::MatMatMul> + Send + Sync>,
     qmmm_i8_i32: Box<dyn Fn(usize, usize, usize) -> Box<dyn mmm::MatMatMul> + Send + Sync>,
     qmmm_u8_i32: Box<dyn Fn(usize, usize, usize) -> Box<dyn mmm::MatMatMul> + Send + Sync>,
     qmmm_u8_u8: Box<dyn Fn(usize, usize, usize) -> Box<dyn mmm::MatMatMul> + Send + Sync>,
@@ -40,6 +43,8 @@ pub struct Ops {
     qmmm_i8_u8_i32: Box<dyn Fn(usize, usize, usize) -> Box<dyn mmm::MatMatMul> + Send + Sync>,
     pub sigmoid_f32: Box<dyn Fn() -> Box<dyn element_wise::ElementWise<f32>> + Send + Sync>,
     pub tanh_f32: Box<dyn Fn() -> Box<dyn element_wise::ElementWise<f32>> + Send + Sync>,
+    pub sigmoid_f16: Box<dyn Fn() -> Box<dyn element_wise::ElementWise<f16>> + Send + Sync>,
+    pub tanh_f16: Box<dyn Fn() -> Box<dyn element_wise::ElementWise<f16>> + Send + Sync>,
     pub lut_u8: Box<dyn Fn(&[u8]) -> Box<dyn lut::Lut> + Send + Sync>,
     pub(crate) prefetch: Option<&'static (dyn Fn(*const u8, usize) + Sync + Send)>,
 }
@@ -59,6 +64,9 @@ impl Ops {
             (F32, F32, F32) => {
                 Some(if n == 1 { (self.mmv_f32)(m, k) } else { (self.mmm_f32)(m, k, n) })
             }
+            (F16, F16, F16) => {
+                Some(if n == 1 { (self.mmv_f16)(m, k) } else { (self.mmm_f16)(m, k, n) })
+            }
             (I8, I8, I32) => Some((self.qmmm_i8_i32)(m, k, n)),
             (U8, U8, I32) => Some((self.qmmm_u8_i32)(m, k, n)),
             (I8, I8, I8) => Some((self.qmmm_i8_i8)(m, k, n)),
@@ -85,6 +93,21 @@ pub fn generic() -> Ops {
                 ),
             )
         }),
+        // f16 products are accumulated in f32: long f16 sums lose precision and overflow
+        mmm_f16: Box::new(|m, k, n| {
+            Box::new(
+                mmm::MatMatMulImpl::<generic::GenericMmm4x4<f16, f16, f16, f32>, f16, f32>::new(
+                    m, k, n,
+                ),
+            )
+        }),
+        mmv_f16: Box::new(|m, k| {
+            Box::new(
+                mmm::MatMatMulImpl::<generic::GenericMmm4x1<f16, f16, f16, f32>, f16, f32>::new(
+                    m, k, 1,
+                ),
+            )
+        }),
         qmmm_i8_i32: Box::new(|m, k, n| {
             Box::new(mmm::MatMatMulImpl::<generic::GenericMmm4x4<i8, i8, i32, i32>, i32, i32>::new(
                 m, k, n,
@@ -116,6 +139,12 @@ pub fn generic() -> Ops {
         tanh_f32: Box::new(|| {
             Box::new(element_wise::ElementWiseImpl::<generic::STanh4, f32>::new())
         }),
+        sigmoid_f16: Box::new(|| {
+            Box::new(element_wise::ElementWiseImpl::<generic::HSigmoid8, f16>::new())
+        }),
+        tanh_f16: Box::new(|| {
+            Box::new(element_wise::ElementWiseImpl::<generic::HTanh8, f16>::new())
+        }),
         lut_u8: Box::new(|table: &[u8]| Box::new(lut::LutImpl::<generic::GenericLut8>::new(table))),
         prefetch: None,
     }
@@ -130,7 +159,7 @@ pub fn best() -> Ops {
     arm32::plug(&mut ops);
     #[cfg(target_arch = "aarch64")]
     arm64::plug(&mut ops);
-    return ops;
+    ops
 }
 
 lazy_static::lazy_static! {
@@ -180,6 +209,16 @@ mod test {
         }
     }
 
+    impl LADatum for tract_data::prelude::f16 {
+        fn strat() -> BoxedStrategy<Self> {
+            // quarters in -1..1: products and sums of small problems are exact in f16
+            (-4isize..4).prop_map(|i| (i as f32 / 4.0).into()).boxed()
+        }
+        fn close(&self, other: &Self) -> bool {
+            (self.0.to_f32() - other.0.to_f32()).abs() < 0.01
+        }
+    }
+
     impl LADatum for u8 {
         fn strat() -> BoxedStrategy<Self> {
             any::<u8>().boxed()
@@ -0,0 +1,128 @@
+use std::cell::RefCell;
+use std::sync::Mutex;
+
+#[cfg(feature = "multithread-mm")]
+use std::sync::Arc;
+
+/// Strategy for running the tiles of a matrix multiplication.
+///
+/// The default executor runs everything on the calling thread. With the
+/// `multithread-mm` feature, a rayon pool can be used to split the tile loops
+/// of large products across several workers. Each tile is computed by the
+/// same kernel in both cases, so results are bit-identical.
+#[derive(Debug, Clone, Default)]
+pub enum Executor {
+    #[default]
+    SingleThread,
+    #[cfg(feature = "multithread-mm")]
+    MultiThread(Arc<rayon::ThreadPool>),
+}
+
+impl Executor {
+    #[cfg(feature = "multithread-mm")]
+    pub fn multithread(n: usize) -> tract_data::anyhow::Result<Executor> {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .thread_name(|n| format!("tract-mmm-{}", n))
+            .num_threads(n)
+            .build()?;
+        Ok(Executor::MultiThread(Arc::new(pool)))
+    }
+
+    pub fn is_multithread(&self) -> bool {
+        !matches!(self, Executor::SingleThread)
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref DEFAULT_EXECUTOR: Mutex<Executor> = Mutex::new(Executor::SingleThread);
+}
+
+thread_local! {
+    static TLS_EXECUTOR_OVERRIDE: RefCell<Option<Executor>> = const { RefCell::new(None) };
+}
+
+/// Executor used by MatMatMul::run when none is set for the current scope.
+pub fn set_default_executor(executor: Executor) {
+    *DEFAULT_EXECUTOR.lock().unwrap() = executor;
+}
+
+/// Executor currently in effect on this thread.
+pub fn current_tract_executor() -> Executor {
+    if let Some(over_ride) = TLS_EXECUTOR_OVERRIDE.with(|global| global.borrow().clone()) {
+        over_ride
+    } else {
+        DEFAULT_EXECUTOR.lock().unwrap().clone()
+    }
+}
+
+/// Run `f` with `executor` overriding the default one on the current thread.
+pub fn multithread_tract_scope<R, F: FnOnce() -> R>(executor: Executor, f: F) -> R {
+    struct Restore(Option<Executor>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            TLS_EXECUTOR_OVERRIDE.with(|tls| *tls.borrow_mut() = previous);
+        }
+    }
+    let previous = TLS_EXECUTOR_OVERRIDE.with(|tls| tls.replace(Some(executor)));
+    let _restore = Restore(previous);
+    f()
+}
+
+#[cfg(all(test, feature = "multithread-mm"))]
+mod test {
+    use super::*;
+    use crate::frame::mmm::*;
+    use crate::generic::GenericMmm4x4;
+    use tract_data::internal::*;
+
+    fn run_product(m: usize, k: usize, n: usize) -> Tensor {
+        let a = tensor1(&(0..m * k).map(|x| (x as f32).sin()).collect::<Vec<_>>())
+            .into_shape(&[m, k])
+            .unwrap();
+        let b = tensor1(&(0..k * n).map(|x| (x as f32).cos()).collect::<Vec<_>>())
+            .into_shape(&[k, n])
+            .unwrap();
+        let op = MatMatMulImpl::<GenericMmm4x4<f32, f32, f32, f32>, f32, f32>::new(m, k, n);
+        unsafe {
+            let mut packed_a = Tensor::uninitialized_aligned::<f32>(
+                &[op.a_pack().len(m)],
+                op.a_pack().alignment(),
+            )
+            .unwrap();
+            op.a_pack().pack(packed_a.view_mut(), a.view(), 1, 0);
+            let mut packed_b = Tensor::uninitialized_aligned::<f32>(
+                &[op.b_pack().len(n)],
+                op.b_pack().alignment(),
+            )
+            .unwrap();
+            op.b_pack().pack(packed_b.view_mut(), b.view(), 0, 1);
+            let mut c = Tensor::zero::<f32>(&[m, n]).unwrap();
+            op.run(
+                &op.a_packed(f32::datum_type()).wrap(&packed_a.view()),
+                &op.b_packed(f32::datum_type()).wrap(&packed_b.view()),
+                &mut op.c_from_data_and_strides(n as isize, 1).wrap(&c.view_mut()),
+                &[],
+            )
+            .unwrap();
+            c
+        }
+    }
+
+    #[test]
+    fn executor_restored_after_panic() {
+        let result = std::panic::catch_unwind(|| {
+            multithread_tract_scope(Executor::multithread(2).unwrap(), || panic!("in scope"))
+        });
+        assert!(result.is_err());
+        assert!(!current_tract_executor().is_multithread());
+    }
+
+    #[test]
+    fn multithread_is_bit_identical() {
+        let single = run_product(37, 19, 23);
+        let multi =
+            multithread_tract_scope(Executor::multithread(4).unwrap(), || run_product(37, 19, 23));
+        assert_eq!(single, multi);
+    }
+}
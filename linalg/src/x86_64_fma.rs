@@ -1,7 +1,9 @@
 use crate::frame::MatMatMulImpl;
 use crate::frame::ElementWiseImpl;
 use crate::Ops;
+use tract_data::prelude::f16;
 
+pub mod f16c;
 pub mod mmm;
 pub mod sigmoid;
 pub mod tanh;
@@ -17,6 +19,18 @@ pub fn plug(ops: &mut Ops) {
         ops.sigmoid_f32 = Box::new(|| Box::new(ElementWiseImpl::<sigmoid::SigmoidF32, f32>::new()));
         ops.tanh_f32 = Box::new(|| Box::new(ElementWiseImpl::<tanh::TanhF32, f32>::new()));
         log::info!("mmm_f32, sigmoid_f32, tang32: x86_64/fma activated");
+        if is_x86_feature_detected!("f16c") {
+            ops.mmm_f16 = Box::new(|m, k, n| {
+                Box::new(MatMatMulImpl::<mmm::MatMatMulF16x16x6, f16, f32>::new(m, k, n))
+            });
+            ops.mmv_f16 = Box::new(|m, k| {
+                Box::new(MatMatMulImpl::<mmm::MatMatMulF16x16x6, f16, f32>::new(m, k, 1))
+            });
+            ops.sigmoid_f16 =
+                Box::new(|| Box::new(ElementWiseImpl::<sigmoid::SigmoidF16, f16>::new()));
+            ops.tanh_f16 = Box::new(|| Box::new(ElementWiseImpl::<tanh::TanhF16, f16>::new()));
+            log::info!("mmm_f16, sigmoid_f16, tanh_f16: x86_64/f16c activated");
+        }
     }
     if is_x86_feature_detected!("avx2") {
         ops.qmmm_i8_i8 = Box::new(|m, k, n| {
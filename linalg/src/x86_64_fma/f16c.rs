@@ -0,0 +1,44 @@
+use tract_data::prelude::f16;
+
+extern_kernel!(fn fma_f16_to_f32(src: *const f16, dst: *mut f32, count: usize) -> ());
+extern_kernel!(fn fma_f32_to_f16(src: *const f32, dst: *mut f16, count: usize) -> ());
+
+const CHUNK: usize = 64;
+
+#[repr(C, align(32))]
+struct Scratch([f32; CHUNK]);
+
+/// Runs a single precision kernel on a half precision buffer, converting by chunks with F16C.
+///
+/// `buf` length must be a multiple of 8.
+pub fn run_as_f32(buf: &mut [f16], kernel: impl Fn(&mut [f32])) {
+    let mut scratch = Scratch([0f32; CHUNK]);
+    for chunk in buf.chunks_mut(CHUNK) {
+        let len = chunk.len();
+        unsafe {
+            fma_f16_to_f32(chunk.as_ptr(), scratch.0.as_mut_ptr(), len);
+            kernel(&mut scratch.0[..len]);
+            fma_f32_to_f16(scratch.0.as_ptr(), chunk.as_mut_ptr(), len);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest::proptest! {
+        #[test]
+        fn roundtrip(xs in proptest::collection::vec(-2048i32..2048, 0..40)) {
+            if is_x86_feature_detected!("f16c") {
+                let mut xs: Vec<f16> =
+                    xs.iter().map(|&x| f16::from(x as f32 / 16.0)).collect();
+                xs.truncate(xs.len() / 8 * 8);
+                let expected: Vec<f16> = xs.iter().map(|x| f16::from(x.0.to_f32() * 2.0)).collect();
+                run_as_f32(&mut xs, |buf| buf.iter_mut().for_each(|x| *x *= 2.0));
+                prop_assert_eq!(xs, expected);
+            }
+        }
+    }
+}
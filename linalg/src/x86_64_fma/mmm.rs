@@ -2,6 +2,7 @@ use crate::frame::mmm::*;
 
 extern_kernel!(fn fma_mmm_f32_16x6(op: *const MatMatMulKerSpec<f32>) -> isize);
 extern_kernel!(fn fma_mmm_f32_64x1(op: *const MatMatMulKerSpec<f32>) -> isize);
+extern_kernel!(fn fma_mmm_f16_16x6(op: *const MatMatMulKerSpec<f32>) -> isize);
 extern_kernel!(fn fma_mmm_i8_8x8(op: *const MatMatMulKerSpec<i32>) -> isize);
 
 #[derive(Copy, Clone, Debug)]
@@ -72,6 +73,41 @@ impl MatMatMulKer<f32> for MatMatMulF32x64x1 {
     }
 }
 
+/// Half precision operands and result, converted with F16C and accumulated in single precision.
+#[derive(Copy, Clone, Debug)]
+pub struct MatMatMulF16x16x6;
+
+impl MatMatMulKer<f32> for MatMatMulF16x16x6 {
+    #[inline(always)]
+    fn name() -> &'static str {
+        "f16c"
+    }
+    #[inline(always)]
+    fn mr() -> usize {
+        16
+    }
+    #[inline(always)]
+    fn nr() -> usize {
+        6
+    }
+    fn alignment_bytes_packed_a() -> usize {
+        16
+    }
+    fn alignment_bytes_packed_b() -> usize {
+        2
+    }
+    fn end_padding_packed_a() -> usize {
+        0
+    }
+    fn end_padding_packed_b() -> usize {
+        0
+    }
+    #[inline(never)]
+    fn kernel(spec: &MatMatMulKerSpec<f32>) -> isize {
+        unsafe { fma_mmm_f16_16x6(spec) }
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 pub struct MatMatMulI8x8x8;
 
@@ -152,6 +188,12 @@ test_mmm_kernel_f32!(
     is_x86_feature_detected!("fma")
 );
 
+test_mmm_kernel_f16!(
+    crate::x86_64_fma::mmm::MatMatMulF16x16x6,
+    test_MatMatMulF16x16x6,
+    is_x86_feature_detected!("fma") && is_x86_feature_detected!("f16c")
+);
+
 test_mmm_kernel_i8!(
     crate::x86_64_fma::mmm::MatMatMulI8x8x8,
     test_MatMatMulI8x8x8,
@@ -163,3 +205,77 @@ test_mmm_kernel_i8_i32!(
     test_MatMatMulI8xI32x8x8,
     is_x86_feature_detected!("avx2")
 );
+
+#[cfg(test)]
+mod test_f16 {
+    use crate::frame::mmm::*;
+    use tract_data::internal::*;
+
+    /// Runs (a·b [+ c] + row) * 0.5 with half precision operands.
+    fn fused(m: usize, k: usize, n: usize, add_c: bool) {
+        if !(is_x86_feature_detected!("fma") && is_x86_feature_detected!("f16c")) {
+            return;
+        }
+        let value = |i: usize| f16::from(((i * 7) % 11) as f32 - 5.0);
+        let matrix = |rows: usize, cols: usize, offset: usize| {
+            tensor1(&(0..rows * cols).map(|i| value(i + offset)).collect::<Vec<_>>())
+                .into_shape(&[rows, cols])
+                .unwrap()
+        };
+        let (a, b, c) = (matrix(m, k, 0), matrix(k, n, 3), matrix(m, n, 5));
+        let bias = tensor1(&(0..m).map(|i| i as f32).collect::<Vec<_>>());
+        let scale = tensor0(0.5f32);
+        let mut specs = vec![FusedSpec::PerRowAdd(&bias), FusedSpec::ScalarMul(&scale)];
+        if add_c {
+            specs.insert(0, FusedSpec::AddC);
+        }
+        let op = MatMatMulImpl::<super::MatMatMulF16x16x6, f16, f32>::new(m, k, n);
+        let mut found = c.clone();
+        unsafe {
+            let mut packed_a = Tensor::uninitialized_aligned::<f16>(
+                &[op.a_pack().len(m)],
+                op.a_pack().alignment(),
+            )
+            .unwrap();
+            op.a_pack().pack(packed_a.view_mut(), a.view(), 1, 0);
+            let mut packed_b = Tensor::uninitialized_aligned::<f16>(
+                &[op.b_pack().len(n)],
+                op.b_pack().alignment(),
+            )
+            .unwrap();
+            op.b_pack().pack(packed_b.view_mut(), b.view(), 0, 1);
+            op.run(
+                &op.a_packed(f16::datum_type()).wrap(&packed_a.view()),
+                &op.b_packed(f16::datum_type()).wrap(&packed_b.view()),
+                &mut op.c_from_data_and_strides(n as isize, 1).wrap(&found.view_mut()),
+                &specs,
+            )
+            .unwrap();
+        }
+        let (a, b, c) = (
+            a.as_slice::<f16>().unwrap(),
+            b.as_slice::<f16>().unwrap(),
+            c.as_slice::<f16>().unwrap(),
+        );
+        let expected = (0..m * n)
+            .map(|ix| {
+                let (row, col) = (ix / n, ix % n);
+                let ab: f32 =
+                    (0..k).map(|i| a[row * k + i].0.to_f32() * b[i * n + col].0.to_f32()).sum();
+                let c = if add_c { c[ix].0.to_f32() } else { 0.0 };
+                f16::from((ab + c + row as f32) * 0.5)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(found.as_slice::<f16>().unwrap(), &*expected);
+    }
+
+    #[test]
+    fn fused_full_tiles_add_c() {
+        fused(32, 5, 12, true)
+    }
+
+    #[test]
+    fn fused_border_tiles() {
+        fused(19, 5, 7, false)
+    }
+}
@@ -1,4 +1,5 @@
 use crate::element_wise::ElementWiseKer;
+use tract_data::prelude::f16;
 
 extern_kernel!(fn fma_sigmoid_f32(ptr: *mut f32, count: usize) -> ());
 
@@ -28,7 +29,43 @@ impl ElementWiseKer<f32> for SigmoidF32 {
     }
 }
 
+/// Half precision sigmoid, converted with F16C and computed by the single precision kernel.
+#[derive(Copy, Clone, Debug)]
+pub struct SigmoidF16;
+
+impl ElementWiseKer<f16> for SigmoidF16 {
+    #[inline(always)]
+    fn name() -> &'static str {
+        "fma"
+    }
+    #[inline(always)]
+    fn nr() -> usize {
+        8
+    }
+    #[inline(always)]
+    fn alignment_items() -> usize {
+        8
+    }
+    #[inline(always)]
+    fn alignment_bytes() -> usize {
+        16
+    }
+    #[inline(never)]
+    fn run(buf: &mut [f16]) {
+        super::f16c::run_as_f32(buf, |buf| unsafe { fma_sigmoid_f32(buf.as_mut_ptr(), buf.len()) })
+    }
+}
+
 #[cfg(test)]
 mod test_simd {
     sigmoid_frame_tests!(is_x86_feature_detected!("fma"), crate::x86_64_fma::sigmoid::SigmoidF32);
+
+    proptest::proptest! {
+        #[test]
+        fn sigmoid_f16(xs in proptest::collection::vec(-25f32..25.0, 0..100)) {
+            if is_x86_feature_detected!("fma") && is_x86_feature_detected!("f16c") {
+                crate::frame::sigmoid::test::test_sigmoid_f16::<super::SigmoidF16>(&*xs).unwrap()
+            }
+        }
+    }
 }
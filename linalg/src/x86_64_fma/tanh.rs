@@ -1,4 +1,5 @@
 use crate::frame::element_wise::ElementWiseKer;
+use tract_data::prelude::f16;
 
 extern_kernel!(fn fma_tanh_f32(ptr: *mut f32, count: usize) -> ());
 
@@ -28,7 +29,43 @@ impl ElementWiseKer<f32> for TanhF32 {
     }
 }
 
+/// Half precision tanh, converted with F16C and computed by the single precision kernel.
+#[derive(Copy, Clone, Debug)]
+pub struct TanhF16;
+
+impl ElementWiseKer<f16> for TanhF16 {
+    #[inline(always)]
+    fn name() -> &'static str {
+        "fma"
+    }
+    #[inline(always)]
+    fn nr() -> usize {
+        8
+    }
+    #[inline(always)]
+    fn alignment_items() -> usize {
+        8
+    }
+    #[inline(always)]
+    fn alignment_bytes() -> usize {
+        16
+    }
+    #[inline(never)]
+    fn run(buf: &mut [f16]) {
+        super::f16c::run_as_f32(buf, |buf| unsafe { fma_tanh_f32(buf.as_mut_ptr(), buf.len()) })
+    }
+}
+
 #[cfg(test)]
 mod test_simd {
     tanh_frame_tests!(is_x86_feature_detected!("fma"), crate::x86_64_fma::tanh::TanhF32);
+
+    proptest::proptest! {
+        #[test]
+        fn tanh_f16(xs in proptest::collection::vec(-25f32..25.0, 0..100)) {
+            if is_x86_feature_detected!("fma") && is_x86_feature_detected!("f16c") {
+                crate::frame::tanh::test::test_tanh_f16::<super::TanhF16>(&*xs).unwrap()
+            }
+        }
+    }
 }
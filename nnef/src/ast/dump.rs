@@ -28,7 +28,7 @@ impl<'a> Dumper<'a> {
             writeln!(self.w, "extension {};", ext.join(" "))?;
         }
         if document.extension.len() > 0 {
-            writeln!(self.w, "")?;
+            writeln!(self.w)?;
         }
         self.fragments(&document.fragments)?;
         self.graph_def(&document.graph_def)?;
@@ -99,12 +99,12 @@ impl<'a> Dumper<'a> {
     }
 
     fn type_name(&mut self, name: &TypeName) -> TractResult<()> {
-        let s = match name {
-            &TypeName::Integer => "integer",
-            &TypeName::Scalar => "scalar",
-            &TypeName::Logical => "logical",
-            &TypeName::String => "string",
-            &TypeName::Any => "?",
+        let s = match *name {
+            TypeName::Integer => "integer",
+            TypeName::Scalar => "scalar",
+            TypeName::Logical => "logical",
+            TypeName::String => "string",
+            TypeName::Any => "?",
         };
         write!(self.w, "{}", s)?;
         Ok(())
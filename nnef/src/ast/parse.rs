@@ -7,7 +7,7 @@ use nom::{bytes::complete::*, character::complete::*, combinator::*, multi::*, s
 
 use crate::ast::*;
 
-fn translate_error<'s, E: std::fmt::Debug>(e: E) -> TractError {
+fn translate_error<E: std::fmt::Debug>(e: E) -> TractError {
     format_err!("Fail to parse NNEF document: {:?}", e)
 }
 
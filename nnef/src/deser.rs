@@ -54,7 +54,7 @@ impl<'mb> ModelBuilder<'mb> {
             .iter()
             .find(|f| f.decl.id == "tract_core_properties")
             .and_then(|f| f.body.as_ref())
-            .and_then(|body| body.get(0))
+            .and_then(|body| body.first())
         {
             let properties: TVec<(String, Arc<Tensor>)> =
                 properties.right.resolve(self)?.to(self)?;
@@ -86,7 +86,7 @@ impl<'mb> ModelBuilder<'mb> {
                     values.len()
                 )
             }
-            self.model.node_mut(values[0].node).name = format!("{}", self.naming_scopes.join("."));
+            self.model.node_mut(values[0].node).name = self.naming_scopes.join(".").to_string();
             for (id, outlet) in identifiers.iter().zip(values.iter()) {
                 self.scopes.last_mut().unwrap().insert(id.to_string(), Value::Wire(*outlet));
             }
@@ -153,25 +153,25 @@ impl<'mb> ModelBuilder<'mb> {
                 }
             }
         }
-        if inputs.iter().all(|o| self.model.outlet_fact(*o).unwrap().konst.is_some()) {
-            if op.as_op().is_stateless() {
-                let inputs: TVec<Arc<Tensor>> = inputs
-                    .iter()
-                    .map(|o| self.model.outlet_fact(*o).unwrap().konst.clone().unwrap())
-                    .collect();
-                let outputs = op.eval(inputs)?;
-                let mut outlets = tvec!();
-                for (ix, o) in outputs.into_iter().enumerate() {
-                    outlets.push(
-                        self.model.wire_node(
-                            format!("{}-{}", name, ix),
-                            tract_core::ops::konst::Const::new(o),
-                            &[],
-                        )?[0],
-                    );
-                }
-                return Ok(outlets);
+        if inputs.iter().all(|o| self.model.outlet_fact(*o).unwrap().konst.is_some())
+            && op.as_op().is_stateless()
+        {
+            let inputs: TVec<Arc<Tensor>> = inputs
+                .iter()
+                .map(|o| self.model.outlet_fact(*o).unwrap().konst.clone().unwrap())
+                .collect();
+            let outputs = op.eval(inputs)?;
+            let mut outlets = tvec!();
+            for (ix, o) in outputs.into_iter().enumerate() {
+                outlets.push(
+                    self.model.wire_node(
+                        format!("{}-{}", name, ix),
+                        tract_core::ops::konst::Const::new(o),
+                        &[],
+                    )?[0],
+                );
             }
+            return Ok(outlets);
         }
         self.model.wire_node(name, op, inputs).with_context(|| format!("inputs are {:?}", inputs))
     }
@@ -12,6 +12,12 @@ pub struct Nnef {
     pub registries: Vec<Registry>,
 }
 
+impl Default for Nnef {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Nnef {
     pub fn new() -> Nnef {
         Nnef { stdlib: stdlib(), registries: vec![crate::ops::tract_nnef()] }
@@ -104,6 +110,13 @@ impl tract_core::prelude::Framework<ProtoModel, TypedModel> for Nnef {
     fn proto_model_for_path(&self, path: impl AsRef<Path>) -> TractResult<ProtoModel> {
         let path = path.as_ref();
         if path.is_file() {
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                let map = map_file(path)?;
+                if map.get(0..2) != Some(&[0x1fu8, 0x8b][..]) {
+                    return proto_model_for_mapped_tar(map);
+                }
+            }
             let mut f = std::fs::File::open(path)?;
             return self.proto_model_for_read(&mut f);
         }
@@ -117,6 +130,16 @@ impl tract_core::prelude::Framework<ProtoModel, TypedModel> for Nnef {
                 .components()
                 .skip(path.components().count())
                 .collect::<std::path::PathBuf>();
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                if let Some(id) = tensor_id(&subpath) {
+                    let map = map_file(entry.path())?;
+                    let tensor = crate::tensors::read_tensor_shared(&map, map.clone())
+                        .with_context(|| format!("Reading {:?}", entry.path()))?;
+                    tensors.push((id?, tensor.into_arc_tensor()));
+                    continue;
+                }
+            }
             let mut stream = std::fs::File::open(entry.path())?;
             read_stream(&subpath, &mut stream, &mut text, &mut tensors)?;
         }
@@ -158,6 +181,57 @@ impl tract_core::prelude::Framework<ProtoModel, TypedModel> for Nnef {
     }
 }
 
+/// Map a file copy-on-write: the pages are shared with the page cache (and other processes
+/// loading the same model) until written to.
+#[cfg(not(target_arch = "wasm32"))]
+fn map_file(path: &Path) -> TractResult<Arc<mapr::MmapMut>> {
+    let file = std::fs::File::open(path).with_context(|| format!("Opening {:?}", path))?;
+    let map = unsafe { mapr::MmapOptions::new().map_copy(&file) }
+        .with_context(|| format!("Mapping {:?}", path))?;
+    Ok(Arc::new(map))
+}
+
+/// Load an uncompressed tar from a mapped file, borrowing the tensors data from the mapping.
+#[cfg(not(target_arch = "wasm32"))]
+fn proto_model_for_mapped_tar(map: Arc<mapr::MmapMut>) -> TractResult<ProtoModel> {
+    let mut text: Option<String> = None;
+    let mut tensors: Vec<(String, Arc<Tensor>)> = Default::default();
+    let mut tar = tar::Archive::new(std::io::Cursor::new(&map[..]));
+    for entry in tar.entries_with_seek()? {
+        let mut entry = entry?;
+        let path = entry.path()?.to_path_buf();
+        if let Some(id) = tensor_id(&path) {
+            let start = entry.raw_file_position() as usize;
+            let bytes = map
+                .get(start..start + entry.size() as usize)
+                .ok_or_else(|| format_err!("Truncated tar entry {:?}", path))?;
+            let tensor = crate::tensors::read_tensor_shared(bytes, map.clone())
+                .with_context(|| format!("Reading {:?}", path))?;
+            tensors.push((id?, tensor.into_arc_tensor()));
+        } else {
+            read_stream(&path, &mut entry, &mut text, &mut tensors)?;
+        }
+    }
+    let text = text.ok_or_else(|| format_err!("Model must contain graph.nnef at top level"))?;
+    let doc = crate::ast::parse::parse_document(&text)?;
+    Ok(ProtoModel { doc, tensors })
+}
+
+/// Tensor identifier for a `.dat` file.
+fn tensor_id(path: &Path) -> Option<TractResult<String>> {
+    if path.extension().map(|e| e == "dat").unwrap_or(false) {
+        let mut path = path.to_path_buf();
+        path.set_extension("");
+        Some(
+            path.to_str()
+                .map(|s| s.to_string())
+                .ok_or_else(|| format_err!("Badly encoded filename for tensor: {:?}", path)),
+        )
+    } else {
+        None
+    }
+}
+
 fn read_stream<R: std::io::Read>(
     path: &std::path::Path,
     reader: &mut R,
@@ -168,14 +242,37 @@ fn read_stream<R: std::io::Read>(
         let mut t = String::new();
         reader.read_to_string(&mut t)?;
         *text = Some(t);
-    } else if path.extension().map(|e| e == "dat").unwrap_or(false) {
-        let mut path = path.to_path_buf();
-        path.set_extension("");
-        let id = path
-            .to_str()
-            .ok_or_else(|| format_err!("Badly encoded filename for tensor: {:?}", path))?;
+    } else if let Some(id) = tensor_id(path) {
         let tensor = crate::tensors::read_tensor(reader)?;
-        tensors.push((id.to_string(), tensor.into_arc_tensor()));
+        tensors.push((id?, tensor.into_arc_tensor()));
     }
     Ok(())
 }
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod test {
+    use super::*;
+    use tract_core::ops::math;
+
+    #[test]
+    fn mapped_tar() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let source = model.add_source("source", TypedFact::dt_shape(f32::datum_type(), &[3]))?;
+        let k = model.add_const("k", tensor1(&[1f32, 2., 3.]))?;
+        let sum = model.wire_node("sum", math::add::bin_typed(), &[source, k])?;
+        model.set_output_outlets(&sum)?;
+        let nnef = crate::nnef();
+        let path =
+            std::env::temp_dir().join(format!("tract-nnef-mapped-{}.tar", std::process::id()));
+        nnef.write_to_tar(&model, std::fs::File::create(&path)?)?;
+        let proto = nnef.proto_model_for_path(&path);
+        std::fs::remove_file(&path)?;
+        let proto = proto?;
+        assert!(proto.tensors.len() > 0);
+        assert!(proto.tensors.iter().all(|(_, t)| t.is_shared()));
+        let model = nnef.model_for_proto_model(&proto)?.into_runnable()?;
+        let result = model.run(tvec!(tensor1(&[1f32, 1., 1.])))?;
+        assert_eq!(*result[0], tensor1(&[2f32, 3., 4.]));
+        Ok(())
+    }
+}
@@ -7,6 +7,7 @@ pub mod framework;
 pub mod ops;
 pub mod registry;
 pub mod ser;
+pub mod snapshot;
 pub mod tensors;
 
 pub use ast::ProtoModel;
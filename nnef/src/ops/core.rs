@@ -5,9 +5,12 @@ mod broadcast;
 mod cast;
 mod downsample;
 mod gather;
+mod nn;
 mod one_hot;
+mod quant;
 mod reduce;
 mod scan;
+mod sort;
 mod source;
 
 pub fn register(registry: &mut Registry) {
@@ -40,8 +43,11 @@ pub fn register(registry: &mut Registry) {
     cast::register(registry);
     downsample::register(registry);
     gather::register(registry);
+    nn::register(registry);
     one_hot::register(registry);
+    quant::register(registry);
     reduce::register(registry);
     scan::register(registry);
+    sort::register(registry);
     source::register(registry);
 }
@@ -0,0 +1,96 @@
+use crate::internal::*;
+use crate::ser::*;
+use tract_core::ops::nn::{AttentionMask, LayerNorm, ScaledDotProductAttention};
+
+pub fn register(registry: &mut Registry) {
+    registry.register_dumper(TypeId::of::<LayerNorm>(), ser_layer_norm);
+    registry.register_primitive(
+        "tract_core_layer_norm",
+        &[
+            TypeName::Scalar.tensor().named("input"),
+            TypeName::Scalar.tensor().named("scale"),
+            TypeName::Scalar.tensor().named("bias"),
+            TypeName::Integer.named("axis"),
+            TypeName::Scalar.named("epsilon"),
+        ],
+        de_layer_norm,
+    );
+    registry.register_dumper(TypeId::of::<ScaledDotProductAttention>(), ser_attention);
+    registry.register_primitive(
+        "tract_core_scaled_dot_product_attention",
+        &[
+            TypeName::Scalar.tensor().named("q"),
+            TypeName::Scalar.tensor().named("k"),
+            TypeName::Scalar.tensor().named("v"),
+            TypeName::Scalar.named("scale"),
+            TypeName::Logical.named("causal").default(false),
+            TypeName::Integer.named("left_context").default(-1),
+            TypeName::Integer.named("chunk").default(0),
+            TypeName::Integer.named("left_chunks").default(0),
+        ],
+        de_attention,
+    );
+}
+
+fn ser_layer_norm(ast: &mut IntoAst, node: &TypedNode) -> TractResult<Option<Arc<RValue>>> {
+    let op = node.op_as::<LayerNorm>().unwrap();
+    let inputs = node.inputs.iter().map(|i| ast.mapping[i].clone()).collect::<TVec<Arc<RValue>>>();
+    Ok(Some(invocation(
+        "tract_core_layer_norm",
+        &inputs,
+        &[("axis", numeric(op.axis)), ("epsilon", numeric(op.epsilon))],
+    )))
+}
+
+fn de_layer_norm(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let scale = invocation.named_arg_as(builder, "scale")?;
+    let bias = invocation.named_arg_as(builder, "bias")?;
+    let axis = invocation.named_arg_as(builder, "axis")?;
+    let epsilon = invocation.named_arg_as(builder, "epsilon")?;
+    builder.wire(LayerNorm { axis, epsilon }, &[input, scale, bias])
+}
+
+fn ser_attention(ast: &mut IntoAst, node: &TypedNode) -> TractResult<Option<Arc<RValue>>> {
+    let op = node.op_as::<ScaledDotProductAttention>().unwrap();
+    let inputs = node.inputs.iter().map(|i| ast.mapping[i].clone()).collect::<TVec<Arc<RValue>>>();
+    let mut attrs = vec![("scale", numeric(op.scale))];
+    match op.mask {
+        AttentionMask::Full => (),
+        AttentionMask::Causal { left_context } => {
+            attrs.push(("causal", logical(true)));
+            attrs.push(("left_context", numeric(left_context.map(|l| l as i64).unwrap_or(-1))));
+        }
+        AttentionMask::Chunked { chunk, left_chunks } => {
+            attrs.push(("chunk", numeric(chunk)));
+            attrs.push(("left_chunks", numeric(left_chunks)));
+        }
+    }
+    Ok(Some(invocation("tract_core_scaled_dot_product_attention", &inputs, &attrs)))
+}
+
+fn de_attention(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    let q = invocation.named_arg_as(builder, "q")?;
+    let k = invocation.named_arg_as(builder, "k")?;
+    let v = invocation.named_arg_as(builder, "v")?;
+    let scale = invocation.named_arg_as(builder, "scale")?;
+    let chunk = invocation.named_arg_as::<i64>(builder, "chunk")?;
+    let mask = if chunk > 0 {
+        let left_chunks = invocation.named_arg_as::<i64>(builder, "left_chunks")? as usize;
+        AttentionMask::Chunked { chunk: chunk as usize, left_chunks }
+    } else if invocation.named_arg_as(builder, "causal")? {
+        let left_context = invocation.named_arg_as::<i64>(builder, "left_context")?;
+        AttentionMask::Causal {
+            left_context: if left_context < 0 { None } else { Some(left_context as usize) },
+        }
+    } else {
+        AttentionMask::Full
+    };
+    builder.wire(ScaledDotProductAttention { scale, mask }, &[q, k, v])
+}
@@ -0,0 +1,400 @@
+use crate::internal::*;
+use crate::ser::*;
+use tract_core::ops::cnn::{ConvUnary, KernelFormat, PaddingSpec, PoolSpec};
+use tract_core::ops::element_wise::ElementWiseOp;
+use tract_core::ops::matmul::{QMatMul, QParams};
+use tract_core::ops::nn::DataFormat;
+use tract_core::ops::quant::{DequantizeLinearF32, QuantizeLinearI8, QuantizeLinearU8};
+
+pub fn register(registry: &mut Registry) {
+    registry.register_element_wise(
+        "tract_core_quantize_linear_u8",
+        TypeId::of::<QuantizeLinearU8>(),
+        ser_quantize_u8,
+        quantize_parameters(),
+        de_quantize_u8,
+    );
+    registry.register_element_wise(
+        "tract_core_quantize_linear_i8",
+        TypeId::of::<QuantizeLinearI8>(),
+        ser_quantize_i8,
+        quantize_parameters(),
+        de_quantize_i8,
+    );
+    registry.register_dumper(TypeId::of::<DequantizeLinearF32>(), ser_dequantize);
+    registry.register_primitive(
+        "tract_core_dequantize_linear",
+        &quantize_parameters(),
+        de_dequantize,
+    );
+    registry.register_dumper(TypeId::of::<QMatMul>(), ser_qmatmul);
+    registry.register_primitive(
+        "tract_core_qmatmul",
+        &[
+            TypeName::Scalar.tensor().named("A"),
+            TypeName::Scalar.tensor().named("B"),
+            TypeName::Scalar.tensor().named("bias"),
+            TypeName::Scalar.tensor().named("a0"),
+            TypeName::Scalar.tensor().named("a_scale"),
+            TypeName::Scalar.tensor().named("b0"),
+            TypeName::Scalar.tensor().named("b_scale"),
+            TypeName::Scalar.tensor().named("c0"),
+            TypeName::Scalar.tensor().named("c_scale"),
+            TypeName::Logical.named("transposeA"),
+            TypeName::Logical.named("transposeB"),
+            TypeName::Logical.named("transposeC"),
+            TypeName::String.named("output_type"),
+        ],
+        de_qmatmul,
+    );
+    registry.register_dumper(TypeId::of::<ConvUnary>(), ser_qconv);
+    registry.register_primitive(
+        "tract_core_qconv",
+        &[
+            TypeName::Scalar.tensor().named("input"),
+            TypeName::Scalar.tensor().named("filter"),
+            TypeName::Scalar.tensor().named("bias"),
+            TypeName::Scalar.tensor().named("a0"),
+            TypeName::Scalar.tensor().named("a_scale"),
+            TypeName::Scalar.tensor().named("b0"),
+            TypeName::Scalar.tensor().named("b_scale"),
+            TypeName::Scalar.tensor().named("c0"),
+            TypeName::Scalar.tensor().named("c_scale"),
+            TypeName::String.named("data_format"),
+            TypeName::String.named("kernel_format"),
+            TypeName::Integer.named("groups"),
+            TypeName::Integer.array().named("dilation"),
+            TypeName::Integer.array().named("stride"),
+            TypeName::String.named("padding"),
+            TypeName::Integer.array().named("padding_before"),
+            TypeName::Integer.array().named("padding_after"),
+            TypeName::Integer.named("output_channels"),
+            TypeName::String.named("output_type"),
+        ],
+        de_qconv,
+    );
+}
+
+fn quantize_parameters() -> Vec<Parameter> {
+    vec![
+        TypeName::Scalar.tensor().named("input"),
+        TypeName::Scalar.named("scale"),
+        TypeName::Integer.named("zero_point"),
+    ]
+}
+
+fn ser_quantize_u8(ast: &mut IntoAst, node: &TypedNode) -> TractResult<Option<Arc<RValue>>> {
+    let op = node.op_as::<ElementWiseOp>().unwrap().0.downcast_ref::<QuantizeLinearU8>().unwrap();
+    let input = ast.mapping[&node.inputs[0]].clone();
+    Ok(Some(invocation(
+        "tract_core_quantize_linear_u8",
+        &[input],
+        &[("scale", numeric(op.scale)), ("zero_point", numeric(op.zero_point))],
+    )))
+}
+
+fn de_quantize_u8(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let scale = invocation.named_arg_as(builder, "scale")?;
+    let zero_point = invocation.named_arg_as::<i64>(builder, "zero_point")? as u8;
+    builder.wire(tract_core::ops::quant::quantize_linear_u8(scale, zero_point), &[input])
+}
+
+fn ser_quantize_i8(ast: &mut IntoAst, node: &TypedNode) -> TractResult<Option<Arc<RValue>>> {
+    let op = node.op_as::<ElementWiseOp>().unwrap().0.downcast_ref::<QuantizeLinearI8>().unwrap();
+    let input = ast.mapping[&node.inputs[0]].clone();
+    Ok(Some(invocation(
+        "tract_core_quantize_linear_i8",
+        &[input],
+        &[("scale", numeric(op.scale)), ("zero_point", numeric(op.zero_point))],
+    )))
+}
+
+fn de_quantize_i8(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let scale = invocation.named_arg_as(builder, "scale")?;
+    let zero_point = invocation.named_arg_as::<i64>(builder, "zero_point")? as i8;
+    builder.wire(tract_core::ops::quant::quantize_linear_i8(scale, zero_point), &[input])
+}
+
+fn ser_dequantize(ast: &mut IntoAst, node: &TypedNode) -> TractResult<Option<Arc<RValue>>> {
+    let op = node.op_as::<DequantizeLinearF32>().unwrap();
+    let input = ast.mapping[&node.inputs[0]].clone();
+    Ok(Some(invocation(
+        "tract_core_dequantize_linear",
+        &[input],
+        &[("scale", numeric(op.scale)), ("zero_point", numeric(op.zero_point))],
+    )))
+}
+
+fn de_dequantize(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let scale = invocation.named_arg_as(builder, "scale")?;
+    let zero_point = invocation.named_arg_as::<i64>(builder, "zero_point")? as i32;
+    builder.wire(DequantizeLinearF32::new(scale, zero_point), &[input])
+}
+
+/// Quantization parameters are dumped as positional tensors, in QParams order.
+fn ser_qparams(ast: &mut IntoAst, node: &TypedNode, params: &QParams) -> TVec<Arc<RValue>> {
+    params
+        .iter()
+        .map(|(name, qp)| match qp {
+            AttrOrInput::Attr(t) => ast.konst(format!("{}_{}", node.name, name), t),
+            AttrOrInput::Input(ix) => ast.mapping[&node.inputs[*ix]].clone(),
+        })
+        .collect()
+}
+
+/// Constant parameters are inlined (and restored to their expected type, as NNEF scalars
+/// are untyped), others become extra op inputs.
+fn de_qparams(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+    inputs: &mut TVec<OutletId>,
+    a_dt: DatumType,
+    b_dt: DatumType,
+    c_dt: DatumType,
+) -> TractResult<QParams> {
+    let mut params = QParams::noop_static(a_dt);
+    for (name, qp) in params.iter_mut() {
+        let dt = match name {
+            "a0" => a_dt,
+            "b0" => b_dt,
+            "c0" => c_dt,
+            _ => f32::datum_type(),
+        };
+        let wire: OutletId = invocation.named_arg_as(builder, name)?;
+        *qp = if let Some(konst) = builder.model.outlet_fact(wire)?.konst.clone() {
+            AttrOrInput::Attr(konst.cast_to_dt(dt)?.into_owned().into_arc_tensor())
+        } else {
+            inputs.push(wire);
+            AttrOrInput::Input(inputs.len() - 1)
+        };
+    }
+    Ok(params)
+}
+
+fn ser_qmatmul(ast: &mut IntoAst, node: &TypedNode) -> TractResult<Option<Arc<RValue>>> {
+    let op = node.op_as::<QMatMul>().unwrap();
+    let mut inputs: TVec<Arc<RValue>> =
+        node.inputs[0..3].iter().map(|i| ast.mapping[i].clone()).collect();
+    inputs.extend(ser_qparams(ast, node, &op.params));
+    Ok(Some(invocation(
+        "tract_core_qmatmul",
+        &inputs,
+        &[
+            ("transposeA", logical(op.a_trans)),
+            ("transposeB", logical(op.b_trans)),
+            ("transposeC", logical(op.c_trans)),
+            ("output_type", string(format!("{:?}", op.output_type).to_lowercase())),
+        ],
+    )))
+}
+
+fn de_qmatmul(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    let a: OutletId = invocation.named_arg_as(builder, "A")?;
+    let b: OutletId = invocation.named_arg_as(builder, "B")?;
+    let mut bias: OutletId = invocation.named_arg_as(builder, "bias")?;
+    if builder.model.outlet_fact(bias)?.datum_type != i32::datum_type() {
+        bias = builder.wire(tract_core::ops::cast::cast(i32::datum_type()), &[bias])?[0];
+    }
+    let a_trans = invocation.named_arg_as(builder, "transposeA")?;
+    let b_trans = invocation.named_arg_as(builder, "transposeB")?;
+    let c_trans = invocation.named_arg_as(builder, "transposeC")?;
+    let output_type = invocation.named_arg_as::<String>(builder, "output_type")?.parse()?;
+    let a_dt = builder.model.outlet_fact(a)?.datum_type;
+    let b_dt = builder.model.outlet_fact(b)?.datum_type;
+    let mut inputs = tvec!(a, b, bias);
+    let params = de_qparams(builder, invocation, &mut inputs, a_dt, b_dt, output_type)?;
+    builder.wire(QMatMul::new(a_trans, b_trans, c_trans, output_type, params), &inputs)
+}
+
+fn ser_qconv(ast: &mut IntoAst, node: &TypedNode) -> TractResult<Option<Arc<RValue>>> {
+    let op = node.op_as::<ConvUnary>().unwrap();
+    let (output_type, params) = if let Some(qp) = &op.q_params {
+        qp
+    } else {
+        return Ok(None);
+    };
+    let input = ast.mapping[&node.inputs[0]].clone();
+    let filter = ast.konst_variable(format!("{}_weights", node.name), &op.kernel);
+    let bias = if let Some(bias) = &op.bias {
+        ast.konst(format!("{}_bias", node.name), bias)
+    } else {
+        numeric(0).into()
+    };
+    let mut inputs = tvec!(input, filter, bias);
+    inputs.extend(ser_qparams(ast, node, params));
+    let (padding, before, after) = match &op.pool_spec.padding {
+        PaddingSpec::Explicit(before, after, _) => ("explicit", &**before, &**after),
+        PaddingSpec::Valid => ("valid", &[][..], &[][..]),
+        PaddingSpec::SameUpper => ("same_upper", &[][..], &[][..]),
+        PaddingSpec::SameLower => ("same_lower", &[][..], &[][..]),
+    };
+    let output_channels =
+        op.pool_spec.data_format.shape(&node.outputs[0].fact.shape.to_tvec())?.c().to_usize()?;
+    Ok(Some(invocation(
+        "tract_core_qconv",
+        &inputs,
+        &[
+            ("data_format", string(format!("{:?}", op.pool_spec.data_format))),
+            ("kernel_format", string(format!("{:?}", op.kernel_fmt))),
+            ("groups", numeric(op.group)),
+            ("dilation", ints(&op.pool_spec.dilations())),
+            ("stride", ints(&op.pool_spec.strides())),
+            ("padding", string(padding)),
+            ("padding_before", ints(before)),
+            ("padding_after", ints(after)),
+            ("output_channels", numeric(output_channels)),
+            ("output_type", string(format!("{:?}", output_type).to_lowercase())),
+        ],
+    )))
+}
+
+fn de_qconv(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    let input: OutletId = invocation.named_arg_as(builder, "input")?;
+    let kernel: Arc<Tensor> = invocation.named_arg_as(builder, "filter")?;
+    let bias: Arc<Tensor> = invocation.named_arg_as(builder, "bias")?;
+    let bias = if bias.is_uniform() && bias.cast_to_scalar::<f32>()? == 0.0 {
+        None
+    } else {
+        Some(bias.cast_to::<i32>()?.into_owned().into_arc_tensor())
+    };
+    let data_format = match &*invocation.named_arg_as::<String>(builder, "data_format")? {
+        "NCHW" => DataFormat::NCHW,
+        "NHWC" => DataFormat::NHWC,
+        "CHW" => DataFormat::CHW,
+        "HWC" => DataFormat::HWC,
+        s => bail!("Unsupported data format {}", s),
+    };
+    let kernel_fmt = match &*invocation.named_arg_as::<String>(builder, "kernel_format")? {
+        "OIHW" => KernelFormat::OIHW,
+        "HWIO" => KernelFormat::HWIO,
+        s => bail!("Unsupported kernel format {}", s),
+    };
+    let group = invocation.named_arg_as(builder, "groups")?;
+    let dilation: TVec<usize> = invocation.named_arg_as(builder, "dilation")?;
+    let stride: TVec<usize> = invocation.named_arg_as(builder, "stride")?;
+    let padding = match &*invocation.named_arg_as::<String>(builder, "padding")? {
+        "explicit" => PaddingSpec::Explicit(
+            invocation.named_arg_as(builder, "padding_before")?,
+            invocation.named_arg_as(builder, "padding_after")?,
+            false,
+        ),
+        "valid" => PaddingSpec::Valid,
+        "same_upper" => PaddingSpec::SameUpper,
+        "same_lower" => PaddingSpec::SameLower,
+        s => bail!("Unsupported padding {}", s),
+    };
+    let kernel_shape = match kernel_fmt {
+        KernelFormat::OIHW => &kernel.shape()[2..],
+        KernelFormat::HWIO => &kernel.shape()[..kernel.rank() - 2],
+    };
+    let pool_spec = PoolSpec::new(
+        data_format,
+        kernel_shape.into(),
+        padding,
+        Some(dilation),
+        Some(stride),
+        Some(invocation.named_arg_as(builder, "output_channels")?),
+    );
+    let output_type = invocation.named_arg_as::<String>(builder, "output_type")?.parse()?;
+    let a_dt = kernel.datum_type();
+    let b_dt = builder.model.outlet_fact(input)?.datum_type;
+    let mut inputs = tvec!(input);
+    let params = de_qparams(builder, invocation, &mut inputs, a_dt, b_dt, output_type)?;
+    let op =
+        ConvUnary::new(pool_spec, kernel_fmt, kernel, group, bias, Some((output_type, params)));
+    builder.wire(op, &inputs)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn qparams() -> QParams {
+        QParams {
+            a0: AttrOrInput::Attr(rctensor0(1i8)),
+            a_scale: AttrOrInput::Attr(rctensor0(0.5f32)),
+            b0: AttrOrInput::Attr(rctensor0(-2i8)),
+            b_scale: AttrOrInput::Attr(rctensor0(0.25f32)),
+            c0: AttrOrInput::Attr(rctensor0(3i8)),
+            c_scale: AttrOrInput::Attr(rctensor0(0.125f32)),
+        }
+    }
+
+    fn round_trip(model: TypedModel, inputs: TVec<Tensor>) -> TractResult<TypedModel> {
+        let nnef = crate::nnef().with_tract_core();
+        let buffer = nnef.write_to_tar(&model, vec![])?;
+        let reloaded = nnef.model_for_read(&mut &*buffer)?;
+        assert_eq!(reloaded.output_fact(0)?.datum_type, i8::datum_type());
+        let expected = model.into_runnable()?.run(inputs.clone())?;
+        let found = reloaded.clone().into_runnable()?.run(inputs)?;
+        assert_eq!(expected, found);
+        Ok(reloaded)
+    }
+
+    #[test]
+    fn qmatmul_round_trip() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let a = model.add_source("a", TypedFact::dt_shape(i8::datum_type(), &[2, 3]))?;
+        let b = model.add_source("b", TypedFact::dt_shape(i8::datum_type(), &[2, 4]))?;
+        let bias = model.add_const("bias", tensor0(0i32))?;
+        let op = QMatMul::new(true, false, false, i8::datum_type(), qparams());
+        let output = model.wire_node("qmm", op, &[a, b, bias])?;
+        model.set_output_outlets(&output)?;
+        let inputs =
+            tvec!(tensor2(&[[1i8, -3, 5], [7, 0, -2]]), tensor2(&[[4i8, -1, 2, 9], [-6, 3, 0, 1]]));
+        let reloaded = round_trip(model, inputs)?;
+        let op = reloaded.nodes().iter().find_map(|n| n.op_as::<QMatMul>()).unwrap();
+        assert!(op.a_trans && !op.b_trans && !op.c_trans);
+        Ok(())
+    }
+
+    #[test]
+    fn qconv_round_trip() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let source =
+            model.add_source("input", TypedFact::dt_shape(i8::datum_type(), &[1, 1, 5]))?;
+        let pool_spec = PoolSpec::new(
+            DataFormat::NCHW,
+            tvec!(2),
+            PaddingSpec::Explicit(tvec!(1), tvec!(0), false),
+            None,
+            None,
+            Some(2),
+        );
+        let kernel = rctensor3(&[[[1i8, -2]], [[3, 4]]]);
+        let bias = rctensor1(&[5i32, -7]);
+        let op = ConvUnary::new(
+            pool_spec,
+            KernelFormat::OIHW,
+            kernel,
+            1,
+            Some(bias),
+            Some((i8::datum_type(), qparams())),
+        );
+        let output = model.wire_node("qconv", op, &[source])?;
+        model.set_output_outlets(&output)?;
+        let reloaded = round_trip(model, tvec!(tensor3(&[[[10i8, -20, 30, 0, 5]]])))?;
+        let op = reloaded.nodes().iter().find_map(|n| n.op_as::<ConvUnary>()).unwrap();
+        assert_eq!(op.bias.as_deref(), Some(&tensor1(&[5i32, -7])));
+        assert!(op.q_params.is_some());
+        Ok(())
+    }
+}
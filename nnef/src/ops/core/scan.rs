@@ -69,11 +69,11 @@ fn ser_scan(ast: &mut IntoAst, node: &TypedNode) -> TractResult<Option<Arc<RValu
                 ));
             }
             InputMapping::State { initializer } => {
-                let initializer = match &initializer {
-                    &StateInitializer::Value(v) => {
+                let initializer = match initializer {
+                    StateInitializer::Value(v) => {
                         ast.konst_variable(format!("{}_state_init_{}", node.name, state.len()), v)
                     }
-                    &StateInitializer::FromInput(slot) => ast.mapping[&node.inputs[*slot]].clone(),
+                    StateInitializer::FromInput(slot) => ast.mapping[&node.inputs[*slot]].clone(),
                 }
                 .as_ref()
                 .clone();
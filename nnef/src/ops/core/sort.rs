@@ -0,0 +1,106 @@
+use crate::internal::*;
+use crate::ser::*;
+use tract_core::ops::array::{ArgSort, Sort, TopK};
+
+pub fn register(registry: &mut Registry) {
+    registry.register_dumper(TypeId::of::<Sort>(), ser_sort);
+    registry.register_primitive(
+        "tract_core_sort",
+        &[
+            TypeName::Scalar.tensor().named("input"),
+            TypeName::Integer.named("axis"),
+            TypeName::Logical.named("descending").default(false),
+        ],
+        de_sort,
+    );
+    registry.register_dumper(TypeId::of::<ArgSort>(), ser_argsort);
+    registry.register_primitive(
+        "tract_core_argsort",
+        &[
+            TypeName::Scalar.tensor().named("input"),
+            TypeName::Integer.named("axis"),
+            TypeName::Logical.named("descending").default(false),
+        ],
+        de_argsort,
+    );
+    registry.register_dumper(TypeId::of::<TopK>(), ser_topk);
+    registry.register_primitive(
+        "tract_core_topk",
+        &[
+            TypeName::Scalar.tensor().named("input"),
+            TypeName::Integer.tensor().named("k"),
+            TypeName::Integer.named("axis"),
+            TypeName::Logical.named("largest").default(true),
+            TypeName::Logical.named("sorted").default(true),
+        ],
+        de_topk,
+    );
+}
+
+fn ser_sort(ast: &mut IntoAst, node: &TypedNode) -> TractResult<Option<Arc<RValue>>> {
+    let op = node.op_as::<Sort>().unwrap();
+    let input = ast.mapping[&node.inputs[0]].clone();
+    Ok(Some(invocation(
+        "tract_core_sort",
+        &[input],
+        &[("axis", numeric(op.axis)), ("descending", logical(op.descending))],
+    )))
+}
+
+fn de_sort(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let axis = invocation.named_arg_as(builder, "axis")?;
+    let descending = invocation.named_arg_as(builder, "descending")?;
+    builder.wire(Sort { axis, descending }, &[input])
+}
+
+fn ser_argsort(ast: &mut IntoAst, node: &TypedNode) -> TractResult<Option<Arc<RValue>>> {
+    let op = node.op_as::<ArgSort>().unwrap();
+    let input = ast.mapping[&node.inputs[0]].clone();
+    Ok(Some(invocation(
+        "tract_core_argsort",
+        &[input],
+        &[("axis", numeric(op.axis)), ("descending", logical(op.descending))],
+    )))
+}
+
+fn de_argsort(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let axis = invocation.named_arg_as(builder, "axis")?;
+    let descending = invocation.named_arg_as(builder, "descending")?;
+    builder.wire(ArgSort { axis, descending }, &[input])
+}
+
+fn ser_topk(ast: &mut IntoAst, node: &TypedNode) -> TractResult<Option<Arc<RValue>>> {
+    let op = node.op_as::<TopK>().unwrap();
+    let input = ast.mapping[&node.inputs[0]].clone();
+    let k = ast.mapping[&node.inputs[1]].clone();
+    Ok(Some(invocation(
+        "tract_core_topk",
+        &[input, k],
+        &[
+            ("axis", numeric(op.axis)),
+            ("largest", logical(op.largest)),
+            ("sorted", logical(op.sorted)),
+        ],
+    )))
+}
+
+fn de_topk(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let k = invocation.named_arg_as(builder, "k")?;
+    let axis = invocation.named_arg_as(builder, "axis")?;
+    let largest = invocation.named_arg_as(builder, "largest")?;
+    let sorted = invocation.named_arg_as(builder, "sorted")?;
+    let fallback_k = builder.model.symbol_table.new_with_prefix("k").into();
+    builder.wire(TopK { axis, largest, sorted, fallback_k }, &[input, k])
+}
@@ -253,6 +253,100 @@ pub fn conv(
     builder.wire(op, &[input])
 }
 
+pub fn deconv(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    use ops::cnn::{DeconvUnary, KernelFormat};
+    use ops::cnn::{PaddingSpec, PoolSpec};
+    use ops::nn::DataFormat;
+    let input: OutletId = invocation.named_arg_as(builder, "input")?;
+    let kernel: Arc<Tensor> = invocation.named_arg_as(builder, "filter")?;
+    let input_fact = builder.model.outlet_fact(input)?.clone();
+    if input_fact.rank() != kernel.rank() {
+        bail!(
+            "Deconvolution input expected as NCHW, filter as OIHW. Got {:?} and {:?}.",
+            input_fact,
+            kernel
+        );
+    }
+    let mut group = invocation.named_arg_as(builder, "groups")?;
+    if group == 0 {
+        group = kernel.shape()[0]
+    }
+    if input_fact.shape[1] != kernel.shape()[0].to_dim() {
+        bail!(
+            "Deconvolution input channels and kernel first axis must match. Got {:?} and {:?}.",
+            input_fact,
+            kernel
+        );
+    }
+    let geo_rank = input_fact.rank() - 2;
+    let dilation: TVec<usize> = invocation.named_arg_as(builder, "dilation")?;
+    if dilation.len() != 0 && dilation.len() != geo_rank {
+        bail!("Deconvolution dilation only apply to spatial dimensions, so it should be of rank {}. Got {:?}", geo_rank, dilation)
+    }
+    let stride: TVec<usize> = invocation.named_arg_as(builder, "stride")?;
+    if stride.len() != 0 && stride.len() != geo_rank {
+        bail!("Deconvolution stride only apply to spatial dimensions, so it should be of rank {}. Got {:?}", geo_rank, stride)
+    }
+    let padding: TVec<TVec<usize>> = invocation.named_arg_as(builder, "padding")?;
+    let padding = if padding.len() == 0 {
+        PaddingSpec::SameUpper
+    } else {
+        let mut before = tvec!();
+        let mut after = tvec!();
+        for p in padding {
+            before.push(p[0]);
+            after.push(p[1]);
+        }
+        PaddingSpec::Explicit(before, after, false)
+    };
+    let pool_spec = PoolSpec::new(
+        DataFormat::NCHW,
+        kernel.shape()[2..].into(),
+        padding,
+        if dilation.len() > 0 { Some(dilation) } else { None },
+        if stride.len() > 0 { Some(stride) } else { None },
+        Some(kernel.shape()[1] * group),
+    );
+    let output_shape: TVec<usize> = invocation.named_arg_as(builder, "output_shape")?;
+    let mut adjustments: TVec<usize> = tvec!(0; geo_rank);
+    if output_shape.len() > 0 {
+        if output_shape.len() != input_fact.rank() {
+            bail!(
+                "Deconvolution output_shape should be of rank {}. Got {:?}",
+                input_fact.rank(),
+                output_shape
+            )
+        }
+        let input_shape = input_fact.shape.to_tvec();
+        let computed = ops::cnn::deconv::output_shape(&pool_spec, &*input_shape, &adjustments)?;
+        for ix in 0..geo_rank {
+            let computed = computed[ix + 2].to_usize()?;
+            if output_shape[ix + 2] < computed {
+                bail!(
+                    "Deconvolution output_shape {:?} is smaller than computed shape {:?}",
+                    output_shape,
+                    computed
+                );
+            }
+            adjustments[ix] = output_shape[ix + 2] - computed;
+        }
+    }
+    let bias: Arc<Tensor> = invocation.named_arg_as(builder, "bias")?;
+    let bias: Option<Arc<Tensor>> =
+        if bias.is_uniform() && bias.cast_to_scalar::<f32>()? == 0.0 { None } else { Some(bias) };
+
+    let border: String = invocation.named_arg_as(builder, "border")?;
+    if border != "constant" {
+        bail!("Deconvolution only supports constant border, got {:?}", border);
+    }
+    let op =
+        DeconvUnary::new(pool_spec, KernelFormat::OIHW, kernel.clone(), bias, adjustments, group);
+    builder.wire(op, &[input])
+}
+
 fn pool_spec_for_pools(
     builder: &mut ModelBuilder,
     invocation: &ResolvedInvocation,
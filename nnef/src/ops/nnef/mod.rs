@@ -98,6 +98,9 @@ pub fn tract_nnef() -> Registry {
     primitive(&mut registry, "conv", deser::conv);
     dumper!(ops::cnn::ConvUnary, ser::conv);
 
+    primitive(&mut registry, "deconv", deser::deconv);
+    dumper!(ops::cnn::DeconvUnary, ser::deconv);
+
     primitive(&mut registry, "sum_reduce", deser::reduce);
     primitive(&mut registry, "max_reduce", deser::reduce);
     primitive(&mut registry, "min_reduce", deser::reduce);
@@ -117,3 +120,46 @@ pub fn tract_nnef() -> Registry {
     }
     registry
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tract_core::ops::cnn::{DeconvUnary, KernelFormat, PaddingSpec, PoolSpec};
+    use tract_core::ops::nn::DataFormat;
+
+    #[test]
+    fn deconv_same_adjustments_round_trip() -> TractResult<()> {
+        let pool_spec = PoolSpec::new(
+            DataFormat::NCHW,
+            tvec!(3),
+            PaddingSpec::SameUpper,
+            None,
+            Some(tvec!(2)),
+            Some(2),
+        );
+        let kernel = rctensor3(&[[[1f32, 2., 3.], [10., 20., 30.]]]);
+        let op = DeconvUnary::new(pool_spec, KernelFormat::OIHW, kernel, None, tvec!(1), 1);
+        let mut model = TypedModel::default();
+        let source =
+            model.add_source("source", TypedFact::dt_shape(f32::datum_type(), &[1, 1, 3]))?;
+        let deconv = model.wire_node("deconv", op, &[source])?;
+        model.set_output_outlets(&deconv)?;
+        assert_eq!(
+            model.output_fact(0)?.shape.to_tvec(),
+            tvec!(1.to_dim(), 2.to_dim(), 7.to_dim())
+        );
+
+        let nnef = crate::nnef().with_tract_core();
+        let buffer = nnef.write_to_tar(&model, vec![])?;
+        let reloaded = nnef.model_for_read(&mut &*buffer)?;
+        let op = reloaded.nodes().iter().find_map(|n| n.op_as::<DeconvUnary>()).unwrap();
+        assert_eq!(op.adjustments, tvec!(1));
+        assert_eq!(reloaded.output_fact(0)?.shape, model.output_fact(0)?.shape);
+
+        let input = tvec!(tensor3(&[[[1f32, 2., 3.]]]));
+        let expected = model.into_runnable()?.run(input.clone())?;
+        let found = reloaded.into_runnable()?.run(input)?;
+        assert_eq!(expected, found);
+        Ok(())
+    }
+}
@@ -126,17 +126,22 @@ fn data_from_ncwh(data_format: DataFormat, geo_rank: usize, mut wire: Arc<RValue
     wire
 }
 
-fn conv_fragment<'a>(ast: &'a mut IntoAst, data_format: DataFormat, geo_rank: usize) -> String {
+fn conv_fragment<'a>(
+    ast: &'a mut IntoAst,
+    data_format: DataFormat,
+    geo_rank: usize,
+    op_name: &str,
+) -> String {
     if data_format == DataFormat::NCHW {
-        return "conv".into();
+        return op_name.into();
     }
-    let fragment_name = format!("tract_conv_{:?}_{}D", data_format, geo_rank).to_lowercase();
+    let fragment_name = format!("tract_{}_{:?}_{}D", op_name, data_format, geo_rank).to_lowercase();
     if ast.fragments.contains_key(&fragment_name) {
         return fragment_name;
     }
 
     let mut body = vec![];
-    let mut fragment = ast.framework.stdlib.iter().find(|f| f.decl.id == "conv").unwrap().clone();
+    let mut fragment = ast.framework.stdlib.iter().find(|f| f.decl.id == op_name).unwrap().clone();
     fragment.decl.id = fragment_name.clone();
 
     let mut wire = ident("input").into();
@@ -144,7 +149,7 @@ fn conv_fragment<'a>(ast: &'a mut IntoAst, data_format: DataFormat, geo_rank: us
 
     body.push(assignment("nchw", wire));
     wire = invocation(
-        "conv",
+        op_name,
         &[ident("nchw").into(), ident("filter").into(), ident("bias").into()],
         &*fragment
             .decl
@@ -170,6 +175,9 @@ pub fn conv(
     op: &ops::cnn::conv::ConvUnary,
 ) -> TractResult<Option<Arc<RValue>>> {
     use tract_core::ops::cnn::PaddingSpec;
+    if op.q_params.is_some() {
+        return Ok(None);
+    }
     let ci = op
         .pool_spec
         .data_format
@@ -185,7 +193,7 @@ pub fn conv(
     weights.set_shape(&*kernel_shape)?;
     let weigths = ast.konst_variable(format!("{}_weigths", node.name), &weights.into_arc_tensor());
     wire = ast.force_assign(format!("{}_input", node.name), &wire);
-    let conv_fragment = conv_fragment(ast, op.pool_spec.data_format, op.pool_spec.rank());
+    let conv_fragment = conv_fragment(ast, op.pool_spec.data_format, op.pool_spec.rank(), "conv");
     let padding = match &op.pool_spec.padding {
         PaddingSpec::Explicit(bef, after, _) => array(
             &bef.iter()
@@ -219,6 +227,65 @@ pub fn conv(
     Ok(Some(wire))
 }
 
+pub fn deconv(
+    ast: &mut IntoAst,
+    node: &TypedNode,
+    op: &ops::cnn::deconv::DeconvUnary,
+) -> TractResult<Option<Arc<RValue>>> {
+    use tract_core::ops::cnn::{KernelFormat, PaddingSpec};
+    let mut wire = ast.mapping[&node.inputs[0]].clone();
+    let weights = match op.kernel_format {
+        KernelFormat::OIHW => op.kernel.clone(),
+        KernelFormat::HWIO => {
+            let rank = op.kernel.rank();
+            let mut permutation: TVec<usize> = tvec!(rank - 1, rank - 2);
+            permutation.extend(0..rank - 2);
+            op.kernel.clone().into_tensor().permute_axes(&permutation)?.into_arc_tensor()
+        }
+    };
+    let weigths = ast.konst_variable(format!("{}_weigths", node.name), &weights);
+    wire = ast.force_assign(format!("{}_input", node.name), &wire);
+    let conv_fragment = conv_fragment(ast, op.pool_spec.data_format, op.pool_spec.rank(), "deconv");
+    let padding = match &op.pool_spec.padding {
+        PaddingSpec::Explicit(bef, after, _) => array(
+            &bef.iter()
+                .zip(after.iter())
+                .map(|(a, b)| tuple_2(numeric(a), numeric(b)))
+                .collect::<Vec<_>>(),
+        ),
+        PaddingSpec::SameUpper => array(&[]),
+        PaddingSpec::SameLower => bail!("Unsupported padding scheme"),
+        PaddingSpec::Valid => array(
+            (0..op.pool_spec.rank()).map(|_| tuple_2(numeric(0), numeric(0))).collect::<Vec<_>>(),
+        ),
+    };
+    let mut inputs = tvec![wire, weigths];
+    if let Some(bias) = op.bias.as_ref() {
+        let bias = ast.konst(format!("{}_bias", node.name), bias);
+        inputs.push(bias)
+    }
+    let mut named_args = vec![
+        ("dilation", ints(&op.pool_spec.dilations())),
+        ("stride", ints(&op.pool_spec.strides())),
+        ("border", string("constant")),
+        ("groups", numeric(op.group)),
+        ("padding", padding),
+    ];
+    if op.adjustments.iter().any(|a| *a != 0) {
+        // adjustments are only expressible through the full output shape, in NCHW
+        let shape = op.pool_spec.data_format.shape(node.outputs[0].fact.shape.to_tvec())?;
+        let mut output_shape = tvec!(shape.n().cloned().unwrap_or(TDim::from(1)).to_usize()?);
+        output_shape.push(shape.c().to_usize()?);
+        for d in shape.hw_dims() {
+            output_shape.push(d.to_usize()?);
+        }
+        named_args.push(("output_shape", ints(&output_shape)));
+    }
+    wire = invocation(&conv_fragment, &inputs, &named_args);
+    wire = ast.force_assign(&node.name, &wire);
+    Ok(Some(wire))
+}
+
 fn cnn_pool_fragment<'a>(
     ast: &'a mut IntoAst,
     data_format: DataFormat,
@@ -190,7 +190,7 @@ impl<'a> IntoAst<'a> {
         let doc = Document {
             version: "1.0".into(),
             extension,
-            fragments: fragments.into_iter().map(|(_, v)| v).collect(),
+            fragments: fragments.into_values().collect(),
             graph_def: GraphDef { id, parameters, results, body },
         };
         Ok(ProtoModel { doc, tensors })
@@ -298,7 +298,7 @@ impl<'a> IntoAst<'a> {
 
     fn assignment(&mut self, name: impl Into<String>, right: Arc<RValue>) {
         let name = name.into();
-        if &*right == &ident(&name) {
+        if *right == ident(&name) {
             return;
         }
         self.body.push(assignment(&name, right))
@@ -330,7 +330,7 @@ pub fn ident(s: impl Into<String>) -> RValue {
 }
 
 pub fn array(items: impl AsRef<[RValue]>) -> RValue {
-    RValue::Array(items.as_ref().iter().cloned().collect())
+    RValue::Array(items.as_ref().to_vec())
 }
 
 pub fn tuple_2(a: RValue, b: RValue) -> RValue {
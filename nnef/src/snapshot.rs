@@ -0,0 +1,58 @@
+//! Portable `StateSnapshot` blobs: a tar of NNEF `.dat` tensors.
+//!
+//! Op state tensors go to `states/<node name>/<index>.dat`, session tensors to
+//! `tensors/<name>.dat`.
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+
+use crate::internal::*;
+
+pub fn write_snapshot<W: Write>(snapshot: &StateSnapshot, w: W) -> TractResult<W> {
+    let mut ar = tar::Builder::new(w);
+    let mut append = |path: String, tensor: &Tensor| -> TractResult<()> {
+        let mut data = vec![];
+        crate::tensors::write_tensor(&mut data, tensor)?;
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        ar.append_data(&mut header, path, &mut &*data)?;
+        Ok(())
+    };
+    for (name, tensors) in &snapshot.states {
+        for (ix, tensor) in tensors.iter().enumerate() {
+            append(format!("states/{}/{}.dat", name, ix), tensor)?;
+        }
+    }
+    for (name, tensor) in &snapshot.tensors {
+        append(format!("tensors/{}.dat", name), tensor)?;
+    }
+    Ok(ar.into_inner()?)
+}
+
+pub fn read_snapshot<R: Read>(r: R) -> TractResult<StateSnapshot> {
+    let mut states: BTreeMap<String, BTreeMap<usize, Tensor>> = BTreeMap::new();
+    let mut snapshot = StateSnapshot::default();
+    let mut ar = tar::Archive::new(r);
+    for entry in ar.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.to_string_lossy().into_owned();
+        let tensor = crate::tensors::read_tensor(&mut entry)
+            .with_context(|| format!("Reading snapshot entry {}", path))?;
+        let stem = path
+            .strip_suffix(".dat")
+            .ok_or_else(|| format_err!("Unexpected snapshot entry {}", path))?;
+        if let Some(state) = stem.strip_prefix("states/") {
+            let split = state.rfind('/').ok_or_else(|| format_err!("Malformed path {}", path))?;
+            let ix = state[split + 1..].parse::<usize>()?;
+            states.entry(state[..split].to_string()).or_default().insert(ix, tensor);
+        } else if let Some(name) = stem.strip_prefix("tensors/") {
+            snapshot.tensors.insert(name.to_string(), tensor);
+        } else {
+            bail!("Unexpected snapshot entry {}", path);
+        }
+    }
+    snapshot.states =
+        states.into_iter().map(|(name, tensors)| (name, tensors.into_values().collect())).collect();
+    Ok(snapshot)
+}
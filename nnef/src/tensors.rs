@@ -19,55 +19,59 @@ struct Header {
     padding: [u32; 11],
 }
 
+fn parse_header(header: &Header) -> TractResult<(DatumType, TVec<usize>)> {
+    if header.magic != [0x4e, 0xef] {
+        bail!("Wrong magic number");
+    };
+    if header.version_maj != 1 && header.version_min != 0 {
+        bail!("Wrong version number");
+    }
+    if header.rank > 8 {
+        bail!("Wrong tensor rank {}", header.rank);
+    }
+    let shape: TVec<usize> = header.dims[0..header.rank as usize].iter().map(|d| *d as _).collect();
+    let len = shape.iter().product::<usize>();
+    if header.bits_per_item != 0xFFFFFFFF
+        && len * (header.bits_per_item as usize / 8) != header.data_size_bytes as usize
+    {
+        bail!(
+            "Shape and len mismatch: shape:{:?}, bits_per_item:{}, bytes:{} ",
+            shape,
+            header.bits_per_item,
+            header.data_size_bytes
+        );
+    }
+    if header.item_type_vendor != 0 && header.item_type_vendor != TRACT_ITEM_TYPE_VENDOR {
+        bail!("Unknownn item type vendor {}", header.item_type_vendor);
+    }
+    let dt = match (header.item_type_vendor, header.item_type, header.bits_per_item) {
+        (0, 0, 16) => DatumType::F16,
+        (0, 0, 32) => DatumType::F32,
+        (0, 0, 64) => DatumType::F64,
+        (0, 1, 8) => DatumType::U8,
+        (0, 1, 16) => DatumType::U16,
+        (0, 1, 32) => DatumType::U32,
+        (0, 1, 64) => DatumType::U64,
+        (0, 0x0100, 8) => DatumType::I8,
+        (0, 0x0100, 16) => DatumType::I16,
+        (0, 0x0100, 32) => DatumType::I32,
+        (0, 0x0100, 64) => DatumType::I64,
+        (TRACT_ITEM_TYPE_VENDOR, 0x1000, 0xFFFF) => DatumType::String,
+        _ => bail!(
+            "Unsupported type in tensor type:{} bits_per_item:{}",
+            header.item_type,
+            header.bits_per_item
+        ),
+    };
+    Ok((dt, shape))
+}
+
 pub fn read_tensor<R: std::io::Read>(mut reader: R) -> TractResult<Tensor> {
     unsafe {
         let mut header: Header = std::mem::zeroed();
         let buffer: &mut [u8; 128] = std::mem::transmute(&mut header);
         reader.read_exact(buffer)?;
-        if header.magic != [0x4e, 0xef] {
-            bail!("Wrong magic number");
-        };
-        if header.version_maj != 1 && header.version_min != 0 {
-            bail!("Wrong version number");
-        }
-        if header.rank > 8 {
-            bail!("Wrong tensor rank {}", header.rank);
-        }
-        let shape: TVec<usize> =
-            header.dims[0..header.rank as usize].iter().map(|d| *d as _).collect();
-        let len = shape.iter().product::<usize>();
-        if header.bits_per_item != 0xFFFFFFFF
-            && len * (header.bits_per_item as usize / 8) != header.data_size_bytes as usize
-        {
-            bail!(
-                "Shape and len mismatch: shape:{:?}, bits_per_item:{}, bytes:{} ",
-                shape,
-                header.bits_per_item,
-                header.data_size_bytes
-            );
-        }
-        if header.item_type_vendor != 0 && header.item_type_vendor != TRACT_ITEM_TYPE_VENDOR {
-            bail!("Unknownn item type vendor {}", header.item_type_vendor);
-        }
-        let dt = match (header.item_type_vendor, header.item_type, header.bits_per_item) {
-            (0, 0, 16) => DatumType::F16,
-            (0, 0, 32) => DatumType::F32,
-            (0, 0, 64) => DatumType::F64,
-            (0, 1, 8) => DatumType::U8,
-            (0, 1, 16) => DatumType::U16,
-            (0, 1, 32) => DatumType::U32,
-            (0, 1, 64) => DatumType::U64,
-            (0, 0x0100, 8) => DatumType::I8,
-            (0, 0x0100, 16) => DatumType::I16,
-            (0, 0x0100, 32) => DatumType::I32,
-            (0, 0x0100, 64) => DatumType::I64,
-            (TRACT_ITEM_TYPE_VENDOR, 0x1000, 0xFFFF) => DatumType::String,
-            _ => bail!(
-                "Unsupported type in tensor type:{} bits_per_item:{}",
-                header.item_type,
-                header.bits_per_item
-            ),
-        };
+        let (dt, shape) = parse_header(&header)?;
         if dt.is_copy() {
             let mut tensor = Tensor::uninitialized_dt(dt, &shape)?;
             reader.read_exact(tensor.as_bytes_mut())?;
@@ -76,8 +80,7 @@ pub fn read_tensor<R: std::io::Read>(mut reader: R) -> TractResult<Tensor> {
             let mut tensor = Tensor::zero_dt(dt, &shape)?;
             for item in tensor.as_slice_mut_unchecked::<String>() {
                 let len: u32 = reader.read_u32::<LE>()?;
-                let mut bytes = Vec::with_capacity(len as usize);
-                bytes.set_len(len as usize);
+                let mut bytes = vec![0u8; len as usize];
                 reader.read_exact(&mut bytes)?;
                 *item = String::from_utf8(bytes)?;
             }
@@ -88,6 +91,31 @@ pub fn read_tensor<R: std::io::Read>(mut reader: R) -> TractResult<Tensor> {
     }
 }
 
+/// Build a tensor from a `.dat` file content, borrowing the data from `owner` instead of copying
+/// it when possible.
+pub fn read_tensor_shared(
+    bytes: &[u8],
+    owner: Arc<dyn std::any::Any + Send + Sync>,
+) -> TractResult<Tensor> {
+    if bytes.len() < 128 {
+        bail!("Truncated tensor file");
+    }
+    unsafe {
+        let mut header: Header = std::mem::zeroed();
+        let buffer: &mut [u8; 128] = std::mem::transmute(&mut header);
+        buffer.copy_from_slice(&bytes[0..128]);
+        let (dt, shape) = parse_header(&header)?;
+        if !dt.is_copy() {
+            return read_tensor(bytes);
+        }
+        let len = shape.iter().product::<usize>() * dt.size_of();
+        let data = bytes
+            .get(128..128 + len)
+            .ok_or_else(|| format_err!("Truncated tensor file, expected {} bytes", len))?;
+        Tensor::from_raw_dt_shared(dt, &shape, data, owner)
+    }
+}
+
 pub fn write_tensor<W: std::io::Write>(w: &mut W, tensor: &Tensor) -> TractResult<()> {
     unsafe {
         let mut header: Header = std::mem::zeroed();
@@ -122,7 +150,7 @@ pub fn write_tensor<W: std::io::Write>(w: &mut W, tensor: &Tensor) -> TractResul
             w.write_all(tensor.as_bytes())?;
         } else if tensor.datum_type() == DatumType::String {
             for s in tensor.as_slice_unchecked::<String>() {
-                w.write_u32::<LE>(s.as_bytes().len() as u32)?;
+                w.write_u32::<LE>(s.len() as u32)?;
                 w.write_all(s.as_bytes())?;
             }
         }
@@ -138,4 +166,20 @@ mod test {
     fn header_is_128_bytes() {
         assert_eq!(std::mem::size_of::<Header>(), 128);
     }
+
+    #[test]
+    fn shared_tensor() -> TractResult<()> {
+        let t = tensor1(&[1f32, 2., 3.]);
+        let mut bytes = vec![];
+        write_tensor(&mut bytes, &t)?;
+        // a u32 buffer, so that the data is aligned for f32
+        let words: Vec<u32> =
+            bytes.chunks(4).map(|c| u32::from_ne_bytes([c[0], c[1], c[2], c[3]])).collect();
+        let owner = Arc::new(words);
+        let view = unsafe { std::slice::from_raw_parts(owner.as_ptr() as *const u8, bytes.len()) };
+        let shared = read_tensor_shared(view, owner.clone())?;
+        assert!(shared.is_shared());
+        assert_eq!(shared, t);
+        Ok(())
+    }
 }
@@ -1,6 +1,6 @@
 use std::hash::*;
-use tract_nnef::internal::*;
 use tract_itertools::Itertools;
+use tract_nnef::internal::*;
 use tract_smallvec::SmallVec;
 
 pub fn register(registry: &mut Registry) {
@@ -109,9 +109,7 @@ impl ReverseLookup {
             let keys = keys.as_slice_unchecked::<T>();
             let mut hashmap = HashMap::<u64, SmallVec<[i32; 1]>>::default();
             for (ix, k) in keys.iter().enumerate() {
-                let mut hasher = hashmap.hasher().build_hasher();
-                k.hash(&mut hasher);
-                let u = hasher.finish();
+                let u = hashmap.hasher().hash_one(&k);
                 hashmap.entry(u).or_default().push(ix as i32);
             }
             hashmap
@@ -122,9 +120,8 @@ impl ReverseLookup {
 
     unsafe fn search_t<T: Datum + Hash>(&self, needle: &T) -> Option<i32> {
         let keys = self.keys.as_slice_unchecked::<T>();
-        let mut hasher = self.index.hasher().build_hasher();
-        needle.hash(&mut hasher);
-        let u = hasher.finish();
+
+        let u = self.index.hasher().hash_one(&needle);
         if let Some(candidates) = self.index.get(&u) {
             for candidate in candidates {
                 if &keys[*candidate as usize] == needle {
@@ -52,8 +52,8 @@ impl Cmp {
 impl TryFrom<u8> for Cmp {
     type Error = TractError;
     fn try_from(value: u8) -> Result<Self, Self::Error> {
-        if value >= 1 && value <= 5 {
-            unsafe { Ok(std::mem::transmute(value)) }
+        if (1..=5).contains(&value) {
+            unsafe { Ok(std::mem::transmute::<u8, Cmp>(value)) }
         } else {
             bail!("Invalid value for Cmp: {}", value);
         }
@@ -91,7 +91,7 @@ pub struct TreeEnsembleData {
 impl TreeEnsembleData {
     unsafe fn get_unchecked(&self, node: usize) -> TreeNode {
         let row = &self.nodes.as_slice_unchecked::<u32>()[node * 5..][..5];
-        if let Some(cmp) = ((row[4] & 0xFF) as u8).try_into().ok() {
+        if let Ok(cmp) = ((row[4] & 0xFF) as u8).try_into() {
             let feature_id = row[0];
             let true_id = row[1];
             let false_id = row[2];
@@ -190,12 +190,6 @@ enum TreeNode {
     Leaf(LeafNode),
 }
 
-#[derive(Copy, Clone, Debug)]
-struct Leaf {
-    class_id: u32,
-    weight: f32,
-}
-
 pub trait AggregateFn: Default {
     fn aggregate(&mut self, score: f32, total: &mut f32);
 
@@ -248,20 +242,15 @@ impl AggregateFn for MinFn {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
 pub enum Aggregate {
+    #[default]
     Sum,
     Avg,
     Max,
     Min,
 }
 
-impl Default for Aggregate {
-    fn default() -> Self {
-        Aggregate::Sum
-    }
-}
-
 #[derive(Clone, Debug, Hash)]
 pub struct TreeEnsemble {
     pub data: TreeEnsembleData,
@@ -302,15 +291,16 @@ impl TreeEnsemble {
     }
 
     pub fn check_n_features(&self, n_features: usize) -> TractResult<()> {
-        Ok(ensure!(
+        let _: () = ensure!(
             n_features > self.max_used_feature,
             "Invalid input shape: input has {} features, tree ensemble use feature #{}",
             n_features,
             self.max_used_feature
-        ))
+        );
+        Ok(())
     }
 
-    fn eval_2d<'i, A, T>(&self, input: &ArrayView2<T>) -> TractResult<Array2<f32>>
+    fn eval_2d<A, T>(&self, input: &ArrayView2<T>) -> TractResult<Array2<f32>>
     where
         A: AggregateFn,
         T: AsPrimitive<f32>,
@@ -332,7 +322,7 @@ impl TreeEnsemble {
         Ok(output)
     }
 
-    fn eval_1d<'i, A, T>(&self, input: &ArrayView1<T>) -> TractResult<Array1<f32>>
+    fn eval_1d<A, T>(&self, input: &ArrayView1<T>) -> TractResult<Array1<f32>>
     where
         A: AggregateFn,
         T: AsPrimitive<f32>,
@@ -508,7 +498,7 @@ mod tests {
     fn generate_gbm_ensemble() -> TreeEnsemble {
         // converted manually from LightGBM, fitted on iris dataset
         let trees = generate_gbm_trees();
-        TreeEnsemble::build(trees, 4, 3, Aggregate::Sum).unwrap()
+        TreeEnsemble::build(trees, 3, 3, Aggregate::Sum).unwrap()
     }
 
     fn generate_gbm_input() -> Array2<f32> {
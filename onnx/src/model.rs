@@ -6,6 +6,7 @@ use std::collections::HashMap;
 use tract_hir::internal::*;
 
 use crate::pb;
+use crate::tensor::ExternalData;
 use prost::Message;
 
 pub fn optional_inputs(pb: &pb::NodeProto) -> impl Iterator<Item = Option<usize>> + '_ {
@@ -38,6 +39,8 @@ pub struct ParsingContext<'a> {
     pub framework: &'a Onnx,
     pub model: &'a pb::ModelProto,
     pub parent_graphs: Vec<&'a pb::GraphProto>,
+    pub external_data: &'a ExternalData,
+    pub symbol_table: SymbolScope,
 }
 
 #[derive(Clone, Debug)]
@@ -52,12 +55,13 @@ impl<'a> ParsingContext<'a> {
         let mut ctx = self.clone();
         ctx.parent_graphs.push(graph);
         let mut model = InferenceModel::default();
+        model.symbol_table = self.symbol_table.clone();
         let mut unresolved_inputs = vec![];
         let mut closures_to_wire = vec![];
         let mut initializers: HashMap<&str, Tensor> = graph
             .initializer
             .iter()
-            .map(|init| Ok((&*init.name, init.try_into()?)))
+            .map(|init| Ok((&*init.name, self.external_data.load(init)?)))
             .collect::<TractResult<_>>()?;
         for (k, v) in initializers.iter() {
             trace!("Initializer: {} {:?}", k, v);
@@ -72,7 +76,10 @@ impl<'a> ParsingContext<'a> {
                 let fact = input.r#type.as_ref().unwrap().value.as_ref().unwrap();
                 #[allow(irrefutable_let_patterns)]
                 let fact: InferenceFact = if let pb::type_proto::Value::TensorType(fact) = fact {
-                    fact.try_into()?
+                    // named dims of the model inputs are symbols: equal names, equal dims
+                    let symbols =
+                        Some(&self.symbol_table).filter(|_| self.parent_graphs.is_empty());
+                    crate::tensor::translate_inference_fact(fact, symbols)?
                 } else {
                     bail!("Can not parse tensor type");
                 };
@@ -90,9 +97,9 @@ impl<'a> ParsingContext<'a> {
         }
         let consts = model.nodes().len();
         for pbnode in graph.node.iter() {
-            let name = if pbnode.name != "" {
+            let name = if !pbnode.name.is_empty() {
                 pbnode.name.to_string()
-            } else if pbnode.output.len() > 0 && pbnode.output[0] != "" {
+            } else if pbnode.output.len() > 0 && !pbnode.output[0].is_empty() {
                 pbnode.output[0].to_owned()
             } else {
                 format!("{}-{}", model.nodes().len(), pbnode.op_type)
@@ -199,30 +206,62 @@ pub struct Onnx {
 }
 
 impl Onnx {
-    pub fn parse(&self, proto: &pb::ModelProto) -> TractResult<ParseResult> {
+    /// Translate a proto model, resolving external data files relatively to `model_dir`.
+    pub fn parse(
+        &self,
+        proto: &pb::ModelProto,
+        model_dir: Option<&path::Path>,
+    ) -> TractResult<ParseResult> {
         let onnx_operator_set_version = proto
             .opset_import
             .iter()
-            .find(|import| import.domain == "" || import.domain == "ai.onnx")
+            .find(|import| import.domain.is_empty() || import.domain == "ai.onnx")
             .map(|op| op.version)
             .unwrap_or(0);
         let graph = &proto.graph;
         debug!("ONNX operator set version: {:?}", onnx_operator_set_version);
-        if onnx_operator_set_version != 0
-            && (onnx_operator_set_version < 9 || onnx_operator_set_version > 12)
-        {
+        if onnx_operator_set_version != 0 && !(9..=12).contains(&onnx_operator_set_version) {
             warn!("ONNX operator for your model is {}, tract is tested against \
                   operator set 9, 10, 11 and 12 only. Your model may still work so this is not a hard fail.",
                   onnx_operator_set_version);
         }
+        let external_data = ExternalData::new(model_dir);
         let ctx = ParsingContext {
             framework: self,
             model: proto,
             parent_graphs: vec![],
             onnx_operator_set_version,
+            external_data: &external_data,
+            symbol_table: SymbolScope::default(),
         };
         ctx.parse_graph(graph.as_ref().unwrap())
     }
+
+    /// Parse a proto model from a reader, fetching the content of external data files through
+    /// `resolver` (called with the file location as stored in the model).
+    ///
+    /// The resulting proto model is self-contained.
+    pub fn proto_model_for_read_with_resolver(
+        &self,
+        r: &mut dyn std::io::Read,
+        resolver: &dyn Fn(&str) -> TractResult<Vec<u8>>,
+    ) -> TractResult<pb::ModelProto> {
+        let mut proto = self.proto_model_for_read(r)?;
+        crate::tensor::inline_external_data(&mut proto, resolver)?;
+        Ok(proto)
+    }
+
+    fn model_for_proto_model_and_dir(
+        &self,
+        proto: &pb::ModelProto,
+        model_dir: Option<&path::Path>,
+    ) -> TractResult<InferenceModel> {
+        let ParseResult { model, unresolved_inputs, .. } = self.parse(proto, model_dir)?;
+        if unresolved_inputs.len() > 0 {
+            bail!("Could not resolve inputs at top-level: {:?}", unresolved_inputs)
+        }
+        Ok(model)
+    }
 }
 
 impl Framework<pb::ModelProto, InferenceModel> for Onnx {
@@ -242,10 +281,178 @@ impl Framework<pb::ModelProto, InferenceModel> for Onnx {
     }
 
     fn model_for_proto_model(&self, proto: &pb::ModelProto) -> TractResult<InferenceModel> {
-        let ParseResult { model, unresolved_inputs, .. } = self.parse(proto)?;
-        if unresolved_inputs.len() > 0 {
-            bail!("Could not resolve inputs at top-level: {:?}", unresolved_inputs)
+        self.model_for_proto_model_and_dir(proto, None)
+    }
+
+    fn model_for_path(&self, p: impl AsRef<path::Path>) -> TractResult<InferenceModel> {
+        let proto = self.proto_model_for_path(p.as_ref())?;
+        self.model_for_proto_model_and_dir(&proto, p.as_ref().parent())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::pb::tensor_proto::{DataLocation, DataType};
+    use crate::pb::*;
+
+    fn model_with_external_weights(location: &str, constant: bool) -> ModelProto {
+        let fact = TypeProto {
+            value: Some(type_proto::Value::TensorType(type_proto::Tensor {
+                elem_type: DataType::Float as i32,
+                shape: Some(TensorShapeProto {
+                    dim: vec![tensor_shape_proto::Dimension {
+                        value: Some(tensor_shape_proto::dimension::Value::DimValue(4)),
+                        ..Default::default()
+                    }],
+                }),
+            })),
+            ..Default::default()
+        };
+        let entry = |key: &str, value: &str| StringStringEntryProto {
+            key: key.to_string(),
+            value: value.to_string(),
+        };
+        let weights = TensorProto {
+            name: "w".to_string(),
+            dims: vec![4],
+            data_type: DataType::Float as i32,
+            data_location: DataLocation::External as i32,
+            external_data: vec![
+                entry("location", location),
+                entry("offset", "4"),
+                entry("length", "16"),
+            ],
+            ..Default::default()
+        };
+        let mut node = vec![NodeProto {
+            input: vec!["x".to_string(), "w".to_string()],
+            output: vec!["y".to_string()],
+            op_type: "Add".to_string(),
+            ..Default::default()
+        }];
+        let mut initializer = vec![];
+        if constant {
+            let value = AttributeProto {
+                name: "value".to_string(),
+                r#type: attribute_proto::AttributeType::Tensor as i32,
+                t: Some(weights),
+                ..Default::default()
+            };
+            node.insert(
+                0,
+                NodeProto {
+                    output: vec!["w".to_string()],
+                    op_type: "Constant".to_string(),
+                    attribute: vec![value],
+                    ..Default::default()
+                },
+            );
+        } else {
+            initializer.push(weights);
         }
-        Ok(model)
+        let graph = GraphProto {
+            node,
+            initializer,
+            input: vec![ValueInfoProto {
+                name: "x".to_string(),
+                r#type: Some(fact.clone()),
+                ..Default::default()
+            }],
+            output: vec![ValueInfoProto {
+                name: "y".to_string(),
+                r#type: Some(fact),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        ModelProto {
+            opset_import: vec![OperatorSetIdProto { domain: String::new(), version: 11 }],
+            graph: Some(graph),
+            ..Default::default()
+        }
+    }
+
+    fn weights_file() -> Vec<u8> {
+        let mut bytes = vec![0u8; 4];
+        for w in &[1f32, 2., 3., 4.] {
+            bytes.extend(w.to_le_bytes().iter());
+        }
+        bytes
+    }
+
+    fn check(model: InferenceModel) -> TractResult<()> {
+        let model = model.into_optimized()?.into_runnable()?;
+        let result = model.run(tvec!(tensor1(&[1f32, 1., 1., 1.])))?;
+        assert_eq!(*result[0], tensor1(&[2f32, 3., 4., 5.]));
+        Ok(())
+    }
+
+    fn model_for_path(location: &str, constant: bool) -> TractResult<InferenceModel> {
+        static COUNT: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+        let count = COUNT.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        let dir = std::env::temp_dir().join(format!(
+            "tract-onnx-external-{}-{}",
+            std::process::id(),
+            count
+        ));
+        fs::create_dir_all(dir.join("model"))?;
+        let mut proto = vec![];
+        model_with_external_weights(location, constant).encode(&mut proto)?;
+        fs::write(dir.join("model/model.onnx"), proto)?;
+        fs::write(dir.join("weights.bin"), weights_file())?;
+        fs::write(dir.join("model/weights.bin"), weights_file())?;
+        let model = crate::onnx().model_for_path(dir.join("model/model.onnx"));
+        fs::remove_dir_all(&dir)?;
+        model
+    }
+
+    #[test]
+    fn external_data_from_path() -> TractResult<()> {
+        check(model_for_path("weights.bin", false)?)
+    }
+
+    #[test]
+    fn external_data_in_constant_from_path() -> TractResult<()> {
+        check(model_for_path("./weights.bin", true)?)
+    }
+
+    #[test]
+    fn external_data_outside_model_dir() {
+        assert!(model_for_path("../weights.bin", false).is_err());
+        assert!(model_for_path("sub/../../weights.bin", true).is_err());
+        let absolute = std::env::temp_dir().join("weights.bin");
+        assert!(model_for_path(absolute.to_str().unwrap(), false).is_err());
+    }
+
+    #[test]
+    fn external_data_from_resolver() -> TractResult<()> {
+        let mut proto = vec![];
+        model_with_external_weights("weights.bin", false).encode(&mut proto)?;
+        let onnx = crate::onnx();
+        let proto = onnx.proto_model_for_read_with_resolver(&mut &*proto, &|location| {
+            assert_eq!(location, "weights.bin");
+            Ok(weights_file())
+        })?;
+        check(onnx.model_for_proto_model(&proto)?)
+    }
+
+    #[test]
+    fn external_data_out_of_file() -> TractResult<()> {
+        let onnx = crate::onnx();
+        for (offset, length) in &[(usize::MAX.to_string(), "16"), ("4".to_string(), "32")] {
+            let mut model = model_with_external_weights("weights.bin", false);
+            let weights = &mut model.graph.as_mut().unwrap().initializer[0];
+            weights.external_data[1].value = offset.clone();
+            weights.external_data[2].value = length.to_string();
+            let mut proto = vec![];
+            model.encode(&mut proto)?;
+            let err = onnx
+                .proto_model_for_read_with_resolver(&mut &*proto, &|_| Ok(weights_file()))
+                .and_then(|proto| onnx.model_for_proto_model(&proto))
+                .unwrap_err();
+            assert!(format!("{:?}", err).contains("External data for w"));
+        }
+        Ok(())
     }
 }
@@ -15,18 +15,24 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("Concat", concat);
     reg.insert("ConstantLike", constant_like);
     reg.insert("ConstantOfShape", constant_of_shape);
-    reg.insert("Expand", |_, _| Ok((expand(array::MultiBroadcastTo::default()), vec![])));
+    reg.insert("Expand", |_, _| Ok((expand(array::MultiBroadcastTo), vec![])));
     reg.insert("EyeLike", eye_like);
     reg.insert("Flatten", flatten);
     reg.insert("Gather", gather);
+    reg.insert("GatherElements", gather_elements);
+    reg.insert("GatherND", gather_nd);
     reg.insert("NonZero", |_, _| Ok((Box::new(nonzero::NonZero::non_zero()), vec![])));
     reg.insert("OneHot", one_hot::one_hot);
     reg.insert("Pad", pad::pad);
     reg.insert("Reshape", |_, _| Ok((expand(array::Reshape::default()), vec![])));
+    reg.insert("Scatter", scatter_elements);
+    reg.insert("ScatterElements", scatter_elements);
+    reg.insert("ScatterND", scatter_nd);
     reg.insert("Shape", |_, _| Ok((expand(array::Shape::new(DatumType::I64)), vec![])));
     reg.insert("Size", |_, _| Ok((expand(array::Size::new(DatumType::I64)), vec![])));
     reg.insert("Transpose", transpose);
-    reg.insert("Tile", |_, _| Ok((expand(array::Tile::default()), vec![])));
+    reg.insert("Tile", |_, _| Ok((expand(array::Tile), vec![])));
+    reg.insert("TopK", topk);
     reg.insert("Slice", slice::slice);
     reg.insert("Split", split);
     reg.insert("Squeeze", squeeze);
@@ -99,6 +105,47 @@ pub fn gather(
     Ok((Box::new(array::Gather::new(axis)), vec![]))
 }
 
+pub fn gather_elements(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let axis = node.get_attr_opt("axis")?.unwrap_or(0);
+    Ok((expand(array::GatherElements::new(axis)), vec![]))
+}
+
+pub fn gather_nd(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let batch_dims = node.get_attr_opt("batch_dims")?.unwrap_or(0);
+    Ok((Box::new(array::GatherNd::new(batch_dims)), vec![]))
+}
+
+pub fn scatter_elements(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let axis = node.get_attr_opt("axis")?.unwrap_or(0);
+    scatter_reduction(node)?;
+    Ok((expand(array::ScatterElements::new(axis)), vec![]))
+}
+
+pub fn scatter_nd(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    scatter_reduction(node)?;
+    Ok((Box::new(array::ScatterNd), vec![]))
+}
+
+/// Only plain overwrite is supported, not the reductions of opset 16.
+fn scatter_reduction(node: &NodeProto) -> TractResult<()> {
+    match node.get_attr_opt("reduction")? {
+        None | Some("none") => Ok(()),
+        Some(reduction) => node.check_value("reduction", Err(reduction)),
+    }
+}
+
 pub fn split(
     _ctx: &ParsingContext,
     node: &NodeProto,
@@ -116,6 +163,19 @@ pub fn squeeze(
     Ok((expand(array::Squeeze::new(axes)), vec![]))
 }
 
+pub fn topk(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    if node.get_attr_opt::<i64>("k")?.is_some() {
+        bail!("TopK with k as an attribute (opset 1) is not supported");
+    }
+    let axis = node.get_attr_opt("axis")?.unwrap_or(-1);
+    let largest = node.get_attr_opt("largest")?.unwrap_or(1i64) == 1;
+    let sorted = node.get_attr_opt("sorted")?.unwrap_or(1i64) == 1;
+    Ok((expand(array::TopK::new(axis, largest, sorted)), vec![]))
+}
+
 pub fn transpose(
     _ctx: &ParsingContext,
     node: &NodeProto,
@@ -19,12 +19,12 @@ impl NonZero {
         let mut output = Tensor::uninitialized::<i64>(&[input.rank(), count])?;
         let mut view_mut: tract_ndarray::ArrayViewMut2<i64> =
             output.to_array_view_mut_unchecked::<i64>().into_dimensionality().unwrap();
-        let mut i = 0;
-        for (coords, _) in view.indexed_iter().filter(|(_, value)| !value.is_zero()) {
+        for (i, (coords, _)) in
+            view.indexed_iter().filter(|(_, value)| !value.is_zero()).enumerate()
+        {
             view_mut
                 .index_axis_mut(tract_ndarray::Axis(1), i)
                 .assign(&coords.as_array_view().map(|d| *d as i64));
-            i += 1;
         }
         Ok(output)
     }
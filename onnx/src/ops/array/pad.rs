@@ -8,7 +8,7 @@ pub fn pad(
     node: &NodeProto,
 ) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
     match ctx.onnx_operator_set_version {
-        2 | 3 | 4 | 5 | 6 | 7 | 8 | 9 | 10 => pad_2(ctx, node),
+        2..=10 => pad_2(ctx, node),
         v if v >= 10 => pad_11(ctx, node),
         _ => bail!("Unsupported operator set for Pad operator"),
     }
@@ -7,7 +7,7 @@ pub fn slice(
     node: &NodeProto,
 ) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
     let v = ctx.onnx_operator_set_version;
-    if v >= 1 && v < 10 {
+    if (1..10).contains(&v) {
         slice1(ctx, node)
     } else {
         slice10(ctx, node)
@@ -58,25 +58,12 @@ impl Expansion for Slice1 {
             (0..shape.len()).try_for_each(move |axis| {
                 let d = &shape[axis];
                 let spec = if let Some(axes) = self.axes.as_ref() {
-                    if let Some(ix) = axes.iter().position(|&a| a == axis) {
-                        Some((self.starts[ix], self.ends[ix]))
-                    } else {
-                        None
-                    }
+                    axes.iter().position(|&a| a == axis).map(|ix| (self.starts[ix], self.ends[ix]))
                 } else {
                     Some((self.starts[axis].into(), self.ends[axis].into()))
                 };
-                if let Some((mut b, mut e)) = spec {
-                    if let Ok(d) = d.to_isize() {
-                        if b > d {
-                            b = d.into();
-                        }
-                        if e > d {
-                            e = d.into();
-                        }
-                    }
-                    let b = if b < 0 { d.bex() + TDim::from(b) } else { TDim::from(b).bex() };
-                    let e = if e < 0 { d.bex() + TDim::from(e) } else { TDim::from(e).bex() };
+                if let Some((b, e)) = spec {
+                    let (b, e) = clamp(d, b, e);
                     s.equals(&outputs[0].shape[axis], e - b)
                 } else {
                     s.equals(&outputs[0].shape[axis], &shape[axis])
@@ -97,18 +84,13 @@ impl Expansion for Slice1 {
         for (ix, (&b, &e)) in self.starts.iter().zip(self.ends.iter()).enumerate() {
             let axis = self.axes.as_ref().map(|axes| axes[ix]).unwrap_or(ix);
             let dim = &input.shape[axis];
-            if let Ok(dim) = dim.to_isize() {
-                let b = (if b >= 0 { b.min(dim) } else { dim + b }) as usize;
-                let e = (if e >= 0 { e.min(dim) } else { dim + e }) as usize;
-                if b > 0 || e < dim as usize {
-                    wire = target.wire_node(
-                        format!("{}.axis-{}", prefix, axis),
-                        tract_hir::ops::array::Slice::new(axis, b, e),
-                        [wire].as_ref(),
-                    )?[0];
-                }
-            } else {
-                bail!("Can't translate slice: axis={} dim={} b={} e={}", axis, dim, b, e)
+            let (b, e) = clamp(dim, b, e);
+            if b != 0.to_dim() || &e != dim {
+                wire = target.wire_node(
+                    format!("{}.axis-{}", prefix, axis),
+                    tract_hir::ops::array::Slice::new(axis, b, e),
+                    [wire].as_ref(),
+                )?[0];
             }
         }
         target.rename_node(wire.node, &*prefix)?;
@@ -116,6 +98,25 @@ impl Expansion for Slice1 {
     }
 }
 
+/// Start and end of a slice on an axis of size `dim`, as ONNX clamps them.
+fn clamp(dim: &TDim, b: isize, e: isize) -> (TDim, TDim) {
+    let clamp = |x: isize| {
+        if x <= i32::MIN as isize {
+            // INT_MIN and below stand for "from the start" for axes of unknown size
+            0.to_dim()
+        } else if x < 0 {
+            // counted from the end, and clamped to the start
+            (dim.clone() + x).max(0.to_dim())
+        } else if x >= i32::MAX as isize {
+            // INT_MAX and above stand for "up to the end" for axes of unknown size
+            dim.clone()
+        } else {
+            TDim::from(x).min(dim.clone())
+        }
+    };
+    (clamp(b), clamp(e))
+}
+
 fn slice10(
     _ctx: &ParsingContext,
     node: &NodeProto,
@@ -132,3 +133,22 @@ fn slice10(
         vec![],
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clamp_bounds() {
+        let d = 10.to_dim();
+        assert_eq!(clamp(&d, -3, -1), (7.to_dim(), 9.to_dim()));
+        assert_eq!(clamp(&d, -15, 4), (0.to_dim(), 4.to_dim()));
+        assert_eq!(clamp(&d, 2, 12), (2.to_dim(), 10.to_dim()));
+        let s = Symbol::new("s");
+        let d = s.to_dim();
+        assert_eq!(clamp(&d, i32::MIN as isize, i32::MAX as isize), (0.to_dim(), d.clone()));
+        let (b, _) = clamp(&d, -4, -1);
+        assert_eq!(b.eval(&SymbolValues::default().with(s.clone(), 2)), 0.to_dim());
+        assert_eq!(b.eval(&SymbolValues::default().with(s, 6)), 2.to_dim());
+    }
+}
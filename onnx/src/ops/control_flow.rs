@@ -0,0 +1,460 @@
+use crate::model::{optional_inputs, OnnxOpRegister, ParseResult, ParsingContext};
+use crate::pb::*;
+use tract_hir::infer::*;
+use tract_hir::internal::*;
+
+use tract_core::ops::control_flow;
+
+pub fn register_all_ops(reg: &mut OnnxOpRegister) {
+    reg.insert("If", if_then_else);
+    reg.insert("Loop", loop_);
+}
+
+pub fn if_then_else(
+    ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let then_graph: &GraphProto = node.get_attr("then_branch")?;
+    let else_graph: &GraphProto = node.get_attr("else_branch")?;
+    let ParseResult { model: then_body, unresolved_inputs: then_closures, .. } =
+        ctx.parse_graph(then_graph)?;
+    let ParseResult { model: else_body, unresolved_inputs: else_closures, .. } =
+        ctx.parse_graph(else_graph)?;
+    let mut closures: Vec<String> = then_closures.clone();
+    for c in &else_closures {
+        if !closures.contains(c) {
+            closures.push(c.clone());
+        }
+    }
+    // outer input 0 is the condition, closures follow
+    let mapping = |branch: &[String]| {
+        branch.iter().map(|c| 1 + closures.iter().position(|o| o == c).unwrap()).collect()
+    };
+    let then_input_mapping = mapping(&then_closures);
+    let else_input_mapping = mapping(&else_closures);
+    Ok((Box::new(If { then_body, then_input_mapping, else_body, else_input_mapping }), closures))
+}
+
+pub fn loop_(
+    ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let graph: &GraphProto = node.get_attr("body")?;
+    let ParseResult { model: body, unresolved_inputs, .. } = ctx.parse_graph(graph)?;
+    let mut options = optional_inputs(node);
+    let optional_trip_count_input = options.next().unwrap();
+    let optional_cond_input = options.next().unwrap();
+    let state_count = body.input_outlets()?.len() - 2 - unresolved_inputs.len();
+    Ok((
+        Box::new(Loop { body, optional_trip_count_input, optional_cond_input, state_count }),
+        unresolved_inputs,
+    ))
+}
+
+fn bool_scalar() -> InferenceFact {
+    InferenceFact::dt_shape(bool::datum_type(), TVec::<usize>::new())
+}
+
+fn i64_scalar() -> InferenceFact {
+    InferenceFact::dt_shape(i64::datum_type(), TVec::<usize>::new())
+}
+
+fn known_dim(shape: &ShapeFactoid, axis: usize) -> Option<TDim> {
+    shape.dim(axis).and_then(|d| d.concretize())
+}
+
+/// Unify datum types and ranks, leaving the dimensions free.
+fn unify_dt_rank(a: &mut InferenceFact, b: &mut InferenceFact) -> TractResult<bool> {
+    let mut changed = a.datum_type.unify_with_mut(&mut b.datum_type)?;
+    let rank = a.shape.rank().concretize().or_else(|| b.shape.rank().concretize());
+    if let Some(rank) = rank {
+        let shape = ShapeFactoid::closed(tvec![DimFact::default(); rank as usize]);
+        changed |= a.shape.unify_with(&shape)?;
+        changed |= b.shape.unify_with(&shape)?;
+    }
+    Ok(changed)
+}
+
+#[derive(Debug, Clone, Default, Hash)]
+pub struct If {
+    pub then_body: InferenceModel,
+    pub then_input_mapping: Vec<usize>,
+    pub else_body: InferenceModel,
+    pub else_input_mapping: Vec<usize>,
+}
+
+impl_dyn_hash!(If);
+
+impl If {
+    fn to_core(&self, symbols: &SymbolScope) -> TractResult<control_flow::IfThenElse> {
+        control_flow::IfThenElse::new(
+            self.then_body.clone().into_typed()?,
+            self.then_input_mapping.clone(),
+            self.else_body.clone().into_typed()?,
+            self.else_input_mapping.clone(),
+            symbols,
+        )
+    }
+}
+
+impl Op for If {
+    fn name(&self) -> Cow<str> {
+        "If".into()
+    }
+
+    op_onnx!();
+    not_a_typed_op!();
+}
+
+impl EvalOp for If {
+    fn is_stateless(&self) -> bool {
+        false
+    }
+
+    fn state(
+        &self,
+        session: &mut SessionState,
+        node_id: usize,
+    ) -> TractResult<Option<Box<dyn OpState>>> {
+        self.to_core(&self.then_body.symbol_table)?.state(session, node_id)
+    }
+}
+
+impl InferenceOp for If {
+    fn infer_facts(
+        &mut self,
+        inputs: TVec<&InferenceFact>,
+        outputs: TVec<&InferenceFact>,
+        _observed: TVec<&InferenceFact>,
+    ) -> TractResult<(TVec<InferenceFact>, TVec<InferenceFact>, TVec<InferenceFact>)> {
+        let mut inputs: TVec<InferenceFact> = inputs.into_iter().cloned().collect();
+        let mut outputs: TVec<InferenceFact> = outputs.into_iter().cloned().collect();
+        inputs[0].unify_with(&bool_scalar())?;
+        loop {
+            let mut changed = false;
+            for (body, mapping) in &mut [
+                (&mut self.then_body, &self.then_input_mapping),
+                (&mut self.else_body, &self.else_input_mapping),
+            ] {
+                for (ix, slot) in mapping.iter().enumerate() {
+                    changed |= inputs[*slot].unify_with_mut(body.input_fact_mut(ix)?)?;
+                }
+                changed |= body.analyse(false).context("analysing branch")?;
+                for (ix, output) in outputs.iter_mut().enumerate() {
+                    let fact = body.output_fact_mut(ix)?;
+                    changed |= output.datum_type.unify_with_mut(&mut fact.datum_type)?;
+                }
+            }
+            if let Some(cond) = inputs[0].value.concretize() {
+                let body = if *cond.to_scalar::<bool>()? {
+                    &mut self.then_body
+                } else {
+                    &mut self.else_body
+                };
+                for (ix, output) in outputs.iter_mut().enumerate() {
+                    changed |= output.shape.unify_with_mut(&mut body.output_fact_mut(ix)?.shape)?;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        Ok((inputs, outputs, tvec!()))
+    }
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let inputs = node.inputs.iter().map(|m| mapping[m]).collect::<TVec<_>>();
+        let op = self.to_core(&target.symbol_table)?;
+        target.wire_node(&*node.name, op, &*inputs)
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(self.then_body.output_outlets()?.len())
+    }
+
+    as_op!();
+}
+
+#[derive(Debug, Clone, Default, Hash)]
+pub struct Loop {
+    pub body: InferenceModel,
+    pub optional_trip_count_input: Option<usize>,
+    pub optional_cond_input: Option<usize>,
+    pub state_count: usize,
+}
+
+impl_dyn_hash!(Loop);
+
+impl Loop {
+    fn first_state_input(&self) -> usize {
+        self.optional_trip_count_input.is_some() as usize
+            + self.optional_cond_input.is_some() as usize
+    }
+
+    fn to_core(&self, symbols: &SymbolScope) -> TractResult<control_flow::Loop> {
+        let iters = symbols.new_with_prefix("loop_iters");
+        Ok(control_flow::Loop::new(self.body.clone().into_typed()?, self.state_count, iters))
+    }
+
+    /// Set the dimensions of the loop-carried body inputs.
+    ///
+    /// Loop-carried values may change shape from one iteration to the next.
+    /// A dimension the body leaves unchanged is taken from the initial value,
+    /// a dimension it changes becomes a symbol.
+    fn infer_state_dims(&mut self, initial: &[InferenceFact]) -> TractResult<bool> {
+        let mut changed = false;
+        let mut probe: Option<InferenceModel> = None;
+        for (ix, init) in initial.iter().enumerate() {
+            let rank = if let Some(rank) = init.shape.rank().concretize() {
+                rank as usize
+            } else {
+                continue;
+            };
+            for axis in 0..rank {
+                if known_dim(&self.body.input_fact(2 + ix)?.shape, axis).is_some() {
+                    continue;
+                }
+                let init_dim =
+                    if let Some(d) = known_dim(&init.shape, axis) { d } else { continue };
+                if probe.is_none() {
+                    probe = Some(self.probe_body(initial)?);
+                }
+                let probe = probe.as_ref().unwrap();
+                let dim = match known_dim(&probe.output_fact(1 + ix)?.shape, axis) {
+                    None => continue,
+                    Some(output_dim) if output_dim == init_dim => init_dim,
+                    Some(_) => self
+                        .body
+                        .symbol_table
+                        .new_with_prefix(&format!("loop_state_{}_{}", ix, axis))
+                        .to_dim(),
+                };
+                changed |= self.body.input_fact_mut(2 + ix)?.shape.set_dim(axis, dim);
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Body analysed with the undecided dimensions of the loop-carried inputs
+    /// set from the initial values.
+    fn probe_body(&self, initial: &[InferenceFact]) -> TractResult<InferenceModel> {
+        let mut probe = self.body.clone();
+        for (ix, init) in initial.iter().enumerate() {
+            let fact = probe.input_fact_mut(2 + ix)?;
+            for axis in 0..init.shape.dims().count() {
+                if let (Some(dim), None) =
+                    (known_dim(&init.shape, axis), known_dim(&fact.shape, axis))
+                {
+                    fact.shape.set_dim(axis, dim);
+                }
+            }
+        }
+        probe.analyse(false).context("analysing loop body")?;
+        Ok(probe)
+    }
+
+    /// Insert the default trip count and condition when they are omitted.
+    fn complete_inputs<T: Clone>(&self, inputs: &[T], trip_count: T, cond: T) -> TVec<T> {
+        let mut full: TVec<T> = tvec!();
+        full.push(self.optional_trip_count_input.map(|i| inputs[i].clone()).unwrap_or(trip_count));
+        full.push(self.optional_cond_input.map(|i| inputs[i].clone()).unwrap_or(cond));
+        full.extend(inputs[self.first_state_input()..].iter().cloned());
+        full
+    }
+}
+
+impl Op for Loop {
+    fn name(&self) -> Cow<str> {
+        "Loop".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("loop-carried values: {}", self.state_count)])
+    }
+
+    op_onnx!();
+    not_a_typed_op!();
+}
+
+impl EvalOp for Loop {
+    fn is_stateless(&self) -> bool {
+        false
+    }
+
+    fn state(
+        &self,
+        session: &mut SessionState,
+        node_id: usize,
+    ) -> TractResult<Option<Box<dyn OpState>>> {
+        let core = self.to_core(&self.body.symbol_table)?;
+        let state = core.state(session, node_id)?.context("Loop is expected to be stateful")?;
+        Ok(Some(Box::new(LoopState { core, state })))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct LoopState {
+    core: control_flow::Loop,
+    state: Box<dyn OpState>,
+}
+
+impl OpState for LoopState {
+    fn eval(
+        &mut self,
+        session: &mut SessionState,
+        op: &dyn Op,
+        inputs: TVec<Arc<Tensor>>,
+    ) -> TractResult<TVec<Arc<Tensor>>> {
+        let op = op.downcast_ref::<Loop>().context("Wrong op")?;
+        let inputs = op.complete_inputs(
+            &inputs,
+            tensor0(i64::MAX).into_arc_tensor(),
+            tensor0(true).into_arc_tensor(),
+        );
+        self.state.eval(session, &self.core, inputs)
+    }
+
+    fn snapshot(&self) -> TractResult<TVec<Tensor>> {
+        self.state.snapshot()
+    }
+
+    fn restore(&mut self, tensors: TVec<Tensor>) -> TractResult<()> {
+        self.state.restore(tensors)
+    }
+}
+
+impl InferenceOp for Loop {
+    fn infer_facts(
+        &mut self,
+        inputs: TVec<&InferenceFact>,
+        outputs: TVec<&InferenceFact>,
+        _observed: TVec<&InferenceFact>,
+    ) -> TractResult<(TVec<InferenceFact>, TVec<InferenceFact>, TVec<InferenceFact>)> {
+        let mut inputs: TVec<InferenceFact> = inputs.into_iter().cloned().collect();
+        let mut outputs: TVec<InferenceFact> = outputs.into_iter().cloned().collect();
+        let first_state = self.first_state_input();
+        let closures = inputs.len() - first_state - self.state_count;
+        if let Some(trip_count) = self.optional_trip_count_input {
+            inputs[trip_count].unify_with(&i64_scalar())?;
+        }
+        if let Some(cond) = self.optional_cond_input {
+            inputs[cond].unify_with(&bool_scalar())?;
+        }
+        self.body.input_fact_mut(0)?.unify_with(&i64_scalar())?;
+        self.body.input_fact_mut(1)?.unify_with(&bool_scalar())?;
+        self.body.output_fact_mut(0)?.unify_with(&bool_scalar())?;
+        loop {
+            let mut changed = false;
+            for ix in 0..self.state_count {
+                let input = &mut inputs[first_state + ix];
+                changed |= unify_dt_rank(input, self.body.input_fact_mut(2 + ix)?)?;
+                changed |= unify_dt_rank(input, self.body.output_fact_mut(1 + ix)?)?;
+                changed |= unify_dt_rank(input, &mut outputs[ix])?;
+            }
+            for ix in 0..closures {
+                let input = &mut inputs[first_state + self.state_count + ix];
+                changed |=
+                    input.unify_with_mut(self.body.input_fact_mut(2 + self.state_count + ix)?)?;
+            }
+            changed |= self.infer_state_dims(&inputs[first_state..][..self.state_count])?;
+            changed |= self.body.analyse(false).context("analysing loop body")?;
+            for ix in 0..self.state_count {
+                let input = self.body.input_fact(2 + ix)?.shape.clone();
+                let output = self.body.output_fact(1 + ix)?.shape.clone();
+                for (axis, (i, o)) in input.dims().zip(output.dims()).enumerate() {
+                    if let (Some(i), Some(o)) = (i.concretize(), o.concretize()) {
+                        if i == o && i.to_usize().is_ok() {
+                            changed |= outputs[ix].shape.set_dim(axis, i);
+                        }
+                    }
+                }
+            }
+            for (ix, output) in outputs.iter_mut().enumerate().skip(self.state_count) {
+                let fact = self.body.output_fact_mut(1 + ix)?;
+                changed |= output.datum_type.unify_with_mut(&mut fact.datum_type)?;
+                if !fact.shape.is_open() {
+                    let stacked = std::iter::once(DimFact::default())
+                        .chain(fact.shape.dims().cloned())
+                        .collect();
+                    changed |= output.shape.unify_with(&ShapeFactoid::closed(stacked))?;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        Ok((inputs, outputs, tvec!()))
+    }
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let inputs = node.inputs.iter().map(|m| mapping[m]).collect::<TVec<_>>();
+        let trip_count = match self.optional_trip_count_input {
+            Some(ix) => inputs[ix],
+            None => target.add_const(format!("{}.trip_count", node.name), tensor0(i64::MAX))?,
+        };
+        let cond = match self.optional_cond_input {
+            Some(ix) => inputs[ix],
+            None => target.add_const(format!("{}.cond", node.name), tensor0(true))?,
+        };
+        let inputs = self.complete_inputs(&inputs, trip_count, cond);
+        let op = self.to_core(&target.symbol_table)?;
+        target.wire_node(&*node.name, op, &*inputs)
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(self.body.output_outlets()?.len() - 1)
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tract_hir::ops::array::Concat;
+
+    #[test]
+    fn growing_loop_carried_value() -> TractResult<()> {
+        // acc = concat(acc, [1]) at each iteration
+        let mut body = InferenceModel::default();
+        body.add_source("iter", InferenceFact::default())?;
+        let cond = body.add_source("cond", InferenceFact::default())?;
+        let acc = body.add_source("acc", InferenceFact::default())?;
+        let one = body.add_const("one", tensor1(&[1f32]))?;
+        let acc = body.wire_node("grow", expand(Concat::new(0)), &[acc, one])?;
+        body.set_output_outlets(&[cond, acc[0]])?;
+        let mut model = InferenceModel::default();
+        let trip_count = model.add_const("trip_count", tensor0(3i64))?;
+        let init =
+            model.add_source("init", InferenceFact::dt_shape(f32::datum_type(), tvec!(2usize)))?;
+        let op = Loop {
+            body,
+            optional_trip_count_input: Some(0),
+            optional_cond_input: None,
+            state_count: 1,
+        };
+        let output = model.wire_node("loop", op, &[trip_count, init])?;
+        model.set_output_outlets(&output)?;
+        let model = model.into_typed()?;
+        let output = model.clone().into_runnable()?.run(tvec!(tensor1(&[0f32, 0.])))?;
+        assert_eq!(*output[0], tensor1(&[0f32, 0., 1., 1., 1.]));
+        let optimized = model.into_optimized()?;
+        // the state grows, it can not become a scan state
+        assert!(optimized.nodes().iter().any(|n| n.op_is::<control_flow::Loop>()));
+        let output = optimized.into_runnable()?.run(tvec!(tensor1(&[0f32, 0.])))?;
+        assert_eq!(*output[0], tensor1(&[0f32, 0., 1., 1., 1.]));
+        Ok(())
+    }
+}
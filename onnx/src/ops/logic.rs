@@ -14,5 +14,5 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("LessOrEqual", |_, _| Ok((ops::logic::LesserEqual.into_hir(), vec![])));
     reg.insert("GreaterOrEqual", |_, _| Ok((ops::logic::GreaterEqual.into_hir(), vec![])));
 
-    reg.insert("Where", |_, _| Ok((Box::new(ops::logic::Iff::default()), vec![])));
+    reg.insert("Where", |_, _| Ok((Box::new(ops::logic::Iff), vec![])));
 }
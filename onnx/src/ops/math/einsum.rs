@@ -0,0 +1,341 @@
+use crate::model::ParsingContext;
+use crate::pb::*;
+use tract_hir::internal::*;
+use tract_hir::ops;
+
+pub fn einsum(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let equation: &str = node.get_attr("equation")?;
+    Ok((expand(Einsum::parse(equation)?), vec![]))
+}
+
+/// Einstein summation, translated to Reduce, AxisOp and MatMul.
+///
+/// Operands are folded pairwise from the left. Each pair becomes one (batched) MatMul. Axes
+/// that appear in a single operand and not in the output are summed first.
+#[derive(Debug, Clone, Hash)]
+pub struct Einsum {
+    inputs: Vec<Vec<char>>,
+    output: Vec<char>,
+}
+
+impl_dyn_hash!(Einsum);
+
+impl Einsum {
+    pub fn parse(equation: &str) -> TractResult<Einsum> {
+        let equation: String = equation.chars().filter(|c| !c.is_whitespace()).collect();
+        if equation.contains('.') {
+            bail!("Einsum: ellipsis is not supported ({})", equation);
+        }
+        let (lhs, rhs) = if let Some(arrow) = equation.find("->") {
+            (&equation[..arrow], Some(&equation[arrow + 2..]))
+        } else {
+            (&*equation, None)
+        };
+        let inputs: Vec<Vec<char>> = lhs.split(',').map(|term| term.chars().collect()).collect();
+        let output: Vec<char> = if let Some(rhs) = rhs {
+            rhs.chars().collect()
+        } else {
+            let mut letters: Vec<char> = inputs.iter().flatten().copied().collect();
+            letters.sort();
+            letters
+                .iter()
+                .copied()
+                .filter(|l| letters.iter().filter(|o| *o == l).count() == 1)
+                .collect()
+        };
+        for term in inputs.iter().chain(std::iter::once(&output)) {
+            if let Some(c) = term.iter().find(|c| !c.is_ascii_alphabetic()) {
+                bail!("Einsum: invalid label {:?} in {}", c, equation);
+            }
+            if term.iter().enumerate().any(|(ix, c)| term[ix + 1..].contains(c)) {
+                bail!("Einsum: repeated labels in one term are not supported ({})", equation);
+            }
+        }
+        if let Some(c) = output.iter().find(|c| !inputs.iter().any(|i| i.contains(c))) {
+            bail!("Einsum: output label {:?} does not appear in any input ({})", c, equation);
+        }
+        Ok(Einsum { inputs, output })
+    }
+}
+
+impl Expansion for Einsum {
+    fn name(&self) -> Cow<str> {
+        "Einsum".into()
+    }
+
+    op_onnx!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, self.inputs.len())?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&outputs[0].rank, self.output.len() as i64)?;
+        for (input, term) in inputs.iter().zip(self.inputs.iter()) {
+            s.equals(&input.datum_type, &outputs[0].datum_type)?;
+            s.equals(&input.rank, term.len() as i64)?;
+        }
+        // each label is bound to the first input axis carrying it
+        let mut seen: Vec<(char, usize, usize)> = vec![];
+        for (ix, term) in self.inputs.iter().enumerate() {
+            for (axis, label) in term.iter().enumerate() {
+                if let Some(&(_, first_ix, first_axis)) = seen.iter().find(|seen| seen.0 == *label)
+                {
+                    s.equals(&inputs[ix].shape[axis], &inputs[first_ix].shape[first_axis])?;
+                } else {
+                    seen.push((*label, ix, axis));
+                }
+            }
+        }
+        for (axis, label) in self.output.iter().enumerate() {
+            let &(_, ix, input_axis) = seen.iter().find(|seen| seen.0 == *label).unwrap();
+            s.equals(&outputs[0].shape[axis], &inputs[ix].shape[input_axis])?;
+        }
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        name: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let mut wire = inputs[0];
+        let mut labels = self.inputs[0].clone();
+        for ix in 1..inputs.len() {
+            let needed: Vec<char> =
+                self.output.iter().chain(self.inputs[ix + 1..].iter().flatten()).copied().collect();
+            let kept: Vec<char> = labels
+                .iter()
+                .chain(self.inputs[ix].iter())
+                .copied()
+                .filter(|l| needed.contains(l))
+                .fold(vec![], |mut kept, l| {
+                    if !kept.contains(&l) {
+                        kept.push(l)
+                    }
+                    kept
+                });
+            wire = wire_pair(
+                &format!("{}.pair-{}", name, ix),
+                model,
+                (wire, &labels),
+                (inputs[ix], &self.inputs[ix]),
+                &kept,
+            )?;
+            labels = kept;
+        }
+        wire = wire_single(name, model, wire, &labels, &self.output)?;
+        Ok(tvec!(wire))
+    }
+}
+
+/// Sum away the labels missing from `output`, then transpose to `output` order.
+fn wire_single(
+    name: &str,
+    model: &mut TypedModel,
+    mut wire: OutletId,
+    labels: &[char],
+    output: &[char],
+) -> TractResult<OutletId> {
+    let summed: TVec<usize> =
+        (0..labels.len()).filter(|&ax| !output.contains(&labels[ax])).collect();
+    if summed.len() > 0 {
+        wire = model.wire_node(
+            format!("{}.sum", name),
+            tract_core::ops::nn::Reduce::new(summed.clone(), tract_core::ops::nn::Reducer::Sum),
+            &[wire],
+        )?[0];
+        for &axis in summed.iter().rev() {
+            wire = model.wire_node(format!("{}.rm-{}", name, axis), AxisOp::Rm(axis), &[wire])?[0];
+        }
+    }
+    let labels: Vec<char> = labels.iter().copied().filter(|l| output.contains(l)).collect();
+    wire_transpose(name, model, wire, labels, output)
+}
+
+/// Transpose with a chain of AxisOp::Move so that the axes go from `labels` to `target` order.
+fn wire_transpose(
+    name: &str,
+    model: &mut TypedModel,
+    mut wire: OutletId,
+    mut labels: Vec<char>,
+    target: &[char],
+) -> TractResult<OutletId> {
+    for (ix, label) in target.iter().enumerate() {
+        let pos = labels.iter().position(|l| l == label).unwrap();
+        if pos != ix {
+            wire = model.wire_node(
+                format!("{}.move-{}", name, label),
+                AxisOp::Move(pos, ix),
+                &[wire],
+            )?[0];
+            let l = labels.remove(pos);
+            labels.insert(ix, l);
+        }
+    }
+    Ok(wire)
+}
+
+/// Turn `count` axes starting at `at` into a single one.
+fn wire_merge(
+    name: &str,
+    model: &mut TypedModel,
+    wire: OutletId,
+    at: usize,
+    count: usize,
+) -> TractResult<OutletId> {
+    let op = match count {
+        0 => AxisOp::Add(at),
+        1 => return Ok(wire),
+        _ => {
+            let from: TVec<TDim> = model.outlet_fact(wire)?.shape[at..][..count].into();
+            let merged = from.iter().maybe_product()?;
+            AxisOp::Reshape(at, from, tvec!(merged))
+        }
+    };
+    Ok(model.wire_node(name, op, &[wire])?[0])
+}
+
+/// Contract two operands with a MatMul over [batch, m, k] x [batch, k, n].
+fn wire_pair(
+    name: &str,
+    model: &mut TypedModel,
+    (a, a_labels): (OutletId, &[char]),
+    (b, b_labels): (OutletId, &[char]),
+    output: &[char],
+) -> TractResult<OutletId> {
+    let batch: Vec<char> =
+        output.iter().copied().filter(|l| a_labels.contains(l) && b_labels.contains(l)).collect();
+    let m: Vec<char> =
+        a_labels.iter().copied().filter(|l| output.contains(l) && !batch.contains(l)).collect();
+    let n: Vec<char> =
+        b_labels.iter().copied().filter(|l| output.contains(l) && !batch.contains(l)).collect();
+    let k: Vec<char> =
+        a_labels.iter().copied().filter(|l| b_labels.contains(l) && !output.contains(l)).collect();
+
+    let a_order: Vec<char> = batch.iter().chain(m.iter()).chain(k.iter()).copied().collect();
+    let a = wire_single(&format!("{}.a", name), model, a, a_labels, &a_order)?;
+    let b_order: Vec<char> = batch.iter().chain(k.iter()).chain(n.iter()).copied().collect();
+    let b = wire_single(&format!("{}.b", name), model, b, b_labels, &b_order)?;
+
+    let m_dims: TVec<TDim> = model.outlet_fact(a)?.shape[batch.len()..][..m.len()].into();
+    let n_dims: TVec<TDim> = model.outlet_fact(b)?.shape[batch.len() + k.len()..].into();
+    let a = wire_merge(&format!("{}.a.merge-m", name), model, a, batch.len(), m.len())?;
+    let a = wire_merge(&format!("{}.a.merge-k", name), model, a, batch.len() + 1, k.len())?;
+    let b = wire_merge(&format!("{}.b.merge-k", name), model, b, batch.len(), k.len())?;
+    let b = wire_merge(&format!("{}.b.merge-n", name), model, b, batch.len() + 1, n.len())?;
+
+    let mut wire =
+        model.wire_node(format!("{}.matmul", name), ops::matmul::MatMul::default(), &[a, b])?[0];
+
+    // split the n axis first, so that the m axis stays at the same place
+    let n_axis = batch.len() + 1;
+    wire = match n.len() {
+        0 => model.wire_node(format!("{}.split-n", name), AxisOp::Rm(n_axis), &[wire])?[0],
+        1 => wire,
+        _ => {
+            let merged = n_dims.iter().maybe_product()?;
+            model.wire_node(
+                format!("{}.split-n", name),
+                AxisOp::Reshape(n_axis, tvec!(merged), n_dims),
+                &[wire],
+            )?[0]
+        }
+    };
+    let m_axis = batch.len();
+    wire = match m.len() {
+        0 => model.wire_node(format!("{}.split-m", name), AxisOp::Rm(m_axis), &[wire])?[0],
+        1 => wire,
+        _ => {
+            let merged = m_dims.iter().maybe_product()?;
+            model.wire_node(
+                format!("{}.split-m", name),
+                AxisOp::Reshape(m_axis, tvec!(merged), m_dims),
+                &[wire],
+            )?[0]
+        }
+    };
+    let labels: Vec<char> = batch.iter().chain(m.iter()).chain(n.iter()).copied().collect();
+    wire_transpose(name, model, wire, labels, output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_explicit() {
+        let e = Einsum::parse("bij, bjk -> bik").unwrap();
+        assert_eq!(e.inputs, vec!(vec!('b', 'i', 'j'), vec!('b', 'j', 'k')));
+        assert_eq!(e.output, vec!('b', 'i', 'k'));
+    }
+
+    #[test]
+    fn parse_implicit() {
+        let e = Einsum::parse("ij,jk").unwrap();
+        assert_eq!(e.output, vec!('i', 'k'));
+    }
+
+    #[test]
+    fn parse_rejects_ellipsis() {
+        assert!(Einsum::parse("...ij,...jk").is_err());
+    }
+
+    fn run(equation: &str, inputs: TVec<Tensor>) -> TractResult<Arc<Tensor>> {
+        let op = Einsum::parse(equation)?;
+        let mut model = TypedModel::default();
+        let sources = inputs
+            .iter()
+            .enumerate()
+            .map(|(ix, t)| {
+                model.add_source(
+                    format!("input-{}", ix),
+                    TypedFact::dt_shape(t.datum_type(), t.shape()),
+                )
+            })
+            .collect::<TractResult<TVec<_>>>()?;
+        let output = op.wire("einsum", &mut model, &sources)?;
+        model.set_output_outlets(&output)?;
+        Ok(SimplePlan::new(&model)?.run(inputs)?.remove(0))
+    }
+
+    #[test]
+    fn matmul() -> TractResult<()> {
+        let a = tensor2(&[[1f32, 2.], [3., 4.]]);
+        let b = tensor2(&[[5f32, 6.], [7., 8.]]);
+        assert_eq!(run("ij,jk->ik", tvec!(a, b))?, rctensor2(&[[19f32, 22.], [43., 50.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn transposed_output_and_sum() -> TractResult<()> {
+        let a = tensor3(&[[[1f32, 2.], [3., 4.]]]);
+        let b = tensor2(&[[1f32, 1.], [0., 1.]]);
+        // c[j, k] = sum_b,i a[b, i, k] * b[i, j]
+        assert_eq!(run("bik,ij->jk", tvec!(a, b))?, rctensor2(&[[1f32, 2.], [4., 6.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn single_operand() -> TractResult<()> {
+        let a = tensor2(&[[1f32, 2., 3.], [4., 5., 6.]]);
+        assert_eq!(run("ij->ji", tvec!(a.clone()))?, rctensor2(&[[1f32, 4.], [2., 5.], [3., 6.]]));
+        assert_eq!(run("ij->j", tvec!(a))?, rctensor1(&[5f32, 7., 9.]));
+        Ok(())
+    }
+
+    #[test]
+    fn outer_product() -> TractResult<()> {
+        let a = tensor1(&[1f32, 2.]);
+        let b = tensor1(&[3f32, 4., 5.]);
+        assert_eq!(run("i,j->ij", tvec!(a, b))?, rctensor2(&[[3f32, 4., 5.], [6., 8., 10.]]));
+        Ok(())
+    }
+}
@@ -1,7 +1,6 @@
 use crate::model::{OnnxOpRegister, ParsingContext};
 use crate::pb::NodeProto;
 use crate::pb_helpers::*;
-use std::iter;
 use tract_hir::internal::*;
 use tract_onnx_opl::ml::tree::*;
 
@@ -126,7 +125,7 @@ fn parse_nodes_data(node: &NodeProto, is_classifier: bool) -> TractResult<TreeEn
     let false_ids = get_vec_attr::<usize>(node, "nodes_falsenodeids", n_nodes)?;
     let node_values = get_vec_attr::<f32>(node, "nodes_values", n_nodes)?;
     let nan_is_true = get_vec_attr_opt::<bool>(node, "nodes_missing_value_tracks_true", n_nodes)?
-        .unwrap_or_else(|| iter::repeat(false).take(n_nodes).collect());
+        .unwrap_or_else(|| std::iter::repeat_n(false, n_nodes).collect());
     let node_modes: Vec<Option<Cmp>> = get_vec_attr::<&str>(node, "nodes_modes", n_nodes)?
         .iter()
         .map(|s| parse_node_mode(s))
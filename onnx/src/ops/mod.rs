@@ -13,6 +13,7 @@ macro_rules! op_onnx {
 
 mod array;
 mod cast;
+mod control_flow;
 mod logic;
 mod math;
 mod ml;
@@ -24,9 +25,10 @@ mod resize;
 pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("Cast", cast::cast);
     reg.insert("Constant", konst);
-    reg.insert("Identity", |_, _| Ok((Box::new(ops::identity::Identity::default()), vec![])));
+    reg.insert("Identity", |_, _| Ok((Box::new(ops::identity::Identity), vec![])));
     reg.insert("Resize", resize::resize);
     array::register_all_ops(reg);
+    control_flow::register_all_ops(reg);
     logic::register_all_ops(reg);
     math::register_all_ops(reg);
     ml::register_all_ops(reg);
@@ -36,9 +38,14 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
 }
 
 fn konst(
-    _ctx: &ParsingContext,
+    ctx: &ParsingContext,
     node: &NodeProto,
 ) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
-    let v = node.get_attr::<Tensor>("value")?;
+    let value = node.attribute.iter().find(|a| a.name == "value").and_then(|a| a.t.as_ref());
+    let v = if let Some(value) = value {
+        ctx.external_data.load(value)?
+    } else {
+        node.get_attr::<Tensor>("value")?
+    };
     Ok((Box::new(tract_hir::ops::konst::Const(v.into())), vec![]))
 }
@@ -0,0 +1,23 @@
+use crate::model::ParsingContext;
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+use tract_hir::ops::cnn::{Deconv, KernelFormat};
+use tract_hir::ops::nn::DataFormat;
+
+pub fn conv_transpose(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let op = Deconv {
+        data_format: DataFormat::NCHW,
+        kernel_format: KernelFormat::OIHW,
+        padding: super::pad(node)?,
+        strides: super::strides(node)?,
+        dilations: super::dilations(node)?,
+        adjustments: node.get_attr_opt_tvec("output_padding")?,
+        output_shape: node.get_attr_opt_tvec("output_shape")?,
+        group: node.get_attr_opt("group")?.unwrap_or(1),
+        bias_input: if node.input.len() == 3 { Some(2) } else { None },
+    };
+    Ok((expand(op), vec![]))
+}
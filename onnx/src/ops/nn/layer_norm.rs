@@ -0,0 +1,70 @@
+use crate::model::ParsingContext;
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+
+pub fn layer_normalization(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    if node.output.iter().skip(1).any(|o| !o.is_empty()) {
+        bail!("LayerNormalization: Mean and InvStdDev outputs are not supported");
+    }
+    let axis = node.get_attr_opt("axis")?.unwrap_or(-1);
+    let epsilon = node.get_attr_opt("epsilon")?.unwrap_or(1e-5);
+    Ok((expand(LayerNormalization::new(axis, epsilon)), vec![]))
+}
+
+#[derive(Debug, Clone, new, Default, Educe)]
+#[educe(Hash)]
+pub struct LayerNormalization {
+    axis: i64,
+    #[educe(Hash(method = "hash_f32"))]
+    epsilon: f32,
+}
+
+impl_dyn_hash!(LayerNormalization);
+
+impl Expansion for LayerNormalization {
+    fn name(&self) -> Cow<str> {
+        "LayerNormalization".into()
+    }
+
+    op_onnx!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        if inputs.len() != 2 && inputs.len() != 3 {
+            bail!("LayerNormalization expects 2 or 3 inputs, got {}", inputs.len());
+        }
+        check_output_arity(outputs, 1)?;
+        for input in inputs {
+            s.equals(&input.datum_type, &outputs[0].datum_type)?;
+        }
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        name: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let fact = model.outlet_fact(inputs[0])?.clone();
+        let axis = if self.axis < 0 { self.axis + fact.rank() as i64 } else { self.axis } as usize;
+        let bias = if let Some(bias) = inputs.get(2) {
+            *bias
+        } else {
+            model.add_const(format!("{}.bias", name), Tensor::zero_dt(fact.datum_type, &[])?)?
+        };
+        model.wire_node(
+            name,
+            tract_core::ops::nn::LayerNorm::new(axis, self.epsilon),
+            &[inputs[0], inputs[1], bias],
+        )
+    }
+}
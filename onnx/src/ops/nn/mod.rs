@@ -7,8 +7,10 @@ use crate::pb::NodeProto;
 use crate::pb_helpers::OptionExt;
 
 mod batch_norm;
+mod conv_transpose;
 mod dropout;
 mod instance_norm;
+mod layer_norm;
 mod lrn;
 
 pub fn arg_max_min(
@@ -42,6 +44,7 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("BatchNormalization", batch_normalization);
     reg.insert("Conv", conv);
     reg.insert("ConvInteger", conv_integer);
+    reg.insert("ConvTranspose", conv_transpose::conv_transpose);
     reg.insert("Dropout", dropout::dropout);
     reg.insert("Elu", elu);
     reg.insert("GlobalAveragePool", |_, _| Ok((expand(ops::nn::GlobalAvgPool), vec![])));
@@ -50,6 +53,7 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("Hardmax", layer_hard_max);
     reg.insert("HardSigmoid", hard_sigmoid);
     reg.insert("InstanceNormalization", instance_norm::instance_normalization);
+    reg.insert("LayerNormalization", layer_norm::layer_normalization);
     reg.insert("LeakyRelu", leaky_relu);
     reg.insert("LogSoftmax", layer_log_soft_max);
     reg.insert("LRN", lrn::lrn);
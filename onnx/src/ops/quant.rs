@@ -14,7 +14,8 @@ fn quantize_linear(
     _ctx: &ParsingContext,
     node: &NodeProto,
 ) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
-    let op = QuantizeLinear::new(Some(2).filter(|_| node.input.len() == 3));
+    let axis = node.get_attr_opt("axis")?.unwrap_or(1);
+    let op = QuantizeLinear::new(Some(2).filter(|_| node.input.len() == 3), axis);
     Ok((expand(op), vec![]))
 }
 
@@ -22,7 +23,8 @@ fn dequantize_linear(
     _ctx: &ParsingContext,
     node: &NodeProto,
 ) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
-    let op = DequantizeLinear::new(Some(2).filter(|_| node.input.len() == 3));
+    let axis = node.get_attr_opt("axis")?.unwrap_or(1);
+    let op = DequantizeLinear::new(Some(2).filter(|_| node.input.len() == 3), axis);
     Ok((expand(op), vec![]))
 }
 
@@ -37,6 +39,7 @@ fn dynamic_quantize_linear(
 #[derive(Debug, Clone, new, Default, Hash)]
 pub struct QuantizeLinear {
     optional_zero_point_input: Option<usize>,
+    axis: i64,
 }
 
 impl_dyn_hash!(QuantizeLinear);
@@ -75,13 +78,8 @@ impl Expansion for QuantizeLinear {
         inputs: &[OutletId],
     ) -> TractResult<TVec<OutletId>> {
         use tract_hir::ops::quant::*;
-        let scale = target
-            .outlet_fact(inputs[1])?
-            .konst
-            .as_ref()
-            .context("y_scale must be a const")?
-            .as_slice::<f32>()?[0]
-            .recip();
+        let scale =
+            target.outlet_fact(inputs[1])?.konst.clone().context("y_scale must be a const")?;
         let zero_point = if self.optional_zero_point_input.is_some() {
             target
                 .outlet_fact(inputs[2])?
@@ -92,6 +90,17 @@ impl Expansion for QuantizeLinear {
         } else {
             rctensor0(0u8)
         };
+        if scale.len() > 1 || zero_point.len() > 1 {
+            return wire_quantize_per_axis(
+                prefix,
+                target,
+                inputs[0],
+                self.axis,
+                &scale,
+                &zero_point,
+            );
+        }
+        let scale = scale.as_slice::<f32>()?[0].recip();
         let op: Box<dyn TypedOp> = if zero_point.datum_type() == u8::datum_type() {
             Box::new(quantize_linear_u8(scale, zero_point.as_slice::<u8>()?[0]))
         } else {
@@ -104,6 +113,7 @@ impl Expansion for QuantizeLinear {
 #[derive(Debug, Clone, new, Default, Hash)]
 pub struct DequantizeLinear {
     optional_zero_point_input: Option<usize>,
+    axis: i64,
 }
 
 impl_dyn_hash!(DequantizeLinear);
@@ -140,12 +150,8 @@ impl Expansion for DequantizeLinear {
         target: &mut TypedModel,
         inputs: &[OutletId],
     ) -> TractResult<TVec<OutletId>> {
-        let scale = target
-            .outlet_fact(inputs[1])?
-            .konst
-            .as_ref()
-            .context("y_scale must be a const")?
-            .as_slice::<f32>()?[0];
+        let scale =
+            target.outlet_fact(inputs[1])?.konst.clone().context("y_scale must be a const")?;
         let zero_point = if self.optional_zero_point_input.is_some() {
             target
                 .outlet_fact(inputs[2])?
@@ -156,6 +162,17 @@ impl Expansion for DequantizeLinear {
         } else {
             rctensor0(0u8)
         };
+        if scale.len() > 1 || zero_point.len() > 1 {
+            return wire_dequantize_per_axis(
+                prefix,
+                target,
+                inputs[0],
+                self.axis,
+                &scale,
+                &zero_point,
+            );
+        }
+        let scale = scale.as_slice::<f32>()?[0];
         let op: Box<dyn TypedOp> = if zero_point.datum_type() == u8::datum_type() {
             Box::new(DequantizeLinearF32::new(scale, zero_point.as_slice::<u8>()?[0] as i32))
         } else if zero_point.datum_type() == i8::datum_type() {
@@ -167,6 +184,68 @@ impl Expansion for DequantizeLinear {
     }
 }
 
+/// Reshape a per-axis quantization parameter so that it broadcasts along `axis`.
+fn per_axis_param(param: &Tensor, rank: usize, axis: i64) -> TractResult<Arc<Tensor>> {
+    let axis = if axis < 0 { axis + rank as i64 } else { axis } as usize;
+    if axis >= rank {
+        bail!("Quantization axis {} is out of range for rank {}", axis, rank);
+    }
+    let mut shape = tvec!(1; rank);
+    shape[axis] = param.len();
+    Ok(param.clone().into_shape(&shape)?.into_arc_tensor())
+}
+
+fn wire_quantize_per_axis(
+    prefix: &str,
+    target: &mut TypedModel,
+    input: OutletId,
+    axis: i64,
+    scale: &Tensor,
+    zero_point: &Tensor,
+) -> TractResult<TVec<OutletId>> {
+    use tract_hir::ops::{cast, math};
+    let rank = target.outlet_fact(input)?.rank();
+    let dt = zero_point.datum_type();
+    let mut recip = scale.clone();
+    recip.as_slice_mut::<f32>()?.iter_mut().for_each(|s| *s = s.recip());
+    let recip = per_axis_param(&recip, rank, axis)?;
+    let zero_point = per_axis_param(&*zero_point.cast_to::<i32>()?, rank, axis)?;
+    let wire = target.wire_node(format!("{}.scale", prefix), math::mul::unary(recip), &[input])?;
+    let wire = target.wire_node(format!("{}.round", prefix), math::round(), &wire)?;
+    let wire = target.wire_node(format!("{}.as_i32", prefix), cast(i32::datum_type()), &wire)?;
+    let wire =
+        target.wire_node(format!("{}.zero_point", prefix), math::add::unary(zero_point), &wire)?;
+    let (min, max) = if dt == i8::datum_type() { (-128i32, 127i32) } else { (0, 255) };
+    let max = tensor0(max).broadcast_into_rank(rank)?.into_arc_tensor();
+    let min = tensor0(min).broadcast_into_rank(rank)?.into_arc_tensor();
+    let wire = target.wire_node(format!("{}.min", prefix), math::min::unary(max), &wire)?;
+    let wire = target.wire_node(format!("{}.max", prefix), math::max::unary(min), &wire)?;
+    target.wire_node(prefix, cast(dt), &wire)
+}
+
+fn wire_dequantize_per_axis(
+    prefix: &str,
+    target: &mut TypedModel,
+    input: OutletId,
+    axis: i64,
+    scale: &Tensor,
+    zero_point: &Tensor,
+) -> TractResult<TVec<OutletId>> {
+    use tract_hir::ops::{cast, math};
+    let rank = target.outlet_fact(input)?.rank();
+    let scale = per_axis_param(scale, rank, axis)?;
+    let zero_point = per_axis_param(&*zero_point.cast_to::<i32>()?, rank, axis)?;
+    let zero_point = target.add_const(format!("{}.zero_point", prefix), zero_point)?;
+    let wire = target.wire_node(format!("{}.as_i32", prefix), cast(i32::datum_type()), &[input])?;
+    let wire = target.wire_node(
+        format!("{}.minus_zero_point", prefix),
+        math::sub::bin_typed(),
+        &[wire[0], zero_point],
+    )?;
+    let wire = target.wire_node(format!("{}.as_f32", prefix), cast(f32::datum_type()), &wire)?;
+    target.wire_node(prefix, math::mul::unary(scale), &wire)
+}
+
 #[derive(Debug, Clone, new, Default, Hash)]
 pub struct DynamicQuantizeLinear {}
 
@@ -180,7 +259,7 @@ impl Expansion for DynamicQuantizeLinear {
     op_onnx!();
 
     fn nboutputs(&self) -> TractResult<usize> {
-        return Ok(3);
+        Ok(3)
     }
 
     fn rules<'r, 'p: 'r, 's: 'r>(
@@ -339,6 +418,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_per_axis_round_trip() -> TractResult<()> {
+        let scale = tensor1(&[1f32, 0.5]);
+        let zero_point = tensor1(&[0u8, 10]);
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", TypedFact::dt_shape(f32::datum_type(), &[1usize, 2, 2]))?;
+        let q = wire_quantize_per_axis("q", &mut model, x, 1, &scale, &zero_point)?;
+        let dq = wire_dequantize_per_axis("dq", &mut model, q[0], 1, &scale, &zero_point)?;
+        model.set_output_outlets(&[q[0], dq[0]])?;
+        let x = tensor3(&[[[1f32, 300.], [-6., 2.]]]);
+        let result = SimplePlan::new(&model)?.run(tvec!(x))?;
+        assert_eq!(result[0], rctensor3(&[[[1u8, 255], [0, 14]]]));
+        assert_eq!(result[1], rctensor3(&[[[1f32, 255.], [-5., 2.]]]));
+        Ok(())
+    }
+
     #[test]
     fn test_dynamic_quantize_linear_u8() {
         let data: [(&[f32], &[u8]); 3] = [
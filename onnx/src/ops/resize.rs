@@ -114,7 +114,12 @@ impl Resize {
                 return Ok(size.as_slice::<i64>()?.iter().map(|i| *i as usize).collect());
             }
         }
-        bail!("Neither shape not scale makes sense: input_shape: {:?}, scale: {:?}, sizes: {:?}")
+        bail!(
+            "Neither shape not scale makes sense: input_shape: {:?}, scale: {:?}, sizes: {:?}",
+            input_shape,
+            input_scale,
+            input_sizes
+        )
     }
 }
 
@@ -148,7 +153,7 @@ impl EvalOp for Resize {
                         new_shape[axis],
                     );
                     let mut co_i = co_o.clone();
-                    let x_left = (x_in as usize).min(data.shape()[axis] - 1).max(0);
+                    let x_left = (x_in as usize).min(data.shape()[axis] - 1);
                     co_i[axis] = x_left;
                     let y_left = data[&co_i];
                     let x_right = (x_left + 1).min(data.shape()[axis] - 1);
@@ -1,7 +1,9 @@
-use crate::pb::tensor_proto::DataType;
+use crate::pb::tensor_proto::{DataLocation, DataType};
 use crate::pb::*;
 use prost::Message;
+use std::cell::RefCell;
 use std::convert::{TryFrom, TryInto};
+use std::path::{Component, Path, PathBuf};
 use tract_hir::internal::*;
 
 impl TryFrom<DataType> for DatumType {
@@ -26,28 +28,36 @@ impl TryFrom<DataType> for DatumType {
     }
 }
 
+/// Translate a tensor type. With `symbols`, named dimensions (`dim_param`) become the symbols
+/// of that name in the scope, otherwise they are left unknown.
+pub fn translate_inference_fact(
+    t: &type_proto::Tensor,
+    symbols: Option<&SymbolScope>,
+) -> TractResult<InferenceFact> {
+    use tensor_shape_proto::dimension::Value;
+    let mut fact = InferenceFact::default();
+    fact = fact.with_datum_type(DataType::from_i32(t.elem_type).unwrap().try_into()?);
+    if let Some(shape) = &t.shape {
+        let shape: TVec<DimFact> = shape
+            .dim
+            .iter()
+            .map(|d| match (&d.value, symbols) {
+                (Some(Value::DimValue(v)), _) if *v > 0 => DimFact::from(v.to_dim()),
+                (Some(Value::DimParam(name)), Some(symbols)) if name.len() > 0 => {
+                    DimFact::from(symbols.sym(name).to_dim())
+                }
+                _ => DimFact::default(),
+            })
+            .collect();
+        fact = fact.with_shape(ShapeFactoid::closed(shape));
+    }
+    Ok(fact)
+}
+
 impl<'a> TryFrom<&'a type_proto::Tensor> for InferenceFact {
     type Error = TractError;
     fn try_from(t: &'a type_proto::Tensor) -> TractResult<InferenceFact> {
-        let mut fact = InferenceFact::default();
-        fact = fact.with_datum_type(DataType::from_i32(t.elem_type).unwrap().try_into()?);
-        if let Some(shape) = &t.shape {
-            let shape: TVec<DimFact> = shape
-                .dim
-                .iter()
-                .map(|d| {
-                    let mut fact = DimFact::default();
-                    if let Some(tensor_shape_proto::dimension::Value::DimValue(v)) = d.value {
-                        if v > 0 {
-                            fact = DimFact::from(v.to_dim())
-                        }
-                    }
-                    fact
-                })
-                .collect();
-            fact = fact.with_shape(ShapeFactoid::closed(shape));
-        }
-        Ok(fact)
+        translate_inference_fact(t, None)
     }
 }
 
@@ -63,27 +73,13 @@ impl<'a> TryFrom<&'a TensorProto> for Tensor {
     fn try_from(t: &TensorProto) -> TractResult<Tensor> {
         let dt = DataType::from_i32(t.data_type).unwrap().try_into()?;
         let shape: Vec<usize> = t.dims.iter().map(|&i| i as usize).collect();
-        if t.raw_data.len() > 0 {
-            unsafe {
-                match dt {
-                    DatumType::U8 => Tensor::from_raw::<u8>(&*shape, &*t.raw_data),
-                    DatumType::U16 => Tensor::from_raw::<u16>(&*shape, &*t.raw_data),
-                    DatumType::U32 => Tensor::from_raw::<u32>(&*shape, &*t.raw_data),
-                    DatumType::U64 => Tensor::from_raw::<u64>(&*shape, &*t.raw_data),
-                    DatumType::I8 => Tensor::from_raw::<i8>(&*shape, &*t.raw_data),
-                    DatumType::I16 => Tensor::from_raw::<i16>(&*shape, &*t.raw_data),
-                    DatumType::I32 => Tensor::from_raw::<i32>(&*shape, &*t.raw_data),
-                    DatumType::I64 => Tensor::from_raw::<i64>(&*shape, &*t.raw_data),
-                    DatumType::F16 => Tensor::from_raw::<f16>(&*shape, &*t.raw_data),
-                    DatumType::F32 => Tensor::from_raw::<f32>(&*shape, &*t.raw_data),
-                    DatumType::F64 => Tensor::from_raw::<f64>(&*shape, &*t.raw_data),
-                    DatumType::Bool => Ok(Tensor::from_raw::<u8>(&*shape, &*t.raw_data)?
-                        .into_array::<u8>()?
-                        .mapv(|x| x != 0)
-                        .into()),
-                    _ => unimplemented!("FIXME, raw tensor loading"),
-                }
-            }
+        if t.data_location == DataLocation::External as i32 {
+            bail!(
+                "Tensor {} is stored in external data, it must be loaded with its model path",
+                t.name
+            )
+        } else if t.raw_data.len() > 0 {
+            tensor_from_raw(dt, &shape, &t.raw_data)
         } else {
             use tract_ndarray::Array;
             let it = match dt {
@@ -100,14 +96,8 @@ impl<'a> TryFrom<&'a TensorProto> for Tensor {
                     t.int32_data.iter().map(|&x| x as u16).collect(),
                 )?
                 .into(),
-                DatumType::U32 => {
-                    Array::from_shape_vec(&*shape, t.int32_data.iter().map(|&x| x).collect())?
-                        .into()
-                }
-                DatumType::U64 => {
-                    Array::from_shape_vec(&*shape, t.int64_data.iter().map(|&x| x).collect())?
-                        .into()
-                }
+                DatumType::U32 => Array::from_shape_vec(&*shape, t.int32_data.to_vec())?.into(),
+                DatumType::U64 => Array::from_shape_vec(&*shape, t.int64_data.to_vec())?.into(),
                 DatumType::I8 => {
                     Array::from_shape_vec(&*shape, t.int32_data.iter().map(|&x| x as i8).collect())?
                         .into()
@@ -138,6 +128,199 @@ impl<'a> TryFrom<&'a TensorProto> for Tensor {
     }
 }
 
+fn tensor_from_raw(dt: DatumType, shape: &[usize], data: &[u8]) -> TractResult<Tensor> {
+    unsafe {
+        match dt {
+            DatumType::U8 => Tensor::from_raw::<u8>(shape, data),
+            DatumType::U16 => Tensor::from_raw::<u16>(shape, data),
+            DatumType::U32 => Tensor::from_raw::<u32>(shape, data),
+            DatumType::U64 => Tensor::from_raw::<u64>(shape, data),
+            DatumType::I8 => Tensor::from_raw::<i8>(shape, data),
+            DatumType::I16 => Tensor::from_raw::<i16>(shape, data),
+            DatumType::I32 => Tensor::from_raw::<i32>(shape, data),
+            DatumType::I64 => Tensor::from_raw::<i64>(shape, data),
+            DatumType::F16 => Tensor::from_raw::<f16>(shape, data),
+            DatumType::F32 => Tensor::from_raw::<f32>(shape, data),
+            DatumType::F64 => Tensor::from_raw::<f64>(shape, data),
+            DatumType::Bool => {
+                Ok(Tensor::from_raw::<u8>(shape, data)?.into_array::<u8>()?.mapv(|x| x != 0).into())
+            }
+            _ => bail!("Loading {:?} tensors from raw data is not supported", dt),
+        }
+    }
+}
+
+/// Where a tensor stored out of the protobuf lives: file location (relative to the model
+/// directory), offset and optional length in bytes.
+pub fn external_data_location(t: &TensorProto) -> TractResult<(String, usize, Option<usize>)> {
+    let mut location = None;
+    let mut offset = 0;
+    let mut length = None;
+    for entry in &t.external_data {
+        match &*entry.key {
+            "location" => location = Some(entry.value.clone()),
+            "offset" => offset = entry.value.parse().context("Parsing external data offset")?,
+            "length" => length = Some(entry.value.parse().context("Parsing external data length")?),
+            _ => (),
+        }
+    }
+    let location =
+        location.with_context(|| format!("No location for external data of {}", t.name))?;
+    check_location(&location)?;
+    Ok((location, offset, length))
+}
+
+/// External data must stay in the model directory: reject absolute locations and locations
+/// going up above it.
+fn check_location(location: &str) -> TractResult<()> {
+    let mut depth = 0usize;
+    for component in Path::new(location).components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => (),
+            Component::ParentDir if depth > 0 => depth -= 1,
+            _ => bail!("External data location {:?} is outside of the model directory", location),
+        }
+    }
+    Ok(())
+}
+
+fn slice_external_data<'d>(
+    t: &TensorProto,
+    data: &'d [u8],
+    offset: usize,
+    length: Option<usize>,
+) -> TractResult<&'d [u8]> {
+    let end = match length {
+        Some(l) => offset.checked_add(l).with_context(|| {
+            format!("External data for {} overflows ({} + {} bytes)", t.name, offset, l)
+        })?,
+        None => data.len(),
+    };
+    if end > data.len() || offset > end {
+        bail!(
+            "External data for {} ({}..{}) exceeds its file ({} bytes)",
+            t.name,
+            offset,
+            end,
+            data.len()
+        )
+    }
+    Ok(&data[offset..end])
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+type MappedFile = mapr::MmapMut;
+#[cfg(target_arch = "wasm32")]
+type MappedFile = Vec<u8>;
+
+/// Loads the model tensors, resolving external data relatively to the model directory.
+///
+/// Each external data file is memory-mapped (copy-on-write) once, and tensors borrow their
+/// data from the mapping.
+#[derive(Default)]
+pub struct ExternalData {
+    model_dir: Option<PathBuf>,
+    files: RefCell<HashMap<PathBuf, Arc<MappedFile>>>,
+}
+
+impl ExternalData {
+    pub fn new(model_dir: Option<&Path>) -> ExternalData {
+        ExternalData { model_dir: model_dir.map(|d| d.to_owned()), files: RefCell::default() }
+    }
+
+    fn file(&self, location: &str) -> TractResult<Arc<MappedFile>> {
+        let path = match &self.model_dir {
+            Some(dir) => dir.join(location),
+            None => PathBuf::from(location),
+        };
+        if let Some(file) = self.files.borrow().get(&path) {
+            return Ok(file.clone());
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        let file = unsafe {
+            mapr::MmapOptions::new().map_copy(
+                &std::fs::File::open(&path)
+                    .with_context(|| format!("Opening external data file {:?}", path))?,
+            )?
+        };
+        #[cfg(target_arch = "wasm32")]
+        let file = std::fs::read(&path)
+            .with_context(|| format!("Reading external data file {:?}", path))?;
+        let file = Arc::new(file);
+        self.files.borrow_mut().insert(path, file.clone());
+        Ok(file)
+    }
+
+    pub fn load(&self, t: &TensorProto) -> TractResult<Tensor> {
+        if t.data_location != DataLocation::External as i32 {
+            return t.try_into();
+        }
+        let (location, offset, length) = external_data_location(t)?;
+        let file = self.file(&location)?;
+        let data = slice_external_data(t, &file, offset, length)?;
+        let dt: DatumType = DataType::from_i32(t.data_type).unwrap().try_into()?;
+        let shape: Vec<usize> = t.dims.iter().map(|&i| i as usize).collect();
+        if dt.is_copy() && dt != DatumType::Bool {
+            unsafe { Tensor::from_raw_dt_shared(dt, &shape, data, file.clone()) }
+        } else {
+            tensor_from_raw(dt, &shape, data)
+        }
+        .with_context(|| format!("Loading {} from {:?}", t.name, location))
+    }
+}
+
+/// Copy the content of external data in the tensor protos of a model, making it self-contained.
+///
+/// `resolver` is called once per data file, with its location as stored in the model.
+pub fn inline_external_data(
+    model: &mut ModelProto,
+    resolver: &dyn Fn(&str) -> TractResult<Vec<u8>>,
+) -> TractResult<()> {
+    fn inline_tensor(
+        t: &mut TensorProto,
+        files: &mut HashMap<String, Vec<u8>>,
+        resolver: &dyn Fn(&str) -> TractResult<Vec<u8>>,
+    ) -> TractResult<()> {
+        if t.data_location != DataLocation::External as i32 {
+            return Ok(());
+        }
+        let (location, offset, length) = external_data_location(t)?;
+        if !files.contains_key(&location) {
+            let data = resolver(&location)
+                .with_context(|| format!("Resolving external data {:?}", location))?;
+            files.insert(location.clone(), data);
+        }
+        t.raw_data = slice_external_data(t, &files[&location], offset, length)?.to_vec();
+        t.external_data.clear();
+        t.data_location = DataLocation::Default as i32;
+        Ok(())
+    }
+    fn inline_graph(
+        g: &mut GraphProto,
+        files: &mut HashMap<String, Vec<u8>>,
+        resolver: &dyn Fn(&str) -> TractResult<Vec<u8>>,
+    ) -> TractResult<()> {
+        for t in &mut g.initializer {
+            inline_tensor(t, files, resolver)?;
+        }
+        for attr in g.node.iter_mut().flat_map(|n| n.attribute.iter_mut()) {
+            for t in attr.t.iter_mut().chain(attr.tensors.iter_mut()) {
+                inline_tensor(t, files, resolver)?;
+            }
+            for g in attr.g.iter_mut().chain(attr.graphs.iter_mut()) {
+                inline_graph(g, files, resolver)?;
+            }
+        }
+        Ok(())
+    }
+    let mut files = HashMap::new();
+    if let Some(g) = &mut model.graph {
+        inline_graph(g, &mut files, resolver)?;
+    }
+    Ok(())
+}
+
 impl TryFrom<TensorProto> for Tensor {
     type Error = TractError;
     fn try_from(t: TensorProto) -> TractResult<Tensor> {
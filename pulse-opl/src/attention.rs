@@ -0,0 +1,202 @@
+use tract_nnef::internal::*;
+use tract_nnef::tract_core::ops::nn::{AttentionMask, ScaledDotProductAttention};
+
+pub fn register(registry: &mut Registry) {
+    registry.register_primitive(
+        "tract_pulse_attention",
+        &[
+            TypeName::Scalar.tensor().named("q"),
+            TypeName::Scalar.tensor().named("k"),
+            TypeName::Scalar.tensor().named("v"),
+            TypeName::Scalar.named("scale"),
+            TypeName::Logical.named("causal").default(false),
+            TypeName::Integer.named("left_context").default(-1),
+            TypeName::Integer.named("chunk").default(0),
+            TypeName::Integer.named("left_chunks").default(0),
+            TypeName::Integer.named("delay"),
+            TypeName::Integer.named("context"),
+        ],
+        de_attention,
+    );
+}
+
+fn de_attention(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    let q = invocation.named_arg_as(builder, "q")?;
+    let k = invocation.named_arg_as(builder, "k")?;
+    let v = invocation.named_arg_as(builder, "v")?;
+    let scale = invocation.named_arg_as(builder, "scale")?;
+    let chunk = invocation.named_arg_as::<i64>(builder, "chunk")?;
+    let mask = if chunk > 0 {
+        let left_chunks = invocation.named_arg_as::<i64>(builder, "left_chunks")? as usize;
+        AttentionMask::Chunked { chunk: chunk as usize, left_chunks }
+    } else if invocation.named_arg_as(builder, "causal")? {
+        let left_context = invocation.named_arg_as::<i64>(builder, "left_context")?;
+        AttentionMask::Causal {
+            left_context: if left_context < 0 { None } else { Some(left_context as usize) },
+        }
+    } else {
+        AttentionMask::Full
+    };
+    let delay = invocation.named_arg_as::<i64>(builder, "delay")? as usize;
+    let context = invocation.named_arg_as::<i64>(builder, "context")? as usize;
+    let attention = ScaledDotProductAttention { scale, mask };
+    builder.wire(PulsedAttention { attention, delay, context }, &[q, k, v])
+}
+
+#[derive(Debug, Clone)]
+struct PulsedAttentionState {
+    k: Option<Tensor>,
+    v: Option<Tensor>,
+    position: i64,
+}
+
+impl PulsedAttentionState {
+    /// Prepend the buffered context to the pulse, and keep the last `context` positions.
+    fn extend(buffer: &mut Option<Tensor>, input: &Tensor, context: usize) -> TractResult<Tensor> {
+        let axis = input.rank() - 2;
+        let past = if let Some(past) = buffer.take() {
+            past
+        } else {
+            let mut shape: TVec<usize> = input.shape().into();
+            shape[axis] = context;
+            Tensor::zero_dt(input.datum_type(), &shape)?
+        };
+        let full = Tensor::stack_tensors(axis, &[&past, input])?;
+        let len = full.shape()[axis];
+        *buffer = Some(full.slice(axis, len - context, len)?);
+        Ok(full)
+    }
+}
+
+impl OpState for PulsedAttentionState {
+    fn eval(
+        &mut self,
+        _state: &mut SessionState,
+        op: &dyn Op,
+        mut inputs: TVec<Arc<Tensor>>,
+    ) -> TractResult<TVec<Arc<Tensor>>> {
+        let (q, k, v) = args_3!(inputs);
+        let op =
+            op.downcast_ref::<PulsedAttention>().ok_or_else(|| format_err!("Wrong Op type"))?;
+        let pulse = q.shape()[q.rank() - 2];
+        let keys = Self::extend(&mut self.k, &k, op.context)?;
+        let values = Self::extend(&mut self.v, &v, op.context)?;
+        let output = op.attention.eval_at(
+            &q,
+            &keys,
+            &values,
+            self.position,
+            self.position - op.context as i64,
+        )?;
+        self.position += pulse as i64;
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+
+    fn snapshot(&self) -> TractResult<TVec<Tensor>> {
+        let mut tensors = tvec!(tensor0(self.position));
+        tensors.extend(self.k.iter().chain(self.v.iter()).cloned());
+        Ok(tensors)
+    }
+
+    fn restore(&mut self, tensors: TVec<Tensor>) -> TractResult<()> {
+        if tensors.len() != 1 && tensors.len() != 3 {
+            bail!(
+                "PulsedAttention state expects a position and optional keys and values, got {} tensors",
+                tensors.len()
+            );
+        }
+        let mut tensors = tensors.into_iter();
+        let position = tensors.next().unwrap();
+        if position.rank() != 0 {
+            bail!("PulsedAttention position must be a scalar, got {:?}", position);
+        }
+        let k = tensors.next();
+        let v = tensors.next();
+        if let (Some(k), Some(v)) = (&k, &v) {
+            let rank = k.rank();
+            if rank < 2
+                || k.datum_type() != v.datum_type()
+                || v.rank() != rank
+                || k.shape()[..rank - 1] != v.shape()[..rank - 1]
+            {
+                bail!("PulsedAttention keys {:?} and values {:?} do not match", k, v);
+            }
+        }
+        self.position = *position.to_scalar::<i64>().context("PulsedAttention position")?;
+        self.k = k;
+        self.v = v;
+        Ok(())
+    }
+}
+
+/// Attention over a stream, the sequence being the streaming axis.
+///
+/// Keys and values of the last `context` positions are kept from one pulse to the next, so the
+/// mask must bound the left context. `delay` is the delay of the inputs: keys before the
+/// beginning of the stream are masked out.
+#[derive(Debug, Clone, Hash)]
+pub struct PulsedAttention {
+    pub attention: ScaledDotProductAttention,
+    pub delay: usize,
+    pub context: usize,
+}
+
+impl_dyn_hash!(PulsedAttention);
+
+impl Op for PulsedAttention {
+    fn name(&self) -> Cow<str> {
+        "PulsedAttention".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        let mut info = self.attention.info()?;
+        info.push(format!("delay: {} context: {}", self.delay, self.context));
+        Ok(info)
+    }
+
+    op_pulse!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for PulsedAttention {
+    fn is_stateless(&self) -> bool {
+        false
+    }
+
+    fn state(
+        &self,
+        _session: &mut SessionState,
+        _node_id: usize,
+    ) -> TractResult<Option<Box<dyn OpState>>> {
+        Ok(Some(Box::new(PulsedAttentionState {
+            k: None,
+            v: None,
+            position: -(self.delay as i64),
+        })))
+    }
+}
+
+impl TypedOp for PulsedAttention {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        self.attention.output_facts(inputs)
+    }
+
+    fn cost(&self, inputs: &[&TypedFact]) -> TractResult<TVec<(Cost, TDim)>> {
+        let mut cost = self.attention.cost(inputs)?;
+        let rank = inputs[0].rank();
+        let mut buffer: TDim = 0.into();
+        for fact in &inputs[1..] {
+            let mut buffer_shape = fact.shape.to_tvec();
+            buffer_shape[rank - 2] = self.context.to_dim();
+            let len: TDim = buffer_shape.iter().maybe_product()?;
+            buffer += len;
+        }
+        cost.push((Cost::Buffer(inputs[1].datum_type), buffer));
+        Ok(cost)
+    }
+}
@@ -1,3 +1,4 @@
+#![allow(dead_code)]
 use std::ops::Range;
 use tract_core::ndarray::*;
 use tract_nnef::internal::*;
@@ -32,7 +33,7 @@ impl EvalOp for PulsedSameAxisConcat {
         _session: &mut SessionState,
         _node_id: usize,
     ) -> TractResult<Option<Box<dyn OpState>>> {
-        return Ok(Some(Box::new(PulsedSameAxisConcatState::default())));
+        Ok(Some(Box::new(PulsedSameAxisConcatState::default())))
     }
 }
 
@@ -42,6 +43,11 @@ impl TypedOp for PulsedSameAxisConcat {
     fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
         Ok(tvec!(inputs[0].clone()))
     }
+
+    fn cost(&self, _inputs: &[&TypedFact]) -> TractResult<TVec<(Cost, TDim)>> {
+        let len = self.pre_slice.len() + self.post_slice.len();
+        Ok(tvec!((Cost::Buffer(self.pre_slice.datum_type()), len.to_dim())))
+    }
 }
 
 #[derive(Clone, Debug, Default)]
@@ -87,7 +93,19 @@ impl OpState for PulsedSameAxisConcatState {
             }
         }
 
-        return Ok(tvec!(data.into_arc_tensor()));
+        Ok(tvec!(data.into_arc_tensor()))
+    }
+
+    fn snapshot(&self) -> TractResult<TVec<Tensor>> {
+        Ok(tvec!(tensor0(self.current_pos as i64)))
+    }
+
+    fn restore(&mut self, tensors: TVec<Tensor>) -> TractResult<()> {
+        if tensors.len() != 1 || tensors[0].rank() != 0 {
+            bail!("PulsedSameAxisConcat state expects a scalar position, got {:?}", tensors);
+        }
+        self.current_pos = tensors[0].cast_to_scalar::<i64>()? as usize;
+        Ok(())
     }
 }
 
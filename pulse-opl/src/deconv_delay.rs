@@ -0,0 +1,139 @@
+use tract_core::ndarray::*;
+use tract_core::num_traits::Zero;
+use tract_nnef::internal::*;
+
+pub fn register(registry: &mut Registry) {
+    registry.register_primitive(
+        "tract_pulse_deconv_delay",
+        &[
+            TypeName::Scalar.tensor().named("input"),
+            TypeName::Integer.named("axis"),
+            TypeName::Integer.named("overlap"),
+        ],
+        de_deconv_delay,
+    );
+}
+
+fn de_deconv_delay(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    let wire = invocation.named_arg_as(builder, "input")?;
+    let axis = invocation.named_arg_as::<i64>(builder, "axis")? as usize;
+    let overlap = invocation.named_arg_as::<i64>(builder, "overlap")? as usize;
+    let op = DeconvDelay { axis, overlap, delay: 0, dim: 0.to_dim() };
+    builder.wire(op, &[wire])
+}
+
+#[derive(Debug, Clone)]
+struct DeconvDelayState {
+    buffer: Option<Tensor>,
+}
+
+impl DeconvDelayState {
+    fn eval_t<T: Datum + Zero + Copy + std::ops::AddAssign>(
+        &mut self,
+        op: &DeconvDelay,
+        input: &Tensor,
+    ) -> TractResult<Tensor> {
+        let input = input.to_array_view::<T>()?;
+        let output_pulse = input.shape()[op.axis] - op.overlap;
+        let mut combined = input.to_owned();
+        if let Some(buffer) = &self.buffer {
+            combined
+                .slice_axis_mut(Axis(op.axis), (..op.overlap).into())
+                .zip_mut_with(&buffer.to_array_view::<T>()?, |c, b| *c += *b);
+        }
+        self.buffer = Some(
+            combined.slice_axis(Axis(op.axis), (output_pulse..).into()).to_owned().into_tensor(),
+        );
+        Ok(combined.slice_axis(Axis(op.axis), (..output_pulse).into()).to_owned().into_tensor())
+    }
+}
+
+impl OpState for DeconvDelayState {
+    fn eval(
+        &mut self,
+        _state: &mut SessionState,
+        op: &dyn Op,
+        mut inputs: TVec<Arc<Tensor>>,
+    ) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = args_1!(inputs);
+        let op = op.downcast_ref::<DeconvDelay>().ok_or_else(|| format_err!("Wrong Op type"))?;
+        let output = dispatch_floatlike!(Self::eval_t(input.datum_type())(self, op, &input))?;
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+
+    fn snapshot(&self) -> TractResult<TVec<Tensor>> {
+        Ok(self.buffer.iter().cloned().collect())
+    }
+
+    fn restore(&mut self, mut tensors: TVec<Tensor>) -> TractResult<()> {
+        if tensors.len() > 1 {
+            bail!("DeconvDelay state expects at most one buffer, got {} tensors", tensors.len());
+        }
+        self.buffer = tensors.pop();
+        Ok(())
+    }
+}
+
+/// Overlap-add stage of a pulsed deconvolution.
+///
+/// Each input pulse is one deconvolution output pulse followed by `overlap`
+/// frames spilling over the next one. These are kept and added to the head
+/// of the next pulse.
+#[derive(Clone, Debug, PartialEq, Hash)]
+pub struct DeconvDelay {
+    pub axis: usize,
+    pub overlap: usize,
+    /// Output stream delay, only relevant in pulsed models.
+    pub delay: usize,
+    /// Output stream length, only relevant in pulsed models.
+    pub dim: TDim,
+}
+
+impl_dyn_hash!(DeconvDelay);
+
+impl Op for DeconvDelay {
+    fn name(&self) -> Cow<str> {
+        "DeconvDelay".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("axis: {} overlap: {}", self.axis, self.overlap)])
+    }
+
+    op_pulse!();
+    impl_op_same_as!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for DeconvDelay {
+    fn is_stateless(&self) -> bool {
+        false
+    }
+
+    fn state(
+        &self,
+        _session: &mut SessionState,
+        _node_id: usize,
+    ) -> TractResult<Option<Box<dyn OpState>>> {
+        Ok(Some(Box::new(DeconvDelayState { buffer: None })))
+    }
+}
+
+impl TypedOp for DeconvDelay {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let mut fact = inputs[0].clone();
+        fact.shape.set(self.axis, fact.shape[self.axis].clone() - self.overlap.to_dim());
+        Ok(tvec!(fact))
+    }
+
+    fn cost(&self, inputs: &[&TypedFact]) -> TractResult<TVec<(Cost, TDim)>> {
+        let mut buffer_shape = inputs[0].shape.to_tvec();
+        buffer_shape[self.axis] = self.overlap.to_dim();
+        Ok(tvec!((Cost::Buffer(inputs[0].datum_type), buffer_shape.iter().maybe_product()?)))
+    }
+}
@@ -79,6 +79,18 @@ impl OpState for DelayState {
             Ok(tvec!(output))
         }
     }
+
+    fn snapshot(&self) -> TractResult<TVec<Tensor>> {
+        Ok(tvec!(self.buffer.clone()))
+    }
+
+    fn restore(&mut self, mut tensors: TVec<Tensor>) -> TractResult<()> {
+        if tensors.len() > 1 {
+            bail!("Delay state expects one buffer, got {} tensors", tensors.len());
+        }
+        self.buffer = tensors.pop().context("Missing delay buffer")?;
+        Ok(())
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Hash)]
@@ -94,7 +106,6 @@ impl_dyn_hash!(Delay);
 
 impl Delay {
     pub fn new(axis: usize, input_fact: &TypedFact, delay: usize, overlap: usize) -> Delay {
-        let axis = axis;
         let mut buffer_shape = input_fact.shape.to_tvec();
         buffer_shape[axis] = (delay + overlap).to_dim();
         Delay { datum_type: input_fact.datum_type, buffer_shape, axis, delay, overlap }
@@ -106,7 +117,7 @@ impl Delay {
         delay: usize,
         overlap: usize,
     ) -> TractResult<Delay> {
-        let mut buffer_shape: TVec<TDim> = input_fact.shape.iter().map(|d| d.clone()).collect();
+        let mut buffer_shape: TVec<TDim> = input_fact.shape.iter().collect();
         buffer_shape[axis] = (delay + overlap).to_dim();
         Ok(Delay { datum_type: input_fact.datum_type, buffer_shape, axis, delay, overlap })
     }
@@ -3,7 +3,9 @@ use tract_nnef::internal::*;
 #[macro_use]
 mod macros;
 
+mod attention;
 mod concat;
+mod deconv_delay;
 mod delay;
 mod pad;
 
@@ -16,6 +18,8 @@ pub mod prelude {
 }
 
 pub mod ops {
+    pub use super::attention::PulsedAttention;
+    pub use super::deconv_delay::DeconvDelay;
     pub use super::delay::Delay;
     pub use super::pad::PulsePad;
 }
@@ -34,6 +38,8 @@ impl WithPulse for tract_nnef::framework::Nnef {
 
 pub fn tract_nnef_registry() -> Registry {
     let mut reg = Registry::new("pulse");
+    attention::register(&mut reg);
+    deconv_delay::register(&mut reg);
     delay::register(&mut reg);
     reg
 }
@@ -20,6 +20,29 @@ impl OpState for PulsePadOpState {
         let tensor = self.pad(session, op, input)?;
         Ok(tvec!(tensor.into_arc_tensor()))
     }
+
+    fn snapshot(&self) -> TractResult<TVec<Tensor>> {
+        let mut tensors = tvec!(tensor0(self.current_pos as i64));
+        tensors.extend(self.last_valid_frame.iter().cloned());
+        Ok(tensors)
+    }
+
+    fn restore(&mut self, tensors: TVec<Tensor>) -> TractResult<()> {
+        if tensors.is_empty() || tensors.len() > 2 {
+            bail!(
+                "PulsePad state expects a position and an optional frame, got {} tensors",
+                tensors.len()
+            );
+        }
+        let mut tensors = tensors.into_iter();
+        let position = tensors.next().unwrap();
+        if position.rank() != 0 {
+            bail!("PulsePad position must be a scalar, got {:?}", position);
+        }
+        self.current_pos = position.cast_to_scalar::<i64>().context("PulsePad position")? as usize;
+        self.last_valid_frame = tensors.next();
+        Ok(())
+    }
 }
 
 impl PulsePadOpState {
@@ -192,5 +215,19 @@ impl TypedOp for PulsePad {
         Ok(tvec!(inputs[0].clone()))
     }
 
+    fn cost(&self, inputs: &[&TypedFact]) -> TractResult<TVec<(Cost, TDim)>> {
+        if let PadMode::Edge = self.mode {
+            if self.after != 0.to_dim() {
+                let mut frame = inputs[0].shape.to_tvec();
+                frame[self.axis] = 1.to_dim();
+                return Ok(tvec!((
+                    Cost::Buffer(inputs[0].datum_type),
+                    frame.iter().maybe_product()?
+                )));
+            }
+        }
+        Ok(tvec!())
+    }
+
     as_op!();
 }
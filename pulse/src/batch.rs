@@ -0,0 +1,386 @@
+//! Independent streams sharing one pulsed model, stacked along a batch axis.
+use crate::internal::*;
+
+type Plan = TypedSimplePlan<TypedModel>;
+type State = TypedSimpleState<TypedModel, Arc<Plan>>;
+
+/// How a state tensor relates to the batch.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Layout {
+    /// Streams are stacked along this axis.
+    Batched(usize),
+    /// One value for the whole batch (stream positions, ...).
+    Shared,
+}
+
+/// What must be equal for two streams to be batched together.
+#[derive(Debug, PartialEq)]
+enum Key<'a> {
+    Batched(DatumType, &'a [usize]),
+    Shared(&'a Tensor),
+    Solo(usize),
+}
+
+/// Runs many independent streams through one pulsed model, stacking them along the batch axis
+/// for each pulse.
+///
+/// The model is the typed translation of a pulsed model whose inputs have a symbolic batch
+/// dimension. Each stream keeps its own op states as a `StateSnapshot` between pulses, so streams
+/// can join and leave the batch at any pulse.
+///
+/// Streams are only batched together when their states are compatible: same buffer shapes, and
+/// same values for the states not depending on the batch, like the stream positions held by
+/// pulsed pads. Streams at different positions of such models run in separate batches.
+#[derive(Debug)]
+pub struct BatchedStreams {
+    state: State,
+    axis: usize,
+    output_axes: TVec<usize>,
+    layouts: HashMap<(String, usize), Layout>,
+    streams: HashMap<usize, StateSnapshot>,
+    next_stream: usize,
+}
+
+/// The only axis where a tensor computed for one stream and for `batch` streams differ.
+fn batch_axis(one: &[usize], many: &[usize], batch: usize) -> Option<usize> {
+    if one.len() != many.len() {
+        return None;
+    }
+    let mut diff = (0..one.len()).filter(|&ix| one[ix] != many[ix]);
+    match (diff.next(), diff.next()) {
+        (Some(axis), None) if one[axis] == 1 && many[axis] == batch => Some(axis),
+        _ => None,
+    }
+}
+
+impl BatchedStreams {
+    /// `axis` is the batch axis of the model inputs.
+    ///
+    /// The state layouts are found by running the model for a pulse of zeros, for one and two
+    /// streams.
+    pub fn new(model: TypedModel, axis: usize) -> TractResult<BatchedStreams> {
+        let plan = Arc::new(SimplePlan::new(model)?);
+        let (snapshot_1, outputs_1) = Self::probe(&plan, axis, 1)?;
+        let (snapshot_2, outputs_2) = Self::probe(&plan, axis, 2)?;
+        let output_axes = outputs_1
+            .iter()
+            .zip(outputs_2.iter())
+            .enumerate()
+            .map(|(ix, (one, two))| {
+                batch_axis(one.shape(), two.shape(), 2)
+                    .ok_or_else(|| format_err!("Can not find the batch axis of output {}", ix))
+            })
+            .collect::<TractResult<TVec<usize>>>()?;
+        let mut batched = BatchedStreams {
+            state: SimpleState::new(plan)?,
+            axis,
+            output_axes,
+            layouts: HashMap::new(),
+            streams: HashMap::new(),
+            next_stream: 0,
+        };
+        batched.record_layouts(&snapshot_1, &snapshot_2, 2);
+        Ok(batched)
+    }
+
+    /// Record the layouts of the states of a batch of `batch` streams, comparing them to the
+    /// states of one stream for the same pulse.
+    fn record_layouts(&mut self, one: &StateSnapshot, many: &StateSnapshot, batch: usize) {
+        for (name, tensors_1) in &one.states {
+            let tensors_n = many.states.iter().find(|s| &s.0 == name).map(|s| &s.1);
+            for (ix, one) in tensors_1.iter().enumerate() {
+                let key = (name.clone(), ix);
+                if self.layouts.contains_key(&key) {
+                    continue;
+                }
+                let many = if let Some(many) = tensors_n.and_then(|t| t.get(ix)) {
+                    many
+                } else {
+                    continue;
+                };
+                let layout = if one.shape() == many.shape() {
+                    Layout::Shared
+                } else if let Some(axis) = batch_axis(one.shape(), many.shape(), batch) {
+                    Layout::Batched(axis)
+                } else {
+                    continue;
+                };
+                self.layouts.insert(key, layout);
+            }
+        }
+    }
+
+    /// True if some state tensors of the snapshot have no known layout.
+    fn has_unknown_layouts(&self, snapshot: &StateSnapshot) -> bool {
+        snapshot.states.iter().any(|(name, tensors)| {
+            (0..tensors.len()).any(|ix| !self.layouts.contains_key(&(name.clone(), ix)))
+        })
+    }
+
+    fn probe(
+        plan: &Arc<Plan>,
+        axis: usize,
+        batch: usize,
+    ) -> TractResult<(StateSnapshot, TVec<Arc<Tensor>>)> {
+        let model = plan.model();
+        let inputs = model
+            .input_outlets()?
+            .iter()
+            .map(|input| {
+                let fact = model.outlet_fact(*input)?;
+                let shape = fact
+                    .shape
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, d)| if ix == axis { Ok(batch) } else { d.to_usize() })
+                    .collect::<TractResult<TVec<usize>>>()?;
+                Tensor::zero_dt(fact.datum_type, &shape)
+            })
+            .collect::<TractResult<TVec<_>>>()?;
+        let mut state = SimpleState::new(plan.clone())?;
+        let outputs = state.run(inputs)?;
+        Ok((state.snapshot()?, outputs))
+    }
+
+    /// Add a stream, starting from the beginning. Returns its id.
+    pub fn join(&mut self) -> usize {
+        self.join_from(StateSnapshot::default())
+    }
+
+    /// Add a stream resuming from a snapshot of its states (for one stream). Returns its id.
+    pub fn join_from(&mut self, snapshot: StateSnapshot) -> usize {
+        let id = self.next_stream;
+        self.next_stream += 1;
+        self.streams.insert(id, snapshot);
+        id
+    }
+
+    /// Remove a stream, returning the snapshot of its states.
+    pub fn leave(&mut self, stream: usize) -> TractResult<StateSnapshot> {
+        self.streams.remove(&stream).ok_or_else(|| format_err!("No stream {}", stream))
+    }
+
+    /// Number of streams currently in the batch.
+    pub fn len(&self) -> usize {
+        self.streams.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.streams.is_empty()
+    }
+
+    /// Run one pulse for some of the streams.
+    ///
+    /// Inputs come with the stream id, with a batch dimension of one. Outputs are returned in
+    /// the same order, also with a batch dimension of one.
+    pub fn run(&mut self, inputs: Vec<(usize, TVec<Tensor>)>) -> TractResult<Vec<TVec<Tensor>>> {
+        for (ix, (stream, _)) in inputs.iter().enumerate() {
+            if !self.streams.contains_key(stream) {
+                bail!("No stream {}", stream);
+            }
+            if inputs[..ix].iter().any(|(other, _)| other == stream) {
+                bail!("Stream {} appears twice in the same pulse", stream);
+            }
+        }
+        let mut outputs: Vec<Option<TVec<Tensor>>> = vec![None; inputs.len()];
+        for group in self.groups(&inputs) {
+            let streams = &self.streams;
+            let snapshots = group.iter().map(|&ix| &streams[&inputs[ix].0]).collect::<Vec<_>>();
+            let snapshot = self.stack(&snapshots)?;
+            self.state.restore(&snapshot)?;
+            let batch = (0..inputs[group[0]].1.len())
+                .map(|input| {
+                    let parts = group.iter().map(|&ix| &inputs[ix].1[input]).collect::<Vec<_>>();
+                    Tensor::stack_tensors(self.axis, &parts)
+                })
+                .collect::<TractResult<TVec<_>>>()?;
+            let results = self.state.run(batch)?;
+            let snapshot = self.state.snapshot()?;
+            if group.len() > 1 && self.has_unknown_layouts(&snapshot) {
+                // states that did not show up when probing: run the first stream alone for the
+                // same pulse to find how they are batched
+                self.state.restore(snapshots[0])?;
+                self.state.run(inputs[group[0]].1.clone())?;
+                let alone = self.state.snapshot()?;
+                self.record_layouts(&alone, &snapshot, group.len());
+            }
+            for (slot, &ix) in group.iter().enumerate() {
+                let stream = self.unstack(&snapshot, slot, group.len())?;
+                self.streams.insert(inputs[ix].0, stream);
+                outputs[ix] = Some(
+                    results
+                        .iter()
+                        .zip(self.output_axes.iter())
+                        .map(|(r, &axis)| r.slice(axis, slot, slot + 1))
+                        .collect::<TractResult<_>>()?,
+                );
+            }
+        }
+        Ok(outputs.into_iter().map(|o| o.unwrap()).collect())
+    }
+
+    fn key<'a>(&self, stream: usize, name: &str, ix: usize, tensor: &'a Tensor) -> Key<'a> {
+        match self.layouts.get(&(name.to_string(), ix)) {
+            Some(Layout::Batched(axis)) if tensor.rank() > *axis && tensor.shape()[*axis] == 1 => {
+                Key::Batched(tensor.datum_type(), tensor.shape())
+            }
+            Some(Layout::Shared) => Key::Shared(tensor),
+            _ if tensor.rank() == 0 => Key::Shared(tensor),
+            _ => Key::Solo(stream),
+        }
+    }
+
+    /// Partition the inputs in batches of streams with compatible states.
+    fn groups(&self, inputs: &[(usize, TVec<Tensor>)]) -> Vec<Vec<usize>> {
+        let mut groups: Vec<(_, Vec<usize>)> = vec![];
+        for (ix, (stream, _)) in inputs.iter().enumerate() {
+            let snapshot = &self.streams[stream];
+            let signature = (
+                &snapshot.tensors,
+                snapshot
+                    .states
+                    .iter()
+                    .map(|(name, tensors)| {
+                        let keys = tensors
+                            .iter()
+                            .enumerate()
+                            .map(|(ix, t)| self.key(*stream, name, ix, t))
+                            .collect::<Vec<_>>();
+                        (name, keys)
+                    })
+                    .collect::<Vec<_>>(),
+            );
+            if let Some(group) = groups.iter_mut().find(|g| g.0 == signature) {
+                group.1.push(ix);
+            } else {
+                groups.push((signature, vec![ix]));
+            }
+        }
+        groups.into_iter().map(|g| g.1).collect()
+    }
+
+    fn stack(&self, snapshots: &[&StateSnapshot]) -> TractResult<StateSnapshot> {
+        let mut stacked = StateSnapshot { states: vec![], tensors: snapshots[0].tensors.clone() };
+        for (node, (name, tensors)) in snapshots[0].states.iter().enumerate() {
+            let mut batched = tvec!();
+            for (ix, tensor) in tensors.iter().enumerate() {
+                if let Some(Layout::Batched(axis)) = self.layouts.get(&(name.clone(), ix)) {
+                    let parts = snapshots.iter().map(|s| &s.states[node].1[ix]).collect::<Vec<_>>();
+                    batched.push(Tensor::stack_tensors(*axis, &parts)?);
+                } else {
+                    batched.push(tensor.clone());
+                }
+            }
+            stacked.states.push((name.clone(), batched));
+        }
+        Ok(stacked)
+    }
+
+    /// Extract the states of one stream from the states of a batch of `batch` streams.
+    fn unstack(
+        &self,
+        snapshot: &StateSnapshot,
+        slot: usize,
+        batch: usize,
+    ) -> TractResult<StateSnapshot> {
+        let mut stream = StateSnapshot { states: vec![], tensors: snapshot.tensors.clone() };
+        for (name, tensors) in &snapshot.states {
+            let mut unbatched = tvec!();
+            for (ix, tensor) in tensors.iter().enumerate() {
+                let key = (name.clone(), ix);
+                if batch > 1 && !self.layouts.contains_key(&key) {
+                    bail!("Can not find the batch axis of state {} of {}", ix, name);
+                }
+                if let Some(Layout::Batched(axis)) = self.layouts.get(&key) {
+                    unbatched.push(tensor.slice(*axis, slot, slot + 1)?);
+                } else {
+                    unbatched.push(tensor.clone());
+                }
+            }
+            stream.states.push((name.clone(), unbatched));
+        }
+        Ok(stream)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tract_core::ops::array::Slice;
+    use tract_core::ops::math;
+
+    // y[t] = x[t] + x[t + 1]: the pulsed model delays x by one frame
+    fn delaying_model() -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let s = stream_dim();
+        let batch = Symbol::new('N');
+        let fact =
+            TypedFact::dt_shape(f32::datum_type(), [batch.into(), s.clone(), 2.to_dim()].as_ref());
+        let x = model.add_source("x", fact)?;
+        let a = model.wire_node("a", Slice::new(1, 0, s.clone() - 1), &[x])?;
+        let b = model.wire_node("b", Slice::new(1, 1, s), &[x])?;
+        let y = model.wire_node("y", math::add::bin_typed(), &[a[0], b[0]])?;
+        model.set_output_outlets(&y)?;
+        PulsedModel::new(&model, 2)?.into_typed()
+    }
+
+    fn pulse(stream: f32, i: usize) -> Tensor {
+        tract_ndarray::Array3::from_shape_fn((1, 2, 2), |(_, t, c)| {
+            stream * 100. + (2 * i + t) as f32 * 2. + c as f32
+        })
+        .into_tensor()
+    }
+
+    // the first output frame of a stream is garbage (the delay buffer is uninitialized)
+    fn check(
+        solo: &mut TypedSimpleState<TypedModel, Arc<Plan>>,
+        input: Tensor,
+        i: usize,
+        out: &Tensor,
+    ) -> TractResult<()> {
+        let expected = solo.run(tvec!(input))?.remove(0);
+        let skip = if i == 0 { 1 } else { 0 };
+        assert_eq!(expected.slice(1, skip, 2)?, out.slice(1, skip, 2)?);
+        Ok(())
+    }
+
+    #[test]
+    fn streams_joining_late() -> TractResult<()> {
+        let pulsed = delaying_model()?;
+        let mut batched = BatchedStreams::new(pulsed.clone(), 0)?;
+        let plan = Arc::new(SimplePlan::new(pulsed)?);
+        let (mut solo_a, mut solo_b) = (SimpleState::new(plan.clone())?, SimpleState::new(plan)?);
+        let stream_a = batched.join();
+        let output = batched.run(vec![(stream_a, tvec!(pulse(0., 0)))])?;
+        check(&mut solo_a, pulse(0., 0), 0, &output[0][0])?;
+        let stream_b = batched.join();
+        for i in 1..4 {
+            let output = batched
+                .run(vec![(stream_b, tvec!(pulse(1., i - 1))), (stream_a, tvec!(pulse(0., i)))])?;
+            check(&mut solo_b, pulse(1., i - 1), i - 1, &output[0][0])?;
+            check(&mut solo_a, pulse(0., i), i, &output[1][0])?;
+        }
+        batched.leave(stream_a)?;
+        assert_eq!(batched.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn layouts_unknown_after_probing() -> TractResult<()> {
+        // the delay buffer is [batch, 1, 2]: its batch axis can not be told from its size
+        let pulsed = delaying_model()?;
+        let mut batched = BatchedStreams::new(pulsed.clone(), 0)?;
+        batched.layouts.clear();
+        let plan = Arc::new(SimplePlan::new(pulsed)?);
+        let (mut solo_a, mut solo_b) = (SimpleState::new(plan.clone())?, SimpleState::new(plan)?);
+        let (stream_a, stream_b) = (batched.join(), batched.join());
+        for i in 0..3 {
+            let output = batched
+                .run(vec![(stream_a, tvec!(pulse(0., i))), (stream_b, tvec!(pulse(1., i)))])?;
+            check(&mut solo_a, pulse(0., i), i, &output[0][0])?;
+            check(&mut solo_b, pulse(1., i), i, &output[1][0])?;
+        }
+        assert!(batched.layouts.values().any(|l| *l == Layout::Batched(0)));
+        Ok(())
+    }
+}
@@ -5,11 +5,11 @@ lazy_static::lazy_static! {
 }
 
 pub fn stream_symbol() -> Symbol {
-    *S
+    S.clone()
 }
 
 pub fn stream_dim() -> TDim {
-    (*S).into()
+    TDim::from(&*S)
 }
 
 pub trait StreamFact {
@@ -31,13 +31,23 @@ impl StreamFact for ShapeFact {
     }
 }
 
+/// Where and how a value streams.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct StreamInfo {
+    /// The streaming axis.
+    pub axis: usize,
+    /// Length of the whole stream along the axis.
+    pub dim: TDim,
+    /// Number of leading positions of the pulses coming before the stream starts.
+    pub delay: usize,
+}
+
 #[derive(Clone, PartialEq, Hash)]
 pub struct PulsedFact {
     pub datum_type: DatumType,
     pub shape: TVec<TDim>,
-    pub axis: usize,
-    pub dim: TDim,
-    pub delay: usize,
+    /// None for values that do not stream: they are the same whole tensor at every pulse.
+    pub stream: Option<StreamInfo>,
 }
 
 impl_dyn_hash!(PulsedFact);
@@ -51,11 +61,46 @@ impl PulsedFact {
             .ok_or_else(|| format_err!("Can not pulse a tensor with no streaming dim"))?;
         let mut shape: TVec<TDim> = tf.shape.iter().collect();
         shape[axis] = pulse.into();
-        Ok(PulsedFact { datum_type, shape, axis, dim: len.clone(), delay: 0 })
+        Ok(PulsedFact {
+            datum_type,
+            shape,
+            stream: Some(StreamInfo { axis, dim: len.clone(), delay: 0 }),
+        })
+    }
+
+    /// A value that does not stream.
+    pub fn non_streaming(tf: &TypedFact) -> TractResult<PulsedFact> {
+        if tf.shape.stream_info().is_some() {
+            bail!("Expected a non-streaming fact, got {:?}", tf)
+        }
+        Ok(PulsedFact { datum_type: tf.datum_type, shape: tf.shape.to_tvec(), stream: None })
+    }
+
+    /// Stream information, for operators that only work on streaming values.
+    pub fn streaming(&self) -> TractResult<&StreamInfo> {
+        self.stream
+            .as_ref()
+            .ok_or_else(|| format_err!("Expected a streaming value, got {:?}", self))
+    }
+
+    pub fn streaming_mut(&mut self) -> TractResult<&mut StreamInfo> {
+        if self.stream.is_none() {
+            bail!("Expected a streaming value, got {:?}", self)
+        }
+        Ok(self.stream.as_mut().unwrap())
+    }
+
+    pub fn pulse(&self) -> Option<usize> {
+        self.stream.as_ref().map(|stream| {
+            self.shape[stream.axis]
+                .to_usize()
+                .expect("Pulse should be an integer. This is a tract bug.")
+        })
     }
 
-    pub fn pulse(&self) -> usize {
-        self.shape[self.axis].to_usize().expect("Pulse should be an integer. This is a tract bug.")
+    /// Delay of the value, zero if it does not stream.
+    pub fn delay(&self) -> usize {
+        self.stream.as_ref().map(|s| s.delay).unwrap_or(0)
     }
 
     pub fn to_pulse_fact(&self) -> TypedFact {
@@ -66,29 +111,34 @@ impl PulsedFact {
         self.shape
             .iter()
             .enumerate()
-            .map(|(ix, d)| if ix == self.axis { self.dim.clone() } else { d.clone() })
+            .map(|(ix, d)| match &self.stream {
+                Some(stream) if stream.axis == ix => stream.dim.clone(),
+                _ => d.clone(),
+            })
             .collect()
     }
 
     pub fn to_streaming_fact(&self) -> TypedFact {
-        let mut info = self.to_pulse_fact();
-        info.shape.set(self.axis, self.dim.clone());
-        info
+        TypedFact::dt_shape(self.datum_type, &*self.streaming_shape())
     }
 }
 
 impl fmt::Debug for PulsedFact {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         use tract_itertools::Itertools;
-        write!(
-            fmt,
-            "{},{:?} [pulse axis:{} ∂:{} full dim:{}]",
-            self.shape.iter().join(","),
-            self.datum_type,
-            self.axis,
-            self.delay,
-            self.dim
-        )
+        if let Some(stream) = &self.stream {
+            write!(
+                fmt,
+                "{},{:?} [pulse axis:{} ∂:{} full dim:{}]",
+                self.shape.iter().join(","),
+                self.datum_type,
+                stream.axis,
+                stream.delay,
+                stream.dim
+            )
+        } else {
+            write!(fmt, "{},{:?} [not streaming]", self.shape.iter().join(","), self.datum_type)
+        }
     }
 }
 
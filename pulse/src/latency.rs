@@ -0,0 +1,118 @@
+//! Lookahead and memory footprint of a pulsed model.
+use crate::internal::*;
+use tract_core::num_traits::Zero;
+
+/// Delay and buffers of one node of a pulsed model.
+#[derive(Clone, Debug)]
+pub struct NodeLatency {
+    pub node: usize,
+    pub name: String,
+    /// Delay of the node outputs, in frames.
+    pub delay: usize,
+    /// Delay added by the node on top of its most delayed input, in frames.
+    pub added_delay: usize,
+    /// Size of the state the node keeps from one pulse to the next, in bytes.
+    pub buffer_bytes: TDim,
+}
+
+/// End-to-end delay of a pulsed model, and the nodes contributing to it or holding state.
+///
+/// Delays come from the `PulsedFact` of each wire, buffer sizes from the `Cost::Buffer` reported
+/// by the stateful ops (`Delay`, edge `PulsePad`, same-axis concat, scan hidden states).
+#[derive(Clone, Debug)]
+pub struct LatencyReport {
+    /// Delay of each model output, in frames.
+    pub output_delays: TVec<usize>,
+    /// Nodes adding delay or holding a buffer, in evaluation order.
+    pub nodes: Vec<NodeLatency>,
+    /// Total state size, in bytes.
+    pub buffer_bytes: TDim,
+}
+
+impl LatencyReport {
+    pub fn new(model: &PulsedModel) -> TractResult<LatencyReport> {
+        let mut nodes = vec![];
+        for id in model.eval_order()? {
+            let node = model.node(id);
+            let input_facts = model.node_input_facts(id)?;
+            let delay = node.outputs.iter().map(|o| o.fact.delay()).max().unwrap_or(0);
+            let input_delay = input_facts.iter().map(|f| f.delay()).max().unwrap_or(delay);
+            let typed_facts = input_facts.iter().map(|f| TypedFact::from(*f)).collect::<TVec<_>>();
+            let typed_facts = typed_facts.iter().collect::<TVec<_>>();
+            let mut buffer_bytes = TDim::zero();
+            for (cost, count) in node.op.to_typed().cost(&*typed_facts)? {
+                if let Cost::Buffer(dt) = cost {
+                    buffer_bytes += count * dt.size_of();
+                }
+            }
+            if delay > input_delay || !buffer_bytes.is_zero() {
+                nodes.push(NodeLatency {
+                    node: id,
+                    name: node.name.clone(),
+                    delay,
+                    added_delay: delay.saturating_sub(input_delay),
+                    buffer_bytes,
+                });
+            }
+        }
+        let output_delays = model
+            .output_outlets()?
+            .iter()
+            .map(|o| Ok(model.outlet_fact(*o)?.delay()))
+            .collect::<TractResult<_>>()?;
+        let buffer_bytes = nodes.iter().map(|n| &n.buffer_bytes).sum();
+        Ok(LatencyReport { output_delays, nodes, buffer_bytes })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tract_core::ops::array::PadMode;
+    use tract_pulse_opl::ops::{Delay, PulsePad};
+
+    #[test]
+    fn delays_and_buffers() -> TractResult<()> {
+        let mut model = PulsedModel::default();
+        let fact = PulsedFact {
+            datum_type: f32::datum_type(),
+            shape: tvec![4.to_dim(), 3.to_dim()],
+            stream: Some(StreamInfo { axis: 0, dim: stream_dim(), delay: 0 }),
+        };
+        let source = model.add_source("source", fact.clone())?;
+        let delay = model.wire_node("delay", Delay::new(0, &(&fact).into(), 2, 1), &[source])?;
+        model.set_output_outlets(&delay)?;
+        let report = LatencyReport::new(&model)?;
+        assert_eq!(report.output_delays, tvec!(3));
+        assert_eq!(report.nodes.len(), 1);
+        assert_eq!(report.nodes[0].added_delay, 3);
+        assert_eq!(report.buffer_bytes, (3 * 3 * 4).to_dim());
+        Ok(())
+    }
+
+    #[test]
+    fn edge_pad_keeps_last_frame() -> TractResult<()> {
+        let mut model = PulsedModel::default();
+        let fact = PulsedFact {
+            datum_type: f32::datum_type(),
+            shape: tvec![4.to_dim(), 3.to_dim()],
+            stream: Some(StreamInfo { axis: 0, dim: stream_dim(), delay: 0 }),
+        };
+        let source = model.add_source("source", fact)?;
+        let pad = PulsePad {
+            axis: 0,
+            pulse: 4,
+            before: 0,
+            after: 2.to_dim(),
+            begin_input: 0,
+            end_input: stream_dim(),
+            mode: PadMode::Edge,
+        };
+        let pad = model.wire_node("pad", pad, &[source])?;
+        model.set_output_outlets(&pad)?;
+        let report = LatencyReport::new(&model)?;
+        assert_eq!(report.nodes.len(), 1);
+        assert_eq!(report.buffer_bytes, (3 * 4).to_dim());
+        Ok(())
+    }
+}
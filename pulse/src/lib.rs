@@ -1,7 +1,9 @@
 #[macro_use]
 pub mod macros;
 
+pub mod batch;
 pub mod fact;
+pub mod latency;
 pub mod model;
 pub mod ops;
 
@@ -12,7 +14,8 @@ pub mod internal {
 
     pub use downcast_rs::Downcast;
 
-    pub use crate::fact::{stream_dim, stream_symbol, PulsedFact};
+    pub use crate::fact::{stream_dim, stream_symbol, PulsedFact, StreamInfo};
+    pub use crate::latency::LatencyReport;
     pub use crate::model::{PulsedModel, PulsedModelExt};
     pub use crate::ops::{OpPulsifier, PulsedOp};
     pub use tract_pulse_opl::op_pulse;
@@ -36,7 +39,9 @@ impl WithPulse for tract_nnef::framework::Nnef {
 
 fn tract_nnef_registry() -> Registry {
     let mut reg = tract_pulse_opl::tract_nnef_registry();
+    ops::cnn::deconv::register(&mut reg);
     ops::delay::register(&mut reg);
+    ops::nn::attention::register(&mut reg);
     reg
 }
 
@@ -25,7 +25,13 @@ impl PulsedModelExt for PulsedModel {
         pulse: usize,
     ) -> TractResult<(PulsedModel, HashMap<OutletId, OutletId>)> {
         let pulsifiers = crate::ops::OpPulsifier::inventory();
-        Pulsifier(pulse, pulsifiers).translate_model_with_mappings(source)
+        let (pulsed, mapping) =
+            Pulsifier(pulse, pulsifiers).translate_model_with_mappings(source)?;
+        if pulsed.input_outlets()?.iter().all(|i| pulsed.outlet_fact(*i).unwrap().stream.is_none())
+        {
+            bail!("Can not pulsify a model with no streaming input")
+        }
+        Ok((pulsed, mapping))
     }
 
     fn into_typed(self) -> TractResult<TypedModel> {
@@ -34,7 +40,7 @@ impl PulsedModelExt for PulsedModel {
             &self
                 .output_outlets()?
                 .iter()
-                .map(|oo| Ok(self.outlet_fact(*oo)?.delay as _))
+                .map(|oo| Ok(self.outlet_fact(*oo)?.delay() as _))
                 .collect::<TractResult<TVec<i64>>>()?,
         );
         typed.properties.insert("pulse.delay".to_string(), delays.into_arc_tensor());
@@ -99,8 +105,25 @@ impl
         target: &mut PulsedModel,
         mapping: &HashMap<OutletId, OutletId>,
     ) -> TractResult<TVec<OutletId>> {
-        if let Some(pulsifier) = self.1.get(&node.op.type_id())
-        {
+        // constants do not stream: the pulsifiers accepting them embed them in the pulsed op
+        if node.op_is::<tract_core::ops::konst::Const>() {
+            if source.output_outlets()?.iter().any(|o| o.node == node.id) {
+                bail!("Can not pulsify constant output {}", node);
+            }
+            return Ok(tvec!());
+        }
+        if let Some(input) = node.inputs.iter().find(|i| !mapping.contains_key(i)) {
+            if !node.op_is::<tract_core::ops::array::Gather>()
+                && !node.op_is::<tract_core::ops::array::MultiBroadcastTo>()
+            {
+                bail!(
+                    "Can not pulsify {}: input {} does not stream",
+                    node,
+                    source.node(input.node)
+                );
+            }
+        }
+        if let Some(pulsifier) = self.1.get(&node.op.type_id()) {
             (pulsifier.func)(source, node, target, mapping, self.0)
         } else {
             bail!("No pulsifier for {}", node);
@@ -0,0 +1,160 @@
+use crate::internal::*;
+use tract_core::ops::array::MultiBroadcastTo;
+
+register_all!(MultiBroadcastTo: pulsify);
+
+fn pulsify(
+    op: &MultiBroadcastTo,
+    source: &TypedModel,
+    node: &TypedNode,
+    target: &mut PulsedModel,
+    mapping: &HashMap<OutletId, OutletId>,
+    pulse: usize,
+) -> TractResult<TVec<OutletId>> {
+    let input_fact = source.outlet_fact(node.inputs[0])?;
+    if let Some(&input) = mapping.get(&node.inputs[0]) {
+        if let Some(stream) = &target.outlet_fact(input)?.stream {
+            let mut shape = op.shape.clone();
+            shape[stream.axis] = pulse.to_dim();
+            return target.wire_node(&*node.name, MultiBroadcastTo::new(shape), &[input]);
+        }
+    }
+    let axis = op
+        .shape
+        .iter()
+        .position(|d| d.symbols().contains(&stream_symbol()))
+        .with_context(|| format!("Can not pulsify {}: no streaming axis", node))?;
+    let mut shape = op.shape.clone();
+    shape[axis] = pulse.to_dim();
+    let broadcast = PulsedBroadcast { shape, axis, dim: op.shape[axis].clone(), konst: None };
+    if let Some(konst) = &input_fact.konst {
+        target.wire_node(
+            &*node.name,
+            PulsedBroadcast { konst: Some(konst.clone()), ..broadcast },
+            &[],
+        )
+    } else {
+        target.wire_node(&*node.name, broadcast, &[mapping[&node.inputs[0]]])
+    }
+}
+
+impl PulsedOp for MultiBroadcastTo {
+    fn pulsed_output_facts(&self, inputs: &[&PulsedFact]) -> TractResult<TVec<PulsedFact>> {
+        let mut fact = inputs[0].clone();
+        fact.shape = self.shape.clone();
+        Ok(tvec!(fact))
+    }
+
+    as_op!();
+    pulsed_op_to_typed_op!();
+}
+
+/// Broadcast of a value that does not stream (a constant, or a non-streaming model input fed
+/// whole at every pulse) to a stream.
+#[derive(Debug, Clone, Hash)]
+pub struct PulsedBroadcast {
+    pub shape: TVec<TDim>,
+    pub axis: usize,
+    pub dim: TDim,
+    pub konst: Option<Arc<Tensor>>,
+}
+
+impl_dyn_hash!(PulsedBroadcast);
+
+impl Op for PulsedBroadcast {
+    fn name(&self) -> Cow<str> {
+        "PulsedBroadcast".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("axis: {} shape: {:?}", self.axis, self.shape)])
+    }
+
+    op_pulse!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for PulsedBroadcast {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = if let Some(konst) = &self.konst { konst.clone() } else { inputs[0].clone() };
+        MultiBroadcastTo::new(self.shape.clone()).eval(tvec!(input))
+    }
+}
+
+impl TypedOp for PulsedBroadcast {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let dt =
+            if let Some(konst) = &self.konst { konst.datum_type() } else { inputs[0].datum_type };
+        Ok(tvec!(TypedFact::dt_shape(dt, &*self.shape)))
+    }
+
+    fn declutter(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        let mut patch = TypedModelPatch::default();
+        let input = if let Some(konst) = &self.konst {
+            patch.add_const(format!("{}.const", node.name), konst.clone())?
+        } else {
+            patch.tap_model(model, node.inputs[0])?
+        };
+        let wire =
+            patch.wire_node(&*node.name, MultiBroadcastTo::new(self.shape.clone()), &[input])?[0];
+        patch.shunt_outside(model, node.id.into(), wire)?;
+        Ok(Some(patch))
+    }
+}
+
+impl PulsedOp for PulsedBroadcast {
+    fn pulsed_output_facts(&self, inputs: &[&PulsedFact]) -> TractResult<TVec<PulsedFact>> {
+        let datum_type =
+            if let Some(konst) = &self.konst { konst.datum_type() } else { inputs[0].datum_type };
+        Ok(tvec!(PulsedFact {
+            datum_type,
+            shape: self.shape.clone(),
+            stream: Some(StreamInfo { axis: self.axis, dim: self.dim.clone(), delay: 0 }),
+        }))
+    }
+
+    as_op!();
+    pulsed_op_to_typed_op!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn broadcast_non_streaming_input() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let fact = TypedFact::dt_shape(f32::datum_type(), [stream_dim(), 2.to_dim()].as_ref());
+        let x = model.add_source("x", fact)?;
+        let bias = model.add_source("bias", TypedFact::dt_shape(f32::datum_type(), &[1, 2]))?;
+        let shape = tvec!(stream_dim(), 2.to_dim());
+        let bias = model.wire_node("broadcast", MultiBroadcastTo::new(shape), &[bias])?;
+        let sum = model.wire_node("sum", tract_core::ops::math::add::bin_typed(), &[x, bias[0]])?;
+        model.set_output_outlets(&sum)?;
+
+        let pulsed = PulsedModel::new(&model, 3)?;
+        assert!(pulsed.outlet_fact(pulsed.input_outlets()?[1])?.stream.is_none());
+        let fact = pulsed.outlet_fact(pulsed.output_outlets()?[0])?;
+        assert_eq!(fact.streaming()?.axis, 0);
+        assert_eq!(fact.shape, tvec!(3.to_dim(), 2.to_dim()));
+
+        let plan = SimplePlan::new(pulsed.into_typed()?)?;
+        let mut state = SimpleState::new(&plan)?;
+        let bias = tensor2(&[[10f32, 20.]]);
+        let output = state.run(tvec!(tensor2(&[[0f32, 1.], [2., 3.], [4., 5.]]), bias.clone()))?;
+        assert_eq!(*output[0], tensor2(&[[10f32, 21.], [12., 23.], [14., 25.]]));
+        let output = state.run(tvec!(tensor2(&[[6f32, 7.], [8., 9.], [10., 11.]]), bias))?;
+        assert_eq!(*output[0], tensor2(&[[16f32, 27.], [18., 29.], [20., 31.]]));
+        Ok(())
+    }
+}
@@ -21,7 +21,7 @@ fn pulsify(
     let input = mapping[&node.inputs[0]];
     let fact = target.outlet_fact(input)?;
 
-    if fact.axis == op.axis {
+    if fact.streaming()?.axis == op.axis {
         pulsify_along_concat_axis(op, source, node, target, mapping)
     } else {
         bail!("Pulsify for Concat on a separate axis is not implemented (but possible)");
@@ -40,7 +40,8 @@ fn pulsify_along_concat_axis(
     }
     let mut input = mapping[&node.inputs[0]];
     let fact = target.outlet_fact(input)?.clone();
-    assert_eq!(fact.axis, op.axis);
+    let stream = fact.streaming()?;
+    assert_eq!(stream.axis, op.axis);
     let var_index = op.slices.iter().position(|s| s.is_var()).unwrap();
     let pre_owned = op.slices[0..var_index]
         .iter()
@@ -54,10 +55,10 @@ fn pulsify_along_concat_axis(
     let post = Tensor::stack_tensors(op.axis, &*post_owned)?;
 
     let before = pre.shape()[op.axis];
-    if fact.delay < before {
+    if stream.delay < before {
         input = target.wire_node(
             format!("{}.Delay", node.name),
-            Delay::new(fact.axis, &(&fact).into(), before - fact.delay, 0),
+            Delay::new(stream.axis, &(&fact).into(), before - stream.delay, 0),
             &[input],
         )?[0];
     }
@@ -65,8 +66,8 @@ fn pulsify_along_concat_axis(
         axis: op.axis,
         pre_slice: pre,
         post_slice: post,
-        input_delay: fact.delay.saturating_sub(before),
-        input_len: fact.dim.clone(),
+        input_delay: stream.delay.saturating_sub(before),
+        input_len: stream.dim.clone(),
     };
     target.wire_node(&*node.name, main_op, &[input])
 }
@@ -76,8 +77,9 @@ impl PulsedOp for PulsedSameAxisConcat {
         let mut fact = inputs[0].clone();
         let before = self.pre_slice.shape()[self.axis];
         let after = self.post_slice.shape()[self.axis];
-        fact.dim += (before + after).to_dim();
-        fact.delay -= before;
+        let stream = fact.streaming_mut()?;
+        stream.dim += (before + after).to_dim();
+        stream.delay -= before;
         Ok(tvec!(fact))
     }
 
@@ -116,7 +118,7 @@ impl EvalOp for PulsedSameAxisConcat {
         _node_id: usize,
     ) -> TractResult<Option<Box<dyn OpState>>> {
         let symbols_in_dim = self.input_len.symbols().into_iter().collect();
-        return Ok(Some(Box::new(PulsedSameAxisConcatState { current_pos: 0, symbols_in_dim })));
+        Ok(Some(Box::new(PulsedSameAxisConcatState { current_pos: 0, symbols_in_dim })))
     }
 }
 
@@ -159,7 +161,7 @@ impl OpState for PulsedSameAxisConcatState {
             &op.pre_slice,
             pre_offset
         ))?;
-        if self.symbols_in_dim.iter().all(|s| session.resolved_symbols[*s].is_some()) {
+        if self.symbols_in_dim.iter().all(|s| session.resolved_symbols[s].is_some()) {
             let l = op.input_len.eval(&session.resolved_symbols).to_usize().unwrap();
             let post_offset = op.input_delay + l as usize;
             dispatch_datum!(overwrite_part_of_pulse(data.datum_type())(
@@ -171,7 +173,19 @@ impl OpState for PulsedSameAxisConcatState {
             ))?;
         }
 
-        return Ok(tvec!(data.into_arc_tensor()));
+        Ok(tvec!(data.into_arc_tensor()))
+    }
+
+    fn snapshot(&self) -> TractResult<TVec<Tensor>> {
+        Ok(tvec!(tensor0(self.current_pos as i64)))
+    }
+
+    fn restore(&mut self, tensors: TVec<Tensor>) -> TractResult<()> {
+        if tensors.len() != 1 || tensors[0].rank() != 0 {
+            bail!("PulsedSameAxisConcat state expects a scalar position, got {:?}", tensors);
+        }
+        self.current_pos = tensors[0].cast_to_scalar::<i64>()? as usize;
+        Ok(())
     }
 }
 
@@ -218,6 +232,7 @@ pub fn overwrite_part_of_pulse<T: Datum>(
     Ok(())
 }
 
+#[allow(dead_code)]
 #[derive(Copy, Clone, Debug)]
 enum RangeInRange {
     /// ----[--]<-->HHHH----
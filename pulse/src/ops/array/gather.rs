@@ -0,0 +1,323 @@
+use crate::internal::*;
+use tract_core::ops::array::Gather;
+
+register_all!(Gather: pulsify);
+
+fn pulsify(
+    op: &Gather,
+    source: &TypedModel,
+    node: &TypedNode,
+    target: &mut PulsedModel,
+    mapping: &HashMap<OutletId, OutletId>,
+    _pulse: usize,
+) -> TractResult<TVec<OutletId>> {
+    let data = source.outlet_fact(node.inputs[0])?.konst.clone();
+    let indices = source.outlet_fact(node.inputs[1])?.konst.clone();
+    let (konst, const_slot, input) = match (data, indices) {
+        (None, Some(indices)) => {
+            let input = mapping[&node.inputs[0]];
+            if let Some(stream) = target.outlet_fact(input)?.stream.as_ref() {
+                if stream.axis == op.axis {
+                    let indices = indices.cast_to::<i64>()?.into_owned().into_arc_tensor();
+                    if indices.as_slice::<i64>()?.iter().any(|&i| i < 0) {
+                        bail!(
+                            "Can not pulsify Gather along the streaming axis with negative indices"
+                        );
+                    }
+                    let op = PulsedStreamGather { axis: op.axis, indices, delay: stream.delay };
+                    return target.wire_node(&*node.name, op, &[input]);
+                }
+            }
+            (indices, 1, input)
+        }
+        (Some(data), None) => (data, 0, mapping[&node.inputs[1]]),
+        _ => bail!("Can only pulsify Gather with one constant input"),
+    };
+    target.wire_node(&*node.name, PulsedGather { op: op.clone(), konst, const_slot }, &[input])
+}
+
+/// Gather with a constant data or indices input, the other one streaming.
+///
+/// Constants have no pulsed counterpart, so the pulsed op carries it. It goes back to a Const
+/// and a Gather when the model is decluttered.
+#[derive(Debug, Clone, Hash)]
+pub struct PulsedGather {
+    pub op: Gather,
+    pub konst: Arc<Tensor>,
+    pub const_slot: usize,
+}
+
+impl_dyn_hash!(PulsedGather);
+
+impl PulsedGather {
+    fn with_const<T: Clone>(&self, input: T, konst: T) -> TVec<T> {
+        if self.const_slot == 0 {
+            tvec!(konst, input)
+        } else {
+            tvec!(input, konst)
+        }
+    }
+}
+
+impl Op for PulsedGather {
+    fn name(&self) -> Cow<str> {
+        "PulsedGather".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        let what = if self.const_slot == 0 { "data" } else { "indices" };
+        Ok(vec![format!("axis: {} constant {}: {:?}", self.op.axis, what, self.konst)])
+    }
+
+    op_pulse!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for PulsedGather {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = args_1!(inputs);
+        self.op.eval(self.with_const(input, self.konst.clone()))
+    }
+}
+
+impl TypedOp for PulsedGather {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let konst = TypedFact::from(self.konst.clone());
+        let inputs = self.with_const(inputs[0], &konst);
+        self.op.output_facts(&inputs)
+    }
+
+    fn declutter(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        let mut patch = TypedModelPatch::default();
+        let input = patch.tap_model(model, node.inputs[0])?;
+        let konst = patch.add_const(format!("{}.const", node.name), self.konst.clone())?;
+        let wire =
+            patch.wire_node(&*node.name, self.op.clone(), &*self.with_const(input, konst))?[0];
+        patch.shunt_outside(model, node.id.into(), wire)?;
+        Ok(Some(patch))
+    }
+}
+
+impl PulsedOp for PulsedGather {
+    fn pulsed_output_facts(&self, inputs: &[&PulsedFact]) -> TractResult<TVec<PulsedFact>> {
+        let konst = self.konst.shape().iter().map(|d| d.to_dim()).collect::<TVec<_>>();
+        let mut fact = inputs[0].clone();
+        if self.const_slot == 0 {
+            fact.datum_type = self.konst.datum_type();
+            fact.shape = self.op.compute_output_shape(&konst, &inputs[0].shape)?;
+            if let Some(stream) = &mut fact.stream {
+                stream.axis += self.op.axis;
+            }
+        } else {
+            fact.shape = self.op.compute_output_shape(&inputs[0].shape, &konst)?;
+            if let Some(stream) = fact.stream.as_mut().filter(|s| self.op.axis < s.axis) {
+                stream.axis = stream.axis + konst.len() - 1;
+            }
+        }
+        Ok(tvec!(fact))
+    }
+
+    as_op!();
+    pulsed_op_to_typed_op!();
+}
+
+/// Gather along the streaming axis, with constant indices.
+///
+/// The op picks the indexed frames as they come by, and outputs them all at every pulse, so the
+/// output does not stream. The frames not received yet are zeroes.
+#[derive(Debug, Clone, Hash)]
+pub struct PulsedStreamGather {
+    pub axis: usize,
+    pub indices: Arc<Tensor>,
+    pub delay: usize,
+}
+
+impl_dyn_hash!(PulsedStreamGather);
+
+impl PulsedStreamGather {
+    fn output_fact(&self, input: &TypedFact) -> TractResult<TypedFact> {
+        let indices = self.indices.shape().iter().map(|d| d.to_dim()).collect::<TVec<_>>();
+        let shape = Gather::new(self.axis).compute_output_shape(&input.shape, &indices)?;
+        Ok(TypedFact::dt_shape(input.datum_type, &*shape))
+    }
+}
+
+impl Op for PulsedStreamGather {
+    fn name(&self) -> Cow<str> {
+        "PulsedStreamGather".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("axis: {} delay: {} indices: {:?}", self.axis, self.delay, self.indices)])
+    }
+
+    op_pulse!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for PulsedStreamGather {
+    fn is_stateless(&self) -> bool {
+        false
+    }
+
+    fn state(
+        &self,
+        _session: &mut SessionState,
+        _node_id: usize,
+    ) -> TractResult<Option<Box<dyn OpState>>> {
+        Ok(Some(Box::new(PulsedStreamGatherState { position: 0, frames: None })))
+    }
+}
+
+impl TypedOp for PulsedStreamGather {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(tvec!(self.output_fact(inputs[0])?))
+    }
+
+    fn cost(&self, inputs: &[&TypedFact]) -> TractResult<TVec<(Cost, TDim)>> {
+        let output = self.output_fact(inputs[0])?;
+        Ok(tvec!((Cost::Buffer(output.datum_type), output.shape.iter().maybe_product()?)))
+    }
+}
+
+impl PulsedOp for PulsedStreamGather {
+    fn pulsed_output_facts(&self, inputs: &[&PulsedFact]) -> TractResult<TVec<PulsedFact>> {
+        let output = self.output_fact(&inputs[0].to_pulse_fact())?;
+        Ok(tvec!(PulsedFact::non_streaming(&output)?))
+    }
+
+    as_op!();
+    pulsed_op_to_typed_op!();
+}
+
+#[derive(Debug, Clone)]
+struct PulsedStreamGatherState {
+    /// Frames received so far, delay included.
+    position: usize,
+    /// The gathered frames, with the indices flattened along the axis.
+    frames: Option<Tensor>,
+}
+
+impl OpState for PulsedStreamGatherState {
+    fn eval(
+        &mut self,
+        _session: &mut SessionState,
+        op: &dyn Op,
+        mut inputs: TVec<Arc<Tensor>>,
+    ) -> TractResult<TVec<Arc<Tensor>>> {
+        let op =
+            op.downcast_ref::<PulsedStreamGather>().ok_or_else(|| format_err!("Wrong Op type"))?;
+        let input = args_1!(inputs);
+        let indices = op.indices.as_slice::<i64>()?;
+        if self.frames.is_none() {
+            let mut shape: TVec<usize> = input.shape().into();
+            shape[op.axis] = indices.len();
+            self.frames = Some(Tensor::zero_dt(input.datum_type(), &shape)?);
+        }
+        let frames = self.frames.as_mut().unwrap();
+        let pulse = input.shape()[op.axis];
+        // frames of the pulse, in stream coordinates
+        let start = self.position as i64 - op.delay as i64;
+        self.position += pulse;
+        for (ix, &index) in indices.iter().enumerate() {
+            if start <= index && index < start + pulse as i64 {
+                let frame = (index - start) as usize;
+                frames.assign_slice(ix..ix + 1, &input, frame..frame + 1, op.axis)?;
+            }
+        }
+        let mut shape: TVec<usize> = input.shape().into();
+        shape.remove(op.axis);
+        for (ix, &d) in op.indices.shape().iter().enumerate() {
+            shape.insert(op.axis + ix, d);
+        }
+        Ok(tvec!(frames.clone().into_shape(&shape)?.into_arc_tensor()))
+    }
+
+    fn snapshot(&self) -> TractResult<TVec<Tensor>> {
+        let mut tensors = tvec!(tensor0(self.position as i64));
+        tensors.extend(self.frames.iter().cloned());
+        Ok(tensors)
+    }
+
+    fn restore(&mut self, tensors: TVec<Tensor>) -> TractResult<()> {
+        if tensors.is_empty() || tensors.len() > 2 {
+            bail!(
+                "PulsedStreamGather state expects a position and optional frames, got {} tensors",
+                tensors.len()
+            );
+        }
+        let mut tensors = tensors.into_iter();
+        let position = tensors.next().unwrap();
+        if position.rank() != 0 {
+            bail!("PulsedStreamGather position must be a scalar, got {:?}", position);
+        }
+        self.position =
+            position.cast_to_scalar::<i64>().context("PulsedStreamGather position")? as usize;
+        self.frames = tensors.next();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn embedding_over_pulses() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let fact = TypedFact::dt_shape(i64::datum_type(), [stream_dim()].as_ref());
+        let indices = model.add_source("indices", fact)?;
+        let table = model.add_const("table", rctensor2(&[[0f32, 1.], [2., 3.], [4., 5.]]))?;
+        let output = model.wire_node("gather", Gather::new(0), &[table, indices])?;
+        model.set_output_outlets(&output)?;
+
+        let pulsed = PulsedModel::new(&model, 2)?;
+        let fact = pulsed.outlet_fact(pulsed.output_outlets()?[0])?;
+        assert_eq!(fact.streaming()?.axis, 0);
+        assert_eq!(fact.shape, tvec!(2.to_dim(), 2.to_dim()));
+
+        let typed = pulsed.into_typed()?.declutter()?;
+        let plan = SimplePlan::new(typed)?;
+        let mut state = SimpleState::new(plan)?;
+        let output = state.run(tvec!(tensor1(&[2i64, 0])))?;
+        assert_eq!(*output[0], tensor2(&[[4f32, 5.], [0., 1.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn gather_frames_along_the_stream() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let fact = TypedFact::dt_shape(f32::datum_type(), [stream_dim(), 2.to_dim()].as_ref());
+        let input = model.add_source("input", fact)?;
+        let indices = model.add_const("indices", rctensor1(&[4i64, 1, 6]))?;
+        let output = model.wire_node("gather", Gather::new(0), &[input, indices])?;
+        model.set_output_outlets(&output)?;
+
+        let pulsed = PulsedModel::new(&model, 3)?;
+        let fact = pulsed.outlet_fact(pulsed.output_outlets()?[0])?;
+        assert!(fact.stream.is_none());
+        assert_eq!(fact.shape, tvec!(3.to_dim(), 2.to_dim()));
+
+        let plan = SimplePlan::new(pulsed.into_typed()?)?;
+        let mut state = SimpleState::new(plan)?;
+        let input = tract_ndarray::Array2::from_shape_fn((9, 2), |(i, j)| (2 * i + j) as f32);
+        let mut output = None;
+        for chunk in input.axis_chunks_iter(tract_ndarray::Axis(0), 3) {
+            output = Some(state.run(tvec!(chunk.to_owned().into_tensor()))?.remove(0));
+        }
+        assert_eq!(*output.unwrap(), tensor2(&[[8f32, 9.], [2., 3.], [12., 13.]]));
+        Ok(())
+    }
+}
@@ -1,7 +1,9 @@
 use crate::internal::*;
 
+mod broadcast;
 mod concat;
+mod gather;
 mod pad;
 mod slice;
 
-register_all_mod!(concat, pad, slice);
+register_all_mod!(broadcast, concat, gather, pad, slice);
@@ -15,16 +15,17 @@ fn pulsify(
 ) -> TractResult<TVec<OutletId>> {
     let mut input = mapping[&node.inputs[0]];
     let fact = target.outlet_fact(input)?.clone();
-    if !op.pads.iter().enumerate().all(|(ax, &(a, b))| ax == fact.axis || (a == 0 && b == 0)) {
+    let stream = fact.streaming()?;
+    if !op.pads.iter().enumerate().all(|(ax, &(a, b))| ax == stream.axis || (a == 0 && b == 0)) {
         bail!("Pad pulse only implemented for streaming dim");
     }
-    let (before, after) = op.pads[fact.axis];
-    let pulse = fact.pulse();
-    let mut extra_delay = before.saturating_sub(fact.delay);
+    let (before, after) = op.pads[stream.axis];
+    let pulse = fact.pulse().unwrap();
+    let mut extra_delay = before.saturating_sub(stream.delay);
     match op.mode {
         PadMode::Constant(_) => (),
         PadMode::Edge if before < pulse => {
-            let start_offset = (fact.delay + extra_delay) % pulse;
+            let start_offset = (stream.delay + extra_delay) % pulse;
             if before > start_offset {
                 extra_delay += before - start_offset;
             }
@@ -39,22 +40,23 @@ fn pulsify(
     if extra_delay > 0 {
         input = target.wire_node(
             format!("{}.Delay", node.name),
-            Delay::new(fact.axis, &(&fact).into(), extra_delay, 0),
+            Delay::new(stream.axis, &(&fact).into(), extra_delay, 0),
             &[input],
         )?[0];
     }
     let op = PulsePad {
-        axis: fact.axis,
+        axis: stream.axis,
         pulse,
         before,
         after: after.into(),
-        begin_input: fact.delay + extra_delay,
-        end_input: fact.delay.to_dim() + extra_delay + fact.dim,
+        begin_input: stream.delay + extra_delay,
+        end_input: stream.delay.to_dim() + extra_delay + &stream.dim,
         mode: op.mode.clone(),
     };
     target.wire_node(&*node.name, op, &[input])
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, Default, Hash)]
 struct PulsePadOpState {
     current_pos: usize,
@@ -73,8 +75,32 @@ impl OpState for PulsePadOpState {
         let tensor = self.pad(session, op, input)?;
         Ok(tvec!(tensor.into_arc_tensor()))
     }
+
+    fn snapshot(&self) -> TractResult<TVec<Tensor>> {
+        let mut tensors = tvec!(tensor0(self.current_pos as i64));
+        tensors.extend(self.last_valid_frame.iter().cloned());
+        Ok(tensors)
+    }
+
+    fn restore(&mut self, tensors: TVec<Tensor>) -> TractResult<()> {
+        if tensors.is_empty() || tensors.len() > 2 {
+            bail!(
+                "PulsePad state expects a position and an optional frame, got {} tensors",
+                tensors.len()
+            );
+        }
+        let mut tensors = tensors.into_iter();
+        let position = tensors.next().unwrap();
+        if position.rank() != 0 {
+            bail!("PulsePad position must be a scalar, got {:?}", position);
+        }
+        self.current_pos = position.cast_to_scalar::<i64>().context("PulsePad position")? as usize;
+        self.last_valid_frame = tensors.next();
+        Ok(())
+    }
 }
 
+#[allow(dead_code)]
 impl PulsePadOpState {
     unsafe fn save_frame<T: Datum + Copy>(&mut self, op: &PulsePad, input: &Tensor, frame: usize) {
         let data = input.to_array_view_unchecked::<T>();
@@ -200,8 +226,9 @@ impl PulsePadOpState {
 impl PulsedOp for PulsePad {
     fn pulsed_output_facts(&self, inputs: &[&PulsedFact]) -> TractResult<TVec<PulsedFact>> {
         let mut fact = inputs[0].clone();
-        fact.dim += self.before.to_dim() + &self.after;
-        fact.delay -= self.before;
+        let stream = fact.streaming_mut()?;
+        stream.dim += self.before.to_dim() + &self.after;
+        stream.delay -= self.before;
         Ok(tvec!(fact))
     }
 
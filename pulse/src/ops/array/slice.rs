@@ -13,7 +13,7 @@ fn pulsify(
 ) -> TractResult<TVec<OutletId>> {
     let input = mapping[&node.inputs[0]];
     let fact = target.outlet_fact(input)?.clone();
-    let op: Box<dyn PulsedOp> = if op.axis == fact.axis {
+    let op: Box<dyn PulsedOp> = if fact.stream.as_ref().map(|s| s.axis) == Some(op.axis) {
         let skip = op.start.to_usize()?;
         let take = (op.end.clone() - &op.start).to_dim();
         PulsedAxisSlice { axis: op.axis, skip, take }.into()
@@ -27,9 +27,9 @@ impl PulsedOp for Slice {
     fn pulsed_output_facts(&self, inputs: &[&PulsedFact]) -> TractResult<TVec<PulsedFact>> {
         let mut fact = inputs[0].clone();
         let len = (self.end.clone() - &self.start).to_dim();
-        if self.axis == fact.axis {
-            fact.delay += self.start.to_usize()?;
-            fact.dim = len
+        if let Some(stream) = fact.stream.as_mut().filter(|s| s.axis == self.axis) {
+            stream.delay += self.start.to_usize()?;
+            stream.dim = len
         } else {
             fact.shape[self.axis] = len;
         }
@@ -75,13 +75,14 @@ impl EvalOp for PulsedAxisSlice {
 impl PulsedOp for PulsedAxisSlice {
     fn pulsed_output_facts(&self, inputs: &[&PulsedFact]) -> TractResult<TVec<PulsedFact>> {
         let mut fact = inputs[0].clone();
-        fact.delay += self.skip;
-        fact.dim = self.take.clone();
+        let stream = fact.streaming_mut()?;
+        stream.delay += self.skip;
+        stream.dim = self.take.clone();
         Ok(tvec!(fact))
     }
 
     fn to_typed(&self) -> Box<dyn TypedOp> {
-        Box::new(tract_core::ops::identity::Identity::default())
+        Box::new(tract_core::ops::identity::Identity)
     }
 
     as_op!();
@@ -5,7 +5,7 @@ use tract_pulse_opl::ops::Delay;
 
 register_all!(UnaryOp: pulsify_un, TypedBinOp: pulsify_bin, Iff: pulsify_iff);
 
-fn sync_inputs(
+pub(crate) fn sync_inputs(
     node: &TypedNode,
     target: &mut PulsedModel,
     mapping: &HashMap<OutletId, OutletId>,
@@ -13,26 +13,40 @@ fn sync_inputs(
     let delay = node
         .inputs
         .iter()
-        .map(|input| target.outlet_fact(mapping[input]).unwrap().delay)
+        .map(|input| Ok(target.outlet_fact(mapping[input])?.delay()))
+        .collect::<TractResult<TVec<_>>>()?
+        .into_iter()
         .max()
         .unwrap();
     let mut inputs = tvec!();
     for input in &node.inputs {
         let mut input = mapping[input];
         let fact = target.outlet_fact(input)?.clone();
-        if fact.delay < delay {
-            let add_delay = delay - fact.delay;
-            input = target.wire_node(
-                format!("{}.Delay", &*node.name),
-                Delay::new(fact.axis, &fact.into(), add_delay, 0),
-                &[input],
-            )?[0];
+        if let Some(stream) = &fact.stream {
+            if stream.delay < delay {
+                let add_delay = delay - stream.delay;
+                input = target.wire_node(
+                    format!("{}.Delay", &*node.name),
+                    Delay::new(stream.axis, &fact.into(), add_delay, 0),
+                    &[input],
+                )?[0];
+            }
         }
         inputs.push(input);
     }
     Ok(inputs)
 }
 
+/// Stream of the output of a broadcasting op: the one of its streaming inputs, if any.
+pub(crate) fn broadcast_stream(inputs: &[&PulsedFact], rank: usize) -> Option<StreamInfo> {
+    inputs.iter().find_map(|fact| {
+        fact.stream.as_ref().map(|stream| StreamInfo {
+            axis: stream.axis + rank - fact.shape.len(),
+            ..stream.clone()
+        })
+    })
+}
+
 fn pulsify_bin(
     op: &TypedBinOp,
     _source: &TypedModel,
@@ -53,6 +67,7 @@ impl PulsedOp for TypedBinOp {
             .ok_or_else(|| {
                 format_err!("Can not broadcast: {:?} and {:?}", inputs[0].shape, inputs[1].shape)
             })?;
+        fact.stream = broadcast_stream(inputs, fact.shape.len());
         Ok(tvec!(fact))
     }
 
@@ -81,6 +96,7 @@ impl PulsedOp for UnaryOp {
             &self.a.shape().iter().map(|d| d.into()).collect(),
         ])
         .unwrap();
+        fact.stream = broadcast_stream(inputs, fact.shape.len());
         Ok(tvec!(fact))
     }
 
@@ -117,9 +133,37 @@ impl PulsedOp for Iff {
                 inputs[2].shape
             )
         })?;
+        fact.stream = broadcast_stream(inputs, fact.shape.len());
         Ok(tvec!(fact))
     }
 
     as_op!();
     pulsed_op_to_typed_op!();
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn non_streaming_operand() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let fact = TypedFact::dt_shape(f32::datum_type(), [stream_dim(), 2.to_dim()].as_ref());
+        let x = model.add_source("x", fact)?;
+        let bias = model.add_source("bias", TypedFact::dt_shape(f32::datum_type(), &[2]))?;
+        let bias = model.wire_node("bias.add-axis", AxisOp::Add(0), &[bias])?;
+        let sum = model.wire_node("sum", tract_core::ops::math::add::bin_typed(), &[x, bias[0]])?;
+        model.set_output_outlets(&sum)?;
+
+        let pulsed = PulsedModel::new(&model, 2)?;
+        assert!(pulsed.input_fact(1)?.stream.is_none());
+        let fact = pulsed.output_fact(0)?;
+        assert_eq!(fact.streaming()?, &StreamInfo { axis: 0, dim: stream_dim(), delay: 0 });
+
+        let plan = SimplePlan::new(pulsed.into_typed()?)?;
+        let mut state = SimpleState::new(&plan)?;
+        let output = state.run(tvec!(tensor2(&[[0f32, 1.], [2., 3.]]), tensor1(&[10f32, 20.])))?;
+        assert_eq!(*output[0], tensor2(&[[10f32, 21.], [12., 23.]]));
+        Ok(())
+    }
+}
@@ -0,0 +1,152 @@
+use crate::internal::*;
+use tract_core::ops::array::PadMode;
+use tract_core::ops::cnn::{DeconvUnary, PaddingSpec};
+use tract_pulse_opl::ops::{DeconvDelay, PulsePad};
+
+register_all!(DeconvUnary: pulsify);
+
+pub fn register(registry: &mut Registry) {
+    registry.register_dumper(TypeId::of::<DeconvDelay>(), ser_deconv_delay)
+}
+
+fn ser_deconv_delay(ast: &mut IntoAst, node: &TypedNode) -> TractResult<Option<Arc<RValue>>> {
+    let op = node.op().downcast_ref::<DeconvDelay>().unwrap();
+    let wire = ast.mapping[&node.inputs[0]].clone();
+    Ok(Some(invocation(
+        "tract_pulse_deconv_delay",
+        &[wire],
+        &[("axis", numeric(op.axis)), ("overlap", numeric(op.overlap))],
+    )))
+}
+
+fn pulsify(
+    op: &DeconvUnary,
+    source: &TypedModel,
+    node: &TypedNode,
+    target: &mut PulsedModel,
+    mapping: &HashMap<OutletId, OutletId>,
+    _pulse: usize,
+) -> TractResult<TVec<OutletId>> {
+    let mut wire = mapping[&node.inputs[0]];
+    let fact = target.outlet_fact(wire)?.clone();
+    let stream = fact.streaming()?.clone();
+    let input_shape = op.pool_spec.data_format.shape(&*fact.shape)?;
+    if Some(stream.axis) == input_shape.n_axis() {
+        return target.wire_node(&node.name, op.clone(), &[wire]);
+    }
+    if stream.axis == input_shape.c_axis() {
+        bail!("Can not pulsify deconvolution along the input channel axis");
+    }
+    let geo_axis = stream.axis - input_shape.h_axis();
+    let stride = op.pool_spec.stride(geo_axis);
+    let kernel_field =
+        (op.pool_spec.kernel_shape[geo_axis] - 1) * op.pool_spec.dilation(geo_axis) + 1;
+    let overlap = kernel_field.saturating_sub(stride);
+    let adjustment = op.adjustments[geo_axis];
+
+    // frames outside the stream must be zero, as they spill over the valid ones
+    let after = (kernel_field + adjustment).div_ceil(stride);
+    let pad = PulsePad {
+        axis: stream.axis,
+        pulse: fact.pulse().unwrap(),
+        before: stream.delay,
+        after: after.to_dim(),
+        begin_input: stream.delay,
+        end_input: stream.delay.to_dim() + &stream.dim,
+        mode: PadMode::Constant(Tensor::zero_dt(fact.datum_type, &[])?.into_arc_tensor()),
+    };
+    wire = target.wire_node(format!("{}.pad", node.name), pad, &[wire])?[0];
+
+    // no padding on the stream axis, and just enough adjustment to get the
+    // overlap on each pulse
+    let source_shape = source.outlet_fact(node.inputs[0])?.shape.to_tvec();
+    let source_shape = op.pool_spec.data_format.shape(&*source_shape)?;
+    let computed = op.pool_spec.padding.compute_for_deconv(
+        source_shape.hw_dims(),
+        &op.pool_spec.kernel_shape,
+        &op.pool_spec.dilations(),
+        &op.pool_spec.strides(),
+        &op.adjustments,
+    );
+    let mut bef = tvec!();
+    let mut aft = tvec!();
+    for (ix, c) in computed.iter().enumerate() {
+        if ix == geo_axis {
+            bef.push(0);
+            aft.push(0);
+        } else {
+            bef.push(c.pad_before.to_usize()?);
+            aft.push(c.pad_after.to_usize()?);
+        }
+    }
+    let mut pool_spec = op.pool_spec.clone();
+    pool_spec.padding = PaddingSpec::Explicit(bef, aft, false);
+    let mut adjustments = op.adjustments.clone();
+    adjustments[geo_axis] = stride.saturating_sub(kernel_field);
+    let deconv = DeconvUnary { pool_spec, bias: None, adjustments, ..op.clone() };
+    wire = target.wire_node(format!("{}.deconv", node.name), deconv, &[wire])?[0];
+
+    let output_fact = &node.outputs[0].fact;
+    let output_shape = op.pool_spec.data_format.shape(output_fact.shape.to_tvec())?;
+    let deconv_delay = DeconvDelay {
+        axis: stream.axis,
+        overlap,
+        delay: stream.delay * stride + computed[geo_axis].pad_before.to_usize()?,
+        dim: output_shape.hw_dims()[geo_axis].clone(),
+    };
+    let name =
+        if op.bias.is_some() { format!("{}.overlap_add", node.name) } else { node.name.clone() };
+    wire = target.wire_node(name, deconv_delay, &[wire])?[0];
+
+    if let Some(bias) = &op.bias {
+        let mut bias_shape = tvec!(1; output_fact.rank());
+        bias_shape[output_shape.c_axis()] = bias.len();
+        let bias = bias.clone().into_tensor().into_shape(&bias_shape)?;
+        wire = target.wire_node(
+            &node.name,
+            tract_core::ops::math::add::unary(bias.into_arc_tensor()),
+            &[wire],
+        )?[0];
+    }
+    Ok(tvec!(wire))
+}
+
+impl PulsedOp for DeconvUnary {
+    fn pulsed_output_facts(&self, inputs: &[&PulsedFact]) -> TractResult<TVec<PulsedFact>> {
+        let mut fact = inputs[0].clone();
+        let input_shape = self.pool_spec.data_format.shape(&*inputs[0].shape)?;
+        fact.shape = tract_core::ops::cnn::deconv::output_shape(
+            &self.pool_spec,
+            &*inputs[0].shape,
+            &self.adjustments,
+        )?;
+        let stream = fact.streaming_mut()?;
+        if Some(stream.axis) != input_shape.n_axis() {
+            let geo_axis = stream.axis - input_shape.h_axis();
+            let stride = self.pool_spec.stride(geo_axis);
+            let kernel_field =
+                (self.pool_spec.kernel_shape[geo_axis] - 1) * self.pool_spec.dilation(geo_axis) + 1;
+            stream.delay *= stride;
+            stream.dim =
+                (stream.dim.clone() - 1) * stride + kernel_field + self.adjustments[geo_axis];
+        }
+        Ok(tvec!(fact))
+    }
+
+    as_op!();
+    pulsed_op_to_typed_op!();
+}
+
+impl PulsedOp for DeconvDelay {
+    fn pulsed_output_facts(&self, inputs: &[&PulsedFact]) -> TractResult<TVec<PulsedFact>> {
+        let mut fact = inputs[0].clone();
+        fact.shape[self.axis] -= self.overlap;
+        let stream = fact.streaming_mut()?;
+        stream.delay += self.delay;
+        stream.dim = self.dim.clone();
+        Ok(tvec!(fact))
+    }
+
+    as_op!();
+    pulsed_op_to_typed_op!();
+}
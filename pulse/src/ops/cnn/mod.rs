@@ -1,6 +1,7 @@
 use crate::internal::*;
 
 mod conv;
+pub mod deconv;
 mod pools;
 
-register_all_mod!(conv, pools);
+register_all_mod!(conv, deconv, pools);
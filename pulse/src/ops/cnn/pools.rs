@@ -74,13 +74,16 @@ pub fn pulsed_output_facts(
         spatial_dims,
     )?;
     let mut fact = inputs[0].clone();
-    let input_shape = spec.data_format.shape(&*fact.shape)?;
-    let geo_axis = fact.axis - input_shape.h_axis();
-    let dilation = spec.dilations.as_ref().map(|d| d[geo_axis]).unwrap_or(1);
-    let kernel_len = (spec.kernel_shape[geo_axis] - 1) * dilation;
-    let stride = spec.strides.as_ref().and_then(|v| v.get(geo_axis).cloned()).unwrap_or(1);
-    fact.delay /= stride;
-    fact.dim = (fact.dim.clone() - kernel_len.to_dim()).div_ceil(stride as _);
+    let input_shape = spec.data_format.shape(&*inputs[0].shape)?;
+    let stream = fact.streaming_mut()?;
+    if Some(stream.axis) != input_shape.n_axis() {
+        let geo_axis = stream.axis - input_shape.h_axis();
+        let dilation = spec.dilations.as_ref().map(|d| d[geo_axis]).unwrap_or(1);
+        let kernel_len = (spec.kernel_shape[geo_axis] - 1) * dilation;
+        let stride = spec.strides.as_ref().and_then(|v| v.get(geo_axis).cloned()).unwrap_or(1);
+        stream.delay /= stride;
+        stream.dim = (stream.dim.clone() - kernel_len.to_dim()).div_ceil(stride as _);
+    }
     fact.shape = oshape.shape;
     Ok(tvec!(fact))
 }
@@ -95,24 +98,25 @@ pub fn pulsify(
 ) -> TractResult<(OutletId, PoolSpec)> {
     let mut wire = mapping[&node.inputs[0]];
     let mut fact: PulsedFact = target.outlet_fact(wire)?.clone();
+    let mut stream = fact.streaming()?.clone();
     let input_shape = spec.data_format.shape(fact.shape.clone())?;
-    if Some(fact.axis) == input_shape.n_axis() {
+    if Some(stream.axis) == input_shape.n_axis() {
         return Ok((wire, spec.clone()));
     }
-    if fact.axis == input_shape.c_axis() {
+    if stream.axis == input_shape.c_axis() {
         bail!("Can not pulsify cnn pooling ops along the input channel axis");
     }
 
-    let geo_axis = fact.axis - input_shape.h_axis();
+    let geo_axis = stream.axis - input_shape.h_axis();
     let stride = spec.strides.as_ref().and_then(|v| v.get(geo_axis).cloned()).unwrap_or(1);
-    let pulse = fact.pulse();
-    if pulse % stride != 0 {
+    let pulse = fact.pulse().unwrap();
+    if !pulse.is_multiple_of(stride) {
         bail!("Pulsificaton requires pulse to be a stride multiple")
     }
 
     let computed_padding = spec.padding.compute_one(
         geo_axis,
-        &fact.dim,
+        &stream.dim,
         spec.kernel_shape[geo_axis],
         spec.dilation(geo_axis),
         spec.stride(geo_axis),
@@ -128,39 +132,41 @@ pub fn pulsify(
             bail!("No padding value for streaming pool operation");
         };
         let before = computed_padding.pad_before.to_usize()?;
-        let extra_delay = before.saturating_sub(fact.delay);
+        let extra_delay = before.saturating_sub(stream.delay);
         if extra_delay > 0 {
             wire = target.wire_node(
                 format!("{}.delay-for-pad", node.name),
-                tract_pulse_opl::ops::Delay::new(fact.axis, &(&fact).into(), extra_delay, 0),
+                tract_pulse_opl::ops::Delay::new(stream.axis, &(&fact).into(), extra_delay, 0),
                 &[wire],
             )?[0];
             fact = target.outlet_fact(wire)?.clone();
+            stream = fact.streaming()?.clone();
         }
         let op = tract_pulse_opl::ops::PulsePad {
-            axis: fact.axis,
+            axis: stream.axis,
             pulse,
             before,
             after: computed_padding.pad_after.clone(),
-            begin_input: fact.delay,
-            end_input: fact.delay.to_dim() + &fact.dim,
+            begin_input: stream.delay,
+            end_input: stream.delay.to_dim() + &stream.dim,
             mode: PadMode::Constant(value),
         };
         wire = target.wire_node(format!("{}.pad", node.name), op, &[wire])?[0];
         fact = target.outlet_fact(wire)?.clone();
+        stream = fact.streaming()?.clone();
     }
 
     let dilation = spec.dilations.as_ref().map(|d| d[geo_axis]).unwrap_or(1);
     let kernel_len = (spec.kernel_shape[geo_axis] - 1) * dilation;
     let overlap = (kernel_len + 1).saturating_sub(stride);
-    let misalignment = fact.delay % pulse;
+    let misalignment = stream.delay % pulse;
 
     if overlap > 0 || misalignment > 0 {
-        let align_to = (overlap + fact.delay).div_ceil(stride) * stride;
-        let delay = align_to - overlap - fact.delay;
+        let align_to = (overlap + stream.delay).div_ceil(stride) * stride;
+        let delay = align_to - overlap - stream.delay;
         wire = target.wire_node(
             format!("{}.delay", node.name),
-            tract_pulse_opl::ops::Delay::new(fact.axis, &(&fact).into(), delay, overlap),
+            tract_pulse_opl::ops::Delay::new(stream.axis, &(&fact).into(), delay, overlap),
             &[wire],
         )?[0];
     }
@@ -23,7 +23,7 @@ impl PulsedOp for Delay {
     fn pulsed_output_facts(&self, inputs: &[&PulsedFact]) -> TractResult<TVec<PulsedFact>> {
         let mut fact = inputs[0].clone();
         fact.shape[self.axis] += self.overlap;
-        fact.delay += self.delay + self.overlap;
+        fact.streaming_mut()?.delay += self.delay + self.overlap;
         Ok(tvec!(fact))
     }
 
@@ -40,13 +40,11 @@ mod test {
         let fact1 = PulsedFact {
             datum_type: u8::datum_type(),
             shape: tvec![pulse.to_dim()],
-            axis: 0,
-            dim: stream_dim(),
-            delay: 0,
+            stream: Some(StreamInfo { axis: 0, dim: stream_dim(), delay: 0 }),
         };
         let source = model.add_source("source", fact1.clone()).unwrap();
         model
-            .wire_node("delay", Delay::new(fact1.axis, &(&fact1).into(), delay, overlap), &[source])
+            .wire_node("delay", Delay::new(0, &(&fact1).into(), delay, overlap), &[source])
             .unwrap();
         model.auto_outputs().unwrap();
 
@@ -84,6 +82,33 @@ mod test {
         test_pulse_delay_over(4, 0, 6);
     }
 
+    #[test]
+    fn snapshot_and_restore() -> TractResult<()> {
+        let mut model = PulsedModel::default();
+        let fact = PulsedFact {
+            datum_type: u8::datum_type(),
+            shape: tvec![4.to_dim()],
+            stream: Some(StreamInfo { axis: 0, dim: stream_dim(), delay: 0 }),
+        };
+        let source = model.add_source("source", fact.clone())?;
+        let delay = model.wire_node("delay", Delay::new(0, &(&fact).into(), 6, 0), &[source])?;
+        model.set_output_outlets(&delay)?;
+        let plan = Arc::new(SimplePlan::new(model.into_typed()?)?);
+
+        let pulse = |i: u8| tensor1(&[4 * i, 4 * i + 1, 4 * i + 2, 4 * i + 3]);
+        let mut state = SimpleState::new(plan.clone())?;
+        for i in 0..2 {
+            state.run(tvec!(pulse(i)))?;
+        }
+        let blob = tract_nnef::snapshot::write_snapshot(&state.snapshot()?, vec![])?;
+        let mut restored = SimpleState::new(plan)?;
+        restored.restore(&tract_nnef::snapshot::read_snapshot(&*blob)?)?;
+        for i in 2..4 {
+            assert_eq!(restored.run(tvec!(pulse(i)))?, state.run(tvec!(pulse(i)))?);
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_two_delays() {
         let pulse = 4usize;
@@ -91,18 +116,15 @@ mod test {
         let fact_0 = PulsedFact {
             datum_type: u8::datum_type(),
             shape: tvec![pulse.to_dim()],
-            axis: 0,
-            dim: stream_dim(),
-            delay: 0,
+            stream: Some(StreamInfo { axis: 0, dim: stream_dim(), delay: 0 }),
         };
         let source = model.add_source("source", fact_0.clone()).unwrap();
         let delay_1 = model
-            .wire_node("delay-1", Delay::new(fact_0.axis, &(&fact_0).into(), 2, 0), &[source])
+            .wire_node("delay-1", Delay::new(0, &(&fact_0).into(), 2, 0), &[source])
             .unwrap()[0];
         let fact_1 = model.outlet_fact(delay_1).unwrap().clone();
-        let delay_2 = model
-            .wire_node("delay-1", Delay::new(fact_1.axis, &(&fact_1).into(), 2, 0), &[delay_1])
-            .unwrap();
+        let delay_2 =
+            model.wire_node("delay-1", Delay::new(0, &(&fact_1).into(), 2, 0), &[delay_1]).unwrap();
         model.set_output_outlets(&delay_2).unwrap();
 
         let plan = SimplePlan::new(model).unwrap();
@@ -12,7 +12,11 @@ fn pulsify(
     _pulse: usize,
 ) -> TractResult<TVec<OutletId>> {
     let input = mapping[&node.inputs[0]];
-    let pulse = target.outlet_fact(input)?.pulse();
+    let fact = target.outlet_fact(input)?;
+    if fact.streaming()?.axis != op.axis {
+        bail!("Can only pulsify Downsample along the streaming axis")
+    }
+    let pulse = fact.pulse().unwrap();
     let stride = if op.stride > 0 {
         op.stride as usize
     } else {
@@ -28,7 +32,8 @@ impl PulsedOp for Downsample {
     fn pulsed_output_facts(&self, inputs: &[&PulsedFact]) -> TractResult<TVec<PulsedFact>> {
         let mut fact = inputs[0].clone();
         fact.shape[self.axis] /= self.stride as usize;
-        fact.dim = fact.dim.div_ceil(self.stride as _);
+        let stream = fact.streaming_mut()?;
+        stream.dim = stream.dim.clone().div_ceil(self.stride as _);
         Ok(tvec!(fact))
     }
 
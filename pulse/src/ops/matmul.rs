@@ -1,7 +1,7 @@
 use crate::internal::*;
-use tract_core::ops::matmul::MatMulUnary;
+use tract_core::ops::matmul::{MatMul, MatMulUnary};
 
-register_all!(MatMulUnary: pulsify);
+register_all!(MatMulUnary: pulsify, MatMul: pulsify_bin);
 
 fn pulsify(
     op: &MatMulUnary,
@@ -13,16 +13,98 @@ fn pulsify(
 ) -> TractResult<TVec<OutletId>> {
     let input = mapping[&node.inputs[0]];
     let fact = target.outlet_fact(input)?;
-    if fact.axis >= fact.shape.len() - op.b_trans as usize {
-        bail!("Can not pulsify MatMulUnaryA on the k dimension");
+    // a constant A has a fixed k: B can not stream along k
+    if let Some(stream) = &fact.stream {
+        if stream.axis == b_k_axis(fact.shape.len(), op.b_trans) {
+            bail!("Can not pulsify MatMulUnaryA on the k dimension");
+        }
     }
     target.wire_node(&*node.name, op.clone(), &[input])
 }
+
+fn pulsify_bin(
+    op: &MatMul,
+    _source: &TypedModel,
+    node: &TypedNode,
+    target: &mut PulsedModel,
+    mapping: &HashMap<OutletId, OutletId>,
+    _pulse: usize,
+) -> TractResult<TVec<OutletId>> {
+    let inputs = crate::ops::binary::sync_inputs(node, target, mapping)?;
+    let a = target.outlet_fact(inputs[0])?;
+    let b = target.outlet_fact(inputs[1])?;
+    let rank = a.shape.len();
+    if b.shape.len() != rank {
+        bail!("Can only pulsify MatMul with inputs of the same rank");
+    }
+    match (&a.stream, &b.stream) {
+        (Some(sa), Some(sb)) if sa.axis == sb.axis && sa.axis + 2 < rank => {
+            target.wire_node(&*node.name, op.clone(), &inputs)
+        }
+        (Some(sa), Some(sb))
+            if sa.axis == a_k_axis(rank, op.a_trans) && sb.axis == b_k_axis(rank, op.b_trans) =>
+        {
+            let contracted =
+                PulsedContractedMatMul { op: op.clone(), delay: sb.delay, dim: sb.dim.clone() };
+            target.wire_node(&*node.name, contracted, &inputs)
+        }
+        _ => bail!(
+            "Can only pulsify MatMul with both inputs streaming along the same batch axis, or along the k axis"
+        ),
+    }
+}
+
+fn a_k_axis(rank: usize, a_trans: bool) -> usize {
+    rank - 1 - a_trans as usize
+}
+
+fn b_k_axis(rank: usize, b_trans: bool) -> usize {
+    rank - 2 + b_trans as usize
+}
+
+/// Position of the output axis coming from a streaming axis of B.
+fn c_axis_from_b(rank: usize, axis: usize, c_trans: bool) -> usize {
+    if axis + 2 < rank {
+        axis
+    } else {
+        rank - 1 - c_trans as usize
+    }
+}
+
+/// Shape of the product, whatever the lengths of the k axes.
+fn product_shape<D: DimLike>(a: &[D], b: &[D], op: &MatMul) -> TractResult<TVec<D>> {
+    let mut a: TVec<D> = a.into();
+    let mut b: TVec<D> = b.into();
+    let rank = a.len();
+    a[a_k_axis(rank, op.a_trans)] = D::one();
+    b[b_k_axis(rank, op.b_trans)] = D::one();
+    Ok(tract_core::ops::matmul::compute_shape(&a, &b, op.a_trans, op.b_trans, op.c_trans)?.3)
+}
+
+impl PulsedOp for MatMul {
+    fn pulsed_output_facts(&self, inputs: &[&PulsedFact]) -> TractResult<TVec<PulsedFact>> {
+        let mut fact = inputs[0].clone();
+        let (_m, _k, _n, c_shape) = tract_core::ops::matmul::compute_shape(
+            &inputs[0].shape,
+            &inputs[1].shape,
+            self.a_trans,
+            self.b_trans,
+            self.c_trans,
+        )?;
+        fact.datum_type = tract_core::ops::matmul::output_type(inputs[0].datum_type);
+        fact.shape = c_shape;
+        Ok(tvec!(fact))
+    }
+
+    as_op!();
+    pulsed_op_to_typed_op!();
+}
+
 impl PulsedOp for MatMulUnary {
     fn pulsed_output_facts(&self, inputs: &[&PulsedFact]) -> TractResult<TVec<PulsedFact>> {
         let mut fact = inputs[0].clone();
         let (_m, _k, _n, c_shape) = tract_core::ops::matmul::compute_shape(
-            &self.a.shape().into_iter().map(|d| d.to_dim()).collect::<TVec<_>>(),
+            &self.a.shape().iter().map(|d| d.to_dim()).collect::<TVec<_>>(),
             &inputs[0].shape,
             self.a_trans,
             self.b_trans,
@@ -30,9 +112,285 @@ impl PulsedOp for MatMulUnary {
         )?;
         fact.datum_type = tract_core::ops::matmul::output_type(inputs[0].datum_type);
         fact.shape = c_shape;
+        let rank = fact.shape.len();
+        if let Some(stream) = &mut fact.stream {
+            stream.axis = c_axis_from_b(rank, stream.axis, self.c_trans);
+        }
         Ok(tvec!(fact))
     }
 
     as_op!();
     pulsed_op_to_typed_op!();
 }
+
+/// MatMul contracting along the streaming axis.
+///
+/// The product over the whole stream is the sum of the products over its pulses: the op keeps
+/// the running sum, and outputs it at every pulse. It is the full product once the stream is
+/// over, so the output does not stream.
+#[derive(Debug, Clone, Hash)]
+pub struct PulsedContractedMatMul {
+    pub op: MatMul,
+    pub delay: usize,
+    pub dim: TDim,
+}
+
+impl_dyn_hash!(PulsedContractedMatMul);
+
+impl PulsedContractedMatMul {
+    fn output_fact(&self, inputs: &[&TypedFact]) -> TractResult<TypedFact> {
+        let shape = product_shape(&inputs[0].shape, &inputs[1].shape, &self.op)?;
+        Ok(TypedFact::dt_shape(tract_core::ops::matmul::output_type(inputs[0].datum_type), &*shape))
+    }
+}
+
+impl Op for PulsedContractedMatMul {
+    fn name(&self) -> Cow<str> {
+        "PulsedContractedMatMul".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("delay: {} stream: {}", self.delay, self.dim)])
+    }
+
+    op_pulse!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for PulsedContractedMatMul {
+    fn is_stateless(&self) -> bool {
+        false
+    }
+
+    fn state(
+        &self,
+        _session: &mut SessionState,
+        _node_id: usize,
+    ) -> TractResult<Option<Box<dyn OpState>>> {
+        Ok(Some(Box::new(PulsedContractedMatMulState { position: 0, sum: None })))
+    }
+}
+
+impl TypedOp for PulsedContractedMatMul {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(tvec!(self.output_fact(inputs)?))
+    }
+
+    fn cost(&self, inputs: &[&TypedFact]) -> TractResult<TVec<(Cost, TDim)>> {
+        let output = self.output_fact(inputs)?;
+        Ok(tvec!((Cost::Buffer(output.datum_type), output.shape.iter().maybe_product()?)))
+    }
+}
+
+impl PulsedOp for PulsedContractedMatMul {
+    fn pulsed_output_facts(&self, inputs: &[&PulsedFact]) -> TractResult<TVec<PulsedFact>> {
+        let typed = inputs.iter().map(|f| f.to_streaming_fact()).collect::<TVec<_>>();
+        let output = self.output_fact(&*typed.iter().collect::<TVec<_>>())?;
+        Ok(tvec!(PulsedFact::non_streaming(&output)?))
+    }
+
+    as_op!();
+    pulsed_op_to_typed_op!();
+}
+
+#[derive(Debug, Clone)]
+struct PulsedContractedMatMulState {
+    /// Frames received so far, delay included.
+    position: usize,
+    sum: Option<Tensor>,
+}
+
+impl PulsedContractedMatMulState {
+    fn accumulate<T: Datum + std::ops::AddAssign + Copy>(
+        sum: &mut Tensor,
+        product: &Tensor,
+    ) -> TractResult<()> {
+        let mut sum = sum.to_array_view_mut::<T>()?;
+        sum += &product.to_array_view::<T>()?;
+        Ok(())
+    }
+}
+
+impl OpState for PulsedContractedMatMulState {
+    fn eval(
+        &mut self,
+        session: &mut SessionState,
+        op: &dyn Op,
+        inputs: TVec<Arc<Tensor>>,
+    ) -> TractResult<TVec<Arc<Tensor>>> {
+        let op = op
+            .downcast_ref::<PulsedContractedMatMul>()
+            .ok_or_else(|| format_err!("Wrong Op type"))?;
+        let (a, b) = (&inputs[0], &inputs[1]);
+        let rank = b.rank();
+        let (a_axis, b_axis) = (a_k_axis(rank, op.op.a_trans), b_k_axis(rank, op.op.b_trans));
+        let pulse = b.shape()[b_axis];
+        // frames of the pulse, in stream coordinates
+        let start = self.position as i64 - op.delay as i64;
+        self.position += pulse;
+        let mut end = start + pulse as i64;
+        if let Ok(len) = op.dim.eval(&session.resolved_symbols).to_i64() {
+            end = end.min(len);
+        }
+        let valid = start.max(0)..end;
+        if valid.start < valid.end {
+            let in_pulse = (valid.start - start) as usize..(valid.end - start) as usize;
+            let a = a.slice(a_axis, in_pulse.start, in_pulse.end)?;
+            let b = b.slice(b_axis, in_pulse.start, in_pulse.end)?;
+            let product = op.op.eval(tvec!(a.into_arc_tensor(), b.into_arc_tensor()))?.remove(0);
+            if let Some(sum) = &mut self.sum {
+                dispatch_numbers!(Self::accumulate(sum.datum_type())(sum, &product))?;
+            } else {
+                self.sum = Some(product.into_tensor());
+            }
+        }
+        if self.sum.is_none() {
+            let facts = inputs.iter().map(|t| TypedFact::from(t.clone())).collect::<TVec<_>>();
+            let fact = op.output_fact(&*facts.iter().collect::<TVec<_>>())?;
+            self.sum = Some(Tensor::zero_dt(fact.datum_type, &*fact.shape.as_concrete().unwrap())?);
+        }
+        Ok(tvec!(self.sum.clone().unwrap().into_arc_tensor()))
+    }
+
+    fn snapshot(&self) -> TractResult<TVec<Tensor>> {
+        let mut tensors = tvec!(tensor0(self.position as i64));
+        tensors.extend(self.sum.iter().cloned());
+        Ok(tensors)
+    }
+
+    fn restore(&mut self, tensors: TVec<Tensor>) -> TractResult<()> {
+        if tensors.is_empty() || tensors.len() > 2 {
+            bail!(
+                "PulsedContractedMatMul state expects a position and optional partial sum, got {} tensors",
+                tensors.len()
+            );
+        }
+        let mut tensors = tensors.into_iter();
+        let position = tensors.next().unwrap();
+        if position.rank() != 0 {
+            bail!("PulsedContractedMatMul position must be a scalar, got {:?}", position);
+        }
+        self.position =
+            position.cast_to_scalar::<i64>().context("PulsedContractedMatMul position")? as usize;
+        self.sum = tensors.next();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tract_core::ops::array::{Pad, PadMode};
+
+    fn stream(len: usize, channels: usize, freq: f32) -> Tensor {
+        tract_ndarray::Array2::from_shape_fn((len, channels), |(i, j)| {
+            ((3 * i + j) as f32 * freq).sin()
+        })
+        .into_tensor()
+    }
+
+    /// Runs the pulsed model over the whole streams, returns its last output.
+    fn run_pulsed(model: &TypedModel, pulse: usize, inputs: &[Tensor]) -> TractResult<Tensor> {
+        let len = inputs[0].shape()[0];
+        let pulsed = PulsedModel::new(model, pulse)?;
+        let output_fact = pulsed.output_fact(0)?.clone();
+        assert!(output_fact.stream.is_none());
+        let plan = SimplePlan::new(pulsed.into_typed()?)?;
+        let mut state = SimpleState::new(plan)?;
+        state.session_state.resolved_symbols[&stream_symbol()] = Some(len as i64);
+        let mut output = None;
+        // a few more pulses than the stream, to cover the delays
+        for i in 0..len.div_ceil(pulse) + 2 {
+            let pulses = inputs
+                .iter()
+                .map(|input| {
+                    let mut chunk = Tensor::zero::<f32>(&[pulse, input.shape()[1]])?;
+                    let end = ((i + 1) * pulse).min(len);
+                    if i * pulse < end {
+                        chunk.assign_slice(0..end - i * pulse, input, i * pulse..end, 0)?;
+                    }
+                    Ok(chunk)
+                })
+                .collect::<TractResult<TVec<_>>>()?;
+            output = Some(state.run(pulses)?.remove(0).into_tensor());
+        }
+        Ok(output.unwrap())
+    }
+
+    #[test]
+    fn matmul_unary_along_k_is_rejected() -> TractResult<()> {
+        // a typed model can not stream along the k of a constant: pulsify the op on its own
+        let mut model = TypedModel::default();
+        let b = model.add_source("b", TypedFact::dt_shape(f32::datum_type(), &[4usize, 3]))?;
+        let op = MatMulUnary::new(rctensor2(&[[1f32; 4]; 2]), false, false, false);
+        let c = model.wire_node("c", op.clone(), &[b])?;
+        model.set_output_outlets(&c)?;
+        let mut target = PulsedModel::default();
+        let fact = PulsedFact {
+            datum_type: f32::datum_type(),
+            shape: tvec!(4.to_dim(), 3.to_dim()),
+            stream: Some(StreamInfo { axis: 0, dim: stream_dim(), delay: 0 }),
+        };
+        let source = target.add_source("b", fact)?;
+        let mapping = std::iter::once((b, source)).collect();
+        let node = model.node(c[0].node);
+        let err = pulsify(&op, &model, node, &mut target, &mapping, 4).unwrap_err();
+        assert!(err.to_string().contains("k dimension"));
+        Ok(())
+    }
+
+    #[test]
+    fn gram_matrix_over_pulses() -> TractResult<()> {
+        // xᵀ·x, the stream length is not a multiple of the pulse
+        let mut model = TypedModel::default();
+        let fact = TypedFact::dt_shape(f32::datum_type(), [stream_dim(), 3.to_dim()].as_ref());
+        let x = model.add_source("x", fact)?;
+        let op = MatMul::default().with_a_trans(true);
+        let output = model.wire_node("gram", op, &[x, x])?;
+        model.set_output_outlets(&output)?;
+
+        let x = stream(7, 3, 0.4);
+        let expected = model.clone().into_runnable()?.run(tvec!(x.clone()))?.remove(0);
+        let found = run_pulsed(&model, 3, &[x])?;
+        found.close_enough(&expected, true)
+    }
+
+    #[test]
+    fn matmul_along_k_with_delay() -> TractResult<()> {
+        // xᵀ·y with y one frame late: sums x[t]ᵀ·y[t - 1]
+        let mut model = TypedModel::default();
+        let fact =
+            |c: usize| TypedFact::dt_shape(f32::datum_type(), [stream_dim(), c.to_dim()].as_ref());
+        let x = model.add_source("x", fact(2))?;
+        let y = model.add_source("y", fact(3))?;
+        let pad = |before, after| Pad {
+            pads: vec![(before, after), (0, 0)],
+            mode: PadMode::Constant(rctensor0(0f32)),
+        };
+        let x = model.wire_node("x.pad", pad(0, 1), &[x])?[0];
+        let y = model.wire_node("y.pad", pad(1, 0), &[y])?[0];
+        let op = MatMul::default().with_a_trans(true);
+        let output = model.wire_node("matmul", op, &[x, y])?;
+        model.set_output_outlets(&output)?;
+
+        let (x, y) = (stream(7, 2, 0.3), stream(7, 3, 0.7));
+        let expected = model.clone().into_runnable()?.run(tvec!(x.clone(), y.clone()))?.remove(0);
+        let found = run_pulsed(&model, 2, &[x, y])?;
+        found.close_enough(&expected, true)
+    }
+
+    #[test]
+    fn restore_rejects_malformed_snapshot() -> TractResult<()> {
+        let mut state = PulsedContractedMatMulState { position: 0, sum: None };
+        assert!(state.restore(tvec!()).is_err());
+        assert!(state.restore(tvec!(tensor1(&[4i64]))).is_err());
+        let sum = tensor2(&[[1f32, 2.]]);
+        assert!(state.restore(tvec!(tensor0(4i64), sum.clone(), sum.clone())).is_err());
+        state.restore(tvec!(tensor0(4i64), sum.clone()))?;
+        assert_eq!(state.position, 4);
+        assert_eq!(state.sum, Some(sum));
+        Ok(())
+    }
+}
@@ -0,0 +1,139 @@
+use crate::internal::*;
+use tract_core::ops::nn::{AttentionMask, ScaledDotProductAttention};
+use tract_pulse_opl::ops::{Delay, PulsedAttention};
+
+register_all!(ScaledDotProductAttention: pulsify);
+
+pub fn register(registry: &mut Registry) {
+    registry.register_dumper(TypeId::of::<PulsedAttention>(), ser_attention)
+}
+
+fn ser_attention(ast: &mut IntoAst, node: &TypedNode) -> TractResult<Option<Arc<RValue>>> {
+    let op = node.op().downcast_ref::<PulsedAttention>().unwrap();
+    let inputs = node.inputs.iter().map(|i| ast.mapping[i].clone()).collect::<TVec<_>>();
+    let mut attrs = vec![("scale", numeric(op.attention.scale))];
+    match op.attention.mask {
+        AttentionMask::Full => (),
+        AttentionMask::Causal { left_context } => {
+            attrs.push(("causal", logical(true)));
+            attrs.push(("left_context", numeric(left_context.map(|l| l as i64).unwrap_or(-1))));
+        }
+        AttentionMask::Chunked { chunk, left_chunks } => {
+            attrs.push(("chunk", numeric(chunk)));
+            attrs.push(("left_chunks", numeric(left_chunks)));
+        }
+    }
+    attrs.push(("delay", numeric(op.delay)));
+    attrs.push(("context", numeric(op.context)));
+    Ok(Some(invocation("tract_pulse_attention", &inputs, &attrs)))
+}
+
+fn pulsify(
+    op: &ScaledDotProductAttention,
+    _source: &TypedModel,
+    node: &TypedNode,
+    target: &mut PulsedModel,
+    mapping: &HashMap<OutletId, OutletId>,
+    pulse: usize,
+) -> TractResult<TVec<OutletId>> {
+    let context = match op.mask {
+        AttentionMask::Causal { left_context: Some(left_context) } => left_context,
+        AttentionMask::Chunked { chunk, left_chunks } => {
+            if !pulse.is_multiple_of(chunk) {
+                bail!("Pulse ({}) must be a multiple of the attention chunk ({})", pulse, chunk);
+            }
+            chunk * left_chunks
+        }
+        mask => bail!("Can not pulsify attention with unbounded left context ({:?})", mask),
+    };
+    let mut inputs = crate::ops::binary::sync_inputs(node, target, mapping)?;
+    for input in &inputs {
+        let fact = target.outlet_fact(*input)?;
+        if fact.streaming()?.axis + 2 != fact.shape.len() {
+            bail!("Can only pulsify attention along the sequence axis");
+        }
+    }
+    // pulses must start on a chunk boundary
+    if let AttentionMask::Chunked { chunk, .. } = op.mask {
+        let misalignment = target.outlet_fact(inputs[0])?.delay() % chunk;
+        if misalignment > 0 {
+            for (ix, input) in inputs.iter_mut().enumerate() {
+                let fact = target.outlet_fact(*input)?.clone();
+                *input = target.wire_node(
+                    format!("{}.Delay-{}", &*node.name, ix),
+                    Delay::new(fact.streaming()?.axis, &(&fact).into(), chunk - misalignment, 0),
+                    &[*input],
+                )?[0];
+            }
+        }
+    }
+    let delay = target.outlet_fact(inputs[0])?.delay();
+    target.wire_node(
+        &*node.name,
+        PulsedAttention { attention: op.clone(), delay, context },
+        &inputs,
+    )
+}
+
+impl PulsedOp for PulsedAttention {
+    fn pulsed_output_facts(&self, inputs: &[&PulsedFact]) -> TractResult<TVec<PulsedFact>> {
+        let typed = inputs.iter().map(|f| TypedFact::from(*f)).collect::<TVec<_>>();
+        let output = self.attention.output_facts(&*typed.iter().collect::<TVec<_>>())?;
+        let mut fact = inputs[0].clone();
+        fact.shape = output[0].shape.iter().collect();
+        Ok(tvec!(fact))
+    }
+
+    as_op!();
+    pulsed_op_to_typed_op!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn check_over_pulses(mask: AttentionMask, pulse: usize) -> TractResult<()> {
+        let op = ScaledDotProductAttention::new(0.5).with_mask(mask);
+        let mut model = TypedModel::default();
+        let fact = TypedFact::dt_shape(f32::datum_type(), [stream_dim(), 2.to_dim()].as_ref());
+        let q = model.add_source("q", fact.clone())?;
+        let k = model.add_source("k", fact.clone())?;
+        let v = model.add_source("v", fact)?;
+        let output = model.wire_node("attention", op.clone(), &[q, k, v])?;
+        model.set_output_outlets(&output)?;
+
+        let pulsed = PulsedModel::new(&model, pulse)?;
+        let delay = pulsed.outlet_fact(pulsed.output_outlets()?[0])?.delay();
+        let plan = SimplePlan::new(pulsed.into_typed()?)?;
+        let mut state = SimpleState::new(plan)?;
+
+        let len = 6 * pulse;
+        let input = |freq: f32| {
+            tract_ndarray::Array2::from_shape_fn((len, 2), |(i, j)| {
+                ((2 * i + j) as f32 * freq).sin()
+            })
+            .into_tensor()
+        };
+        let (q, k, v) = (input(0.3), input(0.7), input(1.1));
+        let expected = op.eval_at(&q, &k, &v, 0, 0)?;
+        let mut outputs = tvec!();
+        for i in 0..6 {
+            let pulse_of = |t: &Tensor| t.slice(0, i * pulse, (i + 1) * pulse);
+            let out = state.run(tvec!(pulse_of(&q)?, pulse_of(&k)?, pulse_of(&v)?))?;
+            outputs.push(out[0].clone().into_tensor());
+        }
+        let output = Tensor::stack_tensors(0, &outputs)?;
+        output.slice(0, delay, len)?.close_enough(&expected.slice(0, 0, len - delay)?, true)?;
+        Ok(())
+    }
+
+    #[test]
+    fn causal_attention_over_pulses() -> TractResult<()> {
+        check_over_pulses(AttentionMask::Causal { left_context: Some(4) }, 3)
+    }
+
+    #[test]
+    fn chunked_attention_over_pulses() -> TractResult<()> {
+        check_over_pulses(AttentionMask::Chunked { chunk: 2, left_chunks: 1 }, 4)
+    }
+}
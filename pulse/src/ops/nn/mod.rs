@@ -1,5 +1,6 @@
 use crate::internal::*;
 
+pub mod attention;
 mod reduce;
 
-register_all_mod!(reduce);
+register_all_mod!(attention, reduce);
@@ -12,8 +12,8 @@ fn pulsify(
     _pulse: usize,
 ) -> TractResult<TVec<OutletId>> {
     let input = mapping[&node.inputs[0]];
-    let axis = target.outlet_fact(input)?.axis;
-    if op.axes.contains(&axis) {
+    let fact = target.outlet_fact(input)?;
+    if fact.stream.as_ref().map(|s| op.axes.contains(&s.axis)).unwrap_or(false) {
         bail!("Can not reduce over streaming axis");
     }
     target.wire_node(&*node.name, op.clone(), &[input])
@@ -23,7 +23,7 @@ fn pulsify(
         if chunk < 0 {
             bail!("Can not pulsify a backward scan.")
         }
-        if input_fact.axis != axis {
+        if input_fact.streaming()?.axis != axis {
             bail!("Scan pulsification limited to scanning axis");
         }
     }
@@ -31,7 +31,7 @@ fn pulsify(
     let pulse_inputs = node.inputs.iter().map(|i| mapping[i]).collect::<TVec<_>>();
 
     let mut op = op.clone();
-    op.skip = target.outlet_fact(pulse_inputs[0])?.delay;
+    op.skip = target.outlet_fact(pulse_inputs[0])?.streaming()?.delay;
     op.output_mapping.iter_mut().find(|om| om.full_slot.is_some()).unwrap().full_dim_hint = None;
     target.wire_node(&*node.name, op, &pulse_inputs)
 }
@@ -47,26 +47,24 @@ impl PulsedOp for Scan {
                 format_err!("Expects output 0 to be the full stream (and no other output)")
             })?;
         let output_body_fact = self.body.output_fact(output_body_ix)?;
-        let shape = output_body_fact
-            .shape
-            .iter()
-            .enumerate()
-            .map(
-                |(axis, d)| {
+        let stream = inputs[0].streaming()?;
+        let shape =
+            output_body_fact
+                .shape
+                .iter()
+                .enumerate()
+                .map(|(axis, d)| {
                     if axis == output_mapping.axis {
-                        inputs[0].pulse().to_dim()
+                        inputs[0].pulse().unwrap().to_dim()
                     } else {
                         d
                     }
-                },
-            )
-            .collect();
+                })
+                .collect();
         let fact = PulsedFact {
             datum_type: output_body_fact.datum_type,
             shape,
-            axis: output_mapping.axis,
-            dim: inputs[0].dim.clone(),
-            delay: inputs[0].delay,
+            stream: Some(StreamInfo { axis: output_mapping.axis, ..stream.clone() }),
         };
         Ok(tvec!(fact))
     }
@@ -1,3 +1,4 @@
+use crate::fact::StreamFact;
 use crate::internal::*;
 use tract_core::ops::source::*;
 
@@ -11,7 +12,13 @@ fn pulsify(
     _mapping: &HashMap<OutletId, OutletId>,
     pulse: usize,
 ) -> TractResult<TVec<OutletId>> {
-    let pulsed_fact = PulsedFact::from_tensor_fact_pulse(&node.outputs[0].fact, pulse)?;
+    let fact = &node.outputs[0].fact;
+    let pulsed_fact = if fact.shape.stream_info().is_some() {
+        PulsedFact::from_tensor_fact_pulse(fact, pulse)?
+    } else {
+        // inputs that do not stream are fed whole at every pulse
+        PulsedFact::non_streaming(fact)?
+    };
     let id = target.add_source(node.name.clone(), pulsed_fact)?;
     Ok(tvec!(id))
 }
@@ -0,0 +1,226 @@
+//! Writes tests/models/affine_saved_model: a SavedModel computing y = x·w + b,
+//! w and b being variables restored from a one-shard checkpoint bundle, with
+//! the layout TF2 `tf.saved_model.save` gives them.
+//!
+//! Run from the tensorflow directory: `cargo run --example affine_saved_model`.
+extern crate tract_tensorflow;
+use prost::Message;
+use std::collections::HashMap;
+use std::path::Path;
+use tract_tensorflow::tfpb;
+use tract_tensorflow::tfpb::tensorflow::tensor_shape_proto::Dim;
+use tract_tensorflow::tfpb::tensorflow::trackable_object_graph::trackable_object::{
+    ObjectReference, SerializedTensor,
+};
+use tract_tensorflow::tfpb::tensorflow::trackable_object_graph::TrackableObject;
+use tract_tensorflow::tfpb::tensorflow::*;
+
+fn shape(dims: &[i64]) -> TensorShapeProto {
+    TensorShapeProto {
+        dim: dims.iter().map(|&size| Dim { size, name: String::new() }).collect(),
+        unknown_rank: false,
+    }
+}
+
+fn encode(message: &impl Message) -> Vec<u8> {
+    let mut buf = vec![];
+    message.encode(&mut buf).unwrap();
+    buf
+}
+
+fn masked_crc32c(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0x82f63b78 } else { crc >> 1 };
+        }
+    }
+    let crc = !crc;
+    crc.rotate_right(15).wrapping_add(0xa282ead8)
+}
+
+fn write_varint(buf: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        buf.push((v as u8) | 0x80);
+        v >>= 7;
+    }
+    buf.push(v as u8);
+}
+
+/// Appends an uncompressed table block, returns its handle.
+fn write_block(table: &mut Vec<u8>, entries: &[(Vec<u8>, Vec<u8>)]) -> Vec<u8> {
+    let offset = table.len();
+    let mut previous: &[u8] = &[];
+    for (key, value) in entries {
+        let shared = key.iter().zip(previous.iter()).take_while(|(a, b)| a == b).count();
+        write_varint(table, shared as u64);
+        write_varint(table, (key.len() - shared) as u64);
+        write_varint(table, value.len() as u64);
+        table.extend_from_slice(&key[shared..]);
+        table.extend_from_slice(value);
+        previous = key;
+    }
+    table.extend_from_slice(&0u32.to_le_bytes());
+    table.extend_from_slice(&1u32.to_le_bytes());
+    let mut handle = vec![];
+    write_varint(&mut handle, offset as u64);
+    write_varint(&mut handle, (table.len() - offset) as u64);
+    table.push(0); // no compression
+    let crc = masked_crc32c(&table[offset..]);
+    table.extend_from_slice(&crc.to_le_bytes());
+    handle
+}
+
+fn graph() -> GraphDef {
+    let var = |name: &str, dims: &[i64]| {
+        tfpb::node()
+            .name(name)
+            .op("VarHandleOp")
+            .attr("dtype", DataType::DtFloat)
+            .attr("shape", shape(dims))
+            .attr("shared_name", name)
+            .attr("container", "")
+    };
+    let read = |name: &str, var: &str| {
+        tfpb::node().name(name).op("ReadVariableOp").input(var).attr("dtype", DataType::DtFloat)
+    };
+    tfpb::graph()
+        .node(
+            tfpb::node()
+                .name("x")
+                .op("Placeholder")
+                .attr("dtype", DataType::DtFloat)
+                .attr("shape", shape(&[-1, 2])),
+        )
+        .node(var("w", &[2, 2]))
+        .node(var("b", &[2]))
+        .node(read("w/read", "w"))
+        .node(read("b/read", "b"))
+        .node(
+            tfpb::node()
+                .name("matmul")
+                .op("MatMul")
+                .input("x")
+                .input("w/read")
+                .attr("T", DataType::DtFloat)
+                .attr("transpose_a", false)
+                .attr("transpose_b", false),
+        )
+        .node(
+            tfpb::node()
+                .name("y")
+                .op("AddV2")
+                .input("matmul")
+                .input("b/read")
+                .attr("T", DataType::DtFloat),
+        )
+        // not needed by any signature
+        .node(tfpb::node().name("print").op("PrintV2").input("y"))
+}
+
+fn saved_model() -> SavedModel {
+    let tensor_info = |name: &str| TensorInfo {
+        dtype: DataType::DtFloat.into(),
+        tensor_shape: Some(shape(&[-1, 2])),
+        encoding: Some(tensor_info::Encoding::Name(name.to_string())),
+    };
+    let signature = |output: &str, tensor: &str| {
+        let mut inputs = HashMap::new();
+        inputs.insert("x".to_string(), tensor_info("x:0"));
+        let mut outputs = HashMap::new();
+        outputs.insert(output.to_string(), tensor_info(tensor));
+        SignatureDef { inputs, outputs, method_name: "tensorflow/serving/predict".into() }
+    };
+    let mut signature_def = HashMap::new();
+    signature_def.insert("serving_default".to_string(), signature("y", "y:0"));
+    signature_def.insert("project".to_string(), signature("projected", "matmul:0"));
+    let meta = MetaGraphDef {
+        meta_info_def: Some(meta_graph_def::MetaInfoDef {
+            tags: vec!["serve".into()],
+            ..Default::default()
+        }),
+        graph_def: Some(graph()),
+        signature_def,
+        ..MetaGraphDef::default()
+    };
+    SavedModel { saved_model_schema_version: 1, meta_graphs: vec![meta] }
+}
+
+/// Index table and data shard of the checkpoint.
+fn checkpoint() -> (Vec<u8>, Vec<u8>) {
+    let key = |name: &str| format!("{}/.ATTRIBUTES/VARIABLE_VALUE", name);
+    let variable = |name: &str| TrackableObject {
+        attributes: vec![SerializedTensor {
+            name: "VARIABLE_VALUE".into(),
+            full_name: name.into(),
+            checkpoint_key: key(name),
+            optional_restore: false,
+        }],
+        ..TrackableObject::default()
+    };
+    let root = TrackableObject {
+        children: vec![
+            ObjectReference { node_id: 1, local_name: "w".into() },
+            ObjectReference { node_id: 2, local_name: "b".into() },
+        ],
+        ..TrackableObject::default()
+    };
+    let object_graph = TrackableObjectGraph { nodes: vec![root, variable("w"), variable("b")] };
+    // a string tensor: lengths, crc of the lengths, bytes
+    let object_graph = encode(&object_graph);
+    let mut object_graph_tensor = vec![];
+    write_varint(&mut object_graph_tensor, object_graph.len() as u64);
+    object_graph_tensor.extend_from_slice(
+        &masked_crc32c(&(object_graph.len() as u64).to_le_bytes()).to_le_bytes(),
+    );
+    object_graph_tensor.extend_from_slice(&object_graph);
+
+    let floats = |values: &[f32]| values.iter().flat_map(|x| x.to_le_bytes()).collect::<Vec<u8>>();
+    let mut data = vec![];
+    let mut entry = |dt: DataType, dims: &[i64], bytes: &[u8]| {
+        let entry = BundleEntryProto {
+            dtype: dt.into(),
+            shape: Some(shape(dims)),
+            shard_id: 0,
+            offset: data.len() as i64,
+            size: bytes.len() as i64,
+            crc32c: masked_crc32c(bytes),
+            slices: vec![],
+        };
+        data.extend_from_slice(bytes);
+        encode(&entry)
+    };
+    let header = BundleHeaderProto {
+        num_shards: 1,
+        endianness: 0,
+        version: Some(VersionDef { producer: 1, min_consumer: 0, bad_consumers: vec![] }),
+    };
+    // keys in order, the header under the empty key
+    let entries = vec![
+        (vec![], encode(&header)),
+        (
+            b"_CHECKPOINTABLE_OBJECT_GRAPH".to_vec(),
+            entry(DataType::DtString, &[], &object_graph_tensor),
+        ),
+        (key("b").into_bytes(), entry(DataType::DtFloat, &[2], &floats(&[10., 20.]))),
+        (key("w").into_bytes(), entry(DataType::DtFloat, &[2, 2], &floats(&[1., 2., 3., 4.]))),
+    ];
+    let mut table = vec![];
+    let data_block = write_block(&mut table, &entries);
+    let mut footer = write_block(&mut table, &[]); // meta index
+    footer.extend(write_block(&mut table, &[(key("w").into_bytes(), data_block)]));
+    footer.resize(40, 0);
+    footer.extend_from_slice(&0xdb4775248b80fb57u64.to_le_bytes());
+    table.extend_from_slice(&footer);
+    (table, data)
+}
+
+fn main() {
+    let dir = Path::new("tests/models/affine_saved_model");
+    std::fs::create_dir_all(dir.join("variables")).unwrap();
+    std::fs::write(dir.join("saved_model.pb"), encode(&saved_model())).unwrap();
+    let (index, data) = checkpoint();
+    std::fs::write(dir.join("variables/variables.index"), index).unwrap();
+    std::fs::write(dir.join("variables/variables.data-00000-of-00001"), data).unwrap();
+}
@@ -0,0 +1,292 @@
+//! TensorFlow checkpoint bundles (`variables/variables.index` and
+//! `variables/variables.data-?????-of-?????` in a SavedModel).
+//!
+//! The index is a LevelDB-style table mapping tensor names to
+//! `BundleEntryProto`s, locating the tensor bytes in one of the data shards.
+
+use crate::tfpb::tensorflow::{
+    BundleEntryProto, BundleHeaderProto, DataType, TrackableObjectGraph,
+};
+use prost::Message;
+use std::convert::{TryFrom, TryInto};
+use std::io::{Read, Seek, SeekFrom};
+use std::{fs, path};
+use tract_hir::internal::*;
+
+const TABLE_MAGIC: u64 = 0xdb4775248b80fb57;
+const FOOTER_LEN: usize = 48;
+const BLOCK_TRAILER_LEN: usize = 5;
+
+/// Checkpoint key of the serialized `TrackableObjectGraph` in TF2 checkpoints.
+pub const OBJECT_GRAPH_KEY: &str = "_CHECKPOINTABLE_OBJECT_GRAPH";
+
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    prefix: path::PathBuf,
+    header: BundleHeaderProto,
+    entries: HashMap<String, BundleEntryProto>,
+}
+
+impl Checkpoint {
+    /// Open the bundle at `prefix` (`prefix.index` and its data shards).
+    pub fn open(prefix: impl AsRef<path::Path>) -> TractResult<Checkpoint> {
+        let prefix = prefix.as_ref().to_path_buf();
+        let index = fs::read(with_suffix(&prefix, "index"))
+            .with_context(|| format!("Reading checkpoint index {:?}", prefix))?;
+        let mut header = None;
+        let mut entries = HashMap::new();
+        for (key, value) in read_table(&index)? {
+            if key.is_empty() {
+                header = Some(BundleHeaderProto::decode(&*value)?);
+            } else {
+                let key = String::from_utf8(key)
+                    .map_err(|_| format_err!("Non UTF-8 key in checkpoint {:?}", prefix))?;
+                entries.insert(key, BundleEntryProto::decode(&*value)?);
+            }
+        }
+        let header = header.with_context(|| format!("No header in checkpoint {:?}", prefix))?;
+        if header.endianness != 0 {
+            bail!("Only little-endian checkpoints are supported");
+        }
+        Ok(Checkpoint { prefix, header, entries })
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(|k| &**k)
+    }
+
+    fn shard_path(&self, shard: i32) -> path::PathBuf {
+        with_suffix(&self.prefix, &format!("data-{:05}-of-{:05}", shard, self.header.num_shards))
+    }
+
+    /// Read the tensor stored under `key`, if any.
+    pub fn tensor(&self, key: &str) -> TractResult<Option<Tensor>> {
+        let entry = if let Some(entry) = self.entries.get(key) {
+            entry
+        } else {
+            return Ok(None);
+        };
+        if !entry.slices.is_empty() {
+            bail!("Partitioned variable {} is not supported", key);
+        }
+        let dt = DataType::from_i32(entry.dtype)
+            .with_context(|| format!("Invalid data type for {}", key))?;
+        let shape: TVec<usize> = entry
+            .shape
+            .as_ref()
+            .map(TVec::<usize>::try_from)
+            .transpose()?
+            .unwrap_or_else(|| tvec!());
+        let mut file = fs::File::open(self.shard_path(entry.shard_id))?;
+        file.seek(SeekFrom::Start(entry.offset as u64))?;
+        let mut bytes = vec![0u8; entry.size as usize];
+        file.read_exact(&mut bytes)?;
+        let tensor = if dt == DataType::DtString {
+            decode_strings(&shape, &bytes)?
+        } else {
+            unsafe { Tensor::from_raw_dt(DatumType::try_from(dt)?, &shape, &bytes)? }
+        };
+        Ok(Some(tensor))
+    }
+
+    /// Map variable names to checkpoint keys, from the object graph of TF2
+    /// checkpoints.
+    pub fn variable_keys(&self) -> TractResult<HashMap<String, String>> {
+        let mut keys = HashMap::new();
+        if let Some(graph) = self.tensor(OBJECT_GRAPH_KEY)? {
+            let graph = TrackableObjectGraph::decode(&*graph.to_scalar::<Blob>()?.0)?;
+            for node in graph.nodes {
+                for attr in node.attributes {
+                    if attr.name == "VARIABLE_VALUE" && !attr.full_name.is_empty() {
+                        keys.insert(attr.full_name, attr.checkpoint_key);
+                    }
+                }
+            }
+        }
+        Ok(keys)
+    }
+}
+
+fn with_suffix(prefix: &path::Path, suffix: &str) -> path::PathBuf {
+    let mut path = prefix.as_os_str().to_owned();
+    path.push(".");
+    path.push(suffix);
+    path.into()
+}
+
+/// String tensors are stored as the varint64 lengths, a (masked) crc32c of
+/// the lengths, then the bytes.
+fn decode_strings(shape: &[usize], bytes: &[u8]) -> TractResult<Tensor> {
+    let len = shape.iter().product();
+    let mut cursor = bytes;
+    let lengths = (0..len)
+        .map(|_| read_varint(&mut cursor).map(|l| l as usize))
+        .collect::<TractResult<Vec<_>>>()?;
+    if cursor.len() < 4 {
+        bail!("Truncated string tensor");
+    }
+    cursor = &cursor[4..];
+    let mut strings = Vec::with_capacity(len);
+    for l in lengths {
+        if cursor.len() < l {
+            bail!("Truncated string tensor");
+        }
+        strings.push(Blob(cursor[..l].to_vec()));
+        cursor = &cursor[l..];
+    }
+    Ok(tract_ndarray::ArrayD::from_shape_vec(shape, strings)?.into())
+}
+
+fn read_varint(cursor: &mut &[u8]) -> TractResult<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *cursor.first().context("Truncated varint")?;
+        *cursor = &cursor[1..];
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    bail!("Invalid varint")
+}
+
+fn read_block_handle(cursor: &mut &[u8]) -> TractResult<(usize, usize)> {
+    Ok((read_varint(cursor)? as usize, read_varint(cursor)? as usize))
+}
+
+fn read_block(table: &[u8], (offset, size): (usize, usize)) -> TractResult<&[u8]> {
+    if offset + size + BLOCK_TRAILER_LEN > table.len() {
+        bail!("Table block out of bounds");
+    }
+    if table[offset + size] != 0 {
+        bail!("Compressed checkpoint tables are not supported");
+    }
+    Ok(&table[offset..][..size])
+}
+
+/// Iterate over the key/value pairs of a table block, undoing the key prefix
+/// compression.
+fn read_block_entries(block: &[u8]) -> TractResult<Vec<(Vec<u8>, &[u8])>> {
+    if block.len() < 4 {
+        bail!("Truncated table block");
+    }
+    let restarts = u32::from_le_bytes(block[block.len() - 4..].try_into()?) as usize;
+    let end = block
+        .len()
+        .checked_sub(4 * (restarts + 1))
+        .context("Invalid restart count in table block")?;
+    let mut cursor = &block[..end];
+    let mut key: Vec<u8> = vec![];
+    let mut entries = vec![];
+    while !cursor.is_empty() {
+        let shared = read_varint(&mut cursor)? as usize;
+        let non_shared = read_varint(&mut cursor)? as usize;
+        let value_len = read_varint(&mut cursor)? as usize;
+        if shared > key.len() || cursor.len() < non_shared + value_len {
+            bail!("Corrupted table block");
+        }
+        key.truncate(shared);
+        key.extend_from_slice(&cursor[..non_shared]);
+        entries.push((key.clone(), &cursor[non_shared..][..value_len]));
+        cursor = &cursor[non_shared + value_len..];
+    }
+    Ok(entries)
+}
+
+/// All key/value pairs of a table, in key order.
+fn read_table(table: &[u8]) -> TractResult<Vec<(Vec<u8>, Vec<u8>)>> {
+    if table.len() < FOOTER_LEN {
+        bail!("Truncated table");
+    }
+    let footer = &table[table.len() - FOOTER_LEN..];
+    if u64::from_le_bytes(footer[FOOTER_LEN - 8..].try_into()?) != TABLE_MAGIC {
+        bail!("Not a checkpoint index (bad magic number)");
+    }
+    let mut cursor = footer;
+    let _metaindex = read_block_handle(&mut cursor)?;
+    let index = read_block_handle(&mut cursor)?;
+    let mut pairs = vec![];
+    for (_, mut handle) in read_block_entries(read_block(table, index)?)? {
+        let block = read_block(table, read_block_handle(&mut handle)?)?;
+        for (key, value) in read_block_entries(block)? {
+            pairs.push((key, value.to_vec()));
+        }
+    }
+    Ok(pairs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_varint(buf: &mut Vec<u8>, mut v: u64) {
+        while v >= 0x80 {
+            buf.push((v as u8) | 0x80);
+            v >>= 7;
+        }
+        buf.push(v as u8);
+    }
+
+    fn write_block(table: &mut Vec<u8>, entries: &[(&[u8], &[u8])]) -> Vec<u8> {
+        let offset = table.len();
+        let mut previous: &[u8] = &[];
+        for (key, value) in entries {
+            let shared = key.iter().zip(previous.iter()).take_while(|(a, b)| a == b).count();
+            write_varint(table, shared as u64);
+            write_varint(table, (key.len() - shared) as u64);
+            write_varint(table, value.len() as u64);
+            table.extend_from_slice(&key[shared..]);
+            table.extend_from_slice(value);
+            previous = key;
+        }
+        table.extend_from_slice(&0u32.to_le_bytes());
+        table.extend_from_slice(&1u32.to_le_bytes());
+        let mut handle = vec![];
+        write_varint(&mut handle, offset as u64);
+        write_varint(&mut handle, (table.len() - offset) as u64);
+        table.extend_from_slice(&[0; BLOCK_TRAILER_LEN]);
+        handle
+    }
+
+    #[test]
+    fn table_entries() -> TractResult<()> {
+        let mut table = vec![];
+        let data = write_block(
+            &mut table,
+            &[
+                (&b""[..], &b"header"[..]),
+                (&b"dense/bias"[..], &b"b"[..]),
+                (&b"dense/kernel"[..], &b"k"[..]),
+            ],
+        );
+        let index = write_block(&mut table, &[(&b"dense/kernel"[..], &*data)]);
+        let mut footer = vec![];
+        write_varint(&mut footer, 0);
+        write_varint(&mut footer, 0);
+        footer.extend_from_slice(&index);
+        footer.resize(FOOTER_LEN - 8, 0);
+        footer.extend_from_slice(&TABLE_MAGIC.to_le_bytes());
+        table.extend_from_slice(&footer);
+        let pairs = read_table(&table)?;
+        assert_eq!(
+            pairs,
+            vec![
+                (b"".to_vec(), b"header".to_vec()),
+                (b"dense/bias".to_vec(), b"b".to_vec()),
+                (b"dense/kernel".to_vec(), b"k".to_vec()),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn string_tensor() -> TractResult<()> {
+        let bytes = [2u8, 3, 0, 0, 0, 0, b'a', b'b', b'c', b'd', b'e'];
+        let tensor = decode_strings(&[2], &bytes)?;
+        assert_eq!(
+            tensor,
+            tract_ndarray::arr1(&[Blob(b"ab".to_vec()), Blob(b"cde".to_vec())]).into()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,274 @@
+//! Function library support: TF2 graphs wrap most of their computation in
+//! functions, called through `PartitionedCall`, `StatefulPartitionedCall` or
+//! directly by name, and used as `While` and `If` bodies.
+
+use crate::tfpb::tensorflow::attr_value::Value;
+use crate::tfpb::tensorflow::{
+    DataType, FunctionDef, FunctionDefLibrary, GraphDef, NodeDef, OpDef, OpList,
+};
+use tract_hir::internal::*;
+
+#[derive(Clone, Debug, Default)]
+pub struct FunctionLibrary {
+    pub functions: HashMap<String, FunctionDef>,
+    /// Op definitions, used to resolve named function outputs
+    /// ("node:output_arg:index") to output slots.
+    pub op_defs: HashMap<String, OpDef>,
+}
+
+impl FunctionLibrary {
+    pub fn new(library: Option<&FunctionDefLibrary>, op_list: Option<&OpList>) -> FunctionLibrary {
+        let functions = library
+            .into_iter()
+            .flat_map(|l| l.function.iter())
+            .filter_map(|f| f.signature.as_ref().map(|s| (s.name.clone(), f.clone())))
+            .collect();
+        let op_defs = op_list
+            .into_iter()
+            .flat_map(|l| l.op.iter())
+            .map(|o| (o.name.clone(), o.clone()))
+            .collect();
+        FunctionLibrary { functions, op_defs }
+    }
+
+    pub fn function(&self, name: &str) -> TractResult<&FunctionDef> {
+        self.functions.get(name).with_context(|| format!("Function {} not found in library", name))
+    }
+
+    fn signature<'f>(&self, func: &'f FunctionDef) -> TractResult<&'f OpDef> {
+        func.signature.as_ref().context("Function without signature")
+    }
+
+    /// The function a node calls, if any.
+    fn called_function(&self, node: &NodeDef) -> TractResult<Option<&FunctionDef>> {
+        if node.op == "PartitionedCall" || node.op == "StatefulPartitionedCall" {
+            Ok(Some(self.function(node.get_attr_func("f")?)?))
+        } else {
+            Ok(self.functions.get(&node.op))
+        }
+    }
+
+    /// Replace the function call nodes of `graph` by the nodes of the called
+    /// functions, recursively.
+    ///
+    /// Nodes of the function are prefixed by the name of the call node, which
+    /// becomes an `IdentityN` exposing the function outputs.
+    pub fn inline_calls(&self, graph: &GraphDef) -> TractResult<GraphDef> {
+        let mut inlined = graph.clone();
+        inlined.node = vec![];
+        for node in &graph.node {
+            self.inline_node(node, &mut inlined.node)?;
+        }
+        Ok(inlined)
+    }
+
+    fn inline_node(&self, node: &NodeDef, nodes: &mut Vec<NodeDef>) -> TractResult<()> {
+        let func = if let Some(func) = self.called_function(node)? {
+            func
+        } else {
+            nodes.push(node.clone());
+            return Ok(());
+        };
+        let args: Vec<String> =
+            node.input.iter().filter(|i| !i.starts_with('^')).cloned().collect();
+        let (body, outputs) = self
+            .instantiate(func, &format!("{}/", node.name), &args)
+            .with_context(|| format!("Inlining {}", node.name))?;
+        for n in &body {
+            self.inline_node(n, nodes)?;
+        }
+        let mut identity = crate::tfpb::node().name(&node.name).op("IdentityN");
+        identity.input = outputs;
+        identity.input.extend(node.input.iter().filter(|i| i.starts_with('^')).cloned());
+        nodes.push(identity);
+        Ok(())
+    }
+
+    /// Standalone graph for a function: a `Placeholder` per argument (named
+    /// after it) and the function nodes, calls inlined.
+    ///
+    /// Returns the graph, and the function outputs, as "node:slot" inputs.
+    pub fn function_graph(&self, name: &str) -> TractResult<(GraphDef, Vec<String>)> {
+        let func = self.function(name)?;
+        let signature = self.signature(func)?;
+        let mut graph = GraphDef::default();
+        let mut args = vec![];
+        for arg in &signature.input_arg {
+            let mut placeholder = crate::tfpb::node().name(&arg.name).op("Placeholder");
+            match DataType::from_i32(arg.r#type) {
+                None | Some(DataType::DtInvalid) | Some(DataType::DtResource) => (),
+                Some(dt) => placeholder = placeholder.attr("dtype", dt),
+            }
+            graph.node.push(placeholder);
+            args.push(arg.name.clone());
+        }
+        let (body, outputs) =
+            self.instantiate(func, "", &args).with_context(|| format!("Instantiating {}", name))?;
+        for n in &body {
+            self.inline_node(n, &mut graph.node)?;
+        }
+        Ok((graph, outputs))
+    }
+
+    /// Nodes of `func` in the graph format, names prefixed by `prefix`, with
+    /// its arguments bound to `args`.
+    fn instantiate(
+        &self,
+        func: &FunctionDef,
+        prefix: &str,
+        args: &[String],
+    ) -> TractResult<(Vec<NodeDef>, Vec<String>)> {
+        let signature = self.signature(func)?;
+        if signature.input_arg.len() != args.len() {
+            bail!(
+                "Function {} expects {} arguments, got {}",
+                signature.name,
+                signature.input_arg.len(),
+                args.len()
+            );
+        }
+        let args: HashMap<&str, &str> =
+            signature.input_arg.iter().map(|a| &*a.name).zip(args.iter().map(|a| &**a)).collect();
+        let nodes: HashMap<&str, &NodeDef> = func.node_def.iter().map(|n| (&*n.name, n)).collect();
+        let rename = |input: &str| -> TractResult<String> {
+            if let Some(name) = input.strip_prefix('^') {
+                return Ok(if let Some(arg) = args.get(name) {
+                    format!("^{}", arg.trim_start_matches('^').split(':').next().unwrap())
+                } else {
+                    format!("^{}{}", prefix, name)
+                });
+            }
+            let tokens: Vec<&str> = input.split(':').collect();
+            match &*tokens {
+                [name] => Ok(args
+                    .get(name)
+                    .map(|a| a.to_string())
+                    .unwrap_or_else(|| format!("{}{}", prefix, name))),
+                [name, output, index] => {
+                    let node = nodes
+                        .get(name)
+                        .with_context(|| format!("Unknown node {} in {}", name, signature.name))?;
+                    let slot = self.output_slot(node, output, index.parse()?)?;
+                    Ok(format!("{}{}:{}", prefix, name, slot))
+                }
+                _ => bail!("Unexpected function input {}", input),
+            }
+        };
+        let mut body = vec![];
+        for node in &func.node_def {
+            let mut node = node.clone();
+            node.name = format!("{}{}", prefix, node.name);
+            node.input = node.input.iter().map(|i| rename(i)).collect::<TractResult<_>>()?;
+            body.push(node);
+        }
+        let outputs = signature
+            .output_arg
+            .iter()
+            .map(|o| {
+                let ret = func.ret.get(&o.name).with_context(|| {
+                    format!("No value for output {} of {}", o.name, signature.name)
+                })?;
+                rename(ret)
+            })
+            .collect::<TractResult<_>>()?;
+        Ok((body, outputs))
+    }
+
+    /// Flat output slot of the `index`-th tensor of the `output` argument of
+    /// `node`.
+    ///
+    /// Without an op definition, the op is assumed to have one output
+    /// argument.
+    fn output_slot(&self, node: &NodeDef, output: &str, index: usize) -> TractResult<usize> {
+        let output_args = if let Some(func) = self.called_function(node)? {
+            &self.signature(func)?.output_arg
+        } else if let Some(op_def) = self.op_defs.get(&node.op) {
+            &op_def.output_arg
+        } else {
+            return Ok(index);
+        };
+        let mut slot = 0;
+        for arg in output_args {
+            if arg.name == output {
+                return Ok(slot + index);
+            }
+            slot += if !arg.number_attr.is_empty() {
+                node.get_attr_int::<usize>(&arg.number_attr)?
+            } else if !arg.type_list_attr.is_empty() {
+                match node.attr.get(&arg.type_list_attr).and_then(|a| a.value.as_ref()) {
+                    Some(Value::List(list)) => list.r#type.len(),
+                    _ => bail!(
+                        "Node {} expected type list attribute {}",
+                        node.name,
+                        arg.type_list_attr
+                    ),
+                }
+            } else {
+                1
+            };
+        }
+        bail!("Op {} has no output {}", node.op, output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tfpb::tensorflow::op_def::ArgDef;
+    use crate::tfpb::tensorflow::{AttrValue, NameAttrList};
+
+    fn arg(name: &str) -> ArgDef {
+        ArgDef { name: name.to_string(), r#type: DataType::DtFloat.into(), ..ArgDef::default() }
+    }
+
+    // f(x, y) = (x + y, y)
+    fn add_function() -> FunctionDef {
+        let signature = OpDef {
+            name: "add_fn".to_string(),
+            input_arg: vec![arg("x"), arg("y")],
+            output_arg: vec![arg("sum"), arg("y_out")],
+            ..OpDef::default()
+        };
+        let add = crate::tfpb::node().name("add").op("AddV2").input("x").input("y");
+        let mut func = FunctionDef {
+            signature: Some(signature),
+            node_def: vec![add],
+            ..FunctionDef::default()
+        };
+        func.ret.insert("sum".to_string(), "add:z:0".to_string());
+        func.ret.insert("y_out".to_string(), "y".to_string());
+        func
+    }
+
+    #[test]
+    fn inline_partitioned_call() -> TractResult<()> {
+        let library = FunctionLibrary::new(
+            Some(&FunctionDefLibrary { function: vec![add_function()], gradient: vec![] }),
+            None,
+        );
+        let f = AttrValue {
+            value: Some(Value::Func(NameAttrList {
+                name: "add_fn".to_string(),
+                attr: Default::default(),
+            })),
+        };
+        let graph = crate::tfpb::graph()
+            .node(crate::tfpb::node().name("a").op("Placeholder"))
+            .node(crate::tfpb::node().name("b").op("Placeholder"))
+            .node(
+                crate::tfpb::node()
+                    .name("call")
+                    .op("PartitionedCall")
+                    .input("a")
+                    .input("b")
+                    .attr("f", f),
+            );
+        let inlined = library.inline_calls(&graph)?;
+        let names: Vec<&str> = inlined.node.iter().map(|n| &*n.name).collect();
+        assert_eq!(names, vec!["a", "b", "call/add", "call"]);
+        assert_eq!(inlined.node[2].input, vec!["a", "b"]);
+        assert_eq!(inlined.node[3].op, "IdentityN");
+        assert_eq!(inlined.node[3].input, vec!["call/add:0", "b"]);
+        Ok(())
+    }
+}
@@ -1,5 +1,9 @@
-use crate::tfpb::tensorflow::{GraphDef, NodeDef, SavedModel};
+use crate::checkpoint::Checkpoint;
+use crate::function::FunctionLibrary;
+use crate::tfpb::tensorflow::tensor_info::Encoding;
+use crate::tfpb::tensorflow::{GraphDef, MetaGraphDef, NodeDef, SavedModel, TensorInfo};
 use prost::Message;
+use std::collections::HashSet;
 use std::{fs, path};
 use tract_hir::internal::*;
 
@@ -74,13 +78,12 @@ impl Tensorflow {
 
     pub fn determinize(model: &mut GraphDef) -> TractResult<()> {
         for pbnode in &mut model.node {
-            if pbnode.op == "RandomUniform" {
-                if pbnode.get_attr_int::<i64>("seed")? == 0
-                    && pbnode.get_attr_int::<i64>("seed2")? == 0
-                {
-                    pbnode.attr.insert("seed".to_string(), 1.into());
-                    pbnode.attr.insert("seed2".to_string(), 1.into());
-                }
+            if pbnode.op == "RandomUniform"
+                && pbnode.get_attr_int::<i64>("seed")? == 0
+                && pbnode.get_attr_int::<i64>("seed2")? == 0
+            {
+                pbnode.attr.insert("seed".to_string(), 1.into());
+                pbnode.attr.insert("seed2".to_string(), 1.into());
             }
         }
         Ok(())
@@ -108,16 +111,230 @@ impl Tensorflow {
         Ok(SavedModel::decode(b)?)
     }
 
-    /// Convenience method: will read the first model in the saved model
-    /// container. Use open_avec_model for more control.
+    /// Read the `saved_model.pb` of a SavedModel directory.
+    pub fn read_saved_model_dir(&self, dir: impl AsRef<path::Path>) -> TractResult<SavedModel> {
+        let pb = dir.as_ref().join("saved_model.pb");
+        self.open_saved_model(
+            &mut fs::File::open(&pb).with_context(|| format!("Could not open {:?}", pb))?,
+        )
+    }
+
+    /// The meta graph tagged "serve", or the first one.
+    pub fn serving_meta_graph(saved: &SavedModel) -> TractResult<&MetaGraphDef> {
+        saved
+            .meta_graphs
+            .iter()
+            .find(|m| m.meta_info_def.as_ref().is_some_and(|i| i.tags.iter().any(|t| t == "serve")))
+            .or_else(|| saved.meta_graphs.first())
+            .context("Saved model contains no meta graph")
+    }
+
+    /// Convenience method: will read the graph of the serving meta graph in
+    /// the saved model container, ignoring its signatures and variables. Use
+    /// open_saved_model and parse_saved_model for more control.
     pub fn read_saved_model(&self, r: &mut dyn std::io::Read) -> TractResult<GraphDef> {
-        let mut saved = self.open_saved_model(r)?;
-        Ok(saved.meta_graphs.remove(0).graph_def.unwrap())
+        let saved = self.open_saved_model(r)?;
+        Self::serving_meta_graph(&saved)?.graph_def.clone().context("Meta graph without graph")
+    }
+
+    /// Load a SavedModel directory: the graph of the signature named
+    /// `signature` (or "serving_default"), with its variables restored from
+    /// `variables/variables`.
+    pub fn model_for_saved_model_dir(
+        &self,
+        dir: impl AsRef<path::Path>,
+        signature: Option<&str>,
+    ) -> TractResult<TfModelAndExtensions> {
+        let saved = self.read_saved_model_dir(dir.as_ref())?;
+        let prefix = dir.as_ref().join("variables").join("variables");
+        let checkpoint = if prefix.with_extension("index").exists() {
+            Some(Checkpoint::open(&prefix)?)
+        } else {
+            None
+        };
+        self.parse_saved_model(&saved, signature, checkpoint.as_ref())
+    }
+
+    /// Build the model computing a signature of the serving meta graph.
+    ///
+    /// Model inputs and outputs are the signature tensors, in the order of
+    /// their keys, and are labelled by them. Without a signature name,
+    /// "serving_default" or the only signature is used.
+    ///
+    /// Variables are restored from `checkpoint`. Those that are never
+    /// assigned become constants.
+    pub fn parse_saved_model(
+        &self,
+        saved: &SavedModel,
+        signature: Option<&str>,
+        checkpoint: Option<&Checkpoint>,
+    ) -> TractResult<TfModelAndExtensions> {
+        let meta = Self::serving_meta_graph(saved)?;
+        let graph = meta.graph_def.as_ref().context("Meta graph without graph")?;
+        let library = FunctionLibrary::new(
+            graph.library.as_ref(),
+            meta.meta_info_def.as_ref().and_then(|i| i.stripped_op_list.as_ref()),
+        );
+        let mut names: Vec<&str> = meta.signature_def.keys().map(|k| &**k).collect();
+        names.sort();
+        let name = match signature {
+            Some(name) => name,
+            None if names.contains(&"serving_default") || names.len() != 1 => "serving_default",
+            None => names[0],
+        };
+        let signature = meta.signature_def.get(name).with_context(|| {
+            format!("Signature {} not found in saved model (found: {:?})", name, names)
+        })?;
+        let inputs = Self::signature_tensors(&signature.inputs)?;
+        let outputs = Self::signature_tensors(&signature.outputs)?;
+        let graph = Self::prune(graph, outputs.iter().map(|o| &*o.1))?;
+        let TfModelAndExtensions(mut model, extensions) =
+            self.parse_graph_with_library(&graph, &library)?;
+        let inputs = Self::signature_outlets(&mut model, &inputs)?;
+        model.set_input_outlets(&inputs)?;
+        let outputs = Self::signature_outlets(&mut model, &outputs)?;
+        model.set_output_outlets(&outputs)?;
+        if let Some(checkpoint) = checkpoint {
+            Self::restore_variables(&mut model, checkpoint)?;
+        }
+        Ok(TfModelAndExtensions(model, extensions))
+    }
+
+    /// Signature tensors, as (key, tensor name) sorted by key.
+    fn signature_tensors(tensors: &HashMap<String, TensorInfo>) -> TractResult<Vec<(&str, &str)>> {
+        let mut tensors = tensors
+            .iter()
+            .map(|(k, info)| match &info.encoding {
+                Some(Encoding::Name(name)) => Ok((&**k, &**name)),
+                _ => bail!("Signature tensor {} is not a dense tensor", k),
+            })
+            .collect::<TractResult<Vec<_>>>()?;
+        tensors.sort();
+        Ok(tensors)
+    }
+
+    /// Outlets of signature tensors, labelled by their keys.
+    fn signature_outlets(
+        model: &mut InferenceModel,
+        tensors: &[(&str, &str)],
+    ) -> TractResult<TVec<OutletId>> {
+        let mut outlets = tvec!();
+        for (key, tensor) in tensors {
+            let (node, slot) = Self::parse_input(tensor)?;
+            let outlet = OutletId::new(model.node_id_by_name(node)?, slot);
+            model.set_outlet_label(outlet, key.to_string())?;
+            outlets.push(outlet);
+        }
+        Ok(outlets)
+    }
+
+    /// Keep only the nodes `outputs` depend on.
+    fn prune<'a>(
+        graph: &GraphDef,
+        outputs: impl Iterator<Item = &'a str>,
+    ) -> TractResult<GraphDef> {
+        let nodes: HashMap<&str, &NodeDef> = graph.node.iter().map(|n| (&*n.name, n)).collect();
+        let mut todo: Vec<&str> =
+            outputs.map(|o| Ok(Self::parse_input(o)?.0)).collect::<TractResult<_>>()?;
+        let mut keep = HashSet::new();
+        while let Some(name) = todo.pop() {
+            if keep.insert(name) {
+                let node = nodes.get(name).with_context(|| format!("Node {} not found", name))?;
+                for i in &node.input {
+                    todo.push(Self::parse_input(i)?.0);
+                }
+            }
+        }
+        let mut pruned = graph.clone();
+        pruned.node.retain(|n| keep.contains(&*n.name));
+        Ok(pruned)
+    }
+
+    /// Set the value of variables from a checkpoint. The checkpoint key of a
+    /// variable is found from its name in TF2 object graph, or is its name.
+    pub fn restore_variables(
+        model: &mut InferenceModel,
+        checkpoint: &Checkpoint,
+    ) -> TractResult<()> {
+        use crate::ops::vars::*;
+        let keys = checkpoint.variable_keys()?;
+        let assigned: HashSet<String> = model
+            .nodes()
+            .iter()
+            .filter_map(|n| n.op_as::<Assign>().and_then(|a| a.var_id.clone()))
+            .collect();
+        for id in 0..model.nodes().len() {
+            let var = if let Some(var) = model.node(id).op_as::<VariableV2>() {
+                var.clone()
+            } else {
+                continue;
+            };
+            let mut value = None;
+            for name in var.shared_name.iter().chain(std::iter::once(&var.name)) {
+                let key = keys.get(name).unwrap_or(name);
+                if let Some(tensor) = checkpoint.tensor(key)? {
+                    value = Some(tensor.into_arc_tensor());
+                    break;
+                }
+            }
+            let value = if let Some(value) = value { value } else { continue };
+            if assigned.contains(&var.id) {
+                model.node_mut(id).op_as_mut::<VariableV2>().unwrap().initializer = Some(value);
+            } else {
+                model.node_mut(id).op = Box::new(tract_hir::ops::konst::Const(value));
+            }
+        }
+        Ok(())
+    }
+
+    /// Model computing the outputs of a library function from its arguments.
+    pub fn parse_function(
+        &self,
+        library: &FunctionLibrary,
+        name: &str,
+    ) -> TractResult<InferenceModel> {
+        let (graph, outputs) = library.function_graph(name)?;
+        let TfModelAndExtensions(mut model, _) = self
+            .parse_graph_with_library(&graph, library)
+            .with_context(|| format!("Parsing function {}", name))?;
+        let signature = library.function(name)?.signature.as_ref().unwrap();
+        let inputs = signature
+            .input_arg
+            .iter()
+            .map(|arg| Ok(OutletId::new(model.node_id_by_name(&arg.name)?, 0)))
+            .collect::<TractResult<TVec<_>>>()?;
+        let outputs = outputs
+            .iter()
+            .map(|o| {
+                let (node, slot) = Self::parse_input(o)?;
+                Ok(OutletId::new(model.node_id_by_name(node)?, slot))
+            })
+            .collect::<TractResult<TVec<_>>>()?;
+        model.set_input_outlets(&inputs)?;
+        model.set_output_outlets(&outputs)?;
+        Ok(model)
     }
 
     pub fn parse_graph(&self, graph: &GraphDef) -> TractResult<TfModelAndExtensions> {
+        self.parse_graph_with_library(graph, &FunctionLibrary::new(graph.library.as_ref(), None))
+    }
+
+    /// Parse a graph, inlining the calls to `library` functions.
+    pub fn parse_graph_with_library(
+        &self,
+        graph: &GraphDef,
+        library: &FunctionLibrary,
+    ) -> TractResult<TfModelAndExtensions> {
         use crate::ops::control_flow as cf;
 
+        let inlined;
+        let graph = if library.functions.is_empty() {
+            graph
+        } else {
+            inlined = library.inline_calls(graph)?;
+            &inlined
+        };
+
         let mut model = InferenceModel::default();
         let mut inputs = tvec!();
         let mut context = ParsingContext::default();
@@ -144,9 +361,17 @@ impl Tensorflow {
                 continue;
             }
 
-            let op = match self.op_register.0.get(&pbnode.op) {
-                Some(builder) => (builder)(&context, pbnode)?,
-                None => tract_hir::ops::unimpl::UnimplementedOp::new(
+            let op: Box<dyn InferenceOp> = match (&*pbnode.op, self.op_register.0.get(&pbnode.op)) {
+                ("While", _) | ("StatelessWhile", _) => Box::new(cf::While::new(
+                    self.parse_function(library, pbnode.get_attr_func("cond")?)?,
+                    self.parse_function(library, pbnode.get_attr_func("body")?)?,
+                )),
+                ("If", _) | ("StatelessIf", _) => Box::new(cf::If::new(
+                    self.parse_function(library, pbnode.get_attr_func("then_branch")?)?,
+                    self.parse_function(library, pbnode.get_attr_func("else_branch")?)?,
+                )),
+                (_, Some(builder)) => (builder)(&context, pbnode)?,
+                (_, None) => tract_hir::ops::unimpl::UnimplementedOp::new(
                     context.node_output_arities.get(name).cloned().unwrap_or(1),
                     &pbnode.op,
                     format!("{:?}", pbnode),
@@ -160,8 +385,11 @@ impl Tensorflow {
 
             let node_id = model.add_node(name.clone(), op, facts)?;
             if pbnode.op == "Placeholder" {
-                let dt = pbnode.get_attr_datum_type("dtype")?;
-                let mut fact = InferenceFact::dt(dt);
+                // function arguments holding resources have no dtype
+                let mut fact = pbnode
+                    .get_attr_opt_datum_type("dtype")?
+                    .map(InferenceFact::dt)
+                    .unwrap_or_default();
                 if let Some(shape) = pbnode.get_attr_opt_shape("shape")? {
                     let shape_factoid = ShapeFactoid::closed(
                         shape
@@ -235,7 +463,15 @@ impl Tensorflow {
 
 impl Framework<GraphDef, InferenceModel> for Tensorflow {
     /// This method will try to read as frozen model, then as a saved model.
+    /// Directories are read as SavedModel directories.
     fn proto_model_for_path(&self, r: impl AsRef<path::Path>) -> TractResult<GraphDef> {
+        if r.as_ref().is_dir() {
+            let saved = self.read_saved_model_dir(r)?;
+            return Self::serving_meta_graph(&saved)?
+                .graph_def
+                .clone()
+                .context("Meta graph without graph");
+        }
         self.read_frozen_model(&mut fs::File::open(r.as_ref())?)
             .or_else(|_| self.read_saved_model(&mut fs::File::open(r.as_ref())?))
     }
@@ -249,4 +485,15 @@ impl Framework<GraphDef, InferenceModel> for Tensorflow {
     fn model_for_proto_model(&self, graph: &GraphDef) -> TractResult<InferenceModel> {
         Ok(self.parse_graph(graph)?.0)
     }
+
+    /// SavedModel directories are loaded with their default signature and
+    /// variables.
+    fn model_for_path(&self, p: impl AsRef<path::Path>) -> TractResult<InferenceModel> {
+        if p.as_ref().is_dir() {
+            Ok(self.model_for_saved_model_dir(p, None)?.0)
+        } else {
+            let proto = self.proto_model_for_path(p)?;
+            self.model_for_proto_model(&proto)
+        }
+    }
 }
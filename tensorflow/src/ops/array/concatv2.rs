@@ -68,7 +68,7 @@ impl Expansion for ConcatV2 {
     ) -> TractResult<TVec<OutletId>> {
         if let Some(ref axis) = model.outlet_fact(*inputs.last().unwrap())?.konst {
             let axis = *axis.to_scalar::<i32>()? as usize;
-            let inputs = inputs.into_iter().copied().rev().skip(1).rev().collect::<TVec<_>>();
+            let inputs = inputs.iter().copied().rev().skip(1).rev().collect::<TVec<_>>();
             model.wire_node(
                 prefix,
                 tract_hir::tract_core::ops::array::TypedConcat::concat_vars(axis, inputs.len()),
@@ -37,7 +37,7 @@ impl Expansion for GatherV2 {
             move |s, input_shape, indices_shape, axis| {
                 let mut axis = *axis.to_scalar::<i32>()?;
                 if axis < 0 {
-                    axis = input_shape.len() as i32 + axis;
+                    axis += input_shape.len() as i32;
                 }
                 let op = tract_hir::ops::array::Gather::new(axis as usize);
                 let output_shape = op.compute_output_shape(&input_shape, &indices_shape)?;
@@ -55,7 +55,7 @@ impl Expansion for GatherV2 {
         if let Some(axis) = target.outlet_fact(inputs[2])?.konst.as_ref() {
             let mut axis = *axis.to_scalar::<i32>()?;
             if axis < 0 {
-                axis = target.outlet_fact(inputs[0])?.rank() as i32 + axis;
+                axis += target.outlet_fact(inputs[0])?.rank() as i32;
             }
             let op = tract_hir::ops::array::Gather::new(axis as usize);
             target.wire_node(&*prefix, op, &inputs[0..2])
@@ -0,0 +1,54 @@
+use tract_hir::internal::*;
+
+use crate::model::ParsingContext;
+use crate::tfpb::tensorflow::NodeDef;
+
+pub fn identity_n(_ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    let n = pb.input.iter().filter(|i| !i.starts_with('^')).count();
+    Ok(expand(IdentityN { n }))
+}
+
+/// Forward each input to the matching output.
+#[derive(Debug, Clone, Hash)]
+pub struct IdentityN {
+    n: usize,
+}
+
+impl_dyn_hash!(IdentityN);
+
+impl Expansion for IdentityN {
+    fn name(&self) -> Cow<str> {
+        "IdentityN".into()
+    }
+
+    op_tf!();
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(self.n)
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, self.n)?;
+        check_output_arity(&outputs, self.n)?;
+        for (input, output) in inputs.iter().zip(outputs.iter()) {
+            s.equals(&input.datum_type, &output.datum_type)?;
+            s.equals(&input.shape, &output.shape)?;
+            s.equals(&input.value, &output.value)?;
+        }
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        _prefix: &str,
+        _model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        Ok(inputs.into())
+    }
+}
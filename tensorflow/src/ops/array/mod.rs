@@ -7,8 +7,8 @@ use crate::tfpb::tensorflow::NodeDef;
 mod concatv2;
 mod expand_dims;
 mod fill;
-mod gather;
 mod gather_v2;
+mod identity_n;
 mod pack;
 mod pad;
 mod range;
@@ -19,8 +19,9 @@ pub fn register_all_ops(reg: &mut TfOpRegister) {
     reg.insert("ConcatV2", concatv2::build);
     reg.insert("ExpandDims", expand_dims::build);
     reg.insert("Fill", fill::fill);
-    reg.insert("GatherNd", gather::gather_nd);
+    reg.insert("GatherNd", |_, _| Ok(Box::new(tract_hir::ops::array::GatherNd::new(0))));
     reg.insert("GatherV2", gather_v2::gather_v2);
+    reg.insert("IdentityN", identity_n::identity_n);
     reg.insert("Pack", pack::pack);
     reg.insert("Pad", pad::pad);
     reg.insert("Range", range::range);
@@ -29,6 +30,7 @@ pub fn register_all_ops(reg: &mut TfOpRegister) {
     reg.insert("Slice", slice);
     reg.insert("Squeeze", squeeze::squeeze);
     reg.insert("StridedSlice", strided_slice);
+    reg.insert("TensorScatterUpdate", |_, _| Ok(Box::new(tract_hir::ops::array::ScatterNd)));
     reg.insert("Tile", |_, _| Ok(expand(::tract_hir::ops::array::Tile)));
     reg.insert("Transpose", transpose::transpose);
 }
@@ -1,4 +1,5 @@
 use tract_hir::internal::*;
+use tract_hir::tract_core::ops::control_flow;
 
 use crate::model::TfOpRegister;
 
@@ -117,3 +118,428 @@ impl InferenceRulesOp for NextIteration {
 
     as_op!();
 }
+
+fn bool_scalar() -> InferenceFact {
+    InferenceFact::dt_shape(bool::datum_type(), TVec::<usize>::new())
+}
+
+/// Unify type and shape, but not value: loop-carried values change across
+/// iterations.
+fn unify_dt_shape(a: &mut InferenceFact, b: &mut InferenceFact) -> TractResult<bool> {
+    Ok(a.datum_type.unify_with_mut(&mut b.datum_type)? | a.shape.unify_with_mut(&mut b.shape)?)
+}
+
+/// Inline the nodes of `model` in `target`, feeding its inputs from `inputs`.
+fn wire_model(
+    target: &mut TypedModel,
+    prefix: &str,
+    model: &TypedModel,
+    inputs: &[OutletId],
+) -> TractResult<TVec<OutletId>> {
+    let mut mapping: HashMap<OutletId, OutletId> = HashMap::new();
+    for (ix, input) in model.input_outlets()?.iter().enumerate() {
+        mapping.insert(*input, inputs[ix]);
+    }
+    for n in model.eval_order()? {
+        if model.input_outlets()?.iter().any(|i| i.node == n) {
+            continue;
+        }
+        let node = model.node(n);
+        let node_inputs = node.inputs.iter().map(|i| mapping[i]).collect::<TVec<_>>();
+        let wires =
+            target.wire_node(format!("{}.{}", prefix, node.name), node.op.clone(), &node_inputs)?;
+        for (ix, w) in wires.into_iter().enumerate() {
+            mapping.insert(OutletId::new(n, ix), w);
+        }
+    }
+    Ok(model.output_outlets()?.iter().map(|o| mapping[o]).collect())
+}
+
+/// TF2 functional loop (`While` and `StatelessWhile`): `body` is applied to
+/// the loop variables as long as `cond` holds on them.
+///
+/// Translates to a core `Loop`, whose body computes the next loop variables
+/// then the condition on them.
+#[derive(Debug, Clone, Default, Hash, new)]
+pub struct While {
+    pub cond: InferenceModel,
+    pub body: InferenceModel,
+}
+
+impl_dyn_hash!(While);
+
+impl Op for While {
+    fn name(&self) -> Cow<str> {
+        "While".into()
+    }
+
+    op_tf!();
+    not_a_typed_op!();
+}
+
+impl EvalOp for While {
+    fn is_stateless(&self) -> bool {
+        false
+    }
+
+    fn state(
+        &self,
+        _session: &mut SessionState,
+        _node_id: usize,
+    ) -> TractResult<Option<Box<dyn OpState>>> {
+        Ok(Some(Box::new(WhileState {
+            cond_state: TypedSimpleState::new(Arc::new(TypedSimplePlan::new(
+                self.cond.clone().into_typed()?,
+            )?))?,
+            body_state: TypedSimpleState::new(Arc::new(TypedSimplePlan::new(
+                self.body.clone().into_typed()?,
+            )?))?,
+        })))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct WhileState {
+    cond_state: TypedSimpleState<TypedModel, Arc<TypedSimplePlan<TypedModel>>>,
+    body_state: TypedSimpleState<TypedModel, Arc<TypedSimplePlan<TypedModel>>>,
+}
+
+impl OpState for WhileState {
+    fn eval(
+        &mut self,
+        _session: &mut SessionState,
+        _op: &dyn Op,
+        inputs: TVec<Arc<Tensor>>,
+    ) -> TractResult<TVec<Arc<Tensor>>> {
+        let mut vars = inputs;
+        loop {
+            let cond_inputs = vars.iter().map(|t| t.clone().into_tensor()).collect();
+            let cond = self.cond_state.run(cond_inputs).context("Evaluating while condition")?;
+            if !*cond[0].to_scalar::<bool>()? {
+                break;
+            }
+            let body_inputs = vars.into_iter().map(|t| t.into_tensor()).collect();
+            vars = self.body_state.run(body_inputs).context("Evaluating while body")?;
+        }
+        Ok(vars)
+    }
+
+    fn snapshot(&self) -> TractResult<TVec<Tensor>> {
+        Ok(tvec!())
+    }
+
+    fn restore(&mut self, _tensors: TVec<Tensor>) -> TractResult<()> {
+        Ok(())
+    }
+}
+
+impl InferenceOp for While {
+    fn infer_facts(
+        &mut self,
+        inputs: TVec<&InferenceFact>,
+        outputs: TVec<&InferenceFact>,
+        _observed: TVec<&InferenceFact>,
+    ) -> TractResult<(TVec<InferenceFact>, TVec<InferenceFact>, TVec<InferenceFact>)> {
+        let mut inputs: TVec<InferenceFact> = inputs.into_iter().cloned().collect();
+        let mut outputs: TVec<InferenceFact> = outputs.into_iter().cloned().collect();
+        self.cond.output_fact_mut(0)?.unify_with(&bool_scalar())?;
+        loop {
+            let mut changed = false;
+            for (ix, input) in inputs.iter_mut().enumerate() {
+                changed |= unify_dt_shape(input, self.cond.input_fact_mut(ix)?)?;
+                changed |= unify_dt_shape(input, self.body.input_fact_mut(ix)?)?;
+                changed |= unify_dt_shape(input, self.body.output_fact_mut(ix)?)?;
+                changed |= unify_dt_shape(input, &mut outputs[ix])?;
+            }
+            changed |= self.cond.analyse(false).context("analysing while condition")?;
+            changed |= self.body.analyse(false).context("analysing while body")?;
+            if !changed {
+                break;
+            }
+        }
+        Ok((inputs, outputs, tvec!()))
+    }
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let inputs = node.inputs.iter().map(|m| mapping[m]).collect::<TVec<_>>();
+        let cond = self.cond.clone().into_typed()?;
+        let body = self.body.clone().into_typed()?;
+        let first_cond = wire_model(target, &format!("{}.cond", node.name), &cond, &inputs)?[0];
+        let trip_count =
+            target.add_const(format!("{}.trip_count", node.name), tensor0(i64::MAX))?;
+
+        let mut loop_body = TypedModel::default();
+        loop_body.add_source("iteration", TypedFact::dt_shape(i64::datum_type(), &[0; 0]))?;
+        loop_body.add_source("cond", TypedFact::dt_shape(bool::datum_type(), &[0; 0]))?;
+        let mut vars = tvec!();
+        for (ix, input) in body.input_outlets()?.iter().enumerate() {
+            let fact = body.outlet_fact(*input)?.clone();
+            vars.push(loop_body.add_source(format!("var.{}", ix), fact)?);
+        }
+        let next = wire_model(&mut loop_body, "body", &body, &vars)?;
+        let next_cond = wire_model(&mut loop_body, "cond", &cond, &next)?[0];
+        let loop_outputs =
+            std::iter::once(next_cond).chain(next.iter().cloned()).collect::<TVec<_>>();
+        loop_body.set_output_outlets(&loop_outputs)?;
+
+        let mut loop_inputs = tvec!(trip_count, first_cond);
+        loop_inputs.extend(inputs.iter().cloned());
+        let iters = target.symbol_table.new_with_prefix("loop_iters");
+        target.wire_node(
+            &*node.name,
+            control_flow::Loop::new(loop_body, inputs.len(), iters),
+            &loop_inputs,
+        )
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(self.body.output_outlets()?.len())
+    }
+
+    as_op!();
+}
+
+/// TF2 functional conditional (`If` and `StatelessIf`). Input 0 is the
+/// (boolean scalar) condition, the others are the branch arguments.
+#[derive(Debug, Clone, Default, Hash, new)]
+pub struct If {
+    pub then_body: InferenceModel,
+    pub else_body: InferenceModel,
+}
+
+impl_dyn_hash!(If);
+
+impl If {
+    fn to_core(&self, symbols: &SymbolScope) -> TractResult<control_flow::IfThenElse> {
+        let then_body = self.then_body.clone().into_typed()?;
+        let else_body = self.else_body.clone().into_typed()?;
+        let then_input_mapping = (1..=then_body.input_outlets()?.len()).collect();
+        let else_input_mapping = (1..=else_body.input_outlets()?.len()).collect();
+        control_flow::IfThenElse::new(
+            then_body,
+            then_input_mapping,
+            else_body,
+            else_input_mapping,
+            symbols,
+        )
+    }
+}
+
+impl Op for If {
+    fn name(&self) -> Cow<str> {
+        "If".into()
+    }
+
+    op_tf!();
+    not_a_typed_op!();
+}
+
+impl EvalOp for If {
+    fn is_stateless(&self) -> bool {
+        false
+    }
+
+    fn state(
+        &self,
+        session: &mut SessionState,
+        node_id: usize,
+    ) -> TractResult<Option<Box<dyn OpState>>> {
+        self.to_core(&self.then_body.symbol_table)?.state(session, node_id)
+    }
+}
+
+impl InferenceOp for If {
+    fn infer_facts(
+        &mut self,
+        inputs: TVec<&InferenceFact>,
+        outputs: TVec<&InferenceFact>,
+        _observed: TVec<&InferenceFact>,
+    ) -> TractResult<(TVec<InferenceFact>, TVec<InferenceFact>, TVec<InferenceFact>)> {
+        let mut inputs: TVec<InferenceFact> = inputs.into_iter().cloned().collect();
+        let mut outputs: TVec<InferenceFact> = outputs.into_iter().cloned().collect();
+        inputs[0].unify_with(&bool_scalar())?;
+        loop {
+            let mut changed = false;
+            for body in &mut [&mut self.then_body, &mut self.else_body] {
+                for (ix, input) in inputs.iter_mut().enumerate().skip(1) {
+                    changed |= input.unify_with_mut(body.input_fact_mut(ix - 1)?)?;
+                }
+                changed |= body.analyse(false).context("analysing branch")?;
+                for (ix, output) in outputs.iter_mut().enumerate() {
+                    let fact = body.output_fact_mut(ix)?;
+                    changed |= output.datum_type.unify_with_mut(&mut fact.datum_type)?;
+                }
+            }
+            if let Some(cond) = inputs[0].value.concretize() {
+                let body = if *cond.to_scalar::<bool>()? {
+                    &mut self.then_body
+                } else {
+                    &mut self.else_body
+                };
+                for (ix, output) in outputs.iter_mut().enumerate() {
+                    changed |= output.shape.unify_with_mut(&mut body.output_fact_mut(ix)?.shape)?;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        Ok((inputs, outputs, tvec!()))
+    }
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let inputs = node.inputs.iter().map(|m| mapping[m]).collect::<TVec<_>>();
+        let op = self.to_core(&target.symbol_table)?;
+        target.wire_node(&*node.name, op, &*inputs)
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(self.then_body.output_outlets()?.len())
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tfpb::tensorflow::attr_value::Value;
+    use crate::tfpb::tensorflow::op_def::ArgDef;
+    use crate::tfpb::tensorflow::{
+        AttrValue, DataType, FunctionDef, FunctionDefLibrary, NameAttrList, NodeDef, OpDef,
+        TensorProto,
+    };
+    use crate::tfpb::{graph, node};
+    use std::convert::TryFrom;
+    use tract_hir::prelude::*;
+
+    fn arg(name: &str, dt: DataType) -> ArgDef {
+        ArgDef { name: name.to_string(), r#type: dt.into(), ..ArgDef::default() }
+    }
+
+    fn konst(name: &str, value: i32) -> TractResult<NodeDef> {
+        Ok(node()
+            .name(name)
+            .op("Const")
+            .attr("dtype", DataType::DtInt32)
+            .attr("value", TensorProto::try_from(&tensor0(value))?))
+    }
+
+    fn function(name: &str, output: ArgDef, nodes: Vec<NodeDef>, ret: &str) -> FunctionDef {
+        let signature = OpDef {
+            name: name.to_string(),
+            input_arg: vec![arg("i", DataType::DtInt32)],
+            output_arg: vec![output.clone()],
+            ..OpDef::default()
+        };
+        let mut func =
+            FunctionDef { signature: Some(signature), node_def: nodes, ..FunctionDef::default() };
+        func.ret.insert(output.name, ret.to_string());
+        func
+    }
+
+    fn func_attr(name: &str) -> AttrValue {
+        AttrValue {
+            value: Some(Value::Func(NameAttrList {
+                name: name.to_string(),
+                attr: Default::default(),
+            })),
+        }
+    }
+
+    // i = x; while i < 5 { i = i + 1 }
+    #[test]
+    fn while_loop() -> TractResult<()> {
+        let cond = function(
+            "cond",
+            arg("lt", DataType::DtBool),
+            vec![
+                konst("five", 5)?,
+                node().name("less").op("Less").input("i").input("five:output:0"),
+            ],
+            "less:z:0",
+        );
+        let body = function(
+            "body",
+            arg("next", DataType::DtInt32),
+            vec![konst("one", 1)?, node().name("add").op("AddV2").input("i").input("one:output:0")],
+            "add:z:0",
+        );
+        let mut graph =
+            graph().node(node().name("x").op("Placeholder").attr("dtype", DataType::DtInt32)).node(
+                node()
+                    .name("loop")
+                    .op("StatelessWhile")
+                    .input("x")
+                    .attr("cond", func_attr("cond"))
+                    .attr("body", func_attr("body")),
+            );
+        graph.library = Some(FunctionDefLibrary { function: vec![cond, body], gradient: vec![] });
+        let mut model = crate::tensorflow().model_for_proto_model(&graph)?;
+        model
+            .set_input_fact(0, InferenceFact::dt_shape(i32::datum_type(), TVec::<usize>::new()))?;
+        let model = model.into_optimized()?.into_runnable()?;
+        for (x, expected) in &[(0, 5), (3, 5), (7, 7)] {
+            let result = model.run(tvec!(tensor0(*x)))?;
+            assert_eq!(result[0].to_scalar::<i32>()?, expected);
+        }
+        Ok(())
+    }
+
+    // if c { x + 1 } else { x - 1 }
+    #[test]
+    fn if_then_else() -> TractResult<()> {
+        let then_branch = function(
+            "then",
+            arg("plus", DataType::DtInt32),
+            vec![konst("one", 1)?, node().name("add").op("AddV2").input("i").input("one:output:0")],
+            "add:z:0",
+        );
+        let else_branch = function(
+            "else",
+            arg("minus", DataType::DtInt32),
+            vec![konst("one", 1)?, node().name("sub").op("Sub").input("i").input("one:output:0")],
+            "sub:z:0",
+        );
+        for op in &["If", "StatelessIf"] {
+            let mut graph = graph()
+                .node(node().name("c").op("Placeholder").attr("dtype", DataType::DtBool))
+                .node(node().name("x").op("Placeholder").attr("dtype", DataType::DtInt32))
+                .node(
+                    node()
+                        .name("if")
+                        .op(op)
+                        .input("c")
+                        .input("x")
+                        .attr("then_branch", func_attr("then"))
+                        .attr("else_branch", func_attr("else")),
+                );
+            graph.library = Some(FunctionDefLibrary {
+                function: vec![then_branch.clone(), else_branch.clone()],
+                gradient: vec![],
+            });
+            let mut model = crate::tensorflow().model_for_proto_model(&graph)?;
+            model.set_input_fact(
+                1,
+                InferenceFact::dt_shape(i32::datum_type(), TVec::<usize>::new()),
+            )?;
+            let model = model.into_optimized()?.into_runnable()?;
+            for (c, expected) in &[(true, 4), (false, 2)] {
+                let result = model.run(tvec!(tensor0(*c), tensor0(3i32)))?;
+                assert_eq!(result[0].to_scalar::<i32>()?, expected);
+            }
+        }
+        Ok(())
+    }
+}
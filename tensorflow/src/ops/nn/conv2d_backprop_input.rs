@@ -0,0 +1,88 @@
+use tract_hir::internal::*;
+use tract_hir::ops::cnn::{Deconv, KernelFormat, PaddingSpec};
+use tract_hir::ops::nn::DataFormat;
+
+use crate::model::ParsingContext;
+use crate::tfpb::tensorflow::NodeDef;
+
+pub fn conv2d_backprop_input(
+    _ctx: &ParsingContext,
+    pb: &NodeDef,
+) -> TractResult<Box<dyn InferenceOp>> {
+    let strides = super::strides(pb)?;
+    let data_format = super::data_format(pb)?;
+    let padding = super::padding(pb)?;
+    let strides = if data_format == DataFormat::NHWC { &strides[1..3] } else { &strides[2..4] };
+    Ok(expand(Conv2DBackpropInput { data_format, padding, strides: strides.into() }))
+}
+
+/// Gradient of Conv2D with respect to its input, which is a deconvolution.
+///
+/// Inputs are the shape of the original convolution input (which becomes
+/// our output), the filter (HWIO), and the gradient of the convolution output.
+#[derive(Debug, Clone, Hash)]
+pub struct Conv2DBackpropInput {
+    data_format: DataFormat,
+    padding: PaddingSpec,
+    strides: TVec<usize>,
+}
+
+impl_dyn_hash!(Conv2DBackpropInput);
+
+impl Expansion for Conv2DBackpropInput {
+    fn name(&self) -> Cow<str> {
+        "Conv2DBackpropInput".into()
+    }
+
+    op_tf!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 3)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[0].rank, 1)?;
+        s.equals(&inputs[0].shape[0], 4.to_dim())?;
+        s.equals(&inputs[1].rank, 4)?;
+        s.equals(&inputs[2].rank, 4)?;
+        s.equals(&outputs[0].rank, 4)?;
+        s.equals(&inputs[1].datum_type, &inputs[2].datum_type)?;
+        s.equals(&outputs[0].datum_type, &inputs[2].datum_type)?;
+        s.given(&inputs[0].value, move |s, sizes| {
+            let shape: TVec<TDim> =
+                sizes.cast_to::<i64>()?.as_slice::<i64>()?.iter().map(|&x| x.to_dim()).collect();
+            s.equals(&outputs[0].shape, shape.bex())
+        })
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let sizes = model
+            .outlet_fact(inputs[0])?
+            .konst
+            .clone()
+            .context("Conv2DBackpropInput input sizes must be const")?;
+        let sizes = sizes.cast_to::<i64>()?;
+        let sizes = sizes.as_slice::<i64>()?.iter().map(|&x| x as usize).collect::<TVec<_>>();
+        let output_shape = self.data_format.shape(sizes)?.hw_dims().into();
+        let deconv = Deconv {
+            data_format: self.data_format,
+            kernel_format: KernelFormat::HWIO,
+            padding: self.padding.clone(),
+            strides: Some(self.strides.clone()),
+            dilations: None,
+            adjustments: None,
+            output_shape: Some(output_shape),
+            group: 1,
+            bias_input: None,
+        };
+        deconv.wire(prefix, model, &[inputs[2], inputs[1]])
+    }
+}
@@ -6,14 +6,17 @@ use crate::model::TfOpRegister;
 use crate::tfpb::tensorflow::NodeDef;
 
 pub mod conv2d;
+pub mod conv2d_backprop_input;
 pub mod dw_conv2d;
 pub mod fused_batch_norm;
 pub mod pools;
 pub mod s2b;
+pub mod top_k;
 
 pub fn register_all_ops(reg: &mut TfOpRegister) {
     reg.insert("AvgPool", pools::avgpool);
     reg.insert("Conv2D", conv2d::conv2d);
+    reg.insert("Conv2DBackpropInput", conv2d_backprop_input::conv2d_backprop_input);
     reg.insert("DepthwiseConv2dNative", dw_conv2d::depthwise_conv2d);
     reg.insert("FusedBatchNorm", fused_batch_norm::fused_batch_norm);
     reg.insert("MaxPool", pools::maxpool);
@@ -25,6 +28,7 @@ pub fn register_all_ops(reg: &mut TfOpRegister) {
     reg.insert("Softmax", |_, _| Ok(expand(LayerSoftmax::new(1))));
     reg.insert("SpaceToBatchND", s2b::space_to_batch_nd);
     reg.insert("BatchToSpaceND", s2b::batch_to_space_nd);
+    reg.insert("TopKV2", top_k::top_k_v2);
 }
 
 pub fn strides(pb: &NodeDef) -> TractResult<Vec<usize>> {
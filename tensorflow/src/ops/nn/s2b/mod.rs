@@ -55,14 +55,14 @@ fn space_to_batch<T: Copy + Datum + Zero>(
     }
     reshaped.extend(&data.shape()[block_shape.len() + 1..]);
     final_shape.extend(&data.shape()[block_shape.len() + 1..]);
-    let data = data.into_shape(reshaped)?;
+    let data = data.as_standard_layout().into_owned().into_shape(reshaped)?;
 
     let mut permuted_axes: Vec<_> = (0..block_shape.len()).map(|x| 2 * x + 2).collect();
     permuted_axes.push(0);
     permuted_axes.extend((0..block_shape.len()).map(|x| 2 * x + 1));
     permuted_axes.extend((block_shape.len() * 2 + 1)..data.ndim());
     let data = data.permuted_axes(permuted_axes);
-    let data: Vec<T> = data.into_iter().map(|x| *x).collect();
+    let data: Vec<T> = data.iter().copied().collect();
     let data = tract_ndarray::ArrayD::from_shape_vec(final_shape, data)?;
 
     Ok(data.into_arc_tensor())
@@ -96,7 +96,7 @@ fn batch_to_space<T: Copy + Datum + Zero>(
     permuted_axes.extend((1 + block_shape.len() * 2)..data.ndim());
     padded_shape.extend(&input_shape[1 + block_shape.len()..]);
     let data = data.permuted_axes(permuted_axes);
-    let data: Vec<T> = data.into_iter().map(|x| *x).collect();
+    let data: Vec<T> = data.iter().copied().collect();
     let data = tract_ndarray::ArrayD::from_shape_vec(padded_shape, data)?;
     let mut data = data;
     for (i, crop) in crops.outer_iter().enumerate() {
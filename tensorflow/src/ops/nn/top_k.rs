@@ -0,0 +1,79 @@
+use tract_hir::internal::*;
+
+use crate::model::ParsingContext;
+use crate::tfpb::tensorflow::NodeDef;
+
+pub fn top_k_v2(_ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    let sorted = pb.get_attr_opt_bool("sorted")?.unwrap_or(true);
+    let index_type = pb.get_attr_opt_datum_type("index_type")?.unwrap_or(DatumType::I32);
+    Ok(expand(TopKV2 { sorted, index_type }))
+}
+
+/// TopK on the last axis, with indices as `index_type`.
+#[derive(Debug, Clone, Hash)]
+pub struct TopKV2 {
+    sorted: bool,
+    index_type: DatumType,
+}
+
+impl_dyn_hash!(TopKV2);
+
+impl Expansion for TopKV2 {
+    fn name(&self) -> Cow<str> {
+        "TopKV2".into()
+    }
+
+    op_tf!();
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(2)
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 2)?;
+        check_output_arity(&outputs, 2)?;
+        s.equals(&outputs[0].datum_type, &inputs[0].datum_type)?;
+        s.equals(&outputs[1].datum_type, self.index_type)?;
+        s.equals(&inputs[1].rank, 0)?;
+        s.equals(&outputs[0].rank, &inputs[0].rank)?;
+        s.equals(&outputs[1].rank, &inputs[0].rank)?;
+        s.given(&inputs[0].rank, move |s, rank| {
+            let rank = rank as usize;
+            for ix in 0..rank - 1 {
+                s.equals(&outputs[0].shape[ix], &inputs[0].shape[ix])?;
+                s.equals(&outputs[1].shape[ix], &inputs[0].shape[ix])?;
+            }
+            s.given(&inputs[1].value, move |s, k| {
+                let k = k.cast_to::<TDim>()?.to_scalar::<TDim>()?.clone();
+                s.equals(&outputs[0].shape[rank - 1], k.clone())?;
+                s.equals(&outputs[1].shape[rank - 1], k)
+            })
+        })
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let axis = model.outlet_fact(inputs[0])?.rank() - 1;
+        let fallback_k = model.symbol_table.new_with_prefix("k").into();
+        let mut wires = model.wire_node(
+            format!("{}.topk", prefix),
+            tract_hir::tract_core::ops::array::TopK::new(axis, true, self.sorted, fallback_k),
+            inputs,
+        )?;
+        wires[1] = model.wire_node(
+            format!("{}.cast_indices", prefix),
+            tract_hir::ops::cast(self.index_type),
+            &[wires[1]],
+        )?[0];
+        Ok(wires)
+    }
+}
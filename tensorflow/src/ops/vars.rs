@@ -5,14 +5,15 @@ use crate::tfpb::tensorflow::NodeDef;
 
 pub fn register_all_ops(reg: &mut TfOpRegister) {
     reg.insert("Assign", |_, _| Ok(Box::new(Assign::default())));
+    reg.insert("AssignVariableOp", |_, _| Ok(Box::new(Assign::default())));
+    reg.insert("ReadVariableOp", |_, _| Ok(Box::new(tract_hir::ops::identity::Identity)));
+    reg.insert("VarHandleOp", variable_v2);
     reg.insert("VariableV2", variable_v2);
 }
 
 fn variable_v2(_ctx: &ParsingContext, node: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
-    let shared_name = node.get_attr_str("shared_name")?;
-    let shared_name = if shared_name != "" { Some(shared_name) } else { None };
-    let container = node.get_attr_str("container")?;
-    let container = if container != "" { Some(container) } else { None };
+    let shared_name = node.get_attr_opt_str("shared_name")?.filter(|s| !s.is_empty());
+    let container = node.get_attr_opt_str("container")?.filter(|s| !s.is_empty());
     let name = node.name.to_string();
     let id = format!("{:?}#{:?}#{}", container, shared_name, name);
     let shape = node.get_attr_shape("shape")?;
@@ -47,13 +48,21 @@ impl OpState for VariableV2State {
             .with_context(|| format!("Could not find state for variable {}", op.id))?;
         Ok(tvec!(tensor.clone().into()))
     }
+
+    fn snapshot(&self) -> TractResult<TVec<Tensor>> {
+        Ok(tvec!())
+    }
+
+    fn restore(&mut self, _tensors: TVec<Tensor>) -> TractResult<()> {
+        Ok(())
+    }
 }
 
 #[derive(Clone, Debug, new, Hash)]
 pub struct VariableV2 {
     container: Option<String>,
-    shared_name: Option<String>,
-    name: String,
+    pub shared_name: Option<String>,
+    pub name: String,
     pub id: String,
     shape: TVec<usize>,
     dt: DatumType,
@@ -166,6 +166,21 @@ impl NodeDef {
         Ok(None)
     }
 
+    pub fn get_attr_func(&self, name: &str) -> TractResult<&str> {
+        Ok(self.get_attr_opt_func(name)?.with_context(|| {
+            format!("Node {} ({}) expected function attribute '{}'", self.name, self.op, name)
+        })?)
+    }
+
+    pub fn get_attr_opt_func(&self, name: &str) -> TractResult<Option<&str>> {
+        if let Some(a) = self.attr.get(name) {
+            if let Value::Func(ref func) = a.value.as_ref().unwrap() {
+                return Ok(Some(&func.name));
+            }
+        };
+        Ok(None)
+    }
+
     pub fn get_attr_int<T: tract_num_traits::FromPrimitive>(&self, name: &str) -> TractResult<T> {
         Ok(self.get_attr_opt_int(name)?.with_context(|| {
             format!("Node {} ({}) expected int attribute '{}'", self.name, self.op, name)
@@ -236,6 +251,12 @@ impl<'a> From<&'a str> for AttrValue {
     }
 }
 
+impl From<bool> for AttrValue {
+    fn from(t: bool) -> AttrValue {
+        AttrValue { value: Some(Value::B(t)) }
+    }
+}
+
 impl From<i32> for AttrValue {
     fn from(t: i32) -> AttrValue {
         AttrValue::from(t as i64)
@@ -0,0 +1,90 @@
+extern crate tract_tensorflow;
+
+use tract_tensorflow::checkpoint::Checkpoint;
+use tract_tensorflow::model::TfModelAndExtensions;
+use tract_tensorflow::ops::vars::VariableV2;
+use tract_tensorflow::prelude::*;
+use tract_tensorflow::tfpb;
+use tract_tensorflow::tfpb::tensorflow::DataType;
+use tract_tensorflow::Tensorflow;
+
+const DIR: &str = "tests/models/affine_saved_model";
+
+fn load(signature: Option<&str>) -> TractResult<InferenceModel> {
+    let TfModelAndExtensions(model, _) =
+        tract_tensorflow::tensorflow().model_for_saved_model_dir(DIR, signature)?;
+    Ok(model)
+}
+
+fn run(model: InferenceModel) -> TractResult<Tensor> {
+    let model = model
+        .with_input_fact(0, InferenceFact::dt_shape(f32::datum_type(), &[1, 2]))?
+        .into_optimized()?
+        .into_runnable()?;
+    Ok(model.run(tvec!(tensor2(&[[1f32, 1.]])))?.remove(0).into_tensor())
+}
+
+#[test]
+fn load_restore_and_run() -> TractResult<()> {
+    let model = load(None)?;
+    assert_eq!(model.outlet_label(model.input_outlets()?[0]), Some("x"));
+    assert_eq!(model.outlet_label(model.output_outlets()?[0]), Some("y"));
+    // never assigned, the variables became constants
+    assert!(model.nodes().iter().all(|n| !n.op_is::<VariableV2>()));
+    // x·w + b
+    assert_eq!(run(model)?, tensor2(&[[14f32, 26.]]));
+    Ok(())
+}
+
+#[test]
+fn select_signature() -> TractResult<()> {
+    let model = load(Some("project"))?;
+    assert_eq!(model.outlet_label(model.output_outlets()?[0]), Some("projected"));
+    assert_eq!(run(model)?, tensor2(&[[4f32, 6.]]));
+    let error = load(Some("train")).unwrap_err();
+    assert!(format!("{:?}", error).contains("Signature train not found"));
+    Ok(())
+}
+
+#[test]
+fn prune_to_signature_outputs() -> TractResult<()> {
+    let model = load(None)?;
+    assert!(model.node_by_name("print").is_err());
+    let model = load(Some("project"))?;
+    assert!(model.node_by_name("b").is_err());
+    assert!(model.node_by_name("w").is_ok());
+    Ok(())
+}
+
+#[test]
+fn checkpoint_keys_and_tensors() -> TractResult<()> {
+    let checkpoint = Checkpoint::open(format!("{}/variables/variables", DIR))?;
+    let keys = checkpoint.variable_keys()?;
+    assert_eq!(keys["w"], "w/.ATTRIBUTES/VARIABLE_VALUE");
+    let w = checkpoint.tensor("w/.ATTRIBUTES/VARIABLE_VALUE")?.unwrap();
+    assert_eq!(w, tensor2(&[[1f32, 2.], [3., 4.]]));
+    assert!(checkpoint.tensor("w")?.is_none());
+    Ok(())
+}
+
+#[test]
+fn restore_assigned_variable() -> TractResult<()> {
+    // an assigned variable keeps its state, initialized from the checkpoint
+    let graph = tfpb::graph()
+        .node(
+            tfpb::node()
+                .name("w")
+                .op("VarHandleOp")
+                .attr("dtype", DataType::DtFloat)
+                .attr("shape", tfpb::tensorflow::TensorShapeProto::default())
+                .attr("shared_name", "w"),
+        )
+        .node(tfpb::node().name("value").op("Placeholder").attr("dtype", DataType::DtFloat))
+        .node(tfpb::node().name("assign").op("AssignVariableOp").input("w").input("value"));
+    let TfModelAndExtensions(mut model, _) = tract_tensorflow::tensorflow().parse_graph(&graph)?;
+    let checkpoint = Checkpoint::open(format!("{}/variables/variables", DIR))?;
+    Tensorflow::restore_variables(&mut model, &checkpoint)?;
+    let var = model.node_by_name("w")?.op_as::<VariableV2>().unwrap();
+    assert_eq!(var.initializer.as_deref(), Some(&tensor2(&[[1f32, 2.], [3., 4.]])));
+    Ok(())
+}